@@ -0,0 +1,430 @@
+//! Per-note expression: pitch bend, pressure and timbre (CC74).
+//!
+//! Rules:
+//! - Pitch bend is the signed 14-bit value scaled by the channel's bend
+//!   range (RPN 0; 2 semitones until set).
+//! - MPE zones are configured by the MPE Configuration Message (RPN 6 on
+//!   channel 1 or 16). Member channels default to a 48-semitone range,
+//!   managers to 2; a manager's bend adds to every member note's bend.
+//!   RPN 0 on a member channel sets the range of every member of its zone.
+//! - Mono mode (MIDI mode 4, e.g. guitar-to-MIDI with one string per
+//!   channel) is set by `ExpressionConfig::mono_channels` or by CC126
+//!   (Mono Mode On, from the basic channel up) and cleared by CC127 (Poly
+//!   Mode On). A mono channel sounds one note at a time.
+//! - Channel pressure applies to all notes on its channel, poly pressure to
+//!   one key; CC74 sets timbre for the channel (64 until set).
+//! - MIDI 2.0 per-note pitch bend and per-note timbre (`set_note_bend`,
+//!   `set_note_timbre`) apply to one note on top of its channel values.
+//! - Every sounding note records an `ExpressionPoint` whenever one of its
+//!   values changes.
+//!
+//! Pitch resolution turns key + bend into the semitone reported as
+//! `NoteEvent::note`; see `PitchResolution`.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use mt_core::events::NoteId;
+use mt_core::midi::{MidiEventKind, TimedMidiEvent};
+use mt_core::pitch::MidiNote;
+use mt_core::time::SampleTime;
+
+const CC_DATA_ENTRY_MSB: u8 = 6;
+const CC_DATA_ENTRY_LSB: u8 = 38;
+const CC_TIMBRE: u8 = 74;
+const CC_NRPN_LSB: u8 = 98;
+const CC_NRPN_MSB: u8 = 99;
+const CC_RPN_LSB: u8 = 100;
+const CC_RPN_MSB: u8 = 101;
+const CC_MONO_ON: u8 = 126;
+const CC_POLY_ON: u8 = 127;
+
+const RPN_BEND_RANGE: (u8, u8) = (0, 0);
+const RPN_MPE_CONFIG: (u8, u8) = (0, 6);
+const RPN_NULL: (u8, u8) = (127, 127);
+
+/// Which semitone a bent note is reported as.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PitchResolution {
+    /// The key that was pressed; bend is ignored.
+    Key,
+    /// Nearest semitone to key + bend at the onset.
+    Onset,
+    /// Nearest semitone held for the longest time while the note sounded.
+    Dominant,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug)]
+pub struct ExpressionConfig {
+    pub pitch: PitchResolution,
+    /// Bend range in semitones for non-MPE channels until RPN 0 sets one.
+    pub default_bend_range: u8,
+    /// Keep `NoteExpression` curves for `MidiNormalizer::take_expressions`.
+    pub record_curves: bool,
+    /// Channels in mono mode from the start, one bit each (bit 0 = channel
+    /// 1). Guitar-to-MIDI converters send each string on its own channel.
+    pub mono_channels: u16,
+}
+
+impl Default for ExpressionConfig {
+    fn default() -> Self {
+        Self {
+            pitch: PitchResolution::Dominant,
+            default_bend_range: 2,
+            record_curves: false,
+            mono_channels: 0,
+        }
+    }
+}
+
+/// Expression values of one note from `position` on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExpressionPoint {
+    pub position: SampleTime,
+    /// Bend from the pressed key, in cents.
+    pub bend_cents: i32,
+    pub pressure: u8,
+    pub timbre: u8,
+}
+
+/// Expression curve of one completed note.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoteExpression {
+    pub id: NoteId,
+    /// Key that was pressed (before pitch resolution).
+    pub key: MidiNote,
+    /// Points in time order; the first is at the onset.
+    pub points: Vec<ExpressionPoint>,
+}
+
+impl NoteExpression {
+    /// Sounding pitch at the onset in cents (MIDI 60 = 6000).
+    #[must_use]
+    pub fn onset_pitch_cents(&self) -> i32 {
+        i32::from(self.key.value()) * 100 + self.points.first().map_or(0, |p| p.bend_cents)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ChannelExpression {
+    bend: i16,
+    bend_range_cents: i32,
+    pressure: u8,
+    timbre: u8,
+    rpn: (u8, u8),
+}
+
+/// Expression state of one sounding note.
+#[derive(Clone, Debug)]
+struct NoteCurve {
+    /// Per-note bend in cents, added to the channel bend.
+    bend: i32,
+    /// Per-note timbre; overrides the channel's CC74 once set.
+    timbre: Option<u8>,
+    points: Vec<ExpressionPoint>,
+}
+
+/// Channel, zone and per-note expression state.
+#[derive(Debug, Clone)]
+pub struct ExpressionTracker {
+    cfg: ExpressionConfig,
+    channels: [ChannelExpression; 16],
+    /// Member channels of the lower (manager 0) and upper (manager 15) zones.
+    lower_members: u8,
+    upper_members: u8,
+    /// Channels in mono mode, one bit each.
+    mono: u16,
+    /// Curves of sounding notes by `(channel, key)`.
+    curves: BTreeMap<(u8, u8), NoteCurve>,
+}
+
+impl ExpressionTracker {
+    pub fn new(cfg: ExpressionConfig) -> Self {
+        let channel = ChannelExpression {
+            bend: 0,
+            bend_range_cents: i32::from(cfg.default_bend_range) * 100,
+            pressure: 0,
+            timbre: 64,
+            rpn: RPN_NULL,
+        };
+        Self {
+            cfg,
+            channels: [channel; 16],
+            lower_members: 0,
+            upper_members: 0,
+            mono: cfg.mono_channels,
+            curves: BTreeMap::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn config(&self) -> ExpressionConfig {
+        self.cfg
+    }
+
+    /// Whether `channel` is in mono mode (one sounding note at a time).
+    #[must_use]
+    pub fn is_mono(&self, channel: u8) -> bool {
+        self.mono & (1 << (channel & 0x0F)) != 0
+    }
+
+    /// Start the curve of a note pressed at `ev`.
+    pub fn note_on(&mut self, ev: &TimedMidiEvent) {
+        let ch = ev.event.channel.value();
+        let key = ev.event.data1 & 0x7F;
+        let point = self.point(ch, ev.position, None);
+        let curve = NoteCurve { bend: 0, timbre: None, points: alloc::vec![point] };
+        self.curves.insert((ch, key), curve);
+    }
+
+    /// Set the per-note bend of a sounding note, in cents from its key.
+    pub fn set_note_bend(&mut self, channel: u8, key: u8, position: SampleTime, cents: i32) {
+        self.update_note(channel, key, position, |curve| curve.bend = cents);
+    }
+
+    /// Set the per-note timbre of a sounding note.
+    pub fn set_note_timbre(&mut self, channel: u8, key: u8, position: SampleTime, timbre: u8) {
+        self.update_note(channel, key, position, |curve| curve.timbre = Some(timbre & 0x7F));
+    }
+
+    /// Apply a non-note event; sounding notes it affects get a new point.
+    pub fn apply(&mut self, ev: &TimedMidiEvent) {
+        let e = ev.event;
+        let ch = e.channel.value();
+        let state = &mut self.channels[usize::from(ch)];
+        match e.kind {
+            MidiEventKind::PitchBend => state.bend = e.pitch_bend_value().unwrap_or(0),
+            MidiEventKind::ChannelPressure => state.pressure = e.data1 & 0x7F,
+            MidiEventKind::PolyPressure => {
+                let key = e.data1 & 0x7F;
+                if let Some(curve) = self.curves.get_mut(&(ch, key))
+                    && let Some(&last) = curve.points.last()
+                {
+                    let point =
+                        ExpressionPoint { position: ev.position, pressure: e.data2 & 0x7F, ..last };
+                    push_point(&mut curve.points, point);
+                }
+                return;
+            }
+            MidiEventKind::ControlChange => {
+                if !self.control_change(ch, e.data1, e.data2 & 0x7F) {
+                    return;
+                }
+            }
+            _ => return,
+        }
+        self.refresh(ch, ev.position, e.kind == MidiEventKind::ChannelPressure);
+    }
+
+    /// End the curve of `(channel, key)` at `offset`; returns the reported
+    /// semitone and the curve.
+    pub fn note_off(
+        &mut self,
+        channel: u8,
+        key: u8,
+        offset: SampleTime,
+    ) -> Option<(MidiNote, Vec<ExpressionPoint>)> {
+        let key = key & 0x7F;
+        let curve = self.curves.remove(&(channel, key))?.points;
+        let cents = |p: &ExpressionPoint| i32::from(key) * 100 + p.bend_cents;
+        let resolved = match self.cfg.pitch {
+            PitchResolution::Key => i32::from(key),
+            PitchResolution::Onset => nearest_semitone(cents(&curve[0])),
+            PitchResolution::Dominant => dominant_semitone(&curve, offset, cents),
+        };
+        let note = MidiNote::new(resolved.clamp(0, 127) as u8).ok()?;
+        Some((note, curve))
+    }
+
+    /// Handle CC; returns `true` if expression values changed.
+    fn control_change(&mut self, ch: u8, cc: u8, value: u8) -> bool {
+        let state = &mut self.channels[usize::from(ch)];
+        match cc {
+            CC_TIMBRE => {
+                state.timbre = value;
+                return true;
+            }
+            CC_RPN_MSB => state.rpn.0 = value,
+            CC_RPN_LSB => state.rpn.1 = value,
+            CC_NRPN_MSB | CC_NRPN_LSB => state.rpn = RPN_NULL,
+            CC_DATA_ENTRY_MSB if state.rpn == RPN_BEND_RANGE => {
+                self.set_bend_range(ch, i32::from(value) * 100);
+                return true;
+            }
+            CC_DATA_ENTRY_LSB if state.rpn == RPN_BEND_RANGE => {
+                let cents = state.bend_range_cents / 100 * 100 + i32::from(value.min(99));
+                self.set_bend_range(ch, cents);
+                return true;
+            }
+            CC_DATA_ENTRY_MSB if state.rpn == RPN_MPE_CONFIG => {
+                self.configure_zone(ch, value.min(15));
+                return true;
+            }
+            CC_MONO_ON => {
+                // M = 0 means every channel from the basic channel up.
+                let count = if value == 0 { 16 - ch } else { value.min(16 - ch) };
+                let bits = u16::try_from((1u32 << count) - 1).unwrap_or(u16::MAX);
+                self.mono |= bits << ch;
+            }
+            CC_POLY_ON => self.mono = 0,
+            _ => {}
+        }
+        false
+    }
+
+    /// MPE Configuration Message on a manager channel.
+    fn configure_zone(&mut self, manager: u8, members: u8) {
+        let lower = match manager {
+            0 => true,
+            15 => false,
+            _ => return,
+        };
+        if lower {
+            self.lower_members = members;
+            if self.upper_members > 0 && members + self.upper_members > 14 {
+                self.upper_members = 14u8.saturating_sub(members);
+            }
+        } else {
+            self.upper_members = members;
+            if self.lower_members > 0 && members + self.lower_members > 14 {
+                self.lower_members = 14u8.saturating_sub(members);
+            }
+        }
+        // MPE defaults: 48 semitones on members, 2 on the manager.
+        self.channels[usize::from(manager)].bend_range_cents = 200;
+        for ch in 0..16u8 {
+            if self.manager_of(ch) == Some(manager) {
+                self.channels[usize::from(ch)].bend_range_cents = 4800;
+            }
+        }
+    }
+
+    /// Set the bend range of `ch`; on a member channel, of its whole zone.
+    fn set_bend_range(&mut self, ch: u8, cents: i32) {
+        let zone = self.manager_of(ch);
+        for c in 0..16u8 {
+            if c == ch || (zone.is_some() && self.manager_of(c) == zone) {
+                self.channels[usize::from(c)].bend_range_cents = cents;
+            }
+        }
+    }
+
+    /// Zone manager for a member channel.
+    fn manager_of(&self, ch: u8) -> Option<u8> {
+        if (1..=self.lower_members).contains(&ch) {
+            Some(0)
+        } else if self.upper_members > 0 && (15 - self.upper_members..15).contains(&ch) {
+            Some(15)
+        } else {
+            None
+        }
+    }
+
+    fn bend_cents(&self, ch: u8) -> i32 {
+        let cents = |c: &ChannelExpression| i32::from(c.bend) * c.bend_range_cents / 8192;
+        let own = cents(&self.channels[usize::from(ch)]);
+        let zone = self.manager_of(ch).map_or(0, |m| cents(&self.channels[usize::from(m)]));
+        own + zone
+    }
+
+    fn point(&self, ch: u8, position: SampleTime, key: Option<u8>) -> ExpressionPoint {
+        let state = &self.channels[usize::from(ch)];
+        let curve = key.and_then(|k| self.curves.get(&(ch, k)));
+        let pressure = curve.and_then(|c| c.points.last()).map_or(state.pressure, |p| p.pressure);
+        ExpressionPoint {
+            position,
+            bend_cents: self.bend_cents(ch) + curve.map_or(0, |c| c.bend),
+            pressure,
+            timbre: curve.and_then(|c| c.timbre).unwrap_or(state.timbre),
+        }
+    }
+
+    /// Change one sounding note and record a point for it.
+    fn update_note(
+        &mut self,
+        ch: u8,
+        key: u8,
+        position: SampleTime,
+        change: impl FnOnce(&mut NoteCurve),
+    ) {
+        let key = key & 0x7F;
+        let Some(curve) = self.curves.get_mut(&(ch, key)) else {
+            return;
+        };
+        change(curve);
+        let point = self.point(ch, position, Some(key));
+        if let Some(curve) = self.curves.get_mut(&(ch, key)) {
+            push_point(&mut curve.points, point);
+        }
+    }
+
+    /// Re-sample notes on `ch`, on all its members if it is a manager, or
+    /// on its whole zone if it is a member (bend ranges are zone-wide).
+    fn refresh(&mut self, ch: u8, position: SampleTime, channel_pressure: bool) {
+        let zone = self.manager_of(ch).or(Some(ch));
+        let affected: Vec<(u8, u8)> = self
+            .curves
+            .keys()
+            .copied()
+            .filter(|&(c, _)| c == ch || self.manager_of(c) == zone)
+            .collect();
+        let pressure = self.channels[usize::from(ch)].pressure;
+        for (c, k) in affected {
+            let mut point = self.point(c, position, Some(k));
+            if channel_pressure && c == ch {
+                // Channel pressure overrides earlier poly pressure.
+                point.pressure = pressure;
+            }
+            if let Some(curve) = self.curves.get_mut(&(c, k)) {
+                push_point(&mut curve.points, point);
+            }
+        }
+    }
+}
+
+impl Default for ExpressionTracker {
+    fn default() -> Self {
+        Self::new(ExpressionConfig::default())
+    }
+}
+
+fn push_point(curve: &mut Vec<ExpressionPoint>, point: ExpressionPoint) {
+    match curve.last_mut() {
+        Some(last)
+            if (last.bend_cents, last.pressure, last.timbre)
+                == (point.bend_cents, point.pressure, point.timbre) => {}
+        Some(last) if last.position == point.position => *last = point,
+        _ => curve.push(point),
+    }
+}
+
+fn nearest_semitone(cents: i32) -> i32 {
+    (cents + 50).div_euclid(100)
+}
+
+/// Semitone with the most sounding time; ties go to the earliest.
+fn dominant_semitone(
+    curve: &[ExpressionPoint],
+    offset: SampleTime,
+    cents: impl Fn(&ExpressionPoint) -> i32,
+) -> i32 {
+    let mut held: Vec<(i32, i64)> = Vec::new();
+    for (i, p) in curve.iter().enumerate() {
+        let end = curve.get(i + 1).map_or(offset, |n| n.position);
+        let dur = (end.value() - p.position.value()).max(0);
+        let semi = nearest_semitone(cents(p));
+        match held.iter_mut().find(|(s, _)| *s == semi) {
+            Some((_, d)) => *d += dur,
+            None => held.push((semi, dur)),
+        }
+    }
+    let mut best = held[0];
+    for &(s, d) in &held[1..] {
+        if d > best.1 {
+            best = (s, d);
+        }
+    }
+    best.0
+}
@@ -2,19 +2,23 @@
 //!
 //! Heap-based primitives built on `mt-core`:
 //! - Tempo map over `TempoEvent` / `MeterEvent` / `SwingEvent`.
-//! - MIDI normalizer → `NoteEvent`s.
-//! - NoteStore for efficient queries.
-//! - EventRing: generic ring buffer for batch-style events.
-//! - SpscQueue: single-producer, single-consumer queue for RT↔non-RT (opt-in).
-//! - FeatureBuffer: buffers for numeric features.
+//! - MIDI normalizer → `NoteEvent`s, with pedal and per-note expression
+//!   (pitch bend, MPE, pressure, timbre) handling.
+//! - `NoteStore`: interval index over `NoteEvent` with overlap/stabbing
+//!   queries, edits by `NoteId` and per-track views.
+//! - `EventRing`: generic ring buffer for batch-style events.
+//! - `SpscQueue`: wait-free single-producer, single-consumer queue for
+//!   RT↔non-RT, splittable into `Send` halves.
+//! - `FeatureBuffer`: buffers for numeric features.
 //!
 //! Design:
 //! - Deterministic.
-//! - No I/O, no randomness.
+//! - No I/O, no randomness; file formats live in `mt-formats`.
 //! - All concurrency semantics explicit, no hidden threads.
+//! - `unsafe` is confined to `spsc_queue` (see its audit notes).
 
 #![cfg_attr(not(feature = "std"), no_std)]
-#![forbid(unsafe_code)]
+#![deny(unsafe_code)]
 #![deny(clippy::all, clippy::pedantic)]
 #![allow(
     clippy::module_name_repetitions,
@@ -28,15 +32,18 @@ extern crate alloc;
 
 pub mod alloc_utils;
 pub mod event_ring;
+pub mod expression;
 pub mod feature_buffer;
 pub mod midi_normalizer;
 pub mod note_store;
+#[allow(unsafe_code)]
 pub mod spsc_queue;
 pub mod tempo_map;
 
 pub use event_ring::EventRing;
+pub use expression::{ExpressionConfig, NoteExpression, PitchResolution};
 pub use feature_buffer::FeatureBuffer;
 pub use midi_normalizer::MidiNormalizer;
 pub use note_store::NoteStore;
-pub use spsc_queue::SpscQueue;
+pub use spsc_queue::{Consumer, Producer, SpscQueue};
 pub use tempo_map::TempoMap;
@@ -4,14 +4,32 @@
 //!
 //! Policy:
 //! - If NoteOff missing, we can close on next NoteOn of same note/channel.
+//! - Sustain (CC64), sostenuto (CC66) and half pedal extend offsets per
+//!   `PedalConfig` (see `mt_core::pedal`); a re-struck key ends the note
+//!   the pedal was holding. Notes struck under the soft pedal (CC67) get
+//!   `PedalConfig::soft_velocity_x1000` applied to their velocity.
+//! - Pitch bend, pressure and CC74 are tracked per note (MPE aware, see
+//!   `expression`); the reported note is the resolved sounding semitone.
+//!   On a mono-mode channel a new note ends the previous one.
+//! - MIDI 2.0 UMP input (`process_ump`) is merged across groups. Per-note
+//!   pitch bend (48 semitones), pitch attributes and per-note pitch/timbre
+//!   controllers go to the note's expression; everything else is
+//!   translated to MIDI 1.0 first.
 //! - All behavior is deterministic.
 
 use alloc::vec::Vec;
 
+use crate::expression::{ExpressionConfig, ExpressionTracker, NoteExpression};
+
 use mt_core::events::{NoteEvent, NoteId, TrackId};
-use mt_core::midi::{MidiEvent, MidiEventKind};
+use mt_core::midi::{MidiEventKind, TimedMidiEvent};
+use mt_core::pedal::{self, PedalConfig, PedalState};
 use mt_core::pitch::MidiNote;
 use mt_core::time::SampleTime;
+use mt_core::ump::{self, Midi2Voice, Ump};
+
+/// Range of MIDI 2.0 per-note pitch bend, in cents either side of center.
+const PER_NOTE_BEND_RANGE_CENTS: i32 = 4800;
 
 /// Internal state of an active note.
 #[derive(Clone, Copy, Debug)]
@@ -26,10 +44,24 @@ pub struct MidiNormalizer {
     active: [[Option<ActiveNote>; 128]; 16],
     next_id: u32,
     track_for_channel: [TrackId; 16],
+    pedal: PedalState,
+    expression: ExpressionTracker,
+    /// Curves of completed notes, if `record_curves` is set.
+    expressions: Vec<NoteExpression>,
 }
 
 impl MidiNormalizer {
     pub fn new() -> Self {
+        Self::with_pedal(PedalConfig::default())
+    }
+
+    /// Normalizer with explicit pedal handling.
+    pub fn with_pedal(cfg: PedalConfig) -> Self {
+        Self::with_config(cfg, ExpressionConfig::default())
+    }
+
+    /// Normalizer with explicit pedal and expression handling.
+    pub fn with_config(pedal: PedalConfig, expression: ExpressionConfig) -> Self {
         // TrackId: simple mapping channel -> TrackId(channel).
         let mut tracks = [TrackId(0); 16];
         let mut i = 0u16;
@@ -38,20 +70,29 @@ impl MidiNormalizer {
             i += 1;
         }
 
-        Self { active: [[None; 128]; 16], next_id: 1, track_for_channel: tracks }
+        Self {
+            active: [[None; 128]; 16],
+            next_id: 1,
+            track_for_channel: tracks,
+            pedal: PedalState::new(pedal),
+            expression: ExpressionTracker::new(expression),
+            expressions: Vec::new(),
+        }
     }
 
-    /// Process a batch of MIDI events at a given time.
+    /// Process a batch of MIDI events, each at its own position.
     ///
     /// Returns zero or more completed NoteEvents.
-    pub fn process(&mut self, time: SampleTime, events: &[MidiEvent]) -> Vec<NoteEvent> {
+    pub fn process(&mut self, events: &[TimedMidiEvent]) -> Vec<NoteEvent> {
         let mut out = Vec::new();
 
-        for ev in events {
+        for timed in events {
+            let (time, ev) = (timed.position, timed.event);
             let ch = ev.channel.value() as usize;
             match ev.kind {
                 MidiEventKind::NoteOn if ev.data2 > 0 => {
                     let note = ev.data1 as usize;
+                    self.pedal.note_on(ev.channel.value(), ev.data1);
                     // If a note was already active, close it deterministically.
                     if let Some(active) = self.active[ch][note] {
                         out.push(self.make_note_event(
@@ -62,21 +103,86 @@ impl MidiNormalizer {
                             active.velocity,
                         ));
                     }
-                    self.active[ch][note] = Some(ActiveNote { onset: time, velocity: ev.data2 });
+                    if self.expression.is_mono(ev.channel.value()) {
+                        // Mono mode: a new note ends the one sounding on its channel.
+                        for key in (0..128u8).filter(|&k| usize::from(k) != note) {
+                            self.close(ch, key, time, &mut out);
+                        }
+                    }
+                    let velocity = self.pedal.velocity(ev.channel.value(), ev.data2);
+                    self.active[ch][note] = Some(ActiveNote { onset: time, velocity });
+                    self.expression.note_on(timed);
                 }
                 MidiEventKind::NoteOn | MidiEventKind::NoteOff => {
-                    let note = ev.data1 as usize;
-                    if let Some(active) = self.active[ch][note] {
-                        out.push(self.make_note_event(
-                            ch,
-                            note as u8,
-                            active.onset,
-                            time,
-                            active.velocity,
-                        ));
-                        self.active[ch][note] = None;
+                    if self.pedal.note_off(ev.channel.value(), ev.data1) {
+                        self.close(ch, ev.data1, time, &mut out);
+                    }
+                }
+                MidiEventKind::ControlChange => {
+                    self.expression.apply(timed);
+                    let released =
+                        self.pedal.control_change(ev.channel.value(), ev.data1, ev.data2);
+                    for key in pedal::keys(released) {
+                        self.close(ch, key, time, &mut out);
+                    }
+                }
+                MidiEventKind::PitchBend
+                | MidiEventKind::ChannelPressure
+                | MidiEventKind::PolyPressure => self.expression.apply(timed),
+                _ => {}
+            }
+        }
+
+        out
+    }
+
+    /// Process a stream of Universal MIDI Packets, all at `position`.
+    ///
+    /// A trailing partial packet is ignored. Returns zero or more completed
+    /// `NoteEvent`s.
+    pub fn process_ump(&mut self, position: SampleTime, words: &[u32]) -> Vec<NoteEvent> {
+        let mut out = Vec::new();
+        let timed = |event| TimedMidiEvent::at(position, event);
+
+        for packet in ump::decode(words) {
+            if let Some(events) = packet.controller_to_midi1() {
+                out.extend(self.process(&events.map(timed)));
+                continue;
+            }
+            if let Some(event) = packet.to_midi1() {
+                out.extend(self.process(&[timed(event)]));
+            }
+            let Ump::Midi2 { channel, message, .. } = packet else {
+                continue;
+            };
+            let ch = channel.value();
+            match message {
+                Midi2Voice::NoteOn { note, .. } => {
+                    if let Some(cents) = message.attribute_pitch_cents() {
+                        let bend = cents - i32::from(note) * 100;
+                        self.expression.set_note_bend(ch, note, position, bend);
                     }
                 }
+                Midi2Voice::PerNotePitchBend { note, value } => {
+                    let bend = ump::bend_cents(value, PER_NOTE_BEND_RANGE_CENTS);
+                    self.expression.set_note_bend(ch, note, position, bend);
+                }
+                Midi2Voice::RegisteredPerNoteController {
+                    note,
+                    index: ump::PER_NOTE_PITCH_7_25,
+                    value,
+                } => {
+                    let bend = ump::pitch_7_25_cents(value) - i32::from(note) * 100;
+                    self.expression.set_note_bend(ch, note, position, bend);
+                }
+                Midi2Voice::RegisteredPerNoteController {
+                    note,
+                    index: ump::PER_NOTE_TIMBRE,
+                    value,
+                } => {
+                    let timbre = ump::scale_to_7(value);
+                    self.expression.set_note_timbre(ch, note, position, timbre);
+                }
                 _ => {}
             }
         }
@@ -84,6 +190,13 @@ impl MidiNormalizer {
         out
     }
 
+    /// Expression curves of notes completed since the last call, in the
+    /// order their notes were emitted. Empty unless
+    /// `ExpressionConfig::record_curves` is set.
+    pub fn take_expressions(&mut self) -> Vec<NoteExpression> {
+        core::mem::take(&mut self.expressions)
+    }
+
     /// Flush any hanging notes at the given cutoff time.
     pub fn flush(&mut self, cutoff: SampleTime) -> Vec<NoteEvent> {
         let mut out = Vec::new();
@@ -104,6 +217,12 @@ impl MidiNormalizer {
         out
     }
 
+    fn close(&mut self, ch: usize, note: u8, time: SampleTime, out: &mut Vec<NoteEvent>) {
+        if let Some(active) = self.active[ch][usize::from(note & 0x7F)].take() {
+            out.push(self.make_note_event(ch, note & 0x7F, active.onset, time, active.velocity));
+        }
+    }
+
     fn make_note_event(
         &mut self,
         ch: usize,
@@ -115,12 +234,21 @@ impl MidiNormalizer {
         let id = NoteId(self.next_id);
         self.next_id = self.next_id.wrapping_add(1);
 
+        let mut resolved = MidiNote::new(note).expect("0..127");
+        if let Some((sounding, points)) = self.expression.note_off(ch as u8, note, offset) {
+            resolved = sounding;
+            if self.expression.config().record_curves {
+                let key = MidiNote::new(note).expect("0..127");
+                self.expressions.push(NoteExpression { id, key, points });
+            }
+        }
+
         NoteEvent {
             id,
             track: self.track_for_channel[ch],
             onset,
             offset,
-            note: MidiNote::new(note).expect("0..127"),
+            note: resolved,
             velocity,
         }
     }
@@ -1,64 +1,386 @@
-//! NoteStore:
-//! - Stores NoteEvent in onset-sorted order.
-//! - Provides simple time range queries.
+//! `NoteStore`:
+//! - Interval index over `NoteEvent`: an arena-backed treap keyed by
+//!   `(onset, track, id)`, each node augmented with the max offset of its
+//!   subtree.
+//! - Overlap and stabbing queries in O(log n + k); subtrees that end before
+//!   the query are pruned.
+//! - Insert, remove and update by `(TrackId, NoteId)` in O(log n) expected.
+//! - `from_notes` builds in O(n log n) (sort) + O(n).
+//!
+//! A note is identified by its track and id together: sources number notes
+//! per track (SMF tracks, MIDI channels, stems), so the same `NoteId` on
+//! two tracks is two notes. Inserting a note whose `(track, id)` is
+//! already stored replaces the old note. Priorities are derived from
+//! `(track, id)`, so the tree shape (and iteration order) is deterministic.
 
+use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
 
-use mt_core::events::NoteEvent;
+use mt_core::events::{NoteEvent, NoteId, TrackId};
 use mt_core::time::SampleTime;
 
+const NIL: usize = usize::MAX;
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    note: NoteEvent,
+    /// Largest offset in this subtree.
+    max_offset: SampleTime,
+    priority: u32,
+    left: usize,
+    right: usize,
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct NoteStore {
-    notes: Vec<NoteEvent>,
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    root: Option<usize>,
+    by_id: BTreeMap<(u16, u32), usize>,
 }
 
 impl NoteStore {
     pub fn new() -> Self {
-        Self { notes: Vec::new() }
+        Self::default()
+    }
+
+    /// Build from notes in any order; for duplicate `(track, id)` pairs the
+    /// last one wins.
+    pub fn from_notes(notes: impl IntoIterator<Item = NoteEvent>) -> Self {
+        let mut latest: BTreeMap<(u16, u32), NoteEvent> = BTreeMap::new();
+        for n in notes {
+            latest.insert(id_key(&n), n);
+        }
+        let mut sorted: Vec<NoteEvent> = latest.into_values().collect();
+        sorted.sort_by_key(key);
+
+        let mut store = Self { nodes: Vec::with_capacity(sorted.len()), ..Self::default() };
+        // Cartesian tree over the sorted keys: keep the right spine on a stack.
+        let mut spine: Vec<usize> = Vec::new();
+        for note in sorted {
+            let idx = store.alloc(note);
+            let mut last = NIL;
+            while let Some(&top) = spine.last() {
+                if store.nodes[top].priority >= store.nodes[idx].priority {
+                    break;
+                }
+                last = top;
+                spine.pop();
+            }
+            store.nodes[idx].left = last;
+            if let Some(&top) = spine.last() {
+                store.nodes[top].right = idx;
+            }
+            spine.push(idx);
+        }
+        if let Some(&root) = spine.first() {
+            store.root = Some(root);
+            store.refresh_subtree(root);
+        }
+        store
     }
 
-    /// Insert a note keeping onset ordering.
-    pub fn insert(&mut self, note: NoteEvent) {
-        let idx = self.notes.binary_search_by_key(&note.onset, |n| n.onset).unwrap_or_else(|i| i);
-        self.notes.insert(idx, note);
+    /// Insert a note; returns the note it replaced (same track and id), if
+    /// any.
+    pub fn insert(&mut self, note: NoteEvent) -> Option<NoteEvent> {
+        let old = self.remove(note.track, note.id);
+        let idx = self.alloc(note);
+        let (l, r) = self.split(self.root_idx(), key(&note), false);
+        let merged = self.merge(l, idx);
+        let root = self.merge(merged, r);
+        self.set_root(root);
+        old
     }
 
-    /// Bulk extend with pre-sorted notes (by onset).
-    pub fn extend_sorted(&mut self, mut new_notes: Vec<NoteEvent>) {
-        if new_notes.is_empty() {
-            return;
+    /// Remove the note `id` of `track`.
+    pub fn remove(&mut self, track: TrackId, id: NoteId) -> Option<NoteEvent> {
+        let idx = self.by_id.remove(&(track.0, id.0))?;
+        let note = self.nodes[idx].note;
+        let (l, rest) = self.split(self.root_idx(), key(&note), false);
+        let (mid, r) = self.split(rest, key(&note), true);
+        debug_assert_eq!(mid, idx);
+        let root = self.merge(l, r);
+        self.set_root(root);
+        self.free.push(idx);
+        Some(note)
+    }
+
+    /// Replace the stored note with the same track and id; returns the
+    /// previous version, or `None` (and stores nothing) if it is unknown.
+    pub fn update(&mut self, note: NoteEvent) -> Option<NoteEvent> {
+        if !self.by_id.contains_key(&id_key(&note)) {
+            return None;
+        }
+        self.insert(note)
+    }
+
+    #[must_use]
+    pub fn get(&self, track: TrackId, id: NoteId) -> Option<&NoteEvent> {
+        self.by_id.get(&(track.0, id.0)).map(|&i| &self.nodes[i].note)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.by_id.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.by_id.is_empty()
+    }
+
+    /// Notes whose onset is in [start, end), in onset order.
+    pub fn notes_in_range(&self, start: SampleTime, end: SampleTime) -> Iter<'_> {
+        Iter::new(self, start, end, SampleTime::new(i64::MIN), None)
+    }
+
+    /// Notes sounding anywhere in [start, end): onset < end and offset > start.
+    pub fn notes_overlapping(&self, start: SampleTime, end: SampleTime) -> Iter<'_> {
+        Iter::new(self, SampleTime::new(i64::MIN), end, start, None)
+    }
+
+    /// Notes sounding at `t`: onset <= t < offset.
+    pub fn notes_at(&self, t: SampleTime) -> Iter<'_> {
+        self.notes_overlapping(t, SampleTime::new(t.value().saturating_add(1)))
+    }
+
+    /// All notes in onset order.
+    pub fn all(&self) -> Iter<'_> {
+        Iter::new(
+            self,
+            SampleTime::new(i64::MIN),
+            SampleTime::new(i64::MAX),
+            SampleTime::new(i64::MIN),
+            None,
+        )
+    }
+
+    /// The same queries restricted to one track.
+    #[must_use]
+    pub fn track(&self, track: TrackId) -> TrackView<'_> {
+        TrackView { store: self, track }
+    }
+
+    fn alloc(&mut self, note: NoteEvent) -> usize {
+        let slot = Node {
+            note,
+            max_offset: note.offset,
+            priority: priority(&note),
+            left: NIL,
+            right: NIL,
+        };
+        let idx = if let Some(idx) = self.free.pop() {
+            self.nodes[idx] = slot;
+            idx
+        } else {
+            self.nodes.push(slot);
+            self.nodes.len() - 1
+        };
+        self.by_id.insert(id_key(&note), idx);
+        idx
+    }
+
+    fn root_idx(&self) -> usize {
+        self.root.unwrap_or(NIL)
+    }
+
+    fn set_root(&mut self, root: usize) {
+        self.root = (root != NIL).then_some(root);
+    }
+
+    /// Split `t` into keys `< k` and the rest (`<= k` and the rest if
+    /// `inclusive`).
+    fn split(&mut self, t: usize, k: (SampleTime, u16, u32), inclusive: bool) -> (usize, usize) {
+        if t == NIL {
+            return (NIL, NIL);
+        }
+        let tk = key(&self.nodes[t].note);
+        let goes_left = if inclusive { tk <= k } else { tk < k };
+        if goes_left {
+            let (l, r) = self.split(self.nodes[t].right, k, inclusive);
+            self.nodes[t].right = l;
+            self.refresh(t);
+            (t, r)
+        } else {
+            let (l, r) = self.split(self.nodes[t].left, k, inclusive);
+            self.nodes[t].left = r;
+            self.refresh(t);
+            (l, t)
+        }
+    }
+
+    /// Join two treaps where every key in `l` is below every key in `r`.
+    fn merge(&mut self, l: usize, r: usize) -> usize {
+        if l == NIL {
+            return r;
         }
-        // Simple merge; deterministic.
-        let mut merged = Vec::with_capacity(self.notes.len() + new_notes.len());
-        let mut i = 0;
-        let mut j = 0;
-        self.notes.sort_by_key(|n| n.onset);
-        new_notes.sort_by_key(|n| n.onset);
-        while i < self.notes.len() && j < new_notes.len() {
-            if self.notes[i].onset <= new_notes[j].onset {
-                merged.push(self.notes[i]);
-                i += 1;
+        if r == NIL {
+            return l;
+        }
+        if self.nodes[l].priority >= self.nodes[r].priority {
+            let right = self.nodes[l].right;
+            self.nodes[l].right = self.merge(right, r);
+            self.refresh(l);
+            l
+        } else {
+            let left = self.nodes[r].left;
+            self.nodes[r].left = self.merge(l, left);
+            self.refresh(r);
+            r
+        }
+    }
+
+    fn refresh(&mut self, t: usize) {
+        let n = self.nodes[t];
+        let mut max = n.note.offset;
+        for child in [n.left, n.right] {
+            if child != NIL {
+                max = max.max(self.nodes[child].max_offset);
+            }
+        }
+        self.nodes[t].max_offset = max;
+    }
+
+    fn refresh_subtree(&mut self, t: usize) {
+        // Post-order without recursion: the bulk-built tree may be deep.
+        let mut stack = Vec::new();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        stack.push(t);
+        while let Some(n) = stack.pop() {
+            order.push(n);
+            for child in [self.nodes[n].left, self.nodes[n].right] {
+                if child != NIL {
+                    stack.push(child);
+                }
+            }
+        }
+        for &n in order.iter().rev() {
+            self.refresh(n);
+        }
+    }
+
+    fn max_offset(&self, t: usize) -> SampleTime {
+        self.nodes[t].max_offset
+    }
+}
+
+/// Per-track queries over a `NoteStore`.
+#[derive(Debug, Clone, Copy)]
+pub struct TrackView<'a> {
+    store: &'a NoteStore,
+    track: TrackId,
+}
+
+impl<'a> TrackView<'a> {
+    pub fn notes_in_range(&self, start: SampleTime, end: SampleTime) -> Iter<'a> {
+        Iter::new(self.store, start, end, SampleTime::new(i64::MIN), Some(self.track))
+    }
+
+    pub fn notes_overlapping(&self, start: SampleTime, end: SampleTime) -> Iter<'a> {
+        Iter::new(self.store, SampleTime::new(i64::MIN), end, start, Some(self.track))
+    }
+
+    pub fn notes_at(&self, t: SampleTime) -> Iter<'a> {
+        self.notes_overlapping(t, SampleTime::new(t.value().saturating_add(1)))
+    }
+
+    pub fn all(&self) -> Iter<'a> {
+        Iter::new(
+            self.store,
+            SampleTime::new(i64::MIN),
+            SampleTime::new(i64::MAX),
+            SampleTime::new(i64::MIN),
+            Some(self.track),
+        )
+    }
+}
+
+/// In-order walk yielding notes with `onset` in `[onset_from, onset_to)`
+/// and `offset > offset_after`.
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    store: &'a NoteStore,
+    stack: Vec<usize>,
+    onset_from: SampleTime,
+    onset_to: SampleTime,
+    offset_after: SampleTime,
+    track: Option<TrackId>,
+}
+
+impl<'a> Iter<'a> {
+    fn new(
+        store: &'a NoteStore,
+        onset_from: SampleTime,
+        onset_to: SampleTime,
+        offset_after: SampleTime,
+        track: Option<TrackId>,
+    ) -> Self {
+        let mut it = Self { store, stack: Vec::new(), onset_from, onset_to, offset_after, track };
+        it.descend(store.root_idx());
+        it
+    }
+
+    /// Push the left spine of `t`, skipping subtrees that cannot match.
+    fn descend(&mut self, mut t: usize) {
+        while t != NIL && self.store.max_offset(t) > self.offset_after {
+            let node = &self.store.nodes[t];
+            if node.note.onset < self.onset_from {
+                // This node and its left subtree start too early.
+                t = node.right;
             } else {
-                merged.push(new_notes[j]);
-                j += 1;
+                self.stack.push(t);
+                t = node.left;
             }
         }
-        merged.extend_from_slice(&self.notes[i..]);
-        merged.extend_from_slice(&new_notes[j..]);
-        self.notes = merged;
     }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a NoteEvent;
 
-    /// Query notes whose onset is in [start, end).
-    pub fn notes_in_range(&self, start: SampleTime, end: SampleTime) -> &[NoteEvent] {
-        // Lower bound on start.
-        let from = self.notes.partition_point(|n| n.onset < start);
-        // First index where onset >= end.
-        let to = self.notes.partition_point(|n| n.onset < end);
-        &self.notes[from..to]
+    fn next(&mut self) -> Option<&'a NoteEvent> {
+        while let Some(t) = self.stack.pop() {
+            let node = &self.store.nodes[t];
+            if node.note.onset >= self.onset_to {
+                // Everything after this in order starts even later.
+                self.stack.clear();
+                return None;
+            }
+            self.descend(node.right);
+            let ev = &node.note;
+            if ev.offset > self.offset_after && self.track.is_none_or(|tr| tr == ev.track) {
+                return Some(ev);
+            }
+        }
+        None
     }
+}
 
-    #[must_use]
-    pub fn all(&self) -> &[NoteEvent] {
-        &self.notes
+/// Bulk insert in any order; same rules as `insert`.
+impl Extend<NoteEvent> for NoteStore {
+    fn extend<I: IntoIterator<Item = NoteEvent>>(&mut self, notes: I) {
+        if self.is_empty() {
+            *self = Self::from_notes(notes);
+            return;
+        }
+        for n in notes {
+            self.insert(n);
+        }
     }
 }
+
+fn key(n: &NoteEvent) -> (SampleTime, u16, u32) {
+    (n.onset, n.track.0, n.id.0)
+}
+
+fn id_key(n: &NoteEvent) -> (u16, u32) {
+    (n.track.0, n.id.0)
+}
+
+/// Deterministic treap priority (splitmix-style hash of track and id).
+fn priority(n: &NoteEvent) -> u32 {
+    let mut x = n.id.0.wrapping_add(0x9E37_79B9) ^ u32::from(n.track.0).rotate_left(16);
+    x = (x ^ (x >> 16)).wrapping_mul(0x85EB_CA6B);
+    x = (x ^ (x >> 13)).wrapping_mul(0xC2B2_AE35);
+    x ^ (x >> 16)
+}
@@ -1,43 +1,264 @@
 //! SpscQueue:
-//! - Single-producer, single-consumer queue abstraction.
-//! - Implementation is single-threaded and safe; can be used in RT-ish contexts.
-//! - For true cross-thread lock-free behavior, implement in a separate crate
-//!   or relax `unsafe` constraints in a small, audited module.
+//! - Bounded single-producer, single-consumer queue for RT↔non-RT handoff.
+//! - `split` yields a `Producer` and a `Consumer` that are `Send` and can
+//!   live on different threads.
+//! - Wait-free: every operation finishes in a bounded number of steps; no
+//!   locks, no CAS loops, no allocation after construction.
+//! - Capacity is rounded up to a power of two, so a slot is its index
+//!   masked by `capacity - 1` and stays continuous when the `usize`
+//!   counters wrap.
+//!
+//! This is the crate's only `unsafe` module. Audit notes:
+//! - Slots are `UnsafeCell<MaybeUninit<T>>`, `T: Copy` (nothing to drop).
+//! - `tail` is written only by the producer, `head` only by the consumer.
+//!   Slots in `[head, tail)` are initialized and owned by the consumer;
+//!   all others are owned by the producer.
+//! - Each side publishes its index with `Release` after touching slots and
+//!   reads the other's with `Acquire` before touching slots, so a slot is
+//!   never read and written at the same time.
 
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering};
 
-/// Bounded SPSC queue (logical contract).
+struct Shared<T> {
+    /// Power-of-two length.
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    /// Items popped so far (wrapping).
+    head: AtomicUsize,
+    /// Items pushed so far (wrapping).
+    tail: AtomicUsize,
+    /// Highest occupancy the producer has observed.
+    high_water: AtomicUsize,
+}
+
+// SAFETY: slot access is partitioned between the two halves by the
+// head/tail protocol above; items only move between threads by value.
+unsafe impl<T: Copy + Send> Sync for Shared<T> {}
+
+impl<T> Shared<T> {
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    fn slot(&self, index: usize) -> *mut MaybeUninit<T> {
+        self.slots[index & (self.slots.len() - 1)].get()
+    }
+}
+
+/// Bounded SPSC queue.
 ///
-/// Implementation:
-/// - Internally same as EventRing.
-/// - Caller must ensure only one producer and one consumer touch it at a time,
-///   or wrap in appropriate synchronization in higher layers.
-#[derive(Debug)]
+/// Use it directly through `&mut self` on one thread, or `split` it into
+/// halves for cross-thread use.
 pub struct SpscQueue<T> {
-    ring: super::event_ring::EventRing<T>,
+    producer: Producer<T>,
+    consumer: Consumer<T>,
 }
 
-impl<T> SpscQueue<T> {
+impl<T: Copy> SpscQueue<T> {
+    /// Queue holding at least `cap` items: `cap` rounded up to a power of
+    /// two.
+    ///
+    /// # Panics
+    /// If `cap` is zero or rounds up past `usize::MAX`.
     pub fn with_capacity(cap: usize) -> Self {
+        assert!(cap > 0, "capacity must be > 0");
+        let cap = cap.checked_next_power_of_two().expect("capacity overflow");
+        let slots = (0..cap).map(|_| UnsafeCell::new(MaybeUninit::uninit())).collect();
+        let shared = Arc::new(Shared {
+            slots,
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            high_water: AtomicUsize::new(0),
+        });
         Self {
-            ring: super::event_ring::EventRing::with_capacity(cap),
+            producer: Producer { shared: Arc::clone(&shared), tail: 0, head_cache: 0 },
+            consumer: Consumer { shared, head: 0, tail_cache: 0 },
         }
     }
 
+    /// Separate the halves; move each to its thread.
+    #[must_use]
+    pub fn split(self) -> (Producer<T>, Consumer<T>) {
+        (self.producer, self.consumer)
+    }
+
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.ring.is_empty()
+        self.len() == 0
     }
 
     #[must_use]
     pub fn is_full(&self) -> bool {
-        self.ring.is_full()
+        self.len() == self.producer.capacity()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.producer.tail.wrapping_sub(self.consumer.head)
     }
 
     pub fn push(&mut self, value: T) -> Result<(), T> {
-        self.ring.push(value)
+        self.producer.push(value)
     }
 
     pub fn pop(&mut self) -> Option<T> {
-        self.ring.pop()
+        self.consumer.pop()
+    }
+}
+
+impl<T> core::fmt::Debug for SpscQueue<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SpscQueue")
+            .field("capacity", &self.producer.shared.capacity())
+            .field("len", &self.producer.tail.wrapping_sub(self.consumer.head))
+            .finish()
+    }
+}
+
+/// Writing half of an `SpscQueue`.
+pub struct Producer<T> {
+    shared: Arc<Shared<T>>,
+    tail: usize,
+    /// Last `head` seen; refreshed when it limits a push.
+    head_cache: usize,
+}
+
+impl<T: Copy> Producer<T> {
+    /// Free slots right now.
+    pub fn free_len(&mut self) -> usize {
+        self.head_cache = self.shared.head.load(Ordering::Acquire);
+        self.shared.capacity() - self.tail.wrapping_sub(self.head_cache)
+    }
+
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        if self.push_slice(core::slice::from_ref(&value)) == 1 { Ok(()) } else { Err(value) }
+    }
+
+    /// Push as many leading items of `items` as fit; returns how many.
+    pub fn push_slice(&mut self, items: &[T]) -> usize {
+        let cap = self.shared.capacity();
+        let mut free = cap - self.tail.wrapping_sub(self.head_cache);
+        if free < items.len() {
+            free = self.free_len();
+        }
+        let n = free.min(items.len());
+        for (i, &item) in items[..n].iter().enumerate() {
+            // SAFETY: slot `tail + i` is outside `[head, tail)`, so only the
+            // producer may touch it; `T: Copy` needs no drop of the old value.
+            unsafe { (*self.shared.slot(self.tail.wrapping_add(i))).write(item) };
+        }
+        if n > 0 {
+            self.tail = self.tail.wrapping_add(n);
+            self.shared.tail.store(self.tail, Ordering::Release);
+            let used = self.tail.wrapping_sub(self.head_cache);
+            if used > self.shared.high_water.load(Ordering::Relaxed) {
+                self.shared.high_water.store(used, Ordering::Relaxed);
+            }
+        }
+        n
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity()
+    }
+
+    /// Highest occupancy seen so far (an upper bound: it is measured
+    /// against the producer's last view of the consumer).
+    pub fn high_water_mark(&self) -> usize {
+        self.shared.high_water.load(Ordering::Relaxed)
+    }
+}
+
+/// Reading half of an `SpscQueue`.
+pub struct Consumer<T> {
+    shared: Arc<Shared<T>>,
+    head: usize,
+    /// Last `tail` seen; refreshed when it limits a pop.
+    tail_cache: usize,
+}
+
+impl<T: Copy> Consumer<T> {
+    /// Items ready to pop.
+    pub fn len(&self) -> usize {
+        self.shared.tail.load(Ordering::Acquire).wrapping_sub(self.head)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let mut out = [MaybeUninit::uninit()];
+        if self.pop_into(&mut out) == 1 {
+            // SAFETY: `pop_into` initialized the one element it reported.
+            Some(unsafe { out[0].assume_init() })
+        } else {
+            None
+        }
+    }
+
+    /// Pop up to `out.len()` items into `out`; returns how many.
+    pub fn pop_slice(&mut self, out: &mut [T]) -> usize {
+        let n = self.ready(out.len());
+        for (i, dst) in out[..n].iter_mut().enumerate() {
+            // SAFETY: slot `head + i` is inside `[head, tail)`: initialized
+            // by the producer and published by its `Release` store of `tail`.
+            *dst = unsafe { (*self.shared.slot(self.head.wrapping_add(i))).assume_init() };
+        }
+        self.release(n);
+        n
+    }
+
+    /// Copy of the item at `offset` past the front, without popping it.
+    pub fn peek(&mut self, offset: usize) -> Option<T> {
+        if offset >= self.ready(offset + 1) {
+            return None;
+        }
+        // SAFETY: as in `pop_slice`; the slot stays owned by the consumer.
+        Some(unsafe { (*self.shared.slot(self.head.wrapping_add(offset))).assume_init() })
+    }
+
+    /// Drop up to `n` items from the front; returns how many.
+    pub fn skip(&mut self, n: usize) -> usize {
+        let n = self.ready(n);
+        self.release(n);
+        n
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity()
+    }
+
+    /// Highest occupancy the producer has observed so far.
+    pub fn high_water_mark(&self) -> usize {
+        self.shared.high_water.load(Ordering::Relaxed)
+    }
+
+    fn pop_into(&mut self, out: &mut [MaybeUninit<T>]) -> usize {
+        let n = self.ready(out.len());
+        for (i, dst) in out[..n].iter_mut().enumerate() {
+            // SAFETY: see `pop_slice`.
+            *dst = unsafe { *self.shared.slot(self.head.wrapping_add(i)) };
+        }
+        self.release(n);
+        n
+    }
+
+    /// Number of items (at most `want`) ready at the front.
+    fn ready(&mut self, want: usize) -> usize {
+        if self.tail_cache.wrapping_sub(self.head) < want {
+            self.tail_cache = self.shared.tail.load(Ordering::Acquire);
+        }
+        self.tail_cache.wrapping_sub(self.head).min(want)
+    }
+
+    fn release(&mut self, n: usize) {
+        if n > 0 {
+            self.head = self.head.wrapping_add(n);
+            self.shared.head.store(self.head, Ordering::Release);
+        }
     }
 }
@@ -1,8 +1,9 @@
-//! TempoMap: deterministic mapping between sample positions and musical time.
+//! `TempoMap`: deterministic mapping between sample positions and musical time.
 //!
 //! Responsibilities:
-//! - Store ordered TempoEvent, MeterEvent, SwingEvent sequences.
-//! - Provide monotonic conversions SampleTime <-> beats (in fixed-point).
+//! - Store ordered `TempoEvent`, `MeterEvent`, `SwingEvent` sequences.
+//! - Provide monotonic conversions `SampleTime` <-> beats (in fixed-point or
+//!   as an exact fraction).
 //!
 //! This is purely arithmetic; no wall-clock, no scheduling.
 
@@ -57,6 +58,18 @@ impl TempoMap {
         self.swing_events.push(ev);
     }
 
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn tempo_events(&self) -> &[TempoEvent] {
+        &self.tempo_events
+    }
+
+    pub fn meter_events(&self) -> &[MeterEvent] {
+        &self.meter_events
+    }
+
     /// Convert sample position to beats * 1000 using stepwise-constant tempo.
     ///
     /// For v1: ignores meter/swing; they are available for callers.
@@ -70,17 +83,73 @@ impl TempoMap {
                 break;
             }
             let dt = ev.position.value() - last_pos.value();
-            let bpm = last_bpm_x1000 as i64;
-            // beats = samples / sr * (bpm / 60)
-            let beats_x1000 = dt as i128 * bpm as i128 / (self.sample_rate as i128 * 60) * 1000;
+            let bpm = i64::from(last_bpm_x1000);
+            // beats = samples / sr * (bpm / 60); bpm is already x1000.
+            let beats_x1000 =
+                i128::from(dt) * i128::from(bpm) / (i128::from(self.sample_rate) * 60);
             acc_beats_x1000 = acc_beats_x1000.saturating_add(beats_x1000 as i64);
             last_pos = ev.position;
             last_bpm_x1000 = ev.bpm_x1000;
         }
 
         let dt = pos.value() - last_pos.value();
-        let bpm = last_bpm_x1000 as i64;
-        let beats_x1000 = dt as i128 * bpm as i128 / (self.sample_rate as i128 * 60) * 1000;
+        let bpm = i64::from(last_bpm_x1000);
+        let beats_x1000 = i128::from(dt) * i128::from(bpm) / (i128::from(self.sample_rate) * 60);
         acc_beats_x1000.saturating_add(beats_x1000 as i64)
     }
+
+    /// Convert a beat position `beats_num / beats_den` to a sample position
+    /// (the inverse of `sample_to_beats_x1000`, without its rounding).
+    ///
+    /// Rounds down to whole samples; returns `SampleTime::ZERO` for
+    /// `beats_den <= 0`.
+    pub fn beats_to_sample(&self, beats_num: i64, beats_den: i64) -> SampleTime {
+        if beats_den <= 0 {
+            return SampleTime::ZERO;
+        }
+        // Beats are counted in units of 1 / (sr * 60_000) so every segment
+        // (samples * bpm_x1000) is an exact integer.
+        let units_per_beat = i128::from(self.sample_rate) * 60_000;
+        let target = i128::from(beats_num) * units_per_beat / i128::from(beats_den);
+        let mut last_pos = SampleTime::ZERO;
+        let mut last_bpm_x1000: i128 = 120_000;
+        let mut acc: i128 = 0;
+
+        for ev in &self.tempo_events {
+            let seg = i128::from(ev.position.value() - last_pos.value()) * last_bpm_x1000;
+            if acc + seg > target {
+                break;
+            }
+            acc += seg;
+            last_pos = ev.position;
+            last_bpm_x1000 = i128::from(ev.bpm_x1000.max(1));
+        }
+
+        let dt = (target - acc) / last_bpm_x1000;
+        SampleTime::new(last_pos.value().saturating_add(dt as i64))
+    }
+
+    /// Convert a sample position to ticks at `ticks_per_beat`, rounded to
+    /// the nearest tick, so `beats_to_sample(ticks, ticks_per_beat)` maps
+    /// back to the same tick whenever a tick spans at least two samples.
+    pub fn sample_to_ticks(&self, pos: SampleTime, ticks_per_beat: u16) -> i64 {
+        let units_per_beat = i128::from(self.sample_rate.max(1)) * 60_000;
+        let mut last_pos = SampleTime::ZERO;
+        let mut last_bpm_x1000: i128 = 120_000;
+        let mut acc: i128 = 0;
+
+        for ev in &self.tempo_events {
+            if ev.position >= pos {
+                break;
+            }
+            acc += i128::from(ev.position.value() - last_pos.value()) * last_bpm_x1000;
+            last_pos = ev.position;
+            last_bpm_x1000 = i128::from(ev.bpm_x1000.max(1));
+        }
+
+        acc += i128::from(pos.value() - last_pos.value()) * last_bpm_x1000;
+        let scaled = acc * i128::from(ticks_per_beat);
+        let ticks = (scaled + units_per_beat / 2).div_euclid(units_per_beat);
+        i64::try_from(ticks).unwrap_or(if ticks < 0 { i64::MIN } else { i64::MAX })
+    }
 }
@@ -0,0 +1,166 @@
+use mt_alloc::MidiNormalizer;
+use mt_alloc::expression::{ExpressionConfig, PitchResolution};
+use mt_core::midi::{MidiChannel, MidiEvent, MidiEventKind, TimedMidiEvent};
+use mt_core::pedal::PedalConfig;
+use mt_core::pitch::MidiNote;
+use mt_core::time::SampleTime;
+
+fn at(t: i64, event: MidiEvent) -> TimedMidiEvent {
+    TimedMidiEvent::at(SampleTime::new(t), event)
+}
+
+fn cc(ch: u8, data1: u8, data2: u8) -> MidiEvent {
+    let channel = MidiChannel::new(ch).unwrap();
+    MidiEvent { channel, kind: MidiEventKind::ControlChange, data1, data2 }
+}
+
+fn normalizer(pitch: PitchResolution) -> MidiNormalizer {
+    let expression = ExpressionConfig { pitch, record_curves: true, ..Default::default() };
+    MidiNormalizer::with_config(PedalConfig::default(), expression)
+}
+
+#[test]
+fn mpe_member_bend_resolves_sounding_pitch() {
+    let ch0 = MidiChannel::new(0).unwrap();
+    let ch1 = MidiChannel::new(1).unwrap();
+    let c4 = MidiNote::new(60).unwrap();
+    let mut norm = normalizer(PitchResolution::Onset);
+
+    // MCM: lower zone with 15 members (48-semitone member bend range).
+    let mut events = vec![at(0, cc(0, 101, 0)), at(0, cc(0, 100, 6)), at(0, cc(0, 6, 15))];
+    // Member note starts a fifth up (+700 cents), manager adds +100.
+    events.push(at(10, MidiEvent::pitch_bend(ch1, 1195)));
+    events.push(at(10, MidiEvent::pitch_bend(ch0, 4096)));
+    events.push(at(10, MidiEvent::note_on(ch1, c4, 100)));
+    events.push(at(20, cc(1, 74, 90)));
+    events.push(at(100, MidiEvent::note_off(ch1, c4, 0)));
+
+    let notes = norm.process(&events);
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].note.value(), 68);
+
+    let curves = norm.take_expressions();
+    assert_eq!(curves.len(), 1);
+    assert_eq!(curves[0].id, notes[0].id);
+    assert_eq!(curves[0].key, c4);
+    assert_eq!(curves[0].onset_pitch_cents(), 6800);
+    let timbre: Vec<u8> = curves[0].points.iter().map(|p| p.timbre).collect();
+    assert_eq!(timbre, [64, 90]);
+}
+
+#[test]
+fn bend_range_and_pitch_resolution_modes() {
+    let ch = MidiChannel::new(2).unwrap();
+    let c4 = MidiNote::new(60).unwrap();
+    let events = [
+        // RPN 0: bend range 12 semitones.
+        at(0, cc(2, 101, 0)),
+        at(0, cc(2, 100, 0)),
+        at(0, cc(2, 6, 12)),
+        at(0, MidiEvent::note_on(ch, c4, 100)),
+        // Bend a full octave up after a short attack.
+        at(10, MidiEvent::pitch_bend(ch, 8191)),
+        at(100, MidiEvent::note_off(ch, c4, 0)),
+    ];
+
+    let sounding = |pitch| normalizer(pitch).process(&events)[0].note.value();
+    assert_eq!(sounding(PitchResolution::Key), 60);
+    assert_eq!(sounding(PitchResolution::Onset), 60);
+    assert_eq!(sounding(PitchResolution::Dominant), 72);
+}
+
+#[test]
+fn pitch_bend_value_round_trips() {
+    let ch = MidiChannel::new(0).unwrap();
+    for v in [-8192, -1, 0, 1, 4096, 8191] {
+        assert_eq!(MidiEvent::pitch_bend(ch, v).pitch_bend_value(), Some(v));
+    }
+    assert_eq!(MidiEvent::pitch_bend(ch, 0).data2, 0x40);
+    assert_eq!(cc(0, 1, 2).pitch_bend_value(), None);
+}
+
+#[test]
+fn ump_per_note_bend_and_timbre() {
+    let mut norm = normalizer(PitchResolution::Onset);
+    let position = SampleTime::new(0);
+    let words = [
+        // Note on, channel 0, key 60, attribute pitch 7.9 = 60.5 semitones.
+        0x4090_3C03,
+        0xC000_7900,
+        // Per-note timbre (registered per-note controller 74) on key 60.
+        0x4000_3C4A,
+        0xFFFF_FFFF,
+        // Per-note pitch bend on key 60: +1200 cents of 4800.
+        0x4060_3C00,
+        0xA000_0000,
+    ];
+    assert!(norm.process_ump(position, &words).is_empty());
+
+    // Note off, channel 0, key 60.
+    let notes = norm.process_ump(SampleTime::new(100), &[0x4080_3C00, 0]);
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].velocity, 96);
+    // All packets share the onset, so the per-note bend replaces the
+    // attribute pitch there.
+    assert_eq!(notes[0].note.value(), 72);
+
+    let curves = norm.take_expressions();
+    let last = curves[0].points.last().unwrap();
+    assert_eq!((last.bend_cents, last.timbre), (1200, 127));
+}
+
+#[test]
+fn member_bend_range_applies_to_the_whole_zone() {
+    let ch2 = MidiChannel::new(2).unwrap();
+    let c4 = MidiNote::new(60).unwrap();
+    let mut norm = normalizer(PitchResolution::Onset);
+    let events = [
+        // MCM: lower zone with 3 members.
+        at(0, cc(0, 101, 0)),
+        at(0, cc(0, 100, 6)),
+        at(0, cc(0, 6, 3)),
+        // RPN 0 on member 1: 12 semitones, for members 1..=3.
+        at(0, cc(1, 101, 0)),
+        at(0, cc(1, 100, 0)),
+        at(0, cc(1, 6, 12)),
+        at(10, MidiEvent::pitch_bend(ch2, 4096)),
+        at(10, MidiEvent::note_on(ch2, c4, 100)),
+        at(100, MidiEvent::note_off(ch2, c4, 0)),
+    ];
+    let notes = norm.process(&events);
+    assert_eq!(notes[0].note.value(), 66);
+    assert_eq!(norm.take_expressions()[0].onset_pitch_cents(), 6600);
+}
+
+#[test]
+fn guitar_mono_mode_sounds_one_note_per_string() {
+    let string = MidiChannel::new(1).unwrap();
+    let (e4, g4) = (MidiNote::new(64).unwrap(), MidiNote::new(67).unwrap());
+    // Hammer-on: G4 struck while E4 is still held on the same string.
+    let events = [
+        at(0, MidiEvent::note_on(string, e4, 100)),
+        at(50, MidiEvent::note_on(string, g4, 100)),
+        at(60, MidiEvent::note_off(string, e4, 0)),
+        at(100, MidiEvent::note_off(string, g4, 0)),
+    ];
+    let spans = |mut norm: MidiNormalizer, modes: &[TimedMidiEvent]| {
+        norm.process(modes);
+        let notes = norm.process(&events);
+        notes
+            .iter()
+            .map(|n| (n.note.value(), n.onset.value(), n.offset.value()))
+            .collect::<Vec<_>>()
+    };
+
+    let key = || normalizer(PitchResolution::Key);
+    assert_eq!(spans(key(), &[]), [(64, 0, 60), (67, 50, 100)]);
+    let mono = ExpressionConfig { mono_channels: 1 << 1, ..Default::default() };
+    let mono = MidiNormalizer::with_config(PedalConfig::default(), mono);
+    assert_eq!(spans(mono, &[]), [(64, 0, 50), (67, 50, 100)]);
+
+    // Mono Mode On from channel 1 for six strings; Poly Mode On undoes it.
+    let mono_on = at(0, cc(1, 126, 6));
+    assert_eq!(spans(key(), &[mono_on]), [(64, 0, 50), (67, 50, 100)]);
+    let poly_on = at(0, cc(1, 127, 0));
+    assert_eq!(spans(key(), &[mono_on, poly_on]), [(64, 0, 60), (67, 50, 100)]);
+}
@@ -0,0 +1,53 @@
+use mt_alloc::MidiNormalizer;
+use mt_core::midi::{MidiChannel, MidiEvent, MidiEventKind, TimedMidiEvent};
+use mt_core::pedal::{NoteDurationMode, PedalConfig};
+use mt_core::pitch::MidiNote;
+use mt_core::time::SampleTime;
+
+fn sustain(value: u8) -> MidiEvent {
+    let channel = MidiChannel::new(0).unwrap();
+    MidiEvent { channel, kind: MidiEventKind::ControlChange, data1: 64, data2: value }
+}
+
+fn offsets(mode: NoteDurationMode) -> Vec<(u8, i64)> {
+    let ch = MidiChannel::new(0).unwrap();
+    let c4 = MidiNote::new(60).unwrap();
+    let e4 = MidiNote::new(64).unwrap();
+    let mut norm =
+        MidiNormalizer::with_pedal(PedalConfig { durations: mode, ..Default::default() });
+
+    let steps = [
+        (0, vec![sustain(127), MidiEvent::note_on(ch, c4, 90)]),
+        (100, vec![MidiEvent::note_off(ch, c4, 0)]),
+        (200, vec![MidiEvent::note_on(ch, e4, 90)]),
+        (300, vec![MidiEvent::note_off(ch, e4, 0)]),
+        (400, vec![sustain(0)]),
+    ];
+    let mut out = Vec::new();
+    for (t, events) in steps {
+        let timed: Vec<_> =
+            events.into_iter().map(|e| TimedMidiEvent::at(SampleTime::new(t), e)).collect();
+        out.extend(norm.process(&timed));
+    }
+    out.extend(norm.flush(SampleTime::new(1_000)));
+    out.iter().map(|n| (n.note.value(), n.offset.value())).collect()
+}
+
+#[test]
+fn midi_normalizer_applies_sustain_pedal() {
+    assert_eq!(offsets(NoteDurationMode::Sounding), [(60, 400), (64, 400)]);
+    assert_eq!(offsets(NoteDurationMode::KeyRelease), [(60, 100), (64, 300)]);
+}
+
+#[test]
+fn midi_normalizer_applies_soft_pedal_velocity() {
+    let ch = MidiChannel::new(0).unwrap();
+    let c4 = MidiNote::new(60).unwrap();
+    let soft = MidiEvent { channel: ch, kind: MidiEventKind::ControlChange, data1: 67, data2: 127 };
+    let mut norm =
+        MidiNormalizer::with_pedal(PedalConfig { soft_velocity_x1000: 750, ..Default::default() });
+    let at = |t, e| TimedMidiEvent::at(SampleTime::new(t), e);
+    let mut out = norm.process(&[at(0, soft), at(0, MidiEvent::note_on(ch, c4, 100))]);
+    out.extend(norm.process(&[at(100, MidiEvent::note_off(ch, c4, 0))]));
+    assert_eq!(out.iter().map(|n| n.velocity).collect::<Vec<_>>(), [75]);
+}
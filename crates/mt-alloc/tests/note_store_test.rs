@@ -0,0 +1,102 @@
+use mt_alloc::NoteStore;
+use mt_core::events::{NoteEvent, NoteId, TrackId};
+use mt_core::pitch::MidiNote;
+use mt_core::time::SampleTime;
+
+fn note(id: u32, track: u16, onset: i64, offset: i64) -> NoteEvent {
+    NoteEvent {
+        id: NoteId(id),
+        track: TrackId(track),
+        onset: SampleTime::new(onset),
+        offset: SampleTime::new(offset),
+        note: MidiNote::new(60).unwrap(),
+        velocity: 100,
+    }
+}
+
+fn ids<'a>(notes: impl Iterator<Item = &'a NoteEvent>) -> Vec<u32> {
+    notes.map(|n| n.id.0).collect()
+}
+
+#[test]
+fn note_store_queries_match_linear_scan() {
+    // Deterministic pseudo-random notes, long and short mixed.
+    let mut seed = 1_u32;
+    let mut rand = |m: u32| {
+        seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        i64::from((seed >> 8) % m)
+    };
+    let notes: Vec<NoteEvent> = (1..=500)
+        .map(|id| {
+            let onset = rand(10_000);
+            let len = if id % 17 == 0 { rand(5_000) } else { rand(200) } + 1;
+            note(id, (id % 3) as u16, onset, onset + len)
+        })
+        .collect();
+    let store = NoteStore::from_notes(notes.clone());
+    assert_eq!(store.len(), 500);
+
+    let mut sorted = notes.clone();
+    sorted.sort_by_key(|n| (n.onset, n.track.0, n.id.0));
+    assert_eq!(ids(store.all()), ids(sorted.iter()));
+
+    for (a, b) in [(0, 100), (2_500, 2_600), (5_000, 9_000), (9_999, 20_000)] {
+        let (s, e) = (SampleTime::new(a), SampleTime::new(b));
+        let overlap = sorted.iter().filter(|n| n.onset < e && n.offset > s);
+        assert_eq!(ids(store.notes_overlapping(s, e)), ids(overlap));
+        let in_range = sorted.iter().filter(|n| n.onset >= s && n.onset < e);
+        assert_eq!(ids(store.notes_in_range(s, e)), ids(in_range));
+        let at = sorted.iter().filter(|n| n.onset <= s && n.offset > s);
+        assert_eq!(ids(store.notes_at(s)), ids(at));
+        let on_track =
+            sorted.iter().filter(|n| n.track == TrackId(1) && n.onset < e && n.offset > s);
+        assert_eq!(ids(store.track(TrackId(1)).notes_overlapping(s, e)), ids(on_track));
+    }
+}
+
+#[test]
+fn note_store_edits_by_id() {
+    let mut store = NoteStore::new();
+    store.insert(note(1, 0, 0, 100));
+    store.insert(note(2, 0, 50, 60));
+    store.insert(note(3, 1, 200, 300));
+    assert_eq!(ids(store.notes_at(SampleTime::new(55))), [1, 2]);
+
+    assert_eq!(store.remove(TrackId(0), NoteId(1)).map(|n| n.offset.value()), Some(100));
+    assert_eq!(store.remove(TrackId(0), NoteId(1)), None);
+    assert_eq!(ids(store.notes_at(SampleTime::new(55))), [2]);
+
+    // Moving a note re-sorts it.
+    assert!(store.update(note(3, 1, 10, 400)).is_some());
+    assert!(store.update(note(9, 1, 0, 1)).is_none());
+    assert_eq!(ids(store.all()), [3, 2]);
+    assert_eq!(ids(store.notes_at(SampleTime::new(350))), [3]);
+    assert_eq!(store.get(TrackId(1), NoteId(3)).map(|n| n.onset.value()), Some(10));
+    assert_eq!(store.len(), 2);
+}
+
+#[test]
+fn note_store_keys_notes_by_track_and_id() {
+    // Two tracks numbering their notes from 1.
+    let mut store = NoteStore::from_notes([note(1, 0, 0, 100), note(1, 1, 10, 50)]);
+    assert_eq!(store.len(), 2);
+    assert_eq!(store.get(TrackId(1), NoteId(1)).map(|n| n.onset.value()), Some(10));
+    assert_eq!(ids(store.notes_at(SampleTime::new(20))), [1, 1]);
+
+    // Replacing and removing touch only the matching track.
+    assert!(store.insert(note(1, 1, 60, 70)).is_some());
+    assert_eq!(store.get(TrackId(0), NoteId(1)).map(|n| n.onset.value()), Some(0));
+    assert_eq!(store.remove(TrackId(0), NoteId(1)).map(|n| n.track), Some(TrackId(0)));
+    assert_eq!(store.len(), 1);
+    assert!(store.get(TrackId(0), NoteId(1)).is_none());
+
+    store.extend([note(2, 0, 5, 80), note(1, 1, 60, 90), note(3, 1, 0, 10)]);
+    assert_eq!(store.len(), 3);
+    assert_eq!(ids(store.all()), [3, 2, 1]);
+    assert_eq!(store.get(TrackId(1), NoteId(1)).map(|n| n.offset.value()), Some(90));
+
+    let mut empty = NoteStore::new();
+    empty.extend([note(1, 0, 0, 1), note(1, 1, 0, 1), note(1, 0, 5, 6)]);
+    assert_eq!(empty.len(), 2);
+    assert_eq!(empty.get(TrackId(0), NoteId(1)).map(|n| n.onset.value()), Some(5));
+}
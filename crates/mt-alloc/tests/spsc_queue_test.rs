@@ -0,0 +1,112 @@
+//! The cross-thread tests double as Miri checks of the slot protocol:
+//! `cargo +nightly miri test -p mt-alloc --test spsc_queue_test`.
+
+use mt_alloc::SpscQueue;
+
+/// Fewer items under Miri, which runs the same code orders of magnitude
+/// slower.
+const ITEMS: u64 = if cfg!(miri) { 2_000 } else { 100_000 };
+
+#[test]
+fn spsc_queue_batches_wrap_and_track_high_water() {
+    let (mut tx, mut rx) = SpscQueue::<u32>::with_capacity(4).split();
+
+    assert_eq!(tx.push_slice(&[1, 2, 3, 4, 5]), 4);
+    assert_eq!(tx.push(6), Err(6));
+    assert_eq!(tx.high_water_mark(), 4);
+
+    let mut out = [0; 3];
+    assert_eq!(rx.pop_slice(&mut out), 3);
+    assert_eq!(out, [1, 2, 3]);
+    assert_eq!(rx.peek(0), Some(4));
+    assert_eq!(rx.peek(1), None);
+
+    assert_eq!(tx.push_slice(&[7, 8, 9]), 3);
+    assert_eq!(rx.len(), 4);
+    assert_eq!(rx.pop(), Some(4));
+    assert_eq!(rx.skip(1), 1);
+    assert_eq!(rx.pop_slice(&mut out), 2);
+    assert_eq!(out[..2], [8, 9]);
+    assert!(rx.is_empty());
+}
+
+#[test]
+fn spsc_queue_keeps_order_across_threads() {
+    const N: u64 = ITEMS;
+    let (mut tx, mut rx) = SpscQueue::<u64>::with_capacity(64).split();
+
+    let producer = std::thread::spawn(move || {
+        let mut next = 0;
+        while next < N {
+            let batch: [u64; 8] = core::array::from_fn(|i| next + i as u64);
+            let len = (N - next).min(8) as usize;
+            next += tx.push_slice(&batch[..len]) as u64;
+            std::thread::yield_now();
+        }
+    });
+
+    let mut expected = 0;
+    let mut buf = [0; 16];
+    while expected < N {
+        let n = rx.pop_slice(&mut buf);
+        for &v in &buf[..n] {
+            assert_eq!(v, expected);
+            expected += 1;
+        }
+        std::thread::yield_now();
+    }
+    producer.join().unwrap();
+    assert!(rx.high_water_mark() <= rx.capacity());
+}
+
+#[test]
+fn spsc_queue_rounds_capacity_to_a_power_of_two() {
+    let mut q = SpscQueue::<u32>::with_capacity(3);
+    for i in 0..4 {
+        assert_eq!(q.push(i), Ok(()));
+    }
+    assert!(q.is_full());
+    assert_eq!(q.push(4), Err(4));
+
+    // Cycle through the slots many times at an odd stride.
+    let (mut tx, mut rx) = q.split();
+    assert_eq!(tx.capacity(), 4);
+    assert_eq!(rx.skip(4), 4);
+    let mut out = [0; 3];
+    for round in 0..100 {
+        let base = round * 3;
+        assert_eq!(tx.push_slice(&[base, base + 1, base + 2]), 3);
+        assert_eq!(rx.peek(2), Some(base + 2));
+        assert_eq!(rx.pop_slice(&mut out), 3);
+        assert_eq!(out, [base, base + 1, base + 2]);
+    }
+}
+
+#[test]
+fn spsc_queue_single_items_across_threads() {
+    // Capacity 5 rounds to 8; single pushes and pops stress every slot
+    // handoff rather than batches.
+    let (mut tx, mut rx) = SpscQueue::<u64>::with_capacity(5).split();
+    assert_eq!(rx.capacity(), 8);
+
+    let producer = std::thread::spawn(move || {
+        for v in 0..ITEMS / 10 {
+            while tx.push(v).is_err() {
+                std::thread::yield_now();
+            }
+        }
+    });
+
+    let mut expected = 0;
+    while expected < ITEMS / 10 {
+        match rx.pop() {
+            Some(v) => {
+                assert_eq!(v, expected);
+                expected += 1;
+            }
+            None => std::thread::yield_now(),
+        }
+    }
+    producer.join().unwrap();
+    assert!(rx.is_empty());
+}
@@ -0,0 +1,30 @@
+use mt_alloc::TempoMap;
+use mt_core::events::TempoEvent;
+use mt_core::time::SampleTime;
+
+const SR: u32 = 48_000;
+
+#[test]
+fn sample_to_beats_x1000_counts_thousandths_of_a_beat() {
+    // Default 120 BPM: one second is two beats.
+    let map = TempoMap::new(SR);
+    assert_eq!(map.sample_to_beats_x1000(SampleTime::new(i64::from(SR))), 2_000);
+    assert_eq!(map.sample_to_beats_x1000(SampleTime::new(i64::from(SR) / 4)), 500);
+
+    // Two seconds at 120, then 90 BPM: four beats plus 1.5 per second.
+    let mut map = TempoMap::new(SR);
+    map.push_tempo(TempoEvent { position: SampleTime::new(2 * i64::from(SR)), bpm_x1000: 90_000 });
+    assert_eq!(map.sample_to_beats_x1000(SampleTime::new(2 * i64::from(SR))), 4_000);
+    assert_eq!(map.sample_to_beats_x1000(SampleTime::new(4 * i64::from(SR))), 7_000);
+}
+
+#[test]
+fn sample_to_beats_x1000_inverts_beats_to_sample() {
+    let mut map = TempoMap::new(SR);
+    map.push_tempo(TempoEvent { position: SampleTime::new(30_000), bpm_x1000: 97_500 });
+    for beats_x1000 in [0, 250, 1_000, 3_750, 12_125] {
+        let pos = map.beats_to_sample(beats_x1000, 1_000);
+        let back = map.sample_to_beats_x1000(pos);
+        assert!((beats_x1000 - back).abs() <= 1, "{beats_x1000} -> {pos:?} -> {back}");
+    }
+}
@@ -125,7 +125,7 @@ impl AudioNoteAnalyzer for SimpleAudioNoteAnalyzer {
     }
 }
 
-fn rms(frame: &[f32]) -> f32 {
+pub(crate) fn rms(frame: &[f32]) -> f32 {
     if frame.is_empty() {
         return 0.0;
     }
@@ -136,7 +136,7 @@ fn rms(frame: &[f32]) -> f32 {
     (sum / frame.len() as f32).sqrt()
 }
 
-fn estimate_freq_zc(frame: &[f32], sample_rate: u32) -> Option<f32> {
+pub(crate) fn estimate_freq_zc(frame: &[f32], sample_rate: u32) -> Option<f32> {
     if frame.len() < 2 {
         return None;
     }
@@ -168,7 +168,7 @@ fn estimate_freq_zc(frame: &[f32], sample_rate: u32) -> Option<f32> {
     }
 }
 
-fn freq_to_midi(freq: f32) -> Option<u8> {
+pub(crate) fn freq_to_midi(freq: f32) -> Option<u8> {
     if !(freq > 0.0) {
         return None;
     }
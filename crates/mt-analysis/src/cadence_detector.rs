@@ -0,0 +1,162 @@
+//! Cadence detection over a chord timeline.
+//!
+//! Strategy:
+//! - Merge repeated chords, then read each chord change (approach → arrival)
+//!   as scale degrees of the key active at the arrival.
+//! - Classify by degree and quality: V → I (PAC when both are in root
+//!   position, else IAC; vii° → I is IAC), V → vi/VI deceptive, IV → I
+//!   plagal, iv6 → V in minor Phrygian half, and V not resolving to I/vi
+//!   half.
+//! - Confidence multiplies key and chord confidence with the metric weight
+//!   of the arrival (downbeat, mid-bar, other) and how long it is held.
+//!
+//! Soprano position is not checked; PAC vs IAC rests on inversions only.
+
+use crate::confidence::clamp01_to_confidence_x1000;
+use crate::config::CadenceConfig;
+use crate::metric_grid::MetricGrid;
+use crate::traits::CadenceAnalyzer;
+use mt_core::chord::Chord;
+use mt_core::chord_kind::ChordKindId;
+use mt_core::events::{CadenceEvent, CadenceKind, ChordEvent, KeyEvent, MeterEvent, TempoEvent};
+use mt_core::key::{Key, KeyMode};
+
+pub struct CadenceDetector;
+
+impl CadenceAnalyzer for CadenceDetector {
+    fn detect_cadences(
+        &self,
+        chords: &[ChordEvent],
+        keys: &[KeyEvent],
+        tempo_events: &[TempoEvent],
+        meter_events: &[MeterEvent],
+        sample_rate: u32,
+        cfg: &CadenceConfig,
+    ) -> Vec<CadenceEvent> {
+        if keys.is_empty() {
+            return Vec::new();
+        }
+        let sr = if sample_rate == 0 { 44_100 } else { sample_rate }; // stable default.
+        let grid = MetricGrid::new(sr, tempo_events, meter_events);
+
+        // Repeated chords are one harmonic event.
+        let mut runs: Vec<ChordEvent> = Vec::with_capacity(chords.len());
+        for c in chords {
+            match runs.last_mut() {
+                Some(last) if last.chord == c.chord => {
+                    last.offset = c.offset;
+                    last.confidence_x1000 = last.confidence_x1000.min(c.confidence_x1000);
+                }
+                _ => runs.push(*c),
+            }
+        }
+
+        let mut out = Vec::new();
+        for (i, pair) in runs.windows(2).enumerate() {
+            let (approach, arrival) = (&pair[0], &pair[1]);
+            let key_event =
+                keys.iter().rev().find(|k| k.position <= arrival.onset).unwrap_or(&keys[0]);
+            let key = key_event.key;
+            let Some(kind) = classify(approach.chord, arrival.chord, key) else {
+                continue;
+            };
+            // A plain half cadence needs V to stay unresolved; iv6 → V is
+            // distinctive enough on its own.
+            if kind == CadenceKind::Half
+                && runs.get(i + 2).is_some_and(|next| {
+                    matches!(
+                        classify(arrival.chord, next.chord, key),
+                        Some(
+                            CadenceKind::PerfectAuthentic
+                                | CadenceKind::ImperfectAuthentic
+                                | CadenceKind::Deceptive
+                        )
+                    )
+                })
+            {
+                continue;
+            }
+
+            let (bar, numerator) = grid.bar(arrival.onset.value());
+            let beat_in_bar = (bar - bar.floor()) * f32::from(numerator);
+            let near = |beat: f32| (beat_in_bar - beat).abs() <= 0.25;
+            let metric = if near(0.0) || near(f32::from(numerator)) {
+                1.0
+            } else if numerator >= 4 && numerator % 2 == 0 && near(f32::from(numerator / 2)) {
+                cfg.mid_bar_weight
+            } else {
+                cfg.offbeat_weight
+            };
+            let held = grid.beats(arrival.offset.value()) - grid.beats(arrival.onset.value());
+            let length = 0.5 + 0.5 * (held / cfg.min_arrival_beats.max(1e-3)).clamp(0.0, 1.0);
+            let chord_conf =
+                f32::from(approach.confidence_x1000.min(arrival.confidence_x1000)) / 1000.0;
+            let key_conf = f32::from(key_event.confidence_x1000) / 1000.0;
+
+            let score = key_conf * chord_conf * metric * length;
+            if score >= cfg.min_confidence {
+                out.push(CadenceEvent {
+                    kind,
+                    key,
+                    approach: approach.onset,
+                    position: arrival.onset,
+                    confidence_x1000: clamp01_to_confidence_x1000(score),
+                });
+            }
+        }
+        out
+    }
+}
+
+/// Cadence type of the chord change `approach` → `arrival` in `key`.
+///
+/// Judges the two chords alone: metric position, duration and what follows
+/// are left to `CadenceDetector`.
+#[must_use]
+pub fn classify(approach: Chord, arrival: Chord, key: Key) -> Option<CadenceKind> {
+    let degree = |c: Chord| (c.root.as_u8() + 12 - key.tonic().as_u8()) % 12;
+    let (from, to) = (degree(approach), degree(arrival));
+    let minor = key.mode() == KeyMode::Minor;
+
+    let is_dominant =
+        |deg: u8, c: Chord| deg == 7 && matches!(c.kind, ChordKindId::Maj | ChordKindId::Dom7);
+    let is_leading_tone = from == 11
+        && matches!(approach.kind, ChordKindId::Dim | ChordKindId::HalfDim7 | ChordKindId::Dim7);
+    let major_quality = matches!(
+        arrival.kind,
+        ChordKindId::Maj | ChordKindId::Maj7 | ChordKindId::Maj6 | ChordKindId::SixNine
+    );
+    let minor_quality =
+        matches!(arrival.kind, ChordKindId::Min | ChordKindId::Min7 | ChordKindId::Min6);
+    // Minor keys also accept a major tonic (Picardy third).
+    let is_tonic = to == 0 && (major_quality || (minor && minor_quality));
+    let root_position = |c: Chord| c.bass.is_none_or(|b| b == c.root);
+
+    if is_tonic && is_dominant(from, approach) {
+        return Some(if root_position(approach) && root_position(arrival) {
+            CadenceKind::PerfectAuthentic
+        } else {
+            CadenceKind::ImperfectAuthentic
+        });
+    }
+    if is_tonic && is_leading_tone {
+        return Some(CadenceKind::ImperfectAuthentic);
+    }
+    if is_tonic && from == 5 {
+        return Some(CadenceKind::Plagal);
+    }
+    if is_dominant(from, approach)
+        && ((!minor && to == 9 && minor_quality) || (minor && to == 8 && major_quality))
+    {
+        return Some(CadenceKind::Deceptive);
+    }
+    if is_dominant(to, arrival) && !is_dominant(from, approach) {
+        let tonic = key.tonic().as_u8();
+        let iv6 = minor
+            && from == 5
+            && matches!(approach.kind, ChordKindId::Min | ChordKindId::Min6 | ChordKindId::Min7)
+            && approach.bass.is_some_and(|b| b.as_u8() == (tonic + 8) % 12);
+        return Some(if iv6 { CadenceKind::PhrygianHalf } else { CadenceKind::Half });
+    }
+    None
+}
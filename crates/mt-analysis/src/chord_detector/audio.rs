@@ -0,0 +1,89 @@
+//! Chord recognition directly from audio via chroma.
+//!
+//! Strategy:
+//! - Extract chroma frames (`crate::chroma`); no note transcription, so
+//!   dense guitar voicings and full mixes keep their harmony.
+//! - Score every chord state per frame against harmonic-weighted templates
+//!   (`templates`), since chroma also carries each tone's overtones.
+//! - Smooth over time with the same HMM as `HmmChordAnalyzer`.
+
+use crate::chord_detector::hmm::{ChordHmm, EMISSION_SHARPNESS, decode_runs};
+use crate::chord_detector::templates::{
+    CHORD_STATES, harmonic_state_template, similarities_with, state_chord,
+};
+use crate::chroma::chroma_frames;
+use crate::confidence::clamp01_to_confidence_x1000;
+use crate::config::{ChordConfig, ChromaConfig};
+use crate::traits::AudioChordAnalyzer;
+use mt_core::events::ChordEvent;
+use mt_core::key::Key;
+use mt_core::time::SampleTime;
+
+/// Harmonics modelled per chord tone in the templates.
+const HARMONIC_PARTIALS: u8 = 4;
+/// Relative weight of each successive harmonic.
+const HARMONIC_DECAY: f32 = 0.6;
+
+/// Chord analyzer working on chroma frames of mono audio.
+///
+/// `key`, when known, conditions the chord prior like `HmmChordAnalyzer`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ChromaChordAnalyzer {
+    pub key: Option<Key>,
+}
+
+impl ChromaChordAnalyzer {
+    pub const fn new(key: Option<Key>) -> Self {
+        Self { key }
+    }
+}
+
+impl AudioChordAnalyzer for ChromaChordAnalyzer {
+    fn detect_audio_chords(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        chroma: &ChromaConfig,
+        cfg: &ChordConfig,
+    ) -> Vec<ChordEvent> {
+        let features = chroma_frames(samples, sample_rate, chroma);
+        if features.frames() == 0 {
+            return Vec::new();
+        }
+
+        let templates: Vec<[f32; 12]> = (0..CHORD_STATES)
+            .map(|s| harmonic_state_template(s, HARMONIC_PARTIALS, HARMONIC_DECAY))
+            .collect();
+
+        let hop = chroma.hop_size.max(1) as i64;
+        let mut frames = Vec::with_capacity(features.frames());
+        let mut log_emissions = Vec::with_capacity(features.frames());
+        let mut sims = [0.0_f32; CHORD_STATES];
+        for i in 0..features.frames() {
+            let mut pc = [0.0_f32; 12];
+            if let Some(f) = features.frame(i) {
+                pc.copy_from_slice(f);
+            }
+            similarities_with(&pc, |s| templates[s], &mut sims);
+            let mut em = [0.0_f32; CHORD_STATES];
+            for (e, s) in em.iter_mut().zip(sims.iter()) {
+                *e = EMISSION_SHARPNESS * s;
+            }
+            log_emissions.push(em);
+            let on = i as i64 * hop;
+            frames.push((on, on + hop, pc.iter().any(|c| *c > 0.0)));
+        }
+
+        let hmm = ChordHmm::new(cfg.self_transition, self.key, cfg.key_prior_weight);
+        decode_runs(|_| &hmm, &frames, &log_emissions)
+            .into_iter()
+            .filter(|run| run.score >= cfg.min_confidence)
+            .map(|run| ChordEvent {
+                chord: state_chord(run.state),
+                onset: SampleTime::new(run.onset),
+                offset: SampleTime::new(run.offset),
+                confidence_x1000: clamp01_to_confidence_x1000(run.score),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,101 @@
+//! Lowest-voice tracking for inversions and slash chords.
+//!
+//! Strategy:
+//! - Split a window at every note boundary; in each slice the lowest
+//!   sounding note is the bass voice, everything above it the upper voices.
+//! - Accumulate both voices as duration-weighted pitch-class vectors.
+//! - The bass pitch-class is reported only if it dominates the bass voice.
+
+use crate::config::ChordBassMode;
+use mt_core::chord::{BassPolicy, Chord};
+use mt_core::events::NoteEvent;
+use mt_core::pitch::PitchClass;
+
+/// Duration-weighted pitch-classes split into bass and upper voices.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VoicedWeights {
+    pub bass: [f32; 12],
+    pub upper: [f32; 12],
+}
+
+impl VoicedWeights {
+    /// Combined pitch-class weights of both voices.
+    #[must_use]
+    pub fn all(&self) -> [f32; 12] {
+        core::array::from_fn(|pc| self.bass[pc] + self.upper[pc])
+    }
+
+    /// Pitch-class holding at least `min_share` of the bass voice, if any.
+    #[must_use]
+    pub fn dominant_bass(&self, min_share: f32) -> Option<PitchClass> {
+        let total = self.bass.iter().sum::<f32>();
+        if total <= 0.0 {
+            return None;
+        }
+        let (pc, weight) = self.bass.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1))?;
+        (weight / total >= min_share).then(|| PitchClass::from_unchecked(pc as u8))
+    }
+}
+
+/// Bass/upper pitch-class weights of notes sounding in `[start, end)`.
+///
+/// `notes` may be any superset of the sounding notes, e.g. a whole slice or
+/// a `NoteStore` overlap query.
+#[must_use]
+pub fn voiced_weights<'a>(
+    notes: impl IntoIterator<Item = &'a NoteEvent>,
+    start: i64,
+    end: i64,
+) -> VoicedWeights {
+    let active: Vec<&NoteEvent> =
+        notes.into_iter().filter(|n| n.onset.value() < end && n.offset.value() > start).collect();
+
+    let mut bounds: Vec<i64> = Vec::with_capacity(active.len() * 2 + 2);
+    bounds.push(start);
+    bounds.push(end);
+    for n in &active {
+        bounds.push(n.onset.value().clamp(start, end));
+        bounds.push(n.offset.value().clamp(start, end));
+    }
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    let mut out = VoicedWeights::default();
+    for slice in bounds.windows(2) {
+        let (a, b) = (slice[0], slice[1]);
+        let dur = (b - a) as f32;
+        let sounding = || active.iter().filter(|n| n.onset.value() < b && n.offset.value() > a);
+        let Some(lowest) = sounding().min_by_key(|n| n.note.value()) else {
+            continue;
+        };
+        for n in sounding() {
+            let pc = n.note.pitch_class().as_u8() as usize;
+            if core::ptr::eq(*n, *lowest) {
+                out.bass[pc] += dur;
+            } else {
+                out.upper[pc] += dur;
+            }
+        }
+    }
+    out
+}
+
+/// Attach `bass` to a root-position `chord` as allowed by `mode`.
+///
+/// A bass equal to the root leaves the chord in root position; a
+/// non-chord-tone bass is kept only in `ChordBassMode::SlashChords`.
+#[must_use]
+pub fn label_bass(chord: Chord, bass: Option<PitchClass>, mode: ChordBassMode) -> Chord {
+    let Some(bass) = bass else {
+        return chord;
+    };
+    if bass == chord.root {
+        return chord;
+    }
+    let policy = match mode {
+        ChordBassMode::RootPosition => return chord,
+        ChordBassMode::Inversions => BassPolicy::ChordTone,
+        ChordBassMode::SlashChords => BassPolicy::Any,
+    };
+    Chord::with_bass_policy(chord.root, chord.kind, Some(bass), policy).unwrap_or(chord)
+}
@@ -0,0 +1,441 @@
+//! HMM chord decoder with transition smoothing.
+//!
+//! Strategy:
+//! - Partition timeline into hop-spaced frames (same windowing as rule-based).
+//! - Score every chord state (`CHORD_KINDS` × root) per frame by template fit.
+//! - Viterbi-decode with a transition matrix biased towards staying on the
+//!   same chord and, optionally, towards chords diatonic to the local key
+//!   (one model per key span; the key active at a frame conditions the
+//!   transition into it).
+//! - Forward–backward posteriors give each emitted chord its confidence and
+//!   are exposed per frame as a `ChordProbabilityMap`.
+//! - Each decoded run is labelled with its dominant bass (inversion/slash).
+
+use crate::chord_detector::bass::{label_bass, voiced_weights};
+use crate::chord_detector::probability_map::ChordProbabilityMap;
+use crate::chord_detector::templates::{
+    CHORD_STATES, pitch_class_weights, state_chord, state_template, template_similarities,
+};
+use crate::confidence::clamp01_to_confidence_x1000;
+use crate::config::{ChordBassMode, ChordConfig};
+use crate::traits::ChordAnalyzer;
+use mt_alloc::NoteStore;
+use mt_core::chord_kind::CHORD_KINDS;
+use mt_core::events::{ChordEvent, KeyEvent, NoteEvent};
+use mt_core::key::{Key, KeyMode};
+use mt_core::pitch::PitchClass;
+use mt_core::scale::{ScaleId, build_scale};
+use mt_core::time::SampleTime;
+
+/// Emission log-likelihood per unit of template fit.
+pub(crate) const EMISSION_SHARPNESS: f32 = 20.0;
+
+/// Chord analyzer decoding the whole timeline with an HMM.
+///
+/// `keys`, in position order, condition the chord prior (see
+/// `ChordConfig::key_prior_weight`): each key from its position until the
+/// next, the first one also before its position. Empty means no key prior.
+#[derive(Clone, Debug, Default)]
+pub struct HmmChordAnalyzer {
+    pub keys: Vec<KeyEvent>,
+}
+
+impl HmmChordAnalyzer {
+    pub const fn new(keys: Vec<KeyEvent>) -> Self {
+        Self { keys }
+    }
+}
+
+impl HmmChordAnalyzer {
+    /// Per-frame posterior distribution over chord states (voiced frames only).
+    #[must_use]
+    pub fn chord_probabilities(
+        &self,
+        notes: &[NoteEvent],
+        cfg: &ChordConfig,
+    ) -> ChordProbabilityMap {
+        let Some((frames, log_emissions)) = note_frames(notes, cfg) else {
+            return ChordProbabilityMap::default();
+        };
+        let models = KeyedModels::new(&self.keys, &frames, cfg);
+        let posteriors = posteriors(|f| models.at(f), &log_emissions);
+
+        let mut map = ChordProbabilityMap::with_capacity(frames.len());
+        for (&(on, off, voiced), post) in frames.iter().zip(posteriors.iter()) {
+            if voiced {
+                map.push_frame(SampleTime::new(on), SampleTime::new(off), post);
+            }
+        }
+        map
+    }
+}
+
+/// Hop-spaced frames `(onset, offset, voiced)` and their emission log-likelihoods.
+type NoteFrames = (Vec<(i64, i64, bool)>, Vec<[f32; CHORD_STATES]>);
+
+fn note_frames(notes: &[NoteEvent], cfg: &ChordConfig) -> Option<NoteFrames> {
+    let start = notes.first()?.onset.value();
+    let end = notes.iter().map(|n| n.offset.value()).max().unwrap_or(start);
+    if end <= start {
+        return None;
+    }
+
+    let hop = seconds_to_samples(cfg.hop_seconds, cfg.sample_rate);
+    let win = seconds_to_samples(cfg.window_seconds, cfg.sample_rate);
+    if hop <= 0 || win <= 0 {
+        return None;
+    }
+
+    let index = NoteStore::from_notes(notes.iter().copied());
+    let mut frames = Vec::new();
+    let mut log_emissions = Vec::new();
+    let mut sims = [0.0_f32; CHORD_STATES];
+    let mut t = start;
+    while t < end {
+        let w_end = (t + win).min(end);
+        let window = index.notes_overlapping(SampleTime::new(t), SampleTime::new(w_end));
+        let pc_weights = pitch_class_weights(window, t, w_end);
+        let voiced = pc_weights.iter().any(|w| *w > 0.0);
+        template_similarities(&pc_weights, &mut sims);
+        let mut em = [0.0_f32; CHORD_STATES];
+        for (e, s) in em.iter_mut().zip(sims.iter()) {
+            *e = EMISSION_SHARPNESS * s;
+        }
+        log_emissions.push(em);
+        frames.push((t, (t + hop).min(end), voiced));
+        t += hop;
+    }
+    Some((frames, log_emissions))
+}
+
+fn seconds_to_samples(seconds: f32, sample_rate: u32) -> i64 {
+    (f64::from(seconds) * f64::from(sample_rate)) as i64
+}
+
+impl ChordAnalyzer for HmmChordAnalyzer {
+    fn detect_chords(&self, notes: &[NoteEvent], cfg: &ChordConfig) -> Vec<ChordEvent> {
+        let Some((frames, log_emissions)) = note_frames(notes, cfg) else {
+            return Vec::new();
+        };
+
+        let models = KeyedModels::new(&self.keys, &frames, cfg);
+        let mut out = Vec::new();
+        for run in decode_runs(|f| models.at(f), &frames, &log_emissions) {
+            if run.score < cfg.min_confidence {
+                continue;
+            }
+            let bass = match cfg.bass_mode {
+                ChordBassMode::RootPosition => None,
+                _ => voiced_weights(notes, run.onset, run.offset).dominant_bass(cfg.min_bass_share),
+            };
+            let state = bass.and_then(|b| rerooted_state(run.state, b)).unwrap_or(run.state);
+            out.push(ChordEvent {
+                chord: label_bass(state_chord(state), bass, cfg.bass_mode),
+                onset: SampleTime::new(run.onset),
+                offset: SampleTime::new(run.offset),
+                confidence_x1000: clamp01_to_confidence_x1000(run.score),
+            });
+        }
+        out
+    }
+}
+
+/// Consecutive voiced frames decoded to the same chord state.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct DecodedRun {
+    pub state: usize,
+    pub onset: i64,
+    pub offset: i64,
+    /// Mean posterior of `state` over the run.
+    pub score: f32,
+}
+
+/// One `ChordHmm` per key span, looked up per frame.
+struct KeyedModels {
+    models: Vec<ChordHmm>,
+    /// Index into `models` per frame.
+    by_frame: Vec<usize>,
+}
+
+impl KeyedModels {
+    fn new(keys: &[KeyEvent], frames: &[(i64, i64, bool)], cfg: &ChordConfig) -> Self {
+        let model = |key| ChordHmm::new(cfg.self_transition, key, cfg.key_prior_weight);
+        if keys.is_empty() {
+            return Self { models: vec![model(None)], by_frame: vec![0; frames.len()] };
+        }
+        let mut models = Vec::new();
+        let mut model_keys: Vec<Key> = Vec::new();
+        let mut by_frame = Vec::with_capacity(frames.len());
+        for &(onset, _, _) in frames {
+            let active = keys.partition_point(|k| k.position.value() <= onset);
+            let key = keys[active.saturating_sub(1)].key;
+            let index = model_keys.iter().position(|&k| k == key).unwrap_or_else(|| {
+                model_keys.push(key);
+                models.push(model(Some(key)));
+                models.len() - 1
+            });
+            by_frame.push(index);
+        }
+        Self { models, by_frame }
+    }
+
+    fn at(&self, frame: usize) -> &ChordHmm {
+        &self.models[self.by_frame[frame]]
+    }
+}
+
+/// Viterbi-decode `frames` and group consecutive voiced frames sharing a state.
+///
+/// `model(f)` is the HMM for frame `f` (see `viterbi`).
+pub(crate) fn decode_runs<'a>(
+    model: impl Fn(usize) -> &'a ChordHmm + Copy,
+    frames: &[(i64, i64, bool)],
+    log_emissions: &[[f32; CHORD_STATES]],
+) -> Vec<DecodedRun> {
+    let path = viterbi(model, log_emissions);
+    let posteriors = posteriors(model, log_emissions);
+
+    let mut out = Vec::new();
+    let mut run: Option<(usize, i64, i64, f32, u32)> = None; // (state, on, off, post_sum, n)
+    let mut close = |r: (usize, i64, i64, f32, u32)| {
+        let (state, onset, offset, sum, n) = r;
+        out.push(DecodedRun { state, onset, offset, score: sum / n as f32 });
+    };
+    for (i, &(f_on, f_off, voiced)) in frames.iter().enumerate() {
+        let state = path[i];
+        match run {
+            Some((s, on, _, sum, n)) if voiced && s == state => {
+                run = Some((s, on, f_off, sum + posteriors[i][state], n + 1));
+            }
+            _ => {
+                if let Some(r) = run.take() {
+                    close(r);
+                }
+                if voiced {
+                    run = Some((state, f_on, f_off, posteriors[i][state], 1));
+                }
+            }
+        }
+    }
+    if let Some(r) = run {
+        close(r);
+    }
+    out
+}
+
+/// State rooted on `bass` with the same pitch-class set (C6 over A -> Am7).
+fn rerooted_state(state: usize, bass: PitchClass) -> Option<usize> {
+    let tones = |s| state_template(s).map(|w| w > 0.0);
+    let target = tones(state);
+    let kinds = CHORD_KINDS.len();
+    let first = bass.as_u8() as usize * kinds;
+    (first..first + kinds).find(|&s| s != state && tones(s) == target)
+}
+
+/// Hidden Markov model over the chord state space.
+///
+/// Emissions are supplied per frame as log-likelihoods, so the same model
+/// decodes note-derived or chroma-derived frames.
+#[derive(Clone, Debug)]
+pub struct ChordHmm {
+    initial: [f32; CHORD_STATES],
+    /// Row-major `[from][to]` transition probabilities.
+    transitions: Vec<f32>,
+    /// `transitions`, as log-probabilities for Viterbi.
+    log_transitions: Vec<f32>,
+}
+
+impl ChordHmm {
+    /// Build a model from a self-transition probability and an optional key prior.
+    #[must_use]
+    pub fn new(self_transition: f32, key: Option<Key>, key_prior_weight: f32) -> Self {
+        let p_stay =
+            if self_transition.is_finite() { self_transition.clamp(0.0, 0.999) } else { 0.9 };
+
+        let prior = chord_prior(key, key_prior_weight);
+        let prior_total: f32 = prior.iter().sum();
+        let mut initial = [0.0_f32; CHORD_STATES];
+        for (i, p) in initial.iter_mut().enumerate() {
+            *p = prior[i] / prior_total;
+        }
+
+        let mut transitions = vec![0.0_f32; CHORD_STATES * CHORD_STATES];
+        for from in 0..CHORD_STATES {
+            let others = prior_total - prior[from];
+            for to in 0..CHORD_STATES {
+                transitions[from * CHORD_STATES + to] =
+                    if from == to { p_stay } else { (1.0 - p_stay) * prior[to] / others };
+            }
+        }
+        let log_transitions = transitions.iter().map(|p| p.max(f32::MIN_POSITIVE).ln()).collect();
+
+        Self { initial, transitions, log_transitions }
+    }
+
+    fn transition(&self, from: usize, to: usize) -> f32 {
+        self.transitions[from * CHORD_STATES + to]
+    }
+
+    /// Most likely state sequence (one state per frame).
+    #[must_use]
+    pub fn viterbi(&self, log_emissions: &[[f32; CHORD_STATES]]) -> Vec<usize> {
+        viterbi(|_| self, log_emissions)
+    }
+
+    /// Per-frame state posteriors via scaled forward–backward.
+    #[must_use]
+    pub fn posteriors(&self, log_emissions: &[[f32; CHORD_STATES]]) -> Vec<[f32; CHORD_STATES]> {
+        posteriors(|_| self, log_emissions)
+    }
+}
+
+/// Viterbi over a model per frame: `model(0)` gives the initial
+/// distribution, `model(f)` the transitions into frame `f`.
+fn viterbi<'a>(
+    model: impl Fn(usize) -> &'a ChordHmm,
+    log_emissions: &[[f32; CHORD_STATES]],
+) -> Vec<usize> {
+    let frames = log_emissions.len();
+    if frames == 0 {
+        return Vec::new();
+    }
+
+    let first = model(0);
+    let mut delta = [0.0_f32; CHORD_STATES];
+    for (s, d) in delta.iter_mut().enumerate() {
+        *d = first.initial[s].max(f32::MIN_POSITIVE).ln() + log_emissions[0][s];
+    }
+    let mut back = vec![[0_u16; CHORD_STATES]; frames];
+
+    for (f, em) in log_emissions.iter().enumerate().skip(1) {
+        let log_trans = &model(f).log_transitions;
+        let mut next = [f32::MIN; CHORD_STATES];
+        for to in 0..CHORD_STATES {
+            let mut best = f32::MIN;
+            let mut arg = 0usize;
+            for (from, d) in delta.iter().enumerate() {
+                let v = d + log_trans[from * CHORD_STATES + to];
+                if v > best {
+                    best = v;
+                    arg = from;
+                }
+            }
+            next[to] = best + em[to];
+            back[f][to] = arg as u16;
+        }
+        delta = next;
+    }
+
+    let mut state = 0usize;
+    for (s, d) in delta.iter().enumerate() {
+        if *d > delta[state] {
+            state = s;
+        }
+    }
+
+    let mut path = vec![0usize; frames];
+    for f in (0..frames).rev() {
+        path[f] = state;
+        state = back[f][state] as usize;
+    }
+    path
+}
+
+/// Scaled forward–backward over a model per frame (as in `viterbi`).
+fn posteriors<'a>(
+    model: impl Fn(usize) -> &'a ChordHmm,
+    log_emissions: &[[f32; CHORD_STATES]],
+) -> Vec<[f32; CHORD_STATES]> {
+    let frames = log_emissions.len();
+    let emissions: Vec<[f32; CHORD_STATES]> = log_emissions
+        .iter()
+        .map(|le| {
+            let max = le.iter().copied().fold(f32::MIN, f32::max);
+            let mut e = [0.0_f32; CHORD_STATES];
+            for (o, l) in e.iter_mut().zip(le.iter()) {
+                *o = (l - max).exp();
+            }
+            e
+        })
+        .collect();
+
+    let mut alpha = vec![[0.0_f32; CHORD_STATES]; frames];
+    for f in 0..frames {
+        let hmm = model(f);
+        for to in 0..CHORD_STATES {
+            let prior = if f == 0 {
+                hmm.initial[to]
+            } else {
+                (0..CHORD_STATES).map(|from| alpha[f - 1][from] * hmm.transition(from, to)).sum()
+            };
+            alpha[f][to] = prior * emissions[f][to];
+        }
+        normalize(&mut alpha[f]);
+    }
+
+    let mut beta = vec![[1.0_f32; CHORD_STATES]; frames];
+    for f in (0..frames.saturating_sub(1)).rev() {
+        let hmm = model(f + 1);
+        for from in 0..CHORD_STATES {
+            beta[f][from] = (0..CHORD_STATES)
+                .map(|to| hmm.transition(from, to) * emissions[f + 1][to] * beta[f + 1][to])
+                .sum();
+        }
+        normalize(&mut beta[f]);
+    }
+
+    for (a, b) in alpha.iter_mut().zip(beta.iter()) {
+        for (x, y) in a.iter_mut().zip(b.iter()) {
+            *x *= y;
+        }
+        normalize(a);
+    }
+    alpha
+}
+
+fn normalize(v: &mut [f32; CHORD_STATES]) {
+    let sum: f32 = v.iter().sum();
+    if sum > 0.0 {
+        for x in v.iter_mut() {
+            *x /= sum;
+        }
+    } else {
+        v.fill(1.0 / CHORD_STATES as f32);
+    }
+}
+
+/// Unnormalized chord prior: `exp(weight * (diatonic_fit - 1))`.
+///
+/// Diatonic fit is the share of chord tones in the key's scale (natural and
+/// harmonic minor combined for minor keys). Uniform without a key.
+fn chord_prior(key: Option<Key>, weight: f32) -> [f32; CHORD_STATES] {
+    let mut prior = [1.0_f32; CHORD_STATES];
+    let Some(key) = key else {
+        return prior;
+    };
+    if weight.is_nan() || weight <= 0.0 {
+        return prior;
+    }
+
+    let mut in_scale = [false; 12];
+    let scales: &[ScaleId] = match key.mode() {
+        KeyMode::Major => &[ScaleId::Major],
+        KeyMode::Minor => &[ScaleId::NaturalMinor, ScaleId::HarmonicMinor],
+    };
+    for id in scales {
+        if let Ok((len, pcs)) = build_scale(key.tonic(), *id) {
+            for pc in &pcs[..len] {
+                in_scale[pc.as_u8() as usize] = true;
+            }
+        }
+    }
+
+    for (state, p) in prior.iter_mut().enumerate() {
+        let t = state_template(state);
+        let size: f32 = t.iter().sum();
+        let fit: f32 =
+            t.iter().zip(in_scale.iter()).filter(|(_, s)| **s).map(|(t, _)| t).sum::<f32>() / size;
+        *p = (weight * (fit - 1.0)).exp();
+    }
+    prior
+}
@@ -1,6 +1,14 @@
 //! Chord detection façade.
 
+pub mod audio;
+pub mod bass;
+pub mod hmm;
+pub mod probability_map;
 pub mod rule_based;
 pub mod template_matching; // can reuse rule-based or provide alt strategy
+pub mod templates;
 
+pub use audio::ChromaChordAnalyzer;
+pub use hmm::{ChordHmm, HmmChordAnalyzer};
+pub use probability_map::ChordProbabilityMap;
 pub use rule_based::RuleBasedChordAnalyzer;
@@ -0,0 +1,84 @@
+//! Per-frame chord probability maps.
+//!
+//! Full distributions over the chord state space are stored row-major in a
+//! `FeatureBuffer` (one row per frame, `CHORD_STATES` columns). Consumers
+//! that only need the head of each distribution project it into
+//! `ChordProbabilityFrame`s.
+
+use crate::chord_detector::templates::{CHORD_STATES, state_chord};
+use crate::confidence::probability_to_confidence_x1000;
+use mt_alloc::FeatureBuffer;
+use mt_core::events::{ChordCandidate, ChordProbabilityFrame, MAX_CHORD_CANDIDATES};
+use mt_core::time::SampleTime;
+
+/// Chord state distributions over a sequence of analysis frames.
+#[derive(Clone, Debug)]
+pub struct ChordProbabilityMap {
+    bounds: Vec<(SampleTime, SampleTime)>,
+    probabilities: FeatureBuffer,
+}
+
+impl Default for ChordProbabilityMap {
+    fn default() -> Self {
+        Self::with_capacity(0)
+    }
+}
+
+impl ChordProbabilityMap {
+    pub fn with_capacity(frames: usize) -> Self {
+        Self {
+            bounds: Vec::with_capacity(frames),
+            probabilities: FeatureBuffer::with_capacity(CHORD_STATES, frames),
+        }
+    }
+
+    /// Append one frame's distribution (should sum to 1).
+    pub fn push_frame(
+        &mut self,
+        onset: SampleTime,
+        offset: SampleTime,
+        probabilities: &[f32; CHORD_STATES],
+    ) {
+        self.bounds.push((onset, offset));
+        self.probabilities.push_frame(probabilities);
+    }
+
+    #[must_use]
+    pub fn frames(&self) -> usize {
+        self.bounds.len()
+    }
+
+    /// `[onset, offset)` of frame `index`.
+    pub fn frame_bounds(&self, index: usize) -> Option<(SampleTime, SampleTime)> {
+        self.bounds.get(index).copied()
+    }
+
+    /// Full distribution of frame `index`, indexed by chord state.
+    pub fn probabilities(&self, index: usize) -> Option<&[f32]> {
+        self.probabilities.frame(index)
+    }
+
+    /// The `n` most probable chords of frame `index` (capped at
+    /// `MAX_CHORD_CANDIDATES`). Ties keep state order.
+    pub fn top_n(&self, index: usize, n: usize) -> Option<ChordProbabilityFrame> {
+        let (onset, offset) = self.frame_bounds(index)?;
+        let probs = self.probabilities(index)?;
+
+        let mut order: [usize; CHORD_STATES] = core::array::from_fn(|i| i);
+        order.sort_by(|a, b| probs[*b].total_cmp(&probs[*a]).then(a.cmp(b)));
+
+        let mut frame = ChordProbabilityFrame::new(onset, offset);
+        for &state in order.iter().take(n.min(MAX_CHORD_CANDIDATES)) {
+            frame.push(ChordCandidate {
+                chord: state_chord(state),
+                probability_x1000: probability_to_confidence_x1000(probs[state]),
+            });
+        }
+        Some(frame)
+    }
+
+    /// Project every frame to its top `n` candidates.
+    pub fn to_top_n_frames(&self, n: usize) -> Vec<ChordProbabilityFrame> {
+        (0..self.frames()).filter_map(|i| self.top_n(i, n)).collect()
+    }
+}
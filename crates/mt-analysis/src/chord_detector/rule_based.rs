@@ -2,16 +2,25 @@
 //!
 //! Strategy:
 //! - Partition timeline into fixed hops.
-//! - For each slice, collect active pitch-classes (duration-weighted).
+//! - Index notes in a `NoteStore` so each window only visits the notes
+//!   overlapping it (near-linear in song length instead of O(n * windows)).
+//! - For each slice, collect active pitch-classes (duration-weighted) and
+//!   track the lowest sounding note.
 //! - Match against known chord templates (from mt-core::chord_kind).
+//! - Label inversions and slash chords from the dominant bass
+//!   (see `ChordConfig::bass_mode`).
 //! - Emit ChordEvents with confidence based on template fit.
 
 #[cfg(not(feature = "std"))]
 extern crate alloc;
 
+use core::cmp::Reverse;
+
+use crate::chord_detector::bass::{label_bass, voiced_weights};
 use crate::confidence::clamp01_to_confidence_x1000;
-use crate::config::ChordConfig;
+use crate::config::{ChordBassMode, ChordConfig};
 use crate::traits::ChordAnalyzer;
+use mt_alloc::NoteStore;
 use mt_core::chord::Chord;
 use mt_core::chord_kind::{CHORD_KINDS, ChordKindId, chord_intervals};
 use mt_core::events::{ChordEvent, NoteEvent};
@@ -32,12 +41,13 @@ impl ChordAnalyzer for RuleBasedChordAnalyzer {
             return Vec::new();
         }
 
-        let hop = (cfg.hop_seconds * 44_100.0) as i64; // stable default; engine can adjust.
-        let win = (cfg.window_seconds * 44_100.0) as i64;
+        let hop = (f64::from(cfg.hop_seconds) * f64::from(cfg.sample_rate)) as i64;
+        let win = (f64::from(cfg.window_seconds) * f64::from(cfg.sample_rate)) as i64;
         if hop <= 0 || win <= 0 {
             return Vec::new();
         }
 
+        let index = NoteStore::from_notes(notes.iter().copied());
         let mut out = Vec::new();
         let mut t = start;
 
@@ -45,24 +55,38 @@ impl ChordAnalyzer for RuleBasedChordAnalyzer {
             let w_start = t;
             let w_end = (t + win).min(end);
 
-            let mut pc_weights = [0.0_f32; 12];
-
-            for n in notes {
-                let on = n.onset.value();
-                let off = n.offset.value();
-                if off <= w_start || on >= w_end {
-                    continue;
+            let window = index.notes_overlapping(SampleTime::new(w_start), SampleTime::new(w_end));
+            let voiced = voiced_weights(window, w_start, w_end);
+            let pc_weights = voiced.all();
+            let bass = match cfg.bass_mode {
+                ChordBassMode::RootPosition => None,
+                _ => voiced.dominant_bass(cfg.min_bass_share),
+            };
+
+            let (mut best_chord, mut score) = match bass {
+                None if cfg.bass_mode == ChordBassMode::RootPosition => {
+                    best_chord_match(&pc_weights, |_| ())
                 }
-                let is = on.max(w_start);
-                let ie = off.min(w_end);
-                if ie > is {
-                    let pc = n.note.pitch_class().as_u8() as usize;
-                    let dur = (ie - is) as f32;
-                    pc_weights[pc] += dur;
+                _ => best_chord_match(&pc_weights, |c| {
+                    (bass_rank(c, bass), template_fit_rank(c, &pc_weights))
+                }),
+            };
+            if let Some(b) = bass
+                && b != best_chord.root
+                && voiced.upper[b.as_u8() as usize] <= 0.0
+            {
+                // Bass stands apart from the upper voices: read the chord above it.
+                let (upper_chord, upper_score) =
+                    best_chord_match(&voiced.upper, |c| template_fit_rank(c, &pc_weights));
+                if upper_score >= score
+                    && (upper_chord.contains(b) || cfg.bass_mode == ChordBassMode::SlashChords)
+                {
+                    best_chord = upper_chord;
+                    score = upper_score;
                 }
             }
+            let best_chord = label_bass(best_chord, bass, cfg.bass_mode);
 
-            let (best_chord, score) = best_chord_match(&pc_weights);
             let conf = clamp01_to_confidence_x1000(score);
             if score >= cfg.min_confidence {
                 let onset = SampleTime::new(w_start);
@@ -77,8 +101,14 @@ impl ChordAnalyzer for RuleBasedChordAnalyzer {
     }
 }
 
-fn best_chord_match(pc_weights: &[f32; 12]) -> (Chord, f32) {
+/// Best-fitting root-position chord; ties go to the highest `rank`, then to
+/// catalog order.
+fn best_chord_match<R: Ord + Default>(
+    pc_weights: &[f32; 12],
+    rank: impl Fn(Chord) -> R,
+) -> (Chord, f32) {
     let mut best_score = 0.0;
+    let mut best_rank = R::default();
     let mut best = Chord::new(PitchClass::new(0).unwrap(), ChordKindId::Maj, None).unwrap();
 
     for root_pc in 0..12 {
@@ -87,8 +117,7 @@ fn best_chord_match(pc_weights: &[f32; 12]) -> (Chord, f32) {
             // template: 1.0 for chord tones, 0.0 for others.
             let mut chord_sum = 0.0;
             let mut total = 0.0;
-            for pc in 0..12 {
-                let weight = pc_weights[pc];
+            for (pc, &weight) in pc_weights.iter().enumerate() {
                 if weight <= 0.0 {
                     continue;
                 }
@@ -102,9 +131,13 @@ fn best_chord_match(pc_weights: &[f32; 12]) -> (Chord, f32) {
             }
             if total > 0.0 {
                 let score = chord_sum / total;
-                if score > best_score {
+                let candidate = Chord::new(root, kind.id, None).unwrap();
+                let rank = rank(candidate);
+                let tied = (score - best_score).abs() <= f32::EPSILON;
+                if (score > best_score && !tied) || (tied && rank > best_rank) {
                     best_score = score;
-                    best = Chord::new(root, kind.id, None).unwrap();
+                    best_rank = rank;
+                    best = candidate;
                 }
             }
         }
@@ -113,6 +146,29 @@ fn best_chord_match(pc_weights: &[f32; 12]) -> (Chord, f32) {
     (best, best_score)
 }
 
+/// 2 = bass is the root, 1 = bass is another chord tone, 0 = otherwise.
+fn bass_rank(chord: Chord, bass: Option<PitchClass>) -> u8 {
+    match bass {
+        Some(b) if b == chord.root => 2,
+        Some(b) if chord.contains(b) => 1,
+        _ => 0,
+    }
+}
+
+/// Prefers templates with fewer missing tones, then more sounding tones.
+fn template_fit_rank(chord: Chord, pc_weights: &[f32; 12]) -> (Reverse<u8>, u8) {
+    let mut missing = 0;
+    let mut covered = 0;
+    for iv in chord_intervals(chord.kind) {
+        if pc_weights[chord.root.transpose(*iv as i8).as_u8() as usize] > 0.0 {
+            covered += 1;
+        } else {
+            missing += 1;
+        }
+    }
+    (Reverse(missing), covered)
+}
+
 fn merge_adjacent_same_chords(mut chords: Vec<ChordEvent>) -> Vec<ChordEvent> {
     if chords.is_empty() {
         return chords;
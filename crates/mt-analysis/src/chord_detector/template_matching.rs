@@ -5,8 +5,8 @@
 
 use std::vec::Vec;
 
-use crate::config::ChordConfig;
 use crate::chord_detector::rule_based::RuleBasedChordAnalyzer;
+use crate::config::ChordConfig;
 use crate::traits::ChordAnalyzer;
 use mt_core::events::{ChordEvent, NoteEvent};
 
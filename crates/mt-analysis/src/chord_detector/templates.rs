@@ -0,0 +1,106 @@
+//! Shared chord-template scoring over pitch-class vectors.
+//!
+//! The chord state space is every `CHORD_KINDS` entry on every root:
+//! `state = root * CHORD_KINDS.len() + kind_index`. Frame-based detectors
+//! (HMM, chroma) score all states per frame with these helpers.
+
+use mt_core::chord::Chord;
+use mt_core::chord_kind::CHORD_KINDS;
+use mt_core::events::NoteEvent;
+use mt_core::pitch::PitchClass;
+
+/// Number of chord states (12 roots × all chord kinds).
+pub const CHORD_STATES: usize = 12 * CHORD_KINDS.len();
+
+/// Chord (root position) for a state index.
+#[must_use]
+pub fn state_chord(state: usize) -> Chord {
+    let kinds = CHORD_KINDS.len();
+    Chord {
+        root: PitchClass::from_unchecked((state / kinds % 12) as u8),
+        kind: CHORD_KINDS[state % kinds].id,
+        bass: None,
+    }
+}
+
+/// State index of a chord (bass is ignored).
+#[must_use]
+pub fn chord_state(chord: &Chord) -> Option<usize> {
+    let kind = CHORD_KINDS.iter().position(|k| k.id == chord.kind)?;
+    Some(chord.root.as_u8() as usize * CHORD_KINDS.len() + kind)
+}
+
+/// Binary pitch-class template for a state.
+#[must_use]
+pub fn state_template(state: usize) -> [f32; 12] {
+    let kinds = CHORD_KINDS.len();
+    let root = PitchClass::from_unchecked((state / kinds % 12) as u8);
+    let mut t = [0.0_f32; 12];
+    for iv in CHORD_KINDS[state % kinds].intervals {
+        t[root.transpose(*iv as i8).as_u8() as usize] = 1.0;
+    }
+    t
+}
+
+/// Pitch-class template for a state including upper partials.
+///
+/// Each chord tone contributes `decay^(h-1)` at the pitch-class of its
+/// `h`-th harmonic (h = 1..=partials), matching what chroma "hears".
+#[must_use]
+pub fn harmonic_state_template(state: usize, partials: u8, decay: f32) -> [f32; 12] {
+    let base = state_template(state);
+    let mut t = [0.0_f32; 12];
+    for (pc, _) in base.iter().enumerate().filter(|(_, w)| **w > 0.0) {
+        let mut weight = 1.0;
+        for h in 1..=partials {
+            let semitones = (12.0 * f32::from(h).log2()).round() as usize;
+            t[(pc + semitones) % 12] += weight;
+            weight *= decay;
+        }
+    }
+    t
+}
+
+/// Cosine similarity between a pitch-class vector and every state template.
+///
+/// Writes one value in [0, 1] per state; all zeros if `pc_weights` is silent.
+pub fn template_similarities(pc_weights: &[f32; 12], out: &mut [f32; CHORD_STATES]) {
+    similarities_with(pc_weights, state_template, out);
+}
+
+/// Like `template_similarities`, against arbitrary per-state templates.
+pub fn similarities_with(
+    pc_weights: &[f32; 12],
+    template: impl Fn(usize) -> [f32; 12],
+    out: &mut [f32; CHORD_STATES],
+) {
+    let norm = pc_weights.iter().map(|w| w * w).sum::<f32>().sqrt();
+    for (state, slot) in out.iter_mut().enumerate() {
+        if norm <= 0.0 {
+            *slot = 0.0;
+            continue;
+        }
+        let t = template(state);
+        let t_norm = t.iter().map(|v| v * v).sum::<f32>().sqrt();
+        let dot = pc_weights.iter().zip(t.iter()).map(|(w, t)| w * t).sum::<f32>();
+        *slot = dot / (norm * t_norm);
+    }
+}
+
+/// Duration-weighted pitch-classes of notes sounding in `[start, end)`.
+#[must_use]
+pub fn pitch_class_weights<'a>(
+    notes: impl IntoIterator<Item = &'a NoteEvent>,
+    start: i64,
+    end: i64,
+) -> [f32; 12] {
+    let mut pc_weights = [0.0_f32; 12];
+    for n in notes {
+        let is = n.onset.value().max(start);
+        let ie = n.offset.value().min(end);
+        if ie > is {
+            pc_weights[n.note.pitch_class().as_u8() as usize] += (ie - is) as f32;
+        }
+    }
+    pc_weights
+}
@@ -0,0 +1,47 @@
+//! Audio → chroma frames.
+//!
+//! Strategy:
+//! - Hann-windowed frames at `ChromaConfig::hop_size`.
+//! - Goertzel semitone filterbank folded to 12 pitch-classes
+//!   (`mt_signal_core::chroma`).
+//! - Magnitudes are max-normalized and log-compressed so quiet chord tones
+//!   still register; silent frames stay all-zero.
+
+use crate::config::ChromaConfig;
+use mt_alloc::FeatureBuffer;
+use mt_signal_core::chroma::chroma_frame;
+use mt_signal_core::window::{WindowKind, fill_window};
+
+/// Log-compression strength applied to normalized chroma.
+const COMPRESSION: f32 = 1.0;
+
+/// Chroma matrix `[frames x 12]`; frame `i` starts at sample `i * hop_size`.
+#[must_use]
+pub fn chroma_frames(samples: &[f32], sample_rate: u32, cfg: &ChromaConfig) -> FeatureBuffer {
+    let frame = cfg.frame_size.max(1);
+    let hop = cfg.hop_size.max(1);
+    let count = if samples.len() < frame { 0 } else { (samples.len() - frame) / hop + 1 };
+    let mut out = FeatureBuffer::with_capacity(12, count);
+
+    let mut window = vec![0.0_f32; frame];
+    fill_window(WindowKind::Hann, &mut window);
+
+    let mut chroma = [0.0_f32; 12];
+    for i in 0..count {
+        let slice = &samples[i * hop..i * hop + frame];
+        let rms = (slice.iter().map(|x| x * x).sum::<f32>() / frame as f32).sqrt();
+        if rms < cfg.rms_threshold {
+            out.push_frame(&[0.0; 12]);
+            continue;
+        }
+        chroma_frame(slice, &window, sample_rate, cfg.min_midi, cfg.max_midi, &mut chroma);
+        let max = chroma.iter().fold(0.0_f32, |m, c| m.max(c.sqrt()));
+        if max > 0.0 {
+            for c in &mut chroma {
+                *c = (1.0 + COMPRESSION * c.sqrt() / max).ln() / (1.0 + COMPRESSION).ln();
+            }
+        }
+        out.push_frame(&chroma);
+    }
+    out
+}
@@ -4,20 +4,29 @@
 //! instead of ad-hoc knobs.
 
 use crate::traits::TempoRange;
+use mt_core::pedal::PedalConfig;
 
 /// Global analysis config, grouping per-module configs.
 ///
 /// This type is designed to be:
 /// - serializable (with `serde`)
 /// - stable across versions (breaking changes bump major).
+///
+/// Omitted sections deserialize to their defaults.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, Debug)]
 pub struct AnalysisConfig {
     pub tempo: TempoConfig,
     pub key: KeyConfig,
+    pub tension: TensionConfig,
     pub chord: ChordConfig,
+    pub chroma: ChromaConfig,
+    pub mfcc: MfccConfig,
     pub swing: SwingConfig,
     pub segment: SegmentConfig,
+    pub phrase: PhraseConfig,
+    pub cadence: CadenceConfig,
     pub midi: MidiNoteConfig,
     pub audio_note: AudioNoteConfig,
 }
@@ -27,9 +36,14 @@ impl Default for AnalysisConfig {
         Self {
             tempo: TempoConfig::default(),
             key: KeyConfig::default(),
+            tension: TensionConfig::default(),
             chord: ChordConfig::default(),
+            chroma: ChromaConfig::default(),
+            mfcc: MfccConfig::default(),
             swing: SwingConfig::default(),
             segment: SegmentConfig::default(),
+            phrase: PhraseConfig::default(),
+            cadence: CadenceConfig::default(),
             midi: MidiNoteConfig::default(),
             audio_note: AudioNoteConfig::default(),
         }
@@ -60,18 +74,98 @@ impl Default for TempoConfig {
     }
 }
 
+/// Key-profile pair (major + minor) used to score pitch-class histograms.
+///
+/// Built-in variants reproduce the published tables verbatim; `Custom`
+/// lets callers supply their own (index 0 = tonic).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyProfile {
+    /// Krumhansl & Kessler (1982) probe-tone ratings.
+    KrumhanslKessler,
+    /// Temperley (2007) Kostka–Payne corpus profile.
+    Temperley,
+    /// Aarden (2003) Essen folksong corpus profile.
+    AardenEssen,
+    /// Albrecht & Shanahan (2013) corpus profile.
+    AlbrechtShanahan,
+    /// Bellman (2005) / Budge (1943) chord-frequency profile.
+    BellmanBudge,
+    /// User-supplied profile pair.
+    Custom { major: [f32; 12], minor: [f32; 12] },
+}
+
+/// How a histogram is compared against a rotated key profile.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyCorrelation {
+    /// Plain dot product (unnormalized).
+    DotProduct,
+    /// Pearson correlation coefficient (Krumhansl–Schmuckler).
+    Pearson,
+}
+
+/// How each note contributes to the pitch-class histogram.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyWeighting {
+    /// Every note counts once.
+    Count,
+    /// Weight by sounding duration.
+    Duration,
+    /// Weight by duration × velocity / 127.
+    DurationVelocity,
+    /// Duration × velocity, with notes that are the lowest sounding pitch at
+    /// their onset multiplied by `bass_weight`.
+    DurationVelocityBass { bass_weight: f32 },
+}
+
 /// Key detection configuration.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug)]
 pub struct KeyConfig {
     /// Minimum duration (in seconds) per key region for it to be emitted.
     pub min_region_seconds: f32,
+    /// Major/minor profile pair to correlate against.
+    pub profile: KeyProfile,
+    /// Scoring method between histogram and profile.
+    pub correlation: KeyCorrelation,
+    /// Histogram weighting mode.
+    pub weighting: KeyWeighting,
+    /// Sample rate note positions are expressed in.
+    pub sample_rate: u32,
 }
 
 impl Default for KeyConfig {
     fn default() -> Self {
         Self {
             min_region_seconds: 4.0,
+            profile: KeyProfile::KrumhanslKessler,
+            correlation: KeyCorrelation::DotProduct,
+            weighting: KeyWeighting::Duration,
+            sample_rate: 44_100,
+        }
+    }
+}
+
+/// Tonal tension (Spiral Array) configuration.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug)]
+pub struct TensionConfig {
+    /// Slice length in seconds over which each tension sample is measured.
+    pub window_seconds: f32,
+    /// Hop between slices in seconds.
+    pub hop_seconds: f32,
+    /// Sample rate note and chord positions are expressed in.
+    pub sample_rate: u32,
+}
+
+impl Default for TensionConfig {
+    fn default() -> Self {
+        Self {
+            window_seconds: 1.0,
+            hop_seconds: 0.5,
+            sample_rate: 44_100,
         }
     }
 }
@@ -86,6 +180,29 @@ pub struct ChordConfig {
     pub hop_seconds: f32,
     /// Minimum confidence to emit chord events (0..1).
     pub min_confidence: f32,
+    /// HMM decoding: probability of staying on the same chord between frames.
+    pub self_transition: f32,
+    /// HMM decoding: strength of the key-conditioned chord prior (0 = off).
+    pub key_prior_weight: f32,
+    /// Which bass notes may be attached to detected chords.
+    pub bass_mode: ChordBassMode,
+    /// Minimum share of the window's sounding time the lowest pitch-class
+    /// must hold before it is reported as the bass (0..1).
+    pub min_bass_share: f32,
+    /// Sample rate note positions are expressed in.
+    pub sample_rate: u32,
+}
+
+/// Bass labelling for detected chords.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChordBassMode {
+    /// Root-position labels only; register is ignored.
+    RootPosition,
+    /// Chord-tone bass notes become inversions (C/E).
+    Inversions,
+    /// Inversions plus non-chord-tone bass notes (C/D).
+    SlashChords,
 }
 
 impl Default for ChordConfig {
@@ -94,6 +211,70 @@ impl Default for ChordConfig {
             window_seconds: 1.0,
             hop_seconds: 0.5,
             min_confidence: 0.2,
+            self_transition: 0.9,
+            key_prior_weight: 2.0,
+            bass_mode: ChordBassMode::RootPosition,
+            min_bass_share: 0.6,
+            sample_rate: 44_100,
+        }
+    }
+}
+
+/// Audio -> chroma extraction (pitch-class energy per frame).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug)]
+pub struct ChromaConfig {
+    /// Frame size in samples.
+    pub frame_size: usize,
+    /// Hop size in samples.
+    pub hop_size: usize,
+    /// Lowest MIDI note in the filterbank.
+    pub min_midi: u8,
+    /// Highest MIDI note in the filterbank.
+    pub max_midi: u8,
+    /// Frames below this RMS are treated as silence.
+    pub rms_threshold: f32,
+}
+
+impl Default for ChromaConfig {
+    fn default() -> Self {
+        Self {
+            frame_size: 8192,
+            hop_size: 4096,
+            min_midi: 36,
+            max_midi: 95,
+            rms_threshold: 0.01,
+        }
+    }
+}
+
+/// Audio -> MFCC extraction (spectral envelope, i.e. timbre, per frame).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug)]
+pub struct MfccConfig {
+    /// Frame size in samples.
+    pub frame_size: usize,
+    /// Hop size in samples.
+    pub hop_size: usize,
+    /// Coefficients per frame including c0 (at most 26).
+    pub coefficients: usize,
+    /// Lower edge of the mel filterbank in Hz.
+    pub min_hz: f32,
+    /// Upper edge of the mel filterbank in Hz (clamped to Nyquist).
+    pub max_hz: f32,
+    /// Frames below this RMS are treated as silence.
+    pub rms_threshold: f32,
+}
+
+impl Default for MfccConfig {
+    fn default() -> Self {
+        Self {
+            frame_size: 2048,
+            hop_size: 1024,
+            coefficients: 13,
+            min_hz: 60.0,
+            max_hz: 8000.0,
+            rms_threshold: 0.01,
         }
     }
 }
@@ -120,6 +301,19 @@ pub struct SegmentConfig {
     pub window_seconds: f32,
     /// Minimum segment length in seconds.
     pub min_segment_seconds: f32,
+    /// Self-similarity: feature frame hop in seconds.
+    pub ssm_hop_seconds: f32,
+    /// Self-similarity: features compared between frames.
+    pub ssm_features: SsmFeatures,
+    /// Self-similarity: checkerboard kernel width in seconds.
+    pub kernel_seconds: f32,
+    /// Boundaries need novelty this far above its local mean (0..1, relative
+    /// to the maximum).
+    pub novelty_threshold: f32,
+    /// Segments at least this similar repeat each other (0..1).
+    pub repeat_threshold: f32,
+    /// Repeats below this similarity are labelled as variants (A') (0..1).
+    pub exact_repeat_threshold: f32,
 }
 
 impl Default for SegmentConfig {
@@ -127,6 +321,123 @@ impl Default for SegmentConfig {
         Self {
             window_seconds: 3.0,
             min_segment_seconds: 8.0,
+            ssm_hop_seconds: 0.5,
+            ssm_features: SsmFeatures::ChromaAndChords,
+            kernel_seconds: 8.0,
+            novelty_threshold: 0.1,
+            repeat_threshold: 0.75,
+            exact_repeat_threshold: 0.9,
+        }
+    }
+}
+
+/// Feature sources for self-similarity segmentation.
+///
+/// Sources without input (no samples, no chords) are skipped.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SsmFeatures {
+    /// Chroma from audio.
+    Chroma,
+    /// MFCCs from audio (timbre; c0 is dropped so loudness does not count).
+    Mfcc,
+    /// Chord templates from the chord timeline.
+    Chords,
+    /// Chroma and chords, concatenated with equal weight.
+    ChromaAndChords,
+    /// Chroma, MFCCs and chords, concatenated with equal weight.
+    All,
+}
+
+impl SsmFeatures {
+    #[must_use]
+    pub const fn uses_chroma(self) -> bool {
+        matches!(self, Self::Chroma | Self::ChromaAndChords | Self::All)
+    }
+
+    #[must_use]
+    pub const fn uses_mfcc(self) -> bool {
+        matches!(self, Self::Mfcc | Self::All)
+    }
+
+    #[must_use]
+    pub const fn uses_chords(self) -> bool {
+        matches!(self, Self::Chords | Self::ChromaAndChords | Self::All)
+    }
+}
+
+/// Phrase and sub-phrase segmentation inside sections.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug)]
+pub struct PhraseConfig {
+    /// Expected phrase length in bars (hypermeter); sub-phrases use half.
+    pub phrase_bars: u8,
+    /// Minimum phrase length in beats.
+    pub min_phrase_beats: f32,
+    /// Minimum sub-phrase length in beats.
+    pub min_sub_phrase_beats: f32,
+    /// Phrase boundaries need at least this cue score (0..1).
+    pub boundary_threshold: f32,
+    /// Sub-phrase boundaries need at least this cue score (0..1).
+    pub sub_boundary_threshold: f32,
+    /// MIDI cue weight: rest in the melody before the boundary.
+    pub rest_weight: f32,
+    /// MIDI cue weight: long melody note before the boundary.
+    pub long_note_weight: f32,
+    /// MIDI cue weight: cadential chord motion before the boundary.
+    pub cadence_weight: f32,
+    /// MIDI cue weight: boundary on a (hyper)metric downbeat.
+    pub metric_weight: f32,
+    /// A melody note this many times the median inter-onset interval
+    /// counts as fully long.
+    pub long_note_ratio: f32,
+    /// Audio: novelty kernel width for phrases in seconds.
+    pub phrase_kernel_seconds: f32,
+    /// Audio: novelty kernel width for sub-phrases in seconds.
+    pub sub_phrase_kernel_seconds: f32,
+}
+
+impl Default for PhraseConfig {
+    fn default() -> Self {
+        Self {
+            phrase_bars: 4,
+            min_phrase_beats: 12.0,
+            min_sub_phrase_beats: 4.0,
+            boundary_threshold: 0.4,
+            sub_boundary_threshold: 0.3,
+            rest_weight: 1.0,
+            long_note_weight: 0.5,
+            cadence_weight: 1.0,
+            metric_weight: 1.0,
+            long_note_ratio: 2.0,
+            phrase_kernel_seconds: 4.0,
+            sub_phrase_kernel_seconds: 2.0,
+        }
+    }
+}
+
+/// Cadence detection over the chord timeline.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug)]
+pub struct CadenceConfig {
+    /// Arrival chords held at least this many beats count as fully
+    /// cadential; shorter ones score lower.
+    pub min_arrival_beats: f32,
+    /// Metric weight of arrivals on the middle of the bar (1 on downbeats).
+    pub mid_bar_weight: f32,
+    /// Metric weight of arrivals on other beats.
+    pub offbeat_weight: f32,
+    /// Minimum confidence to emit cadence events (0..1).
+    pub min_confidence: f32,
+}
+
+impl Default for CadenceConfig {
+    fn default() -> Self {
+        Self {
+            min_arrival_beats: 2.0,
+            mid_bar_weight: 0.6,
+            offbeat_weight: 0.3,
+            min_confidence: 0.2,
         }
     }
 }
@@ -137,12 +448,15 @@ impl Default for SegmentConfig {
 pub struct MidiNoteConfig {
     /// Maximum gap in samples to treat overlapping note-ons as retriggers.
     pub retrigger_tolerance_samples: i64,
+    /// Sustain/sostenuto handling and sounding vs key-release durations.
+    pub pedal: PedalConfig,
 }
 
 impl Default for MidiNoteConfig {
     fn default() -> Self {
         Self {
             retrigger_tolerance_samples: 0,
+            pedal: PedalConfig::default(),
         }
     }
 }
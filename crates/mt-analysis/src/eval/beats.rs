@@ -0,0 +1,57 @@
+//! Beat tracking F-measure.
+//!
+//! Rules:
+//! - Beats before `BEAT_SKIP_MS` are dropped on both sides, as tempo is
+//!   often not established yet (the MIREX convention).
+//! - A hit pairs one reference and one estimated beat at most
+//!   `BEAT_WINDOW_MS` apart, each used once.
+//! - Estimated beats can come from a tempo map: one per beat (quarter
+//!   note) from position 0.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use mt_alloc::TempoMap;
+use mt_core::time::SampleTime;
+
+use super::{FMeasure, matched_count, ms_to_samples};
+
+/// Tolerance either side of a reference beat, in milliseconds.
+pub const BEAT_WINDOW_MS: u32 = 70;
+
+/// Beats before this time are ignored, in milliseconds.
+pub const BEAT_SKIP_MS: u32 = 5000;
+
+/// Beat F-measure of `estimate` against `reference`.
+pub fn beat_f_measure(
+    reference: &[SampleTime],
+    estimate: &[SampleTime],
+    sample_rate: u32,
+) -> FMeasure {
+    let skip = ms_to_samples(BEAT_SKIP_MS, sample_rate);
+    let trimmed = |beats: &[SampleTime]| {
+        let mut out: Vec<i64> = beats.iter().map(|b| b.value()).filter(|&b| b >= skip).collect();
+        out.sort_unstable();
+        out.dedup();
+        out
+    };
+    let reference = trimmed(reference);
+    let estimate = trimmed(estimate);
+    let window = ms_to_samples(BEAT_WINDOW_MS, sample_rate);
+    let hits = matched_count(&reference, &estimate, window);
+    FMeasure::from_counts(hits, estimate.len(), reference.len())
+}
+
+/// Beat positions of `map` before `end`.
+pub fn beats_from_tempo_map(map: &TempoMap, end: SampleTime) -> Vec<SampleTime> {
+    let mut beats = Vec::new();
+    for beat in 0.. {
+        let at = map.beats_to_sample(beat, 1);
+        if at >= end {
+            break;
+        }
+        beats.push(at);
+    }
+    beats
+}
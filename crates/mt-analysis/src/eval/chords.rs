@@ -0,0 +1,215 @@
+//! Weighted chord symbol recall (WCSR) and chord segmentation quality.
+//!
+//! Rules:
+//! - The evaluated span runs from 0 to the last reference chord offset;
+//!   estimated chords beyond it are ignored. Time without a chord on either
+//!   side counts as `N`.
+//! - Each vocabulary maps reference chords it cannot express to "excluded":
+//!   that time counts neither as a hit nor towards the total.
+//!   - `root`: roots match; nothing is excluded.
+//!   - `majmin`: roots and the major/minor triad match; chords whose triad
+//!     is neither (dim, aug, sus, power) are excluded.
+//!   - `majmin_inv`: as `majmin`, and the bass matches; basses outside the
+//!     triad are excluded.
+//!   - `sevenths`: root and kind match; kinds other than maj, min, maj7, 7
+//!     and min7 are excluded.
+//!   - `tetrads`: root and kind match; nothing is excluded.
+//! - `N` matches only `N`, and is never excluded.
+//! - Segmentation quality is `1 - max(over, under)`, the larger of the two
+//!   directional Hamming distances between the chord segmentations,
+//!   normalized by the span.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use mt_core::{chord::Chord, chord_kind::ChordKindId, events::ChordEvent, time::SampleTime};
+
+use super::Ratio;
+
+/// MIREX chord vocabulary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Vocabulary {
+    Root,
+    MajMin,
+    MajMinInv,
+    Sevenths,
+    Tetrads,
+}
+
+impl Vocabulary {
+    pub const ALL: [Self; 5] =
+        [Self::Root, Self::MajMin, Self::MajMinInv, Self::Sevenths, Self::Tetrads];
+
+    /// MIREX name, as used in result tables.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Root => "root",
+            Self::MajMin => "majmin",
+            Self::MajMinInv => "majmin_inv",
+            Self::Sevenths => "sevenths",
+            Self::Tetrads => "tetrads",
+        }
+    }
+
+    /// Whether `estimate` matches `reference`; `None` if the reference
+    /// chord is excluded from this vocabulary.
+    pub fn matches(self, reference: Option<&Chord>, estimate: Option<&Chord>) -> Option<bool> {
+        let Some(reference) = reference else {
+            return Some(estimate.is_none());
+        };
+        let triad = triad(reference.kind);
+        let valid = match self {
+            Self::Root | Self::Tetrads => true,
+            Self::MajMin => triad.is_some(),
+            Self::MajMinInv => triad.is_some_and(|t| t.contains(&bass_interval(*reference))),
+            Self::Sevenths => matches!(
+                reference.kind,
+                ChordKindId::Maj
+                    | ChordKindId::Min
+                    | ChordKindId::Maj7
+                    | ChordKindId::Dom7
+                    | ChordKindId::Min7
+            ),
+        };
+        if !valid {
+            return None;
+        }
+        let Some(estimate) = estimate else {
+            return Some(false);
+        };
+        if estimate.root != reference.root {
+            return Some(false);
+        }
+        Some(match self {
+            Self::Root => true,
+            Self::MajMin => triad == self::triad(estimate.kind),
+            Self::MajMinInv => {
+                triad == self::triad(estimate.kind)
+                    && bass_interval(*reference) == bass_interval(*estimate)
+            }
+            Self::Sevenths | Self::Tetrads => estimate.kind == reference.kind,
+        })
+    }
+}
+
+/// Recall per vocabulary plus segmentation quality, in samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ChordScores {
+    /// Indexed in `Vocabulary::ALL` order.
+    pub recall: [Ratio; 5],
+    pub segmentation: Ratio,
+}
+
+impl ChordScores {
+    pub fn recall(&self, vocabulary: Vocabulary) -> Ratio {
+        self.recall[vocabulary as usize]
+    }
+
+    /// Pool `other` into `self`.
+    pub fn merge(&mut self, other: &Self) {
+        for (mine, theirs) in self.recall.iter_mut().zip(other.recall) {
+            mine.merge(theirs);
+        }
+        self.segmentation.merge(other.segmentation);
+    }
+}
+
+/// Score estimated chords against reference chords.
+pub fn chord_scores(reference: &[ChordEvent], estimate: &[ChordEvent]) -> ChordScores {
+    let end = reference.iter().map(|c| c.offset).max().unwrap_or(SampleTime::ZERO);
+    let reference = sorted(reference);
+    let estimate = sorted(estimate);
+    let reference_cuts = cuts(&reference, end);
+    let estimate_cuts = cuts(&estimate, end);
+
+    let mut scores = ChordScores::default();
+    let mut all = reference_cuts.clone();
+    all.extend_from_slice(&estimate_cuts);
+    all.sort_unstable();
+    all.dedup();
+    for pair in all.windows(2) {
+        let (start, duration) = (pair[0], pair[1] - pair[0]);
+        let r = chord_at(&reference, start);
+        let e = chord_at(&estimate, start);
+        for (ratio, vocabulary) in scores.recall.iter_mut().zip(Vocabulary::ALL) {
+            if let Some(hit) = vocabulary.matches(r, e) {
+                ratio.total += duration;
+                if hit {
+                    ratio.hits += duration;
+                }
+            }
+        }
+    }
+
+    let span = end.value().max(0);
+    let over = directional_hamming(&reference_cuts, &estimate_cuts);
+    let under = directional_hamming(&estimate_cuts, &reference_cuts);
+    scores.segmentation = Ratio { hits: span - over.max(under), total: span };
+    scores
+}
+
+/// Major or minor triad intervals of a kind, if it has one.
+fn triad(kind: ChordKindId) -> Option<[u8; 3]> {
+    match kind {
+        ChordKindId::Maj
+        | ChordKindId::Maj7
+        | ChordKindId::Dom7
+        | ChordKindId::Maj6
+        | ChordKindId::SixNine => Some([0, 4, 7]),
+        ChordKindId::Min | ChordKindId::Min7 | ChordKindId::Min6 => Some([0, 3, 7]),
+        _ => None,
+    }
+}
+
+/// Semitones from root up to the bass; 0 in root position.
+fn bass_interval(chord: Chord) -> u8 {
+    chord.bass.map_or(0, |b| (b.as_u8() + 12 - chord.root.as_u8()) % 12)
+}
+
+fn sorted(events: &[ChordEvent]) -> Vec<ChordEvent> {
+    let mut events = events.to_vec();
+    events.sort_by_key(|c| c.onset);
+    events
+}
+
+/// Every onset and offset within `[0, end]`, plus both ends, sorted.
+fn cuts(events: &[ChordEvent], end: SampleTime) -> Vec<i64> {
+    let end = end.value().max(0);
+    let mut cuts = Vec::with_capacity(events.len() * 2 + 2);
+    cuts.push(0);
+    cuts.push(end);
+    for c in events {
+        cuts.push(c.onset.value().clamp(0, end));
+        cuts.push(c.offset.value().clamp(0, end));
+    }
+    cuts.sort_unstable();
+    cuts.dedup();
+    cuts
+}
+
+/// Chord of the latest event starting at or before `at`, if it still
+/// sounds.
+fn chord_at(events: &[ChordEvent], at: i64) -> Option<&Chord> {
+    let started = events.partition_point(|c| c.onset.value() <= at);
+    let latest = events[..started].last()?;
+    (latest.offset.value() > at).then_some(&latest.chord)
+}
+
+/// Sum over the segments of `a` of the part not covered by the largest
+/// piece `b`'s cuts leave of it.
+fn directional_hamming(a: &[i64], b: &[i64]) -> i64 {
+    let mut distance = 0;
+    for pair in a.windows(2) {
+        let (start, stop) = (pair[0], pair[1]);
+        let first = b.partition_point(|&t| t <= start);
+        let mut previous = start;
+        let mut largest = 0;
+        for &cut in b[first..].iter().take_while(|&&t| t < stop).chain([&stop]) {
+            largest = largest.max(cut - previous);
+            previous = cut;
+        }
+        distance += stop - start - largest;
+    }
+    distance
+}
@@ -0,0 +1,44 @@
+//! MIREX weighted key score.
+//!
+//! Rules:
+//! - Each side is reduced to one global key: the key held longest, the
+//!   earlier one on ties.
+//! - Same key → 1.0; estimate a fifth above in the same mode → 0.5;
+//!   relative major/minor → 0.3; parallel major/minor → 0.2; else 0.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use mt_core::{
+    events::KeyEvent,
+    key::{Key, KeyMode},
+    time::SampleTime,
+};
+
+/// Key held longest before `end`; each key lasts until the next one.
+pub fn global_key(keys: &[KeyEvent], end: SampleTime) -> Option<Key> {
+    let mut keys: Vec<KeyEvent> = keys.to_vec();
+    keys.sort_by_key(|k| k.position);
+    let next = keys.iter().skip(1).map(|k| k.position).chain([end]);
+    let mut longest: Option<(i64, Key)> = None;
+    for (k, stop) in keys.iter().zip(next) {
+        let duration = stop.value() - k.position.value();
+        if longest.is_none_or(|(best, _)| duration > best) {
+            longest = Some((duration, k.key));
+        }
+    }
+    longest.map(|(_, key)| key)
+}
+
+/// Weighted score of `estimate` against `reference`, in [0, 1].
+pub fn weighted_key_score(reference: Key, estimate: Key) -> f32 {
+    let up = (estimate.tonic().as_u8() + 12 - reference.tonic().as_u8()) % 12;
+    match (reference.mode(), estimate.mode(), up) {
+        (r, e, 0) if r == e => 1.0,
+        (r, e, 7) if r == e => 0.5,
+        (KeyMode::Major, KeyMode::Minor, 9) | (KeyMode::Minor, KeyMode::Major, 3) => 0.3,
+        (_, _, 0) => 0.2,
+        _ => 0.0,
+    }
+}
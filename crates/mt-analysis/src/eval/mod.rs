@@ -0,0 +1,238 @@
+//! Evaluation of analysis output against reference annotations.
+//!
+//! Implements the MIREX measures behind the release quality gates (chord
+//! WCSR, key score) plus the usual structure and beat measures, so a
+//! dataset run produces the same numbers as the public benchmarks.
+//!
+//! Rules:
+//! - Both sides are `Annotations` at one sample rate; the reference decides
+//!   which measures apply (no reference chords → no chord scores).
+//! - Chord recall and segmentation are pooled by duration across files;
+//!   every other measure is averaged per file.
+//! - Durations are counted in whole samples, so a file's scores do not
+//!   depend on frame sizes; only pairwise clustering samples frames.
+
+pub mod beats;
+pub mod chords;
+pub mod keys;
+pub mod segments;
+
+use mt_formats::Annotations;
+
+pub use beats::{BEAT_SKIP_MS, BEAT_WINDOW_MS, beat_f_measure, beats_from_tempo_map};
+pub use chords::{ChordScores, Vocabulary, chord_scores};
+pub use keys::{global_key, weighted_key_score};
+pub use segments::{PAIRWISE_FRAME_MS, boundary_detection, pairwise_clustering};
+
+/// Release gate for weighted chord symbol recall (majmin vocabulary).
+pub const WCSR_GATE: f32 = 0.80;
+
+/// Release gate for the mean weighted key score.
+pub const KEY_GATE: f32 = 0.90;
+
+/// Hit-rate windows for segment boundaries, in milliseconds.
+pub const BOUNDARY_WINDOWS_MS: [u32; 2] = [500, 3000];
+
+/// Matched duration (or count) over the total it is measured against.
+///
+/// Kept as integers so files can be pooled before dividing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Ratio {
+    pub hits: i64,
+    pub total: i64,
+}
+
+impl Ratio {
+    /// `hits / total`; `None` for an empty total.
+    pub fn value(self) -> Option<f32> {
+        (self.total > 0).then(|| self.hits as f32 / self.total as f32)
+    }
+
+    /// Pool `other` into `self`.
+    pub fn merge(&mut self, other: Self) {
+        self.hits += other.hits;
+        self.total += other.total;
+    }
+}
+
+/// Precision, recall and their harmonic mean, all in [0, 1].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FMeasure {
+    pub precision: f32,
+    pub recall: f32,
+    pub f_measure: f32,
+}
+
+impl FMeasure {
+    /// From `hits` out of `estimated` and `reference` items.
+    ///
+    /// An empty side scores 0 rather than dividing by zero.
+    pub fn from_counts(hits: usize, estimated: usize, reference: usize) -> Self {
+        let ratio = |total: usize| if total == 0 { 0.0 } else { hits as f32 / total as f32 };
+        Self::from_precision_recall(ratio(estimated), ratio(reference))
+    }
+
+    pub fn from_precision_recall(precision: f32, recall: f32) -> Self {
+        let sum = precision + recall;
+        let f_measure = if sum > 0.0 { 2.0 * precision * recall / sum } else { 0.0 };
+        Self { precision, recall, f_measure }
+    }
+}
+
+/// Running mean of per-file values.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Mean {
+    sum: f32,
+    count: usize,
+}
+
+impl Mean {
+    pub fn push(&mut self, value: f32) {
+        self.sum += value;
+        self.count += 1;
+    }
+
+    /// Number of values pushed.
+    pub fn count(self) -> usize {
+        self.count
+    }
+
+    /// `None` until a value is pushed.
+    pub fn value(self) -> Option<f32> {
+        (self.count > 0).then(|| self.sum / self.count as f32)
+    }
+}
+
+/// Per-file mean of precision, recall and F-measure.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FMeasureMean {
+    pub precision: Mean,
+    pub recall: Mean,
+    pub f_measure: Mean,
+}
+
+impl FMeasureMean {
+    pub fn push(&mut self, value: FMeasure) {
+        self.precision.push(value.precision);
+        self.recall.push(value.recall);
+        self.f_measure.push(value.f_measure);
+    }
+
+    /// `None` until a value is pushed.
+    pub fn value(self) -> Option<FMeasure> {
+        Some(FMeasure {
+            precision: self.precision.value()?,
+            recall: self.recall.value()?,
+            f_measure: self.f_measure.value()?,
+        })
+    }
+}
+
+/// Scores of one file; `None` where the reference has no annotation of
+/// that type.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FileScores {
+    pub chords: Option<ChordScores>,
+    /// MIREX weighted score of the global keys.
+    pub key: Option<f32>,
+    /// Boundary hit rate at each of `BOUNDARY_WINDOWS_MS`.
+    pub boundaries: Option<[FMeasure; 2]>,
+    /// Pairwise frame clustering of segment labels.
+    pub pairwise: Option<FMeasure>,
+    pub beats: Option<FMeasure>,
+}
+
+/// Score `estimate` against `reference`.
+pub fn evaluate(reference: &Annotations, estimate: &Annotations, sample_rate: u32) -> FileScores {
+    let end = reference.end().max(estimate.end());
+    let key = global_key(&reference.keys, end).map(|reference_key| {
+        global_key(&estimate.keys, end).map_or(0.0, |k| weighted_key_score(reference_key, k))
+    });
+    let has_segments = !reference.segments.is_empty();
+    FileScores {
+        chords: (!reference.chords.is_empty())
+            .then(|| chord_scores(&reference.chords, &estimate.chords)),
+        key,
+        boundaries: has_segments.then(|| {
+            BOUNDARY_WINDOWS_MS.map(|ms| {
+                let window = ms_to_samples(ms, sample_rate);
+                boundary_detection(&reference.segments, &estimate.segments, window)
+            })
+        }),
+        pairwise: has_segments.then(|| {
+            let frame = ms_to_samples(PAIRWISE_FRAME_MS, sample_rate);
+            pairwise_clustering(&reference.segments, &estimate.segments, frame)
+        }),
+        beats: (!reference.beats.is_empty())
+            .then(|| beat_f_measure(&reference.beats, &estimate.beats, sample_rate)),
+    }
+}
+
+/// Scores pooled over a dataset.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Summary {
+    pub files: usize,
+    /// Chord recall and segmentation pooled by duration.
+    pub chords: ChordScores,
+    pub key: Mean,
+    pub boundaries: [FMeasureMean; 2],
+    pub pairwise: FMeasureMean,
+    pub beats: FMeasureMean,
+}
+
+impl Summary {
+    pub fn add(&mut self, scores: &FileScores) {
+        self.files += 1;
+        if let Some(chords) = scores.chords {
+            self.chords.merge(&chords);
+        }
+        if let Some(key) = scores.key {
+            self.key.push(key);
+        }
+        if let Some(boundaries) = scores.boundaries {
+            for (mean, value) in self.boundaries.iter_mut().zip(boundaries) {
+                mean.push(value);
+            }
+        }
+        if let Some(pairwise) = scores.pairwise {
+            self.pairwise.push(pairwise);
+        }
+        if let Some(beats) = scores.beats {
+            self.beats.push(beats);
+        }
+    }
+
+    /// Whether the pooled majmin WCSR and mean key score reach
+    /// `WCSR_GATE` and `KEY_GATE`; a measure without data fails.
+    pub fn passes_gates(&self) -> bool {
+        let wcsr = self.chords.recall(Vocabulary::MajMin).value();
+        wcsr.is_some_and(|v| v >= WCSR_GATE) && self.key.value().is_some_and(|v| v >= KEY_GATE)
+    }
+}
+
+/// Milliseconds → samples, rounded down.
+pub(crate) fn ms_to_samples(ms: u32, sample_rate: u32) -> i64 {
+    i64::from(ms) * i64::from(sample_rate) / 1000
+}
+
+/// Size of a maximum one-to-one matching of sorted `reference` and
+/// `estimate` times at most `window` apart.
+///
+/// On a line, pairing each point with the earliest unmatched partner in
+/// reach is optimal, so no bipartite matching is needed.
+pub(crate) fn matched_count(reference: &[i64], estimate: &[i64], window: i64) -> usize {
+    let (mut i, mut j, mut hits) = (0, 0, 0);
+    while i < reference.len() && j < estimate.len() {
+        let (r, e) = (reference[i], estimate[j]);
+        if (r - e).abs() <= window {
+            hits += 1;
+            i += 1;
+            j += 1;
+        } else if e < r {
+            j += 1;
+        } else {
+            i += 1;
+        }
+    }
+    hits
+}
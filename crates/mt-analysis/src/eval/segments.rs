@@ -0,0 +1,99 @@
+//! Structure measures: boundary hit rate and pairwise frame clustering.
+//!
+//! Rules:
+//! - Boundaries are every distinct segment onset and offset, first and
+//!   last included. A hit pairs one reference and one estimated boundary
+//!   at most a window apart, each used once.
+//! - Pairwise clustering samples both sides every `PAIRWISE_FRAME_MS` over
+//!   the reference span. Two frames agree when they carry the same section
+//!   kind and repetition label; frames outside every segment share an
+//!   empty label. Precision and recall compare the sets of agreeing frame
+//!   pairs.
+
+extern crate alloc;
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use mt_core::events::{SectionLabel, SegmentEvent, SegmentKind};
+
+use super::{FMeasure, matched_count};
+
+/// Frame hop of pairwise clustering, in milliseconds.
+pub const PAIRWISE_FRAME_MS: u32 = 100;
+
+/// Boundary hit rate with a window of `window` samples either side.
+pub fn boundary_detection(
+    reference: &[SegmentEvent],
+    estimate: &[SegmentEvent],
+    window: i64,
+) -> FMeasure {
+    let reference = boundaries(reference);
+    let estimate = boundaries(estimate);
+    let hits = matched_count(&reference, &estimate, window);
+    FMeasure::from_counts(hits, estimate.len(), reference.len())
+}
+
+/// Pairwise frame clustering with frames `frame` samples apart.
+pub fn pairwise_clustering(
+    reference: &[SegmentEvent],
+    estimate: &[SegmentEvent],
+    frame: i64,
+) -> FMeasure {
+    let start = reference.iter().map(|s| s.onset.value()).min().unwrap_or(0);
+    let end = reference.iter().map(|s| s.offset.value()).max().unwrap_or(0);
+    let frame = frame.max(1);
+
+    let mut labels: Vec<Option<(SegmentKind, Option<SectionLabel>)>> = Vec::new();
+    let mut id = |label| {
+        if let Some(i) = labels.iter().position(|&l| l == label) {
+            i
+        } else {
+            labels.push(label);
+            labels.len() - 1
+        }
+    };
+    // Frame counts per (reference label, estimated label).
+    let mut counts: BTreeMap<(usize, usize), u64> = BTreeMap::new();
+    let mut at = start;
+    while at < end {
+        let pair = (id(label_at(reference, at)), id(label_at(estimate, at)));
+        *counts.entry(pair).or_default() += 1;
+        at += frame;
+    }
+
+    let mut reference_sizes: BTreeMap<usize, u64> = BTreeMap::new();
+    let mut estimate_sizes: BTreeMap<usize, u64> = BTreeMap::new();
+    let mut both = 0;
+    for (&(r, e), &n) in &counts {
+        *reference_sizes.entry(r).or_default() += n;
+        *estimate_sizes.entry(e).or_default() += n;
+        both += pairs(n);
+    }
+    let reference_pairs = reference_sizes.values().map(|&n| pairs(n)).sum::<u64>();
+    let estimate_pairs = estimate_sizes.values().map(|&n| pairs(n)).sum::<u64>();
+    let ratio = |total: u64| if total == 0 { 0.0 } else { both as f32 / total as f32 };
+    FMeasure::from_precision_recall(ratio(estimate_pairs), ratio(reference_pairs))
+}
+
+/// Distinct onsets and offsets, sorted.
+fn boundaries(segments: &[SegmentEvent]) -> Vec<i64> {
+    let mut out: Vec<i64> =
+        segments.iter().flat_map(|s| [s.onset.value(), s.offset.value()]).collect();
+    out.sort_unstable();
+    out.dedup();
+    out
+}
+
+/// Label of the latest-starting segment covering `at`.
+fn label_at(segments: &[SegmentEvent], at: i64) -> Option<(SegmentKind, Option<SectionLabel>)> {
+    segments
+        .iter()
+        .filter(|s| s.onset.value() <= at && at < s.offset.value())
+        .max_by_key(|s| s.onset)
+        .map(|s| (s.kind, s.label))
+}
+
+/// Unordered pairs among `n` frames.
+fn pairs(n: u64) -> u64 {
+    n * n.saturating_sub(1) / 2
+}
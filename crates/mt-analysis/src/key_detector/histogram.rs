@@ -1,6 +1,14 @@
-//! Histogram-based key detection (Krumhansl-like, simplified).
+//! Histogram-based key detection (Krumhansl–Schmuckler style).
+//!
+//! The profile pair, correlation method and note weighting are selected via
+//! `KeyConfig` so published key-finding setups can be reproduced exactly.
 
-use crate::config::KeyConfig;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+
+use crate::confidence::clamp01_to_confidence_x1000;
+use crate::config::{KeyConfig, KeyCorrelation, KeyWeighting};
+use crate::key_detector::profiles::profile_pair;
 use crate::traits::KeyAnalyzer;
 use mt_core::events::{KeyEvent, NoteEvent};
 use mt_core::key::{Key, KeyMode};
@@ -16,15 +24,11 @@ impl KeyAnalyzer for HistogramKeyAnalyzer {
         }
 
         // Simple global histogram; can be extended to sliding windows later.
-        let mut hist = [0.0_f32; 12];
-        for n in notes {
-            let pc = n.note.pitch_class().as_u8() as usize;
-            let dur = (n.offset.value() - n.onset.value()).max(1) as f32;
-            hist[pc] += dur;
-        }
+        let hist = pitch_class_histogram(notes, cfg.weighting);
 
-        let (maj_tonic, maj_score) = best_key(&hist, KeyMode::Major);
-        let (min_tonic, min_score) = best_key(&hist, KeyMode::Minor);
+        let (major, minor) = profile_pair(&cfg.profile);
+        let (maj_tonic, maj_score) = best_key(&hist, major, cfg.correlation);
+        let (min_tonic, min_score) = best_key(&hist, minor, cfg.correlation);
 
         let (mode, tonic, score) = if maj_score >= min_score {
             (KeyMode::Major, maj_tonic, maj_score)
@@ -32,19 +36,22 @@ impl KeyAnalyzer for HistogramKeyAnalyzer {
             (KeyMode::Minor, min_tonic, min_score)
         };
 
-        let key = Key::new(PitchClass::new(tonic as u8).unwrap(), mode);
-        let min_samples = (cfg.min_region_seconds * 44_100.0) as i64; // assume; engine can override later.
+        let key = Key::new(PitchClass::new(tonic).unwrap(), mode);
+        let min_samples = (f64::from(cfg.min_region_seconds) * f64::from(cfg.sample_rate)) as i64;
 
-        let total_span = notes
-            .last()
-            .map(|n| n.offset.value() - notes[0].onset.value())
-            .unwrap_or(0);
+        let total_span = notes.last().map_or(0, |n| n.offset.value() - notes[0].onset.value());
 
         if total_span < min_samples {
             return Vec::new();
         }
 
-        let conf = if score <= 0.0 { 0 } else { 1000 };
+        let conf = match cfg.correlation {
+            KeyCorrelation::DotProduct => {
+                if score <= 0.0 { 0 } else { 1000 }
+            }
+            // r in [-1, 1]; anti-correlation is no evidence at all.
+            KeyCorrelation::Pearson => clamp01_to_confidence_x1000(score),
+        };
 
         vec![KeyEvent {
             key,
@@ -54,30 +61,109 @@ impl KeyAnalyzer for HistogramKeyAnalyzer {
     }
 }
 
-fn best_key(hist: &[f32; 12], mode: KeyMode) -> (u8, f32) {
-    // Very simple major/minor profiles.
-    let profile_maj: [f32; 12] = [6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88];
-    let profile_min: [f32; 12] = [6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17];
-
-    let base = match mode {
-        KeyMode::Major => &profile_maj,
-        KeyMode::Minor => &profile_min,
+/// Accumulate a 12-bin pitch-class histogram using the given weighting.
+pub(crate) fn pitch_class_histogram(notes: &[NoteEvent], weighting: KeyWeighting) -> [f32; 12] {
+    let lowest = match weighting {
+        KeyWeighting::DurationVelocityBass { .. } => lowest_at_onset(notes),
+        _ => Vec::new(),
     };
+    let mut hist = [0.0_f32; 12];
+    for (i, n) in notes.iter().enumerate() {
+        let pc = n.note.pitch_class().as_u8() as usize;
+        let dur = (n.offset.value() - n.onset.value()).max(1) as f32;
+        let vel = f32::from(n.velocity) / 127.0;
+        hist[pc] += match weighting {
+            KeyWeighting::Count => 1.0,
+            KeyWeighting::Duration => dur,
+            KeyWeighting::DurationVelocity => dur * vel,
+            KeyWeighting::DurationVelocityBass { bass_weight } => {
+                if lowest[i] {
+                    dur * vel * bass_weight
+                } else {
+                    dur * vel
+                }
+            }
+        };
+    }
+    hist
+}
+
+/// Per note: true if no other note sounding at its onset is lower.
+///
+/// One sweep in onset order; notes leave the sounding set at their offset.
+fn lowest_at_onset(notes: &[NoteEvent]) -> Vec<bool> {
+    let mut order: Vec<usize> = (0..notes.len()).collect();
+    order.sort_by_key(|&i| notes[i].onset);
+
+    let mut out = vec![false; notes.len()];
+    // Sounding notes by offset, and how many sound per pitch.
+    let mut ends: BinaryHeap<Reverse<(i64, u8)>> = BinaryHeap::new();
+    let mut sounding: BTreeMap<u8, usize> = BTreeMap::new();
+    for group in order.chunk_by(|&a, &b| notes[a].onset == notes[b].onset) {
+        let t = notes[group[0]].onset.value();
+        while let Some(&Reverse((offset, pitch))) = ends.peek() {
+            if offset > t {
+                break;
+            }
+            ends.pop();
+            if let Some(count) = sounding.get_mut(&pitch) {
+                *count -= 1;
+                if *count == 0 {
+                    sounding.remove(&pitch);
+                }
+            }
+        }
+        for &i in group {
+            let n = &notes[i];
+            if n.offset.value() > t {
+                ends.push(Reverse((n.offset.value(), n.note.value())));
+                *sounding.entry(n.note.value()).or_default() += 1;
+            }
+        }
+        let lowest = sounding.keys().next().copied();
+        for &i in group {
+            out[i] = lowest.is_none_or(|low| notes[i].note.value() <= low);
+        }
+    }
+    out
+}
 
+/// Find the best tonic for one mode; returns `(tonic, score)`.
+pub(crate) fn best_key(hist: &[f32; 12], profile: &[f32; 12], corr: KeyCorrelation) -> (u8, f32) {
     let mut best_tonic = 0u8;
     let mut best_score = f32::MIN;
 
-    for tonic in 0..12 {
-        let mut score = 0.0;
-        for i in 0..12 {
-            let idx = ((i + tonic) % 12) as usize;
-            score += hist[idx] * base[i];
-        }
+    for tonic in 0..12u8 {
+        let score = key_score(hist, profile, tonic, corr);
         if score > best_score {
             best_score = score;
-            best_tonic = tonic as u8;
+            best_tonic = tonic;
         }
     }
 
     (best_tonic, best_score)
 }
+
+/// Score a histogram against `profile` rotated so index 0 lands on `tonic`.
+pub(crate) fn key_score(hist: &[f32; 12], profile: &[f32; 12], tonic: u8, corr: KeyCorrelation) -> f32 {
+    let rotated = |i: usize| hist[(i + tonic as usize) % 12];
+    match corr {
+        KeyCorrelation::DotProduct => (0..12).map(|i| rotated(i) * profile[i]).sum(),
+        KeyCorrelation::Pearson => {
+            let mean_h = hist.iter().sum::<f32>() / 12.0;
+            let mean_p = profile.iter().sum::<f32>() / 12.0;
+            let mut cov = 0.0;
+            let mut var_h = 0.0;
+            let mut var_p = 0.0;
+            for (i, &p) in profile.iter().enumerate() {
+                let dh = rotated(i) - mean_h;
+                let dp = p - mean_p;
+                cov += dh * dp;
+                var_h += dh * dh;
+                var_p += dp * dp;
+            }
+            let den = (var_h * var_p).sqrt();
+            if den > 0.0 { cov / den } else { 0.0 }
+        }
+    }
+}
@@ -1,4 +1,7 @@
 //! Key detection façade.
-mod histogram;
+pub(crate) mod histogram;
+pub mod profiles;
+pub mod spiral_array;
 
 pub use histogram::HistogramKeyAnalyzer;
+pub use spiral_array::SpiralArrayKeyAnalyzer;
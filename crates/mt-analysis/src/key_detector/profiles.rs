@@ -0,0 +1,59 @@
+//! Published key-profile tables.
+//!
+//! Values are copied verbatim from the cited sources so results can be
+//! compared against the literature. Index 0 is the tonic; profiles are
+//! rotated at scoring time.
+
+use crate::config::KeyProfile;
+
+/// Krumhansl & Kessler (1982), major.
+pub const KRUMHANSL_KESSLER_MAJOR: [f32; 12] =
+    [6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88];
+/// Krumhansl & Kessler (1982), minor.
+pub const KRUMHANSL_KESSLER_MINOR: [f32; 12] =
+    [6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17];
+
+/// Temperley (2007), Kostka–Payne corpus, major.
+pub const TEMPERLEY_MAJOR: [f32; 12] =
+    [0.748, 0.060, 0.488, 0.082, 0.670, 0.460, 0.096, 0.715, 0.104, 0.366, 0.057, 0.400];
+/// Temperley (2007), Kostka–Payne corpus, minor.
+pub const TEMPERLEY_MINOR: [f32; 12] =
+    [0.712, 0.084, 0.474, 0.618, 0.049, 0.460, 0.105, 0.747, 0.404, 0.067, 0.133, 0.330];
+
+/// Aarden (2003), Essen folksong collection, major.
+pub const AARDEN_ESSEN_MAJOR: [f32; 12] = [
+    17.7661, 0.145_624, 14.9265, 0.160_186, 19.8049, 11.3587, 0.291_248, 22.062, 0.145_624,
+    8.15494, 0.232_998, 4.95122,
+];
+/// Aarden (2003), Essen folksong collection, minor.
+pub const AARDEN_ESSEN_MINOR: [f32; 12] = [
+    18.2648, 0.737_619, 14.0499, 16.8599, 0.702_494, 14.4362, 0.702_494, 18.6161, 4.56621, 1.93186,
+    7.37619, 1.75623,
+];
+
+/// Albrecht & Shanahan (2013), major.
+pub const ALBRECHT_SHANAHAN_MAJOR: [f32; 12] =
+    [0.238, 0.006, 0.111, 0.006, 0.137, 0.094, 0.016, 0.214, 0.009, 0.080, 0.008, 0.081];
+/// Albrecht & Shanahan (2013), minor.
+pub const ALBRECHT_SHANAHAN_MINOR: [f32; 12] =
+    [0.220, 0.006, 0.104, 0.123, 0.019, 0.103, 0.012, 0.214, 0.062, 0.022, 0.061, 0.052];
+
+/// Bellman (2005) after Budge (1943), major.
+pub const BELLMAN_BUDGE_MAJOR: [f32; 12] =
+    [16.80, 0.86, 12.95, 1.41, 13.49, 11.93, 1.25, 20.28, 1.80, 8.04, 0.62, 10.57];
+/// Bellman (2005) after Budge (1943), minor.
+pub const BELLMAN_BUDGE_MINOR: [f32; 12] =
+    [18.16, 0.69, 12.99, 13.34, 1.07, 11.15, 1.38, 21.07, 7.49, 1.53, 0.92, 10.21];
+
+/// Resolve a profile selection into its `(major, minor)` tables.
+#[must_use]
+pub fn profile_pair(profile: &KeyProfile) -> (&[f32; 12], &[f32; 12]) {
+    match profile {
+        KeyProfile::KrumhanslKessler => (&KRUMHANSL_KESSLER_MAJOR, &KRUMHANSL_KESSLER_MINOR),
+        KeyProfile::Temperley => (&TEMPERLEY_MAJOR, &TEMPERLEY_MINOR),
+        KeyProfile::AardenEssen => (&AARDEN_ESSEN_MAJOR, &AARDEN_ESSEN_MINOR),
+        KeyProfile::AlbrechtShanahan => (&ALBRECHT_SHANAHAN_MAJOR, &ALBRECHT_SHANAHAN_MINOR),
+        KeyProfile::BellmanBudge => (&BELLMAN_BUDGE_MAJOR, &BELLMAN_BUDGE_MINOR),
+        KeyProfile::Custom { major, minor } => (major, minor),
+    }
+}
@@ -0,0 +1,444 @@
+//! Spiral Array key finding and tonal tension (Chew, 2000; Herremans & Chew, 2016).
+//!
+//! Pitches sit on a helix indexed by the line of fifths; chords and keys are
+//! weighted centres of their constituents. A window of notes is summarised by
+//! its centre of effect (CE), and the key is the key representation nearest to
+//! that CE.
+//!
+//! Pitch-classes carry no spelling, so each one is placed at the enharmonic
+//! position (k, k ± 12) closest to the running CE before averaging.
+//!
+//! Besides `KeyAnalyzer`, the analyzer exposes tension measures per slice:
+//! cloud diameter, cloud momentum and tensile strain.
+
+use crate::confidence::clamp01_to_confidence_x1000;
+use crate::config::{KeyConfig, TensionConfig};
+use crate::traits::KeyAnalyzer;
+use mt_core::chord_kind::chord_intervals;
+use mt_core::events::{ChordEvent, KeyEvent, NoteEvent, TensionEvent};
+use mt_core::key::{Key, KeyMode};
+use mt_core::pitch::PitchClass;
+use mt_core::time::SampleTime;
+
+/// Point in Spiral Array space.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SpiralPoint {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl SpiralPoint {
+    /// Euclidean distance to `other`.
+    #[must_use]
+    pub fn distance(self, other: Self) -> f32 {
+        let dx = self.x - other.x;
+        let dy = self.y - other.y;
+        let dz = self.z - other.z;
+        (dx * dx + dy * dy + dz * dz).sqrt()
+    }
+
+    fn scaled(self, w: f32) -> Self {
+        Self { x: self.x * w, y: self.y * w, z: self.z * w }
+    }
+
+    fn add(self, other: Self) -> Self {
+        Self { x: self.x + other.x, y: self.y + other.y, z: self.z + other.z }
+    }
+}
+
+/// Model parameters. Defaults follow Chew's calibrated values.
+#[derive(Clone, Copy, Debug)]
+pub struct SpiralArrayParams {
+    /// Helix radius.
+    pub radius: f32,
+    /// Rise per step along the line of fifths.
+    pub height: f32,
+    /// Chord weights for (root, fifth, third).
+    pub chord_weights: [f32; 3],
+    /// Key weights for (tonic, dominant, subdominant) chords.
+    pub key_weights: [f32; 3],
+    /// Minor keys: share of the major dominant vs. the minor v chord.
+    pub alpha: f32,
+    /// Minor keys: share of the minor iv chord vs. the major IV chord.
+    pub beta: f32,
+}
+
+impl Default for SpiralArrayParams {
+    fn default() -> Self {
+        Self {
+            radius: 1.0,
+            height: (2.0_f32 / 15.0).sqrt(),
+            chord_weights: [0.536, 0.274, 0.19],
+            key_weights: [0.536, 0.274, 0.19],
+            alpha: 0.75,
+            beta: 0.75,
+        }
+    }
+}
+
+/// Spiral Array key analyzer.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpiralArrayKeyAnalyzer {
+    pub params: SpiralArrayParams,
+}
+
+/// A pitch-class contribution over a time span.
+#[derive(Clone, Copy, Debug)]
+struct PitchSpan {
+    onset: i64,
+    offset: i64,
+    pc: u8,
+    weight: f32,
+}
+
+impl SpiralArrayKeyAnalyzer {
+    pub const fn new(params: SpiralArrayParams) -> Self {
+        Self { params }
+    }
+
+    /// Position of the pitch at line-of-fifths index `k` (C = 0, G = 1, F = -1).
+    #[must_use]
+    pub fn pitch_position(&self, k: i32) -> SpiralPoint {
+        let angle = k as f32 * core::f32::consts::FRAC_PI_2;
+        SpiralPoint {
+            x: self.params.radius * angle.sin(),
+            y: self.params.radius * angle.cos(),
+            z: k as f32 * self.params.height,
+        }
+    }
+
+    /// Major triad rooted at index `k`.
+    #[must_use]
+    pub fn major_chord(&self, k: i32) -> SpiralPoint {
+        let [w1, w2, w3] = self.params.chord_weights;
+        self.pitch_position(k)
+            .scaled(w1)
+            .add(self.pitch_position(k + 1).scaled(w2))
+            .add(self.pitch_position(k + 4).scaled(w3))
+    }
+
+    /// Minor triad rooted at index `k`.
+    #[must_use]
+    pub fn minor_chord(&self, k: i32) -> SpiralPoint {
+        let [w1, w2, w3] = self.params.chord_weights;
+        self.pitch_position(k)
+            .scaled(w1)
+            .add(self.pitch_position(k + 1).scaled(w2))
+            .add(self.pitch_position(k - 3).scaled(w3))
+    }
+
+    /// Major key with tonic at index `k`.
+    #[must_use]
+    pub fn major_key(&self, k: i32) -> SpiralPoint {
+        let [w1, w2, w3] = self.params.key_weights;
+        self.major_chord(k)
+            .scaled(w1)
+            .add(self.major_chord(k + 1).scaled(w2))
+            .add(self.major_chord(k - 1).scaled(w3))
+    }
+
+    /// Minor key with tonic at index `k`.
+    #[must_use]
+    pub fn minor_key(&self, k: i32) -> SpiralPoint {
+        let [w1, w2, w3] = self.params.key_weights;
+        let (a, b) = (self.params.alpha, self.params.beta);
+        let dominant =
+            self.major_chord(k + 1).scaled(a).add(self.minor_chord(k + 1).scaled(1.0 - a));
+        let subdominant =
+            self.minor_chord(k - 1).scaled(b).add(self.major_chord(k - 1).scaled(1.0 - b));
+        self.minor_chord(k).scaled(w1).add(dominant.scaled(w2)).add(subdominant.scaled(w3))
+    }
+
+    /// Weighted centre of effect of `(pitch-class, weight)` pairs.
+    ///
+    /// Each pitch-class is spelled so the cloud is as compact as possible
+    /// along the line of fifths; `around` (e.g. the previous CE index) breaks
+    /// ties. Returns `None` if total weight is zero.
+    #[must_use]
+    pub fn center_of_effect(
+        &self,
+        pitches: &[(PitchClass, f32)],
+        around: f32,
+    ) -> Option<SpiralPoint> {
+        let total: f32 = pitches.iter().map(|&(_, w)| w.max(0.0)).sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let hint = around.round() as i32;
+        let mut best_center = hint;
+        let mut best_spread = f32::MAX;
+        for c in hint - 6..=hint + 6 {
+            let mean = pitches
+                .iter()
+                .map(|&(pc, w)| spell(pc.as_u8(), c as f32) as f32 * w.max(0.0))
+                .sum::<f32>()
+                / total;
+            let spread = pitches
+                .iter()
+                .map(|&(pc, w)| {
+                    let d = spell(pc.as_u8(), c as f32) as f32 - mean;
+                    d * d * w.max(0.0)
+                })
+                .sum::<f32>();
+            let closer = (c - hint).abs() < (best_center - hint).abs();
+            if spread < best_spread || (spread <= best_spread && closer) {
+                best_spread = spread;
+                best_center = c;
+            }
+        }
+
+        let mut acc = SpiralPoint::default();
+        for &(pc, w) in pitches {
+            if w > 0.0 {
+                acc = acc.add(self.pitch_position(spell(pc.as_u8(), best_center as f32)).scaled(w));
+            }
+        }
+        Some(acc.scaled(1.0 / total))
+    }
+
+    /// Nearest key to a CE; returns `(key, distance, confidence_x1000)`.
+    ///
+    /// Confidence is the relative margin to the runner-up key.
+    #[must_use]
+    pub fn nearest_key(&self, ce: SpiralPoint) -> (Key, f32, u16) {
+        let center = (ce.z / self.params.height).round() as i32;
+        let mut best: Option<(Key, f32)> = None;
+        let mut second = f32::MAX;
+
+        for k in center - 6..=center + 6 {
+            let tonic = PitchClass::from_unchecked(fifths_to_pc(k));
+            for (mode, pos) in
+                [(KeyMode::Major, self.major_key(k)), (KeyMode::Minor, self.minor_key(k))]
+            {
+                let key = Key::new(tonic, mode);
+                let d = ce.distance(pos);
+                match best {
+                    Some((bk, bd)) if d < bd => {
+                        if bk != key {
+                            second = second.min(bd);
+                        }
+                        best = Some((key, d));
+                    }
+                    Some((bk, _)) => {
+                        if bk != key {
+                            second = second.min(d);
+                        }
+                    }
+                    None => best = Some((key, d)),
+                }
+            }
+        }
+
+        let (key, d) =
+            best.unwrap_or((Key::new(PitchClass::from_unchecked(0), KeyMode::Major), 0.0));
+        let conf = if second > 0.0 && second < f32::MAX {
+            clamp01_to_confidence_x1000((second - d) / second)
+        } else {
+            0
+        };
+        (key, d, conf)
+    }
+
+    /// Key regions from a chord timeline instead of notes.
+    ///
+    /// Chord tones are weighted by chord duration and confidence.
+    pub fn detect_keys_from_chords(&self, chords: &[ChordEvent], cfg: &KeyConfig) -> Vec<KeyEvent> {
+        self.keys_from_spans(&chord_spans(chords), cfg)
+    }
+
+    /// Tension curve over a note timeline.
+    pub fn tension(&self, notes: &[NoteEvent], cfg: &TensionConfig) -> Vec<TensionEvent> {
+        self.tension_from_spans(&note_spans(notes), *cfg)
+    }
+
+    /// Tension curve over a chord timeline.
+    pub fn tension_from_chords(
+        &self,
+        chords: &[ChordEvent],
+        cfg: &TensionConfig,
+    ) -> Vec<TensionEvent> {
+        self.tension_from_spans(&chord_spans(chords), *cfg)
+    }
+
+    fn keys_from_spans(&self, spans: &[PitchSpan], cfg: &KeyConfig) -> Vec<KeyEvent> {
+        let mut out: Vec<KeyEvent> = Vec::new();
+        let Some((start, end)) = extent(spans) else {
+            return out;
+        };
+
+        let win = seconds_to_samples(cfg.min_region_seconds, cfg.sample_rate);
+        if win <= 0 || end - start < win {
+            return out;
+        }
+        let hop = (win / 2).max(1);
+
+        let mut around = 0.0;
+        let mut t = start;
+        while t < end {
+            let w_end = (t + win).min(end);
+            let pitches = window_weights(spans, t, w_end);
+            if let Some(ce) = self.center_of_effect(&pitches, around) {
+                around = ce.z / self.params.height;
+                let (key, _, conf) = self.nearest_key(ce);
+                if out.last().is_none_or(|prev| prev.key != key) {
+                    out.push(KeyEvent {
+                        key,
+                        position: SampleTime::new(t),
+                        confidence_x1000: conf,
+                    });
+                }
+            }
+            t += hop;
+        }
+        out
+    }
+
+    fn tension_from_spans(&self, spans: &[PitchSpan], cfg: TensionConfig) -> Vec<TensionEvent> {
+        let mut out = Vec::new();
+        let Some((start, end)) = extent(spans) else {
+            return out;
+        };
+
+        let win = seconds_to_samples(cfg.window_seconds, cfg.sample_rate);
+        let hop = seconds_to_samples(cfg.hop_seconds, cfg.sample_rate);
+        if win <= 0 || hop <= 0 {
+            return out;
+        }
+
+        // Global key anchors the tensile strain.
+        let all = window_weights(spans, start, end);
+        let Some(global_ce) = self.center_of_effect(&all, 0.0) else {
+            return out;
+        };
+        let around = global_ce.z / self.params.height;
+        let (key, _, _) = self.nearest_key(global_ce);
+        let key_pos = self.key_position(key, around);
+
+        let mut prev_ce: Option<SpiralPoint> = None;
+        let mut t = start;
+        while t < end {
+            let w_end = (t + win).min(end);
+            let pitches = window_weights(spans, t, w_end);
+            if let Some(ce) = self.center_of_effect(&pitches, around) {
+                let positions: Vec<SpiralPoint> = pitches
+                    .iter()
+                    .map(|&(pc, _)| {
+                        self.pitch_position(spell(pc.as_u8(), ce.z / self.params.height))
+                    })
+                    .collect();
+                let mut diameter = 0.0_f32;
+                for (i, a) in positions.iter().enumerate() {
+                    for b in &positions[i + 1..] {
+                        diameter = diameter.max(a.distance(*b));
+                    }
+                }
+                let momentum = prev_ce.map_or(0.0, |p| p.distance(ce));
+                let strain = ce.distance(key_pos);
+                out.push(TensionEvent {
+                    onset: SampleTime::new(t),
+                    offset: SampleTime::new(w_end),
+                    cloud_diameter_x1000: to_x1000(diameter),
+                    cloud_momentum_x1000: to_x1000(momentum),
+                    tensile_strain_x1000: to_x1000(strain),
+                });
+                prev_ce = Some(ce);
+            }
+            t += hop;
+        }
+        out
+    }
+
+    /// Key representation for `key`, spelled nearest to `around`.
+    fn key_position(&self, key: Key, around: f32) -> SpiralPoint {
+        let k = spell(key.tonic().as_u8(), around);
+        match key.mode() {
+            KeyMode::Major => self.major_key(k),
+            KeyMode::Minor => self.minor_key(k),
+        }
+    }
+}
+
+impl KeyAnalyzer for SpiralArrayKeyAnalyzer {
+    fn detect_keys(&self, notes: &[NoteEvent], cfg: &KeyConfig) -> Vec<KeyEvent> {
+        self.keys_from_spans(&note_spans(notes), cfg)
+    }
+}
+
+/// Line-of-fifths index for `pc` closest to `around`.
+fn spell(pc: u8, around: f32) -> i32 {
+    // pc * 7 mod 12 gives the index in 0..12 (7 is its own inverse mod 12).
+    let base = i32::from(pc) * 7 % 12;
+    let mut best = base;
+    for cand in [base - 24, base - 12, base + 12, base + 24] {
+        if (cand as f32 - around).abs() < (best as f32 - around).abs() {
+            best = cand;
+        }
+    }
+    best
+}
+
+fn seconds_to_samples(seconds: f32, sample_rate: u32) -> i64 {
+    (f64::from(seconds) * f64::from(sample_rate)) as i64
+}
+
+fn fifths_to_pc(k: i32) -> u8 {
+    (k * 7).rem_euclid(12) as u8
+}
+
+fn to_x1000(d: f32) -> u32 {
+    if d.is_finite() && d > 0.0 { (d * 1000.0 + 0.5) as u32 } else { 0 }
+}
+
+fn note_spans(notes: &[NoteEvent]) -> Vec<PitchSpan> {
+    notes
+        .iter()
+        .map(|n| PitchSpan {
+            onset: n.onset.value(),
+            offset: n.offset.value(),
+            pc: n.note.pitch_class().as_u8(),
+            weight: 1.0,
+        })
+        .collect()
+}
+
+fn chord_spans(chords: &[ChordEvent]) -> Vec<PitchSpan> {
+    let mut out = Vec::new();
+    for c in chords {
+        let weight = f32::from(c.confidence_x1000.max(1)) / 1000.0;
+        for iv in chord_intervals(c.chord.kind) {
+            out.push(PitchSpan {
+                onset: c.onset.value(),
+                offset: c.offset.value(),
+                pc: c.chord.root.transpose(*iv as i8).as_u8(),
+                weight,
+            });
+        }
+    }
+    out
+}
+
+fn extent(spans: &[PitchSpan]) -> Option<(i64, i64)> {
+    let start = spans.iter().map(|s| s.onset).min()?;
+    let end = spans.iter().map(|s| s.offset).max()?;
+    (end > start).then_some((start, end))
+}
+
+/// Duration-weighted pitch-classes sounding in `[start, end)`.
+fn window_weights(spans: &[PitchSpan], start: i64, end: i64) -> Vec<(PitchClass, f32)> {
+    let mut weights = [0.0_f32; 12];
+    for s in spans {
+        let is = s.onset.max(start);
+        let ie = s.offset.min(end);
+        if ie > is {
+            weights[s.pc as usize] += (ie - is) as f32 * s.weight;
+        }
+    }
+    weights
+        .iter()
+        .enumerate()
+        .filter(|(_, w)| **w > 0.0)
+        .map(|(pc, w)| (PitchClass::from_unchecked(pc as u8), *w))
+        .collect()
+}
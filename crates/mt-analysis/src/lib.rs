@@ -10,11 +10,16 @@
 //!   - MIDI → normalized notes
 //!   - Audio → notes (monophonic/simple polyphonic, deterministic)
 //!   - Tempo + meter
-//!   - Key (histogram-based)
-//!   - Chords (template/rule-based over pitch classes)
+//!   - Key (histogram-based, Spiral Array) and tonal tension
+//!   - Chords (template/rule-based and HMM over pitch classes, chroma from audio)
 //!   - Swing feel
-//!   - Structural segmentation (energy + harmony)
+//!   - Cadences (authentic, half, plagal, deceptive, Phrygian)
+//!   - Structural segmentation (energy, harmony, self-similarity with repeats,
+//!     section → phrase → sub-phrase trees)
+//! - Streaming ports of the simple analyzers for block-based real-time use
 //! - Confidence scoring and simple post-processing utilities.
+//! - Evaluation against reference annotations (MIREX chord, key, structure
+//!   and beat measures).
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![forbid(unsafe_code)]
@@ -39,12 +44,19 @@ pub mod traits;
 pub mod midi_note_detector;
 pub mod audio_note_detector;
 pub mod chord_detector;
+pub mod chroma;
+pub mod mfcc;
 pub mod key_detector;
 pub mod tempo_meter_detector;
 pub mod swing_detector;
 pub mod segmenter;
+pub mod cadence_detector;
+pub mod streaming;
 pub mod postprocess;
 pub mod confidence;
+pub mod eval;
+
+mod metric_grid;
 
 pub use crate::{
     config::AnalysisConfig,
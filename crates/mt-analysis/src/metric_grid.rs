@@ -0,0 +1,45 @@
+//! Beat and bar grid over tempo and meter events.
+//!
+//! Shared by analyzers that score positions metrically (phrases, cadences).
+
+use mt_alloc::TempoMap;
+use mt_core::events::{MeterEvent, TempoEvent};
+use mt_core::time::SampleTime;
+
+/// Beat and bar positions from tempo and meter events.
+pub(crate) struct MetricGrid {
+    tempo: TempoMap,
+    /// `(beat, numerator)` at each meter change.
+    meters: Vec<(f32, u8)>,
+}
+
+impl MetricGrid {
+    pub(crate) fn new(sample_rate: u32, tempo: &[TempoEvent], meter: &[MeterEvent]) -> Self {
+        let mut map = TempoMap::new(sample_rate);
+        for ev in tempo {
+            map.push_tempo(*ev);
+        }
+        let mut grid = Self { tempo: map, meters: Vec::with_capacity(meter.len()) };
+        for ev in meter {
+            let beat = grid.beats(ev.position.value());
+            grid.meters.push((beat, ev.numerator.max(1)));
+        }
+        grid
+    }
+
+    pub(crate) fn beats(&self, t: i64) -> f32 {
+        self.tempo.sample_to_beats_x1000(SampleTime::new(t)) as f32 / 1000.0
+    }
+
+    /// Fractional bar index at `t` and the beats per bar there.
+    pub(crate) fn bar(&self, t: i64) -> (f32, u8) {
+        let beat = self.beats(t);
+        let (mut bars, mut from, mut numerator) = (0.0_f32, 0.0_f32, 4_u8);
+        for &(at, num) in self.meters.iter().take_while(|(at, _)| *at <= beat) {
+            bars += (at - from) / f32::from(numerator);
+            from = at;
+            numerator = num;
+        }
+        (bars + (beat - from) / f32::from(numerator), numerator)
+    }
+}
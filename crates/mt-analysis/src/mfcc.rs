@@ -0,0 +1,40 @@
+//! Audio → MFCC frames.
+//!
+//! Strategy:
+//! - Hann-windowed frames at `MfccConfig::hop_size`.
+//! - Goertzel-sampled mel filterbank, log energies and DCT-II
+//!   (`mt_signal_core::mfcc`).
+//! - Silent frames stay all-zero.
+
+use crate::config::MfccConfig;
+use mt_alloc::FeatureBuffer;
+use mt_signal_core::mfcc::{MEL_BANDS, mfcc_frame};
+use mt_signal_core::window::{WindowKind, fill_window};
+
+/// MFCC matrix `[frames x coefficients]`; frame `i` starts at sample
+/// `i * hop_size`.
+#[must_use]
+pub fn mfcc_frames(samples: &[f32], sample_rate: u32, cfg: &MfccConfig) -> FeatureBuffer {
+    let frame = cfg.frame_size.max(1);
+    let hop = cfg.hop_size.max(1);
+    let dim = cfg.coefficients.clamp(1, MEL_BANDS);
+    let count = if samples.len() < frame { 0 } else { (samples.len() - frame) / hop + 1 };
+    let mut out = FeatureBuffer::with_capacity(dim, count);
+
+    let mut window = vec![0.0_f32; frame];
+    fill_window(WindowKind::Hann, &mut window);
+
+    let silent = vec![0.0_f32; dim];
+    let mut coefs = vec![0.0_f32; dim];
+    for i in 0..count {
+        let slice = &samples[i * hop..i * hop + frame];
+        let rms = (slice.iter().map(|x| x * x).sum::<f32>() / frame as f32).sqrt();
+        if rms < cfg.rms_threshold {
+            out.push_frame(&silent);
+            continue;
+        }
+        mfcc_frame(slice, &window, sample_rate, cfg.min_hz, cfg.max_hz, &mut coefs);
+        out.push_frame(&coefs);
+    }
+    out
+}
@@ -1,8 +1,8 @@
 //! MIDI → NoteEvent normalization.
 //!
 //! Deterministic, order-stable pairing of note-on/off into NoteEvent.
+//! Sustain/sostenuto pedals extend offsets per `MidiNoteConfig::pedal`.
 
-    
 #[cfg(not(feature = "std"))]
 use core::collections::BTreeMap;
 #[cfg(feature = "std")]
@@ -11,7 +11,8 @@ use std::collections::BTreeMap;
 use crate::config::MidiNoteConfig;
 use crate::traits::MidiNoteAnalyzer;
 use mt_core::events::{NoteEvent, NoteId, TrackId};
-use mt_core::midi::{MidiEvent, MidiEventKind};
+use mt_core::midi::{MidiEventKind, TimedMidiEvent};
+use mt_core::pedal::{self, PedalState};
 use mt_core::pitch::MidiNote;
 use mt_core::time::SampleTime;
 
@@ -28,18 +29,46 @@ impl SimpleMidiNoteAnalyzer {
     }
 }
 
+impl SimpleMidiNoteAnalyzer {
+    /// End the active note at `key`, if any, at `t`.
+    fn close(
+        &self,
+        active: &mut BTreeMap<(u8, u8), (SampleTime, u8)>,
+        key: (u8, u8),
+        t: SampleTime,
+        next_id: &mut u32,
+        out: &mut Vec<NoteEvent>,
+    ) {
+        if let Some((on, vel)) = active.remove(&key)
+            && t.value() > on.value()
+        {
+            out.push(NoteEvent {
+                id: NoteId(*next_id),
+                track: self.track,
+                onset: on,
+                offset: t,
+                note: MidiNote::new(key.1 & 0x7F).unwrap(),
+                velocity: vel,
+            });
+            *next_id = next_id.wrapping_add(1);
+        }
+    }
+}
+
 impl MidiNoteAnalyzer for SimpleMidiNoteAnalyzer {
-    fn detect_midi_notes(&self, events: &[MidiEvent], cfg: &MidiNoteConfig) -> Vec<NoteEvent> {
+    fn detect_midi_notes(&self, events: &[TimedMidiEvent], cfg: &MidiNoteConfig) -> Vec<NoteEvent> {
         // active[(channel, note)] = (on_time, velocity)
         let mut active: BTreeMap<(u8, u8), (SampleTime, u8)> = BTreeMap::new();
         let mut out = Vec::new();
         let mut next_id = 1u32;
+        let mut pedals = PedalState::new(cfg.pedal);
 
-        for (i, ev) in events.iter().enumerate() {
-            let t = SampleTime::new(i as i64); // engine should supply real times; here index-based.
+        for timed in events {
+            let (t, ev) = (timed.position, timed.event);
             match ev.kind {
                 MidiEventKind::NoteOn if ev.data2 > 0 => {
                     let key = (ev.channel.value(), ev.data1);
+                    pedals.note_on(key.0, key.1);
                     // If already active, either retrigger or close previous.
                     if let Some((on, vel)) = active.remove(&key) {
                         // Close previous if retrigger tolerance is set.
@@ -61,20 +90,15 @@ impl MidiNoteAnalyzer for SimpleMidiNoteAnalyzer {
                 }
                 MidiEventKind::NoteOff | MidiEventKind::NoteOn => {
                     // NoteOn with velocity 0 is treated as NoteOff.
-                    let key = (ev.channel.value(), ev.data1);
-                    if let Some((on, vel)) = active.remove(&key) {
-                        let offset = t;
-                        if offset.value() > on.value() {
-                            out.push(NoteEvent {
-                                id: NoteId(next_id),
-                                track: self.track,
-                                onset: on,
-                                offset,
-                                note: MidiNote::new(ev.data1).unwrap(),
-                                velocity: vel,
-                            });
-                            next_id = next_id.wrapping_add(1);
-                        }
+                    if pedals.note_off(ev.channel.value(), ev.data1) {
+                        let key = (ev.channel.value(), ev.data1);
+                        self.close(&mut active, key, t, &mut next_id, &mut out);
+                    }
+                }
+                MidiEventKind::ControlChange => {
+                    let ch = ev.channel.value();
+                    for note in pedal::keys(pedals.control_change(ch, ev.data1, ev.data2)) {
+                        self.close(&mut active, (ch, note), t, &mut next_id, &mut out);
                     }
                 }
                 _ => {}
@@ -29,6 +29,7 @@ impl SegmentAnalyzer for EnergySegmenter {
             // Single segment.
             out.push(SegmentEvent {
                 kind: SegmentKind::Other(0),
+                label: None,
                 onset: SampleTime::new(0),
                 offset: SampleTime::new(samples.len() as i64),
                 confidence_x1000: 1000,
@@ -67,6 +68,7 @@ impl SegmentAnalyzer for EnergySegmenter {
         for (s, e) in segments {
             out.push(SegmentEvent {
                 kind: SegmentKind::Other(0),
+                label: None,
                 onset: SampleTime::new(s),
                 offset: SampleTime::new(e),
                 confidence_x1000: 800,
@@ -31,6 +31,7 @@ impl SegmentAnalyzer for HarmonicSegmenter {
             if ev.chord != last_chord && pos - seg_start >= min_len {
                 out.push(SegmentEvent {
                     kind: SegmentKind::Other(1),
+                    label: None,
                     onset: SampleTime::new(seg_start),
                     offset: SampleTime::new(pos),
                     confidence_x1000: 700,
@@ -44,6 +45,7 @@ impl SegmentAnalyzer for HarmonicSegmenter {
         if end > seg_start {
             out.push(SegmentEvent {
                 kind: SegmentKind::Other(1),
+                label: None,
                 onset: SampleTime::new(seg_start),
                 offset: SampleTime::new(end),
                 confidence_x1000: 700,
@@ -0,0 +1,345 @@
+//! Hierarchical segmentation: section → phrase → sub-phrase.
+//!
+//! Strategy:
+//! - Sections come from the caller (MIDI) or `SelfSimilaritySegmenter`
+//!   (audio); each level is then split inside its parent, so children nest
+//!   exactly and tile the parent.
+//! - MIDI: every melody (top-voice) onset is a candidate boundary, scored
+//!   from the rest and the long note before it, cadential root motion into
+//!   the chord under that note, and its position in the (hyper)meter.
+//!   Candidates are accepted strongest first, a minimum number of beats
+//!   apart.
+//! - Audio: peaks of SSM novelty with a shorter kernel per level
+//!   (`phrase_kernel_seconds`, `sub_phrase_kernel_seconds`).
+
+use crate::confidence::clamp01_to_confidence_x1000;
+use crate::config::{PhraseConfig, SegmentConfig};
+use crate::segmenter::self_similarity::{
+    FeatureGrid, SelfSimilarityMatrix, SelfSimilaritySegmenter, pick_peaks,
+};
+use crate::metric_grid::MetricGrid;
+use crate::traits::SegmentAnalyzer;
+use mt_core::events::{
+    ChordEvent, MeterEvent, NoteEvent, SegmentEvent, SegmentKind, SegmentLevel, SegmentNode,
+    TempoEvent,
+};
+use mt_core::time::SampleTime;
+
+/// Segments nested by level with parent links, stored depth-first
+/// (each section is followed by its phrases, each phrase by its
+/// sub-phrases).
+#[derive(Clone, Debug, Default)]
+pub struct SegmentTree {
+    nodes: Vec<SegmentNode>,
+}
+
+impl SegmentTree {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a node; returns its index.
+    pub fn push(&mut self, segment: SegmentEvent, level: SegmentLevel, parent: Option<u32>) -> u32 {
+        self.nodes.push(SegmentNode { segment, level, parent });
+        (self.nodes.len() - 1) as u32
+    }
+
+    #[must_use]
+    pub fn nodes(&self) -> &[SegmentNode] {
+        &self.nodes
+    }
+
+    #[must_use]
+    pub fn get(&self, index: u32) -> Option<&SegmentNode> {
+        self.nodes.get(index as usize)
+    }
+
+    /// Direct children of node `index`, in time order.
+    pub fn children(&self, index: u32) -> impl Iterator<Item = (u32, &SegmentNode)> {
+        self.indexed().filter(move |(_, n)| n.parent == Some(index))
+    }
+
+    /// All nodes at `level`, in time order.
+    pub fn at_level(&self, level: SegmentLevel) -> impl Iterator<Item = (u32, &SegmentNode)> {
+        self.indexed().filter(move |(_, n)| n.level == level)
+    }
+
+    /// Node at `level` whose span `[onset, offset)` contains `position`.
+    #[must_use]
+    pub fn containing(&self, position: SampleTime, level: SegmentLevel) -> Option<u32> {
+        self.at_level(level)
+            .find(|(_, n)| n.segment.onset <= position && position < n.segment.offset)
+            .map(|(i, _)| i)
+    }
+
+    fn indexed(&self) -> impl Iterator<Item = (u32, &SegmentNode)> {
+        self.nodes.iter().enumerate().map(|(i, n)| (i as u32, n))
+    }
+}
+
+/// Builds a `SegmentTree` from MIDI notes or audio.
+pub struct HierarchicalSegmenter;
+
+impl HierarchicalSegmenter {
+    /// Phrases and sub-phrases of `notes` inside `sections`.
+    ///
+    /// `tempo` and `meter` give the bar grid (120 BPM, 4/4 when empty);
+    /// `chords` supply cadence cues. Without sections the whole note span is
+    /// one `Other(0)` section.
+    #[must_use]
+    pub fn segment_midi(
+        &self,
+        notes: &[NoteEvent],
+        chords: &[ChordEvent],
+        tempo: &[TempoEvent],
+        meter: &[MeterEvent],
+        sections: &[SegmentEvent],
+        sample_rate: u32,
+        cfg: &PhraseConfig,
+    ) -> SegmentTree {
+        let sr = if sample_rate == 0 { 44_100 } else { sample_rate }; // stable default.
+        let melody = top_voice(notes, i64::from(sr / 100));
+        if melody.is_empty() {
+            return SegmentTree::new();
+        }
+        let grid = MetricGrid::new(sr, tempo, meter);
+        let cues = BoundaryCue::score_all(&melody, chords, &grid, cfg);
+
+        let whole = [SegmentEvent {
+            kind: SegmentKind::Other(0),
+            label: None,
+            onset: melody[0].onset,
+            offset: melody.iter().map(|n| n.offset).max().unwrap_or(melody[0].offset),
+            confidence_x1000: clamp01_to_confidence_x1000(1.0),
+        }];
+        let sections = if sections.is_empty() { &whole[..] } else { sections };
+
+        build_tree(sections, |parent, level| {
+            let (period, min_beats, threshold) = match level {
+                SegmentLevel::Phrase => {
+                    (cfg.phrase_bars, cfg.min_phrase_beats, cfg.boundary_threshold)
+                }
+                _ => (
+                    (cfg.phrase_bars / 2).max(1),
+                    cfg.min_sub_phrase_beats,
+                    cfg.sub_boundary_threshold,
+                ),
+            };
+            select_boundaries(&cues, parent, &grid, cfg, period, min_beats, threshold)
+        })
+    }
+
+    /// Sections from `SelfSimilaritySegmenter`, phrases and sub-phrases from
+    /// novelty of the same feature grid at shorter kernels.
+    #[must_use]
+    pub fn segment_audio(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        chords: &[ChordEvent],
+        segment: &SegmentConfig,
+        cfg: &PhraseConfig,
+    ) -> SegmentTree {
+        let Some(grid) = FeatureGrid::build(samples, sample_rate, chords, segment) else {
+            return SegmentTree::new();
+        };
+        let sections =
+            SelfSimilaritySegmenter.detect_segments(samples, sample_rate, chords, segment);
+        let ssm = SelfSimilarityMatrix::from_features(&grid.features);
+        let half = |seconds: f32| ((seconds / segment.ssm_hop_seconds).round() as usize / 2).max(1);
+        let phrase_half = half(cfg.phrase_kernel_seconds);
+        let sub_half = half(cfg.sub_phrase_kernel_seconds);
+        let phrase_novelty = ssm.novelty(phrase_half);
+        let sub_novelty = ssm.novelty(sub_half);
+
+        build_tree(&sections, |parent, level| {
+            let (novelty, min_gap) = match level {
+                SegmentLevel::Phrase => (&phrase_novelty, phrase_half),
+                _ => (&sub_novelty, sub_half),
+            };
+            let n = novelty.len();
+            let frame = |t: SampleTime| ((t.value() - grid.start).max(0) / grid.hop) as usize;
+            let (a, b) = (frame(parent.onset).min(n), frame(parent.offset).min(n));
+            pick_peaks(&novelty[a..b], segment.novelty_threshold, min_gap)
+                .into_iter()
+                .map(|p| (grid.frame_start(a + p), novelty[a + p]))
+                .collect()
+        })
+    }
+}
+
+/// Sections as roots, then `split(parent, level)` boundaries for phrases
+/// and sub-phrases.
+fn build_tree(
+    sections: &[SegmentEvent],
+    mut split: impl FnMut(&SegmentEvent, SegmentLevel) -> Vec<(i64, f32)>,
+) -> SegmentTree {
+    let mut tree = SegmentTree::new();
+    for section in sections {
+        let root = tree.push(*section, SegmentLevel::Section, None);
+        for phrase in subdivide(section, &split(section, SegmentLevel::Phrase)) {
+            let parent = tree.push(phrase, SegmentLevel::Phrase, Some(root));
+            for sub in subdivide(&phrase, &split(&phrase, SegmentLevel::SubPhrase)) {
+                tree.push(sub, SegmentLevel::SubPhrase, Some(parent));
+            }
+        }
+    }
+    tree
+}
+
+/// Cut `parent` at `(time, score)` boundaries; the first child keeps the
+/// parent's confidence, later ones their boundary score.
+fn subdivide(parent: &SegmentEvent, boundaries: &[(i64, f32)]) -> Vec<SegmentEvent> {
+    let (start, end) = (parent.onset.value(), parent.offset.value());
+    let mut cuts: Vec<(i64, f32)> =
+        boundaries.iter().copied().filter(|&(t, _)| t > start && t < end).collect();
+    cuts.sort_by_key(|&(t, _)| t);
+    cuts.dedup_by_key(|&mut (t, _)| t);
+
+    let mut out = Vec::with_capacity(cuts.len() + 1);
+    let mut from = (start, parent.confidence_x1000);
+    for (t, score) in cuts.into_iter().chain(core::iter::once((end, 0.0))) {
+        out.push(SegmentEvent {
+            kind: parent.kind,
+            label: None,
+            onset: SampleTime::new(from.0),
+            offset: SampleTime::new(t),
+            confidence_x1000: from.1,
+        });
+        from = (t, clamp01_to_confidence_x1000(score));
+    }
+    out
+}
+
+/// Highest note per onset cluster (`tolerance` samples), in time order.
+fn top_voice(notes: &[NoteEvent], tolerance: i64) -> Vec<NoteEvent> {
+    let mut sorted = notes.to_vec();
+    sorted.sort_by_key(|n| (n.onset, core::cmp::Reverse(n.note.value())));
+    let mut out: Vec<NoteEvent> = Vec::with_capacity(sorted.len());
+    for n in sorted {
+        match out.last_mut() {
+            Some(last) if n.onset.value() - last.onset.value() <= tolerance => {
+                if n.note.value() > last.note.value() {
+                    *last = n;
+                }
+            }
+            _ => out.push(n),
+        }
+    }
+    out
+}
+
+/// Candidate phrase boundary at a melody onset.
+struct BoundaryCue {
+    time: i64,
+    beat: f32,
+    /// Weighted rest + long-note + cadence cues (metric cue depends on the
+    /// parent and is added at selection).
+    local: f32,
+}
+
+impl BoundaryCue {
+    fn score_all(
+        melody: &[NoteEvent],
+        chords: &[ChordEvent],
+        grid: &MetricGrid,
+        cfg: &PhraseConfig,
+    ) -> Vec<Self> {
+        let beats: Vec<f32> = melody.iter().map(|n| grid.beats(n.onset.value())).collect();
+        let mut iois: Vec<f32> = beats.windows(2).map(|w| w[1] - w[0]).collect();
+        iois.sort_by(f32::total_cmp);
+        let median = iois.get(iois.len() / 2).copied().unwrap_or(1.0).max(1e-3);
+        let long_span = (cfg.long_note_ratio - 1.0).max(1e-3);
+
+        (1..melody.len())
+            .map(|k| {
+                let (prev, next) = (&melody[k - 1], &melody[k]);
+                let rest = (beats[k] - grid.beats(prev.offset.value())).clamp(0.0, 1.0);
+                let long = ((beats[k] - beats[k - 1]) / median - 1.0) / long_span;
+                let cadence = cadence_cue(chords, prev.onset, next.onset);
+                Self {
+                    time: next.onset.value(),
+                    beat: beats[k],
+                    local: cfg.rest_weight * rest
+                        + cfg.long_note_weight * long.clamp(0.0, 1.0)
+                        + cfg.cadence_weight * cadence,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Cadential root motion into the chord sounding at `last` (the final
+/// melody note before `boundary`): down a fifth scores 1, up a fifth
+/// (half or plagal motion) 0.5.
+fn cadence_cue(chords: &[ChordEvent], last: SampleTime, boundary: SampleTime) -> f32 {
+    let Some(i) = chords
+        .iter()
+        .rposition(|c| c.onset <= last && c.offset > last)
+        .or_else(|| chords.iter().rposition(|c| c.onset < boundary))
+    else {
+        return 0.0;
+    };
+    let Some(prev) = i.checked_sub(1).map(|p| &chords[p]) else {
+        return 0.0;
+    };
+    match (12 + chords[i].chord.root.as_u8() - prev.chord.root.as_u8()) % 12 {
+        5 => 1.0,
+        7 => 0.5,
+        _ => 0.0,
+    }
+}
+
+/// Boundaries inside `parent`: cue score plus the metric cue (1 on a
+/// downbeat every `period` bars from the parent's first bar, 0.5 halfway,
+/// 0.25 on other downbeats), accepted strongest first `min_beats` apart.
+fn select_boundaries(
+    cues: &[BoundaryCue],
+    parent: &SegmentEvent,
+    grid: &MetricGrid,
+    cfg: &PhraseConfig,
+    period: u8,
+    min_beats: f32,
+    threshold: f32,
+) -> Vec<(i64, f32)> {
+    let total = cfg.rest_weight + cfg.long_note_weight + cfg.cadence_weight + cfg.metric_weight;
+    if total <= 0.0 {
+        return Vec::new();
+    }
+    let (start, end) = (grid.beats(parent.onset.value()), grid.beats(parent.offset.value()));
+    let first_bar = grid.bar(parent.onset.value()).0.round();
+    let period = i64::from(period.max(1));
+
+    let mut scored: Vec<(i64, f32, f32)> = cues
+        .iter()
+        .filter(|c| c.beat - start >= min_beats && end - c.beat >= min_beats)
+        .map(|c| {
+            let (bar, numerator) = grid.bar(c.time);
+            let metric = if (bar - bar.round()).abs() * f32::from(numerator) > 0.25 {
+                0.0
+            } else {
+                let rel = (bar.round() - first_bar) as i64;
+                if rel.rem_euclid(period) == 0 {
+                    1.0
+                } else if period >= 2 && rel.rem_euclid(period / 2) == 0 {
+                    0.5
+                } else {
+                    0.25
+                }
+            };
+            (c.time, c.beat, (c.local + cfg.metric_weight * metric) / total)
+        })
+        .filter(|&(_, _, score)| score >= threshold)
+        .collect();
+    scored.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.0.cmp(&b.0)));
+
+    let mut accepted: Vec<(i64, f32, f32)> = Vec::new();
+    for cand in scored {
+        if accepted.iter().all(|a| (a.1 - cand.1).abs() >= min_beats) {
+            accepted.push(cand);
+        }
+    }
+    accepted.sort_by_key(|a| a.0);
+    accepted.into_iter().map(|(t, _, score)| (t, score)).collect()
+}
@@ -2,6 +2,10 @@
 
 pub mod energy_segmenter;
 pub mod harmonic_segmenter;
+pub mod hierarchy;
+pub mod self_similarity;
 
 pub use energy_segmenter::EnergySegmenter;
 pub use harmonic_segmenter::HarmonicSegmenter;
+pub use hierarchy::{HierarchicalSegmenter, SegmentTree};
+pub use self_similarity::{SelfSimilarityMatrix, SelfSimilaritySegmenter};
@@ -0,0 +1,519 @@
+//! Self-similarity segmentation with repetition labelling.
+//!
+//! Strategy:
+//! - Per-frame features on a `ssm_hop_seconds` grid: averaged chroma and
+//!   MFCCs from audio and/or chord templates from the chord timeline.
+//! - Cosine self-similarity matrix (SSM); boundaries at peaks of
+//!   checkerboard-kernel novelty (Foote). The novelty kernel only reads
+//!   cells near the diagonal, so the SSM of the (frame-count wide)
+//!   structure features is only computed within that band.
+//! - Segments are compared along their best-aligned SSM diagonal and
+//!   clustered into repetition labels (A, B, A').
+//! - Labels map to `SegmentKind` heuristically: the loudest (or most
+//!   repeated) group is the chorus, the repeated group before it the verse,
+//!   one-off sections at the edges intro/outro and after a chorus bridge.
+
+use crate::chord_detector::templates::{chord_state, state_template};
+use crate::chroma::chroma_frames;
+use crate::confidence::clamp01_to_confidence_x1000;
+use crate::config::{ChromaConfig, MfccConfig, SegmentConfig};
+use crate::mfcc::mfcc_frames;
+use crate::traits::SegmentAnalyzer;
+use mt_alloc::FeatureBuffer;
+use mt_core::events::{ChordEvent, SectionLabel, SegmentEvent, SegmentKind};
+use mt_core::time::SampleTime;
+
+pub struct SelfSimilaritySegmenter;
+
+impl SegmentAnalyzer for SelfSimilaritySegmenter {
+    fn detect_segments(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        chords: &[ChordEvent],
+        cfg: &SegmentConfig,
+    ) -> Vec<SegmentEvent> {
+        let Some(grid) = FeatureGrid::build(samples, sample_rate, chords, cfg) else {
+            return Vec::new();
+        };
+        let n = grid.features.frames();
+        let ssm = SelfSimilarityMatrix::from_features(&grid.features);
+
+        let min_gap = ((cfg.min_segment_seconds / cfg.ssm_hop_seconds).round() as usize).max(1);
+        let kernel = ((cfg.kernel_seconds / cfg.ssm_hop_seconds).round() as usize).max(2);
+        let embedded = ssm.smooth_diagonals(kernel / 2);
+        // Chord cycles inside a section make the raw SSM striped; the SSM of
+        // time-lag structure features is block-shaped per section instead.
+        let half = kernel / 2;
+        let structure = SelfSimilarityMatrix::from_features_within(
+            &context_average(&embedded.structure_features(), half),
+            2 * half,
+        );
+        let novelty = structure.novelty(half);
+        let peaks = pick_peaks(&novelty, cfg.novelty_threshold, min_gap);
+
+        let mut ranges = Vec::with_capacity(peaks.len() + 1);
+        let mut from = 0;
+        for &p in &peaks {
+            ranges.push((from, p));
+            from = p;
+        }
+        ranges.push((from, n));
+
+        let labels = label_repeats(&embedded, &ranges, cfg);
+        let energies: Vec<f32> = ranges.iter().map(|&(a, b)| grid.mean_energy(a, b)).collect();
+        let kinds = assign_kinds(&labels, &ranges, &energies);
+
+        ranges
+            .iter()
+            .enumerate()
+            .map(|(i, &(a, b))| {
+                let boundary = if i == 0 { 1.0 } else { novelty[a] };
+                let (label, similarity) = labels[i];
+                SegmentEvent {
+                    kind: kinds[i],
+                    label: Some(label),
+                    onset: SampleTime::new(grid.frame_start(a)),
+                    offset: SampleTime::new(grid.frame_start(b).min(grid.end)),
+                    confidence_x1000: clamp01_to_confidence_x1000(f32::midpoint(
+                        boundary, similarity,
+                    )),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Square cosine self-similarity matrix, row-major.
+#[derive(Clone, Debug)]
+pub struct SelfSimilarityMatrix {
+    size: usize,
+    values: Vec<f32>,
+}
+
+impl SelfSimilarityMatrix {
+    /// Cosine similarity between every pair of feature frames.
+    #[must_use]
+    pub fn from_features(features: &FeatureBuffer) -> Self {
+        Self::from_features_within(features, features.frames())
+    }
+
+    /// Cosine similarity of frames at most `band` frames apart; cells
+    /// further from the diagonal stay 0.
+    ///
+    /// Costs `O(frames * band * dim)` instead of `O(frames² * dim)`.
+    #[must_use]
+    pub fn from_features_within(features: &FeatureBuffer, band: usize) -> Self {
+        let size = features.frames();
+        let norms: Vec<f32> = (0..size)
+            .map(|i| features.frame(i).map_or(0.0, |f| f.iter().map(|v| v * v).sum::<f32>().sqrt()))
+            .collect();
+        let mut values = vec![0.0_f32; size * size];
+        for i in 0..size {
+            for j in i..size.min(i.saturating_add(band).saturating_add(1)) {
+                let (Some(fi), Some(fj)) = (features.frame(i), features.frame(j)) else {
+                    continue;
+                };
+                let denom = norms[i] * norms[j];
+                if denom > 0.0 {
+                    let sim = fi.iter().zip(fj).map(|(a, b)| a * b).sum::<f32>() / denom;
+                    values[i * size + j] = sim;
+                    values[j * size + i] = sim;
+                }
+            }
+        }
+        Self { size, values }
+    }
+
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Similarity of frames `i` and `j` (0 outside the matrix).
+    #[must_use]
+    pub fn get(&self, i: usize, j: usize) -> f32 {
+        if i < self.size && j < self.size { self.values[i * self.size + j] } else { 0.0 }
+    }
+
+    /// Average each cell over a centered run of `len` cells on its diagonal
+    /// (a time-delay embedding: frames match only if their context does).
+    #[must_use]
+    pub fn smooth_diagonals(&self, len: usize) -> Self {
+        let len = len.max(1);
+        let back = len / 2;
+        let mut values = vec![0.0_f32; self.values.len()];
+        for i in 0..self.size {
+            for j in 0..self.size {
+                let start = back.min(i.min(j));
+                let steps = (len - back + start).min(self.size - i.max(j) + start);
+                let sum = (0..steps).map(|k| self.get(i + k - start, j + k - start)).sum::<f32>();
+                values[i * self.size + j] = sum / steps as f32;
+            }
+        }
+        Self { size: self.size, values }
+    }
+
+    /// Time-lag structure features: row `i` holds the similarity of frame
+    /// `i` to frame `i + lag` (circular) for every lag.
+    #[must_use]
+    pub fn structure_features(&self) -> FeatureBuffer {
+        let n = self.size;
+        let mut out = FeatureBuffer::with_capacity(n.max(1), n);
+        let mut row = vec![0.0_f32; n];
+        for i in 0..n {
+            for (lag, v) in row.iter_mut().enumerate() {
+                *v = self.get(i, (i + lag) % n);
+            }
+            out.push_frame(&row);
+        }
+        out
+    }
+
+    /// Foote novelty with a Gaussian-tapered checkerboard kernel.
+    ///
+    /// `half` is the kernel half-width in frames. Value `i` scores a boundary
+    /// between frames `i - 1` and `i`; output is normalized to a maximum of 1.
+    #[must_use]
+    pub fn novelty(&self, half: usize) -> Vec<f32> {
+        let half = half.max(1);
+        let sigma = half as f32 / 2.0;
+        let mut out = vec![0.0_f32; self.size];
+        // Only where the whole kernel fits; a truncated kernel is all
+        // positive quadrant and would fake a boundary at either edge.
+        for (i, slot) in
+            out.iter_mut().enumerate().take(self.size.saturating_sub(half) + 1).skip(half)
+        {
+            let mut sum = 0.0;
+            for x in i - half..i + half {
+                for y in i - half..i + half {
+                    let sign = if (x < i) == (y < i) { 1.0 } else { -1.0 };
+                    let da = x as f32 - i as f32 + 0.5;
+                    let db = y as f32 - i as f32 + 0.5;
+                    let taper = (-(da * da + db * db) / (2.0 * sigma * sigma)).exp();
+                    sum += sign * taper * self.get(x, y);
+                }
+            }
+            *slot = sum.max(0.0);
+        }
+        let max = out.iter().copied().fold(0.0_f32, f32::max);
+        if max > 0.0 {
+            for v in &mut out {
+                *v /= max;
+            }
+        }
+        out
+    }
+
+    /// Similarity of frame ranges `a` and `b` (half-open) along their
+    /// best-aligned diagonal, penalized for length mismatch.
+    #[must_use]
+    pub fn range_similarity(&self, a: (usize, usize), b: (usize, usize)) -> f32 {
+        let (la, lb) = (a.1.saturating_sub(a.0), b.1.saturating_sub(b.0));
+        let len = la.min(lb);
+        if len == 0 {
+            return 0.0;
+        }
+        let slack = la.max(lb) - len;
+        let mut best = 0.0_f32;
+        for shift in 0..=slack {
+            let (sa, sb) = if la >= lb { (a.0 + shift, b.0) } else { (a.0, b.0 + shift) };
+            let mean = (0..len).map(|k| self.get(sa + k, sb + k)).sum::<f32>() / len as f32;
+            best = best.max(mean);
+        }
+        best * (len as f32 / la.max(lb) as f32).sqrt()
+    }
+}
+
+/// Local maxima of `novelty` rising at least `threshold` above the mean
+/// of their `min_gap` neighbourhood; strongest first, kept `min_gap` frames
+/// apart and from either end. Returned in time order.
+#[must_use]
+pub fn pick_peaks(novelty: &[f32], threshold: f32, min_gap: usize) -> Vec<usize> {
+    let n = novelty.len();
+    let local_mean = |i: usize| {
+        let (from, to) = (i.saturating_sub(min_gap), (i + min_gap + 1).min(n));
+        novelty[from..to].iter().sum::<f32>() / (to - from) as f32
+    };
+    let mut candidates: Vec<usize> = (min_gap..n.saturating_sub(min_gap).saturating_add(1))
+        .filter(|&i| i < n)
+        .filter(|&i| {
+            let left = i == 0 || novelty[i] >= novelty[i - 1];
+            let right = i + 1 >= n || novelty[i] >= novelty[i + 1];
+            left && right && novelty[i] - local_mean(i) >= threshold
+        })
+        .collect();
+    candidates.sort_by(|a, b| novelty[*b].total_cmp(&novelty[*a]).then(a.cmp(b)));
+
+    let mut peaks: Vec<usize> = Vec::new();
+    for c in candidates {
+        if peaks.iter().all(|&p| p.abs_diff(c) >= min_gap) {
+            peaks.push(c);
+        }
+    }
+    peaks.sort_unstable();
+    peaks
+}
+
+/// Centered moving average of feature frames over `width` frames.
+#[must_use]
+pub fn context_average(features: &FeatureBuffer, width: usize) -> FeatureBuffer {
+    let n = features.frames();
+    let dim = features.dim();
+    let before = width / 2;
+    let after = width - before;
+    let mut out = FeatureBuffer::with_capacity(dim, n);
+    let mut acc = vec![0.0_f32; dim];
+    for i in 0..n {
+        acc.fill(0.0);
+        let (from, to) = (i.saturating_sub(before), (i + after).min(n));
+        for f in from..to {
+            if let Some(row) = features.frame(f) {
+                acc.iter_mut().zip(row).for_each(|(a, v)| *a += v);
+            }
+        }
+        let count = (to - from).max(1) as f32;
+        for a in &mut acc {
+            *a /= count;
+        }
+        out.push_frame(&acc);
+    }
+    out
+}
+
+/// Cluster segments by repetition; returns each label and its similarity
+/// to the group's first occurrence (1.0 for first occurrences).
+fn label_repeats(
+    ssm: &SelfSimilarityMatrix,
+    ranges: &[(usize, usize)],
+    cfg: &SegmentConfig,
+) -> Vec<(SectionLabel, f32)> {
+    let mut groups: Vec<(usize, u8)> = Vec::new(); // (representative, variants so far)
+    let mut out = Vec::with_capacity(ranges.len());
+    for (i, &range) in ranges.iter().enumerate() {
+        let best = groups
+            .iter()
+            .enumerate()
+            .map(|(g, &(rep, _))| (g, ssm.range_similarity(ranges[rep], range)))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match best {
+            Some((g, sim)) if sim >= cfg.repeat_threshold => {
+                let variant = if sim >= cfg.exact_repeat_threshold {
+                    0
+                } else {
+                    groups[g].1 = groups[g].1.saturating_add(1);
+                    groups[g].1
+                };
+                out.push((SectionLabel { group: g as u8, variant }, sim));
+            }
+            _ => {
+                let group = groups.len().min(usize::from(u8::MAX)) as u8;
+                groups.push((i, 0));
+                out.push((SectionLabel { group, variant: 0 }, 1.0));
+            }
+        }
+    }
+    out
+}
+
+/// Verse/chorus heuristics over repetition labels.
+///
+/// - Chorus: the repeated group with the highest mean energy (most
+///   occurrences without audio).
+/// - Verse: the repeated group starting before the first chorus that covers
+///   the most frames.
+/// - Remaining first/last sections are intro/outro; one-off sections after
+///   the first chorus are bridges.
+fn assign_kinds(
+    labels: &[(SectionLabel, f32)],
+    ranges: &[(usize, usize)],
+    energies: &[f32],
+) -> Vec<SegmentKind> {
+    let n = labels.len();
+    let groups = labels.iter().map(|(l, _)| usize::from(l.group)).max().map_or(0, |g| g + 1);
+    let has_energy = energies.iter().any(|e| *e > 0.0);
+
+    // (occurrences, first index, frames, energy sum) per group.
+    let mut stats = vec![(0_usize, usize::MAX, 0_usize, 0.0_f32); groups];
+    for (i, (label, _)) in labels.iter().enumerate() {
+        let s = &mut stats[usize::from(label.group)];
+        s.0 += 1;
+        s.1 = s.1.min(i);
+        s.2 += ranges[i].1 - ranges[i].0;
+        s.3 += energies.get(i).copied().unwrap_or(0.0);
+    }
+
+    let repeated = || (0..groups).filter(|&g| stats[g].0 >= 2);
+    let chorus = repeated().max_by(|&a, &b| {
+        let key = |g: usize| {
+            if has_energy { stats[g].3 / stats[g].0 as f32 } else { stats[g].0 as f32 }
+        };
+        key(a).total_cmp(&key(b)).then(stats[a].2.cmp(&stats[b].2))
+    });
+    let chorus_first = chorus.map_or(usize::MAX, |g| stats[g].1);
+    let verse = repeated()
+        .filter(|&g| Some(g) != chorus && stats[g].1 < chorus_first)
+        .max_by(|&a, &b| stats[a].2.cmp(&stats[b].2).then(stats[b].1.cmp(&stats[a].1)));
+
+    labels
+        .iter()
+        .enumerate()
+        .map(|(i, (label, _))| {
+            let g = usize::from(label.group);
+            if Some(g) == chorus {
+                SegmentKind::Chorus
+            } else if Some(g) == verse {
+                SegmentKind::Verse
+            } else if n >= 3 && i == 0 {
+                SegmentKind::Intro
+            } else if n >= 3 && i == n - 1 {
+                SegmentKind::Outro
+            } else if stats[g].0 == 1 && i > chorus_first {
+                SegmentKind::Bridge
+            } else {
+                SegmentKind::Other(label.group)
+            }
+        })
+        .collect()
+}
+
+/// Feature frames on a fixed hop grid starting at `start`.
+pub(crate) struct FeatureGrid {
+    pub(crate) features: FeatureBuffer,
+    /// RMS energy per frame (all zero without audio).
+    energy: Vec<f32>,
+    pub(crate) start: i64,
+    pub(crate) hop: i64,
+    pub(crate) end: i64,
+}
+
+impl FeatureGrid {
+    pub(crate) fn build(
+        samples: &[f32],
+        sample_rate: u32,
+        chords: &[ChordEvent],
+        cfg: &SegmentConfig,
+    ) -> Option<Self> {
+        let sr = if sample_rate == 0 { 44_100 } else { sample_rate }; // stable default.
+        let hop = (cfg.ssm_hop_seconds * sr as f32) as i64;
+        let use_chroma = cfg.ssm_features.uses_chroma() && !samples.is_empty();
+        let use_mfcc = cfg.ssm_features.uses_mfcc() && !samples.is_empty();
+        let use_chords = cfg.ssm_features.uses_chords() && !chords.is_empty();
+        let use_audio = use_chroma || use_mfcc;
+        if hop <= 0 || !(use_audio || use_chords) {
+            return None;
+        }
+
+        let start = if use_audio { 0 } else { chords[0].onset.value() };
+        let mut end = if use_audio { samples.len() as i64 } else { start };
+        if use_chords {
+            end = end.max(chords.iter().map(|c| c.offset.value()).max().unwrap_or(start));
+        }
+        let frames = ((end - start + hop - 1) / hop) as usize;
+        if frames == 0 {
+            return None;
+        }
+
+        let chroma = if use_chroma {
+            let chroma_cfg = ChromaConfig::default();
+            let rows = chroma_frames(samples, sr, &chroma_cfg);
+            average_onto_grid(&rows, 0, chroma_cfg.hop_size, hop, frames)
+        } else {
+            Vec::new()
+        };
+        // c0 is overall loudness, which the energy heuristics already use.
+        let mfcc = if use_mfcc {
+            let mfcc_cfg = MfccConfig::default();
+            let rows = mfcc_frames(samples, sr, &mfcc_cfg);
+            average_onto_grid(&rows, 1, mfcc_cfg.hop_size, hop, frames)
+        } else {
+            Vec::new()
+        };
+        let mut energy = vec![0.0_f32; frames];
+        if use_audio {
+            for (f, e) in energy.iter_mut().enumerate() {
+                let a = (f as i64 * hop) as usize;
+                let b = (a + hop as usize).min(samples.len());
+                if b > a {
+                    *e = (samples[a..b].iter().map(|x| x * x).sum::<f32>() / (b - a) as f32).sqrt();
+                }
+            }
+        }
+
+        let mut harmony = vec![vec![0.0_f32; 12]; if use_chords { frames } else { 0 }];
+        if use_chords {
+            for c in chords {
+                let Some(state) = chord_state(&c.chord) else {
+                    continue;
+                };
+                let template = state_template(state);
+                let (on, off) = (c.onset.value() - start, c.offset.value() - start);
+                let first = (on.max(0) / hop) as usize;
+                for (f, acc) in harmony.iter_mut().enumerate().skip(first) {
+                    let (fa, fb) = (f as i64 * hop, (f as i64 + 1) * hop);
+                    if fa >= off {
+                        break;
+                    }
+                    let overlap = (fb.min(off) - fa.max(on)).max(0) as f32;
+                    for (a, t) in acc.iter_mut().zip(template) {
+                        *a += overlap * t;
+                    }
+                }
+            }
+        }
+
+        let dim = [&chroma, &mfcc, &harmony].iter().filter_map(|p| p.first()).map(Vec::len).sum();
+        let mut features = FeatureBuffer::with_capacity(dim, frames);
+        let mut row = Vec::with_capacity(dim);
+        for f in 0..frames {
+            row.clear();
+            for part in [chroma.get(f), mfcc.get(f), harmony.get(f)].into_iter().flatten() {
+                let norm = part.iter().map(|v| v * v).sum::<f32>().sqrt();
+                row.extend(part.iter().map(|v| if norm > 0.0 { v / norm } else { 0.0 }));
+            }
+            features.push_frame(&row);
+        }
+
+        Some(Self { features, energy, start, hop, end })
+    }
+
+    pub(crate) fn frame_start(&self, frame: usize) -> i64 {
+        self.start + frame as i64 * self.hop
+    }
+
+    fn mean_energy(&self, a: usize, b: usize) -> f32 {
+        let slice = &self.energy[a.min(self.energy.len())..b.min(self.energy.len())];
+        if slice.is_empty() { 0.0 } else { slice.iter().sum::<f32>() / slice.len() as f32 }
+    }
+}
+
+/// Mean of the `rows` (produced every `row_hop` samples) falling in each
+/// `hop`-sample grid frame, skipping the first `skip` dimensions.
+fn average_onto_grid(
+    rows: &FeatureBuffer,
+    skip: usize,
+    row_hop: usize,
+    hop: i64,
+    frames: usize,
+) -> Vec<Vec<f32>> {
+    let dim = rows.dim().saturating_sub(skip);
+    let mut out = vec![vec![0.0_f32; dim]; frames];
+    let mut counts = vec![0_u32; frames];
+    for r in 0..rows.frames() {
+        let f = ((r * row_hop) as i64 / hop) as usize;
+        if let (Some(row), Some(acc)) = (rows.frame(r), out.get_mut(f)) {
+            for (a, v) in acc.iter_mut().zip(&row[skip..]) {
+                *a += v;
+            }
+            counts[f] += 1;
+        }
+    }
+    for (acc, &c) in out.iter_mut().zip(&counts) {
+        if c > 0 {
+            for a in acc.iter_mut() {
+                *a /= c as f32;
+            }
+        }
+    }
+    out
+}
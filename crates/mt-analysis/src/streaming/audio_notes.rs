@@ -0,0 +1,120 @@
+//! Streaming audio → `NoteEvent` detection.
+//!
+//! Port of `SimpleAudioNoteAnalyzer`: the same RMS gate, zero-crossing
+//! pitch estimate and frame grouping, run frame by frame as samples arrive.
+//! A note is emitted when the frame that ends it completes; a note still
+//! sounding at `reset` is dropped.
+
+use crate::audio_note_detector::{estimate_freq_zc, freq_to_midi, rms};
+use crate::config::AudioNoteConfig;
+use crate::streaming::{FrameRing, Sink};
+use crate::traits::{StreamingAnalyzer, StreamingAudioAnalyzer};
+use mt_core::events::{NoteEvent, NoteId, TrackId};
+use mt_core::pitch::MidiNote;
+use mt_core::time::SampleTime;
+
+pub struct StreamingAudioNoteAnalyzer {
+    pub track: TrackId,
+    cfg: AudioNoteConfig,
+    sample_rate: u32,
+    frames: FrameRing,
+    /// Timeline position of the first sample since reset.
+    origin: Option<i64>,
+    /// Sounding note and the start of its first frame (samples since reset).
+    current: Option<(u8, u64)>,
+    next_id: u32,
+    /// Outputs that did not fit `out` since `prepare`.
+    dropped: u64,
+}
+
+impl StreamingAudioNoteAnalyzer {
+    pub const fn new(track: TrackId, cfg: AudioNoteConfig) -> Self {
+        Self {
+            track,
+            cfg,
+            sample_rate: 44_100,
+            frames: FrameRing::new(),
+            origin: None,
+            current: None,
+            next_id: 1,
+            dropped: 0,
+        }
+    }
+
+    fn frame_midi(&self) -> Option<u8> {
+        let frame = self.frames.frame();
+        if rms(frame) < self.cfg.rms_threshold {
+            return None;
+        }
+        estimate_freq_zc(frame, self.sample_rate).and_then(freq_to_midi)
+    }
+}
+
+impl StreamingAnalyzer for StreamingAudioNoteAnalyzer {
+    fn prepare(&mut self, sample_rate: u32, _max_block: usize) {
+        self.dropped = 0;
+        self.sample_rate = if sample_rate == 0 { 44_100 } else { sample_rate }; // stable default.
+        self.frames.prepare(self.cfg.frame_size, self.cfg.hop_size);
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.frames.reset();
+        self.origin = None;
+        self.current = None;
+        self.next_id = 1;
+    }
+
+    fn latency_samples(&self) -> u32 {
+        self.frames.frame_len() as u32
+    }
+
+    fn dropped_outputs(&self) -> u64 {
+        self.dropped
+    }
+}
+
+impl StreamingAudioAnalyzer for StreamingAudioNoteAnalyzer {
+    type Output = NoteEvent;
+
+    fn process_block(
+        &mut self,
+        block_start: SampleTime,
+        samples: &[f32],
+        out: &mut [NoteEvent],
+    ) -> usize {
+        let mut sink = Sink::new(out);
+        let origin = *self.origin.get_or_insert(block_start.value());
+        let min_note_samples = (self.cfg.min_note_seconds * self.sample_rate as f32) as u64;
+        let frame_len = self.frames.frame_len() as u64;
+
+        for &x in samples {
+            let Some(frame_start) = self.frames.push(x) else {
+                continue;
+            };
+            let midi = self.frame_midi();
+            match (self.current, midi) {
+                (None, Some(n)) => self.current = Some((n, frame_start)),
+                (Some((n0, _)), Some(n1)) if n0 == n1 => {}
+                (Some((n0, start)), _) => {
+                    let end = frame_start + frame_len;
+                    if end - start >= min_note_samples {
+                        sink.push(NoteEvent {
+                            id: NoteId(self.next_id),
+                            track: self.track,
+                            onset: SampleTime::new(origin + start as i64),
+                            offset: SampleTime::new(origin + end as i64),
+                            note: MidiNote::new(n0).unwrap(),
+                            velocity: 100,
+                        });
+                        self.next_id = self.next_id.wrapping_add(1);
+                    }
+                    self.current = None;
+                }
+                (None, None) => {}
+            }
+        }
+        self.dropped += sink.dropped();
+        sink.len()
+    }
+}
@@ -0,0 +1,117 @@
+//! Streaming energy-based segmentation.
+//!
+//! Port of `EnergySegmenter`: mean energy per non-overlapping window, with
+//! a boundary where it jumps by more than half the previous window's. Each
+//! segment is emitted when the boundary closing it is found; the open
+//! segment is dropped on `reset`.
+
+use crate::config::SegmentConfig;
+use crate::streaming::Sink;
+use crate::traits::{StreamingAnalyzer, StreamingAudioAnalyzer};
+use mt_core::events::{SegmentEvent, SegmentKind};
+use mt_core::time::SampleTime;
+
+pub struct StreamingEnergySegmenter {
+    cfg: SegmentConfig,
+    window: usize,
+    min_len: i64,
+    origin: Option<i64>,
+    /// Running sum of squares and sample count of the open window.
+    sum: f32,
+    filled: usize,
+    windows: u64,
+    last_energy: f32,
+    segment_start: i64,
+    /// Outputs that did not fit `out` since `prepare`.
+    dropped: u64,
+}
+
+impl StreamingEnergySegmenter {
+    pub const fn new(cfg: SegmentConfig) -> Self {
+        Self {
+            cfg,
+            window: 0,
+            min_len: 0,
+            origin: None,
+            sum: 0.0,
+            filled: 0,
+            windows: 0,
+            last_energy: 0.0,
+            segment_start: 0,
+            dropped: 0,
+        }
+    }
+}
+
+impl StreamingAnalyzer for StreamingEnergySegmenter {
+    fn prepare(&mut self, sample_rate: u32, _max_block: usize) {
+        self.dropped = 0;
+        let sr = if sample_rate == 0 { 44_100 } else { sample_rate }; // stable default.
+        self.window = ((self.cfg.window_seconds * sr as f32) as usize).max(1);
+        self.min_len = (self.cfg.min_segment_seconds * sr as f32) as i64;
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.origin = None;
+        self.sum = 0.0;
+        self.filled = 0;
+        self.windows = 0;
+        self.last_energy = 0.0;
+        self.segment_start = 0;
+    }
+
+    fn latency_samples(&self) -> u32 {
+        self.window as u32
+    }
+
+    fn dropped_outputs(&self) -> u64 {
+        self.dropped
+    }
+}
+
+impl StreamingAudioAnalyzer for StreamingEnergySegmenter {
+    type Output = SegmentEvent;
+
+    fn process_block(
+        &mut self,
+        block_start: SampleTime,
+        samples: &[f32],
+        out: &mut [SegmentEvent],
+    ) -> usize {
+        let mut sink = Sink::new(out);
+        let origin = *self.origin.get_or_insert(block_start.value());
+
+        for &s in samples {
+            self.sum += s * s;
+            self.filled += 1;
+            if self.filled < self.window {
+                continue;
+            }
+            let energy = self.sum / self.window as f32;
+            let pos = (self.windows * self.window as u64) as i64;
+            self.sum = 0.0;
+            self.filled = 0;
+            self.windows += 1;
+            if self.windows == 1 {
+                self.last_energy = energy;
+                continue;
+            }
+
+            let diff = (energy - self.last_energy).abs();
+            if diff > self.last_energy * 0.5 && pos - self.segment_start >= self.min_len {
+                sink.push(SegmentEvent {
+                    kind: SegmentKind::Other(0),
+                    label: None,
+                    onset: SampleTime::new(origin + self.segment_start),
+                    offset: SampleTime::new(origin + pos),
+                    confidence_x1000: 800,
+                });
+                self.segment_start = pos;
+            }
+            self.last_energy = energy.max(1e-9);
+        }
+        self.dropped += sink.dropped();
+        sink.len()
+    }
+}
@@ -0,0 +1,137 @@
+//! Streaming key estimation.
+//!
+//! Port of `HistogramKeyAnalyzer`: a running pitch-class histogram of every
+//! completed note since reset. After each block the best key is re-scored;
+//! a `KeyEvent` is emitted once the notes span `min_region_seconds`, and
+//! again whenever the best key changes.
+//!
+//! `DurationVelocityBass` weighting needs simultaneous notes and is applied
+//! as `DurationVelocity` here.
+
+use crate::confidence::clamp01_to_confidence_x1000;
+use crate::config::{KeyConfig, KeyCorrelation, KeyWeighting};
+use crate::key_detector::histogram::best_key;
+use crate::key_detector::profiles::profile_pair;
+use crate::streaming::Sink;
+use crate::traits::{StreamingAnalyzer, StreamingNoteAnalyzer};
+use mt_core::events::{KeyEvent, NoteEvent};
+use mt_core::key::{Key, KeyMode};
+use mt_core::pitch::PitchClass;
+use mt_core::time::SampleTime;
+
+pub struct StreamingKeyAnalyzer {
+    cfg: KeyConfig,
+    sample_rate: u32,
+    hist: [f32; 12],
+    first_onset: Option<i64>,
+    last_offset: i64,
+    current: Option<Key>,
+    /// Outputs that did not fit `out` since `prepare`.
+    dropped: u64,
+}
+
+impl StreamingKeyAnalyzer {
+    pub const fn new(cfg: KeyConfig) -> Self {
+        Self {
+            cfg,
+            sample_rate: 44_100,
+            hist: [0.0; 12],
+            first_onset: None,
+            last_offset: 0,
+            current: None,
+            dropped: 0,
+        }
+    }
+
+    fn add(&mut self, n: &NoteEvent) {
+        let pc = n.note.pitch_class().as_u8() as usize;
+        let dur = (n.offset.value() - n.onset.value()).max(1) as f32;
+        let vel = f32::from(n.velocity) / 127.0;
+        self.hist[pc] += match self.cfg.weighting {
+            KeyWeighting::Count => 1.0,
+            KeyWeighting::Duration => dur,
+            KeyWeighting::DurationVelocity | KeyWeighting::DurationVelocityBass { .. } => dur * vel,
+        };
+        self.first_onset.get_or_insert(n.onset.value());
+        self.last_offset = self.last_offset.max(n.offset.value());
+    }
+}
+
+impl StreamingAnalyzer for StreamingKeyAnalyzer {
+    fn prepare(&mut self, sample_rate: u32, _max_block: usize) {
+        self.dropped = 0;
+        self.sample_rate = if sample_rate == 0 { 44_100 } else { sample_rate }; // stable default.
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.hist = [0.0; 12];
+        self.first_onset = None;
+        self.last_offset = 0;
+        self.current = None;
+    }
+
+    fn latency_samples(&self) -> u32 {
+        (self.cfg.min_region_seconds * self.sample_rate as f32) as u32
+    }
+
+    fn dropped_outputs(&self) -> u64 {
+        self.dropped
+    }
+}
+
+impl StreamingNoteAnalyzer for StreamingKeyAnalyzer {
+    type Output = KeyEvent;
+
+    fn process_block(
+        &mut self,
+        _block_start: SampleTime,
+        notes: &[NoteEvent],
+        out: &mut [KeyEvent],
+    ) -> usize {
+        let mut sink = Sink::new(out);
+        let Some(block_onset) = notes.first().map(|n| n.onset) else {
+            return 0;
+        };
+        for n in notes {
+            self.add(n);
+        }
+        let Some(first) = self.first_onset else {
+            return 0;
+        };
+        let min_samples = (self.cfg.min_region_seconds * self.sample_rate as f32) as i64;
+        if self.last_offset - first < min_samples {
+            return 0;
+        }
+
+        let (major, minor) = profile_pair(&self.cfg.profile);
+        let (maj_tonic, maj_score) = best_key(&self.hist, major, self.cfg.correlation);
+        let (min_tonic, min_score) = best_key(&self.hist, minor, self.cfg.correlation);
+        let (mode, tonic, score) = if maj_score >= min_score {
+            (KeyMode::Major, maj_tonic, maj_score)
+        } else {
+            (KeyMode::Minor, min_tonic, min_score)
+        };
+        let key = Key::new(PitchClass::from_unchecked(tonic), mode);
+        if self.current == Some(key) {
+            return 0;
+        }
+
+        // The first estimate covers everything heard so far.
+        let position = if self.current.is_none() { SampleTime::new(first) } else { block_onset };
+        self.current = Some(key);
+        let confidence_x1000 = match self.cfg.correlation {
+            KeyCorrelation::DotProduct => {
+                if score <= 0.0 {
+                    0
+                } else {
+                    1000
+                }
+            }
+            KeyCorrelation::Pearson => clamp01_to_confidence_x1000(score),
+        };
+        sink.push(KeyEvent { key, position, confidence_x1000 });
+        self.dropped += sink.dropped();
+        sink.len()
+    }
+}
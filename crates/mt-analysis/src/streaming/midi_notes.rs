@@ -0,0 +1,138 @@
+//! Streaming MIDI → `NoteEvent` pairing.
+//!
+//! Same pairing and pedal rules as `SimpleMidiNoteAnalyzer`, applied block
+//! by block. Active notes live in a channel × key table allocated by
+//! `prepare`.
+//!
+//! A block emits at most one note per event, except a pedal release, which
+//! can end every held key of its channel (128). Notes that do not fit `out`
+//! are gone from the table; they are counted in `dropped_outputs`.
+
+use crate::config::MidiNoteConfig;
+use crate::streaming::Sink;
+use crate::traits::{StreamingAnalyzer, StreamingMidiAnalyzer};
+use mt_core::events::{NoteEvent, NoteId, TrackId};
+use mt_core::midi::{MidiEventKind, TimedMidiEvent};
+use mt_core::pedal::{self, PedalState};
+use mt_core::pitch::MidiNote;
+use mt_core::time::SampleTime;
+
+pub struct StreamingMidiNoteAnalyzer {
+    pub track: TrackId,
+    cfg: MidiNoteConfig,
+    /// `(on_time, velocity)` at `channel * 128 + key`.
+    active: Vec<Option<(SampleTime, u8)>>,
+    pedals: PedalState,
+    next_id: u32,
+    /// Outputs that did not fit `out` since `prepare`.
+    dropped: u64,
+}
+
+impl StreamingMidiNoteAnalyzer {
+    pub const fn new(track: TrackId, cfg: MidiNoteConfig) -> Self {
+        Self {
+            track,
+            cfg,
+            active: Vec::new(),
+            pedals: PedalState::new(cfg.pedal),
+            next_id: 1,
+            dropped: 0,
+        }
+    }
+
+    fn note(&mut self, on: SampleTime, offset: SampleTime, key: u8, vel: u8) -> NoteEvent {
+        let note = NoteEvent {
+            id: NoteId(self.next_id),
+            track: self.track,
+            onset: on,
+            offset,
+            // Keys are masked to 0..=127 by the caller.
+            note: MidiNote::new(key).unwrap(),
+            velocity: vel,
+        };
+        self.next_id = self.next_id.wrapping_add(1);
+        note
+    }
+}
+
+impl StreamingMidiNoteAnalyzer {
+    /// End the note at `slot`, if any, at `t`.
+    fn close(&mut self, slot: usize, t: SampleTime, sink: &mut Sink<'_, NoteEvent>) {
+        if let Some((on, vel)) = self.active[slot].take()
+            && t.value() > on.value()
+        {
+            let note = self.note(on, t, (slot % 128) as u8, vel);
+            sink.push(note);
+        }
+    }
+}
+
+impl StreamingAnalyzer for StreamingMidiNoteAnalyzer {
+    fn prepare(&mut self, _sample_rate: u32, _max_block: usize) {
+        self.dropped = 0;
+        self.active = vec![None; 16 * 128];
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.active.fill(None);
+        self.pedals.reset();
+        self.next_id = 1;
+    }
+
+    fn latency_samples(&self) -> u32 {
+        0
+    }
+
+    fn dropped_outputs(&self) -> u64 {
+        self.dropped
+    }
+}
+
+impl StreamingMidiAnalyzer for StreamingMidiNoteAnalyzer {
+    type Output = NoteEvent;
+
+    fn process_block(
+        &mut self,
+        _block_start: SampleTime,
+        events: &[TimedMidiEvent],
+        out: &mut [NoteEvent],
+    ) -> usize {
+        let mut sink = Sink::new(out);
+        for &TimedMidiEvent { position: t, event: ev, .. } in events {
+            let key = ev.data1 & 0x7F;
+            let slot = usize::from(ev.channel.value() & 0x0F) * 128 + usize::from(key);
+            if slot >= self.active.len() {
+                continue; // not prepared.
+            }
+            match ev.kind {
+                MidiEventKind::NoteOn if ev.data2 > 0 => {
+                    // Retrigger: close the sounding note unless within tolerance.
+                    if let Some((on, vel)) = self.active[slot].take()
+                        && (self.cfg.retrigger_tolerance_samples == 0
+                            || t.value() - on.value() > self.cfg.retrigger_tolerance_samples)
+                    {
+                        let note = self.note(on, t, key, vel);
+                        sink.push(note);
+                    }
+                    self.pedals.note_on(ev.channel.value(), key);
+                    self.active[slot] = Some((t, ev.data2));
+                }
+                MidiEventKind::NoteOff | MidiEventKind::NoteOn => {
+                    if self.pedals.note_off(ev.channel.value(), key) {
+                        self.close(slot, t, &mut sink);
+                    }
+                }
+                MidiEventKind::ControlChange => {
+                    let ch = ev.channel.value();
+                    for k in pedal::keys(self.pedals.control_change(ch, ev.data1, ev.data2)) {
+                        self.close(usize::from(ch & 0x0F) * 128 + usize::from(k), t, &mut sink);
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.dropped += sink.dropped();
+        sink.len()
+    }
+}
@@ -0,0 +1,119 @@
+//! Streaming ports of the simple analyzers for real-time use.
+//!
+//! Each analyzer implements `StreamingAnalyzer` plus one input-domain trait
+//! (`StreamingAudioAnalyzer`, `StreamingMidiAnalyzer`,
+//! `StreamingNoteAnalyzer`). Buffers are sized in `prepare`; processing
+//! works on fixed-size state only, so it is safe on an audio thread.
+//! Outputs that do not fit the caller's `out` slice cannot be replayed;
+//! they are counted in `StreamingAnalyzer::dropped_outputs`.
+//!
+//! Results match the batch analyzers on the same input where the algorithm
+//! allows; differences are noted per analyzer.
+
+pub mod audio_notes;
+pub mod energy_segments;
+pub mod key;
+pub mod midi_notes;
+pub mod swing;
+pub mod tempo;
+
+pub use audio_notes::StreamingAudioNoteAnalyzer;
+pub use energy_segments::StreamingEnergySegmenter;
+pub use key::StreamingKeyAnalyzer;
+pub use midi_notes::StreamingMidiNoteAnalyzer;
+pub use swing::StreamingSwingAnalyzer;
+pub use tempo::StreamingTempoAnalyzer;
+
+/// Sliding analysis window over a sample stream.
+///
+/// Frame `k` covers samples `k * hop .. k * hop + frame` since the last
+/// reset, as in the batch analyzers.
+pub(crate) struct FrameRing {
+    ring: Vec<f32>,
+    frame: Vec<f32>,
+    hop: usize,
+    write: usize,
+    seen: u64,
+}
+
+impl FrameRing {
+    pub(crate) const fn new() -> Self {
+        Self { ring: Vec::new(), frame: Vec::new(), hop: 1, write: 0, seen: 0 }
+    }
+
+    /// Allocate for `frame`-sample windows every `hop` samples.
+    pub(crate) fn prepare(&mut self, frame: usize, hop: usize) {
+        let frame = frame.max(1);
+        self.ring = vec![0.0; frame];
+        self.frame = vec![0.0; frame];
+        self.hop = hop.max(1);
+        self.reset();
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.ring.fill(0.0);
+        self.write = 0;
+        self.seen = 0;
+    }
+
+    /// Push one sample; returns the completed frame's start (in samples
+    /// since reset) when this sample closes a frame.
+    pub(crate) fn push(&mut self, x: f32) -> Option<u64> {
+        let len = self.ring.len();
+        if len == 0 {
+            return None;
+        }
+        self.ring[self.write] = x;
+        self.write = (self.write + 1) % len;
+        self.seen += 1;
+
+        let start = self.seen.checked_sub(len as u64)?;
+        if start % self.hop as u64 != 0 {
+            return None;
+        }
+        let (tail, head) = self.ring.split_at(self.write);
+        self.frame[..head.len()].copy_from_slice(head);
+        self.frame[head.len()..].copy_from_slice(tail);
+        Some(start)
+    }
+
+    /// The most recently completed frame, oldest sample first.
+    pub(crate) fn frame(&self) -> &[f32] {
+        &self.frame
+    }
+
+    pub(crate) fn frame_len(&self) -> usize {
+        self.ring.len()
+    }
+}
+
+/// Writes into a caller-provided slice, counting what does not fit.
+pub(crate) struct Sink<'a, T> {
+    out: &'a mut [T],
+    len: usize,
+    dropped: u64,
+}
+
+impl<'a, T> Sink<'a, T> {
+    pub(crate) fn new(out: &'a mut [T]) -> Self {
+        Self { out, len: 0, dropped: 0 }
+    }
+
+    pub(crate) fn push(&mut self, value: T) {
+        if let Some(slot) = self.out.get_mut(self.len) {
+            *slot = value;
+            self.len += 1;
+        } else {
+            self.dropped += 1;
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Values pushed after `out` was full.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
@@ -0,0 +1,121 @@
+//! Streaming swing estimation.
+//!
+//! Works on onset pairs like `SimpleSwingAnalyzer`, but reports the
+//! `SwingEvent` ratio directly:
+//! - Two consecutive inter-onset gaps that together span one beat (±25%)
+//!   form an 8th pair; its ratio is long / (long + short).
+//! - The running mean is emitted once `min_notes` notes have been seen, and
+//!   again when it moves by at least 0.01.
+//!
+//! The beat comes from `set_tempo`; without a tempo nothing is emitted.
+
+use crate::config::SwingConfig;
+use crate::streaming::Sink;
+use crate::traits::{StreamingAnalyzer, StreamingNoteAnalyzer};
+use mt_core::events::{NoteEvent, SwingEvent, TempoEvent};
+use mt_core::time::SampleTime;
+
+pub struct StreamingSwingAnalyzer {
+    cfg: SwingConfig,
+    sample_rate: u32,
+    beat_samples: Option<f32>,
+    /// Last two onsets, oldest first.
+    onsets: [Option<i64>; 2],
+    notes_seen: usize,
+    ratio_sum: f32,
+    pairs: u32,
+    last_x1000: Option<u16>,
+    /// Outputs that did not fit `out` since `prepare`.
+    dropped: u64,
+}
+
+impl StreamingSwingAnalyzer {
+    pub const fn new(cfg: SwingConfig) -> Self {
+        Self {
+            cfg,
+            sample_rate: 44_100,
+            beat_samples: None,
+            onsets: [None; 2],
+            notes_seen: 0,
+            ratio_sum: 0.0,
+            pairs: 0,
+            last_x1000: None,
+            dropped: 0,
+        }
+    }
+
+    /// Set the beat length from the current tempo.
+    pub fn set_tempo(&mut self, tempo: TempoEvent) {
+        let bpm = tempo.bpm_x1000 as f32 / 1000.0;
+        self.beat_samples = (bpm > 0.0).then(|| 60.0 / bpm * self.sample_rate as f32);
+    }
+
+    fn add_onset(&mut self, t: i64) {
+        if let ([Some(a), Some(b)], Some(beat)) = (self.onsets, self.beat_samples) {
+            let first = (b - a) as f32;
+            let second = (t - b) as f32;
+            let span = first + second;
+            if first > 0.0 && second > 0.0 && (span - beat).abs() <= 0.25 * beat {
+                self.ratio_sum += first.max(second) / span;
+                self.pairs += 1;
+            }
+        }
+        self.onsets = [self.onsets[1], Some(t)];
+        self.notes_seen += 1;
+    }
+}
+
+impl StreamingAnalyzer for StreamingSwingAnalyzer {
+    fn prepare(&mut self, sample_rate: u32, _max_block: usize) {
+        self.dropped = 0;
+        self.sample_rate = if sample_rate == 0 { 44_100 } else { sample_rate }; // stable default.
+        self.reset();
+    }
+
+    /// Clears the onset history; the tempo is kept.
+    fn reset(&mut self) {
+        self.onsets = [None; 2];
+        self.notes_seen = 0;
+        self.ratio_sum = 0.0;
+        self.pairs = 0;
+        self.last_x1000 = None;
+    }
+
+    fn latency_samples(&self) -> u32 {
+        0
+    }
+
+    fn dropped_outputs(&self) -> u64 {
+        self.dropped
+    }
+}
+
+impl StreamingNoteAnalyzer for StreamingSwingAnalyzer {
+    type Output = SwingEvent;
+
+    fn process_block(
+        &mut self,
+        _block_start: SampleTime,
+        notes: &[NoteEvent],
+        out: &mut [SwingEvent],
+    ) -> usize {
+        let mut sink = Sink::new(out);
+        for n in notes {
+            self.add_onset(n.onset.value());
+        }
+        let Some(last) = notes.last() else {
+            return 0;
+        };
+        if self.notes_seen < self.cfg.min_notes || self.pairs == 0 {
+            return 0;
+        }
+
+        let ratio_x1000 = (self.ratio_sum / self.pairs as f32 * 1000.0).round() as u16;
+        if self.last_x1000.is_none_or(|prev| prev.abs_diff(ratio_x1000) >= 10) {
+            self.last_x1000 = Some(ratio_x1000);
+            sink.push(SwingEvent { position: last.onset, ratio_x1000 });
+        }
+        self.dropped += sink.dropped();
+        sink.len()
+    }
+}
@@ -0,0 +1,143 @@
+//! Streaming tempo estimation.
+//!
+//! Port of `SimpleTempoMeterAnalyzer` over a sliding window:
+//! - RMS envelope frames are kept in a ring covering four beats at the
+//!   slowest allowed tempo.
+//! - Once the ring is full, the tempo is re-estimated about once a second by
+//!   the same autocorrelation; a `TempoEvent` is emitted when it moves by at
+//!   least 1 BPM.
+//!
+//! Unlike the batch analyzer, lags are measured in envelope frames
+//! (`sample_rate / hop_size` per second).
+
+use crate::audio_note_detector::rms;
+use crate::config::TempoConfig;
+use crate::streaming::{FrameRing, Sink};
+use crate::tempo_meter_detector::estimate_bpm_from_envelope;
+use crate::traits::{StreamingAnalyzer, StreamingAudioAnalyzer};
+use mt_core::events::TempoEvent;
+use mt_core::time::SampleTime;
+
+/// Beats at the slowest tempo kept in the envelope history.
+const HISTORY_BEATS: f32 = 4.0;
+
+pub struct StreamingTempoAnalyzer {
+    cfg: TempoConfig,
+    hop_rate: f32,
+    frames: FrameRing,
+    envelope: Vec<f32>,
+    /// Envelope in time order, rebuilt before each estimate.
+    scratch: Vec<f32>,
+    env_write: usize,
+    env_seen: usize,
+    estimate_every: usize,
+    origin: Option<i64>,
+    last_bpm_x1000: Option<u32>,
+    /// Outputs that did not fit `out` since `prepare`.
+    dropped: u64,
+}
+
+impl StreamingTempoAnalyzer {
+    pub const fn new(cfg: TempoConfig) -> Self {
+        Self {
+            cfg,
+            hop_rate: 0.0,
+            frames: FrameRing::new(),
+            envelope: Vec::new(),
+            scratch: Vec::new(),
+            env_write: 0,
+            env_seen: 0,
+            estimate_every: 1,
+            origin: None,
+            last_bpm_x1000: None,
+            dropped: 0,
+        }
+    }
+
+    fn estimate(&mut self) -> u32 {
+        let (tail, head) = self.envelope.split_at(self.env_write);
+        self.scratch[..head.len()].copy_from_slice(head);
+        self.scratch[head.len()..].copy_from_slice(tail);
+        let range = self.cfg.tempo_range;
+        let bpm =
+            estimate_bpm_from_envelope(&self.scratch, self.hop_rate, range.min_bpm, range.max_bpm);
+        (bpm * 1000.0 + 0.5) as u32
+    }
+}
+
+impl StreamingAnalyzer for StreamingTempoAnalyzer {
+    fn prepare(&mut self, sample_rate: u32, _max_block: usize) {
+        self.dropped = 0;
+        let sr = if sample_rate == 0 { 44_100 } else { sample_rate }; // stable default.
+        let hop = self.cfg.hop_size.max(1);
+        self.hop_rate = sr as f32 / hop as f32;
+        self.frames.prepare(self.cfg.frame_size, hop);
+
+        let slowest_beat = 60.0 / self.cfg.tempo_range.min_bpm.max(1.0) * self.hop_rate;
+        let history = (HISTORY_BEATS * slowest_beat).ceil().max(2.0) as usize;
+        self.envelope = vec![0.0; history];
+        self.scratch = vec![0.0; history];
+        self.estimate_every = (self.hop_rate.round() as usize).max(1);
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.frames.reset();
+        self.envelope.fill(0.0);
+        self.env_write = 0;
+        self.env_seen = 0;
+        self.origin = None;
+        self.last_bpm_x1000 = None;
+    }
+
+    fn latency_samples(&self) -> u32 {
+        let hop = self.cfg.hop_size.max(1);
+        (self.frames.frame_len() + self.envelope.len().saturating_sub(1) * hop) as u32
+    }
+
+    fn dropped_outputs(&self) -> u64 {
+        self.dropped
+    }
+}
+
+impl StreamingAudioAnalyzer for StreamingTempoAnalyzer {
+    type Output = TempoEvent;
+
+    fn process_block(
+        &mut self,
+        block_start: SampleTime,
+        samples: &[f32],
+        out: &mut [TempoEvent],
+    ) -> usize {
+        let mut sink = Sink::new(out);
+        let origin = *self.origin.get_or_insert(block_start.value());
+        if self.envelope.is_empty() {
+            return 0;
+        }
+
+        for &x in samples {
+            let Some(frame_start) = self.frames.push(x) else {
+                continue;
+            };
+            self.envelope[self.env_write] = rms(self.frames.frame());
+            self.env_write = (self.env_write + 1) % self.envelope.len();
+            self.env_seen += 1;
+
+            let full = self.env_seen >= self.envelope.len();
+            if !full || !(self.env_seen - self.envelope.len()).is_multiple_of(self.estimate_every) {
+                continue;
+            }
+            let bpm_x1000 = self.estimate();
+            let moved = self.last_bpm_x1000.is_none_or(|last| last.abs_diff(bpm_x1000) >= 1000);
+            if moved {
+                self.last_bpm_x1000 = Some(bpm_x1000);
+                sink.push(TempoEvent {
+                    position: SampleTime::new(origin + frame_start as i64),
+                    bpm_x1000,
+                });
+            }
+        }
+        self.dropped += sink.dropped();
+        sink.len()
+    }
+}
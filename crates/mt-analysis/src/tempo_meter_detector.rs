@@ -26,7 +26,7 @@ impl TempoMeterAnalyzer for SimpleTempoMeterAnalyzer {
 
         let bpm = estimate_bpm_from_envelope(
             &envelope,
-            sample_rate,
+            sample_rate as f32,
             cfg.tempo_range.min_bpm,
             cfg.tempo_range.max_bpm,
         );
@@ -57,9 +57,9 @@ fn compute_envelope(samples: &[f32], frame: usize, hop: usize) -> Vec<f32> {
 }
 
 /// Naive autocorrelation-based tempo estimate in [min_bpm, max_bpm].
-fn estimate_bpm_from_envelope(env: &[f32], sample_rate: u32, min_bpm: f32, max_bpm: f32) -> f32 {
-    // Envelope hop rate:
-    let hop_rate = sample_rate as f32;
+///
+/// `hop_rate` is the envelope rate in frames per second.
+pub(crate) fn estimate_bpm_from_envelope(env: &[f32], hop_rate: f32, min_bpm: f32, max_bpm: f32) -> f32 {
     let min_period = (60.0 / max_bpm) * hop_rate;
     let max_period = (60.0 / min_bpm) * hop_rate;
 
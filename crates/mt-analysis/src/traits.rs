@@ -5,15 +5,17 @@
 //! for synchronous, batch-style analysis.
 
 use crate::config::{
-    AudioNoteConfig, ChordConfig, KeyConfig, MidiNoteConfig, SegmentConfig,
-    SwingConfig, TempoConfig,
+    AudioNoteConfig, CadenceConfig, ChordConfig, ChromaConfig, KeyConfig, MidiNoteConfig,
+    SegmentConfig, SwingConfig, TempoConfig,
 };
 use mt_core::events::{
-    ChordEvent, KeyEvent, NoteEvent, SegmentEvent, TempoEvent,
+    CadenceEvent, ChordEvent, KeyEvent, MeterEvent, NoteEvent, SegmentEvent, TempoEvent,
 };
-use mt_core::midi::MidiEvent;
+use mt_core::midi::TimedMidiEvent;
+use mt_core::time::SampleTime;
 
 /// Simple tempo range definition used by tempo detectors.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug)]
 pub struct TempoRange {
     pub min_bpm: f32,
@@ -85,9 +87,13 @@ extern crate alloc;
 
 use alloc::vec::Vec;
 
-/// Detects normalized notes from MIDI events.
+/// Detects normalized notes from MIDI events sorted by position.
 pub trait MidiNoteAnalyzer {
-    fn detect_midi_notes(&self, events: &[MidiEvent], cfg: &MidiNoteConfig) -> Vec<NoteEvent>;
+    fn detect_midi_notes(
+        &self,
+        events: &[TimedMidiEvent],
+        cfg: &MidiNoteConfig,
+    ) -> Vec<NoteEvent>;
 }
 
 /// Detects notes from audio samples.
@@ -120,6 +126,30 @@ pub trait ChordAnalyzer {
     fn detect_chords(&self, notes: &[NoteEvent], cfg: &ChordConfig) -> Vec<ChordEvent>;
 }
 
+/// Estimates chord timeline directly from audio (no note transcription).
+pub trait AudioChordAnalyzer {
+    fn detect_audio_chords(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        chroma: &ChromaConfig,
+        cfg: &ChordConfig,
+    ) -> Vec<ChordEvent>;
+}
+
+/// Finds cadences in a chord timeline against local keys and the bar grid.
+pub trait CadenceAnalyzer {
+    fn detect_cadences(
+        &self,
+        chords: &[ChordEvent],
+        keys: &[KeyEvent],
+        tempo_events: &[TempoEvent],
+        meter_events: &[MeterEvent],
+        sample_rate: u32,
+        cfg: &CadenceConfig,
+    ) -> Vec<CadenceEvent>;
+}
+
 /// Estimates swing ratio.
 pub trait SwingAnalyzer {
     fn detect_swing_ratio(
@@ -141,6 +171,67 @@ pub trait SegmentAnalyzer {
     ) -> Vec<SegmentEvent>;
 }
 
+/// Real-time, block-by-block counterpart of the batch traits.
+///
+/// Contract:
+/// - `prepare` performs every allocation, sized for blocks of up to
+///   `max_block` samples; `process_block` and `reset` never allocate.
+/// - Blocks are contiguous; `block_start` is the timeline position of the
+///   block's first sample.
+/// - Results go into the caller's `out` slice and the count written is
+///   returned; results that do not fit are dropped and counted in
+///   `dropped_outputs`.
+/// - A result is emitted at most `latency_samples()` after the input that
+///   completes it.
+pub trait StreamingAnalyzer {
+    fn prepare(&mut self, sample_rate: u32, max_block: usize);
+
+    /// Forget all stream state; keeps the buffers from `prepare`.
+    fn reset(&mut self);
+
+    fn latency_samples(&self) -> u32;
+
+    /// Outputs discarded since `prepare` because `out` was full.
+    fn dropped_outputs(&self) -> u64;
+}
+
+/// Streaming analyzer over a mono audio stream.
+pub trait StreamingAudioAnalyzer: StreamingAnalyzer {
+    type Output;
+
+    fn process_block(
+        &mut self,
+        block_start: SampleTime,
+        samples: &[f32],
+        out: &mut [Self::Output],
+    ) -> usize;
+}
+
+/// Streaming analyzer over MIDI events that arrived with the block, in
+/// ascending position order.
+pub trait StreamingMidiAnalyzer: StreamingAnalyzer {
+    type Output;
+
+    fn process_block(
+        &mut self,
+        block_start: SampleTime,
+        events: &[TimedMidiEvent],
+        out: &mut [Self::Output],
+    ) -> usize;
+}
+
+/// Streaming analyzer over notes completed during the block, in onset order.
+pub trait StreamingNoteAnalyzer: StreamingAnalyzer {
+    type Output;
+
+    fn process_block(
+        &mut self,
+        block_start: SampleTime,
+        notes: &[NoteEvent],
+        out: &mut [Self::Output],
+    ) -> usize;
+}
+
 /// Composite suite: convenience bound for a "full" implementation.
 pub trait AnalysisSuite:
     MidiNoteAnalyzer
@@ -0,0 +1,135 @@
+use mt_analysis::cadence_detector::{CadenceDetector, classify};
+use mt_analysis::config::CadenceConfig;
+use mt_analysis::traits::CadenceAnalyzer;
+use mt_core::chord::Chord;
+use mt_core::chord_kind::ChordKindId::{self, Dim, Dom7, Maj, Min};
+use mt_core::events::{CadenceKind, ChordEvent, KeyEvent};
+use mt_core::key::{Key, KeyMode};
+use mt_core::pitch::PitchClass;
+use mt_core::time::SampleTime;
+
+const SR: u32 = 8_000;
+/// One 4/4 bar at the default 120 BPM.
+const BAR: i64 = 2 * SR as i64;
+
+fn pc(v: u8) -> PitchClass {
+    PitchClass::new(v).unwrap()
+}
+
+fn chord(root: u8, kind: ChordKindId) -> Chord {
+    Chord::new(pc(root), kind, None).unwrap()
+}
+
+fn inverted(root: u8, kind: ChordKindId, bass: u8) -> Chord {
+    Chord::new(pc(root), kind, Some(pc(bass))).unwrap()
+}
+
+const C_MAJOR: Key = Key::new(PitchClass::from_unchecked(0), KeyMode::Major);
+const A_MINOR: Key = Key::new(PitchClass::from_unchecked(9), KeyMode::Minor);
+
+#[test]
+fn authentic_cadences_depend_on_inversions() {
+    let (g, c) = (chord(7, Maj), chord(0, Maj));
+    assert_eq!(classify(g, c, C_MAJOR), Some(CadenceKind::PerfectAuthentic));
+    assert_eq!(classify(chord(7, Dom7), c, C_MAJOR), Some(CadenceKind::PerfectAuthentic));
+    assert_eq!(classify(g, inverted(0, Maj, 4), C_MAJOR), Some(CadenceKind::ImperfectAuthentic));
+    assert_eq!(classify(inverted(7, Maj, 11), c, C_MAJOR), Some(CadenceKind::ImperfectAuthentic));
+    assert_eq!(classify(chord(11, Dim), c, C_MAJOR), Some(CadenceKind::ImperfectAuthentic));
+    // E major → A minor in A minor; Picardy third A major too.
+    assert_eq!(
+        classify(chord(4, Maj), chord(9, Min), A_MINOR),
+        Some(CadenceKind::PerfectAuthentic)
+    );
+    assert_eq!(
+        classify(chord(4, Maj), chord(9, Maj), A_MINOR),
+        Some(CadenceKind::PerfectAuthentic)
+    );
+    // A minor tonic is not a tonic in a major key.
+    assert_eq!(classify(chord(2, Maj), chord(7, Min), Key::new(pc(7), KeyMode::Major)), None);
+}
+
+#[test]
+fn half_and_phrygian_half_cadences_arrive_on_the_dominant() {
+    assert_eq!(classify(chord(5, Maj), chord(7, Maj), C_MAJOR), Some(CadenceKind::Half));
+    assert_eq!(classify(chord(2, Min), chord(7, Dom7), C_MAJOR), Some(CadenceKind::Half));
+    // iv6 → V in A minor: D minor over F, then E major.
+    assert_eq!(
+        classify(inverted(2, Min, 5), chord(4, Maj), A_MINOR),
+        Some(CadenceKind::PhrygianHalf)
+    );
+    assert_eq!(classify(chord(2, Min), chord(4, Maj), A_MINOR), Some(CadenceKind::Half));
+    // V → V is no cadence.
+    assert_eq!(classify(chord(7, Dom7), chord(7, Maj), C_MAJOR), None);
+}
+
+#[test]
+fn plagal_and_deceptive_cadences() {
+    assert_eq!(classify(chord(5, Maj), chord(0, Maj), C_MAJOR), Some(CadenceKind::Plagal));
+    assert_eq!(classify(chord(2, Min), chord(9, Min), A_MINOR), Some(CadenceKind::Plagal));
+    assert_eq!(classify(chord(7, Dom7), chord(9, Min), C_MAJOR), Some(CadenceKind::Deceptive));
+    // V → VI in A minor.
+    assert_eq!(classify(chord(4, Maj), chord(5, Maj), A_MINOR), Some(CadenceKind::Deceptive));
+    assert_eq!(classify(chord(7, Maj), chord(9, Maj), C_MAJOR), None);
+    assert_eq!(classify(chord(0, Maj), chord(5, Maj), C_MAJOR), None);
+}
+
+/// One chord per bar from bar 0, each with full confidence.
+fn bars(chords: &[Chord]) -> Vec<ChordEvent> {
+    (0_i64..)
+        .zip(chords)
+        .map(|(i, &chord)| ChordEvent {
+            chord,
+            onset: SampleTime::new(i * BAR),
+            offset: SampleTime::new((i + 1) * BAR),
+            confidence_x1000: 1000,
+        })
+        .collect()
+}
+
+fn detect(chords: &[ChordEvent]) -> Vec<(CadenceKind, i64, i64, u16)> {
+    let keys = [KeyEvent { key: C_MAJOR, position: SampleTime::ZERO, confidence_x1000: 1000 }];
+    CadenceDetector
+        .detect_cadences(chords, &keys, &[], &[], SR, &CadenceConfig::default())
+        .iter()
+        .map(|e| (e.kind, e.approach.value(), e.position.value(), e.confidence_x1000))
+        .collect()
+}
+
+#[test]
+fn detector_merges_repeats_and_drops_resolved_half_cadences() {
+    let (c, f, g) = (chord(0, Maj), chord(5, Maj), chord(7, Maj));
+    // IV → V resolves to I: only the authentic cadence counts.
+    assert_eq!(
+        detect(&bars(&[c, f, g, g, c])),
+        [(CadenceKind::PerfectAuthentic, 2 * BAR, 4 * BAR, 1000)]
+    );
+    assert_eq!(detect(&bars(&[c, f, g])), [(CadenceKind::Half, BAR, 2 * BAR, 1000)]);
+    assert!(detect(&[]).is_empty());
+    let no_keys = CadenceDetector.detect_cadences(
+        &bars(&[g, c]),
+        &[],
+        &[],
+        &[],
+        SR,
+        &CadenceConfig::default(),
+    );
+    assert!(no_keys.is_empty());
+}
+
+#[test]
+fn detector_weighs_metric_position_and_length() {
+    let mut events = bars(&[chord(7, Maj), chord(0, Maj)]);
+    // Arrival on beat 2, held one beat.
+    let beat = BAR / 4;
+    events[0].offset = SampleTime::new(BAR + beat);
+    events[1].onset = events[0].offset;
+    events[1].offset = SampleTime::new(BAR + 2 * beat);
+    let found = detect(&events);
+    assert_eq!(found.len(), 1);
+    // offbeat 0.3 × length (0.5 + 0.5 × 1/2).
+    assert_eq!(found[0].3, 225);
+
+    let cfg = CadenceConfig { min_confidence: 0.5, ..CadenceConfig::default() };
+    let keys = [KeyEvent { key: C_MAJOR, position: SampleTime::ZERO, confidence_x1000: 1000 }];
+    assert!(CadenceDetector.detect_cadences(&events, &keys, &[], &[], SR, &cfg).is_empty());
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use common::{SR, notes};
+use mt_analysis::chord_detector::bass::{label_bass, voiced_weights};
+use mt_analysis::chord_detector::{HmmChordAnalyzer, RuleBasedChordAnalyzer};
+use mt_analysis::config::{ChordBassMode, ChordConfig};
+use mt_analysis::traits::ChordAnalyzer;
+use mt_core::chord::Chord;
+use mt_core::chord_kind::ChordKindId;
+use mt_core::events::{ChordEvent, NoteEvent};
+use mt_core::pitch::PitchClass;
+
+fn pc(v: u8) -> PitchClass {
+    PitchClass::new(v).unwrap()
+}
+
+fn config(bass_mode: ChordBassMode) -> ChordConfig {
+    ChordConfig { bass_mode, ..ChordConfig::default() }
+}
+
+/// The single chord detected over `song`.
+fn only(chords: &[ChordEvent]) -> Chord {
+    assert_eq!(chords.len(), 1, "{chords:?}");
+    chords[0].chord
+}
+
+/// C major with E in the bass.
+fn first_inversion() -> Vec<NoteEvent> {
+    notes(&[(52, 0, 4), (60, 0, 4), (64, 0, 4), (67, 0, 4)])
+}
+
+/// C major over a D bass.
+fn c_over_d() -> Vec<NoteEvent> {
+    notes(&[(50, 0, 4), (60, 0, 4), (64, 0, 4), (67, 0, 4)])
+}
+
+#[test]
+fn root_position_is_the_default() {
+    assert_eq!(ChordConfig::default().bass_mode, ChordBassMode::RootPosition);
+    let chord =
+        only(&RuleBasedChordAnalyzer.detect_chords(&first_inversion(), &ChordConfig::default()));
+    assert_eq!((chord.root, chord.kind, chord.bass), (pc(0), ChordKindId::Maj, None));
+}
+
+#[test]
+fn rule_based_labels_inversions() {
+    for mode in [ChordBassMode::Inversions, ChordBassMode::SlashChords] {
+        let chord = only(&RuleBasedChordAnalyzer.detect_chords(&first_inversion(), &config(mode)));
+        assert_eq!((chord.root, chord.kind, chord.bass), (pc(0), ChordKindId::Maj, Some(pc(4))));
+    }
+}
+
+#[test]
+fn rule_based_labels_slash_chords() {
+    let slash = only(
+        &RuleBasedChordAnalyzer.detect_chords(&c_over_d(), &config(ChordBassMode::SlashChords)),
+    );
+    assert_eq!((slash.root, slash.kind, slash.bass), (pc(0), ChordKindId::Maj, Some(pc(2))));
+
+    // Without slash chords a non-chord-tone bass is never attached.
+    let chords =
+        RuleBasedChordAnalyzer.detect_chords(&c_over_d(), &config(ChordBassMode::Inversions));
+    for c in &chords {
+        assert!(c.chord.bass.is_none_or(|b| c.chord.contains(b)), "{c:?}");
+    }
+}
+
+#[test]
+fn hmm_labels_inversions_and_slash_chords() {
+    let hmm = HmmChordAnalyzer::default();
+    let inversion =
+        only(&hmm.detect_chords(&first_inversion(), &config(ChordBassMode::Inversions)));
+    assert_eq!((inversion.root, inversion.bass), (pc(0), Some(pc(4))));
+    let root = only(&hmm.detect_chords(&first_inversion(), &config(ChordBassMode::RootPosition)));
+    assert_eq!((root.root, root.bass), (pc(0), None));
+
+    let slash = only(&hmm.detect_chords(&c_over_d(), &config(ChordBassMode::SlashChords)));
+    assert_eq!(slash.bass, Some(pc(2)));
+}
+
+#[test]
+fn hmm_reroots_on_the_bass() {
+    // C6 and Am7 share their pitch-classes; an A bass makes it Am7.
+    let song = notes(&[(45, 0, 4), (60, 0, 4), (64, 0, 4), (67, 0, 4)]);
+    let chord =
+        only(&HmmChordAnalyzer::default().detect_chords(&song, &config(ChordBassMode::Inversions)));
+    assert_eq!((chord.root, chord.bass), (pc(9), None));
+}
+
+#[test]
+fn bass_voice_needs_a_dominant_pitch_class() {
+    // Lowest voice moves E -> G halfway through the window.
+    let song = notes(&[(52, 0, 2), (55, 2, 2), (60, 0, 4), (64, 0, 4)]);
+    let voiced = voiced_weights(&song, 0, 4 * SR);
+    assert_eq!(voiced.bass[4], (2 * SR) as f32);
+    assert_eq!(voiced.bass[7], (2 * SR) as f32);
+    assert_eq!(voiced.upper[0], (4 * SR) as f32);
+    assert_eq!(voiced.all()[4], voiced.bass[4] + voiced.upper[4]);
+    assert_eq!(voiced.dominant_bass(0.6), None);
+    assert!(voiced.dominant_bass(0.5).is_some());
+}
+
+#[test]
+fn label_bass_follows_the_mode() {
+    let c = Chord::new(pc(0), ChordKindId::Maj, None).unwrap();
+    assert_eq!(label_bass(c, Some(pc(4)), ChordBassMode::RootPosition), c);
+    assert_eq!(label_bass(c, Some(pc(0)), ChordBassMode::SlashChords), c);
+    assert_eq!(label_bass(c, Some(pc(4)), ChordBassMode::Inversions).bass, Some(pc(4)));
+    assert_eq!(label_bass(c, Some(pc(2)), ChordBassMode::Inversions), c);
+    assert_eq!(label_bass(c, Some(pc(2)), ChordBassMode::SlashChords).bass, Some(pc(2)));
+    assert_eq!(label_bass(c, None, ChordBassMode::SlashChords), c);
+}
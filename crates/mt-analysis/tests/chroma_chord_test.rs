@@ -0,0 +1,87 @@
+use mt_analysis::chord_detector::ChromaChordAnalyzer;
+use mt_analysis::chroma::chroma_frames;
+use mt_analysis::config::{ChordConfig, ChromaConfig};
+use mt_analysis::traits::AudioChordAnalyzer;
+use mt_core::chord_kind::ChordKindId;
+use mt_core::events::ChordEvent;
+
+const SR: u32 = 22_050;
+
+/// Equal-amplitude sines on `notes` (MIDI numbers) for `seconds`.
+fn tones(notes: &[u8], seconds: f32) -> Vec<f32> {
+    let n = (seconds * SR as f32) as usize;
+    let freqs: Vec<f32> =
+        notes.iter().map(|&m| 440.0 * 2.0_f32.powf((f32::from(m) - 69.0) / 12.0)).collect();
+    (0..n)
+        .map(|i| {
+            let t = i as f32 / SR as f32;
+            freqs.iter().map(|f| (2.0 * std::f32::consts::PI * f * t).sin()).sum::<f32>()
+                / freqs.len() as f32
+        })
+        .collect()
+}
+
+fn labels(events: &[ChordEvent]) -> Vec<(u8, ChordKindId)> {
+    events.iter().map(|e| (e.chord.root.as_u8(), e.chord.kind)).collect()
+}
+
+fn detect(samples: &[f32]) -> Vec<ChordEvent> {
+    ChromaChordAnalyzer::default().detect_audio_chords(
+        samples,
+        SR,
+        &ChromaConfig::default(),
+        &ChordConfig::default(),
+    )
+}
+
+#[test]
+fn chroma_frames_follow_the_hop_grid_and_zero_silence() {
+    let cfg = ChromaConfig { frame_size: 4096, hop_size: 2048, ..ChromaConfig::default() };
+    let mut samples = tones(&[57, 60, 64], 1.0);
+    samples.extend(vec![0.0; SR as usize]);
+    let chroma = chroma_frames(&samples, SR, &cfg);
+    assert_eq!(chroma.dim(), 12);
+    assert_eq!(chroma.frames(), (samples.len() - 4096) / 2048 + 1);
+
+    // A minor: A, C and E are the strongest classes, normalized to 1.
+    let first = chroma.frame(0).unwrap();
+    let mut order: Vec<usize> = (0..12).collect();
+    order.sort_by(|&a, &b| first[b].total_cmp(&first[a]));
+    let mut top = order[..3].to_vec();
+    top.sort_unstable();
+    assert_eq!(top, [0, 4, 9]);
+    assert!((first[order[0]] - 1.0).abs() < 1e-5);
+
+    let last = chroma.frame(chroma.frames() - 1).unwrap();
+    assert!(last.iter().all(|&c| c == 0.0));
+
+    assert_eq!(chroma_frames(&samples[..4095], SR, &cfg).frames(), 0);
+}
+
+#[test]
+fn triads_are_labelled_by_root_and_quality() {
+    let c_major = detect(&tones(&[48, 60, 64, 67], 3.0));
+    assert_eq!(labels(&c_major), [(0, ChordKindId::Maj)]);
+    let a_minor = detect(&tones(&[45, 57, 60, 64], 3.0));
+    assert_eq!(labels(&a_minor), [(9, ChordKindId::Min)]);
+    assert!(c_major[0].confidence_x1000 > 0);
+}
+
+#[test]
+fn a_chord_change_splits_the_timeline() {
+    let mut samples = tones(&[48, 60, 64, 67], 3.0);
+    samples.extend(tones(&[43, 55, 59, 62], 3.0));
+    let events = detect(&samples);
+    assert_eq!(labels(&events), [(0, ChordKindId::Maj), (7, ChordKindId::Maj)]);
+    let change = i64::from(3 * SR);
+    let hop = ChromaConfig::default().hop_size as i64;
+    assert!((events[1].onset.value() - change).abs() <= 2 * hop, "{events:?}");
+    assert_eq!(events[0].offset, events[1].onset);
+}
+
+#[test]
+fn silence_and_short_input_yield_no_chords() {
+    assert!(detect(&[]).is_empty());
+    assert!(detect(&vec![0.0; 3 * SR as usize]).is_empty());
+    assert!(detect(&tones(&[60, 64, 67], 0.1)).is_empty());
+}
@@ -0,0 +1,31 @@
+//! Note fixtures shared by the integration tests.
+
+#![allow(dead_code)]
+
+use mt_core::events::{NoteEvent, NoteId, TrackId};
+use mt_core::pitch::MidiNote;
+use mt_core::time::SampleTime;
+
+/// Sample rate of `notes`.
+pub const SR: i64 = 44_100;
+
+/// `(midi note, onset second, seconds held)` at `SR`.
+pub fn notes(spec: &[(u8, i64, i64)]) -> Vec<NoteEvent> {
+    notes_at(SR, spec)
+}
+
+/// `(midi note, onset second, seconds held)` at `sample_rate`, numbered in
+/// input order.
+pub fn notes_at(sample_rate: i64, spec: &[(u8, i64, i64)]) -> Vec<NoteEvent> {
+    spec.iter()
+        .enumerate()
+        .map(|(i, &(note, onset, seconds))| NoteEvent {
+            id: NoteId(i as u32),
+            track: TrackId(0),
+            onset: SampleTime::new(onset * sample_rate),
+            offset: SampleTime::new((onset + seconds) * sample_rate),
+            note: MidiNote::new(note).unwrap(),
+            velocity: 100,
+        })
+        .collect()
+}
@@ -0,0 +1,184 @@
+use mt_alloc::TempoMap;
+use mt_analysis::eval::{
+    Ratio, Summary, Vocabulary, beat_f_measure, beats_from_tempo_map, boundary_detection,
+    chord_scores, evaluate, global_key, pairwise_clustering, weighted_key_score,
+};
+use mt_core::chord::{BassPolicy, Chord};
+use mt_core::chord_kind::ChordKindId;
+use mt_core::events::{ChordEvent, KeyEvent, SectionLabel, SegmentEvent, SegmentKind};
+use mt_core::key::Key;
+use mt_core::pitch::PitchClass;
+use mt_core::time::SampleTime;
+use mt_formats::Annotations;
+
+// One sample per millisecond keeps the expectations readable.
+const SR: u32 = 1000;
+
+fn at(ms: i64) -> SampleTime {
+    SampleTime::new(ms)
+}
+
+fn chord(onset: i64, offset: i64, root: u8, kind: ChordKindId, bass: Option<u8>) -> ChordEvent {
+    let pc = |v| PitchClass::new(v).unwrap();
+    ChordEvent {
+        chord: Chord::with_bass_policy(pc(root), kind, bass.map(pc), BassPolicy::Any).unwrap(),
+        onset: at(onset),
+        offset: at(offset),
+        confidence_x1000: 1000,
+    }
+}
+
+fn key(tonic: u8, minor: bool) -> Key {
+    Key::from_semitone(tonic, minor).unwrap()
+}
+
+fn segment(onset: i64, offset: i64, kind: SegmentKind, group: u8) -> SegmentEvent {
+    SegmentEvent {
+        kind,
+        label: Some(SectionLabel { group, variant: 0 }),
+        onset: at(onset),
+        offset: at(offset),
+        confidence_x1000: 1000,
+    }
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-4, "{actual} != {expected}");
+}
+
+#[test]
+fn chord_vocabularies_and_segmentation() {
+    let reference = [
+        chord(0, 4000, 0, ChordKindId::Maj, None),
+        chord(4000, 6000, 9, ChordKindId::Min7, None),
+        chord(6000, 8000, 0, ChordKindId::Maj, Some(4)),
+        chord(8000, 10_000, 7, ChordKindId::Sus4, None),
+    ];
+    // The last two seconds are left without a chord (N).
+    let estimate = [
+        chord(6000, 8000, 0, ChordKindId::Maj, None),
+        chord(0, 4000, 0, ChordKindId::Dom7, None),
+        chord(4000, 6000, 9, ChordKindId::Min, None),
+    ];
+    let scores = chord_scores(&reference, &estimate);
+    let recall = |v| scores.recall(v);
+    assert_eq!(recall(Vocabulary::Root), Ratio { hits: 8000, total: 10_000 });
+    assert_eq!(recall(Vocabulary::MajMin), Ratio { hits: 8000, total: 8000 });
+    assert_eq!(recall(Vocabulary::MajMinInv), Ratio { hits: 6000, total: 8000 });
+    assert_eq!(recall(Vocabulary::Sevenths), Ratio { hits: 2000, total: 8000 });
+    assert_eq!(recall(Vocabulary::Tetrads), Ratio { hits: 2000, total: 10_000 });
+    assert_eq!(scores.segmentation, Ratio { hits: 10_000, total: 10_000 });
+
+    // One long chord under-segments: its largest piece is 4 of 10 seconds.
+    let merged = chord_scores(&reference, &[chord(0, 12_000, 0, ChordKindId::Maj, None)]);
+    assert_eq!(merged.segmentation, Ratio { hits: 4000, total: 10_000 });
+    assert_eq!(merged.recall(Vocabulary::MajMin), Ratio { hits: 6000, total: 8000 });
+
+    let n = |r, e| Vocabulary::Tetrads.matches(r, e);
+    assert_eq!(n(None, None), Some(true));
+    assert_eq!(n(None, Some(&reference[0].chord)), Some(false));
+    assert_eq!(Vocabulary::Sevenths.matches(Some(&reference[3].chord), None), None);
+}
+
+#[test]
+fn key_scores() {
+    let c = key(0, false);
+    assert_close(weighted_key_score(c, c), 1.0);
+    assert_close(weighted_key_score(c, key(7, false)), 0.5);
+    assert_close(weighted_key_score(c, key(5, false)), 0.0);
+    assert_close(weighted_key_score(c, key(9, true)), 0.3);
+    assert_close(weighted_key_score(key(9, true), c), 0.3);
+    assert_close(weighted_key_score(c, key(0, true)), 0.2);
+    assert_close(weighted_key_score(c, key(2, true)), 0.0);
+
+    let keys = [
+        KeyEvent { key: key(7, false), position: at(60_000), confidence_x1000: 1000 },
+        KeyEvent { key: c, position: at(0), confidence_x1000: 1000 },
+        KeyEvent { key: key(4, true), position: at(40_000), confidence_x1000: 1000 },
+    ];
+    assert_eq!(global_key(&keys, at(110_000)), Some(key(7, false)));
+    assert_eq!(global_key(&keys, at(80_000)), Some(c));
+    assert_eq!(global_key(&[], at(80_000)), None);
+}
+
+#[test]
+fn boundaries_and_pairwise_clustering() {
+    let reference = [
+        segment(0, 10_000, SegmentKind::Verse, 0),
+        segment(10_000, 30_000, SegmentKind::Chorus, 1),
+    ];
+    let estimate = [
+        segment(0, 10_400, SegmentKind::Verse, 0),
+        segment(10_400, 27_000, SegmentKind::Chorus, 1),
+    ];
+    let tight = boundary_detection(&reference, &estimate, 500);
+    assert_close(tight.precision, 2.0 / 3.0);
+    assert_close(tight.recall, 2.0 / 3.0);
+    assert_close(tight.f_measure, 2.0 / 3.0);
+    assert_close(boundary_detection(&reference, &estimate, 3000).f_measure, 1.0);
+    assert_close(boundary_detection(&reference, &[], 3000).f_measure, 0.0);
+
+    let halves =
+        [segment(0, 10_000, SegmentKind::Verse, 0), segment(10_000, 20_000, SegmentKind::Verse, 1)];
+    let whole = [segment(0, 20_000, SegmentKind::Verse, 0)];
+    // 200 frames: two clusters of 100 against one of 200.
+    let merged = pairwise_clustering(&halves, &whole, 100);
+    assert_close(merged.recall, 1.0);
+    assert_close(merged.precision, 2.0 * 4950.0 / 19_900.0);
+    assert_close(pairwise_clustering(&halves, &halves, 100).f_measure, 1.0);
+}
+
+#[test]
+fn beats() {
+    let reference: Vec<_> = (0..40).map(|i| at(i * 500)).collect();
+    // Late by 50 ms, plus an off-beat between every pair.
+    let estimate: Vec<_> = (0..80).map(|i| at(i * 250 + 50)).collect();
+    let scores = beat_f_measure(&reference, &estimate, SR);
+    assert_close(scores.recall, 1.0);
+    assert_close(scores.precision, 0.5);
+    assert_close(scores.f_measure, 2.0 / 3.0);
+
+    let late: Vec<_> = reference.iter().map(|b| at(b.value() + 80)).collect();
+    assert_close(beat_f_measure(&reference, &late, SR).f_measure, 0.0);
+
+    assert_eq!(
+        beats_from_tempo_map(&TempoMap::new(SR), at(2000)),
+        [at(0), at(500), at(1000), at(1500)]
+    );
+}
+
+#[test]
+fn files_and_summary() {
+    let reference = Annotations {
+        chords: vec![chord(0, 10_000, 0, ChordKindId::Maj, None)],
+        keys: vec![KeyEvent { key: key(0, false), position: at(0), confidence_x1000: 1000 }],
+        ..Annotations::default()
+    };
+    let good = Annotations {
+        chords: vec![chord(0, 9000, 0, ChordKindId::Maj7, None)],
+        keys: reference.keys.clone(),
+        ..Annotations::default()
+    };
+    let scores = evaluate(&reference, &good, SR);
+    assert_eq!(
+        scores.chords.unwrap().recall(Vocabulary::MajMin),
+        Ratio { hits: 9000, total: 10_000 }
+    );
+    assert_eq!(scores.key, Some(1.0));
+    assert_eq!((scores.boundaries, scores.pairwise, scores.beats), (None, None, None));
+
+    let mut summary = Summary::default();
+    summary.add(&scores);
+    assert!(summary.passes_gates());
+
+    // Relative minor key and no chords at all.
+    let poor = Annotations {
+        keys: vec![KeyEvent { key: key(9, true), position: at(0), confidence_x1000: 1000 }],
+        ..Annotations::default()
+    };
+    summary.add(&evaluate(&reference, &poor, SR));
+    assert_eq!(summary.files, 2);
+    assert_eq!(summary.chords.recall(Vocabulary::MajMin), Ratio { hits: 9000, total: 20_000 });
+    assert_close(summary.key.value().unwrap(), 0.65);
+    assert!(!summary.passes_gates());
+}
@@ -0,0 +1,119 @@
+use mt_analysis::config::PhraseConfig;
+use mt_analysis::segmenter::{HierarchicalSegmenter, SegmentTree};
+use mt_core::events::{NoteEvent, NoteId, SegmentEvent, SegmentKind, SegmentLevel, TrackId};
+use mt_core::pitch::MidiNote;
+use mt_core::time::SampleTime;
+
+const SR: u32 = 44_100;
+/// One beat at the default 120 BPM.
+const BEAT: i64 = 22_050;
+const BAR: i64 = 4 * BEAT;
+
+/// Four-bar phrases of quarter notes; bar 2 of each ends on a half note
+/// and bar 4 on a half note followed by a half rest.
+fn melody(phrases: i64) -> Vec<NoteEvent> {
+    let mut notes = Vec::new();
+    let mut push = |onset: i64, beats: i64, note: u8| {
+        notes.push(NoteEvent {
+            id: NoteId(notes.len() as u32),
+            track: TrackId(0),
+            onset: SampleTime::new(onset),
+            offset: SampleTime::new(onset + beats * BEAT),
+            note: MidiNote::new(note).unwrap(),
+            velocity: 90,
+        });
+    };
+    for p in 0..phrases {
+        let start = p * 4 * BAR;
+        for beat in 0..16 {
+            let onset = start + beat * BEAT;
+            match beat {
+                0..=5 | 8..=13 => push(onset, 1, 60 + (beat % 5) as u8 * 2),
+                6 => push(onset, 2, 67),
+                14 => push(onset, 2, 60),
+                _ => {}
+            }
+        }
+    }
+    notes
+}
+
+fn spans(tree: &SegmentTree, level: SegmentLevel) -> Vec<(i64, i64)> {
+    tree.at_level(level).map(|(_, n)| (n.segment.onset.value(), n.segment.offset.value())).collect()
+}
+
+#[test]
+fn midi_phrases_split_at_rests_on_the_hypermeter() {
+    let notes = melody(4);
+    let tree = HierarchicalSegmenter.segment_midi(
+        &notes,
+        &[],
+        &[],
+        &[],
+        &[],
+        SR,
+        &PhraseConfig::default(),
+    );
+
+    let sections = spans(&tree, SegmentLevel::Section);
+    assert_eq!(sections, [(0, 16 * BAR)]);
+    let phrases: Vec<i64> =
+        spans(&tree, SegmentLevel::Phrase).iter().map(|&(onset, _)| onset).collect();
+    assert_eq!(phrases, [0, 4 * BAR, 8 * BAR, 12 * BAR]);
+    let two_bars: Vec<(i64, i64)> = (0..8).map(|i| (i * 2 * BAR, (i + 1) * 2 * BAR)).collect();
+    assert_eq!(spans(&tree, SegmentLevel::SubPhrase), two_bars);
+}
+
+#[test]
+fn levels_nest_and_tile_their_parents() {
+    let notes = melody(4);
+    let section = |onset: i64, offset: i64| SegmentEvent {
+        kind: SegmentKind::Verse,
+        label: None,
+        onset: SampleTime::new(onset),
+        offset: SampleTime::new(offset),
+        confidence_x1000: 800,
+    };
+    let sections = [section(0, 8 * BAR), section(8 * BAR, 16 * BAR)];
+    let tree = HierarchicalSegmenter.segment_midi(
+        &notes,
+        &[],
+        &[],
+        &[],
+        &sections,
+        SR,
+        &PhraseConfig::default(),
+    );
+
+    assert_eq!(tree.at_level(SegmentLevel::Section).count(), 2);
+    for (index, node) in tree.nodes().iter().enumerate() {
+        let children: Vec<_> = tree.children(index as u32).map(|(_, c)| c.segment).collect();
+        if node.level == SegmentLevel::SubPhrase {
+            assert!(children.is_empty());
+            continue;
+        }
+        assert!(!children.is_empty(), "{node:?}");
+        assert_eq!(children[0].onset, node.segment.onset);
+        assert_eq!(children.last().unwrap().offset, node.segment.offset);
+        for pair in children.windows(2) {
+            assert_eq!(pair[0].offset, pair[1].onset);
+        }
+    }
+
+    let position = SampleTime::new(9 * BAR);
+    let phrase = tree.containing(position, SegmentLevel::Phrase).unwrap();
+    let parent = tree.get(phrase).unwrap().parent.unwrap();
+    assert_eq!(tree.get(parent).unwrap().segment.onset.value(), 8 * BAR);
+}
+
+#[test]
+fn audio_tree_is_empty_without_samples() {
+    let tree = HierarchicalSegmenter.segment_audio(
+        &[],
+        SR,
+        &[],
+        &Default::default(),
+        &PhraseConfig::default(),
+    );
+    assert!(tree.nodes().is_empty());
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use common::{SR, notes};
+use mt_analysis::chord_detector::templates::{CHORD_STATES, chord_state};
+use mt_analysis::chord_detector::{ChordHmm, HmmChordAnalyzer};
+use mt_analysis::config::{ChordBassMode, ChordConfig};
+use mt_analysis::traits::ChordAnalyzer;
+use mt_core::chord::Chord;
+use mt_core::chord_kind::ChordKindId;
+use mt_core::events::KeyEvent;
+use mt_core::key::Key;
+use mt_core::pitch::PitchClass;
+use mt_core::time::SampleTime;
+
+fn state(root: u8, kind: ChordKindId) -> usize {
+    chord_state(&Chord::new(PitchClass::new(root).unwrap(), kind, None).unwrap()).unwrap()
+}
+
+/// Flat emissions with `bonus` log-likelihood on `favoured`.
+fn emission(favoured: usize, bonus: f32) -> [f32; CHORD_STATES] {
+    let mut e = [0.0; CHORD_STATES];
+    e[favoured] = bonus;
+    e
+}
+
+fn key_event(tonic: u8, minor: bool, second: i64) -> KeyEvent {
+    KeyEvent {
+        key: Key::from_semitone(tonic, minor).unwrap(),
+        position: SampleTime::new(second * SR),
+        confidence_x1000: 1000,
+    }
+}
+
+#[test]
+fn viterbi_follows_strong_evidence() {
+    let (c, g) = (state(0, ChordKindId::Maj), state(7, ChordKindId::Maj));
+    let mut emissions = vec![emission(c, 10.0); 5];
+    emissions.extend(vec![emission(g, 10.0); 5]);
+    let path = ChordHmm::new(0.9, None, 0.0).viterbi(&emissions);
+    assert_eq!(path, [c, c, c, c, c, g, g, g, g, g]);
+    assert!(ChordHmm::new(0.9, None, 0.0).viterbi(&[]).is_empty());
+}
+
+#[test]
+fn viterbi_smooths_single_frame_blips() {
+    let (c, g) = (state(0, ChordKindId::Maj), state(7, ChordKindId::Maj));
+    let mut emissions = vec![emission(c, 6.0); 7];
+    emissions[3] = emission(g, 6.0);
+
+    let sticky = ChordHmm::new(0.99, None, 0.0).viterbi(&emissions);
+    assert!(sticky.iter().all(|&s| s == c));
+    let loose = ChordHmm::new(0.05, None, 0.0).viterbi(&emissions);
+    assert_eq!(loose[3], g);
+}
+
+#[test]
+fn posteriors_are_distributions() {
+    let (c, a_min) = (state(0, ChordKindId::Maj), state(9, ChordKindId::Min));
+    let emissions = [emission(c, 10.0), emission(c, 10.0), emission(a_min, 10.0)];
+    let posteriors = ChordHmm::new(0.5, None, 0.0).posteriors(&emissions);
+    assert_eq!(posteriors.len(), 3);
+    for row in &posteriors {
+        assert!((row.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+    }
+    assert!(posteriors[0][c] > 0.9);
+    assert!(posteriors[2][a_min] > 0.9);
+
+    // Without evidence or key prior every state is equally likely.
+    let flat = ChordHmm::new(0.9, None, 0.0).posteriors(&[[0.0; CHORD_STATES]; 2]);
+    let uniform = 1.0 / CHORD_STATES as f32;
+    assert!(flat.iter().flatten().all(|p| (p - uniform).abs() < 1e-5));
+}
+
+#[test]
+fn key_prior_favours_diatonic_chords() {
+    let c_major = Key::from_semitone(0, false).unwrap();
+    let flat = ChordHmm::new(0.9, Some(c_major), 4.0).posteriors(&[[0.0; CHORD_STATES]]);
+    let (c, d_min, d_maj) =
+        (state(0, ChordKindId::Maj), state(2, ChordKindId::Min), state(2, ChordKindId::Maj));
+    assert!(flat[0][c] > flat[0][d_maj]);
+    assert!((flat[0][c] - flat[0][d_min]).abs() < 1e-6);
+}
+
+#[test]
+fn key_prior_follows_modulations() {
+    // Open fifths on D fit D major and D minor equally; only the key
+    // prior tells them apart: D minor in C major, D major in G major.
+    let cfg = ChordConfig { key_prior_weight: 4.0, ..ChordConfig::default() };
+    let fifths = notes(&[(50, 0, 16), (57, 0, 16)]);
+    let keys = vec![key_event(0, false, 0), key_event(7, false, 8)];
+    let map = HmmChordAnalyzer::new(keys).chord_probabilities(&fifths, &cfg);
+    let (d_maj, d_min) = (state(2, ChordKindId::Maj), state(2, ChordKindId::Min));
+    let first = map.probabilities(0).unwrap();
+    let last = map.probabilities(map.frames() - 1).unwrap();
+    assert!(first[d_min] > first[d_maj]);
+    assert!(last[d_maj] > last[d_min]);
+
+    // A single key conditions every frame alike.
+    let single =
+        HmmChordAnalyzer::new(vec![key_event(0, false, 0)]).chord_probabilities(&fifths, &cfg);
+    let last = single.probabilities(single.frames() - 1).unwrap();
+    assert!(last[d_min] > last[d_maj]);
+}
+
+#[test]
+fn detects_chords_per_key_span() {
+    let cfg = ChordConfig { bass_mode: ChordBassMode::RootPosition, ..ChordConfig::default() };
+    let song = notes(&[(60, 0, 4), (64, 0, 4), (67, 0, 4), (55, 4, 4), (59, 4, 4), (62, 4, 4)]);
+    let keys = vec![key_event(0, false, 0), key_event(7, false, 4)];
+    let chords = HmmChordAnalyzer::new(keys).detect_chords(&song, &cfg);
+    let roots: Vec<u8> = chords.iter().map(|c| c.chord.root.as_u8()).collect();
+    assert_eq!(roots, [0, 7]);
+    assert!(chords.iter().all(|c| c.chord.kind == ChordKindId::Maj));
+    assert_eq!(chords[0].onset, SampleTime::ZERO);
+    assert_eq!(chords[1].offset.value(), 8 * SR);
+}
+
+#[test]
+fn frames_follow_the_configured_sample_rate() {
+    let triad = notes(&[(60, 0, 2), (64, 0, 2), (67, 0, 2)]);
+    let default = HmmChordAnalyzer::default().chord_probabilities(&triad, &ChordConfig::default());
+    assert_eq!(default.frames(), 4);
+
+    // Same positions read at half the rate: hops are half as many samples.
+    let cfg = ChordConfig { sample_rate: 22_050, ..ChordConfig::default() };
+    let map = HmmChordAnalyzer::default().chord_probabilities(&triad, &cfg);
+    assert_eq!(map.frames(), 8);
+    let (onset, offset) = map.frame_bounds(1).unwrap();
+    assert_eq!((onset.value(), offset.value()), (11_025, 22_050));
+}
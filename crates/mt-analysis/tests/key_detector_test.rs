@@ -0,0 +1,145 @@
+mod common;
+
+use common::notes;
+use mt_analysis::config::{KeyConfig, KeyCorrelation, KeyProfile, KeyWeighting};
+use mt_analysis::key_detector::HistogramKeyAnalyzer;
+use mt_analysis::key_detector::profiles::{
+    AARDEN_ESSEN_MAJOR, KRUMHANSL_KESSLER_MAJOR, KRUMHANSL_KESSLER_MINOR, TEMPERLEY_MINOR,
+    profile_pair,
+};
+use mt_analysis::traits::KeyAnalyzer;
+use mt_core::events::NoteEvent;
+use mt_core::key::Key;
+use mt_core::time::SampleTime;
+
+fn config(profile: KeyProfile, correlation: KeyCorrelation) -> KeyConfig {
+    KeyConfig { profile, correlation, ..KeyConfig::default() }
+}
+
+fn key(tonic: u8, minor: bool) -> Key {
+    Key::from_semitone(tonic, minor).unwrap()
+}
+
+/// C major scale with the tonic triad held twice as long.
+fn c_major() -> Vec<NoteEvent> {
+    notes(&[
+        (60, 0, 2),
+        (62, 2, 1),
+        (64, 3, 2),
+        (65, 5, 1),
+        (67, 6, 2),
+        (69, 8, 1),
+        (71, 9, 1),
+        (72, 10, 2),
+    ])
+}
+
+/// A harmonic minor line, tonic and dominant held longest.
+fn a_minor() -> Vec<NoteEvent> {
+    notes(&[
+        (57, 0, 3),
+        (59, 3, 1),
+        (60, 4, 2),
+        (62, 6, 1),
+        (64, 7, 3),
+        (65, 10, 1),
+        (68, 11, 1),
+        (69, 12, 3),
+    ])
+}
+
+#[test]
+fn profiles_are_the_published_tables() {
+    let (major, minor) = profile_pair(&KeyProfile::KrumhanslKessler);
+    assert_eq!(major, &KRUMHANSL_KESSLER_MAJOR);
+    assert_eq!(minor, &KRUMHANSL_KESSLER_MINOR);
+    assert_eq!(major[0], 6.35);
+    assert_eq!(minor[3], 5.38);
+    assert_eq!(profile_pair(&KeyProfile::Temperley).1, &TEMPERLEY_MINOR);
+    assert_eq!(profile_pair(&KeyProfile::AardenEssen).0, &AARDEN_ESSEN_MAJOR);
+
+    let custom = KeyProfile::Custom { major: [1.0; 12], minor: [2.0; 12] };
+    assert_eq!(profile_pair(&custom), (&[1.0; 12], &[2.0; 12]));
+}
+
+#[test]
+fn every_profile_and_correlation_finds_the_key() {
+    let profiles = [
+        KeyProfile::KrumhanslKessler,
+        KeyProfile::Temperley,
+        KeyProfile::AardenEssen,
+        KeyProfile::AlbrechtShanahan,
+        KeyProfile::BellmanBudge,
+    ];
+    for profile in profiles {
+        for correlation in [KeyCorrelation::DotProduct, KeyCorrelation::Pearson] {
+            let cfg = config(profile, correlation);
+            let major = HistogramKeyAnalyzer.detect_keys(&c_major(), &cfg);
+            assert_eq!(major[0].key, key(0, false), "{profile:?} {correlation:?}");
+            assert_eq!(major[0].position, SampleTime::ZERO);
+            let minor = HistogramKeyAnalyzer.detect_keys(&a_minor(), &cfg);
+            assert_eq!(minor[0].key, key(9, true), "{profile:?} {correlation:?}");
+        }
+    }
+}
+
+#[test]
+fn pearson_confidence_is_the_correlation() {
+    // A histogram equal to the C major profile correlates perfectly.
+    let custom = KeyProfile::Custom {
+        major: [4.0, 0.0, 2.0, 0.0, 3.0, 2.0, 0.0, 3.0, 0.0, 2.0, 0.0, 1.0],
+        minor: [4.0, 0.0, 2.0, 3.0, 0.0, 2.0, 0.0, 3.0, 2.0, 0.0, 1.0, 0.0],
+    };
+    let exact = notes(&[
+        (60, 0, 4),
+        (62, 4, 2),
+        (64, 6, 3),
+        (65, 9, 2),
+        (67, 11, 3),
+        (69, 14, 2),
+        (71, 16, 1),
+    ]);
+    let pearson =
+        HistogramKeyAnalyzer.detect_keys(&exact, &config(custom, KeyCorrelation::Pearson));
+    assert_eq!(pearson[0].key, key(0, false));
+    assert_eq!(pearson[0].confidence_x1000, 1000);
+
+    // Dot products are unnormalized: any positive score is full confidence.
+    let dot =
+        HistogramKeyAnalyzer.detect_keys(&c_major(), &config(custom, KeyCorrelation::DotProduct));
+    assert_eq!(dot[0].confidence_x1000, 1000);
+
+    let pearson = HistogramKeyAnalyzer
+        .detect_keys(&c_major(), &config(KeyProfile::KrumhanslKessler, KeyCorrelation::Pearson));
+    assert!((1..1000).contains(&pearson[0].confidence_x1000));
+}
+
+#[test]
+fn short_spans_emit_nothing() {
+    let cfg = KeyConfig::default();
+    assert!(HistogramKeyAnalyzer.detect_keys(&notes(&[(60, 0, 1), (64, 1, 2)]), &cfg).is_empty());
+    assert!(HistogramKeyAnalyzer.detect_keys(&[], &cfg).is_empty());
+}
+
+#[test]
+fn bass_weighting_favours_the_lowest_sounding_note() {
+    // An A minor seventh chord: C, E, G on top of a sustained A.
+    let chord = notes(&[(45, 0, 8), (60, 0, 8), (64, 0, 8), (67, 0, 8), (72, 4, 4)]);
+    let weighted = |weighting| KeyConfig {
+        weighting,
+        ..config(KeyProfile::KrumhanslKessler, KeyCorrelation::Pearson)
+    };
+    let plain = HistogramKeyAnalyzer.detect_keys(&chord, &weighted(KeyWeighting::DurationVelocity));
+    assert_eq!(plain[0].key, key(0, false));
+    let bass = HistogramKeyAnalyzer
+        .detect_keys(&chord, &weighted(KeyWeighting::DurationVelocityBass { bass_weight: 3.0 }));
+    assert_eq!(bass[0].key, key(9, true));
+
+    // A C entering above the held A is not a bass note.
+    let late_bass = notes(&[(45, 0, 8), (60, 0, 8), (64, 0, 8), (67, 0, 8), (48, 4, 4)]);
+    let late = HistogramKeyAnalyzer.detect_keys(
+        &late_bass,
+        &weighted(KeyWeighting::DurationVelocityBass { bass_weight: 3.0 }),
+    );
+    assert_eq!(late[0].key, key(9, true));
+}
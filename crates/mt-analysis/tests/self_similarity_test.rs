@@ -0,0 +1,173 @@
+use mt_alloc::FeatureBuffer;
+use mt_analysis::config::{SegmentConfig, SsmFeatures};
+use mt_analysis::segmenter::self_similarity::pick_peaks;
+use mt_analysis::segmenter::{SelfSimilarityMatrix, SelfSimilaritySegmenter};
+use mt_analysis::traits::SegmentAnalyzer;
+use mt_core::chord::Chord;
+use mt_core::chord_kind::ChordKindId;
+use mt_core::events::{ChordEvent, SegmentEvent, SegmentKind};
+use mt_core::pitch::PitchClass;
+use mt_core::time::SampleTime;
+
+fn buffer(rows: &[[f32; 3]]) -> FeatureBuffer {
+    let mut out = FeatureBuffer::with_capacity(3, rows.len());
+    for r in rows {
+        out.push_frame(r);
+    }
+    out
+}
+
+/// `len` frames of `a`, then `len` frames of `b`.
+fn two_blocks(len: usize) -> FeatureBuffer {
+    let rows: Vec<[f32; 3]> =
+        (0..2 * len).map(|i| if i < len { [1.0, 0.1, 0.0] } else { [0.0, 0.1, 1.0] }).collect();
+    buffer(&rows)
+}
+
+#[test]
+fn ssm_is_symmetric_cosine_with_silent_frames_at_zero() {
+    let ssm = SelfSimilarityMatrix::from_features(&buffer(&[
+        [1.0, 0.0, 0.0],
+        [2.0, 0.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0],
+    ]));
+    assert_eq!(ssm.size(), 4);
+    assert!((ssm.get(0, 1) - 1.0).abs() < 1e-6);
+    assert!((ssm.get(0, 2) - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+    assert_eq!(ssm.get(2, 0), ssm.get(0, 2));
+    assert_eq!(ssm.get(3, 3), 0.0);
+    assert_eq!(ssm.get(0, 4), 0.0);
+}
+
+#[test]
+fn banded_ssm_matches_the_full_matrix_near_the_diagonal() {
+    let features = two_blocks(10);
+    let full = SelfSimilarityMatrix::from_features(&features);
+    let banded = SelfSimilarityMatrix::from_features_within(&features, 3);
+    for i in 0..20_usize {
+        for j in 0..20 {
+            let expected = if i.abs_diff(j) <= 3 { full.get(i, j) } else { 0.0 };
+            assert_eq!(banded.get(i, j), expected, "({i}, {j})");
+        }
+    }
+    assert_eq!(banded.novelty(2), full.novelty(2));
+}
+
+#[test]
+fn novelty_peaks_at_the_block_boundary() {
+    let ssm = SelfSimilarityMatrix::from_features(&two_blocks(10));
+    let novelty = ssm.novelty(4);
+    assert_eq!(novelty.len(), 20);
+    let peak = (0..20).max_by(|&a, &b| novelty[a].total_cmp(&novelty[b])).unwrap();
+    assert_eq!(peak, 10);
+    assert!((novelty[10] - 1.0).abs() < 1e-6);
+    // The kernel does not fit at the edges.
+    assert_eq!(novelty[..4], [0.0; 4]);
+    assert_eq!(pick_peaks(&novelty, 0.1, 4), [10]);
+}
+
+#[test]
+fn pick_peaks_keeps_the_strongest_of_close_peaks() {
+    let mut novelty = vec![0.0; 30];
+    novelty[8] = 0.6;
+    novelty[10] = 1.0;
+    novelty[20] = 0.8;
+    assert_eq!(pick_peaks(&novelty, 0.1, 4), [10, 20]);
+    assert_eq!(pick_peaks(&novelty, 0.9, 4), Vec::<usize>::new());
+}
+
+#[test]
+fn range_similarity_aligns_and_penalizes_length() {
+    let ssm = SelfSimilarityMatrix::from_features(&two_blocks(10));
+    assert!((ssm.range_similarity((0, 5), (5, 10)) - 1.0).abs() < 1e-5);
+    assert!(ssm.range_similarity((0, 10), (10, 20)) < 0.1);
+    // Best alignment is exact, scaled by sqrt(4 / 12).
+    let short = ssm.range_similarity((0, 4), (4, 16));
+    assert!((short - (1.0_f32 / 3.0).sqrt()).abs() < 1e-5, "{short}");
+    assert_eq!(ssm.range_similarity((3, 3), (0, 5)), 0.0);
+}
+
+const SR: u32 = 8_000;
+
+fn chord(root: u8, kind: ChordKindId, second: i64) -> ChordEvent {
+    ChordEvent {
+        chord: Chord::new(PitchClass::new(root).unwrap(), kind, None).unwrap(),
+        onset: SampleTime::new(second * i64::from(SR)),
+        offset: SampleTime::new((second + 1) * i64::from(SR)),
+        confidence_x1000: 1000,
+    }
+}
+
+fn boundaries(segments: &[SegmentEvent]) -> Vec<f32> {
+    segments.iter().skip(1).map(|s| s.onset.value() as f32 / SR as f32).collect()
+}
+
+fn groups(segments: &[SegmentEvent]) -> Vec<u8> {
+    segments.iter().map(|s| s.label.unwrap().group).collect()
+}
+
+#[test]
+fn repeated_chord_sections_are_labelled_and_named() {
+    use ChordKindId::{Maj, Min};
+    let intro = [(4, Min), (11, Min), (4, Min), (11, Min)];
+    let verse = [(0, Maj), (7, Maj), (9, Min), (5, Maj)];
+    let chorus = [(2, Min), (7, Maj), (0, Maj), (4, Min)];
+    // (cycle, seconds): the longer repeated group is the chorus.
+    let form = [(intro, 8), (verse, 16), (chorus, 24), (verse, 16), (chorus, 24)];
+    let mut chords = Vec::new();
+    let mut second = 0;
+    for (cycle, len) in form {
+        for bar in 0..len {
+            let (root, kind) = cycle[bar % 4];
+            chords.push(chord(root, kind, second));
+            second += 1;
+        }
+    }
+    let cfg = SegmentConfig { ssm_features: SsmFeatures::Chords, ..SegmentConfig::default() };
+    let segments = SelfSimilaritySegmenter.detect_segments(&[], SR, &chords, &cfg);
+
+    assert_eq!(groups(&segments), [0, 1, 2, 1, 2]);
+    for (found, expected) in boundaries(&segments).iter().zip([8.0, 24.0, 48.0, 64.0]) {
+        assert!((found - expected).abs() <= 1.0, "{:?}", boundaries(&segments));
+    }
+    let kinds: Vec<SegmentKind> = segments.iter().map(|s| s.kind).collect();
+    use SegmentKind::{Chorus, Intro, Verse};
+    assert_eq!(kinds, [Intro, Verse, Chorus, Verse, Chorus]);
+    assert_eq!(segments.last().unwrap().offset.value(), 88 * i64::from(SR));
+}
+
+/// An A3 tone, 16 s per entry: a sine for `false`, for `true` a brighter
+/// tone with octave partials (same pitch class, different spectral shape).
+fn timbres(order: &[bool]) -> Vec<f32> {
+    let per = 16 * SR as usize;
+    (0..order.len() * per)
+        .map(|i| {
+            let phase = 2.0 * std::f32::consts::PI * 220.0 * i as f32 / SR as f32;
+            let partials: &[f32] = if order[i / per] { &[1.0, 2.0, 4.0, 8.0] } else { &[1.0] };
+            partials.iter().map(|p| 0.2 * (p * phase).sin()).sum()
+        })
+        .collect()
+}
+
+#[test]
+fn mfcc_features_find_repeated_timbres() {
+    let audio = timbres(&[false, true, false, true]);
+    let mfcc = SegmentConfig { ssm_features: SsmFeatures::Mfcc, ..SegmentConfig::default() };
+    let segments = SelfSimilaritySegmenter.detect_segments(&audio, SR, &[], &mfcc);
+    assert_eq!(groups(&segments), [0, 1, 0, 1]);
+    for (found, expected) in boundaries(&segments).iter().zip([16.0, 32.0, 48.0]) {
+        assert!((found - expected).abs() <= 1.0, "{:?}", boundaries(&segments));
+    }
+}
+
+#[test]
+fn no_usable_features_means_no_segments() {
+    let chords_only =
+        SegmentConfig { ssm_features: SsmFeatures::Chords, ..SegmentConfig::default() };
+    let audio = timbres(&[false]);
+    assert!(SelfSimilaritySegmenter.detect_segments(&audio, SR, &[], &chords_only).is_empty());
+    assert!(
+        SelfSimilaritySegmenter.detect_segments(&[], SR, &[], &SegmentConfig::default()).is_empty()
+    );
+}
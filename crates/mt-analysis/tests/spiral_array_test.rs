@@ -0,0 +1,160 @@
+mod common;
+
+use common::notes_at;
+use mt_analysis::config::{KeyConfig, TensionConfig};
+use mt_analysis::key_detector::SpiralArrayKeyAnalyzer;
+use mt_analysis::traits::KeyAnalyzer;
+use mt_core::chord::Chord;
+use mt_core::chord_kind::ChordKindId;
+use mt_core::events::{ChordEvent, NoteEvent};
+use mt_core::key::{Key, KeyMode};
+use mt_core::pitch::PitchClass;
+use mt_core::time::SampleTime;
+
+fn pc(v: u8) -> PitchClass {
+    PitchClass::new(v).unwrap()
+}
+
+fn key(tonic: u8, minor: bool) -> Key {
+    Key::from_semitone(tonic, minor).unwrap()
+}
+
+/// C major scale, one note per second, tonic triad held longer.
+fn c_major(sample_rate: i64) -> Vec<NoteEvent> {
+    notes_at(
+        sample_rate,
+        &[(60, 0, 2), (62, 2, 1), (64, 3, 2), (65, 5, 1), (67, 6, 2), (69, 8, 1), (71, 9, 1)],
+    )
+}
+
+fn chord(onset: i64, offset: i64, root: u8, kind: ChordKindId) -> ChordEvent {
+    ChordEvent {
+        chord: Chord::new(pc(root), kind, None).unwrap(),
+        onset: SampleTime::new(onset),
+        offset: SampleTime::new(offset),
+        confidence_x1000: 1000,
+    }
+}
+
+#[test]
+fn pitches_sit_on_the_helix() {
+    let sa = SpiralArrayKeyAnalyzer::default();
+    let h = sa.params.height;
+    let c = sa.pitch_position(0);
+    let g = sa.pitch_position(1);
+    assert!(c.x.abs() < 1e-6 && (c.y - 1.0).abs() < 1e-6 && c.z.abs() < 1e-6);
+    assert!((g.x - 1.0).abs() < 1e-6 && g.y.abs() < 1e-6 && (g.z - h).abs() < 1e-6);
+    // Four fifths make a major third: same angle, four steps up.
+    let e = sa.pitch_position(4);
+    assert!((e.x - c.x).abs() < 1e-5 && (e.y - c.y).abs() < 1e-5);
+    assert!((e.z - 4.0 * h).abs() < 1e-6);
+}
+
+#[test]
+fn key_representations_are_their_own_nearest_key() {
+    let sa = SpiralArrayKeyAnalyzer::default();
+    // C, G and A on the line of fifths.
+    for (k, tonic) in [(0, 0), (1, 7), (3, 9)] {
+        let (found, distance, confidence) = sa.nearest_key(sa.major_key(k));
+        assert_eq!(found, key(tonic, false));
+        assert!(distance < 1e-5);
+        assert!(confidence > 0);
+        assert_eq!(sa.nearest_key(sa.minor_key(k)).0, key(tonic, true));
+    }
+}
+
+#[test]
+fn center_of_effect_of_a_triad_is_nearest_its_key() {
+    let sa = SpiralArrayKeyAnalyzer::default();
+    let triad = [(pc(0), 0.536), (pc(7), 0.274), (pc(4), 0.19)];
+    let ce = sa.center_of_effect(&triad, 0.0).unwrap();
+    assert!(ce.distance(sa.major_chord(0)) < 1e-5);
+    assert_eq!(sa.nearest_key(ce).0.mode(), KeyMode::Major);
+    assert!(sa.center_of_effect(&[(pc(0), 0.0)], 0.0).is_none());
+
+    // Spelling follows the hint: F# near the sharps, Gb near the flats.
+    let sharp = sa.center_of_effect(&[(pc(6), 1.0)], 5.0).unwrap();
+    let flat = sa.center_of_effect(&[(pc(6), 1.0)], -5.0).unwrap();
+    assert!(sharp.distance(sa.pitch_position(6)) < 1e-5);
+    assert!(flat.distance(sa.pitch_position(-6)) < 1e-5);
+}
+
+#[test]
+fn keys_use_the_configured_sample_rate() {
+    let sa = SpiralArrayKeyAnalyzer::default();
+    for sample_rate in [1000_u32, 44_100, 48_000] {
+        let cfg = KeyConfig { sample_rate, ..KeyConfig::default() };
+        let keys = sa.detect_keys(&c_major(i64::from(sample_rate)), &cfg);
+        assert_eq!(keys[0].key, key(0, false), "{sample_rate} Hz");
+        assert_eq!(keys[0].position, SampleTime::ZERO);
+    }
+
+    // Ten seconds at 1 kHz are far shorter than one region at 44.1 kHz.
+    let keys = sa.detect_keys(&c_major(1000), &KeyConfig::default());
+    assert!(keys.is_empty());
+}
+
+#[test]
+fn keys_from_chords() {
+    let sa = SpiralArrayKeyAnalyzer::default();
+    let sr = 1000;
+    let cfg = KeyConfig { sample_rate: sr, min_region_seconds: 4.0, ..KeyConfig::default() };
+    let chords = [
+        chord(0, 4000, 9, ChordKindId::Min),
+        chord(4000, 5000, 2, ChordKindId::Min),
+        chord(5000, 6000, 4, ChordKindId::Maj),
+        chord(6000, 12_000, 9, ChordKindId::Min),
+    ];
+    let keys = sa.detect_keys_from_chords(&chords, &cfg);
+    assert_eq!(keys.first().unwrap().key, key(9, true));
+    assert_eq!(keys.last().unwrap().key, key(9, true));
+    // Four-second regions every two seconds.
+    assert!(keys.iter().all(|k| k.position.value() % 2000 == 0));
+}
+
+#[test]
+fn tension_rises_away_from_the_key() {
+    let sa = SpiralArrayKeyAnalyzer::default();
+    let sr = 1000;
+    let cfg = TensionConfig { window_seconds: 2.0, hop_seconds: 2.0, sample_rate: sr };
+    let chords = [
+        chord(0, 2000, 0, ChordKindId::Maj),
+        chord(2000, 4000, 7, ChordKindId::Maj),
+        chord(4000, 6000, 6, ChordKindId::Maj),
+        chord(6000, 8000, 0, ChordKindId::Maj),
+        chord(8000, 10_000, 0, ChordKindId::Maj),
+    ];
+    let tension = sa.tension_from_chords(&chords, &cfg);
+    assert_eq!(tension.len(), 5);
+    for (i, t) in tension.iter().enumerate() {
+        let start = i as i64 * 2000;
+        assert_eq!((t.onset.value(), t.offset.value()), (start, start + 2000));
+    }
+    // A triad's cloud diameter does not depend on its root.
+    assert_eq!(tension[0].cloud_diameter_x1000, tension[2].cloud_diameter_x1000);
+    assert_eq!(tension[0].cloud_momentum_x1000, 0);
+    // F# major is far from C major, G major close to it.
+    assert!(tension[2].tensile_strain_x1000 > tension[0].tensile_strain_x1000);
+    assert!(tension[2].tensile_strain_x1000 > 2 * tension[1].tensile_strain_x1000);
+    assert_eq!(tension[3].tensile_strain_x1000, tension[0].tensile_strain_x1000);
+    assert!(tension[2].cloud_momentum_x1000 > tension[1].cloud_momentum_x1000);
+    assert_eq!(tension[4].cloud_momentum_x1000, 0);
+
+    // The same music at another sample rate yields the same curve.
+    let scaled: Vec<ChordEvent> = chords
+        .iter()
+        .map(|c| ChordEvent {
+            onset: SampleTime::new(c.onset.value() * 48),
+            offset: SampleTime::new(c.offset.value() * 48),
+            ..*c
+        })
+        .collect();
+    let at_48k = sa.tension_from_chords(&scaled, &TensionConfig { sample_rate: 48_000, ..cfg });
+    assert_eq!(at_48k.len(), tension.len());
+    for (a, b) in at_48k.iter().zip(&tension) {
+        assert_eq!(a.tensile_strain_x1000, b.tensile_strain_x1000);
+    }
+
+    let notes = c_major(i64::from(sr));
+    assert!(!sa.tension(&notes, &cfg).is_empty());
+}
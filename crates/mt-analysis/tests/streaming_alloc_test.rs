@@ -0,0 +1,200 @@
+//! `process_block` and `reset` must not allocate once `prepare` has run.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use mt_analysis::config::{
+    AudioNoteConfig, KeyConfig, MidiNoteConfig, SegmentConfig, SwingConfig, TempoConfig,
+};
+use mt_analysis::streaming::{
+    StreamingAudioNoteAnalyzer, StreamingEnergySegmenter, StreamingKeyAnalyzer,
+    StreamingMidiNoteAnalyzer, StreamingSwingAnalyzer, StreamingTempoAnalyzer,
+};
+use mt_analysis::traits::{
+    StreamingAnalyzer, StreamingAudioAnalyzer, StreamingMidiAnalyzer, StreamingNoteAnalyzer,
+};
+use mt_core::events::{NoteEvent, NoteId, SegmentEvent, SwingEvent, TempoEvent, TrackId};
+use mt_core::midi::{MidiChannel, MidiEvent, TimedMidiEvent};
+use mt_core::pitch::MidiNote;
+use mt_core::time::SampleTime;
+
+/// Counts allocations made on the current thread while `COUNTING` is set.
+struct CountingAlloc;
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.with(Cell::get) {
+            ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        }
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if COUNTING.with(Cell::get) {
+            ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        }
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Allocations made by `f` on this thread.
+fn allocations(f: impl FnOnce()) -> usize {
+    ALLOCATIONS.with(|n| n.set(0));
+    COUNTING.with(|c| c.set(true));
+    f();
+    COUNTING.with(|c| c.set(false));
+    ALLOCATIONS.with(Cell::get)
+}
+
+const SR: u32 = 8_000;
+const BLOCK: usize = 256;
+
+/// Two seconds of a pulsing 220 Hz tone, so every analyzer has work to do.
+fn audio() -> Vec<f32> {
+    (0..2 * SR as usize)
+        .map(|i| {
+            let gate = if (i / 1000) % 2 == 0 { 0.8 } else { 0.05 };
+            gate * (2.0 * std::f32::consts::PI * 220.0 * i as f32 / SR as f32).sin()
+        })
+        .collect()
+}
+
+fn notes() -> Vec<NoteEvent> {
+    (0..64_i64)
+        .map(|i| NoteEvent {
+            id: NoteId(i as u32),
+            track: TrackId(0),
+            onset: SampleTime::new(i * 2000),
+            offset: SampleTime::new(i * 2000 + 1500),
+            note: MidiNote::new([60, 64, 67, 72][i as usize % 4]).unwrap(),
+            velocity: 80,
+        })
+        .collect()
+}
+
+fn note() -> NoteEvent {
+    notes()[0]
+}
+
+/// Prepare, then stream `audio()` twice around a `reset`.
+fn assert_audio_without_allocation<A>(mut analyzer: A, blank: A::Output)
+where
+    A: StreamingAudioAnalyzer,
+    A::Output: Clone,
+{
+    let samples = audio();
+    let mut out = vec![blank; 8];
+    analyzer.prepare(SR, BLOCK);
+    let n = allocations(|| {
+        for _ in 0..2 {
+            for (i, chunk) in samples.chunks(BLOCK).enumerate() {
+                analyzer.process_block(SampleTime::new((i * BLOCK) as i64), chunk, &mut out);
+            }
+            analyzer.reset();
+        }
+    });
+    assert_eq!(n, 0);
+}
+
+fn assert_notes_without_allocation<A>(mut analyzer: A, blank: A::Output)
+where
+    A: StreamingNoteAnalyzer,
+    A::Output: Clone,
+{
+    let notes = notes();
+    let mut out = vec![blank; 8];
+    analyzer.prepare(SR, BLOCK);
+    let n = allocations(|| {
+        for _ in 0..2 {
+            for chunk in notes.chunks(4) {
+                analyzer.process_block(chunk[0].onset, chunk, &mut out);
+            }
+            analyzer.reset();
+        }
+    });
+    assert_eq!(n, 0);
+}
+
+#[test]
+fn audio_analyzers_do_not_allocate_after_prepare() {
+    assert_audio_without_allocation(
+        StreamingAudioNoteAnalyzer::new(TrackId(0), AudioNoteConfig::default()),
+        note(),
+    );
+    assert_audio_without_allocation(
+        StreamingTempoAnalyzer::new(TempoConfig::default()),
+        TempoEvent { position: SampleTime::ZERO, bpm_x1000: 0 },
+    );
+    let cfg = SegmentConfig { window_seconds: 0.1, min_segment_seconds: 0.1, ..Default::default() };
+    assert_audio_without_allocation(
+        StreamingEnergySegmenter::new(cfg),
+        SegmentEvent {
+            kind: mt_core::events::SegmentKind::Other(0),
+            label: None,
+            onset: SampleTime::ZERO,
+            offset: SampleTime::ZERO,
+            confidence_x1000: 0,
+        },
+    );
+}
+
+#[test]
+fn note_analyzers_do_not_allocate_after_prepare() {
+    let cfg = KeyConfig { min_region_seconds: 1.0, ..KeyConfig::default() };
+    let key = mt_core::events::KeyEvent {
+        key: mt_core::key::Key::new(
+            mt_core::pitch::PitchClass::from_unchecked(0),
+            mt_core::key::KeyMode::Major,
+        ),
+        position: SampleTime::ZERO,
+        confidence_x1000: 0,
+    };
+    assert_notes_without_allocation(StreamingKeyAnalyzer::new(cfg), key);
+
+    let mut swing = StreamingSwingAnalyzer::new(SwingConfig { min_notes: 4 });
+    swing.set_tempo(TempoEvent { position: SampleTime::ZERO, bpm_x1000: 120_000 });
+    assert_notes_without_allocation(
+        swing,
+        SwingEvent { position: SampleTime::ZERO, ratio_x1000: 0 },
+    );
+}
+
+#[test]
+fn midi_note_analyzer_does_not_allocate_after_prepare() {
+    let channel = MidiChannel::new(0).unwrap();
+    let events: Vec<TimedMidiEvent> = (0..64_u32)
+        .flat_map(|i| {
+            let key = MidiNote::new(60 + (i % 12) as u8).unwrap();
+            let start = SampleTime::new(i64::from(i) * BLOCK as i64);
+            [
+                TimedMidiEvent::new(start, 0, MidiEvent::note_on(channel, key, 90)),
+                TimedMidiEvent::new(start, 100, MidiEvent::note_off(channel, key, 0)),
+            ]
+        })
+        .collect();
+    let mut analyzer = StreamingMidiNoteAnalyzer::new(TrackId(0), MidiNoteConfig::default());
+    let mut out = [note(); 8];
+    analyzer.prepare(SR, BLOCK);
+    let n = allocations(|| {
+        for _ in 0..2 {
+            for (i, pair) in events.chunks(2).enumerate() {
+                let start = SampleTime::new((i * BLOCK) as i64);
+                assert_eq!(analyzer.process_block(start, pair, &mut out), 1);
+            }
+            analyzer.reset();
+        }
+    });
+    assert_eq!(n, 0);
+}
@@ -0,0 +1,296 @@
+use mt_analysis::audio_note_detector::SimpleAudioNoteAnalyzer;
+use mt_analysis::config::{
+    AudioNoteConfig, KeyConfig, MidiNoteConfig, SegmentConfig, SwingConfig, TempoConfig,
+};
+use mt_analysis::key_detector::HistogramKeyAnalyzer;
+use mt_analysis::midi_note_detector::SimpleMidiNoteAnalyzer;
+use mt_analysis::segmenter::EnergySegmenter;
+use mt_analysis::streaming::{
+    StreamingAudioNoteAnalyzer, StreamingEnergySegmenter, StreamingKeyAnalyzer,
+    StreamingMidiNoteAnalyzer, StreamingSwingAnalyzer, StreamingTempoAnalyzer,
+};
+use mt_analysis::traits::{
+    AudioNoteAnalyzer, KeyAnalyzer, MidiNoteAnalyzer, SegmentAnalyzer, StreamingAnalyzer,
+    StreamingAudioAnalyzer, StreamingMidiAnalyzer, StreamingNoteAnalyzer,
+};
+use mt_core::events::{
+    NoteEvent, NoteId, SegmentEvent, SegmentKind, SwingEvent, TempoEvent, TrackId,
+};
+use mt_core::midi::{MidiChannel, MidiEvent, MidiEventKind, TimedMidiEvent};
+use mt_core::pitch::MidiNote;
+use mt_core::time::SampleTime;
+
+const SR: u32 = 8_000;
+const BLOCKS: [usize; 4] = [1, 64, 1000, 4096];
+
+/// Feed `samples` from `origin` in `block`-sized pieces and collect every
+/// output (`blank` fills the output buffer).
+fn stream_audio<A>(
+    analyzer: &mut A,
+    origin: i64,
+    samples: &[f32],
+    block: usize,
+    blank: A::Output,
+) -> Vec<A::Output>
+where
+    A: StreamingAudioAnalyzer,
+    A::Output: Clone,
+{
+    analyzer.prepare(SR, block);
+    let mut out = vec![blank; 16];
+    let mut found = Vec::new();
+    for (i, chunk) in samples.chunks(block).enumerate() {
+        let start = SampleTime::new(origin + (i * block) as i64);
+        let n = analyzer.process_block(start, chunk, &mut out);
+        found.extend_from_slice(&out[..n]);
+    }
+    found
+}
+
+fn sine(freq: f32, amp: f32, seconds: f32) -> Vec<f32> {
+    let n = (seconds * SR as f32) as usize;
+    (0..n).map(|i| amp * (2.0 * std::f32::consts::PI * freq * i as f32 / SR as f32).sin()).collect()
+}
+
+fn blank_note() -> NoteEvent {
+    NoteEvent {
+        id: NoteId(0),
+        track: TrackId(0),
+        onset: SampleTime::ZERO,
+        offset: SampleTime::ZERO,
+        note: MidiNote::new(0).unwrap(),
+        velocity: 0,
+    }
+}
+
+fn shifted(notes: &[NoteEvent], by: i64) -> Vec<NoteEvent> {
+    notes
+        .iter()
+        .map(|n| NoteEvent {
+            onset: SampleTime::new(n.onset.value() + by),
+            offset: SampleTime::new(n.offset.value() + by),
+            ..*n
+        })
+        .collect()
+}
+
+#[test]
+fn audio_notes_match_the_batch_analyzer_in_any_block_size() {
+    let cfg = AudioNoteConfig::default();
+    let mut samples = sine(220.0, 0.5, 0.5);
+    samples.extend(sine(330.0, 0.5, 0.5));
+    // Trailing silence closes the last note; the batch analyzer would also
+    // emit a note still sounding at the end.
+    samples.extend(vec![0.0; SR as usize / 2]);
+    let batch = SimpleAudioNoteAnalyzer::new(TrackId(0)).detect_audio_notes(&samples, SR, &cfg);
+    let pitches: Vec<u8> = batch.iter().map(|n| n.note.value()).collect();
+    assert!(pitches.contains(&57) && pitches.contains(&64), "{pitches:?}");
+
+    for block in BLOCKS {
+        let mut streaming = StreamingAudioNoteAnalyzer::new(TrackId(0), cfg);
+        assert_eq!(
+            stream_audio(&mut streaming, 0, &samples, block, blank_note()),
+            batch,
+            "{block}"
+        );
+        let later = stream_audio(&mut streaming, 10_000, &samples, block, blank_note());
+        assert_eq!(later, shifted(&batch, 10_000), "{block}");
+    }
+}
+
+#[test]
+fn energy_segments_match_the_batch_segmenter_except_the_open_tail() {
+    let cfg =
+        SegmentConfig { window_seconds: 1.0, min_segment_seconds: 2.0, ..SegmentConfig::default() };
+    let mut samples = Vec::new();
+    for amp in [0.1, 0.8, 0.1, 0.4] {
+        samples.extend(sine(110.0, amp, 4.0));
+    }
+    let batch = EnergySegmenter.detect_segments(&samples, SR, &[], &cfg);
+    assert_eq!(batch.len(), 4);
+
+    let blank = SegmentEvent {
+        kind: SegmentKind::Other(0),
+        label: None,
+        onset: SampleTime::ZERO,
+        offset: SampleTime::ZERO,
+        confidence_x1000: 0,
+    };
+    for block in BLOCKS {
+        let mut streaming = StreamingEnergySegmenter::new(cfg);
+        let found = stream_audio(&mut streaming, 0, &samples, block, blank);
+        assert_eq!(found, batch[..3], "{block}");
+    }
+}
+
+#[test]
+fn tempo_is_independent_of_block_size() {
+    // 10 ms clicks at 120 BPM.
+    let beat = SR as usize / 2;
+    let samples: Vec<f32> =
+        (0..20 * SR as usize).map(|i| if i % beat < 80 { 1.0 } else { 0.0 }).collect();
+    let blank = TempoEvent { position: SampleTime::ZERO, bpm_x1000: 0 };
+
+    let mut reference = None;
+    for block in BLOCKS {
+        let mut streaming = StreamingTempoAnalyzer::new(TempoConfig::default());
+        let found = stream_audio(&mut streaming, 0, &samples, block, blank);
+        // Within one envelope frame of lag (16 frames per second).
+        let last = found.last().expect("a tempo estimate").bpm_x1000;
+        assert!(last.abs_diff(120_000) <= 4_000, "{last}");
+        assert!(found[0].position.value() <= i64::from(streaming.latency_samples()));
+        assert_eq!(*reference.get_or_insert_with(|| found.clone()), found, "{block}");
+    }
+}
+
+fn midi(position: i64, kind: MidiEventKind, channel: u8, data1: u8, data2: u8) -> TimedMidiEvent {
+    let event = MidiEvent { channel: MidiChannel::new(channel).unwrap(), kind, data1, data2 };
+    TimedMidiEvent::new(SampleTime::new(position), 0, event)
+}
+
+#[test]
+fn midi_notes_match_the_batch_analyzer_in_any_block_size() {
+    use MidiEventKind::{ControlChange, NoteOff, NoteOn};
+    let events = [
+        midi(0, NoteOn, 0, 60, 90),
+        midi(100, NoteOn, 1, 64, 70),
+        midi(500, NoteOff, 0, 60, 0),
+        // Sustain holds 67 past its release.
+        midi(600, ControlChange, 0, 64, 127),
+        midi(700, NoteOn, 0, 67, 80),
+        midi(900, NoteOff, 0, 67, 0),
+        // Retrigger, then note-on with velocity 0 as a release.
+        midi(1000, NoteOn, 1, 64, 60),
+        midi(1500, NoteOn, 1, 64, 0),
+        midi(2000, ControlChange, 0, 64, 0),
+        // Never released: dropped by both.
+        midi(2100, NoteOn, 0, 72, 100),
+    ];
+    let cfg = MidiNoteConfig::default();
+    let batch = SimpleMidiNoteAnalyzer::new(TrackId(3)).detect_midi_notes(&events, &cfg);
+    assert_eq!(batch.len(), 4);
+
+    for block in [1_i64, 64, 1000, 4096] {
+        let mut streaming = StreamingMidiNoteAnalyzer::new(TrackId(3), cfg);
+        streaming.prepare(SR, block as usize);
+        let mut out = [blank_note(); 4];
+        let mut found = Vec::new();
+        for start in (0..2200).step_by(block as usize) {
+            let in_block: Vec<TimedMidiEvent> = events
+                .iter()
+                .filter(|e| (start..start + block).contains(&e.position.value()))
+                .copied()
+                .collect();
+            let n = streaming.process_block(SampleTime::new(start), &in_block, &mut out);
+            found.extend_from_slice(&out[..n]);
+        }
+        assert_eq!(found, batch, "{block}");
+    }
+}
+
+#[test]
+fn midi_notes_that_do_not_fit_are_counted() {
+    use MidiEventKind::{ControlChange, NoteOff, NoteOn};
+    // Three keys held by the sustain pedal all end at its release.
+    let mut events = vec![midi(0, ControlChange, 0, 64, 127)];
+    for key in [60, 64, 67] {
+        events.extend([midi(10, NoteOn, 0, key, 90), midi(20, NoteOff, 0, key, 0)]);
+    }
+    events.push(midi(100, ControlChange, 0, 64, 0));
+
+    let mut streaming = StreamingMidiNoteAnalyzer::new(TrackId(0), MidiNoteConfig::default());
+    streaming.prepare(SR, 128);
+    let mut out = [blank_note(); 1];
+    assert_eq!(streaming.process_block(SampleTime::ZERO, &events, &mut out), 1);
+    assert_eq!(streaming.dropped_outputs(), 2);
+
+    streaming.prepare(SR, 128);
+    assert_eq!(streaming.dropped_outputs(), 0);
+}
+
+/// A C major scale, repeated: `seconds` of quarter notes at 120 BPM.
+fn scale(seconds: i64) -> Vec<NoteEvent> {
+    let beat = i64::from(SR) / 2;
+    (0..2 * seconds)
+        .map(|i| {
+            let pitch = [60, 62, 64, 65, 67, 69, 71, 72][i as usize % 8];
+            NoteEvent {
+                id: NoteId(i as u32),
+                track: TrackId(0),
+                onset: SampleTime::new(i * beat),
+                offset: SampleTime::new((i + 1) * beat),
+                note: MidiNote::new(pitch).unwrap(),
+                velocity: 80,
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn key_matches_the_batch_analyzer() {
+    let cfg = KeyConfig { sample_rate: SR, ..KeyConfig::default() };
+    let notes = scale(8);
+    let batch = HistogramKeyAnalyzer.detect_keys(&notes, &cfg);
+    assert_eq!(batch.len(), 1);
+
+    let mut streaming = StreamingKeyAnalyzer::new(cfg);
+    streaming.prepare(SR, 512);
+    let mut out = [batch[0]; 2];
+    let n = streaming.process_block(SampleTime::ZERO, &notes, &mut out);
+    assert_eq!(out[..n], batch);
+
+    // Note by note: silent until `min_region_seconds`, settling on the
+    // batch key.
+    streaming.reset();
+    let mut found = Vec::new();
+    for (i, note) in notes.iter().enumerate() {
+        let n = streaming.process_block(note.onset, std::slice::from_ref(note), &mut out);
+        if i < 7 {
+            assert_eq!(n, 0);
+        }
+        found.extend_from_slice(&out[..n]);
+    }
+    assert_eq!(found.first().map(|k| k.position), Some(SampleTime::ZERO));
+    assert_eq!(found.last().map(|k| k.key), Some(batch[0].key));
+}
+
+#[test]
+fn swing_is_independent_of_block_size() {
+    // Swung eighths at 120 BPM: the first of each pair takes 2/3 of the beat.
+    let beat = i64::from(SR) / 2;
+    let notes: Vec<NoteEvent> = (0..32)
+        .map(|i| {
+            let onset = (i / 2) * beat + (i % 2) * beat * 2 / 3;
+            NoteEvent {
+                id: NoteId(i as u32),
+                track: TrackId(0),
+                onset: SampleTime::new(onset),
+                offset: SampleTime::new(onset + beat / 4),
+                note: MidiNote::new(60).unwrap(),
+                velocity: 80,
+            }
+        })
+        .collect();
+    let tempo = TempoEvent { position: SampleTime::ZERO, bpm_x1000: 120_000 };
+    let cfg = SwingConfig::default();
+
+    for per_block in [1, 5, 32] {
+        let mut streaming = StreamingSwingAnalyzer::new(cfg);
+        streaming.prepare(SR, 512);
+        streaming.set_tempo(tempo);
+        let mut out = [SwingEvent { position: SampleTime::ZERO, ratio_x1000: 0 }; 2];
+        let mut found = Vec::new();
+        for chunk in notes.chunks(per_block) {
+            let n = streaming.process_block(chunk[0].onset, chunk, &mut out);
+            found.extend_from_slice(&out[..n]);
+        }
+        let ratio = found.last().expect("a swing estimate").ratio_x1000;
+        assert_eq!(ratio, 667, "{per_block}");
+    }
+
+    // No tempo, no swing.
+    let mut untimed = StreamingSwingAnalyzer::new(cfg);
+    untimed.prepare(SR, 512);
+    let mut out = [SwingEvent { position: SampleTime::ZERO, ratio_x1000: 0 }; 2];
+    assert_eq!(untimed.process_block(SampleTime::ZERO, &notes, &mut out), 0);
+}
@@ -1,17 +1,24 @@
 //! `mt-cli analyze`
 //!
-//! Runs full offline analysis and writes JSON to stdout or file.
+//! Runs full offline analysis and writes JSON to stdout or file, a
+//! Standard MIDI File (`--format midi`) with chords, keys, tempo, meter and
+//! section markers for dragging into a DAW, or a MusicXML lead sheet
+//! (`--format musicxml`) for notation software.
 
 use std::fs::File;
 use std::io::{self, Write};
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
-use clap::Args;
+use clap::{Args, ValueEnum};
 use serde::Serialize;
 
-use mt-engine::types::{AnalyzeRequest, AnalyzeResponse};
-use mt-engine::api::analyze_offline;
+use mt_alloc::TempoMap;
+use mt_core::events::{ChordEvent, KeyEvent, NoteEvent, SegmentEvent};
+use mt_engine::api::analyze_offline;
+use mt_engine::types::{AnalyzeRequest, AnalyzeResponse, EngineEvent, Value};
+use mt_formats::musicxml_writer::{MusicXmlExportConfig, write_musicxml};
+use mt_formats::smf_writer::{SmfExportConfig, VoicingStyle, write_smf};
 
 #[derive(Debug, Args)]
 pub struct AnalyzeArgs {
@@ -31,27 +38,69 @@ pub struct AnalyzeArgs {
     #[arg(long = "project-root")]
     pub project_root: Option<PathBuf>,
 
-    /// Output path for JSON result; if omitted, print to stdout.
+    /// Output path for the result; if omitted, print JSON to stdout.
     #[arg(long = "output")]
     pub output: Option<PathBuf>,
+
+    /// Output format; `midi` and `musicxml` require `--output`.
+    #[arg(long = "format", value_enum, default_value_t = OutputFormat::Json)]
+    pub format: OutputFormat,
+
+    /// Ticks per quarter note (MIDI export).
+    #[arg(long = "ppq", default_value_t = 480)]
+    pub ppq: u16,
+
+    /// Chord voicing style (MIDI export).
+    #[arg(long = "voicing", value_enum, default_value_t = Voicing::Close)]
+    pub voicing: Voicing,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Json,
+    /// Standard MIDI File: conductor track plus a chord track.
+    Midi,
+    /// MusicXML lead sheet: notes as one voice with chord symbols.
+    Musicxml,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Voicing {
+    Close,
+    Drop2,
+    Spread,
+}
+
+impl From<Voicing> for VoicingStyle {
+    fn from(v: Voicing) -> Self {
+        match v {
+            Voicing::Close => VoicingStyle::Close,
+            Voicing::Drop2 => VoicingStyle::Drop2,
+            Voicing::Spread => VoicingStyle::Spread,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
-struct JsonEvent<'a> {
+struct JsonEvent {
     kind: &'static str,
-    #[serde(flatten)]
     data: serde_json::Value,
 }
 
 #[derive(Debug, Serialize)]
 struct AnalyzeOutput {
-    events: Vec<serde_json::Value>,
+    /// Sample rate that every event position is expressed in.
+    sample_rate: u32,
+    events: Vec<JsonEvent>,
 }
 
 pub fn run(args: AnalyzeArgs) -> Result<()> {
     if args.audio.is_empty() && args.midi.is_empty() {
         anyhow::bail!("at least one --audio or --midi input is required");
     }
+    if args.format != OutputFormat::Json && args.output.is_none() {
+        anyhow::bail!("--format midi and --format musicxml require --output");
+    }
 
     let req = AnalyzeRequest {
         audio_paths: args.audio,
@@ -62,49 +111,128 @@ pub fn run(args: AnalyzeArgs) -> Result<()> {
 
     let resp = analyze_offline(req).context("engine analyze_offline failed")?;
 
-    let json = encode_response(&resp)?;
-    write_output(&json, args.output.as_ref())
+    let bytes = match args.format {
+        OutputFormat::Json => encode_response(&resp)?.into_bytes(),
+        OutputFormat::Midi => {
+            let config = SmfExportConfig {
+                ppq: args.ppq,
+                voicing: args.voicing.into(),
+                ..SmfExportConfig::default()
+            };
+            encode_midi(&resp, &config)?
+        }
+        OutputFormat::Musicxml => encode_musicxml(&resp).into_bytes(),
+    };
+    write_output(&bytes, args.output.as_ref())
 }
 
+/// Every node's output, one `{"kind", "data"}` entry per event.
 fn encode_response(resp: &AnalyzeResponse) -> Result<String> {
-    use mt-engine::types::EngineEvent;
-
-    let mut out = Vec::with_capacity(resp.events.len());
-
-    for ev in &resp.events {
-        let (kind, value) = match ev {
-            EngineEvent::Tempo(e) => ("tempo", serde_json::to_value(e)?),
-            EngineEvent::Meter(e) => ("meter", serde_json::to_value(e)?),
-            EngineEvent::Swing(e) => ("swing", serde_json::to_value(e)?),
-            EngineEvent::Note(e) => ("note", serde_json::to_value(e)?),
-            EngineEvent::Chord(e) => ("chord", serde_json::to_value(e)?),
-            EngineEvent::Key(e) => ("key", serde_json::to_value(e)?),
-            EngineEvent::Segment(e) => ("segment", serde_json::to_value(e)?),
-        };
-
-        out.push(
-            serde_json::json!({
-                "kind": kind,
-                "data": value
-            })
-        );
+    let mut out = Vec::new();
+    for EngineEvent::NodeOutput { value, .. } in &resp.events {
+        match value {
+            Value::TempoEvents(e) => push_events(&mut out, "tempo", e)?,
+            Value::MeterEvents(e) => push_events(&mut out, "meter", e)?,
+            Value::SwingEvents(e) => push_events(&mut out, "swing", e)?,
+            Value::NoteEvents(e) => push_events(&mut out, "note", e)?,
+            Value::NoteRoles(e) => push_events(&mut out, "note_role", e)?,
+            Value::ChordEvents(e) => push_events(&mut out, "chord", e)?,
+            Value::ChordProbabilities(e) => push_events(&mut out, "chord_probability", e)?,
+            Value::Cadences(e) => push_events(&mut out, "cadence", e)?,
+            Value::KeyEvents(e) => push_events(&mut out, "key", e)?,
+            Value::SegmentEvents(e) => push_events(&mut out, "segment", e)?,
+            _ => {}
+        }
     }
 
-    let root = AnalyzeOutput { events: out };
+    let root = AnalyzeOutput { sample_rate: resp.sample_rate, events: out };
     let s = serde_json::to_string_pretty(&root)?;
     Ok(s)
 }
 
-fn write_output(json: &str, path: Option<&PathBuf>) -> Result<()> {
+fn push_events<T: Serialize>(
+    out: &mut Vec<JsonEvent>,
+    kind: &'static str,
+    events: &[T],
+) -> Result<()> {
+    for e in events {
+        out.push(JsonEvent { kind, data: serde_json::to_value(e)? });
+    }
+    Ok(())
+}
+
+/// Events the score exporters consume, split by kind.
+struct ScoreEvents {
+    tempo_map: TempoMap,
+    notes: Vec<NoteEvent>,
+    chords: Vec<ChordEvent>,
+    keys: Vec<KeyEvent>,
+    segments: Vec<SegmentEvent>,
+}
+
+/// Every node's output counts; positions are at the response's sample rate.
+fn score_events(resp: &AnalyzeResponse) -> ScoreEvents {
+    let mut tempos = Vec::new();
+    let mut meters = Vec::new();
+    let mut out = ScoreEvents {
+        tempo_map: TempoMap::new(resp.sample_rate),
+        notes: Vec::new(),
+        chords: Vec::new(),
+        keys: Vec::new(),
+        segments: Vec::new(),
+    };
+    for EngineEvent::NodeOutput { value, .. } in &resp.events {
+        match value {
+            Value::TempoEvents(e) => tempos.extend_from_slice(e),
+            Value::MeterEvents(e) => meters.extend_from_slice(e),
+            Value::NoteEvents(e) => out.notes.extend_from_slice(e),
+            Value::ChordEvents(e) => out.chords.extend_from_slice(e),
+            Value::KeyEvents(e) => out.keys.extend_from_slice(e),
+            Value::SegmentEvents(e) => out.segments.extend_from_slice(e),
+            _ => {}
+        }
+    }
+
+    // The tempo map requires position order.
+    tempos.sort_by_key(|e| e.position);
+    meters.sort_by_key(|e| e.position);
+    for e in tempos {
+        out.tempo_map.push_tempo(e);
+    }
+    for e in meters {
+        out.tempo_map.push_meter(e);
+    }
+    out
+}
+
+fn encode_midi(resp: &AnalyzeResponse, config: &SmfExportConfig) -> Result<Vec<u8>> {
+    let ev = score_events(resp);
+    write_smf(&ev.tempo_map, &ev.chords, &ev.keys, &ev.segments, config)
+        .context("MIDI export failed")
+}
+
+fn encode_musicxml(resp: &AnalyzeResponse) -> String {
+    let ev = score_events(resp);
+    write_musicxml(
+        &ev.tempo_map,
+        &ev.notes,
+        &ev.chords,
+        &ev.keys,
+        &ev.segments,
+        &MusicXmlExportConfig::default(),
+    )
+}
+
+fn write_output(bytes: &[u8], path: Option<&PathBuf>) -> Result<()> {
     match path {
         Some(p) => {
             let mut file = File::create(p)
                 .with_context(|| format!("failed to create output file {}", p.display()))?;
-            file.write_all(json.as_bytes())?;
+            file.write_all(bytes)?;
         }
         None => {
             let mut stdout = io::stdout().lock();
-            stdout.write_all(json.as_bytes())?;
+            stdout.write_all(bytes)?;
             stdout.write_all(b"\n")?;
         }
     }
@@ -74,6 +74,9 @@ pub fn run(args: DumpTimelineArgs) -> Result<()> {
                     n.velocity
                 );
             }
+            EngineEvent::NoteRole(r) => {
+                println!("[{}] note id={} role={:?}", fmt-smp(r.position), r.note.0, r.role);
+            }
             EngineEvent::Chord(c) => {
                 println!(
                     "[{}-{}] chord={} conf={:.3}",
@@ -83,6 +86,28 @@ pub fn run(args: DumpTimelineArgs) -> Result<()> {
                     c.confidence_x1000 as f64 / 1000.0
                 );
             }
+            EngineEvent::ChordProbability(f) => {
+                let candidates: Vec<String> = f
+                    .candidates()
+                    .iter()
+                    .map(|c| format!("{} ({:.0}%)", c.chord, c.probability_x1000 as f64 / 10.0))
+                    .collect();
+                println!(
+                    "[{}-{}] chords={}",
+                    fmt-smp(f.onset),
+                    fmt-smp(f.offset),
+                    candidates.join(" / ")
+                );
+            }
+            EngineEvent::Cadence(c) => {
+                println!(
+                    "[{}] cadence={} key={} conf={:.3}",
+                    fmt-smp(c.position),
+                    c.kind,
+                    c.key,
+                    c.confidence_x1000 as f64 / 1000.0
+                );
+            }
             EngineEvent::Key(k) => {
                 println!(
                     "[{}] key={} conf={:.3}",
@@ -92,11 +117,13 @@ pub fn run(args: DumpTimelineArgs) -> Result<()> {
                 );
             }
             EngineEvent::Segment(s) => {
+                let label = s.label.map(|l| format!(" label={l}")).unwrap_or_default();
                 println!(
-                    "[{}-{}] segment={:?} conf={:.3}",
+                    "[{}-{}] segment={:?}{} conf={:.3}",
                     fmt-smp(s.onset),
                     fmt-smp(s.offset),
                     s.kind,
+                    label,
                     s.confidence_x1000 as f64 / 1000.0
                 );
             }
@@ -0,0 +1,347 @@
+//! `mt-cli eval`
+//!
+//! Scores `analyze` output against reference annotations with the MIREX
+//! measures of `mt-analysis::eval` and prints a per-file and an aggregate
+//! table.
+//!
+//! Files pair up by name without the extension and an optional kind
+//! suffix, so `song.chords.lab`, `song.keys.lab` and `song.json` all
+//! describe `song` (and `Dr._Robert.lab` describes `Dr._Robert`).
+//!
+//! Positions are read at the sample rate of the `analyze` JSON files, which
+//! must agree, or at 44.1 kHz if every file is in seconds.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use serde::Deserialize;
+
+use mt_alloc::TempoMap;
+use mt_analysis::eval::{
+    BOUNDARY_WINDOWS_MS, FMeasure, FileScores, KEY_GATE, Summary, Vocabulary, WCSR_GATE,
+    beats_from_tempo_map, evaluate,
+};
+use mt_core::time::SampleTime;
+use mt_formats::Annotations;
+use mt_formats::lab::LabKind;
+
+#[derive(Debug, Args)]
+pub struct EvalArgs {
+    /// Reference annotations: `.lab`, `.jams` or `.csv` files, or
+    /// directories of them. Prefix with `chords=`, `keys=`, `segments=`,
+    /// `tempo=` or `beats=` to say what `.lab` files hold (default chords).
+    #[arg(long = "reference", required = true)]
+    pub reference: Vec<String>,
+
+    /// Estimates: `analyze` JSON output, or annotation files as for
+    /// `--reference`.
+    #[arg(long = "estimate", required = true)]
+    pub estimate: Vec<String>,
+
+    /// Fail unless the aggregate meets the release gates (majmin WCSR and
+    /// key score).
+    #[arg(long = "check-gates")]
+    pub check_gates: bool,
+}
+
+/// Annotations per file name.
+type Dataset = BTreeMap<String, Annotations>;
+
+/// Sample rate when no `analyze` JSON fixes one.
+const DEFAULT_SAMPLE_RATE: u32 = 44_100;
+
+/// Kind suffixes dropped from file names, as in `song.chords.lab`.
+const KIND_SUFFIXES: [&str; 5] = ["chords", "keys", "segments", "tempo", "beats"];
+
+pub fn run(args: EvalArgs) -> Result<()> {
+    let references = list(&args.reference)?;
+    let estimates = list(&args.estimate)?;
+    let sample_rate = sample_rate(references.iter().chain(&estimates))?;
+    let references = load(&references, sample_rate)?;
+    let mut estimates = load(&estimates, sample_rate)?;
+    if references.is_empty() {
+        anyhow::bail!("no reference annotations found");
+    }
+
+    let mut rows = Vec::with_capacity(references.len());
+    let mut summary = Summary::default();
+    for (name, reference) in &references {
+        let estimate = estimates.remove(name).unwrap_or_else(|| {
+            eprintln!("warning: no estimate for {name}; scoring it as empty");
+            Annotations::default()
+        });
+        let end = reference.end().max(estimate.end());
+        let estimate = with_tempo_beats(estimate, end, sample_rate);
+        let scores = evaluate(reference, &estimate, sample_rate);
+        summary.add(&scores);
+        rows.push((name.as_str(), scores));
+    }
+    for name in estimates.keys() {
+        eprintln!("warning: no reference for {name}; skipped");
+    }
+
+    print_files(&rows);
+    println!();
+    print_summary(&summary);
+
+    if args.check_gates && !summary.passes_gates() {
+        anyhow::bail!("release quality gates not met");
+    }
+    Ok(())
+}
+
+/// Every file named by `specs`, with what its `.lab` files hold.
+fn list(specs: &[String]) -> Result<Vec<(LabKind, PathBuf)>> {
+    let mut out = Vec::new();
+    for spec in specs {
+        let (kind, path) = split_spec(spec);
+        let path = Path::new(path);
+        let files = if path.is_dir() {
+            let mut files: Vec<PathBuf> = fs::read_dir(path)
+                .with_context(|| format!("failed to list {}", path.display()))?
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<Result<_, _>>()?;
+            files.retain(|f| f.is_file() && format_of(f).is_some());
+            files.sort();
+            files
+        } else {
+            vec![path.to_path_buf()]
+        };
+        out.extend(files.into_iter().map(|file| (kind, file)));
+    }
+    Ok(out)
+}
+
+/// Sample rate of the `analyze` JSON files among `files`; they must agree.
+fn sample_rate<'a>(files: impl IntoIterator<Item = &'a (LabKind, PathBuf)>) -> Result<u32> {
+    let mut first: Option<(u32, &Path)> = None;
+    for (_, file) in files {
+        if format_of(file) != Some(FileFormat::AnalyzeJson) {
+            continue;
+        }
+        let text = fs::read_to_string(file)
+            .with_context(|| format!("failed to read {}", file.display()))?;
+        let header: AnalyzeHeader = serde_json::from_str(&text)
+            .with_context(|| format!("invalid analyze output in {}", file.display()))?;
+        match first {
+            None => first = Some((header.sample_rate, file)),
+            Some((rate, path)) if rate != header.sample_rate => anyhow::bail!(
+                "{} is at {} Hz but {} is at {rate} Hz",
+                file.display(),
+                header.sample_rate,
+                path.display()
+            ),
+            Some(_) => {}
+        }
+    }
+    Ok(first.map_or(DEFAULT_SAMPLE_RATE, |(rate, _)| rate))
+}
+
+/// Read `files` and merge them per file name.
+fn load(files: &[(LabKind, PathBuf)], sample_rate: u32) -> Result<Dataset> {
+    let mut out = Dataset::new();
+    for (kind, file) in files {
+        let annotations = read_file(file, *kind, sample_rate)?;
+        out.entry(file_name(file)).or_default().merge(annotations);
+    }
+    Ok(out)
+}
+
+/// `keys=path` → (`LabKind::Keys`, `path`); no prefix means chords.
+fn split_spec(spec: &str) -> (LabKind, &str) {
+    let kind = |name| match name {
+        "chords" => Some(LabKind::Chords),
+        "keys" => Some(LabKind::Keys),
+        "segments" => Some(LabKind::Segments),
+        "tempo" => Some(LabKind::Tempo),
+        "beats" => Some(LabKind::Beats),
+        _ => None,
+    };
+    spec.split_once('=')
+        .and_then(|(name, path)| Some((kind(name)?, path)))
+        .unwrap_or((LabKind::Chords, spec))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FileFormat {
+    Lab,
+    Jams,
+    Csv,
+    AnalyzeJson,
+}
+
+fn format_of(path: &Path) -> Option<FileFormat> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "lab" | "txt" => Some(FileFormat::Lab),
+        "jams" => Some(FileFormat::Jams),
+        "csv" => Some(FileFormat::Csv),
+        "json" => Some(FileFormat::AnalyzeJson),
+        _ => None,
+    }
+}
+
+fn read_file(path: &Path, kind: LabKind, sample_rate: u32) -> Result<Annotations> {
+    let format = format_of(path)
+        .with_context(|| format!("unsupported annotation format: {}", path.display()))?;
+    let text =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let annotations = match format {
+        FileFormat::Lab => Annotations::from_lab(&text, kind, sample_rate),
+        FileFormat::Jams => Annotations::from_jams(&text, sample_rate),
+        FileFormat::Csv => Annotations::from_csv(&text, sample_rate),
+        FileFormat::AnalyzeJson => return read_analyze_json(&text, path),
+    };
+    annotations.with_context(|| format!("invalid annotations in {}", path.display()))
+}
+
+/// The part of `analyze` JSON output needed before reading any file.
+#[derive(Debug, Deserialize)]
+struct AnalyzeHeader {
+    sample_rate: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnalyzeOutput {
+    events: Vec<JsonEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonEvent {
+    kind: String,
+    data: serde_json::Value,
+}
+
+/// Chords, keys, segments and tempo of `mt-cli analyze` JSON output.
+fn read_analyze_json(text: &str, path: &Path) -> Result<Annotations> {
+    let invalid = || format!("invalid analyze output in {}", path.display());
+    let output: AnalyzeOutput = serde_json::from_str(text).with_context(invalid)?;
+    let mut out = Annotations::default();
+    for ev in output.events {
+        match ev.kind.as_str() {
+            "chord" => out.chords.push(serde_json::from_value(ev.data).with_context(invalid)?),
+            "key" => out.keys.push(serde_json::from_value(ev.data).with_context(invalid)?),
+            "segment" => out.segments.push(serde_json::from_value(ev.data).with_context(invalid)?),
+            "tempo" => out.tempos.push(serde_json::from_value(ev.data).with_context(invalid)?),
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+/// `song.chords.lab` → `song`, `Dr._Robert.lab` → `Dr._Robert`.
+fn file_name(path: &Path) -> String {
+    let mut name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default().into_owned();
+    if format_of(path).is_some()
+        && let Some((stem, _)) = name.rsplit_once('.')
+    {
+        name.truncate(stem.len());
+    }
+    if let Some((stem, suffix)) = name.rsplit_once('.')
+        && KIND_SUFFIXES.contains(&suffix.to_ascii_lowercase().as_str())
+    {
+        name.truncate(stem.len());
+    }
+    name
+}
+
+/// Estimates without beats get one per tempo-map beat up to `end`.
+fn with_tempo_beats(mut estimate: Annotations, end: SampleTime, sample_rate: u32) -> Annotations {
+    if estimate.beats.is_empty() && !estimate.tempos.is_empty() {
+        let mut map = TempoMap::new(sample_rate);
+        for &ev in &estimate.tempos {
+            map.push_tempo(ev);
+        }
+        estimate.beats = beats_from_tempo_map(&map, end);
+    }
+    estimate
+}
+
+fn percent(value: Option<f32>) -> String {
+    value.map_or_else(|| String::from("-"), |v| format!("{:.1}", v * 100.0))
+}
+
+fn columns() -> Vec<String> {
+    let mut out: Vec<String> = Vocabulary::ALL.iter().map(|v| v.name().to_string()).collect();
+    out.push(String::from("seg"));
+    out.push(String::from("key"));
+    for ms in BOUNDARY_WINDOWS_MS {
+        out.push(format!("bound@{}s", f64::from(ms) / 1000.0));
+    }
+    out.push(String::from("pairwise"));
+    out.push(String::from("beat"));
+    out
+}
+
+/// Table cells of one file: percentages, F-measures for the P/R/F scores.
+fn cells(scores: &FileScores) -> Vec<String> {
+    let f = |m: Option<FMeasure>| percent(m.map(|m| m.f_measure));
+    let mut out: Vec<String> = Vocabulary::ALL
+        .iter()
+        .map(|&v| percent(scores.chords.and_then(|c| c.recall(v).value())))
+        .collect();
+    out.push(percent(scores.chords.and_then(|c| c.segmentation.value())));
+    out.push(percent(scores.key));
+    for i in 0..BOUNDARY_WINDOWS_MS.len() {
+        out.push(f(scores.boundaries.map(|b| b[i])));
+    }
+    out.push(f(scores.pairwise));
+    out.push(f(scores.beats));
+    out
+}
+
+fn print_files(rows: &[(&str, FileScores)]) {
+    let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0).max(4);
+    let header = columns();
+    print!("{:<width$}", "file");
+    for column in &header {
+        print!("  {column:>10}");
+    }
+    println!();
+    for (name, scores) in rows {
+        print!("{name:<width$}");
+        for cell in cells(scores) {
+            print!("  {cell:>10}");
+        }
+        println!();
+    }
+}
+
+fn print_summary(summary: &Summary) {
+    println!("files: {}", summary.files);
+    println!("{:<16}  {:>9}  {:>9}  {:>9}", "measure", "score", "precision", "recall");
+    let score = |name: &str, value: Option<f32>| {
+        println!("{name:<16}  {:>9}  {:>9}  {:>9}", percent(value), "-", "-");
+    };
+    let f_measure = |name: &str, value: Option<FMeasure>| {
+        println!(
+            "{name:<16}  {:>9}  {:>9}  {:>9}",
+            percent(value.map(|m| m.f_measure)),
+            percent(value.map(|m| m.precision)),
+            percent(value.map(|m| m.recall))
+        );
+    };
+    for v in Vocabulary::ALL {
+        score(&format!("wcsr {}", v.name()), summary.chords.recall(v).value());
+    }
+    score("segmentation", summary.chords.segmentation.value());
+    score("key", summary.key.value());
+    for (ms, mean) in BOUNDARY_WINDOWS_MS.iter().zip(summary.boundaries) {
+        f_measure(&format!("boundary@{}s", f64::from(*ms) / 1000.0), mean.value());
+    }
+    f_measure("pairwise", summary.pairwise.value());
+    f_measure("beat", summary.beats.value());
+
+    println!();
+    println!(
+        "gates: majmin wcsr {} (>= {:.0}), key {} (>= {:.0}): {}",
+        percent(summary.chords.recall(Vocabulary::MajMin).value()),
+        WCSR_GATE * 100.0,
+        percent(summary.key.value()),
+        KEY_GATE * 100.0,
+        if summary.passes_gates() { "pass" } else { "fail" }
+    );
+}
@@ -4,3 +4,4 @@ pub mod analyze;
 pub mod dump_timeline;
 pub mod benchmark;
 pub mod validate;
+pub mod eval;
@@ -117,7 +117,10 @@ fn encode_events_to_value(resp: &mt-engine::types::AnalyzeResponse) -> Result<Va
             EngineEvent::Meter(e) => ("meter", serde_json::to_value(e)?),
             EngineEvent::Swing(e) => ("swing", serde_json::to_value(e)?),
             EngineEvent::Note(e) => ("note", serde_json::to_value(e)?),
+            EngineEvent::NoteRole(e) => ("note_role", serde_json::to_value(e)?),
             EngineEvent::Chord(e) => ("chord", serde_json::to_value(e)?),
+            EngineEvent::ChordProbability(e) => ("chord_probability", serde_json::to_value(e)?),
+            EngineEvent::Cadence(e) => ("cadence", serde_json::to_value(e)?),
             EngineEvent::Key(e) => ("key", serde_json::to_value(e)?),
             EngineEvent::Segment(e) => ("segment", serde_json::to_value(e)?),
         };
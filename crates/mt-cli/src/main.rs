@@ -13,6 +13,7 @@ use crate::commands::{
     analyze::AnalyzeArgs,
     benchmark::BenchmarkArgs,
     dump_timeline::DumpTimelineArgs,
+    eval::EvalArgs,
     validate::ValidateArgs,
 };
 
@@ -38,6 +39,8 @@ enum Command {
     Benchmark(BenchmarkArgs),
     /// Validate configuration and golden outputs.
     Validate(ValidateArgs),
+    /// Score analysis output against reference annotations.
+    Eval(EvalArgs),
 }
 
 fn main() -> ExitCode {
@@ -57,5 +60,6 @@ fn run() -> Result<()> {
         Command::DumpTimeline(args) => commands::dump_timeline::run(args),
         Command::Benchmark(args) => commands::benchmark::run(args),
         Command::Validate(args) => commands::validate::run(args),
+        Command::Eval(args) => commands::eval::run(args),
     }
 }
@@ -12,6 +12,7 @@ use crate::{
 };
 
 /// Canonical chord: root + kind + optional bass (for inversions/slash).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Chord {
     pub root: PitchClass,
@@ -19,6 +20,16 @@ pub struct Chord {
     pub bass: Option<PitchClass>,
 }
 
+/// How strictly a chord's bass note is validated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum BassPolicy {
+    /// Bass must be a chord tone (inversions only).
+    #[default]
+    ChordTone,
+    /// Any pitch-class may be the bass (slash chords such as C/D).
+    Any,
+}
+
 impl Chord {
     /// Creates a chord; validates that bass (if present) is one of the chord tones.
     pub fn new(
@@ -26,22 +37,47 @@ impl Chord {
         kind: ChordKindId,
         bass: Option<PitchClass>,
     ) -> Result<Self, TheoryError> {
-        if let Some(bass_pc) = bass {
-            let intervals = chord_intervals(kind);
-            let mut is_member = false;
-            for iv in intervals {
-                if root.transpose(*iv as i8) == bass_pc {
-                    is_member = true;
-                    break;
-                }
-            }
-            if !is_member {
-                return Err(TheoryError::InvalidChord);
-            }
+        Self::with_bass_policy(root, kind, bass, BassPolicy::ChordTone)
+    }
+
+    /// Creates a chord, validating the bass according to `policy`.
+    pub fn with_bass_policy(
+        root: PitchClass,
+        kind: ChordKindId,
+        bass: Option<PitchClass>,
+        policy: BassPolicy,
+    ) -> Result<Self, TheoryError> {
+        if let Some(bass_pc) = bass
+            && policy == BassPolicy::ChordTone
+            && !is_chord_tone(root, kind, bass_pc)
+        {
+            return Err(TheoryError::InvalidChord);
         }
 
         Ok(Self { root, kind, bass })
     }
+
+    /// Whether `pc` belongs to the chord (bass excluded).
+    #[must_use]
+    pub fn contains(&self, pc: PitchClass) -> bool {
+        is_chord_tone(self.root, self.kind, pc)
+    }
+
+    /// Bass is a chord tone other than the root (C/E).
+    #[must_use]
+    pub fn is_inversion(&self) -> bool {
+        self.bass.is_some_and(|b| b != self.root && self.contains(b))
+    }
+
+    /// Bass is not a chord tone (C/D).
+    #[must_use]
+    pub fn is_slash(&self) -> bool {
+        self.bass.is_some_and(|b| !self.contains(b))
+    }
+}
+
+fn is_chord_tone(root: PitchClass, kind: ChordKindId, pc: PitchClass) -> bool {
+    chord_intervals(kind).iter().any(|iv| root.transpose(*iv as i8) == pc)
 }
 
 impl fmt::Display for Chord {
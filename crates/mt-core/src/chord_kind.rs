@@ -5,6 +5,7 @@
 use crate::pitch::PitchClass;
 
 /// Stable identifiers for supported chord kinds.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ChordKindId {
     Maj,
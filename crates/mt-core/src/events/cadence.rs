@@ -0,0 +1,72 @@
+//! Cadence events: harmonic arrivals closing a phrase.
+
+use core::fmt;
+
+use crate::{
+    key::Key,
+    time::SampleTime,
+    traits::{HasConfidence, HasPosition},
+};
+
+/// Cadence type by the final two chords in the local key.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CadenceKind {
+    /// V → I, both in root position.
+    PerfectAuthentic,
+    /// V → I with an inversion, or vii° → I.
+    ImperfectAuthentic,
+    /// Phrase ends on V.
+    Half,
+    /// IV → I.
+    Plagal,
+    /// V → vi (VI in minor).
+    Deceptive,
+    /// iv6 → V in minor.
+    PhrygianHalf,
+}
+
+impl CadenceKind {
+    /// Conventional abbreviation (PAC, IAC, HC, PC, DC, PHC).
+    #[must_use]
+    pub const fn abbreviation(self) -> &'static str {
+        match self {
+            Self::PerfectAuthentic => "PAC",
+            Self::ImperfectAuthentic => "IAC",
+            Self::Half => "HC",
+            Self::Plagal => "PC",
+            Self::Deceptive => "DC",
+            Self::PhrygianHalf => "PHC",
+        }
+    }
+}
+
+impl fmt::Display for CadenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.abbreviation())
+    }
+}
+
+/// Detected cadence; `position` is the onset of the arrival chord,
+/// `approach` the onset of the chord before it.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CadenceEvent {
+    pub kind: CadenceKind,
+    pub key: Key,
+    pub approach: SampleTime,
+    pub position: SampleTime,
+    pub confidence_x1000: u16,
+}
+
+impl HasPosition for CadenceEvent {
+    fn position(&self) -> SampleTime {
+        self.position
+    }
+}
+
+impl HasConfidence for CadenceEvent {
+    fn confidence_x1000(&self) -> u16 {
+        self.confidence_x1000
+    }
+}
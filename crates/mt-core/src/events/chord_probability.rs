@@ -0,0 +1,154 @@
+//! Per-frame chord candidate distributions ("chord probability maps").
+
+use crate::{
+    chord::Chord,
+    chord_kind::ChordKindId,
+    pitch::PitchClass,
+    time::SampleTime,
+    traits::{HasConfidence, HasPosition},
+};
+
+/// Maximum number of candidates carried per frame.
+pub const MAX_CHORD_CANDIDATES: usize = 8;
+
+/// One chord hypothesis with its probability.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChordCandidate {
+    pub chord: Chord,
+    /// Probability * 1000 in [0, 1000].
+    pub probability_x1000: u16,
+}
+
+/// Top-N chord candidates for one analysis frame, most probable first.
+///
+/// Fixed capacity so frames stay `Copy` and allocation-free.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChordProbabilityFrame {
+    pub onset: SampleTime,
+    pub offset: SampleTime,
+    candidates: [ChordCandidate; MAX_CHORD_CANDIDATES],
+    len: u8,
+}
+
+impl ChordProbabilityFrame {
+    /// Empty frame over `[onset, offset)`.
+    #[must_use]
+    pub const fn new(onset: SampleTime, offset: SampleTime) -> Self {
+        let filler = ChordCandidate {
+            chord: Chord {
+                root: PitchClass::from_unchecked(0),
+                kind: ChordKindId::Maj,
+                bass: None,
+            },
+            probability_x1000: 0,
+        };
+        Self { onset, offset, candidates: [filler; MAX_CHORD_CANDIDATES], len: 0 }
+    }
+
+    /// Append a candidate; returns `false` if the frame is full.
+    ///
+    /// Callers push in descending probability order.
+    pub fn push(&mut self, candidate: ChordCandidate) -> bool {
+        let len = self.len as usize;
+        if len == MAX_CHORD_CANDIDATES {
+            return false;
+        }
+        self.candidates[len] = candidate;
+        self.len += 1;
+        true
+    }
+
+    /// Candidates in descending probability order.
+    #[must_use]
+    pub fn candidates(&self) -> &[ChordCandidate] {
+        &self.candidates[..self.len as usize]
+    }
+
+    /// Most probable candidate, if any.
+    #[must_use]
+    pub fn best(&self) -> Option<&ChordCandidate> {
+        self.candidates().first()
+    }
+}
+
+impl HasPosition for ChordProbabilityFrame {
+    fn position(&self) -> SampleTime {
+        self.onset
+    }
+}
+
+impl HasConfidence for ChordProbabilityFrame {
+    fn confidence_x1000(&self) -> u16 {
+        self.best().map_or(0, |c| c.probability_x1000)
+    }
+}
+
+/// Frames serialize only their filled candidates, as a list.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use core::fmt;
+
+    use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+    use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+    use super::{ChordProbabilityFrame, MAX_CHORD_CANDIDATES};
+    use crate::time::SampleTime;
+
+    impl Serialize for ChordProbabilityFrame {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut out = serializer.serialize_struct("ChordProbabilityFrame", 3)?;
+            out.serialize_field("onset", &self.onset)?;
+            out.serialize_field("offset", &self.offset)?;
+            out.serialize_field("candidates", self.candidates())?;
+            out.end()
+        }
+    }
+
+    /// Candidates read straight into a frame's fixed-capacity array.
+    struct Candidates(ChordProbabilityFrame);
+
+    impl<'de> Deserialize<'de> for Candidates {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_seq(CandidatesVisitor)
+        }
+    }
+
+    struct CandidatesVisitor;
+
+    impl<'de> Visitor<'de> for CandidatesVisitor {
+        type Value = Candidates;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "at most {MAX_CHORD_CANDIDATES} chord candidates")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Candidates, A::Error> {
+            let mut frame = ChordProbabilityFrame::new(SampleTime::ZERO, SampleTime::ZERO);
+            while let Some(candidate) = seq.next_element()? {
+                if !frame.push(candidate) {
+                    return Err(de::Error::invalid_length(MAX_CHORD_CANDIDATES + 1, &self));
+                }
+            }
+            Ok(Candidates(frame))
+        }
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename = "ChordProbabilityFrame")]
+    struct Repr {
+        onset: SampleTime,
+        offset: SampleTime,
+        candidates: Candidates,
+    }
+
+    impl<'de> Deserialize<'de> for ChordProbabilityFrame {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let Repr { onset, offset, candidates: Candidates(mut frame) } =
+                Repr::deserialize(deserializer)?;
+            frame.onset = onset;
+            frame.offset = offset;
+            Ok(frame)
+        }
+    }
+}
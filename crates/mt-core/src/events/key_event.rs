@@ -6,6 +6,7 @@ use crate::{
     traits::{HasConfidence, HasPosition},
 };
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct KeyEvent {
     pub key: Key,
@@ -2,18 +2,26 @@
 //!
 //! These types are POD-like, deterministic, and reference mt-core primitives.
 
+pub mod cadence;
 pub mod chord_event;
+pub mod chord_probability;
 pub mod key_event;
 pub mod meter;
 pub mod note;
+pub mod note_role;
 pub mod segment;
 pub mod swing;
 pub mod tempo;
+pub mod tension;
 
+pub use cadence::{CadenceEvent, CadenceKind};
 pub use chord_event::ChordEvent;
+pub use chord_probability::{ChordCandidate, ChordProbabilityFrame, MAX_CHORD_CANDIDATES};
 pub use key_event::KeyEvent;
 pub use meter::MeterEvent;
 pub use note::{NoteEvent, NoteId, TrackId};
-pub use segment::{SegmentEvent, SegmentKind};
+pub use note_role::{ChordToneDegree, NonChordToneKind, NoteRole, NoteRoleEvent, SuspensionKind};
+pub use segment::{SectionLabel, SegmentEvent, SegmentKind, SegmentLevel, SegmentNode};
 pub use swing::SwingEvent;
 pub use tempo::TempoEvent;
+pub use tension::TensionEvent;
@@ -3,14 +3,17 @@
 use crate::{pitch::MidiNote, time::SampleTime, traits::HasPosition};
 
 /// Logical track identifier (e.g., MIDI channel, stem index).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct TrackId(pub u16);
 
 /// Stable note identifier within a session/project.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct NoteId(pub u32);
 
 /// Normalized note event with onset/offset.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct NoteEvent {
     pub id: NoteId,
@@ -0,0 +1,81 @@
+//! Per-note harmonic role annotations (chord tones and non-chord tones).
+
+use crate::{events::note::NoteId, time::SampleTime, traits::HasPosition};
+
+/// Chord member a note sounds as.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChordToneDegree {
+    Root,
+    Third,
+    Fifth,
+    Seventh,
+    /// Any other chord member (sus tones, sixths, added ninths).
+    Tension,
+}
+
+/// Suspension figure, named by the intervals above the bass.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SuspensionKind {
+    FourThree,
+    SevenSix,
+    NineEight,
+    /// Prepared and resolved down by step, other intervals.
+    Other,
+}
+
+/// Non-chord-tone figure by approach and departure.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NonChordToneKind {
+    /// Step in, step out in the same direction.
+    Passing,
+    /// Step away and back to the same pitch.
+    Neighbor,
+    /// Held or repeated from a chord tone, resolved down by step.
+    Suspension(SuspensionKind),
+    /// Leap in, step out in the opposite direction.
+    Appoggiatura,
+    /// Step in, leap out in the opposite direction.
+    EscapeTone,
+    /// Chord tone of the next chord, sounded early.
+    Anticipation,
+    /// Pitch held through several chord changes.
+    Pedal,
+    /// None of the above.
+    Unclassified,
+}
+
+/// Harmonic role of a note against the chord sounding with it.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NoteRole {
+    ChordTone(ChordToneDegree),
+    NonChordTone(NonChordToneKind),
+    /// No chord sounds at the note.
+    NoChord,
+}
+
+/// Role annotation for one note; `position` is where the role applies
+/// (the note onset, or the chord change for held suspensions).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NoteRoleEvent {
+    pub note: NoteId,
+    pub position: SampleTime,
+    pub role: NoteRole,
+}
+
+impl NoteRoleEvent {
+    #[must_use]
+    pub const fn is_chord_tone(&self) -> bool {
+        matches!(self.role, NoteRole::ChordTone(_))
+    }
+}
+
+impl HasPosition for NoteRoleEvent {
+    fn position(&self) -> SampleTime {
+        self.position
+    }
+}
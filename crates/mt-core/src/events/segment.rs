@@ -1,5 +1,7 @@
 //! Structural segment events (intro, verse, etc.).
 
+use core::fmt;
+
 use crate::{
     time::SampleTime,
     traits::{HasConfidence, HasPosition},
@@ -7,6 +9,7 @@ use crate::{
 
 /// High-level section kind.
 /// `Other(id)` allows callers to define their own labels without heap.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum SegmentKind {
     Intro,
@@ -18,9 +21,37 @@ pub enum SegmentKind {
     Other(u8),
 }
 
+/// Repetition label: sections sharing `group` repeat the same material,
+/// `variant > 0` marks a varied repeat (A, B, A', A'').
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SectionLabel {
+    /// 0 = A, 1 = B, ...
+    pub group: u8,
+    /// Number of primes.
+    pub variant: u8,
+}
+
+impl fmt::Display for SectionLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.group < 26 {
+            write!(f, "{}", char::from(b'A' + self.group))?;
+        } else {
+            write!(f, "S{}", self.group)?;
+        }
+        for _ in 0..self.variant {
+            f.write_str("'")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct SegmentEvent {
     pub kind: SegmentKind,
+    /// Repetition label, when the segmenter detects repeats.
+    pub label: Option<SectionLabel>,
     pub onset: SampleTime,
     pub offset: SampleTime,
     pub confidence_x1000: u16,
@@ -37,3 +68,36 @@ impl HasConfidence for SegmentEvent {
         self.confidence_x1000
     }
 }
+
+/// Level of a node in a segment tree, coarsest first.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SegmentLevel {
+    Section,
+    Phrase,
+    SubPhrase,
+}
+
+/// Segment inside a hierarchy (section → phrase → sub-phrase).
+///
+/// `parent` indexes the enclosing node in the owning tree; `None` for
+/// sections.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SegmentNode {
+    pub segment: SegmentEvent,
+    pub level: SegmentLevel,
+    pub parent: Option<u32>,
+}
+
+impl HasPosition for SegmentNode {
+    fn position(&self) -> SampleTime {
+        self.segment.onset
+    }
+}
+
+impl HasConfidence for SegmentNode {
+    fn confidence_x1000(&self) -> u16 {
+        self.segment.confidence_x1000
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::{time::SampleTime, traits::HasPosition};
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct TempoEvent {
     /// Position in samples where this tempo becomes active.
@@ -0,0 +1,25 @@
+//! Tonal tension measures over time (Spiral Array geometry).
+
+use crate::{time::SampleTime, traits::HasPosition};
+
+/// Tension measures for one analysis slice.
+///
+/// Distances are in Spiral Array units (radius = 1) * 1000.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TensionEvent {
+    pub onset: SampleTime,
+    pub offset: SampleTime,
+    /// Largest distance between any two pitches sounding in the slice.
+    pub cloud_diameter_x1000: u32,
+    /// Distance moved by the centre of effect since the previous slice.
+    pub cloud_momentum_x1000: u32,
+    /// Distance between the slice's centre of effect and the key.
+    pub tensile_strain_x1000: u32,
+}
+
+impl HasPosition for TensionEvent {
+    fn position(&self) -> SampleTime {
+        self.onset
+    }
+}
@@ -5,6 +5,8 @@
 //! - Chord kinds and chords
 //! - Keys
 //! - MIDI primitives
+//! - MIDI 2.0 Universal MIDI Packet decoding
+//! - Piano pedal model (sustain, sostenuto, half pedal) for note durations
 //! - Timeline events (tempo, meter, notes, chords, keys, segments)
 //! - Tiny shared traits for position and confidence
 //!
@@ -39,19 +41,21 @@ pub mod events;
 pub mod interval;
 pub mod key;
 pub mod midi;
+pub mod pedal;
 pub mod pitch;
 pub mod scale;
 pub mod time;
 pub mod traits;
+pub mod ump;
 
 // Common re-exports for convenience in other crates.
 pub use crate::{
-    chord::Chord,
+    chord::{BassPolicy, Chord},
     chord_kind::{CHORD_KINDS, ChordKind, ChordKindId},
     error::TheoryError,
     interval::{Interval, IntervalClass, IntervalQuality},
     key::{Key, KeyMode},
-    midi::{MidiChannel, MidiEvent, MidiEventKind},
+    midi::{MidiChannel, MidiEvent, MidiEventKind, TimedMidiEvent},
     pitch::{Accidental, Letter, MidiNote, PITCH_CLASS_COUNT, PitchClass, SpelledPitchClass},
     time::{MusicalPosition, SampleTime},
 };
@@ -5,7 +5,7 @@
 
 use core::fmt;
 
-use crate::{error::TheoryError, pitch::MidiNote};
+use crate::{error::TheoryError, pitch::MidiNote, time::SampleTime};
 
 /// MIDI channel 0..15.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -36,7 +36,12 @@ pub enum MidiEventKind {
     NoteOff,
     ControlChange,
     ProgramChange,
+    /// 14-bit bend: `data1` LSB, `data2` MSB (see `MidiEvent::pitch_bend_value`).
     PitchBend,
+    /// Channel pressure (aftertouch): `data1` is the pressure.
+    ChannelPressure,
+    /// Polyphonic key pressure: `data1` key, `data2` pressure.
+    PolyPressure,
     Other,
 }
 
@@ -71,6 +76,94 @@ impl MidiEvent {
             data2: velocity,
         }
     }
+
+    /// Decode a MIDI 1.0 channel voice message from its status and data
+    /// bytes. Returns `None` for system messages (status `0xF0..`) and
+    /// non-status bytes.
+    #[must_use]
+    pub const fn from_bytes(status: u8, data1: u8, data2: u8) -> Option<Self> {
+        let kind = match status >> 4 {
+            0x8 => MidiEventKind::NoteOff,
+            0x9 => MidiEventKind::NoteOn,
+            0xA => MidiEventKind::PolyPressure,
+            0xB => MidiEventKind::ControlChange,
+            0xC => MidiEventKind::ProgramChange,
+            0xD => MidiEventKind::ChannelPressure,
+            0xE => MidiEventKind::PitchBend,
+            _ => return None,
+        };
+        Some(Self {
+            channel: MidiChannel(status & 0x0F),
+            kind,
+            data1: data1 & 0x7F,
+            data2: data2 & 0x7F,
+        })
+    }
+
+    /// Pitch bend of `value` in `-8192..=8191` (clamped), centered at 0.
+    #[must_use]
+    pub const fn pitch_bend(channel: MidiChannel, value: i16) -> Self {
+        let v = if value < -8192 {
+            0
+        } else if value > 8191 {
+            16_383
+        } else {
+            (value as i32 + 8192) as u16
+        };
+        Self {
+            channel,
+            kind: MidiEventKind::PitchBend,
+            data1: (v & 0x7F) as u8,
+            data2: (v >> 7) as u8,
+        }
+    }
+
+    /// Signed 14-bit bend value (`-8192..=8191`, 0 = centered) for
+    /// `PitchBend` events.
+    #[must_use]
+    pub const fn pitch_bend_value(&self) -> Option<i16> {
+        match self.kind {
+            MidiEventKind::PitchBend => {
+                let raw = ((self.data2 as u16 & 0x7F) << 7) | (self.data1 as u16 & 0x7F);
+                Some(raw as i16 - 8192)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// `MidiEvent` with its timing.
+///
+/// `block_offset` is the sample offset within the audio block the event
+/// arrived with; `position` is the absolute timeline position
+/// (block start + offset). Analysis uses `position`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TimedMidiEvent {
+    pub block_offset: u32,
+    pub position: SampleTime,
+    pub event: MidiEvent,
+}
+
+impl TimedMidiEvent {
+    /// Event at `block_offset` samples into the block starting at `block_start`.
+    #[must_use]
+    pub const fn new(block_start: SampleTime, block_offset: u32, event: MidiEvent) -> Self {
+        Self {
+            block_offset,
+            position: SampleTime::new(block_start.value() + block_offset as i64),
+            event,
+        }
+    }
+
+    /// Event at an absolute position, outside any block (offset 0).
+    #[must_use]
+    pub const fn at(position: SampleTime, event: MidiEvent) -> Self {
+        Self {
+            block_offset: 0,
+            position,
+            event,
+        }
+    }
 }
 
 impl fmt::Display for MidiEvent {
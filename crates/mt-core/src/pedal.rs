@@ -0,0 +1,213 @@
+//! Piano pedal model for turning key presses into sounding notes.
+//!
+//! Rules (per channel):
+//! - CC64 (sustain) `>= sustain_threshold` is down: keys released while it
+//!   is down keep sounding until it comes up.
+//! - Half pedal: a CC64 value in `[half_pedal_min, sustain_threshold)`
+//!   neither catches newly released keys nor releases the notes already
+//!   held; they end when the value drops below `half_pedal_min`.
+//! - CC66 (sostenuto) `>= 64` latches the keys that are down at the moment
+//!   it is pressed; only those keep sounding after release, until CC66
+//!   comes up.
+//! - CC67 (soft pedal) `>= 64` is down. It changes timbre and loudness,
+//!   not duration: `soft` reports it, and notes struck while it is down
+//!   have their velocity scaled by `soft_velocity_x1000`.
+//! - `NoteDurationMode::KeyRelease` ignores pedals entirely.
+//!
+//! Keys are tracked as 128-bit sets, so the model needs no allocation.
+
+/// Sustain pedal controller number.
+pub const CC_SUSTAIN: u8 = 64;
+/// Sostenuto pedal controller number.
+pub const CC_SOSTENUTO: u8 = 66;
+/// Soft pedal controller number.
+pub const CC_SOFT: u8 = 67;
+
+/// Which note end to report.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NoteDurationMode {
+    /// Note ends when the string is damped (pedals applied).
+    Sounding,
+    /// Note ends when the key is released (pedals ignored).
+    KeyRelease,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PedalConfig {
+    pub durations: NoteDurationMode,
+    /// CC64 value at or above which sustain is fully down.
+    pub sustain_threshold: u8,
+    /// CC64 value below which held notes are released. Set equal to
+    /// `sustain_threshold` to disable half-pedal handling.
+    pub half_pedal_min: u8,
+    /// Velocity scale for notes struck with the soft pedal down, in
+    /// thousandths (1000 leaves velocities unchanged).
+    pub soft_velocity_x1000: u16,
+}
+
+impl Default for PedalConfig {
+    fn default() -> Self {
+        Self {
+            durations: NoteDurationMode::Sounding,
+            sustain_threshold: 64,
+            half_pedal_min: 32,
+            soft_velocity_x1000: 1000,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ChannelPedals {
+    /// Keys physically down.
+    keys_down: u128,
+    /// Keys released but still sounding because of a pedal.
+    held: u128,
+    /// Keys caught by the sostenuto pedal.
+    latched: u128,
+    /// Sustain is fully down (catches releases).
+    sustain_down: bool,
+    /// Sustain is at least half down (keeps held notes).
+    sustain_holding: bool,
+    sostenuto_down: bool,
+}
+
+impl ChannelPedals {
+    const UP: Self = Self {
+        keys_down: 0,
+        held: 0,
+        latched: 0,
+        sustain_down: false,
+        sustain_holding: false,
+        sostenuto_down: false,
+    };
+}
+
+/// Pedal state for 16 channels.
+///
+/// Feed it every note-on, note-off and control change in time order; it
+/// tells the caller when a sounding note ends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PedalState {
+    cfg: PedalConfig,
+    channels: [ChannelPedals; 16],
+    /// Soft pedal down, one bit per channel.
+    soft: u16,
+}
+
+impl PedalState {
+    #[must_use]
+    pub const fn new(cfg: PedalConfig) -> Self {
+        Self { cfg, channels: [ChannelPedals::UP; 16], soft: 0 }
+    }
+
+    #[must_use]
+    pub fn config(&self) -> PedalConfig {
+        self.cfg
+    }
+
+    /// Forget all pedal and key state.
+    pub fn reset(&mut self) {
+        self.channels = [ChannelPedals::UP; 16];
+        self.soft = 0;
+    }
+
+    /// Whether the soft pedal is down on `channel`.
+    #[must_use]
+    pub fn soft(&self, channel: u8) -> bool {
+        self.soft & (1 << (channel & 0x0F)) != 0
+    }
+
+    /// Velocity of a key struck now on `channel`: scaled by
+    /// `soft_velocity_x1000` while the soft pedal is down, never below 1.
+    #[must_use]
+    pub fn velocity(&self, channel: u8, velocity: u8) -> u8 {
+        if !self.soft(channel) {
+            return velocity;
+        }
+        let scaled = u32::from(velocity) * u32::from(self.cfg.soft_velocity_x1000) / 1000;
+        u8::try_from(scaled.clamp(1, 127)).unwrap_or(127)
+    }
+
+    /// Record a key press.
+    pub fn note_on(&mut self, channel: u8, key: u8) {
+        let ch = &mut self.channels[usize::from(channel & 0x0F)];
+        let bit = key_bit(key);
+        ch.keys_down |= bit;
+        ch.held &= !bit;
+    }
+
+    /// Record a key release; returns `true` if the note ends now, `false`
+    /// if a pedal keeps it sounding.
+    pub fn note_off(&mut self, channel: u8, key: u8) -> bool {
+        let sounding = self.cfg.durations == NoteDurationMode::Sounding;
+        let ch = &mut self.channels[usize::from(channel & 0x0F)];
+        let bit = key_bit(key);
+        ch.keys_down &= !bit;
+        let caught = ch.sustain_down || (ch.sostenuto_down && ch.latched & bit != 0);
+        if sounding && caught {
+            ch.held |= bit;
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Apply a control change; returns the set of keys (bit `k` = key `k`)
+    /// whose notes end now.
+    pub fn control_change(&mut self, channel: u8, controller: u8, value: u8) -> u128 {
+        let cfg = self.cfg;
+        let ch = &mut self.channels[usize::from(channel & 0x0F)];
+        match controller {
+            CC_SUSTAIN => {
+                ch.sustain_down = value >= cfg.sustain_threshold;
+                ch.sustain_holding = value >= cfg.half_pedal_min.min(cfg.sustain_threshold);
+            }
+            CC_SOSTENUTO => {
+                let down = value >= 64;
+                if down && !ch.sostenuto_down {
+                    ch.latched = ch.keys_down;
+                } else if !down {
+                    ch.latched = 0;
+                }
+                ch.sostenuto_down = down;
+            }
+            CC_SOFT => {
+                let bit = 1 << (channel & 0x0F);
+                self.soft = if value >= 64 { self.soft | bit } else { self.soft & !bit };
+                return 0;
+            }
+            _ => return 0,
+        }
+        if ch.sustain_holding {
+            return 0;
+        }
+        let keep = if ch.sostenuto_down { ch.latched } else { 0 };
+        let released = ch.held & !keep;
+        ch.held &= keep;
+        released
+    }
+}
+
+impl Default for PedalState {
+    fn default() -> Self {
+        Self::new(PedalConfig::default())
+    }
+}
+
+/// Iterate the keys in a set returned by `PedalState::control_change`.
+pub fn keys(mut set: u128) -> impl Iterator<Item = u8> {
+    core::iter::from_fn(move || {
+        if set == 0 {
+            return None;
+        }
+        let key = set.trailing_zeros() as u8;
+        set &= set - 1;
+        Some(key)
+    })
+}
+
+fn key_bit(key: u8) -> u128 {
+    1 << (key & 0x7F)
+}
@@ -111,6 +111,8 @@ impl fmt::Display for SpelledPitchClass {
 ///
 /// Invariant is enforced by constructors, except `from_unchecked` which is
 /// for internal, proven-correct usages.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u8", into = "u8"))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct PitchClass(u8);
 
@@ -147,6 +149,20 @@ impl PitchClass {
     }
 }
 
+impl TryFrom<u8> for PitchClass {
+    type Error = TheoryError;
+
+    fn try_from(pc: u8) -> Result<Self, TheoryError> {
+        Self::new(pc)
+    }
+}
+
+impl From<PitchClass> for u8 {
+    fn from(pc: PitchClass) -> Self {
+        pc.0
+    }
+}
+
 impl fmt::Display for PitchClass {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // Default sharp-oriented spelling for debug/UI; not theory-perfect.
@@ -173,6 +189,8 @@ impl fmt::Display for PitchClass {
 ///
 /// Encodes absolute pitch (including octave) in equal temperament.
 /// Higher layers interpret according to tuning if needed.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u8", into = "u8"))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct MidiNote(u8);
 
@@ -203,3 +221,17 @@ impl MidiNote {
         (self.0 / 12) as i8 - 1
     }
 }
+
+impl TryFrom<u8> for MidiNote {
+    type Error = TheoryError;
+
+    fn try_from(value: u8) -> Result<Self, TheoryError> {
+        Self::new(value)
+    }
+}
+
+impl From<MidiNote> for u8 {
+    fn from(note: MidiNote) -> Self {
+        note.0
+    }
+}
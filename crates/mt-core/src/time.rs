@@ -12,6 +12,7 @@ use crate::error::TheoryError;
 ///
 /// Invariant: opaque i64 is allowed to wrap logically, but API users treat it
 /// as a timeline coordinate (e.g., 0 = start).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SampleTime(i64);
 
@@ -0,0 +1,280 @@
+//! MIDI 2.0 Universal MIDI Packet (UMP) decoding.
+//!
+//! Rules:
+//! - A packet is 1–4 32-bit words; its size follows from the message type
+//!   (top nibble of the first word).
+//! - MIDI 1.0 channel voice (type 0x2) and MIDI 2.0 channel voice (type
+//!   0x4) messages are decoded at full resolution; every other type is
+//!   skipped whole as `Ump::Other`.
+//! - `Ump::to_midi1` translates to a `MidiEvent` using the MIDI 2.0
+//!   down-scaling rules; `Ump::controller_to_midi1` expands registered and
+//!   assignable controllers into their four-CC (N)RPN sequence. Per-note
+//!   controllers and per-note pitch bend have no MIDI 1.0 form.
+
+use crate::midi::{MidiChannel, MidiEvent, MidiEventKind};
+
+/// Note attribute type carrying pitch in 7.9 fixed point (semitones).
+pub const ATTRIBUTE_PITCH_7_9: u8 = 3;
+/// Registered per-note controller: absolute pitch, 7.25 fixed point.
+pub const PER_NOTE_PITCH_7_25: u8 = 3;
+/// Registered per-note controller used for timbre (mirrors CC74).
+pub const PER_NOTE_TIMBRE: u8 = 74;
+
+/// Words in a packet of the given message type.
+#[must_use]
+pub const fn packet_words(message_type: u8) -> usize {
+    match message_type & 0x0F {
+        0x0..=0x2 | 0x6 | 0x7 => 1,
+        0x3 | 0x4 | 0x8..=0xA => 2,
+        0xB | 0xC => 3,
+        _ => 4,
+    }
+}
+
+/// One decoded packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ump {
+    /// MIDI 1.0 channel voice message in UMP form.
+    Midi1 { group: u8, event: MidiEvent },
+    /// MIDI 2.0 channel voice message.
+    Midi2 { group: u8, channel: MidiChannel, message: Midi2Voice },
+    /// Any other message type (utility, system, data, stream, ...).
+    Other { message_type: u8 },
+}
+
+/// MIDI 2.0 channel voice message body. Values keep their full width.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Midi2Voice {
+    NoteOff {
+        note: u8,
+        velocity: u16,
+        attribute_type: u8,
+        attribute: u16,
+    },
+    NoteOn {
+        note: u8,
+        velocity: u16,
+        attribute_type: u8,
+        attribute: u16,
+    },
+    PolyPressure {
+        note: u8,
+        value: u32,
+    },
+    RegisteredPerNoteController {
+        note: u8,
+        index: u8,
+        value: u32,
+    },
+    AssignablePerNoteController {
+        note: u8,
+        index: u8,
+        value: u32,
+    },
+    /// RPN: `bank` is the MSB, `index` the LSB.
+    RegisteredController {
+        bank: u8,
+        index: u8,
+        value: u32,
+    },
+    /// NRPN: `bank` is the MSB, `index` the LSB.
+    AssignableController {
+        bank: u8,
+        index: u8,
+        value: u32,
+    },
+    /// Per-note pitch bend, `0x8000_0000` = centered.
+    PerNotePitchBend {
+        note: u8,
+        value: u32,
+    },
+    ControlChange {
+        index: u8,
+        value: u32,
+    },
+    /// `bank` is `(msb, lsb)` when the bank-valid flag is set.
+    ProgramChange {
+        program: u8,
+        bank: Option<(u8, u8)>,
+    },
+    ChannelPressure {
+        value: u32,
+    },
+    /// Pitch bend, `0x8000_0000` = centered.
+    PitchBend {
+        value: u32,
+    },
+    PerNoteManagement {
+        note: u8,
+        flags: u8,
+    },
+    /// Reserved or relative-controller status.
+    Other {
+        status: u8,
+    },
+}
+
+impl Midi2Voice {
+    /// Pitch of a note on/off with a pitch 7.9 attribute, in cents
+    /// (MIDI 60 = 6000).
+    #[must_use]
+    pub const fn attribute_pitch_cents(&self) -> Option<i32> {
+        match *self {
+            Self::NoteOn { attribute_type: ATTRIBUTE_PITCH_7_9, attribute, .. }
+            | Self::NoteOff { attribute_type: ATTRIBUTE_PITCH_7_9, attribute, .. } => {
+                Some((attribute as i32 * 100 + 256) / 512)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Ump {
+    /// Decode the packet at the start of `words`; returns it and its size
+    /// in words, or `None` if `words` holds only part of a packet.
+    #[must_use]
+    pub fn decode(words: &[u32]) -> Option<(Self, usize)> {
+        let w0 = *words.first()?;
+        let message_type = (w0 >> 28) as u8;
+        let len = packet_words(message_type);
+        if words.len() < len {
+            return None;
+        }
+        let group = ((w0 >> 24) & 0x0F) as u8;
+        let status = ((w0 >> 16) & 0xFF) as u8;
+        let (b3, b4) = (((w0 >> 8) & 0xFF) as u8, (w0 & 0xFF) as u8);
+        let ump = match message_type {
+            0x2 => match MidiEvent::from_bytes(status, b3, b4) {
+                Some(event) => Self::Midi1 { group, event },
+                None => Self::Other { message_type },
+            },
+            0x4 => {
+                let channel = MidiChannel::new(status & 0x0F).ok()?;
+                let message = midi2_voice(status >> 4, b3 & 0x7F, b4, words[1]);
+                Self::Midi2 { group, channel, message }
+            }
+            _ => Self::Other { message_type },
+        };
+        Some((ump, len))
+    }
+
+    /// MIDI 1.0 equivalent, scaled down per the MIDI 2.0 translation rules.
+    #[must_use]
+    pub fn to_midi1(&self) -> Option<MidiEvent> {
+        let (channel, message) = match *self {
+            Self::Midi1 { event, .. } => return Some(event),
+            Self::Midi2 { channel, message, .. } => (channel, message),
+            Self::Other { .. } => return None,
+        };
+        let ev = |kind, data1: u8, data2: u8| Some(MidiEvent { channel, kind, data1, data2 });
+        match message {
+            Midi2Voice::NoteOff { note, velocity, .. } => {
+                ev(MidiEventKind::NoteOff, note, (velocity >> 9) as u8)
+            }
+            // A MIDI 2.0 note-on never means note-off, so velocity stays >= 1.
+            Midi2Voice::NoteOn { note, velocity, .. } => {
+                ev(MidiEventKind::NoteOn, note, ((velocity >> 9) as u8).max(1))
+            }
+            Midi2Voice::PolyPressure { note, value } => {
+                ev(MidiEventKind::PolyPressure, note, scale_to_7(value))
+            }
+            Midi2Voice::ControlChange { index, value } => {
+                ev(MidiEventKind::ControlChange, index, scale_to_7(value))
+            }
+            Midi2Voice::ProgramChange { program, .. } => {
+                ev(MidiEventKind::ProgramChange, program, 0)
+            }
+            Midi2Voice::ChannelPressure { value } => {
+                ev(MidiEventKind::ChannelPressure, scale_to_7(value), 0)
+            }
+            Midi2Voice::PitchBend { value } => {
+                let v = value >> 18;
+                ev(MidiEventKind::PitchBend, (v & 0x7F) as u8, (v >> 7) as u8)
+            }
+            _ => None,
+        }
+    }
+
+    /// MIDI 1.0 form of a registered or assignable controller: the
+    /// (N)RPN select pair followed by data entry MSB and LSB.
+    #[must_use]
+    pub fn controller_to_midi1(&self) -> Option<[MidiEvent; 4]> {
+        let Self::Midi2 { channel, message, .. } = *self else {
+            return None;
+        };
+        let (select, bank, index, value) = match message {
+            Midi2Voice::RegisteredController { bank, index, value } => {
+                ((101, 100), bank, index, value)
+            }
+            Midi2Voice::AssignableController { bank, index, value } => {
+                ((99, 98), bank, index, value)
+            }
+            _ => return None,
+        };
+        let cc = |data1, data2: u8| MidiEvent {
+            channel,
+            kind: MidiEventKind::ControlChange,
+            data1,
+            data2: data2 & 0x7F,
+        };
+        Some([
+            cc(select.0, bank),
+            cc(select.1, index),
+            cc(6, scale_to_7(value)),
+            cc(38, (value >> 18) as u8),
+        ])
+    }
+}
+
+/// Iterate the packets in a word stream; a trailing partial packet is
+/// ignored.
+pub fn decode(mut words: &[u32]) -> impl Iterator<Item = Ump> + '_ {
+    core::iter::from_fn(move || {
+        let (ump, len) = Ump::decode(words)?;
+        words = &words[len..];
+        Some(ump)
+    })
+}
+
+/// 32-bit controller value to 7 bits.
+#[must_use]
+pub const fn scale_to_7(value: u32) -> u8 {
+    (value >> 25) as u8
+}
+
+/// Cents of a 32-bit bend over a range of `range_cents` either side of
+/// center.
+#[must_use]
+pub const fn bend_cents(value: u32, range_cents: i32) -> i32 {
+    ((value as i64 - 0x8000_0000) * range_cents as i64 / 0x8000_0000) as i32
+}
+
+/// Absolute pitch in 7.25 fixed point (semitones) to cents.
+#[must_use]
+pub const fn pitch_7_25_cents(value: u32) -> i32 {
+    ((value as i64 * 100) >> 25) as i32
+}
+
+fn midi2_voice(opcode: u8, b3: u8, b4: u8, data: u32) -> Midi2Voice {
+    let velocity = (data >> 16) as u16;
+    let attribute = (data & 0xFFFF) as u16;
+    match opcode {
+        0x0 => Midi2Voice::RegisteredPerNoteController { note: b3, index: b4, value: data },
+        0x1 => Midi2Voice::AssignablePerNoteController { note: b3, index: b4, value: data },
+        0x2 => Midi2Voice::RegisteredController { bank: b3, index: b4 & 0x7F, value: data },
+        0x3 => Midi2Voice::AssignableController { bank: b3, index: b4 & 0x7F, value: data },
+        0x6 => Midi2Voice::PerNotePitchBend { note: b3, value: data },
+        0x8 => Midi2Voice::NoteOff { note: b3, velocity, attribute_type: b4, attribute },
+        0x9 => Midi2Voice::NoteOn { note: b3, velocity, attribute_type: b4, attribute },
+        0xA => Midi2Voice::PolyPressure { note: b3, value: data },
+        0xB => Midi2Voice::ControlChange { index: b3, value: data },
+        0xC => {
+            let bank = (b4 & 1 == 1).then_some((((data >> 8) & 0x7F) as u8, (data & 0x7F) as u8));
+            Midi2Voice::ProgramChange { program: ((data >> 24) & 0x7F) as u8, bank }
+        }
+        0xD => Midi2Voice::ChannelPressure { value: data },
+        0xE => Midi2Voice::PitchBend { value: data },
+        0xF => Midi2Voice::PerNoteManagement { note: b3, flags: b4 },
+        _ => Midi2Voice::Other { status: opcode << 4 },
+    }
+}
@@ -1,7 +1,7 @@
 #![allow(clippy::expect_used, clippy::panic, clippy::unwrap_used)]
 
 use mt_core::TheoryError;
-use mt_core::chord::Chord;
+use mt_core::chord::{BassPolicy, Chord};
 use mt_core::chord_kind::ChordKindId;
 use mt_core::pitch::PitchClass;
 
@@ -20,3 +20,27 @@ fn chord_rejects_non_member_bass() {
     let err = Chord::new(root, ChordKindId::Maj, Some(bass));
     assert_eq!(err, Err(TheoryError::InvalidChord));
 }
+
+#[test]
+fn chord_allows_slash_bass_with_any_policy() {
+    let root = PitchClass::new(0).unwrap();
+    let bass = PitchClass::new(2).unwrap(); // D
+    let chord =
+        Chord::with_bass_policy(root, ChordKindId::Maj, Some(bass), BassPolicy::Any).unwrap();
+    assert!(chord.is_slash());
+    assert!(!chord.is_inversion());
+    assert_eq!(chord.to_string(), "C/D");
+
+    let strict = Chord::with_bass_policy(root, ChordKindId::Maj, Some(bass), BassPolicy::ChordTone);
+    assert_eq!(strict, Err(TheoryError::InvalidChord));
+}
+
+#[test]
+fn chord_distinguishes_inversion_from_root_position() {
+    let root = PitchClass::new(0).unwrap();
+    let inverted = Chord::new(root, ChordKindId::Maj, Some(PitchClass::new(4).unwrap())).unwrap();
+    let rooted = Chord::new(root, ChordKindId::Maj, Some(root)).unwrap();
+    assert!(inverted.is_inversion());
+    assert!(!inverted.is_slash());
+    assert!(!rooted.is_inversion());
+}
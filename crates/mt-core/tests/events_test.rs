@@ -3,8 +3,10 @@
 use mt_core::chord::Chord;
 use mt_core::chord_kind::ChordKindId;
 use mt_core::events::{
-    ChordEvent, KeyEvent, MeterEvent, NoteEvent, NoteId, SegmentEvent, SegmentKind, SwingEvent,
-    TempoEvent, TrackId,
+    CadenceEvent, CadenceKind, ChordCandidate, ChordEvent, ChordProbabilityFrame, ChordToneDegree,
+    KeyEvent, MAX_CHORD_CANDIDATES, MeterEvent, NonChordToneKind, NoteEvent, NoteId, NoteRole,
+    NoteRoleEvent, SectionLabel, SegmentEvent, SegmentKind, SegmentLevel, SegmentNode,
+    SuspensionKind, SwingEvent, TempoEvent, TensionEvent, TrackId,
 };
 use mt_core::key::{Key, KeyMode};
 use mt_core::pitch::{MidiNote, PitchClass};
@@ -66,6 +68,7 @@ fn key_and_segment_events_forward_confidence() {
 
     let segment_event = SegmentEvent {
         kind: SegmentKind::Chorus,
+        label: None,
         onset: SampleTime::new(16_000),
         offset: SampleTime::new(24_000),
         confidence_x1000: 640,
@@ -82,3 +85,103 @@ fn key_and_segment_events_forward_confidence() {
     assert_eq!(segment_event.position(), SampleTime::new(16_000));
     assert_eq!(swing.position(), SampleTime::new(18_000));
 }
+
+#[test]
+fn tension_event_uses_onset_for_position() {
+    let tension = TensionEvent {
+        onset: SampleTime::new(22_050),
+        offset: SampleTime::new(44_100),
+        cloud_diameter_x1000: 2_129,
+        cloud_momentum_x1000: 730,
+        tensile_strain_x1000: 806,
+    };
+
+    assert_eq!(tension.position(), SampleTime::new(22_050));
+}
+
+#[test]
+fn chord_probability_frame_keeps_candidates_in_order_up_to_capacity() {
+    let g7 = Chord {
+        root: PitchClass::from_unchecked(7),
+        kind: ChordKindId::Dom7,
+        bass: None,
+    };
+    let mut frame = ChordProbabilityFrame::new(SampleTime::new(0), SampleTime::new(22_050));
+    assert!(frame.best().is_none());
+    assert_eq!(frame.confidence_x1000(), 0);
+
+    for p in (0..MAX_CHORD_CANDIDATES as u16).rev() {
+        assert!(frame.push(ChordCandidate {
+            chord: g7,
+            probability_x1000: 100 + p,
+        }));
+    }
+    assert!(!frame.push(ChordCandidate {
+        chord: g7,
+        probability_x1000: 1,
+    }));
+
+    assert_eq!(frame.candidates().len(), MAX_CHORD_CANDIDATES);
+    assert_eq!(frame.best().map(|c| c.chord), Some(g7));
+    assert_eq!(frame.confidence_x1000(), 100 + MAX_CHORD_CANDIDATES as u16 - 1);
+    assert_eq!(frame.position(), SampleTime::new(0));
+}
+
+#[test]
+fn section_label_formats_group_letter_and_primes() {
+    assert_eq!(SectionLabel { group: 0, variant: 0 }.to_string(), "A");
+    assert_eq!(SectionLabel { group: 1, variant: 2 }.to_string(), "B''");
+}
+
+#[test]
+fn segment_node_forwards_segment_position_and_confidence() {
+    let node = SegmentNode {
+        segment: SegmentEvent {
+            kind: SegmentKind::Verse,
+            label: None,
+            onset: SampleTime::new(88_200),
+            offset: SampleTime::new(176_400),
+            confidence_x1000: 640,
+        },
+        level: SegmentLevel::Phrase,
+        parent: Some(0),
+    };
+
+    assert_eq!(node.position(), SampleTime::new(88_200));
+    assert_eq!(node.confidence_x1000(), 640);
+    assert!(SegmentLevel::Section < node.level);
+}
+
+#[test]
+fn cadence_event_reports_arrival_and_abbreviation() {
+    let cadence = CadenceEvent {
+        kind: CadenceKind::PhrygianHalf,
+        key: Key::new(PitchClass::new(9).unwrap(), KeyMode::Minor),
+        approach: SampleTime::new(44_100),
+        position: SampleTime::new(88_200),
+        confidence_x1000: 710,
+    };
+
+    assert_eq!(cadence.position(), SampleTime::new(88_200));
+    assert_eq!(cadence.confidence_x1000(), 710);
+    assert_eq!(cadence.kind.to_string(), "PHC");
+    assert_eq!(CadenceKind::PerfectAuthentic.abbreviation(), "PAC");
+}
+
+#[test]
+fn note_role_event_distinguishes_chord_tones() {
+    let third = NoteRoleEvent {
+        note: NoteId(7),
+        position: SampleTime::new(44_100),
+        role: NoteRole::ChordTone(ChordToneDegree::Third),
+    };
+    let suspension = NoteRoleEvent {
+        note: NoteId(8),
+        position: SampleTime::new(88_200),
+        role: NoteRole::NonChordTone(NonChordToneKind::Suspension(SuspensionKind::FourThree)),
+    };
+
+    assert!(third.is_chord_tone());
+    assert!(!suspension.is_chord_tone());
+    assert_eq!(suspension.position(), SampleTime::new(88_200));
+}
@@ -1,8 +1,9 @@
 #![allow(clippy::expect_used, clippy::panic, clippy::unwrap_used)]
 
 use mt_core::TheoryError;
-use mt_core::midi::{MidiChannel, MidiEvent, MidiEventKind};
+use mt_core::midi::{MidiChannel, MidiEvent, MidiEventKind, TimedMidiEvent};
 use mt_core::pitch::MidiNote;
+use mt_core::time::SampleTime;
 
 #[test]
 fn midi_channel_bounds() {
@@ -29,3 +30,16 @@ fn midi_note_on_and_off_builders() {
     assert_eq!(off.kind, MidiEventKind::NoteOff);
     assert_eq!(off.data2, 64);
 }
+
+#[test]
+fn timed_midi_event_positions() {
+    let on = MidiEvent::note_on(MidiChannel::new(0).unwrap(), MidiNote::new(60).unwrap(), 90);
+
+    let timed = TimedMidiEvent::new(SampleTime::new(44_100), 32, on);
+    assert_eq!(timed.block_offset, 32);
+    assert_eq!(timed.position, SampleTime::new(44_132));
+    assert_eq!(timed.event, on);
+
+    let at = TimedMidiEvent::at(SampleTime::new(7), on);
+    assert_eq!((at.block_offset, at.position), (0, SampleTime::new(7)));
+}
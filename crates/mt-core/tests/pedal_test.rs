@@ -0,0 +1,71 @@
+#![allow(clippy::expect_used, clippy::panic, clippy::unwrap_used)]
+
+use mt_core::pedal::{
+    CC_SOFT, CC_SOSTENUTO, CC_SUSTAIN, NoteDurationMode, PedalConfig, PedalState, keys,
+};
+
+#[test]
+fn sustain_holds_released_keys_until_pedal_up() {
+    let mut p = PedalState::default();
+    p.note_on(0, 60);
+    assert_eq!(p.control_change(0, CC_SUSTAIN, 127), 0);
+    assert!(!p.note_off(0, 60));
+    p.note_on(0, 64);
+    assert!(!p.note_off(0, 64));
+    // Other channels are independent.
+    p.note_on(1, 60);
+    assert!(p.note_off(1, 60));
+
+    let released: Vec<u8> = keys(p.control_change(0, CC_SUSTAIN, 0)).collect();
+    assert_eq!(released, [60, 64]);
+}
+
+#[test]
+fn half_pedal_keeps_but_does_not_catch() {
+    let mut p = PedalState::default();
+    p.note_on(0, 60);
+    p.control_change(0, CC_SUSTAIN, 127);
+    assert!(!p.note_off(0, 60));
+    assert_eq!(p.control_change(0, CC_SUSTAIN, 40), 0);
+    p.note_on(0, 62);
+    assert!(p.note_off(0, 62));
+    assert_eq!(keys(p.control_change(0, CC_SUSTAIN, 10)).collect::<Vec<_>>(), [60]);
+}
+
+#[test]
+fn sostenuto_latches_only_keys_down_when_pressed() {
+    let mut p = PedalState::default();
+    p.note_on(0, 48);
+    p.control_change(0, CC_SOSTENUTO, 127);
+    p.note_on(0, 72);
+    assert!(!p.note_off(0, 48));
+    assert!(p.note_off(0, 72));
+    assert_eq!(keys(p.control_change(0, CC_SOSTENUTO, 0)).collect::<Vec<_>>(), [48]);
+}
+
+#[test]
+fn key_release_mode_ignores_pedals() {
+    let cfg = PedalConfig { durations: NoteDurationMode::KeyRelease, ..PedalConfig::default() };
+    let mut p = PedalState::new(cfg);
+    p.note_on(0, 60);
+    p.control_change(0, CC_SUSTAIN, 127);
+    assert!(p.note_off(0, 60));
+    assert_eq!(p.control_change(0, CC_SUSTAIN, 0), 0);
+}
+
+#[test]
+fn soft_pedal_scales_velocity_but_not_duration() {
+    let mut p = PedalState::new(PedalConfig { soft_velocity_x1000: 500, ..PedalConfig::default() });
+    assert_eq!(p.velocity(0, 100), 100);
+    assert_eq!(p.control_change(0, CC_SOFT, 127), 0);
+    assert!(p.soft(0) && !p.soft(1));
+    assert_eq!(p.velocity(0, 100), 50);
+    assert_eq!(p.velocity(0, 1), 1);
+    assert_eq!(p.velocity(1, 100), 100);
+
+    p.note_on(0, 60);
+    assert!(p.note_off(0, 60));
+    assert_eq!(p.control_change(0, CC_SOFT, 0), 0);
+    assert!(!p.soft(0));
+    assert_eq!(p.velocity(0, 100), 100);
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "serde")]
+#![allow(clippy::expect_used, clippy::panic, clippy::unwrap_used)]
+
+use mt_core::chord::Chord;
+use mt_core::chord_kind::ChordKindId;
+use mt_core::events::{
+    ChordCandidate, ChordEvent, ChordProbabilityFrame, KeyEvent, MAX_CHORD_CANDIDATES, NoteEvent,
+    NoteId, TrackId,
+};
+use mt_core::key::{Key, KeyMode};
+use mt_core::pitch::{MidiNote, PitchClass};
+use mt_core::time::SampleTime;
+
+fn candidate(root: u8, probability_x1000: u16) -> ChordCandidate {
+    ChordCandidate {
+        chord: Chord::new(PitchClass::new(root).unwrap(), ChordKindId::Maj, None).unwrap(),
+        probability_x1000,
+    }
+}
+
+#[test]
+fn events_round_trip_through_json() {
+    let chord = ChordEvent {
+        chord: candidate(7, 0).chord,
+        onset: SampleTime::new(100),
+        offset: SampleTime::new(200),
+        confidence_x1000: 900,
+    };
+    let key = KeyEvent {
+        key: Key::new(PitchClass::new(9).unwrap(), KeyMode::Minor),
+        position: SampleTime::new(0),
+        confidence_x1000: 750,
+    };
+    let note = NoteEvent {
+        id: NoteId(3),
+        track: TrackId(1),
+        onset: SampleTime::new(10),
+        offset: SampleTime::new(20),
+        note: MidiNote::new(64).unwrap(),
+        velocity: 90,
+    };
+
+    let json = serde_json::to_string(&chord).unwrap();
+    assert_eq!(serde_json::from_str::<ChordEvent>(&json).unwrap(), chord);
+    let json = serde_json::to_string(&key).unwrap();
+    assert_eq!(serde_json::from_str::<KeyEvent>(&json).unwrap(), key);
+    let json = serde_json::to_string(&note).unwrap();
+    assert_eq!(serde_json::from_str::<NoteEvent>(&json).unwrap(), note);
+}
+
+#[test]
+fn out_of_range_pitches_are_rejected() {
+    assert!(serde_json::from_str::<PitchClass>("12").is_err());
+    assert!(serde_json::from_str::<MidiNote>("128").is_err());
+    assert_eq!(serde_json::from_str::<MidiNote>("127").unwrap(), MidiNote::new(127).unwrap());
+}
+
+#[test]
+fn chord_probability_frames_carry_only_their_candidates() {
+    let mut frame = ChordProbabilityFrame::new(SampleTime::new(0), SampleTime::new(512));
+    frame.push(candidate(0, 700));
+    frame.push(candidate(7, 200));
+
+    let value = serde_json::to_value(frame).unwrap();
+    assert_eq!(value["candidates"].as_array().unwrap().len(), 2);
+    let back: ChordProbabilityFrame = serde_json::from_value(value).unwrap();
+    assert_eq!(back.onset, frame.onset);
+    assert_eq!(back.offset, frame.offset);
+    assert_eq!(back.candidates(), frame.candidates());
+}
+
+#[test]
+fn chord_probability_frames_reject_too_many_candidates() {
+    let candidates = vec![candidate(0, 100); MAX_CHORD_CANDIDATES + 1];
+    let json = serde_json::json!({ "onset": 0, "offset": 512, "candidates": candidates });
+    assert!(serde_json::from_value::<ChordProbabilityFrame>(json).is_err());
+}
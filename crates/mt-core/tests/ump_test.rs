@@ -0,0 +1,68 @@
+#![allow(clippy::expect_used, clippy::panic, clippy::unwrap_used)]
+
+use mt_core::midi::{MidiChannel, MidiEvent, MidiEventKind};
+use mt_core::ump::{self, Midi2Voice, Ump};
+
+#[test]
+fn decodes_midi1_and_midi2_channel_voice() {
+    let words = [
+        // MIDI 1.0 note on, group 1, channel 3, key 60, velocity 100.
+        0x2193_3C64,
+        // MIDI 2.0 note on, channel 0, key 64, pitch 7.9 attribute = 64.5.
+        0x4090_4003,
+        0xFFFF_8100,
+        // Utility NOOP, then a 128-bit SysEx8 packet to skip.
+        0x0000_0000,
+        0x5000_0000,
+        0,
+        0,
+        0,
+        // MIDI 2.0 pitch bend, channel 2, centered + 1/4 range.
+        0x40E2_0000,
+        0xA000_0000,
+        // Incomplete trailing MIDI 2.0 packet.
+        0x4090_4000,
+    ];
+    let packets: Vec<Ump> = ump::decode(&words).collect();
+    assert_eq!(packets.len(), 5);
+
+    let ch3 = MidiChannel::new(3).unwrap();
+    let expected = MidiEvent { channel: ch3, kind: MidiEventKind::NoteOn, data1: 60, data2: 100 };
+    assert_eq!(packets[0], Ump::Midi1 { group: 1, event: expected });
+
+    let Ump::Midi2 { message, .. } = packets[1] else { panic!("expected MIDI 2.0") };
+    assert_eq!(
+        message,
+        Midi2Voice::NoteOn { note: 64, velocity: 0xFFFF, attribute_type: 3, attribute: 0x8100 }
+    );
+    assert_eq!(message.attribute_pitch_cents(), Some(6450));
+    assert_eq!(packets[1].to_midi1().map(|e| (e.data1, e.data2)), Some((64, 127)));
+
+    assert_eq!(packets[2], Ump::Other { message_type: 0 });
+    assert_eq!(packets[3], Ump::Other { message_type: 5 });
+
+    let bend = packets[4].to_midi1().unwrap();
+    assert_eq!(bend.pitch_bend_value(), Some(2048));
+}
+
+#[test]
+fn midi2_values_scale_down_to_midi1() {
+    // A quiet MIDI 2.0 note-on must stay a note-on after scaling.
+    let quiet = Ump::decode(&[0x4091_3C00, 0x0001_0000]).unwrap().0;
+    assert_eq!(quiet.to_midi1().map(|e| (e.kind, e.data2)), Some((MidiEventKind::NoteOn, 1)));
+
+    let cc = Ump::decode(&[0x40B0_4000, 0x8000_0000]).unwrap().0;
+    assert_eq!(cc.to_midi1().map(|e| (e.data1, e.data2)), Some((64, 64)));
+
+    // RPN 0 (bend range) = 12 semitones + 50 cents.
+    let rpn = Ump::decode(&[0x4020_0000, (12 << 25) | (50 << 18)]).unwrap().0;
+    assert_eq!(rpn.to_midi1(), None);
+    let ccs = rpn.controller_to_midi1().unwrap().map(|e| (e.data1, e.data2));
+    assert_eq!(ccs, [(101, 0), (100, 0), (6, 12), (38, 50)]);
+
+    let per_note = Ump::decode(&[0x4060_3C00, 0xFFFF_FFFF]).unwrap().0;
+    assert_eq!(per_note.to_midi1(), None);
+    assert_eq!(ump::bend_cents(0xFFFF_FFFF, 4800), 4799);
+    assert_eq!(ump::bend_cents(0, 4800), -4800);
+    assert_eq!(ump::pitch_7_25_cents(61 << 25 | 1 << 24), 6150);
+}
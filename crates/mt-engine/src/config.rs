@@ -2,7 +2,13 @@
 //!
 //! Can be constructed programmatically or deserialized (with `serde` feature).
 
-use crate::types::ValueType;
+use std::path::Path;
+
+use mt_analysis::config::AnalysisConfig;
+use mt_core::pedal::PedalConfig;
+
+use crate::nodes::{CADENCE_NODE_ID, CHORD_PROBABILITY_NODE_ID, CHROMA_CHORD_NODE_ID};
+use crate::types::{EngineError, ValueType};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -21,6 +27,9 @@ pub struct NodeConfig {
     /// Declared output type for validation (optional hint).
     #[cfg_attr(feature = "serde", serde(default))]
     pub output_type: Option<ValueType>,
+    /// Analyzer settings; each built-in node reads the sections it uses.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub analysis: AnalysisConfig,
 }
 
 /// Configuration for a directed edge between nodes.
@@ -35,7 +44,7 @@ pub struct EdgeConfig {
 
 /// Pipeline configuration: nodes + edges.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct PipelineConfig {
     /// Logical identifier for this pipeline (human + tooling).
     pub id: String,
@@ -43,16 +52,135 @@ pub struct PipelineConfig {
     pub edges: Vec<EdgeConfig>,
 }
 
-/// Engine configuration wraps a pipeline; additional fields can be added later.
+/// Real-time bridge configuration (`EngineRt` → `EngineSession`).
+///
+/// Queue capacities are fixed at construction; the RT side never grows them.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug)]
+pub struct RtConfig {
+    /// Sample rate of the pushed audio in Hz; `EngineRt` does not resample.
+    pub sample_rate: u32,
+    /// Interleaved channels per audio frame.
+    pub channels: u16,
+    /// Audio queue capacity in frames.
+    pub audio_capacity_frames: usize,
+    /// MIDI queue capacity in events; the UMP queue holds as many words.
+    pub midi_capacity_events: usize,
+    /// Frames per `AudioBlock` handed to the pipeline.
+    pub analysis_block_frames: usize,
+    /// Node outputs `EngineSession` keeps for polling and the event
+    /// getters; older ones are evicted.
+    pub history_capacity_events: usize,
+    /// Pipeline node receiving `Value::AudioBlock`; audio is discarded if unset.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub audio_entry: Option<String>,
+    /// Pipeline node receiving `Value::MidiEvents`. If unset, MIDI is
+    /// normalized into notes for `note_entry`, or counted as dropped.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub midi_entry: Option<String>,
+    /// Pipeline node receiving `Value::NoteEvents` normalized from UMP
+    /// input (and from MIDI without a `midi_entry`); UMP is discarded if
+    /// unset.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub note_entry: Option<String>,
+    /// Pedal handling when the session turns MIDI into notes (MIDI files,
+    /// UMP and MIDI without a `midi_entry`); see `MidiNoteConfig::pedal`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pedal: PedalConfig,
+}
+
+impl Default for RtConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 44_100,
+            channels: 2,
+            audio_capacity_frames: 1 << 16,
+            midi_capacity_events: 4096,
+            analysis_block_frames: 4096,
+            history_capacity_events: 4096,
+            audio_entry: None,
+            midi_entry: None,
+            note_entry: None,
+            pedal: PedalConfig::default(),
+        }
+    }
+}
+
+/// Engine configuration wraps a pipeline; additional fields can be added later.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Default)]
 pub struct EngineConfig {
     pub pipeline: PipelineConfig,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rt: RtConfig,
 }
 
 impl EngineConfig {
     #[must_use]
     pub fn new(pipeline: PipelineConfig) -> Self {
-        Self { pipeline }
+        Self { pipeline, rt: RtConfig::default() }
+    }
+
+    /// Built-in offline pipeline: audio goes to a `ChromaChordNode`, whose
+    /// chords feed a `CadenceNode`; notes (including real-time MIDI, which
+    /// has no entry of its own) go to a `ChordProbabilityNode`.
+    #[must_use]
+    pub fn offline_default() -> Self {
+        let chords = NodeConfig {
+            id: "chords".into(),
+            impl_id: CHROMA_CHORD_NODE_ID.into(),
+            input_type: Some(ValueType::AudioBlock),
+            output_type: Some(ValueType::ChordEvents),
+            analysis: AnalysisConfig::default(),
+        };
+        let cadences = NodeConfig {
+            id: "cadences".into(),
+            impl_id: CADENCE_NODE_ID.into(),
+            input_type: Some(ValueType::ChordEvents),
+            output_type: Some(ValueType::Cadences),
+            analysis: AnalysisConfig::default(),
+        };
+        let probabilities = NodeConfig {
+            id: "probabilities".into(),
+            impl_id: CHORD_PROBABILITY_NODE_ID.into(),
+            input_type: Some(ValueType::NoteEvents),
+            output_type: Some(ValueType::ChordProbabilities),
+            analysis: AnalysisConfig::default(),
+        };
+        let edge = EdgeConfig { from: "chords".into(), to: "cadences".into() };
+        let pipeline = PipelineConfig {
+            id: "offline-default".into(),
+            nodes: vec![chords, cadences, probabilities],
+            edges: vec![edge],
+        };
+        let rt = RtConfig {
+            audio_entry: Some("chords".into()),
+            note_entry: Some("probabilities".into()),
+            ..RtConfig::default()
+        };
+        Self { pipeline, rt }
+    }
+
+    /// Load a JSON config from `path`, or `offline_default` for `None`.
+    ///
+    /// Reading a file needs the `serde` feature.
+    pub fn from_path_or_default(path: Option<&Path>) -> Result<Self, EngineError> {
+        let Some(path) = path else {
+            return Ok(Self::offline_default());
+        };
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| EngineError::InvalidInput(format!("{}: {e}", path.display())))?;
+        Self::from_json(&text)
+            .map_err(|msg| EngineError::InvalidInput(format!("{}: {msg}", path.display())))
+    }
+
+    #[cfg(feature = "serde")]
+    fn from_json(text: &str) -> Result<Self, String> {
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    }
+
+    #[cfg(not(feature = "serde"))]
+    fn from_json(_text: &str) -> Result<Self, String> {
+        Err("reading pipeline configs needs the `serde` feature".into())
     }
 }
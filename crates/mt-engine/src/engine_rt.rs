@@ -0,0 +1,135 @@
+//! `EngineRt`: the audio-thread half of a real-time engine.
+//!
+//! RT contract: every method is wait-free and performs no allocation, no
+//! locking, no logging and no panicking on valid input. Data goes straight
+//! into the fixed-size `mt_alloc` SPSC queues read by `EngineSession`; when
+//! a queue is full the block (or event) is dropped and counted, never
+//! blocked on.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use mt_alloc::spsc_queue::Producer;
+use mt_core::midi::TimedMidiEvent;
+use mt_core::time::SampleTime;
+use mt_core::ump::{self, Ump};
+
+/// Marks one queued audio block; its samples precede it in the sample queue.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct AudioBlockHeader {
+    pub(crate) start: SampleTime,
+    pub(crate) frames: u32,
+}
+
+/// One queued UMP word with the position of its packet.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct UmpWord {
+    pub(crate) position: SampleTime,
+    pub(crate) word: u32,
+}
+
+/// Drop counters written by `EngineRt`, read by `EngineSession`.
+#[derive(Debug, Default)]
+pub(crate) struct RtCounters {
+    pub(crate) audio_frames: AtomicU64,
+    pub(crate) midi_events: AtomicU64,
+}
+
+/// Producer side of the RT bridge. Move it to the audio thread.
+pub struct EngineRt {
+    pub(crate) samples: Producer<f32>,
+    pub(crate) blocks: Producer<AudioBlockHeader>,
+    pub(crate) midi: Producer<TimedMidiEvent>,
+    pub(crate) ump: Producer<UmpWord>,
+    pub(crate) drops: Arc<RtCounters>,
+    pub(crate) sample_rate: u32,
+    pub(crate) channels: u16,
+    /// Position just past the last pushed audio block.
+    pub(crate) position: SampleTime,
+}
+
+impl EngineRt {
+    /// Queue one block of interleaved audio starting at `block_start`.
+    ///
+    /// A trailing partial frame is ignored. Returns `false` if the block was
+    /// dropped because the queue is full.
+    pub fn push_audio_block(&mut self, block_start: SampleTime, samples: &[f32]) -> bool {
+        let channels = usize::from(self.channels.max(1));
+        // Header frame counts are `u32`; longer blocks are cut to fit.
+        let frames = u32::try_from(samples.len() / channels).unwrap_or(u32::MAX);
+        let samples = &samples[..frames as usize * channels];
+        self.position = SampleTime::new(block_start.value() + i64::from(frames));
+
+        // All or nothing: a partial block would tear the frame grid.
+        if self.samples.free_len() < samples.len() || self.blocks.free_len() == 0 {
+            self.drops.audio_frames.fetch_add(u64::from(frames), Ordering::Relaxed);
+            return false;
+        }
+        self.samples.push_slice(samples);
+        let header = AudioBlockHeader { start: block_start, frames };
+        self.blocks.push(header).is_ok()
+    }
+
+    /// Queue MIDI events, e.g. built with `TimedMidiEvent::new(block_start,
+    /// offset, event)`.
+    ///
+    /// Returns the number queued; the rest are dropped and counted.
+    pub fn push_midi(&mut self, events: &[TimedMidiEvent]) -> usize {
+        let queued = self.midi.push_slice(events);
+        if queued < events.len() {
+            let dropped = (events.len() - queued) as u64;
+            self.drops.midi_events.fetch_add(dropped, Ordering::Relaxed);
+        }
+        queued
+    }
+
+    /// Queue a single MIDI event.
+    pub fn push_midi_event(&mut self, event: TimedMidiEvent) -> bool {
+        self.push_midi(core::slice::from_ref(&event)) == 1
+    }
+
+    /// Queue MIDI 2.0 Universal MIDI Packets, all at `block_offset` into the
+    /// block starting at `block_start`.
+    ///
+    /// Packets are queued untranslated: `EngineSession` runs them through
+    /// `MidiNormalizer::process_ump`, so per-note pitch bend, pitch
+    /// attributes and per-note controllers reach the notes sent to
+    /// `RtConfig::note_entry`. A trailing partial packet is ignored. All or
+    /// nothing: returns `false` if the packets were dropped because the
+    /// queue is full.
+    pub fn push_ump(&mut self, block_start: SampleTime, block_offset: u32, words: &[u32]) -> bool {
+        let mut len = 0;
+        while let Some((_, size)) = Ump::decode(&words[len..]) {
+            len += size;
+        }
+        let words = &words[..len];
+        if self.ump.free_len() < words.len() {
+            let dropped = ump::decode(words).count() as u64;
+            self.drops.midi_events.fetch_add(dropped, Ordering::Relaxed);
+            return false;
+        }
+        let position = SampleTime::new(block_start.value() + i64::from(block_offset));
+        for &word in words {
+            let _ = self.ump.push(UmpWord { position, word });
+        }
+        true
+    }
+
+    /// Position just past the last pushed audio block.
+    ///
+    /// Hosts without their own transport clock can pass this as the next
+    /// `block_start`.
+    pub fn position(&self) -> SampleTime {
+        self.position
+    }
+
+    /// Sample rate the pushed audio must have, from `RtConfig::sample_rate`.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Interleaved channels per frame, from `RtConfig::channels`.
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+}
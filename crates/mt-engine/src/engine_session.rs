@@ -1,15 +1,40 @@
 //! High-level Engine wrapper around PipelineGraph.
 //!
-//! This keeps engine usage simple for SDK/CLI callers.
+//! This keeps engine usage simple for SDK/CLI callers. For real-time hosts,
+//! an `Engine` splits into `EngineRt` (audio thread) and `EngineSession`
+//! (analysis thread) joined by wait-free queues. `OfflineSession` runs a
+//! `Pipeline` over whole audio and MIDI files.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+use mt_alloc::MidiNormalizer;
+use mt_alloc::spsc_queue::{Consumer, SpscQueue};
+use mt_core::events::{
+    CadenceEvent, ChordEvent, ChordProbabilityFrame, KeyEvent, NoteEvent, NoteRoleEvent,
+    SegmentEvent,
+};
+use mt_core::midi::TimedMidiEvent;
+use mt_core::time::SampleTime;
+use mt_formats::AudioFileReader;
+use mt_formats::smf::MidiFile;
 
 use crate::{
-    config::EngineConfig,
+    config::{EngineConfig, RtConfig},
+    engine_rt::{AudioBlockHeader, EngineRt, RtCounters, UmpWord},
     event_bus::EventBus,
-    pipeline::{NodeRegistry, PipelineGraph},
+    pipeline::{NodeRegistry, Pipeline, PipelineGraph},
     snapshot::EngineSnapshot,
-    types::{EngineError, Value},
+    types::{AnalyzeResponse, AudioBlock, ENGINE_VERSION, EngineError, EngineEvent, Value},
+    validate,
 };
 
+/// Node id of the outputs an `OfflineSession` reads straight from a MIDI
+/// file: notes, tempo, meter and key signatures.
+pub const MIDI_FILE_NODE_ID: &str = "input.midi";
+
 /// Builder that wires config + registry into a validated Engine.
 pub struct EngineBuilder<'a> {
     cfg: &'a EngineConfig,
@@ -25,10 +50,13 @@ impl<'a> EngineBuilder<'a> {
     /// Build an `Engine` with a compiled, validated graph.
     pub fn build(self) -> Result<Engine, EngineError> {
         let graph = PipelineGraph::from_config(&self.cfg.pipeline, self.registry)?;
-        Ok(Engine {
-            graph,
-            bus: EventBus::new(),
-        })
+        Ok(Engine { graph, bus: EventBus::new() })
+    }
+
+    /// Build an engine and split it for real-time use per `cfg.rt`.
+    pub fn build_realtime(self) -> Result<(EngineRt, EngineSession), EngineError> {
+        let rt = self.cfg.rt.clone();
+        Ok(self.build()?.into_realtime(rt))
     }
 }
 
@@ -54,4 +82,465 @@ impl Engine {
         self.bus.append(events);
         Ok(EngineSnapshot::from_events(self.bus.drain()))
     }
+
+    /// Split into the audio-thread and analysis-thread halves.
+    ///
+    /// All queue memory is allocated here. Nodes are told `rt.sample_rate`.
+    #[must_use]
+    pub fn into_realtime(mut self, rt: RtConfig) -> (EngineRt, EngineSession) {
+        self.graph.set_sample_rate(rt.sample_rate);
+        let channels = usize::from(rt.channels.max(1));
+        let (samples_tx, samples_rx) =
+            SpscQueue::with_capacity((rt.audio_capacity_frames * channels).max(1)).split();
+        // Room for the headers of many small blocks.
+        let (blocks_tx, blocks_rx) = SpscQueue::with_capacity(1024).split();
+        let (midi_tx, midi_rx) = SpscQueue::with_capacity(rt.midi_capacity_events.max(1)).split();
+        let (ump_tx, ump_rx) = SpscQueue::with_capacity(rt.midi_capacity_events.max(1)).split();
+        let drops = Arc::new(RtCounters::default());
+        let producer = EngineRt {
+            samples: samples_tx,
+            blocks: blocks_tx,
+            midi: midi_tx,
+            ump: ump_tx,
+            drops: Arc::clone(&drops),
+            sample_rate: rt.sample_rate,
+            channels: rt.channels,
+            position: SampleTime::ZERO,
+        };
+        let session = EngineSession {
+            engine: self,
+            samples: samples_rx,
+            blocks: blocks_rx,
+            midi: midi_rx,
+            ump: ump_rx,
+            normalizer: MidiNormalizer::with_pedal(rt.pedal),
+            drops,
+            rt,
+            pending_audio: Vec::new(),
+            pending_start: SampleTime::ZERO,
+            history: VecDeque::new(),
+            emitted: 0,
+            polled: 0,
+        };
+        (producer, session)
+    }
+}
+
+/// Samples and events the RT side had to drop because a queue was full.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RtDropCounters {
+    /// Audio frames dropped, counted per frame rather than per sample.
+    pub audio_frames: u64,
+    /// MIDI events dropped, plus UMP packets. Includes MIDI discarded by
+    /// the session because neither `midi_entry` nor `note_entry` is set.
+    pub midi_events: u64,
+}
+
+/// Consumer side of the RT bridge: drains the queues, runs the pipeline and
+/// keeps a bounded event history. Never call from the audio thread.
+pub struct EngineSession {
+    engine: Engine,
+    samples: Consumer<f32>,
+    blocks: Consumer<AudioBlockHeader>,
+    midi: Consumer<TimedMidiEvent>,
+    ump: Consumer<UmpWord>,
+    /// Turns UMP (and MIDI without a MIDI entry) into notes; keeps note and
+    /// expression state across pumps.
+    normalizer: MidiNormalizer,
+    drops: Arc<RtCounters>,
+    rt: RtConfig,
+    /// Interleaved audio waiting for a full analysis block.
+    pending_audio: Vec<f32>,
+    /// Position of the first frame in `pending_audio`.
+    pending_start: SampleTime,
+    /// The last `rt.history_capacity_events` outputs.
+    history: VecDeque<EngineEvent>,
+    /// Outputs recorded since the session started, including evicted ones.
+    emitted: usize,
+    /// History entries already returned by `poll_events`.
+    polled: usize,
+}
+
+impl EngineSession {
+    /// Drain the queues and run the pipeline on what arrived.
+    ///
+    /// Audio is analyzed in `analysis_block_frames` blocks; the remainder
+    /// waits for the next call (or `flush`). A pushed block that does not
+    /// continue the buffered audio flushes it first, so every analysis block
+    /// is contiguous and starts at its true position. MIDI goes to
+    /// `rt.midi_entry`, or is normalized into notes for `rt.note_entry`
+    /// like UMP when there is no MIDI entry. Returns the number of new
+    /// events.
+    pub fn pump(&mut self) -> Result<usize, EngineError> {
+        let before = self.emitted;
+        self.drain_audio()?;
+
+        let midi = self.drain_midi();
+        let mut notes = Vec::new();
+        if let Some(entry) = &self.rt.midi_entry {
+            if !midi.is_empty() {
+                let events = self.engine.graph.execute(entry, Value::MidiEvents(midi))?;
+                self.record(events);
+            }
+        } else if self.rt.note_entry.is_some() {
+            notes = self.normalizer.process(&midi);
+        } else {
+            self.drops.midi_events.fetch_add(midi.len() as u64, Ordering::Relaxed);
+        }
+
+        notes.extend(self.drain_ump());
+        if !notes.is_empty()
+            && let Some(entry) = &self.rt.note_entry
+        {
+            let events = self.engine.graph.execute(entry, Value::NoteEvents(notes))?;
+            self.record(events);
+        }
+        Ok(self.emitted - before)
+    }
+
+    /// Analyze buffered audio shorter than one analysis block, e.g. when the
+    /// transport stops.
+    pub fn flush(&mut self) -> Result<usize, EngineError> {
+        let before = self.emitted;
+        self.pump()?;
+        self.run_pending()?;
+        Ok(self.emitted - before)
+    }
+
+    /// Pump, then append events not yet returned by a previous poll.
+    ///
+    /// Events evicted from the history before a poll are not returned.
+    pub fn poll_events(&mut self, out: &mut Vec<EngineEvent>) -> Result<usize, EngineError> {
+        self.pump()?;
+        let new = self.history.len() - self.polled;
+        out.extend(self.history.range(self.polled..).cloned());
+        self.polled = self.history.len();
+        Ok(new)
+    }
+
+    /// The retained history (the last `rt.history_capacity_events`
+    /// outputs), in emission order.
+    #[must_use]
+    pub fn snapshot_state(&self) -> EngineSnapshot {
+        EngineSnapshot::from_events(self.history.iter().cloned().collect())
+    }
+
+    #[must_use]
+    pub fn drop_counters(&self) -> RtDropCounters {
+        RtDropCounters {
+            audio_frames: self.drops.audio_frames.load(Ordering::Relaxed),
+            midi_events: self.drops.midi_events.load(Ordering::Relaxed),
+        }
+    }
+
+    #[must_use]
+    pub fn note_events(&self) -> Vec<NoteEvent> {
+        self.collect(|v| match v {
+            Value::NoteEvents(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    #[must_use]
+    pub fn note_role_events(&self) -> Vec<NoteRoleEvent> {
+        self.collect(|v| match v {
+            Value::NoteRoles(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    #[must_use]
+    pub fn chord_events(&self) -> Vec<ChordEvent> {
+        self.collect(|v| match v {
+            Value::ChordEvents(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    #[must_use]
+    pub fn chord_probability_frames(&self) -> Vec<ChordProbabilityFrame> {
+        self.collect(|v| match v {
+            Value::ChordProbabilities(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    #[must_use]
+    pub fn cadence_events(&self) -> Vec<CadenceEvent> {
+        self.collect(|v| match v {
+            Value::Cadences(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    #[must_use]
+    pub fn key_events(&self) -> Vec<KeyEvent> {
+        self.collect(|v| match v {
+            Value::KeyEvents(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    #[must_use]
+    pub fn segment_events(&self) -> Vec<SegmentEvent> {
+        self.collect(|v| match v {
+            Value::SegmentEvents(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    /// Append outputs to the history, evicting the oldest beyond
+    /// `rt.history_capacity_events`.
+    fn record(&mut self, events: Vec<EngineEvent>) {
+        self.emitted += events.len();
+        self.history.extend(events);
+        let excess = self.history.len().saturating_sub(self.rt.history_capacity_events);
+        self.history.drain(..excess);
+        self.polled = self.polled.saturating_sub(excess);
+    }
+
+    /// Concatenate every retained node output `pick` accepts, in emission
+    /// order.
+    fn collect<T: Clone>(&self, pick: impl Fn(&Value) -> Option<&Vec<T>>) -> Vec<T> {
+        self.history
+            .iter()
+            .filter_map(|EngineEvent::NodeOutput { value, .. }| pick(value))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    fn run_audio(&mut self, start: SampleTime, frames: Vec<f32>) -> Result<(), EngineError> {
+        let Some(entry) = &self.rt.audio_entry else {
+            return Ok(());
+        };
+        let block = AudioBlock {
+            start,
+            sample_rate: self.rt.sample_rate,
+            channels: self.rt.channels,
+            frames,
+        };
+        let events = self.engine.graph.execute(entry, Value::AudioBlock(block))?;
+        self.record(events);
+        Ok(())
+    }
+
+    /// Analyze whatever is buffered, even if shorter than a block.
+    fn run_pending(&mut self) -> Result<(), EngineError> {
+        if self.pending_audio.is_empty() {
+            return Ok(());
+        }
+        let frames = std::mem::take(&mut self.pending_audio);
+        self.run_audio(self.pending_start, frames)
+    }
+
+    fn drain_audio(&mut self) -> Result<(), EngineError> {
+        let channels = usize::from(self.rt.channels.max(1));
+        let block_frames = self.rt.analysis_block_frames.max(1);
+        let block_len = i64::try_from(block_frames).unwrap_or(i64::MAX);
+        // A header is pushed after its samples, so they are already visible.
+        while let Some(header) = self.blocks.pop() {
+            let samples = header.frames as usize * channels;
+            if self.rt.audio_entry.is_none() {
+                self.samples.skip(samples);
+                continue;
+            }
+            let buffered = i64::try_from(self.pending_audio.len() / channels).unwrap_or(i64::MAX);
+            if header.start.value() != self.pending_start.value().saturating_add(buffered) {
+                self.run_pending()?;
+            }
+            if self.pending_audio.is_empty() {
+                self.pending_start = header.start;
+            }
+            let at = self.pending_audio.len();
+            self.pending_audio.resize(at + samples, 0.0);
+            self.samples.pop_slice(&mut self.pending_audio[at..]);
+
+            while self.pending_audio.len() >= block_frames * channels {
+                let frames: Vec<f32> =
+                    self.pending_audio.drain(..block_frames * channels).collect();
+                let start = self.pending_start;
+                self.pending_start = SampleTime::new(start.value().saturating_add(block_len));
+                self.run_audio(start, frames)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn drain_midi(&mut self) -> Vec<TimedMidiEvent> {
+        let mut out = Vec::with_capacity(self.midi.len());
+        while let Some(ev) = self.midi.pop() {
+            out.push(ev);
+        }
+        // Analyzers expect position order; a stable sort keeps ties in
+        // arrival order.
+        out.sort_by_key(|e| e.position);
+        out
+    }
+
+    /// Normalize queued UMP into the notes it completed.
+    fn drain_ump(&mut self) -> Vec<NoteEvent> {
+        let mut queued = Vec::with_capacity(self.ump.len());
+        while let Some(word) = self.ump.pop() {
+            queued.push(word);
+        }
+        if self.rt.note_entry.is_none() {
+            return Vec::new();
+        }
+        // Each push holds whole packets, so a stable sort keeps them intact.
+        queued.sort_by_key(|w| w.position);
+        let mut notes = Vec::new();
+        for run in queued.chunk_by(|a, b| a.position == b.position) {
+            let words: Vec<u32> = run.iter().map(|w| w.word).collect();
+            notes.extend(self.normalizer.process_ump(run[0].position, &words));
+        }
+        notes
+    }
+}
+
+/// Offline session: ingests whole files in the caller's order and keeps
+/// every output.
+///
+/// Each file is its own timeline starting at sample 0 and is run through
+/// the pipeline as one block: audio goes to `rt.audio_entry`, MIDI to
+/// `rt.midi_entry` and the notes of a MIDI file to `rt.note_entry`. Node
+/// context (`TypedNode::reset`) is cleared before each file. The session
+/// sample rate is fixed by the first file: an audio file's own rate, or
+/// `rt.sample_rate` for a MIDI file. Audio at any other rate is rejected
+/// (there is no resampling).
+pub struct OfflineSession {
+    cfg: EngineConfig,
+    pipeline: Pipeline,
+    project_root: Option<PathBuf>,
+    sample_rate: Option<u32>,
+    events: Vec<EngineEvent>,
+}
+
+impl OfflineSession {
+    /// Session over a validated `pipeline`; relative input paths are
+    /// resolved against `project_root`.
+    pub fn new(
+        cfg: EngineConfig,
+        pipeline: Pipeline,
+        project_root: Option<PathBuf>,
+    ) -> Result<Self, EngineError> {
+        validate::validate_engine(&cfg, &pipeline)?;
+        Ok(Self { cfg, pipeline, project_root, sample_rate: None, events: Vec::new() })
+    }
+
+    /// Sample rate that event positions are expressed in.
+    #[must_use]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate.unwrap_or(self.cfg.rt.sample_rate)
+    }
+
+    /// Decode an audio file and run it through the audio entry node.
+    pub fn ingest_audio_file(&mut self, path: &Path) -> Result<(), EngineError> {
+        let path = self.resolve(path);
+        let mut reader = AudioFileReader::open(&path)?;
+        let info = reader.info();
+        if self.sample_rate.is_some_and(|sr| sr != info.sample_rate) {
+            return Err(EngineError::InvalidInput(format!(
+                "{}: sample rate {} Hz differs from the session's {} Hz",
+                path.display(),
+                info.sample_rate,
+                self.sample_rate()
+            )));
+        }
+        self.start_timeline(info.sample_rate);
+
+        let Some(entry) = self.cfg.rt.audio_entry.clone() else {
+            return Ok(());
+        };
+        let mut frames = Vec::new();
+        while let Some(block) = reader.next_block(self.cfg.rt.analysis_block_frames.max(1))? {
+            frames.extend(block);
+        }
+        let block = AudioBlock {
+            start: SampleTime::ZERO,
+            sample_rate: info.sample_rate,
+            channels: info.channels,
+            frames,
+        };
+        let events = self.pipeline.graph_mut().execute(&entry, Value::AudioBlock(block))?;
+        self.events.extend(events);
+        Ok(())
+    }
+
+    /// Parse a Standard MIDI File: emit its notes (pedals applied per
+    /// `rt.pedal`), tempo, meter and key signatures as `MIDI_FILE_NODE_ID`
+    /// outputs (which every node also observes), then run its events
+    /// through the MIDI entry node and its notes through the note entry
+    /// node.
+    pub fn ingest_midi_file(&mut self, path: &Path) -> Result<(), EngineError> {
+        let path = self.resolve(path);
+        let bytes = std::fs::read(&path)
+            .map_err(|e| EngineError::InvalidInput(format!("{}: {e}", path.display())))?;
+        let sample_rate = self.sample_rate();
+        self.start_timeline(sample_rate);
+        let file = MidiFile::parse(&bytes, sample_rate)
+            .map_err(|e| EngineError::MidiDecode(format!("{}: {e}", path.display())))?;
+
+        let notes = file.notes(self.cfg.rt.pedal);
+        let outputs = [
+            Value::TempoEvents(file.tempos.clone()),
+            Value::MeterEvents(file.meters.clone()),
+            Value::KeyEvents(file.keys.clone()),
+            Value::NoteEvents(notes.clone()),
+        ];
+        for value in outputs.into_iter().filter(|v| !is_empty(v)) {
+            self.pipeline.graph_mut().observe(&value);
+            self.events.push(EngineEvent::NodeOutput { node_id: MIDI_FILE_NODE_ID.into(), value });
+        }
+
+        if let Some(entry) = self.cfg.rt.midi_entry.clone() {
+            let mut midi: Vec<TimedMidiEvent> =
+                file.tracks.iter().flat_map(|t| t.events.iter().copied()).collect();
+            // Stable: simultaneous events keep their track order.
+            midi.sort_by_key(|e| e.position);
+            let events = self.pipeline.graph_mut().execute(&entry, Value::MidiEvents(midi))?;
+            self.events.extend(events);
+        }
+        if let Some(entry) = self.cfg.rt.note_entry.clone()
+            && !notes.is_empty()
+        {
+            let events = self.pipeline.graph_mut().execute(&entry, Value::NoteEvents(notes))?;
+            self.events.extend(events);
+        }
+        Ok(())
+    }
+
+    /// Every output in emission order.
+    pub fn finalize(self) -> Result<AnalyzeResponse, EngineError> {
+        Ok(AnalyzeResponse {
+            engine_version: ENGINE_VERSION,
+            sample_rate: self.sample_rate(),
+            events: self.events,
+        })
+    }
+
+    /// Fix the session sample rate and clear context from earlier files.
+    fn start_timeline(&mut self, sample_rate: u32) {
+        self.sample_rate = Some(sample_rate);
+        let graph = self.pipeline.graph_mut();
+        graph.set_sample_rate(sample_rate);
+        graph.reset();
+    }
+
+    fn resolve(&self, path: &Path) -> PathBuf {
+        match &self.project_root {
+            Some(root) if path.is_relative() => root.join(path),
+            _ => path.to_path_buf(),
+        }
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::TempoEvents(e) => e.is_empty(),
+        Value::MeterEvents(e) => e.is_empty(),
+        Value::KeyEvents(e) => e.is_empty(),
+        Value::NoteEvents(e) => e.is_empty(),
+        _ => false,
+    }
 }
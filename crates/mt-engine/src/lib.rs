@@ -3,6 +3,7 @@
 //! Deterministic analysis pipeline engine built on:
 //! - `mt-core`: semantic primitives
 //! - `mt-alloc`: temporal and buffer structures
+//! - `mt-formats`: MIDI and audio file decoding
 //! - `mt-analysis`: pluggable detectors
 //!
 //! Responsibilities:
@@ -10,6 +11,9 @@
 //! - Instantiate nodes from a `NodeRegistry`.
 //! - Execute a DAG of `DynNode`s on `Value` data.
 //! - Emit `EngineEvent`s and `EngineSnapshot`s.
+//! - Ingest audio and MIDI files (decoded by `mt-formats`) offline.
+//! - Split into `EngineRt` (audio thread, wait-free) and `EngineSession`
+//!   (analysis thread) for real-time hosts.
 //!
 //! This crate does not implement detection logic itself; nodes are
 //! thin adapters around `mt-analysis` and related crates.
@@ -25,18 +29,22 @@
 
 pub mod api;
 pub mod config;
+pub mod engine_rt;
 pub mod engine_session;
 pub mod event_bus;
 pub mod logging;
+pub mod nodes;
 pub mod pipeline;
 pub mod snapshot;
 pub mod types;
 pub mod validate;
 
 pub use crate::{
-    config::{EngineConfig, NodeConfig, PipelineConfig},
-    engine_session::{Engine, EngineBuilder},
+    config::{EngineConfig, NodeConfig, PipelineConfig, RtConfig},
+    engine_rt::EngineRt,
+    engine_session::{Engine, EngineBuilder, EngineSession, RtDropCounters},
     event_bus::EventBus,
+    nodes::register_builtin_nodes,
     pipeline::{DynNode, NodeRegistry},
     snapshot::EngineSnapshot,
     types::{
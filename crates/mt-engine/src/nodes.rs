@@ -0,0 +1,316 @@
+//! Built-in nodes: thin adapters from `mt-analysis` analyzers to the graph.
+//!
+//! Each node has a stable impl ID and a `DynNodeFactory`;
+//! `register_builtin_nodes` installs them into a `NodeRegistry`.
+
+use mt_alloc::NoteStore;
+use mt_analysis::cadence_detector::CadenceDetector;
+use mt_analysis::chord_detector::{ChromaChordAnalyzer, HmmChordAnalyzer, RuleBasedChordAnalyzer};
+use mt_analysis::config::{CadenceConfig, ChordConfig, ChromaConfig, KeyConfig};
+use mt_analysis::key_detector::SpiralArrayKeyAnalyzer;
+use mt_analysis::traits::{AudioChordAnalyzer, CadenceAnalyzer, ChordAnalyzer};
+use mt_core::events::{
+    CadenceEvent, ChordEvent, ChordProbabilityFrame, KeyEvent, MAX_CHORD_CANDIDATES, MeterEvent,
+    NoteEvent, NoteRoleEvent, TempoEvent,
+};
+use mt_core::time::SampleTime;
+use mt_semantic::classify_note_roles;
+
+use crate::{
+    config::NodeConfig,
+    pipeline::{DynNode, NodeAdapter, NodeRegistry, TypedNode},
+    types::{AudioBlock, EngineError, Value, ValueType},
+};
+
+/// Impl ID of `ChromaChordNode`.
+pub const CHROMA_CHORD_NODE_ID: &str = "mt.analysis.chord_detector.chroma.v1";
+
+/// Impl ID of `ChordProbabilityNode`.
+pub const CHORD_PROBABILITY_NODE_ID: &str = "mt.analysis.chord_detector.probabilities.v1";
+
+/// Impl ID of `CadenceNode`.
+pub const CADENCE_NODE_ID: &str = "mt.analysis.cadence_detector.v1";
+
+/// Impl ID of `NoteRoleNode`.
+pub const NOTE_ROLE_NODE_ID: &str = "mt.semantic.note_roles.v1";
+
+/// Register every built-in node under its impl ID.
+pub fn register_builtin_nodes(registry: &mut NodeRegistry) {
+    registry.register(CHROMA_CHORD_NODE_ID, chroma_chord_factory);
+    registry.register(CHORD_PROBABILITY_NODE_ID, chord_probability_factory);
+    registry.register(CADENCE_NODE_ID, cadence_factory);
+    registry.register(NOTE_ROLE_NODE_ID, note_role_factory);
+}
+
+/// `AudioBlock` → `ChordEvents` straight from chroma (no note transcription).
+///
+/// Multichannel blocks are downmixed to mono. Event times are on the block's
+/// timeline (offset by `AudioBlock::start`).
+#[derive(Clone, Debug, Default)]
+pub struct ChromaChordNode {
+    pub analyzer: ChromaChordAnalyzer,
+    pub chroma: ChromaConfig,
+    pub chord: ChordConfig,
+}
+
+impl TypedNode<AudioBlock, Vec<ChordEvent>> for ChromaChordNode {
+    fn id(&self) -> &'static str {
+        CHROMA_CHORD_NODE_ID
+    }
+
+    fn process(&mut self, input: AudioBlock) -> Result<Vec<ChordEvent>, EngineError> {
+        let mono = downmix(&input);
+        let mut events =
+            self.analyzer.detect_audio_chords(&mono, input.sample_rate, &self.chroma, &self.chord);
+        let start = input.start.value();
+        for e in &mut events {
+            e.onset = SampleTime::new(e.onset.value() + start);
+            e.offset = SampleTime::new(e.offset.value() + start);
+        }
+        Ok(events)
+    }
+}
+
+/// Builds a `ChromaChordNode` from the `chroma` and `chord` sections of
+/// `NodeConfig::analysis`.
+fn chroma_chord_factory(cfg: &NodeConfig) -> Result<Box<dyn DynNode>, EngineError> {
+    let chroma = cfg.analysis.chroma;
+    if chroma.frame_size == 0 || chroma.hop_size == 0 {
+        return Err(EngineError::InvalidConfig("chroma frame and hop sizes must be non-zero"));
+    }
+    if chroma.min_midi > chroma.max_midi {
+        return Err(EngineError::InvalidConfig("chroma min_midi is above max_midi"));
+    }
+    let node = ChromaChordNode {
+        analyzer: ChromaChordAnalyzer::default(),
+        chroma,
+        chord: cfg.analysis.chord,
+    };
+    Ok(Box::new(NodeAdapter::new(
+        cfg.id.clone(),
+        ValueType::AudioBlock,
+        ValueType::ChordEvents,
+        node,
+    )))
+}
+
+/// `NoteEvents` → `ChordProbabilities`: the top candidates of each voiced
+/// frame of `HmmChordAnalyzer`'s posterior `ChordProbabilityMap`. The
+/// graph sets `chord.sample_rate` from the stream.
+#[derive(Clone, Debug, Default)]
+pub struct ChordProbabilityNode {
+    pub analyzer: HmmChordAnalyzer,
+    pub chord: ChordConfig,
+}
+
+impl TypedNode<Vec<NoteEvent>, Vec<ChordProbabilityFrame>> for ChordProbabilityNode {
+    fn id(&self) -> &'static str {
+        CHORD_PROBABILITY_NODE_ID
+    }
+
+    fn process(
+        &mut self,
+        input: Vec<NoteEvent>,
+    ) -> Result<Vec<ChordProbabilityFrame>, EngineError> {
+        let mut notes = input;
+        notes.sort_by_key(|n| n.onset);
+        let map = self.analyzer.chord_probabilities(&notes, &self.chord);
+        Ok(map.to_top_n_frames(MAX_CHORD_CANDIDATES))
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: u32) {
+        if sample_rate > 0 {
+            self.chord.sample_rate = sample_rate;
+        }
+    }
+}
+
+/// Builds a `ChordProbabilityNode` from the `chord` section of
+/// `NodeConfig::analysis`.
+fn chord_probability_factory(cfg: &NodeConfig) -> Result<Box<dyn DynNode>, EngineError> {
+    let node =
+        ChordProbabilityNode { analyzer: HmmChordAnalyzer::default(), chord: chord_config(cfg)? };
+    Ok(Box::new(NodeAdapter::new(
+        cfg.id.clone(),
+        ValueType::NoteEvents,
+        ValueType::ChordProbabilities,
+        node,
+    )))
+}
+
+/// `ChordEvents` → `Cadences` over one chord timeline.
+///
+/// Reads the local key, tempo and meter from `KeyEvents`, `TempoEvents` and
+/// `MeterEvents` it observes on the same timeline. Without observed keys,
+/// local keys are estimated from the chords with `SpiralArrayKeyAnalyzer`;
+/// without tempo or meter, the bar grid is 120 BPM 4/4.
+#[derive(Clone, Debug)]
+pub struct CadenceNode {
+    pub key: KeyConfig,
+    pub cadence: CadenceConfig,
+    /// Sample rate of chord positions; the graph sets it from the stream.
+    pub sample_rate: u32,
+    keys: Vec<KeyEvent>,
+    tempos: Vec<TempoEvent>,
+    meters: Vec<MeterEvent>,
+}
+
+impl Default for CadenceNode {
+    fn default() -> Self {
+        Self {
+            key: KeyConfig::default(),
+            cadence: CadenceConfig::default(),
+            sample_rate: 44_100,
+            keys: Vec::new(),
+            tempos: Vec::new(),
+            meters: Vec::new(),
+        }
+    }
+}
+
+impl TypedNode<Vec<ChordEvent>, Vec<CadenceEvent>> for CadenceNode {
+    fn id(&self) -> &'static str {
+        CADENCE_NODE_ID
+    }
+
+    fn process(&mut self, input: Vec<ChordEvent>) -> Result<Vec<CadenceEvent>, EngineError> {
+        let estimated;
+        let keys = if self.keys.is_empty() {
+            let cfg = KeyConfig { sample_rate: self.sample_rate, ..self.key };
+            estimated = SpiralArrayKeyAnalyzer::default().detect_keys_from_chords(&input, &cfg);
+            &estimated
+        } else {
+            &self.keys
+        };
+        Ok(CadenceDetector.detect_cadences(
+            &input,
+            keys,
+            &self.tempos,
+            &self.meters,
+            self.sample_rate,
+            &self.cadence,
+        ))
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: u32) {
+        if sample_rate > 0 {
+            self.sample_rate = sample_rate;
+        }
+    }
+
+    fn observe(&mut self, value: &Value) {
+        match value {
+            Value::KeyEvents(e) => merge_by_position(&mut self.keys, e, |k| k.position),
+            Value::TempoEvents(e) => merge_by_position(&mut self.tempos, e, |t| t.position),
+            Value::MeterEvents(e) => merge_by_position(&mut self.meters, e, |m| m.position),
+            _ => {}
+        }
+    }
+
+    fn reset(&mut self) {
+        self.keys.clear();
+        self.tempos.clear();
+        self.meters.clear();
+    }
+}
+
+/// Builds a `CadenceNode` from the `key` and `cadence` sections of
+/// `NodeConfig::analysis`.
+fn cadence_factory(cfg: &NodeConfig) -> Result<Box<dyn DynNode>, EngineError> {
+    let region = cfg.analysis.key.min_region_seconds;
+    if !(region.is_finite() && region > 0.0) {
+        return Err(EngineError::InvalidConfig("key min_region_seconds must be positive"));
+    }
+    let node = CadenceNode {
+        key: cfg.analysis.key,
+        cadence: cfg.analysis.cadence,
+        ..CadenceNode::default()
+    };
+    Ok(Box::new(NodeAdapter::new(
+        cfg.id.clone(),
+        ValueType::ChordEvents,
+        ValueType::Cadences,
+        node,
+    )))
+}
+
+/// Append `new` to `events`, keeping position order (stable for ties).
+fn merge_by_position<T: Copy>(events: &mut Vec<T>, new: &[T], position: fn(&T) -> SampleTime) {
+    events.extend_from_slice(new);
+    events.sort_by_key(position);
+}
+
+/// `NoteEvents` → `NoteRoles` for the top voice.
+///
+/// Chords are detected from all notes with `RuleBasedChordAnalyzer`; the
+/// melody is the highest note sounding at each onset. The graph sets
+/// `chord.sample_rate` from the stream.
+#[derive(Clone, Debug, Default)]
+pub struct NoteRoleNode {
+    pub chord: ChordConfig,
+}
+
+impl TypedNode<Vec<NoteEvent>, Vec<NoteRoleEvent>> for NoteRoleNode {
+    fn id(&self) -> &'static str {
+        NOTE_ROLE_NODE_ID
+    }
+
+    fn process(&mut self, input: Vec<NoteEvent>) -> Result<Vec<NoteRoleEvent>, EngineError> {
+        let mut notes = input;
+        notes.sort_by_key(|n| n.onset);
+        let chords = RuleBasedChordAnalyzer.detect_chords(&notes, &self.chord);
+        Ok(classify_note_roles(&top_voice(&notes), &chords))
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: u32) {
+        if sample_rate > 0 {
+            self.chord.sample_rate = sample_rate;
+        }
+    }
+}
+
+/// Builds a `NoteRoleNode` from the `chord` section of `NodeConfig::analysis`.
+fn note_role_factory(cfg: &NodeConfig) -> Result<Box<dyn DynNode>, EngineError> {
+    let node = NoteRoleNode { chord: chord_config(cfg)? };
+    Ok(Box::new(NodeAdapter::new(
+        cfg.id.clone(),
+        ValueType::NoteEvents,
+        ValueType::NoteRoles,
+        node,
+    )))
+}
+
+/// The `chord` section of `NodeConfig::analysis`, if note-based chord
+/// analysis can step through it.
+fn chord_config(cfg: &NodeConfig) -> Result<ChordConfig, EngineError> {
+    let chord = cfg.analysis.chord;
+    if !(chord.window_seconds > 0.0 && chord.hop_seconds > 0.0) {
+        return Err(EngineError::InvalidConfig("chord window and hop must be positive"));
+    }
+    Ok(chord)
+}
+
+/// Notes (sorted by onset) with no higher note sounding at their onset.
+///
+/// Each onset only visits the notes sounding at it, via `NoteStore`.
+fn top_voice(notes: &[NoteEvent]) -> Vec<NoteEvent> {
+    let index = NoteStore::from_notes(notes.iter().copied());
+    notes
+        .iter()
+        .filter(|n| !index.notes_at(n.onset).any(|o| o.note.value() > n.note.value()))
+        .copied()
+        .collect()
+}
+
+/// Average interleaved channels into one mono buffer.
+fn downmix(block: &AudioBlock) -> Vec<f32> {
+    let channels = block.channels.max(1);
+    if channels == 1 {
+        return block.frames.clone();
+    }
+    block
+        .frames
+        .chunks_exact(usize::from(channels))
+        .map(|frame| frame.iter().sum::<f32>() / f32::from(channels))
+        .collect()
+}
@@ -3,12 +3,17 @@
 //! - DynNode wrapper using `Value`.
 //! - NodeRegistry.
 //! - PipelineGraph execution.
+//! - Pipeline: a graph built from an `EngineConfig` with the built-in nodes.
 
 use std::collections::BTreeMap;
 
+use mt_core::events::{
+    CadenceEvent, ChordEvent, ChordProbabilityFrame, NoteEvent, NoteRoleEvent,
+};
+
 use crate::{
-    config::{EdgeConfig, NodeConfig, PipelineConfig},
-    types::{EngineError, EngineEvent, Value, ValueType},
+    config::{EngineConfig, NodeConfig, PipelineConfig},
+    types::{AudioBlock, EngineError, EngineEvent, Value, ValueType},
 };
 
 /// Trait for strongly-typed node implementations.
@@ -18,6 +23,16 @@ use crate::{
 pub trait TypedNode<I, O>: Send {
     fn id(&self) -> &'static str;
     fn process(&mut self, input: I) -> Result<O, EngineError>;
+
+    /// Sample rate that input positions are expressed in.
+    fn set_sample_rate(&mut self, _sample_rate: u32) {}
+
+    /// Another output on the same timeline (tempo, meter, keys, ...), for
+    /// nodes that read context beside their input.
+    fn observe(&mut self, _value: &Value) {}
+
+    /// Forget observed context before a new timeline starts.
+    fn reset(&mut self) {}
 }
 
 /// Convert from dynamic `Value` into a typed input.
@@ -51,6 +66,69 @@ impl IntoValue for () {
     }
 }
 
+impl FromValue for AudioBlock {
+    fn from_value(v: Value) -> Result<Self, EngineError> {
+        match v {
+            Value::AudioBlock(block) => Ok(block),
+            other => Err(EngineError::TypeMismatch {
+                node_id: "<audio>".to_string(),
+                expected: ValueType::AudioBlock,
+                actual: value_type_of(&other),
+            }),
+        }
+    }
+}
+
+impl FromValue for Vec<NoteEvent> {
+    fn from_value(v: Value) -> Result<Self, EngineError> {
+        match v {
+            Value::NoteEvents(events) => Ok(events),
+            other => Err(EngineError::TypeMismatch {
+                node_id: "<notes>".to_string(),
+                expected: ValueType::NoteEvents,
+                actual: value_type_of(&other),
+            }),
+        }
+    }
+}
+
+impl IntoValue for Vec<NoteRoleEvent> {
+    fn into_value(self) -> Value {
+        Value::NoteRoles(self)
+    }
+}
+
+impl FromValue for Vec<ChordEvent> {
+    fn from_value(v: Value) -> Result<Self, EngineError> {
+        match v {
+            Value::ChordEvents(events) => Ok(events),
+            other => Err(EngineError::TypeMismatch {
+                node_id: "<chords>".to_string(),
+                expected: ValueType::ChordEvents,
+                actual: value_type_of(&other),
+            }),
+        }
+    }
+}
+
+impl IntoValue for Vec<ChordEvent> {
+    fn into_value(self) -> Value {
+        Value::ChordEvents(self)
+    }
+}
+
+impl IntoValue for Vec<ChordProbabilityFrame> {
+    fn into_value(self) -> Value {
+        Value::ChordProbabilities(self)
+    }
+}
+
+impl IntoValue for Vec<CadenceEvent> {
+    fn into_value(self) -> Value {
+        Value::Cadences(self)
+    }
+}
+
 impl FromValue for Value {
     fn from_value(v: Value) -> Result<Self, EngineError> {
         Ok(v)
@@ -63,22 +141,6 @@ impl IntoValue for Value {
     }
 }
 
-// Helper to compute ValueType at runtime.
-fn value_type_of(v: &Value) -> ValueType {
-    match v {
-        Value::Unit => ValueType::Unit,
-        Value::AudioBlock(_) => ValueType::AudioBlock,
-        Value::MidiEvents(_) => ValueType::MidiEvents,
-        Value::NoteEvents(_) => ValueType::NoteEvents,
-        Value::ChordEvents(_) => ValueType::ChordEvents,
-        Value::KeyEvents(_) => ValueType::KeyEvents,
-        Value::SegmentEvents(_) => ValueType::SegmentEvents,
-        Value::TempoEvents(_) => ValueType::TempoEvents,
-        Value::MeterEvents(_) => ValueType::MeterEvents,
-        Value::SwingEvents(_) => ValueType::SwingEvents,
-    }
-}
-
 /// Type-erased node used inside the graph.
 ///
 /// Implementations must be deterministic and side-effect free (modulo output).
@@ -97,6 +159,15 @@ pub trait DynNode: Send {
 
     /// Process one input value into one output value.
     fn process_dyn(&mut self, input: Value) -> Result<Value, EngineError>;
+
+    /// See `TypedNode::set_sample_rate`.
+    fn set_sample_rate(&mut self, sample_rate: u32);
+
+    /// See `TypedNode::observe`.
+    fn observe(&mut self, value: &Value);
+
+    /// See `TypedNode::reset`.
+    fn reset(&mut self);
 }
 
 /// Generic adapter from a strongly-typed node to DynNode.
@@ -111,8 +182,8 @@ where
     input_type: ValueType,
     output_type: ValueType,
     inner: N,
-    _phantom_i: core::marker::PhantomData<I>,
-    _phantom_o: core::marker::PhantomData<O>,
+    // `fn(I) -> O` keeps the adapter `Send` whatever `I` and `O` are.
+    _phantom: core::marker::PhantomData<fn(I) -> O>,
 }
 
 impl<I, O, N> NodeAdapter<I, O, N>
@@ -128,8 +199,7 @@ where
             input_type,
             output_type,
             inner,
-            _phantom_i: core::marker::PhantomData,
-            _phantom_o: core::marker::PhantomData,
+            _phantom: core::marker::PhantomData,
         }
     }
 }
@@ -161,6 +231,18 @@ where
         let typed_output = self.inner.process(typed_input)?;
         Ok(typed_output.into_value())
     }
+
+    fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.inner.set_sample_rate(sample_rate);
+    }
+
+    fn observe(&mut self, value: &Value) {
+        self.inner.observe(value);
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
 }
 
 /// Factory signature used by the node registry.
@@ -262,6 +344,34 @@ impl PipelineGraph {
         Ok(Self { nodes, edges })
     }
 
+    /// Input type of the node with instance id `node_id`.
+    #[must_use]
+    pub fn input_type(&self, node_id: &str) -> Option<ValueType> {
+        self.nodes.iter().find(|n| n.instance_id() == node_id).map(|n| n.input_type())
+    }
+
+    /// Tell every node the sample rate of the stream.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        for node in &mut self.nodes {
+            node.set_sample_rate(sample_rate);
+        }
+    }
+
+    /// Show every node a value produced outside the graph, e.g. the tempo
+    /// map of a MIDI file.
+    pub fn observe(&mut self, value: &Value) {
+        for node in &mut self.nodes {
+            node.observe(value);
+        }
+    }
+
+    /// Clear observed context before a new timeline.
+    pub fn reset(&mut self) {
+        for node in &mut self.nodes {
+            node.reset();
+        }
+    }
+
     /// Execute the pipeline once starting from a given entry node input.
     ///
     /// v1 model:
@@ -269,6 +379,8 @@ impl PipelineGraph {
     ///   pass an initial `Value` to that node's `process_dyn`.
     /// - Edges propagate outputs to downstream nodes; last outputs
     ///   generate EngineEvents.
+    /// - Every other node observes each output, so later nodes see
+    ///   context produced earlier in the run.
     pub fn execute(
         &mut self,
         entry_node_id: &str,
@@ -306,6 +418,7 @@ impl PipelineGraph {
                 }
                 let output = node.process_dyn(input)?;
                 let out_ty = node.output_type();
+                let node_id = node.instance_id().to_string();
 
                 // Route to children.
                 for edge in self.edges.iter().filter(|e| e.from == idx) {
@@ -313,9 +426,16 @@ impl PipelineGraph {
                     pending[edge.to] = Some(output.clone());
                 }
 
+                // Every other node may read the output as context.
+                for (other_idx, other) in self.nodes.iter_mut().enumerate() {
+                    if other_idx != idx {
+                        other.observe(&output);
+                    }
+                }
+
                 // Emit event for this node's output.
                 events.push(EngineEvent::NodeOutput {
-                    node_id: node.instance_id().to_string(),
+                    node_id,
                     value: match out_ty {
                         _ => output,
                     },
@@ -327,6 +447,30 @@ impl PipelineGraph {
     }
 }
 
+/// Compiled pipeline for an `EngineConfig`, with the built-in nodes.
+pub struct Pipeline {
+    graph: PipelineGraph,
+}
+
+impl Pipeline {
+    /// Build the config's graph from a registry of the built-in nodes.
+    pub fn build(cfg: &EngineConfig) -> Result<Self, EngineError> {
+        let mut registry = NodeRegistry::new();
+        crate::nodes::register_builtin_nodes(&mut registry);
+        let graph = PipelineGraph::from_config(&cfg.pipeline, &registry)?;
+        Ok(Self { graph })
+    }
+
+    #[must_use]
+    pub fn graph(&self) -> &PipelineGraph {
+        &self.graph
+    }
+
+    pub fn graph_mut(&mut self) -> &mut PipelineGraph {
+        &mut self.graph
+    }
+}
+
 // Simple DFS-based cycle detection.
 fn has_cycle(node_count: usize, edges: &[Edge]) -> bool {
     #[derive(Copy, Clone, PartialEq, Eq)]
@@ -372,7 +516,10 @@ pub(crate) fn value_type_of(v: &Value) -> ValueType {
         Value::AudioBlock(_) => ValueType::AudioBlock,
         Value::MidiEvents(_) => ValueType::MidiEvents,
         Value::NoteEvents(_) => ValueType::NoteEvents,
+        Value::NoteRoles(_) => ValueType::NoteRoles,
         Value::ChordEvents(_) => ValueType::ChordEvents,
+        Value::ChordProbabilities(_) => ValueType::ChordProbabilities,
+        Value::Cadences(_) => ValueType::Cadences,
         Value::KeyEvents(_) => ValueType::KeyEvents,
         Value::SegmentEvents(_) => ValueType::SegmentEvents,
         Value::TempoEvents(_) => ValueType::TempoEvents,
@@ -1,16 +1,21 @@
 //! Shared engine-level types:
-//! - EngineError
-//! - EngineVersion
-//! - ValueType / Value (type universe for nodes)
-//! - AudioBlock
-//! - EngineEvent
+//! - `EngineError`
+//! - `EngineVersion`
+//! - `ValueType` / `Value` (type universe for nodes)
+//! - `AudioBlock`
+//! - `EngineEvent`
+//! - `AnalyzeRequest` / `AnalyzeResponse` (offline API)
 
 use std::fmt;
+use std::path::PathBuf;
 
-use mt-core::events::{
-    ChordEvent, KeyEvent, MeterEvent, NoteEvent, SegmentEvent, SwingEvent, TempoEvent,
+use mt_core::events::{
+    CadenceEvent, ChordEvent, ChordProbabilityFrame, KeyEvent, MeterEvent, NoteEvent,
+    NoteRoleEvent, SegmentEvent, SwingEvent, TempoEvent,
 };
-use mt-core::midi::MidiEvent;
+use mt_core::midi::TimedMidiEvent;
+use mt_core::time::SampleTime;
+use mt_formats::AudioError;
 
 /// Version of the engine core.
 ///
@@ -59,6 +64,14 @@ pub enum EngineError {
         node_id: String,
         message: &'static str,
     },
+    /// Audio file in a container or encoding the decoder does not support.
+    UnsupportedAudioFormat(String),
+    /// Audio file that is unreadable, truncated or malformed.
+    AudioDecode(String),
+    /// Standard MIDI File that is truncated or malformed.
+    MidiDecode(String),
+    /// Request the engine cannot run, e.g. no inputs or an unreadable file.
+    InvalidInput(String),
 }
 
 impl fmt::Display for EngineError {
@@ -82,20 +95,37 @@ impl fmt::Display for EngineError {
             Self::ExecutionFailed { node_id, message } => {
                 write!(f, "execution failed at node `{node_id}`: {message}")
             }
+            Self::UnsupportedAudioFormat(what) => write!(f, "unsupported audio format: {what}"),
+            Self::AudioDecode(msg) => write!(f, "audio decode failed: {msg}"),
+            Self::MidiDecode(msg) => write!(f, "MIDI decode failed: {msg}"),
+            Self::InvalidInput(msg) => write!(f, "invalid input: {msg}"),
         }
     }
 }
 
 impl std::error::Error for EngineError {}
 
+impl From<AudioError> for EngineError {
+    fn from(e: AudioError) -> Self {
+        match e {
+            AudioError::Unsupported(what) => Self::UnsupportedAudioFormat(what),
+            AudioError::Decode(msg) => Self::AudioDecode(msg),
+        }
+    }
+}
+
 /// Value "shape" for graph edges.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ValueType {
     Unit,
     AudioBlock,
     MidiEvents,
     NoteEvents,
+    NoteRoles,
     ChordEvents,
+    ChordProbabilities,
+    Cadences,
     KeyEvents,
     SegmentEvents,
     TempoEvents,
@@ -108,6 +138,8 @@ pub enum ValueType {
 /// Samples are interleaved. Size is unconstrained; chunking happens above.
 #[derive(Clone, Debug)]
 pub struct AudioBlock {
+    /// Timeline position of the first frame.
+    pub start: SampleTime,
     pub sample_rate: u32,
     pub channels: u16,
     pub frames: Vec<f32>,
@@ -122,9 +154,15 @@ pub struct AudioBlock {
 pub enum Value {
     Unit,
     AudioBlock(AudioBlock),
-    MidiEvents(Vec<MidiEvent>),
+    /// MIDI in position order, with block offsets and absolute positions.
+    MidiEvents(Vec<TimedMidiEvent>),
     NoteEvents(Vec<NoteEvent>),
+    /// Chord-tone / non-chord-tone role per note.
+    NoteRoles(Vec<NoteRoleEvent>),
     ChordEvents(Vec<ChordEvent>),
+    /// Top-N chord candidates per analysis frame.
+    ChordProbabilities(Vec<ChordProbabilityFrame>),
+    Cadences(Vec<CadenceEvent>),
     KeyEvents(Vec<KeyEvent>),
     SegmentEvents(Vec<SegmentEvent>),
     TempoEvents(Vec<TempoEvent>),
@@ -140,3 +178,26 @@ pub enum EngineEvent {
     /// Node produced an output value.
     NodeOutput { node_id: String, value: Value },
 }
+
+/// Offline analysis request for `api::analyze_offline`.
+#[derive(Clone, Debug, Default)]
+pub struct AnalyzeRequest {
+    /// Audio files (WAV, AIFF, FLAC), ingested first, in order.
+    pub audio_paths: Vec<PathBuf>,
+    /// Standard MIDI Files, ingested after the audio, in order.
+    pub midi_paths: Vec<PathBuf>,
+    /// `EngineConfig` file; the built-in default pipeline if `None`.
+    pub pipeline_config: Option<PathBuf>,
+    /// Base directory for relative input paths.
+    pub project_root: Option<PathBuf>,
+}
+
+/// Result of `api::analyze_offline`.
+#[derive(Clone, Debug)]
+pub struct AnalyzeResponse {
+    pub engine_version: EngineVersion,
+    /// Sample rate that every event position is expressed in.
+    pub sample_rate: u32,
+    /// Every output, in emission order.
+    pub events: Vec<EngineEvent>,
+}
@@ -6,15 +6,16 @@
 use std::collections::BTreeSet;
 
 use crate::{
-    config::PipelineConfig,
-    types::EngineError,
+    config::{EngineConfig, PipelineConfig},
+    pipeline::Pipeline,
+    types::{EngineError, ValueType},
 };
 
 /// Basic structural validation of a PipelineConfig.
 pub fn validate_pipeline_config(cfg: &PipelineConfig) -> Result<(), EngineError> {
     let mut ids = BTreeSet::new();
     for n in &cfg.nodes {
-        if !ids.insert(&n.id) {
+        if !ids.insert(n.id.as_str()) {
             return Err(EngineError::DuplicateNodeId(n.id.clone()));
         }
     }
@@ -30,3 +31,28 @@ pub fn validate_pipeline_config(cfg: &PipelineConfig) -> Result<(), EngineError>
 
     Ok(())
 }
+
+/// Validate a config against the pipeline built from it: the graph is
+/// sound and the audio / MIDI / note entry nodes exist and take the right
+/// input.
+pub fn validate_engine(cfg: &EngineConfig, pipeline: &Pipeline) -> Result<(), EngineError> {
+    validate_pipeline_config(&cfg.pipeline)?;
+    let entries = [
+        (&cfg.rt.audio_entry, ValueType::AudioBlock),
+        (&cfg.rt.midi_entry, ValueType::MidiEvents),
+        (&cfg.rt.note_entry, ValueType::NoteEvents),
+    ];
+    for (entry, expected) in entries {
+        let Some(id) = entry else {
+            continue;
+        };
+        match pipeline.graph().input_type(id) {
+            None => return Err(EngineError::NodeNotFound(id.clone())),
+            Some(actual) if actual != expected => {
+                return Err(EngineError::TypeMismatch { node_id: id.clone(), expected, actual });
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,506 @@
+use mt_analysis::config::CadenceConfig;
+use mt_core::chord::Chord;
+use mt_core::chord_kind::ChordKindId;
+use mt_core::events::{
+    CadenceEvent, CadenceKind, ChordEvent, ChordProbabilityFrame, ChordToneDegree, KeyEvent,
+    MeterEvent, NonChordToneKind, NoteEvent, NoteId, NoteRole, TempoEvent, TrackId,
+};
+use mt_core::key::{Key, KeyMode};
+use mt_core::midi::{MidiChannel, MidiEvent, MidiEventKind, TimedMidiEvent};
+use mt_core::pitch::{MidiNote, PitchClass};
+use mt_core::time::SampleTime;
+use mt_engine::EngineConfig;
+use mt_engine::config::{NodeConfig, PipelineConfig};
+use mt_engine::engine_session::{EngineBuilder, RtDropCounters};
+use mt_engine::nodes::{
+    CHORD_PROBABILITY_NODE_ID, CadenceNode, ChordProbabilityNode, ChromaChordNode,
+    NOTE_ROLE_NODE_ID, NoteRoleNode, register_builtin_nodes,
+};
+use mt_engine::pipeline::{DynNode, NodeAdapter, NodeRegistry, Pipeline, PipelineGraph, TypedNode};
+use mt_engine::types::{AudioBlock, EngineError, EngineEvent, Value, ValueType};
+
+const SR: u32 = 22_050;
+
+/// Mono C major triad.
+fn triad(seconds: f32) -> Vec<f32> {
+    tones(&[261.63, 329.63, 392.0], seconds)
+}
+
+/// Equal-amplitude sines at `freqs`.
+fn tones(freqs: &[f32], seconds: f32) -> Vec<f32> {
+    let n = (seconds * SR as f32) as usize;
+    (0..n)
+        .map(|i| {
+            let t = i as f32 / SR as f32;
+            freqs
+                .iter()
+                .map(|f| (2.0 * std::f32::consts::PI * f * t).sin() / freqs.len() as f32)
+                .sum()
+        })
+        .collect()
+}
+
+fn block(start: i64, frames: Vec<f32>) -> AudioBlock {
+    AudioBlock { start: SampleTime::new(start), sample_rate: SR, channels: 1, frames }
+}
+
+fn spans(events: &[ChordEvent]) -> Vec<(i64, i64)> {
+    events.iter().map(|e| (e.onset.value(), e.offset.value())).collect()
+}
+
+#[test]
+fn chroma_chord_events_are_offset_by_the_block_start() {
+    let mut node = ChromaChordNode::default();
+    let at_zero = node.process(block(0, triad(2.0))).unwrap();
+    let later = node.process(block(5 * i64::from(SR), triad(2.0))).unwrap();
+    assert!(!at_zero.is_empty());
+    let shifted: Vec<(i64, i64)> = spans(&at_zero)
+        .iter()
+        .map(|&(on, off)| (on + 5 * i64::from(SR), off + 5 * i64::from(SR)))
+        .collect();
+    assert_eq!(spans(&later), shifted);
+}
+
+#[test]
+fn chroma_chord_factory_reads_the_analysis_config() {
+    let run = |cfg: &EngineConfig| {
+        let mut pipeline = Pipeline::build(cfg).unwrap();
+        let out = pipeline.graph_mut().execute("chords", Value::AudioBlock(block(0, triad(2.0))));
+        let Some(Value::ChordEvents(chords)) = out.unwrap().into_iter().find_map(|e| {
+            let EngineEvent::NodeOutput { node_id, value } = e;
+            (node_id == "chords").then_some(value)
+        }) else {
+            panic!("no chord output");
+        };
+        chords
+    };
+
+    let mut cfg = EngineConfig::offline_default();
+    let default_frames = spans(&run(&cfg));
+    cfg.pipeline.nodes[0].analysis.chroma.hop_size = 3000;
+    let finer = run(&cfg);
+    assert!(!finer.is_empty());
+    assert!(finer.iter().all(|e| e.offset.value() % 3000 == 0), "{:?}", spans(&finer));
+    assert_ne!(spans(&finer), default_frames);
+
+    cfg.pipeline.nodes[0].analysis.chord.min_confidence = 2.0;
+    assert!(run(&cfg).is_empty());
+
+    cfg.pipeline.nodes[0].analysis.chroma.hop_size = 0;
+    assert!(matches!(Pipeline::build(&cfg).err(), Some(EngineError::InvalidConfig(_))));
+}
+
+#[test]
+fn realtime_blocks_keep_their_timeline_position() {
+    let mut cfg = EngineConfig::offline_default();
+    cfg.rt.sample_rate = SR;
+    cfg.rt.channels = 1;
+    cfg.rt.analysis_block_frames = SR as usize;
+    let mut registry = NodeRegistry::new();
+    register_builtin_nodes(&mut registry);
+    let (mut rt, mut session) = EngineBuilder::new(&cfg, &registry).build_realtime().unwrap();
+
+    // Two contiguous half-second blocks from 10 s make one analysis block;
+    // a block after a gap starts a new one.
+    let audio = triad(1.0);
+    let ten = 10 * i64::from(SR);
+    let half = audio.len() / 2;
+    assert!(rt.push_audio_block(SampleTime::new(ten), &audio[..half]));
+    assert!(rt.push_audio_block(SampleTime::new(ten + half as i64), &audio[half..]));
+    assert!(rt.push_audio_block(SampleTime::new(3 * ten), &audio));
+    session.flush().unwrap();
+
+    let chords = session.chord_events();
+    assert!(!chords.is_empty());
+    let (first, second): (Vec<&ChordEvent>, Vec<&ChordEvent>) =
+        chords.iter().partition(|e| e.onset.value() < 3 * ten);
+    assert!(
+        first.iter().all(|e| e.onset.value() >= ten && e.offset.value() <= ten + i64::from(SR))
+    );
+    assert!(!second.is_empty());
+    assert!(second.iter().all(|e| e.onset.value() >= 3 * ten));
+}
+
+/// I–IV–V–I in C major, one 2 s bar (120 BPM 4/4) per chord.
+fn cadence_bars() -> Vec<ChordEvent> {
+    let bar = 2 * i64::from(SR);
+    [(0, ChordKindId::Maj), (5, ChordKindId::Maj), (7, ChordKindId::Dom7), (0, ChordKindId::Maj)]
+        .iter()
+        .zip(0_i64..)
+        .map(|(&(root, kind), i)| ChordEvent {
+            chord: Chord::new(PitchClass::new(root).unwrap(), kind, None).unwrap(),
+            onset: SampleTime::new(i * bar),
+            offset: SampleTime::new((i + 1) * bar),
+            confidence_x1000: 1000,
+        })
+        .collect()
+}
+
+#[test]
+fn cadence_node_estimates_the_key_from_the_chords() {
+    let mut node = CadenceNode::default();
+    node.set_sample_rate(SR);
+    // Estimated keys carry the Spiral Array's key margin as confidence.
+    node.cadence.min_confidence = 0.1;
+    let cadences = node.process(cadence_bars()).unwrap();
+    assert_eq!(cadences.len(), 1, "{cadences:?}");
+    assert_eq!(cadences[0].kind, CadenceKind::PerfectAuthentic);
+    assert_eq!(cadences[0].key.tonic().as_u8(), 0);
+    assert_eq!(cadences[0].position.value(), 6 * i64::from(SR));
+
+    // Too short a timeline for a key: no cadences.
+    node.key.min_region_seconds = 10.0;
+    assert!(node.process(cadence_bars()).unwrap().is_empty());
+}
+
+fn chord(root: u8, kind: ChordKindId, onset: i64, offset: i64) -> ChordEvent {
+    ChordEvent {
+        chord: Chord::new(PitchClass::new(root).unwrap(), kind, None).unwrap(),
+        onset: SampleTime::new(onset),
+        offset: SampleTime::new(offset),
+        confidence_x1000: 1000,
+    }
+}
+
+#[test]
+fn cadence_node_reads_observed_context_at_the_stream_rate() {
+    // C, D7, G at 48 kHz, one 3/4 bar of 120 BPM (1.5 s) each.
+    const SR_48K: i64 = 48_000;
+    let bar = 3 * SR_48K / 2;
+    let chords = vec![
+        chord(0, ChordKindId::Maj, 0, bar),
+        chord(2, ChordKindId::Dom7, bar, 2 * bar),
+        chord(7, ChordKindId::Maj, 2 * bar, 3 * bar),
+    ];
+    let c_major = Key::new(PitchClass::new(0).unwrap(), KeyMode::Major);
+    let context = [
+        Value::KeyEvents(vec![KeyEvent {
+            key: c_major,
+            position: SampleTime::ZERO,
+            confidence_x1000: 1000,
+        }]),
+        Value::TempoEvents(vec![TempoEvent { position: SampleTime::ZERO, bpm_x1000: 120_000 }]),
+        Value::MeterEvents(vec![MeterEvent {
+            position: SampleTime::ZERO,
+            numerator: 3,
+            denominator: 4,
+        }]),
+    ];
+
+    let mut cfg = EngineConfig::offline_default();
+    // Downbeat arrivals only.
+    cfg.pipeline.nodes[1].analysis.cadence.mid_bar_weight = 0.0;
+    cfg.pipeline.nodes[1].analysis.cadence.offbeat_weight = 0.0;
+    let mut pipeline = Pipeline::build(&cfg).unwrap();
+    let graph = pipeline.graph_mut();
+
+    // In the observed C major, G arrives as V on the downbeat of bar 3.
+    graph.set_sample_rate(48_000);
+    for value in &context {
+        graph.observe(value);
+    }
+    let found = cadences(graph, &chords);
+    assert_eq!(found.len(), 1, "{found:?}");
+    assert_eq!(found[0].kind, CadenceKind::Half);
+    assert_eq!(found[0].key, c_major);
+    assert_eq!(found[0].position.value(), 2 * bar);
+
+    // At 44.1 kHz the same positions fall off the beat.
+    graph.set_sample_rate(44_100);
+    assert!(cadences(graph, &chords).is_empty());
+
+    // Without context, 4/4 puts the arrival mid-bar and the key is read
+    // from the chords (D7 → G is V7 → I in G major).
+    graph.set_sample_rate(48_000);
+    graph.reset();
+    assert!(cadences(graph, &chords).is_empty());
+    cfg.pipeline.nodes[1].analysis.cadence = CadenceConfig::default();
+    let mut pipeline = Pipeline::build(&cfg).unwrap();
+    pipeline.graph_mut().set_sample_rate(48_000);
+    let found = cadences(pipeline.graph_mut(), &chords);
+    assert_eq!(found.len(), 1, "{found:?}");
+    assert_eq!(found[0].kind, CadenceKind::PerfectAuthentic);
+    assert_eq!(found[0].key.tonic().as_u8(), 7);
+}
+
+/// Output of the `cadences` node for `chords`.
+fn cadences(graph: &mut PipelineGraph, chords: &[ChordEvent]) -> Vec<CadenceEvent> {
+    let out = graph.execute("cadences", Value::ChordEvents(chords.to_vec())).unwrap();
+    match out.as_slice() {
+        [EngineEvent::NodeOutput { value: Value::Cadences(c), .. }] => c.clone(),
+        other => panic!("{other:?}"),
+    }
+}
+
+#[test]
+fn default_pipeline_feeds_chords_to_the_cadence_node() {
+    let mut cfg = EngineConfig::offline_default();
+    cfg.pipeline.nodes[1].analysis.cadence.min_confidence = 0.1;
+    let mut pipeline = Pipeline::build(&cfg).unwrap();
+    pipeline.graph_mut().set_sample_rate(SR);
+    let out = pipeline.graph_mut().execute("cadences", Value::ChordEvents(cadence_bars()));
+    let kinds: Vec<CadenceKind> = match out.unwrap().as_slice() {
+        [EngineEvent::NodeOutput { value: Value::Cadences(c), .. }] => {
+            c.iter().map(|e| e.kind).collect()
+        }
+        other => panic!("{other:?}"),
+    };
+    assert_eq!(kinds, [CadenceKind::PerfectAuthentic]);
+
+    // Audio: the chord output reaches the cadence node.
+    let mut audio = Vec::new();
+    for freqs in [[261.63, 329.63, 392.0], [349.23, 440.0, 523.25], [392.0, 493.88, 587.33]] {
+        audio.extend(tones(&freqs, 2.0));
+    }
+    audio.extend(triad(2.0));
+    let events = pipeline.graph_mut().execute("chords", Value::AudioBlock(block(0, audio)));
+    let ids: Vec<String> = events
+        .unwrap()
+        .into_iter()
+        .map(|e| {
+            let EngineEvent::NodeOutput { node_id, .. } = e;
+            node_id
+        })
+        .collect();
+    assert_eq!(ids, ["chords", "cadences"]);
+
+    cfg.pipeline.nodes[1].analysis.key.min_region_seconds = 0.0;
+    assert!(matches!(Pipeline::build(&cfg).err(), Some(EngineError::InvalidConfig(_))));
+}
+
+fn note(id: u32, midi: u8, onset: f32, offset: f32) -> NoteEvent {
+    NoteEvent {
+        id: NoteId(id),
+        track: TrackId(0),
+        onset: SampleTime::new((onset * 44_100.0) as i64),
+        offset: SampleTime::new((offset * 44_100.0) as i64),
+        note: MidiNote::new(midi).unwrap(),
+        velocity: 80,
+    }
+}
+
+#[test]
+fn note_role_node_classifies_the_top_voice() {
+    // C E G held under E5, a chromatic passing F#5, then G5. (A diatonic
+    // passing tone would be read as a chord extension: roles do not feed
+    // back into chord detection.)
+    let mut notes = vec![note(0, 48, 0.0, 2.0), note(1, 52, 0.0, 2.0), note(2, 55, 0.0, 2.0)];
+    notes.extend([note(3, 76, 0.0, 0.9), note(4, 78, 0.9, 1.0), note(5, 79, 1.0, 2.0)]);
+    use ChordToneDegree::{Fifth, Third};
+    let expected = [
+        NoteRole::ChordTone(Third),
+        NoteRole::NonChordTone(NonChordToneKind::Passing),
+        NoteRole::ChordTone(Fifth),
+    ];
+
+    let roles = NoteRoleNode::default().process(notes.clone()).unwrap();
+    assert_eq!(roles.iter().map(|e| e.note).collect::<Vec<_>>(), [3, 4, 5].map(NoteId));
+    assert_eq!(roles.iter().map(|e| e.role).collect::<Vec<_>>(), expected);
+
+    let node = NodeConfig {
+        id: "roles".into(),
+        impl_id: NOTE_ROLE_NODE_ID.into(),
+        input_type: None,
+        output_type: None,
+        analysis: Default::default(),
+    };
+    let cfg = EngineConfig::new(PipelineConfig {
+        id: "roles".into(),
+        nodes: vec![node],
+        edges: Vec::new(),
+    });
+    let mut pipeline = Pipeline::build(&cfg).unwrap();
+    let out = pipeline.graph_mut().execute("roles", Value::NoteEvents(notes)).unwrap();
+    let [EngineEvent::NodeOutput { value: Value::NoteRoles(via_graph), .. }] = out.as_slice()
+    else {
+        panic!("{out:?}");
+    };
+    assert_eq!(via_graph, &roles);
+}
+
+/// Passes its input through, so a test can read what the session sent.
+struct Echo;
+
+impl TypedNode<Value, Value> for Echo {
+    fn id(&self) -> &'static str {
+        "test.echo"
+    }
+
+    fn process(&mut self, input: Value) -> Result<Value, EngineError> {
+        Ok(input)
+    }
+}
+
+fn echo_factory(cfg: &NodeConfig) -> Result<Box<dyn DynNode>, EngineError> {
+    Ok(Box::new(NodeAdapter::new(
+        cfg.id.clone(),
+        ValueType::NoteEvents,
+        ValueType::NoteEvents,
+        Echo,
+    )))
+}
+
+#[test]
+fn realtime_ump_keeps_per_note_expression() {
+    let mut registry = NodeRegistry::new();
+    registry.register("test.echo", echo_factory);
+    let node = NodeConfig {
+        id: "notes".into(),
+        impl_id: "test.echo".into(),
+        input_type: None,
+        output_type: None,
+        analysis: Default::default(),
+    };
+    let mut cfg = EngineConfig::new(PipelineConfig {
+        id: "ump".into(),
+        nodes: vec![node],
+        edges: Vec::new(),
+    });
+    cfg.rt.note_entry = Some("notes".into());
+    cfg.rt.midi_capacity_events = 8;
+    let (mut rt, mut session) = EngineBuilder::new(&cfg, &registry).build_realtime().unwrap();
+
+    // Key 60 at 16-bit velocity 0xC000, bent +1200 cents (of 4800) per
+    // note, released 100 samples later. The trailing half packet is ignored.
+    let start = SampleTime::new(1000);
+    assert!(rt.push_ump(start, 0, &[0x4090_3C00, 0xC000_0000, 0x4060_3C00, 0xA000_0000]));
+    assert!(rt.push_ump(start, 100, &[0x4080_3C00, 0, 0x4090_3C00]));
+    session.pump().unwrap();
+
+    let notes = session.note_events();
+    assert_eq!(notes.len(), 1);
+    assert_eq!((notes[0].onset.value(), notes[0].offset.value()), (1000, 1100));
+    assert_eq!((notes[0].note.value(), notes[0].velocity), (72, 96));
+    assert_eq!(session.drop_counters(), RtDropCounters::default());
+
+    // Packets that do not all fit are dropped together.
+    let words: Vec<u32> = (0..5).flat_map(|key| [0x4090_3E00 + (key << 8), 0xC000_0000]).collect();
+    assert!(!rt.push_ump(start, 200, &words));
+    assert_eq!(session.drop_counters().midi_events, 5);
+    session.pump().unwrap();
+    assert_eq!(session.note_events().len(), 1);
+}
+
+#[test]
+fn realtime_midi_without_a_midi_entry_reaches_the_note_entry() {
+    let midi = |offset: u32, kind: MidiEventKind, key: u8| {
+        let channel = MidiChannel::new(0).unwrap();
+        let event = MidiEvent { channel, kind, data1: key, data2: 100 };
+        TimedMidiEvent::new(SampleTime::ZERO, offset, event)
+    };
+    // C major triad held for two seconds.
+    let triad: Vec<TimedMidiEvent> = [60, 64, 67]
+        .iter()
+        .flat_map(|&k| {
+            [midi(0, MidiEventKind::NoteOn, k), midi(2 * 44_100, MidiEventKind::NoteOff, k)]
+        })
+        .collect();
+
+    let mut registry = NodeRegistry::new();
+    register_builtin_nodes(&mut registry);
+    let cfg = EngineConfig::offline_default();
+    let (mut rt, mut session) = EngineBuilder::new(&cfg, &registry).build_realtime().unwrap();
+    assert_eq!(rt.push_midi(&triad), triad.len());
+    session.pump().unwrap();
+    let frames = session.chord_probability_frames();
+    let c = Chord::new(PitchClass::new(0).unwrap(), ChordKindId::Maj, None).unwrap();
+    assert!(!frames.is_empty());
+    assert!(frames.iter().all(|f| f.best().unwrap().chord == c));
+    assert_eq!(session.drop_counters(), RtDropCounters::default());
+
+    // With no entry for MIDI or notes, the events are reported as dropped.
+    let mut cfg = EngineConfig::offline_default();
+    cfg.rt.note_entry = None;
+    let (mut rt, mut session) = EngineBuilder::new(&cfg, &registry).build_realtime().unwrap();
+    rt.push_midi(&triad);
+    session.pump().unwrap();
+    assert!(session.chord_probability_frames().is_empty());
+    assert_eq!(session.drop_counters().midi_events, 6);
+}
+
+#[test]
+fn realtime_history_keeps_the_latest_outputs() {
+    let mut registry = NodeRegistry::new();
+    registry.register("test.echo", echo_factory);
+    let node = NodeConfig {
+        id: "notes".into(),
+        impl_id: "test.echo".into(),
+        input_type: None,
+        output_type: None,
+        analysis: Default::default(),
+    };
+    let mut cfg = EngineConfig::new(PipelineConfig {
+        id: "history".into(),
+        nodes: vec![node],
+        edges: Vec::new(),
+    });
+    cfg.rt.note_entry = Some("notes".into());
+    cfg.rt.history_capacity_events = 2;
+    let (mut rt, mut session) = EngineBuilder::new(&cfg, &registry).build_realtime().unwrap();
+    // One note per pump, so one echoed output each.
+    let mut play = |key: u8| {
+        let channel = MidiChannel::new(0).unwrap();
+        let at = SampleTime::new(i64::from(key) * 1000);
+        let on = MidiEvent { channel, kind: MidiEventKind::NoteOn, data1: key, data2: 100 };
+        let off = MidiEvent { kind: MidiEventKind::NoteOff, ..on };
+        rt.push_midi(&[TimedMidiEvent::new(at, 0, on), TimedMidiEvent::new(at, 100, off)]);
+    };
+
+    let mut polled = Vec::new();
+    play(60);
+    assert_eq!(session.poll_events(&mut polled).unwrap(), 1);
+    for key in [62, 64, 65] {
+        play(key);
+        assert_eq!(session.pump().unwrap(), 1);
+    }
+    // 62 was evicted before it was polled.
+    assert_eq!(session.poll_events(&mut polled).unwrap(), 2);
+    let keys = |notes: Vec<NoteEvent>| notes.iter().map(|n| n.note.value()).collect::<Vec<_>>();
+    assert_eq!(keys(session.note_events()), [64, 65]);
+    assert_eq!(session.snapshot_state().events.len(), 2);
+}
+
+#[test]
+fn chord_probability_node_ranks_the_sounding_chord_first() {
+    // C major, then F major, two seconds each.
+    let mut notes: Vec<NoteEvent> =
+        [60, 64, 67].iter().zip(0..).map(|(&p, i)| note(i, p, 0.0, 2.0)).collect();
+    notes.extend([65, 69, 72].iter().zip(3..).map(|(&p, i)| note(i, p, 2.0, 4.0)));
+    let best = |frame: &ChordProbabilityFrame| frame.best().unwrap().chord;
+    let c = Chord::new(PitchClass::new(0).unwrap(), ChordKindId::Maj, None).unwrap();
+    let f = Chord::new(PitchClass::new(5).unwrap(), ChordKindId::Maj, None).unwrap();
+
+    let frames = ChordProbabilityNode::default().process(notes.clone()).unwrap();
+    assert!(frames.len() > 2);
+    assert_eq!(best(&frames[0]), c);
+    assert_eq!(best(frames.last().unwrap()), f);
+    for frame in &frames {
+        let p: Vec<u16> = frame.candidates().iter().map(|c| c.probability_x1000).collect();
+        assert!(p.windows(2).all(|w| w[0] >= w[1]), "{p:?}");
+    }
+
+    // Through a real-time session: MIDI 1.0 UMP notes to `note_entry`.
+    let node = NodeConfig {
+        id: "probabilities".into(),
+        impl_id: CHORD_PROBABILITY_NODE_ID.into(),
+        input_type: None,
+        output_type: None,
+        analysis: Default::default(),
+    };
+    let mut cfg = EngineConfig::new(PipelineConfig {
+        id: "probabilities".into(),
+        nodes: vec![node],
+        edges: Vec::new(),
+    });
+    cfg.rt.note_entry = Some("probabilities".into());
+    let mut registry = NodeRegistry::new();
+    register_builtin_nodes(&mut registry);
+    let (mut rt, mut session) = EngineBuilder::new(&cfg, &registry).build_realtime().unwrap();
+    for n in &notes {
+        let key = u32::from(n.note.value()) << 8;
+        assert!(rt.push_ump(n.onset, 0, &[0x2090_0050 | key]));
+        assert!(rt.push_ump(n.offset, 0, &[0x2080_0000 | key]));
+    }
+    session.pump().unwrap();
+    assert_eq!(session.chord_probability_frames(), frames);
+}
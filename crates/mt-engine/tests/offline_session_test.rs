@@ -0,0 +1,224 @@
+use std::path::PathBuf;
+
+use mt_core::pedal::NoteDurationMode;
+use mt_engine::EngineConfig;
+use mt_engine::api::analyze_offline;
+use mt_engine::engine_session::{MIDI_FILE_NODE_ID, OfflineSession};
+use mt_engine::pipeline::Pipeline;
+use mt_engine::types::{AnalyzeRequest, EngineError, EngineEvent, Value};
+
+/// Scratch directory unique to one test.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mt-engine-{}-{name}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn chunk(id: &[u8], body: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.extend((body.len() as u32).to_be_bytes());
+    out.extend(body);
+    out
+}
+
+/// Format 0, 480 ticks per quarter at 60 BPM in 3/4: C4 for a quarter,
+/// then E4 for a half.
+fn song() -> Vec<u8> {
+    #[rustfmt::skip]
+    let track: &[u8] = &[
+        0x00, 0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40,       // 60 BPM
+        0x00, 0xFF, 0x58, 0x04, 0x03, 0x02, 0x18, 0x08, // 3/4
+        0x00, 0x90, 0x3C, 0x64,                         // C4 on
+        0x83, 0x60, 0x80, 0x3C, 0x00,                   // tick 480: C4 off
+        0x00, 0x90, 0x40, 0x50,                         // E4 on
+        0x87, 0x40, 0x80, 0x40, 0x00,                   // tick 1440: E4 off
+        0x00, 0xFF, 0x2F, 0x00,
+    ];
+    let mut out = chunk(b"MThd", &[0, 0, 0, 1, 0x01, 0xE0]);
+    out.extend(chunk(b"MTrk", track));
+    out
+}
+
+/// Format 0 at 60 BPM: C4 held for a quarter under the sustain pedal,
+/// which is lifted two quarters later.
+fn sustained() -> Vec<u8> {
+    #[rustfmt::skip]
+    let track: &[u8] = &[
+        0x00, 0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40, // 60 BPM
+        0x00, 0xB0, 0x40, 0x7F,                   // sustain down
+        0x00, 0x90, 0x3C, 0x64,                   // C4 on
+        0x83, 0x60, 0x80, 0x3C, 0x00,             // tick 480: C4 off
+        0x87, 0x40, 0xB0, 0x40, 0x00,             // tick 1440: sustain up
+        0x00, 0xFF, 0x2F, 0x00,
+    ];
+    let mut out = chunk(b"MThd", &[0, 0, 0, 1, 0x01, 0xE0]);
+    out.extend(chunk(b"MTrk", track));
+    out
+}
+
+/// Mono 16-bit WAV of a C major triad.
+fn triad_wav(sample_rate: u32, seconds: u32) -> Vec<u8> {
+    let le = |id: &[u8], body: &[u8]| {
+        let mut out = id.to_vec();
+        out.extend((body.len() as u32).to_le_bytes());
+        out.extend(body);
+        out
+    };
+    let mut fmt = 1u16.to_le_bytes().to_vec(); // PCM
+    fmt.extend(1u16.to_le_bytes());
+    fmt.extend(sample_rate.to_le_bytes());
+    fmt.extend((sample_rate * 2).to_le_bytes());
+    fmt.extend(2u16.to_le_bytes());
+    fmt.extend(16u16.to_le_bytes());
+    let data: Vec<u8> = (0..sample_rate * seconds)
+        .flat_map(|i| {
+            let t = f64::from(i) / f64::from(sample_rate);
+            let v: f64 = [261.63, 329.63, 392.0]
+                .iter()
+                .map(|f| (2.0 * std::f64::consts::PI * f * t).sin())
+                .sum();
+            ((v / 3.0 * 20_000.0) as i16).to_le_bytes()
+        })
+        .collect();
+    let mut body = b"WAVE".to_vec();
+    body.extend(le(b"fmt ", &fmt));
+    body.extend(le(b"data", &data));
+    le(b"RIFF", &body)
+}
+
+fn outputs(events: &[EngineEvent], node: &str) -> Vec<Value> {
+    events
+        .iter()
+        .filter_map(|EngineEvent::NodeOutput { node_id, value }| {
+            (node_id == node).then(|| value.clone())
+        })
+        .collect()
+}
+
+#[test]
+fn midi_file_notes_and_tempo_reach_the_response() {
+    let dir = scratch("midi");
+    std::fs::write(dir.join("song.mid"), song()).unwrap();
+    let req = AnalyzeRequest {
+        midi_paths: vec![PathBuf::from("song.mid")],
+        project_root: Some(dir.clone()),
+        ..AnalyzeRequest::default()
+    };
+    let resp = analyze_offline(req).unwrap();
+    assert_eq!(resp.sample_rate, 44_100);
+
+    let values = outputs(&resp.events, MIDI_FILE_NODE_ID);
+    assert_eq!(values.len(), 3);
+    let Value::TempoEvents(tempos) = &values[0] else { panic!("{values:?}") };
+    assert_eq!(tempos[0].bpm_x1000, 60_000);
+    let Value::MeterEvents(meters) = &values[1] else { panic!("{values:?}") };
+    assert_eq!((meters[0].numerator, meters[0].denominator), (3, 4));
+    let Value::NoteEvents(notes) = &values[2] else { panic!("{values:?}") };
+    let spans: Vec<(u8, i64, i64)> =
+        notes.iter().map(|n| (n.note.value(), n.onset.value(), n.offset.value())).collect();
+    assert_eq!(spans, [(60, 0, 44_100), (64, 44_100, 132_300)]);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn midi_follows_the_configured_sample_rate() {
+    let dir = scratch("rate");
+    let path = dir.join("song.mid");
+    std::fs::write(&path, song()).unwrap();
+    let mut cfg = EngineConfig::offline_default();
+    cfg.rt.sample_rate = 48_000;
+    let pipeline = Pipeline::build(&cfg).unwrap();
+    let mut session = OfflineSession::new(cfg, pipeline, None).unwrap();
+    session.ingest_midi_file(&path).unwrap();
+    let resp = session.finalize().unwrap();
+    assert_eq!(resp.sample_rate, 48_000);
+    let Some(Value::NoteEvents(notes)) = outputs(&resp.events, MIDI_FILE_NODE_ID).pop() else {
+        panic!("no notes");
+    };
+    assert_eq!(notes[1].onset.value(), 48_000);
+
+    // The notes also reach the default note entry.
+    let Some(Value::ChordProbabilities(frames)) = outputs(&resp.events, "probabilities").pop()
+    else {
+        panic!("no chord probabilities: {:?}", resp.events);
+    };
+    assert!(!frames.is_empty());
+    assert!(frames.iter().all(|f| f.onset.value() >= 0 && f.offset.value() <= 144_000));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn audio_file_runs_through_the_chord_node_at_its_own_rate() {
+    let dir = scratch("audio");
+    std::fs::write(dir.join("triad.wav"), triad_wav(22_050, 2)).unwrap();
+    let req = AnalyzeRequest {
+        audio_paths: vec![PathBuf::from("triad.wav")],
+        project_root: Some(dir.clone()),
+        ..AnalyzeRequest::default()
+    };
+    let resp = analyze_offline(req).unwrap();
+    assert_eq!(resp.sample_rate, 22_050);
+    let Some(Value::ChordEvents(chords)) = outputs(&resp.events, "chords").pop() else {
+        panic!("no chords: {:?}", resp.events);
+    };
+    assert!(!chords.is_empty());
+    assert!(chords.iter().all(|c| c.chord.root.as_u8() == 0), "{chords:?}");
+
+    // A second file at another rate cannot share the timeline.
+    std::fs::write(dir.join("other.wav"), triad_wav(48_000, 1)).unwrap();
+    let mixed = AnalyzeRequest {
+        audio_paths: vec![dir.join("triad.wav"), dir.join("other.wav")],
+        ..AnalyzeRequest::default()
+    };
+    assert!(matches!(analyze_offline(mixed).unwrap_err(), EngineError::InvalidInput(_)));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn bad_requests_are_rejected() {
+    let err = analyze_offline(AnalyzeRequest::default()).unwrap_err();
+    assert!(matches!(err, EngineError::InvalidInput(_)), "{err}");
+
+    let missing = AnalyzeRequest {
+        midi_paths: vec![PathBuf::from("/nonexistent/song.mid")],
+        ..AnalyzeRequest::default()
+    };
+    assert!(matches!(analyze_offline(missing).unwrap_err(), EngineError::InvalidInput(_)));
+
+    let dir = scratch("garbage");
+    std::fs::write(dir.join("bad.mid"), b"RIFF....").unwrap();
+    let bad = AnalyzeRequest { midi_paths: vec![dir.join("bad.mid")], ..AnalyzeRequest::default() };
+    assert!(matches!(analyze_offline(bad).unwrap_err(), EngineError::MidiDecode(_)));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn entry_nodes_must_exist() {
+    let mut cfg = EngineConfig::offline_default();
+    cfg.rt.midi_entry = Some("missing".into());
+    let pipeline = Pipeline::build(&cfg).unwrap();
+    let err = OfflineSession::new(cfg, pipeline, None).err().unwrap();
+    assert!(matches!(err, EngineError::NodeNotFound(ref id) if id == "missing"), "{err}");
+}
+
+#[test]
+fn midi_file_notes_follow_the_configured_pedal() {
+    let dir = scratch("pedal");
+    let path = dir.join("sustained.mid");
+    std::fs::write(&path, sustained()).unwrap();
+    let offsets = |durations: NoteDurationMode| {
+        let mut cfg = EngineConfig::offline_default();
+        cfg.rt.pedal.durations = durations;
+        let pipeline = Pipeline::build(&cfg).unwrap();
+        let mut session = OfflineSession::new(cfg, pipeline, None).unwrap();
+        session.ingest_midi_file(&path).unwrap();
+        let resp = session.finalize().unwrap();
+        let Some(Value::NoteEvents(notes)) = outputs(&resp.events, MIDI_FILE_NODE_ID).pop() else {
+            panic!("no notes");
+        };
+        notes.iter().map(|n| n.offset.value()).collect::<Vec<_>>()
+    };
+    assert_eq!(offsets(NoteDurationMode::Sounding), [132_300]);
+    assert_eq!(offsets(NoteDurationMode::KeyRelease), [44_100]);
+    std::fs::remove_dir_all(dir).unwrap();
+}
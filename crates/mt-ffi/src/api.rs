@@ -5,22 +5,19 @@
 //! - validate pointers and sizes,
 //! - catch panics via `catch_unwind`,
 //! - never unwind across FFI,
-//! - return `mt-ffi_status`.
+//! - return `mt_ffi_status`.
 
-use core::ffi::c_void;
 use core::slice;
 use std::panic::catch_unwind;
 
-use mt_core::midi::MidiEvent;
-use mt_core::pitch::MidiNote;
-
 use crate::engine_handle::{EngineHandle, from_raw_handle, into_box};
 use crate::error::MtFfiStatus;
 use crate::types::{
-    MtChordEvent, MtEngineHandle, MtKeyEvent, MtMidiEvent, MtNoteEvent, MtSegmentEvent,
+    MtCadenceEvent, MtChordEvent, MtChordProbabilityFrame, MtEngineHandle, MtKeyEvent, MtMidiEvent,
+    MtNoteEvent, MtNoteRoleEvent, MtSegmentEvent,
 };
 
-/// Helper: wrap a closure and map panics to mt-FFI_ERROR_PANIC.
+/// Helper: wrap a closure and map panics to MT_FFI_ERROR_PANIC.
 fn guard<F>(f: F) -> MtFfiStatus
 where
     F: FnOnce() -> MtFfiStatus + std::panic::UnwindSafe,
@@ -31,8 +28,8 @@ where
     }
 }
 
-#[no_mangle]
-pub extern "C" fn mt-engine_create_default(handle_out: *mut *mut MtEngineHandle) -> MtFfiStatus {
+#[unsafe(no_mangle)]
+pub extern "C" fn mt_engine_create_default(handle_out: *mut *mut MtEngineHandle) -> MtFfiStatus {
     guard(|| unsafe {
         if handle_out.is_null() {
             return MtFfiStatus::MtFfiErrorNull;
@@ -41,7 +38,7 @@ pub extern "C" fn mt-engine_create_default(handle_out: *mut *mut MtEngineHandle)
         match EngineHandle::new_default() {
             Ok(inner) => {
                 let boxed = Box::new(inner);
-                let raw = Box::into_raw(boxed) as *mut MtEngineHandle;
+                let raw = Box::into_raw(boxed).cast::<MtEngineHandle>();
                 *handle_out = raw;
                 MtFfiStatus::MtFfiOk
             }
@@ -50,8 +47,42 @@ pub extern "C" fn mt-engine_create_default(handle_out: *mut *mut MtEngineHandle)
     })
 }
 
-#[no_mangle]
-pub extern "C" fn mt-engine_destroy(handle: *mut MtEngineHandle) -> MtFfiStatus {
+/// Create an engine running the offline default graph at the host's
+/// audio format.
+///
+/// - `sample_rate`: Hz; must be non-zero
+/// - `channels`: interleaved channel count; must be non-zero
+///
+/// `mt_engine_push_audio_f32_interleaved` must then be called with the
+/// same `sample_rate` and `channels`.
+#[unsafe(no_mangle)]
+pub extern "C" fn mt_engine_create(
+    sample_rate: u32,
+    channels: u16,
+    handle_out: *mut *mut MtEngineHandle,
+) -> MtFfiStatus {
+    guard(|| unsafe {
+        if handle_out.is_null() {
+            return MtFfiStatus::MtFfiErrorNull;
+        }
+        if sample_rate == 0 || channels == 0 {
+            return MtFfiStatus::MtFfiErrorInvalidArg;
+        }
+
+        match EngineHandle::new(sample_rate, channels) {
+            Ok(inner) => {
+                let boxed = Box::new(inner);
+                let raw = Box::into_raw(boxed).cast::<MtEngineHandle>();
+                *handle_out = raw;
+                MtFfiStatus::MtFfiOk
+            }
+            Err(_) => MtFfiStatus::MtFfiErrorEngine,
+        }
+    })
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn mt_engine_destroy(handle: *mut MtEngineHandle) -> MtFfiStatus {
     guard(|| unsafe {
         if handle.is_null() {
             return MtFfiStatus::MtFfiErrorNull;
@@ -64,12 +95,12 @@ pub extern "C" fn mt-engine_destroy(handle: *mut MtEngineHandle) -> MtFfiStatus
 
 /// Push interleaved f32 audio into the engine.
 ///
-/// - `sample_rate`: Hz
-/// - `channels`: number of channels
+/// - `sample_rate`: Hz; must match the engine's `RtConfig::sample_rate`
+/// - `channels`: number of channels; must match `RtConfig::channels`
 /// - `frames`: number of frames; total samples = frames * channels
 /// - `data`: pointer to interleaved samples
-#[no_mangle]
-pub extern "C" fn mt-engine_push_audio_f32_interleaved(
+#[unsafe(no_mangle)]
+pub extern "C" fn mt_engine_push_audio_f32_interleaved(
     handle: *mut MtEngineHandle,
     sample_rate: u32,
     channels: u16,
@@ -90,23 +121,27 @@ pub extern "C" fn mt-engine_push_audio_f32_interleaved(
         let sample_count = frames as usize * channels as usize;
         let slice = slice::from_raw_parts(data, sample_count);
 
-        let mut guard = match engine_handle.engine.lock() {
-            Ok(g) => g,
-            Err(_) => return MtFfiStatus::MtFfiErrorEngine,
-        };
+        // RT path: no lock, no allocation.
+        let rt = engine_handle.rt_mut();
+        // The engine does not resample or remix.
+        if sample_rate != rt.sample_rate() || channels != rt.channels() {
+            return MtFfiStatus::MtFfiErrorInvalidArg;
+        }
 
-        match guard.push_audio_f32_interleaved(sample_rate, channels, frames, slice) {
-            Ok(()) => MtFfiStatus::MtFfiOk,
-            Err(_) => MtFfiStatus::MtFfiErrorEngine,
+        if rt.push_audio_block(rt.position(), slice) {
+            MtFfiStatus::MtFfiOk
+        } else {
+            MtFfiStatus::MtFfiErrorQueueFull
         }
     })
 }
 
 /// Push MIDI events into the engine.
 ///
-/// `events` points to `count` mt-midi_event structures.
-#[no_mangle]
-pub extern "C" fn mt-engine_push_midi(
+/// `events` points to `count` mt_midi_event structures, each timed by its
+/// `block_offset` into the audio block pushed next.
+#[unsafe(no_mangle)]
+pub extern "C" fn mt_engine_push_midi(
     handle: *mut MtEngineHandle,
     events: *const MtMidiEvent,
     count: u32,
@@ -123,28 +158,127 @@ pub extern "C" fn mt-engine_push_midi(
 
         let slice = slice::from_raw_parts(events, count as usize);
 
-        let mut midi = alloc::vec::Vec::with_capacity(slice.len());
+        // RT path: offsets are relative to the next audio block.
+        let rt = engine_handle.rt_mut();
+        let block_start = rt.position();
+        let mut queued = 0usize;
         for e in slice {
-            midi.push(MidiEvent::from(*e));
+            if rt.push_midi_event(e.to_timed(block_start)) {
+                queued += 1;
+            }
         }
 
-        let mut guard = match engine_handle.engine.lock() {
+        if queued == slice.len() {
+            MtFfiStatus::MtFfiOk
+        } else {
+            MtFfiStatus::MtFfiErrorQueueFull
+        }
+    })
+}
+
+/// Push MIDI 2.0 Universal MIDI Packets into the engine.
+///
+/// `words` points to `count` 32-bit UMP words, all timed at `block_offset`
+/// into the audio block pushed next. A trailing partial packet is ignored.
+/// Notes reach the pipeline's `note_entry` with per-note expression intact.
+#[unsafe(no_mangle)]
+pub extern "C" fn mt_engine_push_ump(
+    handle: *mut MtEngineHandle,
+    words: *const u32,
+    count: u32,
+    block_offset: u32,
+) -> MtFfiStatus {
+    guard(|| unsafe {
+        if handle.is_null() {
+            return MtFfiStatus::MtFfiErrorNull;
+        }
+        if words.is_null() {
+            return MtFfiStatus::MtFfiErrorInvalidArg;
+        }
+
+        let engine_handle = match from_raw_handle(handle) {
+            Some(h) => h,
+            None => return MtFfiStatus::MtFfiErrorNull,
+        };
+
+        let slice = slice::from_raw_parts(words, count as usize);
+
+        // RT path: queued as raw words, no lock, no allocation.
+        let rt = engine_handle.rt_mut();
+        let block_start = rt.position();
+        if rt.push_ump(block_start, block_offset, slice) {
+            MtFfiStatus::MtFfiOk
+        } else {
+            MtFfiStatus::MtFfiErrorQueueFull
+        }
+    })
+}
+
+/// Drain the RT queues and run analysis. Call from a non-RT thread.
+///
+/// Sets `out_new_events` (optional) to the number of events produced.
+#[unsafe(no_mangle)]
+pub extern "C" fn mt_engine_process(
+    handle: *mut MtEngineHandle,
+    out_new_events: *mut u32,
+) -> MtFfiStatus {
+    guard(|| unsafe {
+        let engine_handle = match from_raw_handle(handle) {
+            Some(h) => h,
+            None => return MtFfiStatus::MtFfiErrorNull,
+        };
+
+        let mut guard = match engine_handle.session.lock() {
             Ok(g) => g,
             Err(_) => return MtFfiStatus::MtFfiErrorEngine,
         };
 
-        match guard.push_midi_events(&midi) {
-            Ok(()) => MtFfiStatus::MtFfiOk,
+        match guard.pump() {
+            Ok(n) => {
+                if !out_new_events.is_null() {
+                    *out_new_events = n as u32;
+                }
+                MtFfiStatus::MtFfiOk
+            }
             Err(_) => MtFfiStatus::MtFfiErrorEngine,
         }
     })
 }
 
+/// Audio frames and MIDI events dropped by the RT side so far.
+#[unsafe(no_mangle)]
+pub extern "C" fn mt_engine_get_drop_counts(
+    handle: *mut MtEngineHandle,
+    out_audio_frames: *mut u64,
+    out_midi_events: *mut u64,
+) -> MtFfiStatus {
+    guard(|| unsafe {
+        if out_audio_frames.is_null() || out_midi_events.is_null() {
+            return MtFfiStatus::MtFfiErrorNull;
+        }
+
+        let engine_handle = match from_raw_handle(handle) {
+            Some(h) => h,
+            None => return MtFfiStatus::MtFfiErrorNull,
+        };
+
+        let guard = match engine_handle.session.lock() {
+            Ok(g) => g,
+            Err(_) => return MtFfiStatus::MtFfiErrorEngine,
+        };
+
+        let drops = guard.drop_counters();
+        *out_audio_frames = drops.audio_frames;
+        *out_midi_events = drops.midi_events;
+        MtFfiStatus::MtFfiOk
+    })
+}
+
 /// Copy analyzed note events into caller-provided buffer.
 ///
 /// Writes at most `buffer_len` entries and sets `out_len` to the number written.
-#[no_mangle]
-pub extern "C" fn mt-engine_get_note_events(
+#[unsafe(no_mangle)]
+pub extern "C" fn mt_engine_get_note_events(
     handle: *mut MtEngineHandle,
     buffer: *mut MtNoteEvent,
     buffer_len: u32,
@@ -160,7 +294,7 @@ pub extern "C" fn mt-engine_get_note_events(
             None => return MtFfiStatus::MtFfiErrorNull,
         };
 
-        let guard = match engine_handle.engine.lock() {
+        let guard = match engine_handle.session.lock() {
             Ok(g) => g,
             Err(_) => return MtFfiStatus::MtFfiErrorEngine,
         };
@@ -178,9 +312,45 @@ pub extern "C" fn mt-engine_get_note_events(
     })
 }
 
+/// Chord-tone / non-chord-tone roles of melody notes.
+#[unsafe(no_mangle)]
+pub extern "C" fn mt_engine_get_note_roles(
+    handle: *mut MtEngineHandle,
+    buffer: *mut MtNoteRoleEvent,
+    buffer_len: u32,
+    out_len: *mut u32,
+) -> MtFfiStatus {
+    guard(|| unsafe {
+        if handle.is_null() || buffer.is_null() || out_len.is_null() {
+            return MtFfiStatus::MtFfiErrorNull;
+        }
+
+        let engine_handle = match from_raw_handle(handle) {
+            Some(h) => h,
+            None => return MtFfiStatus::MtFfiErrorNull,
+        };
+
+        let guard = match engine_handle.session.lock() {
+            Ok(g) => g,
+            Err(_) => return MtFfiStatus::MtFfiErrorEngine,
+        };
+
+        let events = guard.note_role_events();
+        let max_copy = core::cmp::min(buffer_len as usize, events.len());
+        let out_slice = slice::from_raw_parts_mut(buffer, max_copy);
+
+        for (dst, src) in out_slice.iter_mut().zip(events.iter()) {
+            *dst = MtNoteRoleEvent::from(src);
+        }
+
+        *out_len = max_copy as u32;
+        MtFfiStatus::MtFfiOk
+    })
+}
+
 /// Chord events.
-#[no_mangle]
-pub extern "C" fn mt-engine_get_chord_events(
+#[unsafe(no_mangle)]
+pub extern "C" fn mt_engine_get_chord_events(
     handle: *mut MtEngineHandle,
     buffer: *mut MtChordEvent,
     buffer_len: u32,
@@ -196,7 +366,7 @@ pub extern "C" fn mt-engine_get_chord_events(
             None => return MtFfiStatus::MtFfiErrorNull,
         };
 
-        let guard = match engine_handle.engine.lock() {
+        let guard = match engine_handle.session.lock() {
             Ok(g) => g,
             Err(_) => return MtFfiStatus::MtFfiErrorEngine,
         };
@@ -214,9 +384,81 @@ pub extern "C" fn mt-engine_get_chord_events(
     })
 }
 
+/// Chord probability frames (top-N candidates per analysis frame).
+#[unsafe(no_mangle)]
+pub extern "C" fn mt_engine_get_chord_probabilities(
+    handle: *mut MtEngineHandle,
+    buffer: *mut MtChordProbabilityFrame,
+    buffer_len: u32,
+    out_len: *mut u32,
+) -> MtFfiStatus {
+    guard(|| unsafe {
+        if handle.is_null() || buffer.is_null() || out_len.is_null() {
+            return MtFfiStatus::MtFfiErrorNull;
+        }
+
+        let engine_handle = match from_raw_handle(handle) {
+            Some(h) => h,
+            None => return MtFfiStatus::MtFfiErrorNull,
+        };
+
+        let guard = match engine_handle.session.lock() {
+            Ok(g) => g,
+            Err(_) => return MtFfiStatus::MtFfiErrorEngine,
+        };
+
+        let frames = guard.chord_probability_frames();
+        let max_copy = core::cmp::min(buffer_len as usize, frames.len());
+        let out_slice = slice::from_raw_parts_mut(buffer, max_copy);
+
+        for (dst, src) in out_slice.iter_mut().zip(frames.iter()) {
+            *dst = MtChordProbabilityFrame::from(src);
+        }
+
+        *out_len = max_copy as u32;
+        MtFfiStatus::MtFfiOk
+    })
+}
+
+/// Cadence events (harmonic phrase endings).
+#[unsafe(no_mangle)]
+pub extern "C" fn mt_engine_get_cadence_events(
+    handle: *mut MtEngineHandle,
+    buffer: *mut MtCadenceEvent,
+    buffer_len: u32,
+    out_len: *mut u32,
+) -> MtFfiStatus {
+    guard(|| unsafe {
+        if handle.is_null() || buffer.is_null() || out_len.is_null() {
+            return MtFfiStatus::MtFfiErrorNull;
+        }
+
+        let engine_handle = match from_raw_handle(handle) {
+            Some(h) => h,
+            None => return MtFfiStatus::MtFfiErrorNull,
+        };
+
+        let guard = match engine_handle.session.lock() {
+            Ok(g) => g,
+            Err(_) => return MtFfiStatus::MtFfiErrorEngine,
+        };
+
+        let events = guard.cadence_events();
+        let max_copy = core::cmp::min(buffer_len as usize, events.len());
+        let out_slice = slice::from_raw_parts_mut(buffer, max_copy);
+
+        for (dst, src) in out_slice.iter_mut().zip(events.iter()) {
+            *dst = MtCadenceEvent::from(src);
+        }
+
+        *out_len = max_copy as u32;
+        MtFfiStatus::MtFfiOk
+    })
+}
+
 /// Key events.
-#[no_mangle]
-pub extern "C" fn mt-engine_get_key_events(
+#[unsafe(no_mangle)]
+pub extern "C" fn mt_engine_get_key_events(
     handle: *mut MtEngineHandle,
     buffer: *mut MtKeyEvent,
     buffer_len: u32,
@@ -232,7 +474,7 @@ pub extern "C" fn mt-engine_get_key_events(
             None => return MtFfiStatus::MtFfiErrorNull,
         };
 
-        let guard = match engine_handle.engine.lock() {
+        let guard = match engine_handle.session.lock() {
             Ok(g) => g,
             Err(_) => return MtFfiStatus::MtFfiErrorEngine,
         };
@@ -251,8 +493,8 @@ pub extern "C" fn mt-engine_get_key_events(
 }
 
 /// Segment events.
-#[no_mangle]
-pub extern "C" fn mt-engine_get_segment_events(
+#[unsafe(no_mangle)]
+pub extern "C" fn mt_engine_get_segment_events(
     handle: *mut MtEngineHandle,
     buffer: *mut MtSegmentEvent,
     buffer_len: u32,
@@ -268,7 +510,7 @@ pub extern "C" fn mt-engine_get_segment_events(
             None => return MtFfiStatus::MtFfiErrorNull,
         };
 
-        let guard = match engine_handle.engine.lock() {
+        let guard = match engine_handle.session.lock() {
             Ok(g) => g,
             Err(_) => return MtFfiStatus::MtFfiErrorEngine,
         };
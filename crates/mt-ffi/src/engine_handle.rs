@@ -1,34 +1,62 @@
-//! Internal wrapper around mt-engine's Engine.
+//! Internal wrapper around mt-engine's `EngineRt` / `EngineSession` pair.
 //!
-//! Not exposed directly via C headers; C only sees `mt-engine_handle` opaque.
+//! Not exposed directly via C headers; C only sees `mt_engine_handle` opaque.
 
+use std::cell::UnsafeCell;
 use std::sync::Mutex;
 
 use crate::types::MtEngineHandle;
-use mt_engine::{Engine, EngineConfig};
+use mt_engine::{EngineBuilder, EngineConfig, EngineRt, EngineSession, NodeRegistry};
 
 /// Concrete Rust-side handle layout.
 ///
-/// We rely on a Mutex for thread-safe use from hosts.
-/// FFI never exposes this type directly; only as `*mut mt-engine_handle`.
+/// The RT half is reached without locking (one audio thread at a time);
+/// the session half sits behind a Mutex for the non-RT callers.
+/// FFI never exposes this type directly; only as `*mut mt_engine_handle`.
 pub struct EngineHandle {
-    pub engine: Mutex<Engine>,
+    rt: UnsafeCell<EngineRt>,
+    pub session: Mutex<EngineSession>,
 }
 
+// SAFETY: `rt` is only accessed through `rt_mut`, whose contract limits it
+// to a single (audio) thread at a time; `EngineRt` itself is `Send`.
+unsafe impl Sync for EngineHandle {}
+
 impl EngineHandle {
+    /// Engine running `EngineConfig::offline_default`, so chord, cadence
+    /// and chord probability getters have a node feeding them.
     pub fn new_default() -> Result<Self, mt_engine::EngineError> {
-        let cfg = EngineConfig::default();
-        let engine = Engine::new(cfg)?;
-        Ok(Self { engine: Mutex::new(engine) })
+        Self::new_with_config(EngineConfig::offline_default())
+    }
+
+    /// Engine running `EngineConfig::offline_default` at the given audio
+    /// format instead of the 44.1 kHz stereo default.
+    pub fn new(sample_rate: u32, channels: u16) -> Result<Self, mt_engine::EngineError> {
+        let mut cfg = EngineConfig::offline_default();
+        cfg.rt.sample_rate = sample_rate;
+        cfg.rt.channels = channels;
+        Self::new_with_config(cfg)
     }
 
     pub fn new_with_config(cfg: EngineConfig) -> Result<Self, mt_engine::EngineError> {
-        let engine = Engine::new(cfg)?;
-        Ok(Self { engine: Mutex::new(engine) })
+        let mut registry = NodeRegistry::new();
+        mt_engine::register_builtin_nodes(&mut registry);
+        let (rt, session) = EngineBuilder::new(&cfg, &registry).build_realtime()?;
+        Ok(Self { rt: UnsafeCell::new(rt), session: Mutex::new(session) })
+    }
+
+    /// The RT half.
+    ///
+    /// # Safety
+    /// Only one thread (the host's audio thread) may call RT functions on a
+    /// handle at a time.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn rt_mut(&self) -> &mut EngineRt {
+        unsafe { &mut *self.rt.get() }
     }
 }
 
-/// Cast `*mut mt-engine_handle` back to `&EngineHandle`.
+/// Cast `*mut mt_engine_handle` back to `&EngineHandle`.
 ///
 /// # Safety
 /// Pointer must originate from `Box<EngineHandle>` created in this crate.
@@ -36,15 +64,15 @@ pub unsafe fn from_raw_handle<'a>(ptr: *mut MtEngineHandle) -> Option<&'a Engine
     if ptr.is_null() {
         None
     } else {
-        // mt-engine_handle is a ZST marker; EngineHandle is stored behind it.
-        // Layout: we always create Box<EngineHandle> and cast to *mut mt-engine_handle.
+        // mt_engine_handle is a ZST marker; EngineHandle is stored behind it.
+        // Layout: we always create Box<EngineHandle> and cast to *mut mt_engine_handle.
         let handle = unsafe { &*ptr.cast::<EngineHandle>() };
 
         Some(handle)
     }
 }
 
-/// Cast `*mut mt-engine_handle` back to `Box<EngineHandle>` for drop.
+/// Cast `*mut mt_engine_handle` back to `Box<EngineHandle>` for drop.
 ///
 /// # Safety
 /// Same as `from_raw_handle`, and must be called at most once per handle.
@@ -18,6 +18,8 @@ pub enum MtFfiStatus {
     MtFfiErrorEngine = 3,
     /// A Rust panic was caught inside the FFI boundary.
     MtFfiErrorPanic = 4,
+    /// A real-time queue was full; the data was dropped and counted.
+    MtFfiErrorQueueFull = 5,
 }
 
 impl MtFfiStatus {
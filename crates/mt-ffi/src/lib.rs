@@ -4,10 +4,12 @@
 //!
 //! Design:
 //! - Opaque engine handle managed via create/destroy calls.
-//! - Host pushes audio and MIDI into the engine.
-//! - Host pulls analyzed events into caller-owned buffers.
+//! - Host pushes audio and MIDI from the audio thread (lock-free, no
+//!   allocation; full queues drop and count).
+//! - A non-RT thread calls `mt_engine_process`, then pulls analyzed events
+//!   into caller-owned buffers.
 //! - No panics cross FFI; all calls wrapped in `catch_unwind`.
-//! - All functions return `mt-ffi_status` for predictable error handling.
+//! - All functions return `mt_ffi_status` for predictable error handling.
 //!
 //! This crate defines the ABI contract for mt-engine.
 
@@ -29,7 +31,7 @@ mod api;
 pub use crate::{
     error::MtFfiStatus,
     types::{
-        MtChordEvent, MtKeyEvent, MtMidiEvent, MtNoteEvent, MtSegmentEvent,
-        mt-ABI_VERSION,
+        MtCadenceEvent, MtChordCandidate, MtChordEvent, MtChordProbabilityFrame, MtKeyEvent,
+        MtMidiEvent, MtNoteEvent, MtNoteRoleEvent, MtSegmentEvent, MT_ABI_VERSION,
     },
 };
@@ -5,29 +5,36 @@
 use core::ffi::c_uchar;
 
 use mt_core::{
-    events::{ChordEvent, KeyEvent, NoteEvent, SegmentEvent, SegmentKind},
-    midi::{MidiChannel, MidiEvent, MidiEventKind},
-    pitch::MidiNote,
+    events::{
+        CadenceEvent, CadenceKind, ChordCandidate, ChordEvent, ChordProbabilityFrame, ChordToneDegree,
+        KeyEvent, MAX_CHORD_CANDIDATES, NonChordToneKind, NoteEvent, NoteRole, NoteRoleEvent,
+        SegmentEvent, SegmentKind, SuspensionKind,
+    },
+    midi::{MidiChannel, MidiEvent, MidiEventKind, TimedMidiEvent},
     time::SampleTime,
 };
 
-pub use crate::version::mt-ABI_VERSION;
+pub use crate::version::MT_ABI_VERSION;
 
 /// Opaque handle to an engine instance (owned by Rust).
 ///
 /// In C:
 /// ```c
-/// typedef struct mt-engine_handle mt-engine_handle;
+/// typedef struct mt_engine_handle mt_engine_handle;
 /// ```
 #[repr(C)]
 pub struct MtEngineHandle {
     _private: [u8; 0],
 }
 
-/// C mirror of `MidiEvent`.
+/// C mirror of `TimedMidiEvent`.
+///
+/// `block_offset` is the sample offset into the next audio block; the
+/// absolute position is derived from the engine's block position on push.
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
 pub struct MtMidiEvent {
+    pub block_offset: u32,
     pub channel: c_uchar,
     pub kind: c_uchar,
     pub data1: c_uchar,
@@ -43,6 +50,8 @@ impl From<MtMidiEvent> for MidiEvent {
             2 => MidiEventKind::ControlChange,
             3 => MidiEventKind::ProgramChange,
             4 => MidiEventKind::PitchBend,
+            5 => MidiEventKind::ChannelPressure,
+            6 => MidiEventKind::PolyPressure,
             _ => MidiEventKind::Other,
         };
         MidiEvent {
@@ -54,6 +63,13 @@ impl From<MtMidiEvent> for MidiEvent {
     }
 }
 
+impl MtMidiEvent {
+    /// Timed event in the block starting at `block_start`.
+    pub fn to_timed(self, block_start: SampleTime) -> TimedMidiEvent {
+        TimedMidiEvent::new(block_start, self.block_offset, MidiEvent::from(self))
+    }
+}
+
 /// C mirror of `NoteEvent` (flattened).
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
@@ -79,6 +95,75 @@ impl From<&NoteEvent> for MtNoteEvent {
     }
 }
 
+/// C mirror of `NoteRoleEvent`.
+///
+/// `role`: 0 = chord tone, 1 = non-chord tone, 2 = no chord.
+/// `degree` (chord tones): 0 = root, 1 = third, 2 = fifth, 3 = seventh,
+/// 4 = tension.
+/// `nct_kind` (non-chord tones): 0 = passing, 1 = neighbor, 2 = suspension,
+/// 3 = appoggiatura, 4 = escape tone, 5 = anticipation, 6 = pedal,
+/// 7 = unclassified.
+/// `suspension` (suspensions): 0 = 4-3, 1 = 7-6, 2 = 9-8, 3 = other.
+/// Fields that do not apply are 255.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct MtNoteRoleEvent {
+    pub note_id: u32,
+    pub position_samples: i64,
+    pub role: c_uchar,
+    pub degree: c_uchar,
+    pub nct_kind: c_uchar,
+    pub suspension: c_uchar,
+}
+
+impl From<&NoteRoleEvent> for MtNoteRoleEvent {
+    fn from(e: &NoteRoleEvent) -> Self {
+        let mut out = Self {
+            note_id: e.note.0,
+            position_samples: e.position.value(),
+            role: 2,
+            degree: 255,
+            nct_kind: 255,
+            suspension: 255,
+        };
+        match e.role {
+            NoteRole::ChordTone(degree) => {
+                out.role = 0;
+                out.degree = match degree {
+                    ChordToneDegree::Root => 0,
+                    ChordToneDegree::Third => 1,
+                    ChordToneDegree::Fifth => 2,
+                    ChordToneDegree::Seventh => 3,
+                    ChordToneDegree::Tension => 4,
+                };
+            }
+            NoteRole::NonChordTone(kind) => {
+                out.role = 1;
+                out.nct_kind = match kind {
+                    NonChordToneKind::Passing => 0,
+                    NonChordToneKind::Neighbor => 1,
+                    NonChordToneKind::Suspension(s) => {
+                        out.suspension = match s {
+                            SuspensionKind::FourThree => 0,
+                            SuspensionKind::SevenSix => 1,
+                            SuspensionKind::NineEight => 2,
+                            SuspensionKind::Other => 3,
+                        };
+                        2
+                    }
+                    NonChordToneKind::Appoggiatura => 3,
+                    NonChordToneKind::EscapeTone => 4,
+                    NonChordToneKind::Anticipation => 5,
+                    NonChordToneKind::Pedal => 6,
+                    NonChordToneKind::Unclassified => 7,
+                };
+            }
+            NoteRole::NoChord => {}
+        }
+        out
+    }
+}
+
 /// C mirror of `ChordEvent`.
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
@@ -104,6 +189,89 @@ impl From<&ChordEvent> for MtChordEvent {
     }
 }
 
+/// C mirror of `ChordCandidate`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MtChordCandidate {
+    pub root_pc: c_uchar,
+    pub kind_id: c_uchar,
+    pub bass_pc: c_uchar, // 255 = none
+    pub probability_x1000: u16,
+}
+
+impl From<&ChordCandidate> for MtChordCandidate {
+    fn from(c: &ChordCandidate) -> Self {
+        Self {
+            root_pc: c.chord.root.as_u8(),
+            kind_id: c.chord.kind as u8,
+            bass_pc: c.chord.bass.map(|pc| pc.as_u8()).unwrap_or(u8::MAX),
+            probability_x1000: c.probability_x1000,
+        }
+    }
+}
+
+/// C mirror of `ChordProbabilityFrame`.
+///
+/// Only the first `count` entries of `candidates` are valid.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct MtChordProbabilityFrame {
+    pub onset_samples: i64,
+    pub offset_samples: i64,
+    pub count: u32,
+    pub candidates: [MtChordCandidate; MAX_CHORD_CANDIDATES],
+}
+
+impl From<&ChordProbabilityFrame> for MtChordProbabilityFrame {
+    fn from(f: &ChordProbabilityFrame) -> Self {
+        let mut candidates = [MtChordCandidate::default(); MAX_CHORD_CANDIDATES];
+        for (dst, src) in candidates.iter_mut().zip(f.candidates()) {
+            *dst = MtChordCandidate::from(src);
+        }
+        Self {
+            onset_samples: f.onset.value(),
+            offset_samples: f.offset.value(),
+            count: f.candidates().len() as u32,
+            candidates,
+        }
+    }
+}
+
+/// C mirror of `CadenceEvent`.
+///
+/// `kind`: 0 = PAC, 1 = IAC, 2 = half, 3 = plagal, 4 = deceptive,
+/// 5 = Phrygian half.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct MtCadenceEvent {
+    pub approach_samples: i64,
+    pub position_samples: i64,
+    pub kind: c_uchar,
+    pub tonic_pc: c_uchar,
+    pub is_minor: c_uchar,
+    pub confidence_x1000: u16,
+}
+
+impl From<&CadenceEvent> for MtCadenceEvent {
+    fn from(e: &CadenceEvent) -> Self {
+        Self {
+            approach_samples: e.approach.value(),
+            position_samples: e.position.value(),
+            kind: match e.kind {
+                CadenceKind::PerfectAuthentic => 0,
+                CadenceKind::ImperfectAuthentic => 1,
+                CadenceKind::Half => 2,
+                CadenceKind::Plagal => 3,
+                CadenceKind::Deceptive => 4,
+                CadenceKind::PhrygianHalf => 5,
+            },
+            tonic_pc: e.key.tonic().as_u8(),
+            is_minor: u8::from(e.key.mode() == mt_core::key::KeyMode::Minor),
+            confidence_x1000: e.confidence_x1000,
+        }
+    }
+}
+
 /// C mirror of `KeyEvent`.
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
@@ -136,6 +304,8 @@ pub struct MtSegmentEvent {
     pub offset_samples: i64,
     pub kind: c_uchar,
     pub custom_kind_id: c_uchar,
+    pub label_group: c_uchar, // 255 = unlabelled
+    pub label_variant: c_uchar,
     pub confidence_x1000: u16,
 }
 
@@ -156,6 +326,8 @@ impl From<&SegmentEvent> for MtSegmentEvent {
             offset_samples: e.offset.value(),
             kind,
             custom_kind_id: custom,
+            label_group: e.label.map_or(u8::MAX, |l| l.group),
+            label_variant: e.label.map_or(0, |l| l.variant),
             confidence_x1000: e.confidence_x1000,
         }
     }
@@ -0,0 +1,434 @@
+//! Reference annotations shared by the `.lab`, JAMS and CSV formats, and
+//! the label syntaxes they use.
+//!
+//! Rules:
+//! - Times are seconds in the files and `SampleTime` here; reading rounds
+//!   to the nearest sample, writing prints microseconds, which round-trips
+//!   at any sample rate up to a million samples per second.
+//! - Chords use Harte syntax (`C:min7/b3`, `N`, `X`). Labels whose notes
+//!   match a chord kind exactly map to it; other shorthands map to the
+//!   nearest kind (ninths and larger to their seventh chord, `minmaj7` to
+//!   minor). `N`, `X` and single notes (`C:1`) produce no event.
+//! - Keys read as `C`, `Key C#:minor`, `Eb minor` or `A:min`; modes other
+//!   than major and minor are skipped.
+//! - Segment labels read as written by the SMF writer (`Chorus B'`,
+//!   `Section 3`), plus the usual dataset names (`refrain`, `coda`, ...) and
+//!   bare SALAMI letters (`A`, `B'`). `silence` and `end` are skipped.
+//! - A key or tempo has no end of its own: files give it the next event's
+//!   position, and the last one the end of the annotations.
+//! - Beats are bare times; beat numbers within the bar are not kept.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use mt_core::chord::{BassPolicy, Chord};
+use mt_core::chord_kind::{CHORD_KINDS, ChordKindId};
+use mt_core::events::{ChordEvent, KeyEvent, SectionLabel, SegmentEvent, SegmentKind, TempoEvent};
+use mt_core::key::{Key, KeyMode};
+use mt_core::pitch::PitchClass;
+use mt_core::time::SampleTime;
+
+use crate::smf_writer::marker_text;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnnotationError {
+    /// Line (1-based) of a `.lab` or CSV file that cannot be read.
+    InvalidLine(usize),
+    /// JSON that is not well formed, at this byte offset.
+    MalformedJson(usize),
+    /// JSON without a JAMS `annotations` array.
+    NotJams,
+    /// JAMS observation that cannot be read.
+    InvalidObservation { annotation: usize, index: usize },
+    /// Chord label that is not valid Harte syntax.
+    InvalidChord,
+}
+
+impl fmt::Display for AnnotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLine(line) => write!(f, "invalid annotation on line {line}"),
+            Self::MalformedJson(at) => write!(f, "malformed JSON at byte {at}"),
+            Self::NotJams => write!(f, "not a JAMS file"),
+            Self::InvalidObservation { annotation, index } => {
+                write!(f, "invalid observation {index} in annotation {annotation}")
+            }
+            Self::InvalidChord => write!(f, "invalid Harte chord label"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AnnotationError {}
+
+/// Time-stamped reference annotations of one recording.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Annotations {
+    pub chords: Vec<ChordEvent>,
+    pub keys: Vec<KeyEvent>,
+    pub segments: Vec<SegmentEvent>,
+    pub tempos: Vec<TempoEvent>,
+    pub beats: Vec<SampleTime>,
+}
+
+impl Annotations {
+    /// Latest position covered: the last chord or segment offset, or the
+    /// last key, tempo or beat position.
+    pub fn end(&self) -> SampleTime {
+        let offsets =
+            self.chords.iter().map(|c| c.offset).chain(self.segments.iter().map(|s| s.offset));
+        let positions =
+            self.keys.iter().map(|k| k.position).chain(self.tempos.iter().map(|t| t.position));
+        offsets.chain(positions).chain(self.beats.iter().copied()).max().unwrap_or(SampleTime::ZERO)
+    }
+
+    /// Add the events of `other`, e.g. a chord and a key `.lab` file of
+    /// the same recording, keeping every list in time order.
+    pub fn merge(&mut self, other: Self) {
+        self.chords.extend(other.chords);
+        self.keys.extend(other.keys);
+        self.segments.extend(other.segments);
+        self.tempos.extend(other.tempos);
+        self.beats.extend(other.beats);
+        self.sort();
+    }
+
+    /// Sort every list by time.
+    pub(crate) fn sort(&mut self) {
+        self.chords.sort_by_key(|c| c.onset);
+        self.keys.sort_by_key(|k| k.position);
+        self.segments.sort_by_key(|s| s.onset);
+        self.tempos.sort_by_key(|t| t.position);
+        self.beats.sort_unstable();
+    }
+
+    /// Keys with their end: the next key, or `end()` for the last one.
+    pub(crate) fn key_spans(&self) -> Vec<(SampleTime, SampleTime, Key, u16)> {
+        let end = self.end();
+        let next = self.keys.iter().skip(1).map(|k| k.position).chain([end]);
+        self.keys
+            .iter()
+            .zip(next)
+            .map(|(k, stop)| (k.position, stop.max(k.position), k.key, k.confidence_x1000))
+            .collect()
+    }
+
+    /// Tempos with their end: the next tempo, or `end()` for the last one.
+    pub(crate) fn tempo_spans(&self) -> Vec<(SampleTime, SampleTime, u32)> {
+        let end = self.end();
+        let next = self.tempos.iter().skip(1).map(|t| t.position).chain([end]);
+        self.tempos
+            .iter()
+            .zip(next)
+            .map(|(t, stop)| (t.position, stop.max(t.position), t.bpm_x1000))
+            .collect()
+    }
+}
+
+/// Seconds (decimal, optionally with an exponent) → nearest sample.
+pub fn seconds_to_samples(text: &str, sample_rate: u32) -> Option<SampleTime> {
+    let text = text.trim();
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+        Some((m, e)) => (m, e.parse::<i32>().ok()?),
+        None => (text, 0),
+    };
+    let (whole, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if whole.is_empty() && frac.is_empty() {
+        return None;
+    }
+    // Digits past the 18th significant one are below any sample's length.
+    let mut digits: i128 = 0;
+    let mut scale = exponent;
+    let mut significant = 0;
+    for (i, c) in whole.chars().chain(frac.chars()).enumerate() {
+        let d = i128::from(c.to_digit(10)?);
+        let fraction = i >= whole.len();
+        if significant < 18 {
+            digits = digits * 10 + d;
+            significant += usize::from(digits != 0);
+            scale -= i32::from(fraction);
+        } else if !fraction {
+            scale += 1;
+        }
+    }
+    let num = digits * i128::from(sample_rate);
+    let samples = if scale >= 0 {
+        num.checked_mul(10i128.checked_pow(scale.unsigned_abs())?)?
+    } else if scale < -30 {
+        0
+    } else {
+        let den = 10i128.pow(scale.unsigned_abs());
+        (num + den / 2) / den
+    };
+    let samples = i64::try_from(samples).ok()?;
+    Some(SampleTime::new(if negative { -samples } else { samples }))
+}
+
+/// Samples → seconds with six decimals.
+pub fn samples_to_seconds(position: SampleTime, sample_rate: u32) -> String {
+    let rate = i128::from(sample_rate.max(1));
+    let micros = i128::from(position.value()) * 1_000_000;
+    let micros = if micros >= 0 { (micros + rate / 2) / rate } else { (micros - rate / 2) / rate };
+    let sign = if micros < 0 { "-" } else { "" };
+    let micros = micros.unsigned_abs();
+    format!("{sign}{}.{:06}", micros / 1_000_000, micros % 1_000_000)
+}
+
+/// Confidence from a 0..1 decimal.
+pub(crate) fn confidence_from_unit(text: &str) -> Option<u16> {
+    let x1000 = crate::score::parse_x1000(text)?;
+    Some(x1000.min(1000) as u16)
+}
+
+/// Confidence as a 0..1 decimal with three places.
+pub(crate) fn confidence_to_unit(confidence_x1000: u16) -> String {
+    let c = confidence_x1000.min(1000);
+    format!("{}.{:03}", c / 1000, c % 1000)
+}
+
+/// Thousandths as a short decimal: `120000` → `120`, `72500` → `72.5`.
+pub(crate) fn format_x1000(value: u32) -> String {
+    let mut text = format!("{}.{:03}", value / 1000, value % 1000);
+    while text.ends_with('0') {
+        text.pop();
+    }
+    if text.ends_with('.') {
+        text.pop();
+    }
+    text
+}
+
+/// Parse a Harte chord label; `Ok(None)` for `N`, `X` and labels with no
+/// matching chord kind.
+pub fn parse_harte(label: &str) -> Result<Option<Chord>, AnnotationError> {
+    let label = label.trim();
+    if label == "N" || label == "X" {
+        return Ok(None);
+    }
+    let (main, bass) = match label.split_once('/') {
+        Some((main, bass)) => (main, Some(bass)),
+        None => (label, None),
+    };
+    let split = main.find([':', '(']).unwrap_or(main.len());
+    let (root_text, quality) = main.split_at(split);
+    let root = parse_pitch_class(root_text).ok_or(AnnotationError::InvalidChord)?;
+    let quality = quality.strip_prefix(':').unwrap_or(quality);
+
+    let (shorthand, extensions) = match quality.split_once('(') {
+        Some((short, rest)) => {
+            let list = rest.strip_suffix(')').ok_or(AnnotationError::InvalidChord)?;
+            (short, Some(list))
+        }
+        None => (quality, None),
+    };
+    // A bare root is a major triad; a bare list has no implied notes.
+    let shorthand = if shorthand.is_empty() && extensions.is_none() { "maj" } else { shorthand };
+    let (mut mask, nearest) = if shorthand.is_empty() {
+        (0u16, None)
+    } else {
+        shorthand_notes(shorthand).ok_or(AnnotationError::InvalidChord)?
+    };
+    for item in extensions.into_iter().flat_map(|list| list.split(',')) {
+        let item = item.trim();
+        let (omit, degree) = match item.strip_prefix('*') {
+            Some(degree) => (true, degree),
+            None => (false, item),
+        };
+        let bit = 1u16 << degree_semitone(degree).ok_or(AnnotationError::InvalidChord)?;
+        mask = if omit { mask & !bit } else { mask | bit };
+    }
+    let bass = match bass {
+        Some(degree) => {
+            let semitones = degree_semitone(degree).ok_or(AnnotationError::InvalidChord)?;
+            Some(root.transpose(semitones))
+        }
+        None => None,
+    };
+
+    let exact = CHORD_KINDS.iter().find(|k| interval_mask(k.intervals) == mask).map(|k| k.id);
+    let Some(kind) = exact.or(nearest) else { return Ok(None) };
+    Ok(Chord::with_bass_policy(root, kind, bass, BassPolicy::Any).ok())
+}
+
+/// Harte label for `chord`.
+pub fn format_harte(chord: &Chord) -> String {
+    let shorthand = match chord.kind {
+        ChordKindId::Maj => "maj",
+        ChordKindId::Min => "min",
+        ChordKindId::Dim => "dim",
+        ChordKindId::Aug => "aug",
+        ChordKindId::Sus2 => "sus2",
+        ChordKindId::Sus4 => "sus4",
+        ChordKindId::Power5 => "(1,5)",
+        ChordKindId::Maj7 => "maj7",
+        ChordKindId::Min7 => "min7",
+        ChordKindId::Dom7 => "7",
+        ChordKindId::HalfDim7 => "hdim7",
+        ChordKindId::Dim7 => "dim7",
+        ChordKindId::Maj6 => "maj6",
+        ChordKindId::Min6 => "min6",
+        ChordKindId::SixNine => "maj6(9)",
+    };
+    let mut label = format!("{}:{shorthand}", pitch_name(chord.root));
+    if let Some(bass) = chord.bass.filter(|b| *b != chord.root) {
+        let semitones = (bass.as_u8() + 12 - chord.root.as_u8()) % 12;
+        label.push('/');
+        label.push_str(bass_degree(chord.kind, semitones));
+    }
+    label
+}
+
+/// Parse a key label; `None` for `N`, `Silence` and modal keys.
+pub fn parse_key_label(label: &str) -> Option<Key> {
+    let label = label.trim();
+    let label = label.strip_prefix("Key ").map_or(label, str::trim_start);
+    let split = label.find([':', ' ']).unwrap_or(label.len());
+    let (tonic, mode) = label.split_at(split);
+    let tonic = parse_pitch_class(tonic)?;
+    let mode = match mode.trim_start_matches([':', ' ']).to_ascii_lowercase().as_str() {
+        "" | "major" | "maj" | "ionian" => KeyMode::Major,
+        "minor" | "min" | "aeolian" => KeyMode::Minor,
+        _ => return None,
+    };
+    Some(Key::new(tonic, mode))
+}
+
+/// `C:major` / `A:minor`, as in the JAMS `key_mode` namespace.
+pub fn format_key(key: Key) -> String {
+    let mode = match key.mode() {
+        KeyMode::Major => "major",
+        KeyMode::Minor => "minor",
+    };
+    format!("{}:{mode}", pitch_name(key.tonic()))
+}
+
+/// Parse a segment label; `None` for silence and end markers.
+pub fn parse_segment_label(label: &str) -> Option<(SegmentKind, Option<SectionLabel>)> {
+    let mut words = label.split([' ', '_', '(']).filter(|w| !w.is_empty());
+    let first = words.next()?;
+    let name: String = first.chars().take_while(char::is_ascii_alphabetic).collect();
+    let kind = match name.to_ascii_lowercase().as_str() {
+        "silence" | "end" => return None,
+        "intro" | "introduction" => SegmentKind::Intro,
+        "verse" => SegmentKind::Verse,
+        "chorus" | "refrain" => SegmentKind::Chorus,
+        "bridge" => SegmentKind::Bridge,
+        "solo" | "instrumental" => SegmentKind::Solo,
+        "outro" | "coda" | "ending" | "fade" => SegmentKind::Outro,
+        "section" => {
+            let id = words.next().and_then(|w| w.parse().ok()).unwrap_or(0);
+            return Some((SegmentKind::Other(id), None));
+        }
+        _ => return Some((SegmentKind::Other(0), parse_section_label(first))),
+    };
+    Some((kind, words.next().and_then(parse_section_label)))
+}
+
+/// Text for a segment, as in SMF markers: `Chorus B'`, `Section 3`.
+pub fn format_segment(segment: &SegmentEvent) -> String {
+    marker_text(segment)
+}
+
+/// `A`, `B'`, `S27''`.
+fn parse_section_label(text: &str) -> Option<SectionLabel> {
+    let body = text.trim_end_matches('\'');
+    let variant = u8::try_from(text.len() - body.len()).ok()?;
+    let group = match body.as_bytes() {
+        [b @ b'A'..=b'Z'] => b - b'A',
+        [b'S', digits @ ..] if !digits.is_empty() => body[1..].parse().ok()?,
+        _ => return None,
+    };
+    Some(SectionLabel { group, variant })
+}
+
+/// Letter plus `#`/`b` accidentals, e.g. `Bb`, `F#`, `Cbb`.
+fn parse_pitch_class(text: &str) -> Option<PitchClass> {
+    let mut chars = text.chars();
+    let natural = crate::score::step_semitone(chars.next()?)?;
+    let mut alter = 0i8;
+    for c in chars {
+        alter = alter.checked_add(match c {
+            '#' => 1,
+            'b' => -1,
+            _ => return None,
+        })?;
+    }
+    Some(PitchClass::from_unchecked(natural).transpose(alter))
+}
+
+/// Note name, with flats for the black keys except F#.
+pub(crate) fn pitch_name(pc: PitchClass) -> &'static str {
+    const NAMES: [&str; 12] = ["C", "Db", "D", "Eb", "E", "F", "F#", "G", "Ab", "A", "Bb", "B"];
+    NAMES[usize::from(pc.as_u8() % 12)]
+}
+
+/// Semitones above the root of a Harte degree such as `b3`, `#11`, `7`.
+fn degree_semitone(degree: &str) -> Option<i8> {
+    const MAJOR: [i8; 7] = [0, 2, 4, 5, 7, 9, 11];
+    let digits_at = degree.find(|c: char| c.is_ascii_digit())?;
+    let (accidentals, number) = degree.split_at(digits_at);
+    let number: usize = number.parse().ok()?;
+    if number == 0 || number > 13 {
+        return None;
+    }
+    let mut semitones = MAJOR[(number - 1) % 7];
+    for c in accidentals.chars() {
+        semitones = semitones.checked_add(match c {
+            '#' => 1,
+            'b' => -1,
+            _ => return None,
+        })?;
+    }
+    Some(semitones.rem_euclid(12))
+}
+
+fn bass_degree(kind: ChordKindId, semitones: u8) -> &'static str {
+    const DEGREES: [&str; 12] = ["1", "b2", "2", "b3", "3", "4", "b5", "5", "b6", "6", "b7", "7"];
+    match (kind, semitones) {
+        (ChordKindId::Aug, 8) => "#5",
+        (ChordKindId::Dim7, 9) => "bb7",
+        _ => DEGREES[usize::from(semitones % 12)],
+    }
+}
+
+fn interval_mask(intervals: &[u8]) -> u16 {
+    intervals.iter().fold(0, |mask, iv| mask | 1 << (iv % 12))
+}
+
+/// Notes of a Harte shorthand and the chord kind it is closest to.
+fn shorthand_notes(shorthand: &str) -> Option<(u16, Option<ChordKindId>)> {
+    let (intervals, nearest): (&[u8], Option<ChordKindId>) = match shorthand {
+        "maj" => (&[0, 4, 7], Some(ChordKindId::Maj)),
+        "min" => (&[0, 3, 7], Some(ChordKindId::Min)),
+        "dim" => (&[0, 3, 6], Some(ChordKindId::Dim)),
+        "aug" => (&[0, 4, 8], Some(ChordKindId::Aug)),
+        "maj7" => (&[0, 4, 7, 11], Some(ChordKindId::Maj7)),
+        "min7" => (&[0, 3, 7, 10], Some(ChordKindId::Min7)),
+        "7" => (&[0, 4, 7, 10], Some(ChordKindId::Dom7)),
+        "dim7" => (&[0, 3, 6, 9], Some(ChordKindId::Dim7)),
+        "hdim7" => (&[0, 3, 6, 10], Some(ChordKindId::HalfDim7)),
+        "minmaj7" => (&[0, 3, 7, 11], Some(ChordKindId::Min)),
+        "maj6" => (&[0, 4, 7, 9], Some(ChordKindId::Maj6)),
+        "min6" => (&[0, 3, 7, 9], Some(ChordKindId::Min6)),
+        "9" => (&[0, 4, 7, 10, 14], Some(ChordKindId::Dom7)),
+        "maj9" => (&[0, 4, 7, 11, 14], Some(ChordKindId::Maj7)),
+        "min9" => (&[0, 3, 7, 10, 14], Some(ChordKindId::Min7)),
+        "11" => (&[0, 4, 7, 10, 14, 17], Some(ChordKindId::Dom7)),
+        "min11" => (&[0, 3, 7, 10, 14, 17], Some(ChordKindId::Min7)),
+        "13" => (&[0, 4, 7, 10, 14, 17, 21], Some(ChordKindId::Dom7)),
+        "maj13" => (&[0, 4, 7, 11, 14, 17, 21], Some(ChordKindId::Maj7)),
+        "min13" => (&[0, 3, 7, 10, 14, 17, 21], Some(ChordKindId::Min7)),
+        "sus2" => (&[0, 2, 7], Some(ChordKindId::Sus2)),
+        "sus4" => (&[0, 5, 7], Some(ChordKindId::Sus4)),
+        "5" => (&[0, 7], Some(ChordKindId::Power5)),
+        "1" => (&[0], None),
+        _ => return None,
+    };
+    Some((interval_mask(intervals), nearest))
+}
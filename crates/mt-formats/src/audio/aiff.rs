@@ -0,0 +1,137 @@
+//! AIFF / AIFC header parsing.
+
+use std::io::{Read, Seek, SeekFrom};
+
+use super::pcm::{PcmStream, SampleEncoding};
+use super::{AudioError, AudioFileFormat, AudioFileInfo, io_error, read_chunk};
+
+struct Common {
+    channels: u16,
+    frames: u32,
+    bits: u16,
+    sample_rate: u32,
+    compression: [u8; 4],
+}
+
+/// Scan all chunks (COMM may follow SSND) and leave `reader` at the first
+/// sample.
+pub(crate) fn open<R: Read + Seek>(
+    mut reader: R,
+) -> Result<(AudioFileInfo, PcmStream<R>), AudioError> {
+    let mut header = [0u8; 12];
+    reader.read_exact(&mut header).map_err(io_error)?;
+    let aifc = match &header[8..12] {
+        b"AIFF" => false,
+        b"AIFC" => true,
+        _ => return Err(malformed("missing AIFF/AIFC id")),
+    };
+
+    let mut common: Option<Common> = None;
+    // (offset of first sample, bytes of sample data)
+    let mut sound: Option<(u64, u64)> = None;
+    loop {
+        let mut chunk = [0u8; 8];
+        if reader.read_exact(&mut chunk).is_err() {
+            break;
+        }
+        let len = u32::from_be_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+        let padded = i64::from(len) + i64::from(len & 1);
+        match &chunk[..4] {
+            b"COMM" => {
+                let body = read_chunk(&mut reader, len)?
+                    .ok_or_else(|| malformed("truncated COMM chunk"))?;
+                common = Some(parse_common(&body, aifc)?);
+            }
+            b"SSND" => {
+                let mut offsets = [0u8; 8];
+                reader.read_exact(&mut offsets).map_err(|_| malformed("truncated SSND chunk"))?;
+                let skip = u32::from_be_bytes([offsets[0], offsets[1], offsets[2], offsets[3]]);
+                let start = reader.stream_position().map_err(io_error)? + u64::from(skip);
+                let data_len = u64::from(len).saturating_sub(8 + u64::from(skip));
+                sound = Some((start, data_len));
+                reader.seek(SeekFrom::Current(padded - 8)).map_err(io_error)?;
+            }
+            _ => {
+                reader.seek(SeekFrom::Current(padded)).map_err(io_error)?;
+            }
+        }
+        if common.is_some() && sound.is_some() {
+            break;
+        }
+    }
+
+    let common = common.ok_or_else(|| malformed("no COMM chunk"))?;
+    let (start, data_len) = sound.unwrap_or((0, 0));
+    let encoding = encoding(&common)?;
+    reader.seek(SeekFrom::Start(start)).map_err(io_error)?;
+    let frame_bytes = encoding.bytes() as u64 * u64::from(common.channels);
+    let data_len = data_len.min(u64::from(common.frames) * frame_bytes);
+    let info = AudioFileInfo {
+        format: if aifc { AudioFileFormat::Aifc } else { AudioFileFormat::Aiff },
+        sample_rate: common.sample_rate,
+        channels: common.channels,
+        bits_per_sample: common.bits,
+        frames: Some(u64::from(common.frames)),
+    };
+    Ok((info, PcmStream::new(reader, encoding, common.channels, data_len)))
+}
+
+fn parse_common(body: &[u8], aifc: bool) -> Result<Common, AudioError> {
+    let min_len = if aifc { 22 } else { 18 };
+    if body.len() < min_len {
+        return Err(malformed("COMM chunk too short"));
+    }
+    let mut rate = [0u8; 10];
+    rate.copy_from_slice(&body[8..18]);
+    Ok(Common {
+        channels: u16::from_be_bytes([body[0], body[1]]),
+        frames: u32::from_be_bytes([body[2], body[3], body[4], body[5]]),
+        bits: u16::from_be_bytes([body[6], body[7]]),
+        sample_rate: extended_to_rate(rate),
+        compression: if aifc { [body[18], body[19], body[20], body[21]] } else { *b"NONE" },
+    })
+}
+
+fn encoding(common: &Common) -> Result<SampleEncoding, AudioError> {
+    let bits = common.bits;
+    let int_bytes = bits.div_ceil(8) as u8;
+    match (&common.compression, bits) {
+        (b"NONE" | b"twos", 1..=32) => {
+            Ok(SampleEncoding::Int { bytes: int_bytes, big_endian: true })
+        }
+        (b"sowt", 16 | 24 | 32) => Ok(SampleEncoding::Int { bytes: int_bytes, big_endian: false }),
+        (b"fl32" | b"FL32", _) => Ok(SampleEncoding::Float { bytes: 4, big_endian: true }),
+        (b"fl64" | b"FL64", _) => Ok(SampleEncoding::Float { bytes: 8, big_endian: true }),
+        (b"NONE" | b"twos" | b"sowt", _) => {
+            Err(AudioError::Unsupported(format!("AIFF with {bits} bits per sample")))
+        }
+        (other, _) => Err(AudioError::Unsupported(format!(
+            "AIFC compression '{}'",
+            String::from_utf8_lossy(other)
+        ))),
+    }
+}
+
+/// 80-bit IEEE 754 extended float to a whole sample rate (rounded).
+fn extended_to_rate(b: [u8; 10]) -> u32 {
+    let exponent = i32::from(u16::from_be_bytes([b[0], b[1]]) & 0x7FFF);
+    let mantissa = u64::from_be_bytes([b[2], b[3], b[4], b[5], b[6], b[7], b[8], b[9]]);
+    if b[0] & 0x80 != 0 || mantissa == 0 {
+        return 0;
+    }
+    // value = mantissa * 2^(exponent - 16383 - 63)
+    let shift = exponent - 16383 - 63;
+    let rate = if shift >= 0 {
+        u64::MAX
+    } else if shift > -64 {
+        let s = (-shift) as u32;
+        (mantissa >> s) + ((mantissa >> (s - 1)) & 1)
+    } else {
+        0
+    };
+    u32::try_from(rate).unwrap_or(u32::MAX)
+}
+
+fn malformed(what: &str) -> AudioError {
+    AudioError::Decode(format!("malformed AIFF file: {what}"))
+}
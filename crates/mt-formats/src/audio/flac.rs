@@ -0,0 +1,574 @@
+//! FLAC stream decoding.
+//!
+//! Decodes frame by frame: constant, verbatim, fixed and LPC subframes,
+//! Rice-coded residuals (including escaped partitions), wasted bits and
+//! the three stereo decorrelation modes. Header CRC-8 and frame CRC-16
+//! are verified. A leading `ID3v2` tag is skipped.
+
+use std::io::Read;
+
+use super::{AudioError, AudioFileFormat, AudioFileInfo};
+
+const METADATA_STREAMINFO: u8 = 0;
+
+/// Stream parameters from STREAMINFO.
+#[derive(Clone, Copy, Debug)]
+struct StreamInfo {
+    sample_rate: u32,
+    channels: u16,
+    bits: u16,
+    total_frames: Option<u64>,
+}
+
+/// How the channels of a frame are coded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ChannelLayout {
+    Independent(u16),
+    LeftSide,
+    SideRight,
+    MidSide,
+}
+
+pub(crate) struct FlacStream<R> {
+    bits: BitReader<R>,
+    info: StreamInfo,
+    /// Frames decoded so far.
+    decoded: u64,
+    /// Interleaved samples of the current frame not yet handed out.
+    pending: Vec<f32>,
+    pending_pos: usize,
+    /// Per-channel scratch for one frame.
+    channels: Vec<Vec<i64>>,
+}
+
+/// Read the stream header and metadata; leave `reader` at the first frame.
+pub(crate) fn open<R: Read>(reader: R) -> Result<(AudioFileInfo, FlacStream<R>), AudioError> {
+    let mut bits = BitReader::new(reader);
+    let mut magic = bits.bytes::<4>()?;
+    if &magic[..3] == b"ID3" {
+        // ID3v2: 6 more header bytes, size is four 7-bit bytes.
+        let rest = bits.bytes::<6>()?;
+        let size = rest[2..6].iter().fold(0u32, |acc, &b| (acc << 7) | u32::from(b & 0x7F));
+        let footer = if rest[1] & 0x10 != 0 { 10 } else { 0 };
+        bits.skip_bytes(u64::from(size) + footer)?;
+        magic = bits.bytes::<4>()?;
+    }
+    if &magic != b"fLaC" {
+        return Err(malformed("missing fLaC marker"));
+    }
+
+    let mut info = None;
+    loop {
+        let header = bits.bits(8)? as u8;
+        let len = bits.bits(24)?;
+        if header & 0x7F == METADATA_STREAMINFO {
+            if len < 34 {
+                return Err(malformed("STREAMINFO too short"));
+            }
+            bits.skip_bytes(10)?; // block and frame size bounds
+            let sample_rate = bits.bits(20)?;
+            let channels = bits.bits(3)? as u16 + 1;
+            let bits_per_sample = bits.bits(5)? as u16 + 1;
+            let total = (u64::from(bits.bits(4)?) << 32) | u64::from(bits.bits(32)?);
+            bits.skip_bytes(u64::from(len) - 18)?; // MD5 and any extension
+            info = Some(StreamInfo {
+                sample_rate,
+                channels,
+                bits: bits_per_sample,
+                total_frames: (total > 0).then_some(total),
+            });
+        } else {
+            bits.skip_bytes(u64::from(len))?;
+        }
+        if header & 0x80 != 0 {
+            break;
+        }
+    }
+    let info = info.ok_or_else(|| malformed("no STREAMINFO block"))?;
+    if info.bits < 4 {
+        return Err(AudioError::Unsupported(format!("FLAC with {} bits per sample", info.bits)));
+    }
+
+    let file_info = AudioFileInfo {
+        format: AudioFileFormat::Flac,
+        sample_rate: info.sample_rate,
+        channels: info.channels,
+        bits_per_sample: info.bits,
+        frames: info.total_frames,
+    };
+    let stream = FlacStream {
+        bits,
+        info,
+        decoded: 0,
+        pending: Vec::new(),
+        pending_pos: 0,
+        channels: vec![Vec::new(); usize::from(info.channels)],
+    };
+    Ok((file_info, stream))
+}
+
+impl<R: Read> FlacStream<R> {
+    /// Append up to `max_frames` frames to `out`; returns the frames read.
+    pub(crate) fn read_frames(
+        &mut self,
+        max_frames: usize,
+        out: &mut Vec<f32>,
+    ) -> Result<usize, AudioError> {
+        let channels = usize::from(self.info.channels);
+        let mut read = 0;
+        while read < max_frames {
+            if self.pending_pos == self.pending.len() && !self.decode_frame()? {
+                break;
+            }
+            let available = (self.pending.len() - self.pending_pos) / channels;
+            let take = available.min(max_frames - read);
+            let end = self.pending_pos + take * channels;
+            out.extend_from_slice(&self.pending[self.pending_pos..end]);
+            self.pending_pos = end;
+            read += take;
+        }
+        Ok(read)
+    }
+
+    /// Decode one frame into `pending`; `false` at the end of the stream.
+    fn decode_frame(&mut self) -> Result<bool, AudioError> {
+        if self.info.total_frames.is_some_and(|total| self.decoded >= total) {
+            return Ok(false);
+        }
+        self.bits.start_frame();
+        let Some(first) = self.bits.try_byte()? else {
+            return Ok(false);
+        };
+        let second = self.bits.bits(8)?;
+        if first != 0xFF || second & 0xFE != 0xF8 {
+            return Err(malformed("lost frame sync"));
+        }
+
+        let block_code = self.bits.bits(4)?;
+        let rate_code = self.bits.bits(4)?;
+        let layout = match self.bits.bits(4)? {
+            n @ 0..=7 => ChannelLayout::Independent(n as u16 + 1),
+            8 => ChannelLayout::LeftSide,
+            9 => ChannelLayout::SideRight,
+            10 => ChannelLayout::MidSide,
+            _ => return Err(malformed("reserved channel assignment")),
+        };
+        let bits_per_sample = match self.bits.bits(3)? {
+            0 => u32::from(self.info.bits),
+            1 => 8,
+            2 => 12,
+            4 => 16,
+            5 => 20,
+            6 => 24,
+            7 => 32,
+            _ => return Err(malformed("reserved sample size")),
+        };
+        self.bits.bits(1)?; // reserved
+        self.bits.coded_number()?;
+        let block_size = match block_code {
+            0 => return Err(malformed("reserved block size")),
+            1 => 192,
+            2..=5 => 576 << (block_code - 2),
+            6 => self.bits.bits(8)? + 1,
+            7 => self.bits.bits(16)? + 1,
+            _ => 256 << (block_code - 8),
+        } as usize;
+        match rate_code {
+            12 => {
+                self.bits.bits(8)?;
+            }
+            13 | 14 => {
+                self.bits.bits(16)?;
+            }
+            15 => return Err(malformed("invalid sample rate code")),
+            _ => {}
+        }
+        let crc8 = self.bits.crc8;
+        if self.bits.bits(8)? as u8 != crc8 {
+            return Err(malformed("frame header CRC mismatch"));
+        }
+
+        let channel_count = match layout {
+            ChannelLayout::Independent(n) => n,
+            _ => 2,
+        };
+        if channel_count != self.info.channels {
+            return Err(malformed("channel count changes mid-stream"));
+        }
+
+        for ch in 0..usize::from(channel_count) {
+            let side = matches!(
+                (layout, ch),
+                (ChannelLayout::LeftSide | ChannelLayout::MidSide, 1)
+                    | (ChannelLayout::SideRight, 0)
+            );
+            let bps = bits_per_sample + u32::from(side);
+            let mut samples = core::mem::take(&mut self.channels[ch]);
+            samples.clear();
+            self.subframe(block_size, bps, &mut samples)?;
+            self.channels[ch] = samples;
+        }
+        self.bits.align();
+        let crc16 = self.bits.crc16;
+        if self.bits.bits(16)? as u16 != crc16 {
+            return Err(malformed("frame CRC mismatch"));
+        }
+
+        decorrelate(layout, &mut self.channels);
+        let scale = 1.0 / (1u64 << (bits_per_sample - 1)) as f64;
+        self.pending.clear();
+        self.pending_pos = 0;
+        for i in 0..block_size {
+            for ch in &self.channels {
+                self.pending.push((ch[i] as f64 * scale) as f32);
+            }
+        }
+        self.decoded += block_size as u64;
+        Ok(true)
+    }
+
+    fn subframe(
+        &mut self,
+        block_size: usize,
+        bps: u32,
+        out: &mut Vec<i64>,
+    ) -> Result<(), AudioError> {
+        if self.bits.bits(1)? != 0 {
+            return Err(malformed("subframe padding bit set"));
+        }
+        let kind = self.bits.bits(6)?;
+        let wasted = if self.bits.bits(1)? == 1 { self.bits.unary()? + 1 } else { 0 };
+        if wasted >= bps {
+            return Err(malformed("too many wasted bits"));
+        }
+        let bps = bps - wasted;
+
+        match kind {
+            0 => {
+                let value = self.bits.signed(bps)?;
+                out.resize(block_size, value);
+            }
+            1 => {
+                for _ in 0..block_size {
+                    out.push(self.bits.signed(bps)?);
+                }
+            }
+            8..=12 => {
+                let order = (kind - 8) as usize;
+                self.warmup(order, block_size, bps, out)?;
+                self.residual(block_size, order, out)?;
+                predict_fixed(order, out);
+            }
+            32..=63 => {
+                let order = (kind - 31) as usize;
+                self.warmup(order, block_size, bps, out)?;
+                let precision = self.bits.bits(4)? + 1;
+                if precision == 16 {
+                    return Err(malformed("invalid LPC precision"));
+                }
+                let shift = self.bits.signed(5)?;
+                if shift < 0 {
+                    return Err(malformed("negative LPC shift"));
+                }
+                let mut coefs = [0i64; 32];
+                for c in coefs.iter_mut().take(order) {
+                    *c = self.bits.signed(precision)?;
+                }
+                self.residual(block_size, order, out)?;
+                predict_lpc(&coefs[..order], shift as u32, out);
+            }
+            _ => return Err(malformed("reserved subframe type")),
+        }
+        if wasted > 0 {
+            for s in out.iter_mut() {
+                *s = s.wrapping_shl(wasted);
+            }
+        }
+        Ok(())
+    }
+
+    fn warmup(
+        &mut self,
+        order: usize,
+        block_size: usize,
+        bps: u32,
+        out: &mut Vec<i64>,
+    ) -> Result<(), AudioError> {
+        if order > block_size {
+            return Err(malformed("predictor order exceeds block size"));
+        }
+        for _ in 0..order {
+            out.push(self.bits.signed(bps)?);
+        }
+        Ok(())
+    }
+
+    /// Append `block_size - order` residuals.
+    fn residual(
+        &mut self,
+        block_size: usize,
+        order: usize,
+        out: &mut Vec<i64>,
+    ) -> Result<(), AudioError> {
+        let (param_bits, escape) = match self.bits.bits(2)? {
+            0 => (4, 15),
+            1 => (5, 31),
+            _ => return Err(malformed("reserved residual coding method")),
+        };
+        let partition_order = self.bits.bits(4)?;
+        let partitions = 1usize << partition_order;
+        let per_partition = block_size >> partition_order;
+        if per_partition * partitions != block_size || per_partition < order {
+            return Err(malformed("invalid residual partition order"));
+        }
+        for p in 0..partitions {
+            let count = if p == 0 { per_partition - order } else { per_partition };
+            let param = self.bits.bits(param_bits)?;
+            if param == escape {
+                let raw_bits = self.bits.bits(5)?;
+                for _ in 0..count {
+                    out.push(if raw_bits == 0 { 0 } else { self.bits.signed(raw_bits)? });
+                }
+            } else {
+                for _ in 0..count {
+                    let high = u64::from(self.bits.unary()?);
+                    let low = u64::from(self.bits.bits(param)?);
+                    let folded = (high << param) | low;
+                    out.push((folded >> 1) as i64 ^ -((folded & 1) as i64));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fixed polynomial predictors of order 0–4, applied in place.
+///
+/// Arithmetic wraps: a corrupt stream yields garbage samples, not a panic.
+fn predict_fixed(order: usize, s: &mut [i64]) {
+    for i in order..s.len() {
+        let prediction = match order {
+            0 => 0,
+            1 => s[i - 1],
+            2 => s[i - 1].wrapping_mul(2).wrapping_sub(s[i - 2]),
+            3 => s[i - 1]
+                .wrapping_sub(s[i - 2])
+                .wrapping_mul(3)
+                .wrapping_add(s[i - 3]),
+            _ => s[i - 1]
+                .wrapping_add(s[i - 3])
+                .wrapping_mul(4)
+                .wrapping_sub(s[i - 2].wrapping_mul(6))
+                .wrapping_sub(s[i - 4]),
+        };
+        s[i] = s[i].wrapping_add(prediction);
+    }
+}
+
+/// LPC prediction, wrapping like [`predict_fixed`].
+fn predict_lpc(coefs: &[i64], shift: u32, s: &mut [i64]) {
+    for i in coefs.len()..s.len() {
+        let sum = coefs
+            .iter()
+            .enumerate()
+            .fold(0i64, |acc, (j, &c)| acc.wrapping_add(c.wrapping_mul(s[i - 1 - j])));
+        s[i] = s[i].wrapping_add(sum >> shift);
+    }
+}
+
+fn decorrelate(layout: ChannelLayout, channels: &mut [Vec<i64>]) {
+    let [a, b] = channels else {
+        return;
+    };
+    match layout {
+        ChannelLayout::Independent(_) => {}
+        ChannelLayout::LeftSide => {
+            for (left, side) in a.iter().zip(b.iter_mut()) {
+                *side = left.wrapping_sub(*side);
+            }
+        }
+        ChannelLayout::SideRight => {
+            for (side, right) in a.iter_mut().zip(b.iter()) {
+                *side = side.wrapping_add(*right);
+            }
+        }
+        ChannelLayout::MidSide => {
+            for (mid, side) in a.iter_mut().zip(b.iter_mut()) {
+                let m = mid.wrapping_shl(1) | (*side & 1);
+                let s = *side;
+                *mid = m.wrapping_add(s) >> 1;
+                *side = m.wrapping_sub(s) >> 1;
+            }
+        }
+    }
+}
+
+/// Big-endian bit reader with running CRC-8 / CRC-16 over consumed bytes.
+struct BitReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    pos: usize,
+    len: usize,
+    acc: u64,
+    acc_bits: u32,
+    crc8: u8,
+    crc16: u16,
+}
+
+impl<R: Read> BitReader<R> {
+    fn new(inner: R) -> Self {
+        Self { inner, buf: vec![0; 8192], pos: 0, len: 0, acc: 0, acc_bits: 0, crc8: 0, crc16: 0 }
+    }
+
+    fn start_frame(&mut self) {
+        self.align();
+        self.crc8 = 0;
+        self.crc16 = 0;
+    }
+
+    fn align(&mut self) {
+        self.acc_bits -= self.acc_bits % 8;
+    }
+
+    /// Next byte from the source, or `None` at end of input.
+    fn fetch(&mut self) -> Result<Option<u8>, AudioError> {
+        if self.pos == self.len {
+            self.len = super::read_full(&mut self.inner, &mut self.buf)?;
+            self.pos = 0;
+            if self.len == 0 {
+                return Ok(None);
+            }
+        }
+        let byte = self.buf[self.pos];
+        self.pos += 1;
+        self.crc8 = CRC8_TABLE[usize::from(self.crc8 ^ byte)];
+        self.crc16 = (self.crc16 << 8) ^ CRC16_TABLE[usize::from((self.crc16 >> 8) as u8 ^ byte)];
+        Ok(Some(byte))
+    }
+
+    /// A whole byte at a byte boundary, or `None` at end of input.
+    fn try_byte(&mut self) -> Result<Option<u8>, AudioError> {
+        if self.acc_bits >= 8 {
+            return self.bits(8).map(|b| Some(b as u8));
+        }
+        self.fetch()
+    }
+
+    fn bits(&mut self, n: u32) -> Result<u32, AudioError> {
+        if n == 0 {
+            return Ok(0);
+        }
+        while self.acc_bits < n {
+            let byte = self.fetch()?.ok_or_else(|| malformed("unexpected end of stream"))?;
+            self.acc = (self.acc << 8) | u64::from(byte);
+            self.acc_bits += 8;
+        }
+        self.acc_bits -= n;
+        Ok(((self.acc >> self.acc_bits) & ((1u64 << n) - 1)) as u32)
+    }
+
+    /// `n`-bit two's complement value, `n` up to 33.
+    fn signed(&mut self, n: u32) -> Result<i64, AudioError> {
+        let raw = if n > 32 {
+            (u64::from(self.bits(n - 32)?) << 32) | u64::from(self.bits(32)?)
+        } else {
+            u64::from(self.bits(n)?)
+        };
+        let shift = 64 - n;
+        Ok(((raw << shift) as i64) >> shift)
+    }
+
+    /// Count of 0 bits before the next 1 bit.
+    fn unary(&mut self) -> Result<u32, AudioError> {
+        let mut zeros = 0;
+        loop {
+            if self.acc_bits == 0 {
+                let byte = self.fetch()?.ok_or_else(|| malformed("unexpected end of stream"))?;
+                self.acc = u64::from(byte);
+                self.acc_bits = 8;
+            }
+            let avail = self.acc & ((1u64 << self.acc_bits) - 1);
+            if avail == 0 {
+                zeros += self.acc_bits;
+                self.acc_bits = 0;
+                continue;
+            }
+            let top = avail.ilog2();
+            zeros += self.acc_bits - 1 - top;
+            self.acc_bits = top;
+            return Ok(zeros);
+        }
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], AudioError> {
+        let mut out = [0u8; N];
+        for b in &mut out {
+            *b = self.bits(8)? as u8;
+        }
+        Ok(out)
+    }
+
+    fn skip_bytes(&mut self, n: u64) -> Result<(), AudioError> {
+        for _ in 0..n {
+            self.bits(8)?;
+        }
+        Ok(())
+    }
+
+    /// UTF-8 style frame/sample number (up to 36 bits).
+    fn coded_number(&mut self) -> Result<u64, AudioError> {
+        let first = self.bits(8)? as u8;
+        let extra = match first.leading_ones() {
+            0 => return Ok(u64::from(first)),
+            n @ 2..=7 => n - 1,
+            _ => return Err(malformed("invalid coded frame number")),
+        };
+        let mut value = u64::from(first & (0x7F >> (extra + 1)));
+        for _ in 0..extra {
+            let byte = self.bits(8)?;
+            if byte & 0xC0 != 0x80 {
+                return Err(malformed("invalid coded frame number"));
+            }
+            value = (value << 6) | u64::from(byte & 0x3F);
+        }
+        Ok(value)
+    }
+}
+
+fn malformed(what: &str) -> AudioError {
+    AudioError::Decode(format!("malformed FLAC stream: {what}"))
+}
+
+/// CRC-8, polynomial x^8 + x^2 + x + 1.
+const CRC8_TABLE: [u8; 256] = {
+    let mut table = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// CRC-16, polynomial x^16 + x^15 + x^2 + 1.
+const CRC16_TABLE: [u16; 256] = {
+    let mut table = [0u16; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x8005 } else { crc << 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
@@ -0,0 +1,183 @@
+//! Audio file decoding for offline ingestion.
+//!
+//! Formats:
+//! - WAV: PCM 8/16/24/32-bit and IEEE float 32/64-bit, including
+//!   `WAVE_FORMAT_EXTENSIBLE` and RF64 (64-bit sizes from `ds64`).
+//! - AIFF and AIFC (`NONE`, `twos`, `sowt`, `fl32`, `fl64`).
+//! - FLAC: every subframe type, 4–32 bits per sample, CRC-checked frames.
+//!
+//! Rules:
+//! - The format is detected from the file header, never the extension.
+//! - Samples are decoded to interleaved `f32` in `[-1.0, 1.0)` and handed
+//!   out as `AudioBlock`s of at most `max_frames` frames, at the file's own
+//!   sample rate and channel count (no resampling).
+//! - Unsupported encodings are `AudioError::Unsupported`;
+//!   malformed, truncated or unreadable files are `AudioError::Decode`.
+
+// Bit-level decoding narrows values whose width the format already fixes.
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+
+mod aiff;
+mod flac;
+mod pcm;
+mod wav;
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Audio decoding failure.
+#[derive(Debug)]
+pub enum AudioError {
+    /// Container or encoding the decoder does not support.
+    Unsupported(String),
+    /// Unreadable, truncated or malformed file.
+    Decode(String),
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported(what) => write!(f, "unsupported audio format: {what}"),
+            Self::Decode(msg) => write!(f, "audio decode failed: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for AudioError {}
+
+/// Container format of an audio file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AudioFileFormat {
+    Wav,
+    Rf64,
+    Aiff,
+    Aifc,
+    Flac,
+}
+
+/// Stream parameters from the file header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AudioFileInfo {
+    pub format: AudioFileFormat,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bits_per_sample: u16,
+    /// Total frames, if the header states it.
+    pub frames: Option<u64>,
+}
+
+enum Decoder<R> {
+    Pcm(pcm::PcmStream<R>),
+    Flac(flac::FlacStream<R>),
+}
+
+/// Streaming decoder over one audio file.
+pub struct AudioFileReader<R> {
+    info: AudioFileInfo,
+    decoder: Decoder<R>,
+}
+
+impl AudioFileReader<BufReader<File>> {
+    /// Open and sniff the file at `path`.
+    pub fn open(path: &Path) -> Result<Self, AudioError> {
+        let file = File::open(path)
+            .map_err(|e| AudioError::Decode(format!("cannot open {}: {e}", path.display())))?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read + Seek> AudioFileReader<R> {
+    /// Read the header from `reader` (positioned at the start of the file).
+    pub fn new(mut reader: R) -> Result<Self, AudioError> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic).map_err(io_error)?;
+        reader.seek(SeekFrom::Start(0)).map_err(io_error)?;
+        let (info, decoder) = match &magic {
+            b"RIFF" | b"RF64" => {
+                let (info, stream) = wav::open(reader)?;
+                (info, Decoder::Pcm(stream))
+            }
+            b"FORM" => {
+                let (info, stream) = aiff::open(reader)?;
+                (info, Decoder::Pcm(stream))
+            }
+            b"fLaC" | [b'I', b'D', b'3', _] => {
+                let (info, stream) = flac::open(reader)?;
+                (info, Decoder::Flac(stream))
+            }
+            _ => {
+                return Err(AudioError::Unsupported(
+                    "unrecognized file header (expected WAV, AIFF or FLAC)".into(),
+                ));
+            }
+        };
+        if info.channels == 0 || info.sample_rate == 0 {
+            return Err(AudioError::Decode("zero channels or sample rate".into()));
+        }
+        Ok(Self { info, decoder })
+    }
+}
+
+impl<R: Read> AudioFileReader<R> {
+    #[must_use]
+    pub fn info(&self) -> AudioFileInfo {
+        self.info
+    }
+
+    /// Decode the next block of at most `max_frames` interleaved frames;
+    /// `None` at the end of the stream.
+    pub fn next_block(&mut self, max_frames: usize) -> Result<Option<Vec<f32>>, AudioError> {
+        let mut frames = Vec::new();
+        let max_frames = max_frames.max(1);
+        let read = match &mut self.decoder {
+            Decoder::Pcm(stream) => stream.read_frames(max_frames, &mut frames)?,
+            Decoder::Flac(stream) => stream.read_frames(max_frames, &mut frames)?,
+        };
+        if read == 0 {
+            return Ok(None);
+        }
+        Ok(Some(frames))
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)] // shaped for `map_err(io_error)`
+fn io_error(e: std::io::Error) -> AudioError {
+    AudioError::Decode(format!("read failed: {e}"))
+}
+
+/// Fill `buf` as far as the reader allows; returns the bytes read.
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize, AudioError> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(io_error(e)),
+        }
+    }
+    Ok(filled)
+}
+
+/// Read a chunk body of `len` bytes and its pad byte, if present.
+///
+/// The buffer grows only as data arrives, so a corrupt length costs at
+/// most the bytes left in the file. Returns `None` if the file ends before
+/// `len` bytes.
+fn read_chunk<R: Read>(reader: &mut R, len: u32) -> Result<Option<Vec<u8>>, AudioError> {
+    let padded = u64::from(len) + u64::from(len & 1);
+    let mut body = Vec::new();
+    reader.by_ref().take(padded).read_to_end(&mut body).map_err(io_error)?;
+    if body.len() < len as usize {
+        return Ok(None);
+    }
+    body.truncate(len as usize);
+    Ok(Some(body))
+}
@@ -0,0 +1,100 @@
+//! Uncompressed sample data shared by WAV and AIFF.
+
+use std::io::Read;
+
+use super::{AudioError, read_full};
+
+/// Storage of one sample.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SampleEncoding {
+    /// Unsigned 8-bit, 128 = silence (WAV).
+    U8,
+    /// Signed two's complement, left-justified in `bytes` bytes.
+    Int { bytes: u8, big_endian: bool },
+    /// IEEE float of 4 or 8 bytes.
+    Float { bytes: u8, big_endian: bool },
+}
+
+impl SampleEncoding {
+    pub(crate) fn bytes(self) -> usize {
+        match self {
+            Self::U8 => 1,
+            Self::Int { bytes, .. } | Self::Float { bytes, .. } => usize::from(bytes),
+        }
+    }
+
+    fn decode(self, b: &[u8]) -> f32 {
+        match self {
+            Self::U8 => (f32::from(b[0]) - 128.0) / 128.0,
+            Self::Int { big_endian, .. } => {
+                // Left-justify into an i32 so every width shares one scale.
+                let mut word = [0u8; 4];
+                for (i, &byte) in b.iter().enumerate() {
+                    let from_top = if big_endian { i } else { b.len() - 1 - i };
+                    word[from_top] = byte;
+                }
+                i32::from_be_bytes(word) as f32 / 2_147_483_648.0
+            }
+            Self::Float { bytes: 4, big_endian } => {
+                let word = [b[0], b[1], b[2], b[3]];
+                if big_endian { f32::from_be_bytes(word) } else { f32::from_le_bytes(word) }
+            }
+            Self::Float { big_endian, .. } => {
+                let word = [b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]];
+                let v =
+                    if big_endian { f64::from_be_bytes(word) } else { f64::from_le_bytes(word) };
+                v as f32
+            }
+        }
+    }
+}
+
+/// Interleaved sample data of known encoding, read frame by frame.
+pub(crate) struct PcmStream<R> {
+    reader: R,
+    encoding: SampleEncoding,
+    channels: usize,
+    /// Bytes of sample data left, per the header.
+    remaining: u64,
+    buf: Vec<u8>,
+}
+
+impl<R: Read> PcmStream<R> {
+    /// `reader` must be positioned at the first sample.
+    pub(crate) fn new(reader: R, encoding: SampleEncoding, channels: u16, data_len: u64) -> Self {
+        Self {
+            reader,
+            encoding,
+            channels: usize::from(channels),
+            remaining: data_len,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Append up to `max_frames` frames to `out`; returns the frames read.
+    ///
+    /// A file shorter than its header claims ends early; a trailing
+    /// partial frame is dropped.
+    pub(crate) fn read_frames(
+        &mut self,
+        max_frames: usize,
+        out: &mut Vec<f32>,
+    ) -> Result<usize, AudioError> {
+        let frame_bytes = self.encoding.bytes() * self.channels;
+        let want =
+            (max_frames * frame_bytes).min(usize::try_from(self.remaining).unwrap_or(usize::MAX));
+        self.buf.resize(want - want % frame_bytes, 0);
+        let got = read_full(&mut self.reader, &mut self.buf)?;
+        let frames = got / frame_bytes;
+        self.remaining = if got < self.buf.len() { 0 } else { self.remaining - got as u64 };
+
+        let sample_bytes = self.encoding.bytes();
+        out.reserve(frames * self.channels);
+        out.extend(
+            self.buf[..frames * frame_bytes]
+                .chunks_exact(sample_bytes)
+                .map(|b| self.encoding.decode(b)),
+        );
+        Ok(frames)
+    }
+}
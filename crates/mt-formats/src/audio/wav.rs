@@ -0,0 +1,127 @@
+//! RIFF/RF64 WAVE header parsing.
+
+use std::io::{Read, Seek, SeekFrom};
+
+use super::pcm::{PcmStream, SampleEncoding};
+use super::{AudioError, AudioFileFormat, AudioFileInfo, io_error, read_chunk};
+
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+struct Format {
+    tag: u16,
+    channels: u16,
+    sample_rate: u32,
+    bits: u16,
+}
+
+/// Parse chunks up to `data` and leave `reader` at the first sample.
+pub(crate) fn open<R: Read + Seek>(
+    mut reader: R,
+) -> Result<(AudioFileInfo, PcmStream<R>), AudioError> {
+    let mut header = [0u8; 12];
+    reader.read_exact(&mut header).map_err(io_error)?;
+    if &header[8..12] != b"WAVE" {
+        return Err(malformed("missing WAVE id"));
+    }
+    let rf64 = &header[..4] == b"RF64";
+
+    let mut format: Option<Format> = None;
+    let mut ds64_data_len: Option<u64> = None;
+    loop {
+        let mut chunk = [0u8; 8];
+        reader.read_exact(&mut chunk).map_err(|_| malformed("no data chunk"))?;
+        let id = [chunk[0], chunk[1], chunk[2], chunk[3]];
+        let len = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+        match &id {
+            b"ds64" => {
+                let body = read_body(&mut reader, len)?;
+                if body.len() < 16 {
+                    return Err(malformed("ds64 chunk too short"));
+                }
+                ds64_data_len = Some(le_u64(&body[8..16]));
+            }
+            b"fmt " => format = Some(parse_format(&read_body(&mut reader, len)?)?),
+            b"data" => {
+                let format = format.ok_or_else(|| malformed("data chunk before fmt chunk"))?;
+                let data_len = match ds64_data_len {
+                    Some(n) if rf64 && len == u32::MAX => n,
+                    _ => u64::from(len),
+                };
+                return stream(reader, &format, rf64, data_len);
+            }
+            _ => {
+                let skip = i64::from(len) + i64::from(len & 1);
+                reader.seek(SeekFrom::Current(skip)).map_err(io_error)?;
+            }
+        }
+    }
+}
+
+fn parse_format(body: &[u8]) -> Result<Format, AudioError> {
+    if body.len() < 16 {
+        return Err(malformed("fmt chunk too short"));
+    }
+    let u16_at = |i: usize| u16::from_le_bytes([body[i], body[i + 1]]);
+    let mut tag = u16_at(0);
+    if tag == WAVE_FORMAT_EXTENSIBLE {
+        // The sub-format GUID starts with the real format tag.
+        if body.len() < 40 {
+            return Err(malformed("WAVE_FORMAT_EXTENSIBLE fmt chunk too short"));
+        }
+        tag = u16_at(24);
+    }
+    Ok(Format {
+        tag,
+        channels: u16_at(2),
+        sample_rate: u32::from_le_bytes([body[4], body[5], body[6], body[7]]),
+        bits: u16_at(14),
+    })
+}
+
+fn stream<R: Read>(
+    reader: R,
+    format: &Format,
+    rf64: bool,
+    data_len: u64,
+) -> Result<(AudioFileInfo, PcmStream<R>), AudioError> {
+    let encoding = match (format.tag, format.bits) {
+        (WAVE_FORMAT_PCM, 8) => SampleEncoding::U8,
+        (WAVE_FORMAT_PCM, 16 | 24 | 32) => {
+            SampleEncoding::Int { bytes: (format.bits / 8) as u8, big_endian: false }
+        }
+        (WAVE_FORMAT_IEEE_FLOAT, 32 | 64) => {
+            SampleEncoding::Float { bytes: (format.bits / 8) as u8, big_endian: false }
+        }
+        (WAVE_FORMAT_PCM | WAVE_FORMAT_IEEE_FLOAT, bits) => {
+            return Err(AudioError::Unsupported(format!("WAV with {bits} bits per sample")));
+        }
+        (tag, _) => {
+            return Err(AudioError::Unsupported(format!(
+                "WAV format tag {tag:#06x} (only PCM and IEEE float are supported)"
+            )));
+        }
+    };
+    let frame_bytes = encoding.bytes() as u64 * u64::from(format.channels);
+    let info = AudioFileInfo {
+        format: if rf64 { AudioFileFormat::Rf64 } else { AudioFileFormat::Wav },
+        sample_rate: format.sample_rate,
+        channels: format.channels,
+        bits_per_sample: format.bits,
+        frames: data_len.checked_div(frame_bytes),
+    };
+    Ok((info, PcmStream::new(reader, encoding, format.channels, data_len)))
+}
+
+fn read_body<R: Read>(reader: &mut R, len: u32) -> Result<Vec<u8>, AudioError> {
+    read_chunk(reader, len)?.ok_or_else(|| malformed("truncated chunk"))
+}
+
+fn le_u64(b: &[u8]) -> u64 {
+    u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]])
+}
+
+fn malformed(what: &str) -> AudioError {
+    AudioError::Decode(format!("malformed WAV file: {what}"))
+}
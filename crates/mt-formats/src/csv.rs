@@ -0,0 +1,151 @@
+//! CSV timelines: every annotation type in one table.
+//!
+//! Rules:
+//! - Columns: `type,start,end,label,confidence`, with times in seconds and
+//!   confidence from 0 to 1. The header row is written and, when present,
+//!   skipped on read; the confidence column is optional.
+//! - `type` is `chord`, `key`, `segment`, `tempo` or `beat`; labels use
+//!   the same syntax as `.lab` files, and keys are written `C:major`. Beat
+//!   rows end where they start and have no label.
+//! - Fields containing commas or quotes are quoted, with `""` for a quote.
+//! - Rows are written by start time, then type in the order above.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use mt_core::events::{ChordEvent, KeyEvent, SegmentEvent, TempoEvent};
+use mt_core::time::SampleTime;
+
+use crate::annotation::{
+    AnnotationError, Annotations, confidence_from_unit, confidence_to_unit, format_harte,
+    format_key, format_segment, format_x1000, parse_harte, parse_key_label, parse_segment_label,
+    samples_to_seconds, seconds_to_samples,
+};
+use crate::score::parse_x1000;
+
+const HEADER: &str = "type,start,end,label,confidence";
+
+impl Annotations {
+    /// Read a CSV timeline.
+    pub fn from_csv(text: &str, sample_rate: u32) -> Result<Self, AnnotationError> {
+        let mut out = Self::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || (index == 0 && line.starts_with("type,")) {
+                continue;
+            }
+            let invalid = AnnotationError::InvalidLine(index + 1);
+            let fields = split_fields(line).ok_or(invalid)?;
+            let [kind, start, stop, label, rest @ ..] = fields.as_slice() else {
+                return Err(invalid);
+            };
+            let onset = seconds_to_samples(start, sample_rate).ok_or(invalid)?;
+            let offset = seconds_to_samples(stop, sample_rate).ok_or(invalid)?.max(onset);
+            let confidence_x1000 = match rest.first().map(|c| c.trim()) {
+                None | Some("") => 1000,
+                Some(c) => confidence_from_unit(c).ok_or(invalid)?,
+            };
+            match kind.trim() {
+                "chord" => {
+                    if let Some(chord) = parse_harte(label).map_err(|_| invalid)? {
+                        out.chords.push(ChordEvent { chord, onset, offset, confidence_x1000 });
+                    }
+                }
+                "key" => {
+                    if let Some(key) = parse_key_label(label) {
+                        out.keys.push(KeyEvent { key, position: onset, confidence_x1000 });
+                    }
+                }
+                "segment" => {
+                    if let Some((kind, label)) = parse_segment_label(label) {
+                        out.segments.push(SegmentEvent {
+                            kind,
+                            label,
+                            onset,
+                            offset,
+                            confidence_x1000,
+                        });
+                    }
+                }
+                "tempo" => {
+                    let bpm_x1000 = parse_x1000(label).ok_or(invalid)?;
+                    out.tempos.push(TempoEvent { position: onset, bpm_x1000 });
+                }
+                "beat" => out.beats.push(onset),
+                _ => return Err(invalid),
+            }
+        }
+        out.sort();
+        Ok(out)
+    }
+
+    /// Write all annotations as a CSV timeline.
+    pub fn to_csv(&self, sample_rate: u32) -> String {
+        // (start, type order, end, type, label, confidence)
+        let mut rows: Vec<(SampleTime, u8, SampleTime, &str, String, u16)> = Vec::new();
+        for c in &self.chords {
+            rows.push((c.onset, 0, c.offset, "chord", format_harte(&c.chord), c.confidence_x1000));
+        }
+        for (start, stop, key, confidence) in self.key_spans() {
+            rows.push((start, 1, stop, "key", format_key(key), confidence));
+        }
+        for s in &self.segments {
+            rows.push((s.onset, 2, s.offset, "segment", format_segment(s), s.confidence_x1000));
+        }
+        for (start, stop, bpm_x1000) in self.tempo_spans() {
+            rows.push((start, 3, stop, "tempo", format_x1000(bpm_x1000), 1000));
+        }
+        for &beat in &self.beats {
+            rows.push((beat, 4, beat, "beat", String::new(), 1000));
+        }
+        rows.sort_by_key(|r| (r.0, r.1));
+
+        let mut out = String::from(HEADER);
+        out.push('\n');
+        for (start, _, stop, kind, label, confidence) in rows {
+            let _ = writeln!(
+                out,
+                "{kind},{},{},{},{}",
+                samples_to_seconds(start, sample_rate),
+                samples_to_seconds(stop, sample_rate),
+                quote(&label),
+                confidence_to_unit(confidence)
+            );
+        }
+        out
+    }
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        let mut out = String::from("\"");
+        out.push_str(&field.replace('"', "\"\""));
+        out.push('"');
+        out
+    } else {
+        String::from(field)
+    }
+}
+
+/// Split a row into fields, undoing quoting; `None` on an open quote.
+fn split_fields(line: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.next_if_eq(&'"').is_some() => field.push('"'),
+            (true, '"') => quoted = false,
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => fields.push(core::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    fields.push(field);
+    Some(fields)
+}
@@ -0,0 +1,264 @@
+//! JAMS (JSON Annotated Music Specification) files.
+//!
+//! Rules:
+//! - Reads the first annotation of each supported namespace: `chord` or
+//!   `chord_harte`, `key_mode`, `segment_open` or a SALAMI/TUT segment
+//!   namespace, `tempo` and `beat`. Others are ignored.
+//! - Observations may be a list of `{time, duration, value, confidence}`
+//!   objects (JAMS 0.3) or parallel arrays (older files).
+//! - A `null` or missing confidence reads as full confidence.
+//! - The writer emits JAMS 0.3 with one annotation per non-empty event
+//!   list and empty metadata; `file_metadata.duration` is the end of the
+//!   annotations.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use mt_core::events::{ChordEvent, KeyEvent, SegmentEvent, TempoEvent};
+use mt_core::time::SampleTime;
+
+use crate::annotation::{
+    AnnotationError, Annotations, confidence_from_unit, confidence_to_unit, format_harte,
+    format_key, format_segment, format_x1000, parse_harte, parse_key_label, parse_segment_label,
+    samples_to_seconds, seconds_to_samples,
+};
+use crate::json::{self, Value};
+use crate::score::parse_x1000;
+
+const JAMS_VERSION: &str = "0.3.4";
+
+const ANNOTATION_METADATA: &str = r#"{"curator": {"name": "", "email": ""}, "annotator": {}, "version": "", "corpus": "", "annotation_tools": "", "annotation_rules": "", "validation": "", "data_source": ""}"#;
+
+const FILE_METADATA: &str = r#""title": "", "artist": "", "release": "", "identifiers": {}"#;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Namespace {
+    Chord,
+    Key,
+    Segment,
+    Tempo,
+    Beat,
+}
+
+impl Namespace {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "chord" | "chord_harte" => Some(Self::Chord),
+            "key_mode" => Some(Self::Key),
+            "segment_open"
+            | "segment_salami_function"
+            | "segment_salami_upper"
+            | "segment_salami_lower"
+            | "segment_tut" => Some(Self::Segment),
+            "tempo" => Some(Self::Tempo),
+            "beat" => Some(Self::Beat),
+            _ => None,
+        }
+    }
+}
+
+/// One observation with its time span and raw value.
+struct Observation<'a> {
+    onset: SampleTime,
+    offset: SampleTime,
+    value: &'a Value,
+    confidence_x1000: u16,
+}
+
+impl Annotations {
+    /// Read a JAMS document.
+    pub fn from_jams(text: &str, sample_rate: u32) -> Result<Self, AnnotationError> {
+        let root = json::parse(text).map_err(AnnotationError::MalformedJson)?;
+        let annotations =
+            root.get("annotations").and_then(Value::as_array).ok_or(AnnotationError::NotJams)?;
+
+        let mut out = Self::default();
+        let mut seen: Vec<Namespace> = Vec::new();
+        for (annotation, entry) in annotations.iter().enumerate() {
+            let namespace = entry.get("namespace").and_then(Value::as_str);
+            let Some(namespace) = namespace.and_then(Namespace::from_name) else { continue };
+            if seen.contains(&namespace) {
+                continue;
+            }
+            seen.push(namespace);
+
+            let data = entry.get("data").unwrap_or(&Value::Null);
+            let observations = observations(data, sample_rate)
+                .map_err(|index| AnnotationError::InvalidObservation { annotation, index })?;
+            for (index, obs) in observations.into_iter().enumerate() {
+                let invalid = AnnotationError::InvalidObservation { annotation, index };
+                out.push(namespace, &obs).ok_or(invalid)?;
+            }
+        }
+        out.sort();
+        Ok(out)
+    }
+
+    /// Write a JAMS document.
+    pub fn to_jams(&self, sample_rate: u32) -> String {
+        let time = |t: SampleTime| samples_to_seconds(t, sample_rate);
+        let span = |start: SampleTime, stop: SampleTime| {
+            (
+                time(start),
+                samples_to_seconds(SampleTime::new(stop.value() - start.value()), sample_rate),
+            )
+        };
+
+        let mut blocks: Vec<(&str, Vec<String>)> = Vec::new();
+        let mut chords = Vec::new();
+        for c in &self.chords {
+            let (t, d) = span(c.onset, c.offset);
+            chords.push(observation(
+                &t,
+                &d,
+                &json_string(&format_harte(&c.chord)),
+                c.confidence_x1000,
+            ));
+        }
+        blocks.push(("chord", chords));
+        let mut keys = Vec::new();
+        for (start, stop, key, confidence) in self.key_spans() {
+            let (t, d) = span(start, stop);
+            keys.push(observation(&t, &d, &json_string(&format_key(key)), confidence));
+        }
+        blocks.push(("key_mode", keys));
+        let mut segments = Vec::new();
+        for s in &self.segments {
+            let (t, d) = span(s.onset, s.offset);
+            segments.push(observation(
+                &t,
+                &d,
+                &json_string(&format_segment(s)),
+                s.confidence_x1000,
+            ));
+        }
+        blocks.push(("segment_open", segments));
+        let mut tempos = Vec::new();
+        for (start, stop, bpm_x1000) in self.tempo_spans() {
+            let (t, d) = span(start, stop);
+            tempos.push(observation(&t, &d, &format_x1000(bpm_x1000), 1000));
+        }
+        blocks.push(("tempo", tempos));
+        let mut beats = Vec::new();
+        for &beat in &self.beats {
+            let (t, d) = span(beat, beat);
+            beats.push(observation(&t, &d, "null", 1000));
+        }
+        blocks.push(("beat", beats));
+
+        let mut out = String::from("{\n  \"annotations\": [");
+        let mut first = true;
+        for (namespace, data) in blocks.iter().filter(|(_, data)| !data.is_empty()) {
+            out.push_str(if first { "\n" } else { ",\n" });
+            first = false;
+            let _ = writeln!(out, "    {{");
+            let _ = writeln!(out, "      \"annotation_metadata\": {ANNOTATION_METADATA},");
+            let _ = writeln!(out, "      \"namespace\": \"{namespace}\",");
+            let _ =
+                writeln!(out, "      \"data\": [\n        {}\n      ],", data.join(",\n        "));
+            let _ = writeln!(out, "      \"sandbox\": {{}},");
+            let _ = writeln!(out, "      \"time\": 0,");
+            let _ = writeln!(out, "      \"duration\": null");
+            out.push_str("    }");
+        }
+        out.push_str("\n  ],\n");
+        let duration = time(self.end());
+        let _ = writeln!(
+            out,
+            "  \"file_metadata\": {{{FILE_METADATA}, \"duration\": {duration}, \"jams_version\": \"{JAMS_VERSION}\"}},"
+        );
+        out.push_str("  \"sandbox\": {}\n}\n");
+        out
+    }
+
+    /// Add one observation; `None` if its value does not fit the namespace.
+    fn push(&mut self, namespace: Namespace, obs: &Observation<'_>) -> Option<()> {
+        let Observation { onset, offset, value, confidence_x1000 } = *obs;
+        match namespace {
+            Namespace::Chord => {
+                if let Some(chord) = parse_harte(value.as_str()?).ok()? {
+                    self.chords.push(ChordEvent { chord, onset, offset, confidence_x1000 });
+                }
+            }
+            Namespace::Key => {
+                if let Some(key) = parse_key_label(value.as_str()?) {
+                    self.keys.push(KeyEvent { key, position: onset, confidence_x1000 });
+                }
+            }
+            Namespace::Segment => {
+                if let Some((kind, label)) = parse_segment_label(value.as_str()?) {
+                    self.segments.push(SegmentEvent {
+                        kind,
+                        label,
+                        onset,
+                        offset,
+                        confidence_x1000,
+                    });
+                }
+            }
+            Namespace::Tempo => {
+                let bpm_x1000 = parse_x1000(value.as_number()?)?;
+                self.tempos.push(TempoEvent { position: onset, bpm_x1000 });
+            }
+            Namespace::Beat => self.beats.push(onset),
+        }
+        Some(())
+    }
+}
+
+/// Observations of a `data` field; the error is the failing index.
+fn observations(data: &Value, sample_rate: u32) -> Result<Vec<Observation<'_>>, usize> {
+    match data {
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, o)| {
+                let fields =
+                    [o.get("time"), o.get("duration"), o.get("value"), o.get("confidence")];
+                read_observation(fields, sample_rate).ok_or(i)
+            })
+            .collect(),
+        Value::Object(_) => {
+            let column = |name| data.get(name).and_then(Value::as_array).unwrap_or(&[]);
+            let columns =
+                [column("time"), column("duration"), column("value"), column("confidence")];
+            (0..columns[0].len())
+                .map(|i| read_observation(columns.map(|c| c.get(i)), sample_rate).ok_or(i))
+                .collect()
+        }
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Time, duration, value and confidence fields → an observation.
+fn read_observation(fields: [Option<&Value>; 4], sample_rate: u32) -> Option<Observation<'_>> {
+    let [time, duration, value, confidence] = fields;
+    let seconds = |v: Option<&Value>| seconds_to_samples(v?.as_number()?, sample_rate);
+    let onset = seconds(time)?;
+    let duration = seconds(duration)?;
+    let confidence_x1000 = match confidence {
+        None | Some(Value::Null) => 1000,
+        Some(c) => confidence_from_unit(c.as_number()?)?,
+    };
+    Some(Observation {
+        onset,
+        offset: onset.saturating_add(duration.value().max(0)),
+        value: value?,
+        confidence_x1000,
+    })
+}
+
+fn observation(time: &str, duration: &str, value: &str, confidence_x1000: u16) -> String {
+    let confidence = confidence_to_unit(confidence_x1000);
+    format!(
+        "{{\"time\": {time}, \"duration\": {duration}, \"value\": {value}, \"confidence\": {confidence}}}"
+    )
+}
+
+fn json_string(text: &str) -> String {
+    let mut out = String::new();
+    json::write_string(&mut out, text);
+    out
+}
@@ -0,0 +1,441 @@
+//! Humdrum `**kern` reader → `Score`, with `**harm` Roman numerals as
+//! chord annotations.
+//!
+//! Rules:
+//! - Each `**kern` spine of the first exclusive interpretation is a part;
+//!   spines split from it (`*^`) stay in that part. `*v`, `*-`, `*+` and
+//!   `*x` are followed; `*I"` names the part.
+//! - Every record is one time slice: it lasts until the earliest note
+//!   sounding in any `**kern` spine ends.
+//! - Pitch: `c` is middle C, each repeated letter moves one octave
+//!   (`cc`, `C`, `CC`); `#`, `-` and `n` alter it. Rests, grace notes
+//!   (`q`, `Q`) and other signifiers produce no note.
+//! - Durations: reciprocal with dots, `0`/`00` for breve and long, and
+//!   `n%m` for `m/n` of a whole note.
+//! - Ties: `[` opens, `_` continues and `]` closes.
+//! - `*M3/4` sets the meter, `*MM120` the tempo (quarter notes per
+//!   minute) and `*E-:` / `*f#:` the key (case gives the mode).
+//! - `**harm` numerals are read against the last key: case gives the
+//!   triad, `o` diminished, `+` augmented, `%` or `ø` half-diminished;
+//!   `7` adds the diatonic seventh (`o7` is fully diminished); `-`/`#`
+//!   alter the root; `b`/`c`/`d` are inversions; `N` is the Neapolitan and
+//!   `/` tonicizes. Minor keys use the natural minor scale, except that
+//!   lower-case `vii` sits on the leading tone. Augmented sixths (`Lt`,
+//!   `Fr`, `Gn`) are skipped.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use mt_core::chord::{BassPolicy, Chord};
+use mt_core::chord_kind::{ChordKindId, chord_intervals};
+use mt_core::key::{Key, KeyMode};
+use mt_core::pitch::PitchClass;
+
+use crate::score::{
+    DEFAULT_VELOCITY, Quarters, Score, ScoreBuilder, ScoreError, Tie, key_from_name, parse_x1000,
+    step_semitone,
+};
+
+impl Score {
+    /// Parse a Humdrum file with at least one `**kern` spine.
+    pub fn from_kern(text: &str, sample_rate: u32) -> Result<Self, ScoreError> {
+        let mut reader = KernReader::default();
+        for (index, line) in text.lines().enumerate() {
+            reader.record(line.trim_end_matches('\r'), index + 1)?;
+        }
+        if !reader.has_kern {
+            return Err(ScoreError::NotKern);
+        }
+        Ok(reader.builder.finish(sample_rate))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Kern,
+    Harm,
+    Other,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Spine {
+    kind: Kind,
+    part: Option<u16>,
+    /// End of the last note started in this spine.
+    busy_until: Quarters,
+}
+
+#[derive(Default)]
+struct KernReader {
+    builder: ScoreBuilder,
+    /// `None` until the first exclusive interpretation.
+    spines: Option<Vec<Spine>>,
+    has_kern: bool,
+    now: Quarters,
+    key: Option<Key>,
+}
+
+impl KernReader {
+    fn record(&mut self, line: &str, number: usize) -> Result<(), ScoreError> {
+        if line.is_empty() || line.starts_with('!') {
+            return Ok(());
+        }
+        let tokens: Vec<&str> = line.split('\t').collect();
+        let Some(spines) = self.spines.take() else {
+            if !tokens.iter().all(|t| t.starts_with("**")) {
+                return Err(ScoreError::InvalidSpines(number));
+            }
+            let spines = tokens.iter().map(|t| self.open_spine(t)).collect();
+            self.spines = Some(spines);
+            return Ok(());
+        };
+        if tokens.len() != spines.len() {
+            return Err(ScoreError::InvalidSpines(number));
+        }
+
+        if line.starts_with('*') {
+            let mut spines = spines;
+            for (spine, token) in spines.iter_mut().zip(&tokens) {
+                self.interpretation(spine, token);
+            }
+            self.spines = Some(manipulate(&spines, &tokens, number)?);
+        } else if line.starts_with('=') {
+            self.spines = Some(spines);
+        } else {
+            let mut spines = spines;
+            for (spine, token) in spines.iter_mut().zip(&tokens) {
+                self.data(spine, token, number)?;
+            }
+            self.now = spines
+                .iter()
+                .filter(|s| s.kind == Kind::Kern && s.busy_until > self.now)
+                .map(|s| s.busy_until)
+                .min()
+                .unwrap_or(self.now);
+            self.builder.reach(self.now);
+            self.spines = Some(spines);
+        }
+        Ok(())
+    }
+
+    fn open_spine(&mut self, exclusive: &str) -> Spine {
+        let mut spine = Spine { kind: Kind::Other, part: None, busy_until: self.now };
+        self.set_kind(&mut spine, exclusive);
+        spine
+    }
+
+    fn set_kind(&mut self, spine: &mut Spine, exclusive: &str) {
+        spine.kind = match exclusive {
+            "**kern" => Kind::Kern,
+            "**harm" => Kind::Harm,
+            _ => Kind::Other,
+        };
+        if spine.kind == Kind::Kern && spine.part.is_none() {
+            self.has_kern = true;
+            let number = self.builder.part_count() + 1;
+            spine.part = Some(self.builder.add_part(format!("Spine {number}")));
+        }
+    }
+
+    fn interpretation(&mut self, spine: &mut Spine, token: &str) {
+        if token.starts_with("**") {
+            self.set_kind(spine, token);
+            return;
+        }
+        let body = token.strip_prefix('*').unwrap_or(token);
+        if let Some(name) = body.strip_prefix("I\"") {
+            if let Some(part) = spine.part {
+                self.builder.rename_part(part, name.into());
+            }
+        } else if let Some(bpm) = body.strip_prefix("MM") {
+            if let Some(bpm_x1000) = parse_x1000(bpm) {
+                self.builder.tempo(self.now, bpm_x1000);
+            }
+        } else if let Some(meter) = body.strip_prefix('M') {
+            let parsed =
+                meter.split_once('/').and_then(|(n, d)| Some((n.parse().ok()?, d.parse().ok()?)));
+            if let Some((numerator, denominator)) = parsed {
+                self.builder.meter(self.now, numerator, denominator);
+            }
+        } else if let Some((name, _mode)) = body.split_once(':')
+            && let Some(key) = parse_key(name)
+        {
+            self.key = Some(key);
+            self.builder.key(self.now, key);
+        }
+    }
+
+    fn data(&mut self, spine: &mut Spine, token: &str, number: usize) -> Result<(), ScoreError> {
+        if token == "." {
+            return Ok(());
+        }
+        match spine.kind {
+            Kind::Kern => {
+                let part = spine.part.unwrap_or(0);
+                for sub in token.split(' ').filter(|s| !s.is_empty()) {
+                    let note = parse_note(sub).ok_or(ScoreError::InvalidToken(number))?;
+                    let Some(duration) = note.duration else { continue };
+                    spine.busy_until = spine.busy_until.max(self.now.add(duration));
+                    if let Some(midi) = note.midi {
+                        self.builder.note(
+                            part,
+                            self.now,
+                            duration,
+                            midi,
+                            DEFAULT_VELOCITY,
+                            note.tie,
+                        );
+                    }
+                }
+            }
+            Kind::Harm => {
+                if let Some(chord) = self.key.and_then(|key| roman(token, key)) {
+                    self.builder.chord(self.now, chord);
+                }
+            }
+            Kind::Other => {}
+        }
+        Ok(())
+    }
+}
+
+/// Apply the spine path operators of an interpretation record.
+fn manipulate(spines: &[Spine], tokens: &[&str], number: usize) -> Result<Vec<Spine>, ScoreError> {
+    let mut out = Vec::with_capacity(spines.len() + 1);
+    let mut i = 0;
+    while i < tokens.len() {
+        let spine = spines[i];
+        match tokens[i] {
+            "*^" => out.extend([spine, spine]),
+            "*v" => {
+                let mut merged = spine;
+                while tokens.get(i + 1) == Some(&"*v") {
+                    i += 1;
+                    merged.busy_until = merged.busy_until.max(spines[i].busy_until);
+                }
+                out.push(merged);
+            }
+            "*-" => {}
+            "*+" => out.extend([spine, Spine { kind: Kind::Other, part: None, ..spine }]),
+            "*x" => {
+                if tokens.get(i + 1) != Some(&"*x") {
+                    return Err(ScoreError::InvalidSpines(number));
+                }
+                out.extend([spines[i + 1], spine]);
+                i += 1;
+            }
+            _ => out.push(spine),
+        }
+        i += 1;
+    }
+    Ok(out)
+}
+
+/// Key from `E-`, `f#` and the like; lower case is minor.
+fn parse_key(name: &str) -> Option<Key> {
+    let mut chars = name.chars();
+    let letter = chars.next()?;
+    let alter = accidentals(chars.as_str())?;
+    key_from_name(letter, alter, letter.is_ascii_lowercase())
+}
+
+/// Sum of `#` (+1) and `-` (-1) signs; `None` for anything else.
+fn accidentals(text: &str) -> Option<i8> {
+    text.chars().try_fold(0i8, |acc, c| match c {
+        '#' => acc.checked_add(1),
+        '-' => acc.checked_sub(1),
+        _ => None,
+    })
+}
+
+struct KernNote {
+    /// `None` for grace notes.
+    duration: Option<Quarters>,
+    /// `None` for rests.
+    midi: Option<u8>,
+    tie: Tie,
+}
+
+/// Parse one note, chord member or rest.
+fn parse_note(token: &str) -> Option<KernNote> {
+    let mut recip = None::<(i64, i64)>;
+    let mut dots = 0;
+    let mut letter = None::<char>;
+    let mut repeats = 0i16;
+    let mut alter = 0i16;
+    let mut rest = false;
+    let mut grace = false;
+    let mut tie = Tie::default();
+
+    let mut chars = token.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '0'..='9' if recip.is_none() && letter.is_none() => {
+                let mut digits = String::from(c);
+                while let Some(d) = chars.next_if(char::is_ascii_digit) {
+                    digits.push(d);
+                }
+                let mut value = match digits.as_str() {
+                    // Breve and long: 1/2 and 1/4 of a whole note.
+                    "0" => (1, 2),
+                    "00" => (1, 4),
+                    "000" => (1, 8),
+                    _ => (digits.parse().ok()?, 1),
+                };
+                if chars.next_if_eq(&'%').is_some() {
+                    let mut den = String::new();
+                    while let Some(d) = chars.next_if(char::is_ascii_digit) {
+                        den.push(d);
+                    }
+                    value.1 *= den.parse::<i64>().ok()?;
+                }
+                recip = Some(value);
+            }
+            '.' if recip.is_some() => dots += 1,
+            'a'..='g' | 'A'..='G' => match letter {
+                None => {
+                    letter = Some(c);
+                    repeats = 1;
+                }
+                Some(l) if l == c => repeats += 1,
+                Some(_) => return None,
+            },
+            'r' => rest = true,
+            '#' => alter += 1,
+            '-' => alter -= 1,
+            'q' | 'Q' => grace = true,
+            '[' => tie.start = true,
+            ']' => tie.stop = true,
+            '_' => {
+                tie.start = true;
+                tie.stop = true;
+            }
+            _ => {}
+        }
+    }
+    if letter.is_none() && !rest {
+        return None;
+    }
+
+    // A reciprocal of n (or n%m) is 1/n (m/n) whole notes, i.e. 4m/n quarters.
+    let duration = match recip {
+        Some((n, m)) if !grace && n > 0 => {
+            let base = Quarters::new(4 * m, n);
+            // Each dot adds half of the previous value: d(2 - 1/2^dots).
+            let dotted = (1i64 << dots).checked_mul(2)?;
+            Some(base.scale(dotted - 1, 1i64 << dots))
+        }
+        _ => None,
+    };
+    let midi = match letter {
+        Some(l) if !rest => {
+            let octave = if l.is_ascii_lowercase() { 3 + repeats } else { 4 - repeats };
+            let midi = (octave + 1) * 12 + i16::from(step_semitone(l)?) + alter;
+            Some(u8::try_from(midi).ok().filter(|m| *m < 128)?)
+        }
+        _ => None,
+    };
+    Some(KernNote { duration, midi, tie })
+}
+
+/// Chord for a `**harm` numeral in `key`.
+fn roman(token: &str, key: Key) -> Option<Chord> {
+    let (head, target) = match token.split_once('/') {
+        Some((head, target)) => (head, Some(target)),
+        None => (token, None),
+    };
+    let key = match target {
+        Some(target) => {
+            let tonic = roman(target, key)?;
+            let minor = matches!(
+                tonic.kind,
+                ChordKindId::Min
+                    | ChordKindId::Min7
+                    | ChordKindId::Dim
+                    | ChordKindId::HalfDim7
+                    | ChordKindId::Dim7
+            );
+            Key::new(tonic.root, if minor { KeyMode::Minor } else { KeyMode::Major })
+        }
+        None => key,
+    };
+
+    let numeral_start = head.find(|c: char| c != '-' && c != '#')?;
+    let alter = accidentals(&head[..numeral_start])?;
+    let body = &head[numeral_start..];
+    let numeral_len =
+        body.find(|c: char| !matches!(c, 'I' | 'V' | 'i' | 'v')).unwrap_or(body.len());
+    let (numeral, marks) = body.split_at(numeral_len);
+
+    let (root, upper, marks) = if let Some(marks) = body.strip_prefix('N') {
+        // Neapolitan: major triad on the lowered second degree.
+        (key.tonic().transpose(1 + alter), true, marks)
+    } else {
+        let upper = numeral.chars().all(|c| c.is_ascii_uppercase());
+        let degree = match numeral.to_ascii_uppercase().as_str() {
+            "I" => 0,
+            "II" => 1,
+            "III" => 2,
+            "IV" => 3,
+            "V" => 4,
+            "VI" => 5,
+            "VII" => 6,
+            _ => return None,
+        };
+        if !upper && numeral.chars().any(|c| c.is_ascii_uppercase()) {
+            return None;
+        }
+        let root = key.tonic().transpose(scale_step(key.mode(), degree, upper) + alter);
+        (root, upper, marks)
+    };
+
+    let mut kind = if upper { ChordKindId::Maj } else { ChordKindId::Min };
+    let mut seventh = false;
+    let mut inversion = 0usize;
+    for c in marks.chars() {
+        match c {
+            'o' => kind = ChordKindId::Dim,
+            '+' => kind = ChordKindId::Aug,
+            '%' | 'ø' => kind = ChordKindId::HalfDim7,
+            '7' => seventh = true,
+            'b' => inversion = 1,
+            'c' => inversion = 2,
+            'd' => inversion = 3,
+            _ => {}
+        }
+    }
+    if seventh {
+        kind = match kind {
+            ChordKindId::Maj if diatonic_major_seventh(key, root) => ChordKindId::Maj7,
+            ChordKindId::Maj => ChordKindId::Dom7,
+            ChordKindId::Min => ChordKindId::Min7,
+            ChordKindId::Dim => ChordKindId::Dim7,
+            other => other,
+        };
+    }
+    let bass = match inversion {
+        0 => None,
+        n => Some(root.transpose(i8::try_from(*chord_intervals(kind).get(n)?).ok()?)),
+    };
+    Chord::with_bass_policy(root, kind, bass, BassPolicy::Any).ok()
+}
+
+/// Semitones above the tonic of a scale degree (0-based).
+fn scale_step(mode: KeyMode, degree: usize, upper: bool) -> i8 {
+    const MAJOR: [i8; 7] = [0, 2, 4, 5, 7, 9, 11];
+    const MINOR: [i8; 7] = [0, 2, 3, 5, 7, 8, 10];
+    match mode {
+        KeyMode::Major => MAJOR[degree],
+        // Lower-case vii is the leading-tone chord.
+        KeyMode::Minor if degree == 6 && !upper => 11,
+        KeyMode::Minor => MINOR[degree],
+    }
+}
+
+/// Whether the scale note a seventh above `root` is a major seventh.
+fn diatonic_major_seventh(key: Key, root: PitchClass) -> bool {
+    let major_seventh = root.transpose(11);
+    let offset = (major_seventh.as_u8() + 12 - key.tonic().as_u8()) % 12;
+    (0..7).any(|degree| scale_step(key.mode(), degree, true) as u8 == offset)
+}
@@ -0,0 +1,156 @@
+//! MIREX-style `.lab` files: one `start end label` line per event, one
+//! annotation type per file.
+//!
+//! Rules:
+//! - Fields are separated by spaces or tabs; the label is the rest of the
+//!   line and may contain spaces. Blank lines and `#` comments are skipped.
+//! - Chords are Harte labels; the writer fills gaps with `N`.
+//! - Keys are written Isophonics-style (`Key E`, `Key C#:minor`).
+//! - Segments are written as SMF marker text (`Chorus B'`).
+//! - Tempo files carry beats per minute as the label.
+//! - Beat files hold one time per line; further columns (beat numbers) are
+//!   ignored and not written.
+//! - Read events get full confidence.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use mt_core::events::{ChordEvent, KeyEvent, SegmentEvent, TempoEvent};
+use mt_core::key::KeyMode;
+use mt_core::time::SampleTime;
+
+use crate::annotation::{
+    AnnotationError, Annotations, format_harte, format_segment, format_x1000, parse_harte,
+    parse_key_label, parse_segment_label, pitch_name, samples_to_seconds, seconds_to_samples,
+};
+use crate::score::parse_x1000;
+
+/// Annotation type held by a `.lab` file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LabKind {
+    Chords,
+    Keys,
+    Segments,
+    Tempo,
+    Beats,
+}
+
+impl Annotations {
+    /// Read a `.lab` file holding `kind` events.
+    pub fn from_lab(text: &str, kind: LabKind, sample_rate: u32) -> Result<Self, AnnotationError> {
+        let mut out = Self::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = AnnotationError::InvalidLine(index + 1);
+            if kind == LabKind::Beats {
+                let time = line.split([' ', '\t']).next().unwrap_or(line);
+                out.beats.push(seconds_to_samples(time, sample_rate).ok_or(invalid)?);
+                continue;
+            }
+            let (onset, offset, label) = split_line(line, sample_rate).ok_or(invalid)?;
+            match kind {
+                LabKind::Chords => {
+                    if let Some(chord) = parse_harte(label).map_err(|_| invalid)? {
+                        out.chords.push(ChordEvent {
+                            chord,
+                            onset,
+                            offset,
+                            confidence_x1000: 1000,
+                        });
+                    }
+                }
+                LabKind::Keys => {
+                    if let Some(key) = parse_key_label(label) {
+                        out.keys.push(KeyEvent { key, position: onset, confidence_x1000: 1000 });
+                    }
+                }
+                LabKind::Segments => {
+                    if let Some((kind, label)) = parse_segment_label(label) {
+                        out.segments.push(SegmentEvent {
+                            kind,
+                            label,
+                            onset,
+                            offset,
+                            confidence_x1000: 1000,
+                        });
+                    }
+                }
+                LabKind::Tempo => {
+                    let bpm_x1000 = parse_x1000(label).ok_or(invalid)?;
+                    out.tempos.push(TempoEvent { position: onset, bpm_x1000 });
+                }
+                LabKind::Beats => unreachable!("beat lines are read above"),
+            }
+        }
+        out.sort();
+        Ok(out)
+    }
+
+    /// Write the `kind` events as a `.lab` file.
+    pub fn to_lab(&self, kind: LabKind, sample_rate: u32) -> String {
+        let mut lines: Vec<(SampleTime, SampleTime, String)> = Vec::new();
+        match kind {
+            LabKind::Chords => {
+                let mut chords = self.chords.clone();
+                chords.sort_by_key(|c| c.onset);
+                let mut cursor = SampleTime::ZERO;
+                for c in &chords {
+                    if c.onset > cursor {
+                        lines.push((cursor, c.onset, String::from("N")));
+                    }
+                    lines.push((c.onset, c.offset, format_harte(&c.chord)));
+                    cursor = cursor.max(c.offset);
+                }
+            }
+            LabKind::Keys => {
+                for (start, stop, key, _) in self.key_spans() {
+                    let mut label = format!("Key {}", pitch_name(key.tonic()));
+                    if key.mode() == KeyMode::Minor {
+                        label.push_str(":minor");
+                    }
+                    lines.push((start, stop, label));
+                }
+            }
+            LabKind::Segments => {
+                for s in &self.segments {
+                    lines.push((s.onset, s.offset, format_segment(s)));
+                }
+                lines.sort_by_key(|l| l.0);
+            }
+            LabKind::Tempo => {
+                for (start, stop, bpm_x1000) in self.tempo_spans() {
+                    lines.push((start, stop, format_x1000(bpm_x1000)));
+                }
+            }
+            LabKind::Beats => {
+                let mut out = String::new();
+                for &beat in &self.beats {
+                    let _ = writeln!(out, "{}", samples_to_seconds(beat, sample_rate));
+                }
+                return out;
+            }
+        }
+
+        let mut out = String::new();
+        for (start, stop, label) in lines {
+            let start = samples_to_seconds(start, sample_rate);
+            let stop = samples_to_seconds(stop, sample_rate);
+            let _ = writeln!(out, "{start} {stop} {label}");
+        }
+        out
+    }
+}
+
+/// `start end label` → sample times and the label.
+fn split_line(line: &str, sample_rate: u32) -> Option<(SampleTime, SampleTime, &str)> {
+    let (start, rest) = line.split_once([' ', '\t'])?;
+    let (stop, label) = rest.trim_start().split_once([' ', '\t'])?;
+    let onset = seconds_to_samples(start, sample_rate)?;
+    let offset = seconds_to_samples(stop, sample_rate)?;
+    Some((onset, offset.max(onset), label.trim()))
+}
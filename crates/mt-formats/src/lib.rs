@@ -0,0 +1,51 @@
+//! mt-formats
+//!
+//! File format readers and writers built on `mt-core` and `mt-alloc`:
+//! - SMF reader: Standard MIDI Files → per-track timed events, tempo map,
+//!   meters, keys and markers.
+//! - SMF writer: chords, keys, segments and the tempo map → a `.mid` file.
+//! - Score readers: `MusicXML` and Humdrum `**kern` → notes, tempo,
+//!   meters, keys and chord annotations (`Score`).
+//! - `MusicXML` writer: melody, chord symbols, keys, meters, tempo and
+//!   rehearsal marks → a partwise lead sheet.
+//! - Reference annotations (`Annotations`): MIREX `.lab` (Harte chords,
+//!   keys, segments), JAMS and CSV timelines, read and written.
+//! - Audio decoders (`std` only): WAV/RF64, AIFF/AIFC and FLAC →
+//!   interleaved `f32` blocks.
+//!
+//! Design:
+//! - Deterministic.
+//! - Text and binary formats work on in-memory buffers; only the audio
+//!   decoders read from `std::io`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![deny(clippy::all, clippy::pedantic)]
+#![allow(
+    clippy::module_name_repetitions,
+    clippy::missing_errors_doc,
+    clippy::must_use_candidate,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+
+extern crate alloc;
+
+pub mod annotation;
+#[cfg(feature = "std")]
+pub mod audio;
+pub mod csv;
+pub mod jams;
+mod json;
+pub mod kern;
+pub mod lab;
+pub mod musicxml;
+pub mod musicxml_writer;
+pub mod score;
+pub mod smf;
+pub mod smf_writer;
+mod xml;
+
+pub use annotation::{AnnotationError, Annotations};
+#[cfg(feature = "std")]
+pub use audio::{AudioError, AudioFileFormat, AudioFileInfo, AudioFileReader};
+pub use score::{Score, ScoreError};
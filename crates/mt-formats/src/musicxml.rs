@@ -0,0 +1,366 @@
+//! `MusicXML` reader: partwise or timewise scores → `Score`.
+//!
+//! Rules:
+//! - Uncompressed documents only; `.mxl` archives must be unpacked first.
+//!   Timewise scores are regrouped by part.
+//! - Pitches are sounding pitches: the written pitch plus the part's
+//!   `<transpose>` (chromatic steps and octave change). Key signatures
+//!   and harmony in transposing parts are moved to concert pitch too.
+//! - `<backup>` and `<forward>` move the cursor, so voices and staves
+//!   overlap as written; `<chord/>` notes share the previous onset.
+//! - Grace notes are dropped; cue notes and unpitched notes take time but
+//!   produce no event.
+//! - Ties come from `<tie>`, or `<notations><tied>` when a note has none.
+//! - Tempo comes from `<sound tempo>`, else from a `<metronome>` mark,
+//!   in quarter notes per minute.
+//! - Velocity follows the spec: dynamics 100 % is velocity 90. A note's
+//!   `dynamics` attribute wins over the last `<sound dynamics>`.
+//! - `<harmony>` kinds map to the nearest chord kind: ninths and larger
+//!   become their seventh chord, `augmented-seventh` dominant and
+//!   `major-minor` minor. Augmented sixths, `Tristan`, `pedal`, `none`
+//!   and `other` are skipped, as are function-only harmonies.
+//! - A measure lasts as far as its cursor reached, so pickups and
+//!   irregular bars keep their written length.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use mt_core::chord::{BassPolicy, Chord};
+use mt_core::chord_kind::ChordKindId;
+use mt_core::key::Key;
+use mt_core::pitch::PitchClass;
+
+use crate::score::{
+    DEFAULT_VELOCITY, Quarters, Score, ScoreBuilder, ScoreError, Tie, parse_x1000, step_semitone,
+};
+use crate::smf::key_from_signature;
+use crate::xml::{self, Element};
+
+impl Score {
+    /// Parse a `score-partwise` or `score-timewise` document.
+    pub fn from_musicxml(text: &str, sample_rate: u32) -> Result<Self, ScoreError> {
+        let root = xml::parse(text).map_err(ScoreError::MalformedXml)?;
+        let parts = match root.name.as_str() {
+            "score-partwise" => root
+                .children_named("part")
+                .map(|p| (p.attr("id").unwrap_or(""), p.children_named("measure").collect()))
+                .collect(),
+            "score-timewise" => timewise_parts(&root),
+            _ => return Err(ScoreError::NotMusicXml),
+        };
+
+        let mut builder = ScoreBuilder::default();
+        for (id, measures) in parts {
+            let part = builder.add_part(part_name(&root, id));
+            let mut reader = PartReader::new(&mut builder, part);
+            for measure in measures {
+                reader.measure(measure);
+            }
+        }
+        Ok(builder.finish(sample_rate))
+    }
+}
+
+/// Per-part lists of `<part>` elements, one per `<measure>`.
+fn timewise_parts(root: &Element) -> Vec<(&str, Vec<&Element>)> {
+    let mut parts: Vec<(&str, Vec<&Element>)> = Vec::new();
+    for part in root.children_named("measure").flat_map(|m| m.children_named("part")) {
+        let id = part.attr("id").unwrap_or("");
+        match parts.iter_mut().find(|(known, _)| *known == id) {
+            Some((_, measures)) => measures.push(part),
+            None => parts.push((id, alloc::vec![part])),
+        }
+    }
+    parts
+}
+
+/// Name from the `<part-list>`, falling back to the part id.
+fn part_name(root: &Element, id: &str) -> String {
+    let entry = root
+        .child("part-list")
+        .and_then(|list| list.children_named("score-part").find(|p| p.attr("id") == Some(id)));
+    let name = entry.and_then(|p| p.child_text("part-name")).filter(|n| !n.is_empty());
+    String::from(name.unwrap_or(id))
+}
+
+struct PartReader<'a> {
+    builder: &'a mut ScoreBuilder,
+    part: u16,
+    /// Divisions per quarter note.
+    divisions: i64,
+    /// Written to sounding pitch, in semitones.
+    transpose: i16,
+    velocity: u8,
+    measure_start: Quarters,
+    cursor: Quarters,
+    measure_end: Quarters,
+    /// Onset of the last non-chord note, for `<chord/>` notes.
+    last_onset: Quarters,
+}
+
+impl<'a> PartReader<'a> {
+    fn new(builder: &'a mut ScoreBuilder, part: u16) -> Self {
+        Self {
+            builder,
+            part,
+            divisions: 1,
+            transpose: 0,
+            velocity: DEFAULT_VELOCITY,
+            measure_start: Quarters::ZERO,
+            cursor: Quarters::ZERO,
+            measure_end: Quarters::ZERO,
+            last_onset: Quarters::ZERO,
+        }
+    }
+
+    fn measure(&mut self, measure: &Element) {
+        self.cursor = self.measure_start;
+        self.measure_end = self.measure_start;
+        for item in &measure.children {
+            match item.name.as_str() {
+                "attributes" => self.attributes(item),
+                "note" => self.note(item),
+                "backup" => {
+                    let back = self.duration(item);
+                    self.cursor = self.cursor.sub(back).max(self.measure_start);
+                }
+                "forward" => {
+                    let ahead = self.duration(item);
+                    self.advance(ahead);
+                }
+                "direction" => self.direction(item),
+                "sound" => self.sound(item, self.cursor),
+                "harmony" => self.harmony(item),
+                _ => {}
+            }
+        }
+        self.measure_start = self.measure_end;
+        self.builder.reach(self.measure_end);
+    }
+
+    fn advance(&mut self, by: Quarters) {
+        self.cursor = self.cursor.add(by);
+        self.measure_end = self.measure_end.max(self.cursor);
+    }
+
+    /// `<duration>` of `item` in quarter notes.
+    fn duration(&self, item: &Element) -> Quarters {
+        item.child_text("duration").map_or(Quarters::ZERO, |d| self.divisions_to_quarters(d))
+    }
+
+    /// A (possibly negative, possibly decimal) count of divisions.
+    fn divisions_to_quarters(&self, text: &str) -> Quarters {
+        let (sign, magnitude) = match text.trim().strip_prefix('-') {
+            Some(rest) => (-1, rest),
+            None => (1, text.trim()),
+        };
+        parse_x1000(magnitude)
+            .map_or(Quarters::ZERO, |x| Quarters::new(sign * i64::from(x), self.divisions * 1000))
+    }
+
+    fn attributes(&mut self, attributes: &Element) {
+        if let Some(d) = attributes.child_text("divisions").and_then(parse_x1000) {
+            // Decimal divisions are allowed but never seen in practice.
+            self.divisions = i64::from(d / 1000).max(1);
+        }
+        if let Some(transpose) = attributes.child("transpose") {
+            let chromatic: i16 = transpose.child_text("chromatic").and_then(parse_int).unwrap_or(0);
+            let octaves: i16 =
+                transpose.child_text("octave-change").and_then(parse_int).unwrap_or(0);
+            self.transpose = chromatic + 12 * octaves;
+        }
+        if let Some(key) = attributes.child("key")
+            && let Some(fifths) = key.child_text("fifths").and_then(parse_int::<i8>)
+        {
+            let minor = matches!(key.child_text("mode"), Some("minor" | "aeolian"));
+            let written = key_from_signature(fifths, minor);
+            let tonic = written.tonic().transpose(self.pitch_class_shift());
+            self.builder.key(self.cursor, Key::new(tonic, written.mode()));
+        }
+        if let Some(time) = attributes.child("time")
+            && let Some(beats) = time.child_text("beats")
+            && let Some(beat_type) = time.child_text("beat-type").and_then(parse_int::<u8>)
+        {
+            // Additive meters such as 3+2/8 count all their beats.
+            let beats: Option<u8> = beats.split('+').map(parse_int::<u8>).sum();
+            if let Some(beats) = beats {
+                self.builder.meter(self.cursor, beats, beat_type);
+            }
+        }
+    }
+
+    fn note(&mut self, note: &Element) {
+        if note.has("grace") {
+            return;
+        }
+        let duration = self.duration(note);
+        let start = if note.has("chord") {
+            self.last_onset
+        } else {
+            self.last_onset = self.cursor;
+            self.advance(duration);
+            self.last_onset
+        };
+        if note.has("cue") {
+            return;
+        }
+        let Some(midi) = note.child("pitch").and_then(|p| self.midi(p)) else { return };
+
+        let mut tie = Tie::default();
+        let mut ties = note.children_named("tie").peekable();
+        if ties.peek().is_none() {
+            let notations = note.children_named("notations");
+            for tied in notations.flat_map(|n| n.children_named("tied")) {
+                mark_tie(&mut tie, tied.attr("type"));
+            }
+        }
+        for t in ties {
+            mark_tie(&mut tie, t.attr("type"));
+        }
+
+        let velocity = note
+            .attr("dynamics")
+            .and_then(parse_x1000)
+            .map_or(self.velocity, velocity_from_dynamics);
+        self.builder.note(self.part, start, duration, midi, velocity, tie);
+    }
+
+    /// Written to sounding pitch class, for keys and harmony.
+    fn pitch_class_shift(&self) -> i8 {
+        self.transpose.rem_euclid(12) as i8
+    }
+
+    /// Sounding MIDI pitch of a `<pitch>`.
+    fn midi(&self, pitch: &Element) -> Option<u8> {
+        let step = pitch.child_text("step")?.chars().next().and_then(step_semitone)?;
+        // Microtonal alters round toward zero.
+        let alter = pitch
+            .child_text("alter")
+            .map_or(Some(0), |a| parse_int::<i16>(a.split('.').next()?))?;
+        let octave: i16 = pitch.child_text("octave").and_then(parse_int)?;
+        let midi = (octave + 1) * 12 + i16::from(step) + alter + self.transpose;
+        u8::try_from(midi).ok().filter(|m| *m < 128)
+    }
+
+    fn direction(&mut self, direction: &Element) {
+        let at = self.offset(direction);
+        if let Some(sound) = direction.child("sound") {
+            self.sound(sound, at);
+            if sound.attr("tempo").is_some() {
+                return;
+            }
+        }
+        let metronome =
+            direction.children_named("direction-type").find_map(|d| d.child("metronome"));
+        if let Some(bpm_x1000) = metronome.and_then(metronome_tempo) {
+            self.builder.tempo(at, bpm_x1000);
+        }
+    }
+
+    fn sound(&mut self, sound: &Element, at: Quarters) {
+        if let Some(bpm_x1000) = sound.attr("tempo").and_then(parse_x1000) {
+            self.builder.tempo(at, bpm_x1000);
+        }
+        if let Some(dynamics) = sound.attr("dynamics").and_then(parse_x1000) {
+            self.velocity = velocity_from_dynamics(dynamics);
+        }
+    }
+
+    /// Cursor moved by an `<offset>` child.
+    fn offset(&self, item: &Element) -> Quarters {
+        let offset =
+            item.child_text("offset").map_or(Quarters::ZERO, |o| self.divisions_to_quarters(o));
+        self.cursor.add(offset).max(Quarters::ZERO)
+    }
+
+    fn harmony(&mut self, harmony: &Element) {
+        let at = self.offset(harmony);
+        let Some(root) = harmony.child("root") else { return };
+        let Some(root) = spelled(root, "root-step", "root-alter") else { return };
+        let Some(kind) = harmony.child("kind") else { return };
+        let Some(kind) = chord_kind(kind.text.trim(), harmony) else { return };
+        let bass = harmony.child("bass").and_then(|b| spelled(b, "bass-step", "bass-alter"));
+        let shift = self.pitch_class_shift();
+        let (root, bass) = (root.transpose(shift), bass.map(|b| b.transpose(shift)));
+        if let Ok(chord) = Chord::with_bass_policy(root, kind, bass, BassPolicy::Any) {
+            self.builder.chord(at, chord);
+        }
+    }
+}
+
+fn mark_tie(tie: &mut Tie, kind: Option<&str>) {
+    match kind {
+        Some("start") => tie.start = true,
+        Some("stop") => tie.stop = true,
+        _ => {}
+    }
+}
+
+fn parse_int<T: core::str::FromStr>(text: &str) -> Option<T> {
+    text.trim().parse().ok()
+}
+
+/// Dynamics in thousandths of a percent → velocity (100 % is 90).
+fn velocity_from_dynamics(dynamics_x1000: u32) -> u8 {
+    (u64::from(dynamics_x1000) * 90 / 100_000).clamp(1, 127) as u8
+}
+
+/// Quarter notes per minute from a `<metronome>` beat unit and rate.
+fn metronome_tempo(metronome: &Element) -> Option<u32> {
+    let per_minute = parse_x1000(metronome.child_text("per-minute")?)?;
+    // Beat unit as a fraction of a quarter note.
+    let (mut num, mut den): (u64, u64) = match metronome.child_text("beat-unit")? {
+        "whole" => (4, 1),
+        "half" => (2, 1),
+        "quarter" => (1, 1),
+        "eighth" => (1, 2),
+        "16th" => (1, 4),
+        "32nd" => (1, 8),
+        _ => return None,
+    };
+    for _ in metronome.children_named("beat-unit-dot") {
+        // Each dot adds half of the previous value: x → 3x/2.
+        num *= 3;
+        den *= 2;
+    }
+    u32::try_from(u64::from(per_minute) * num / den).ok()
+}
+
+/// Pitch class from a step and alter child, e.g. `root-step`/`root-alter`.
+fn spelled(element: &Element, step: &str, alter: &str) -> Option<PitchClass> {
+    let natural = element.child_text(step)?.chars().next().and_then(step_semitone)?;
+    let alter =
+        element.child_text(alter).map_or(Some(0), |a| parse_int::<i8>(a.split('.').next()?))?;
+    Some(PitchClass::from_unchecked(natural).transpose(alter))
+}
+
+/// Nearest chord kind for a `<kind>` value.
+fn chord_kind(kind: &str, harmony: &Element) -> Option<ChordKindId> {
+    let kind = match kind {
+        "major" | "Neapolitan" | "" => ChordKindId::Maj,
+        "minor" | "major-minor" => ChordKindId::Min,
+        "augmented" => ChordKindId::Aug,
+        "diminished" => ChordKindId::Dim,
+        "dominant" | "dominant-ninth" | "dominant-11th" | "dominant-13th" | "augmented-seventh" => {
+            ChordKindId::Dom7
+        }
+        "major-seventh" | "major-ninth" | "major-11th" | "major-13th" => ChordKindId::Maj7,
+        "minor-seventh" | "minor-ninth" | "minor-11th" | "minor-13th" => ChordKindId::Min7,
+        "diminished-seventh" => ChordKindId::Dim7,
+        "half-diminished" => ChordKindId::HalfDim7,
+        "major-sixth" if adds_ninth(harmony) => ChordKindId::SixNine,
+        "major-sixth" => ChordKindId::Maj6,
+        "minor-sixth" => ChordKindId::Min6,
+        "suspended-second" => ChordKindId::Sus2,
+        "suspended-fourth" => ChordKindId::Sus4,
+        "power" => ChordKindId::Power5,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+fn adds_ninth(harmony: &Element) -> bool {
+    harmony.children_named("degree").any(|d| {
+        d.child_text("degree-value") == Some("9") && d.child_text("degree-type") == Some("add")
+    })
+}
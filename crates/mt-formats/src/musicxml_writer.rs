@@ -0,0 +1,759 @@
+//! `MusicXML` 4.0 (partwise) lead-sheet writer.
+//!
+//! Produces one part holding the melody, with chord symbols as `<harmony>`,
+//! key and time signatures, tempo marks and rehearsal marks from segments.
+//!
+//! Rules:
+//! - Positions become quarter-note divisions through the `TempoMap`, then
+//!   snap to a grid of `subdivision` notes per quarter. With `triplets`, a
+//!   beat switches to an eighth-triplet grid when that fits a boundary
+//!   better; the whole beat is then notated as a 3:2 tuplet.
+//! - Notes form a single voice: notes with the same quantized onset become
+//!   a chord, and a note still sounding at the next onset is cut there.
+//! - Durations split at barlines and beats into notation values (dotted
+//!   where possible), joined by ties; gaps become rests.
+//! - Time signatures change at the first barline at or after the meter
+//!   event, key signatures at the nearest barline. Denominators above 32
+//!   or not powers of two are skipped.
+//! - Pitches are spelled with sharps in sharp keys and flats in flat keys.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use mt_alloc::TempoMap;
+use mt_core::chord::Chord;
+use mt_core::chord_kind::ChordKindId;
+use mt_core::events::{ChordEvent, KeyEvent, NoteEvent, SegmentEvent};
+use mt_core::pitch::PitchClass;
+use mt_core::time::SampleTime;
+
+use crate::smf_writer::{key_signature, marker_text};
+
+/// Divisions per quarter note: divisible by 8 (32nd notes) and 3 (triplets).
+pub const DIVISIONS: u16 = 24;
+
+const BEAT: u64 = DIVISIONS as u64;
+const TRIPLET_STEP: u64 = BEAT / 3;
+
+#[derive(Clone, Debug)]
+pub struct MusicXmlExportConfig {
+    /// Work title; omitted when empty.
+    pub title: String,
+    pub part_name: String,
+    /// Grid notes per quarter: 1, 2, 4 or 8 (other values round down).
+    pub subdivision: u8,
+    /// Allow eighth-note triplets.
+    pub triplets: bool,
+}
+
+impl Default for MusicXmlExportConfig {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            part_name: String::from("Melody"),
+            subdivision: 4,
+            triplets: true,
+        }
+    }
+}
+
+/// Encode a lead sheet as a `MusicXML` document.
+pub fn write_musicxml(
+    tempo_map: &TempoMap,
+    notes: &[NoteEvent],
+    chords: &[ChordEvent],
+    keys: &[KeyEvent],
+    segments: &[SegmentEvent],
+    config: &MusicXmlExportConfig,
+) -> String {
+    let tick = |pos: SampleTime| tempo_map.sample_to_ticks(pos, DIVISIONS).max(0) as u64;
+
+    let raw_notes: Vec<(u64, u64, u8)> =
+        notes.iter().map(|n| (tick(n.onset), tick(n.offset), n.note.value())).collect();
+    let end = raw_notes
+        .iter()
+        .map(|n| n.1)
+        .chain(chords.iter().map(|c| tick(c.offset)))
+        .chain(segments.iter().map(|s| tick(s.offset)))
+        .max()
+        .unwrap_or(0);
+    let meters: Vec<(u64, u8, u8)> = tempo_map
+        .meter_events()
+        .iter()
+        .filter(|m| m.numerator > 0 && m.denominator.is_power_of_two() && m.denominator <= 32)
+        .map(|m| (tick(m.position), m.numerator, m.denominator))
+        .collect();
+    let bars = layout_bars(&meters, end);
+
+    let step = match config.subdivision {
+        0 | 1 => BEAT,
+        2 | 3 => BEAT / 2,
+        4..=7 => BEAT / 4,
+        _ => BEAT / 8,
+    };
+    let mut grid = Grid { bars: &bars, step, triplets: config.triplets, triplet_beats: Vec::new() };
+    for &(on, off, _) in &raw_notes {
+        grid.mark(on);
+        grid.mark(off);
+    }
+    let total_end = bars.last().map_or(0, Bar::end);
+    let items = melody(&grid, &raw_notes, total_end);
+
+    // Key signature (fifths, minor) in effect from each bar.
+    let mut bar_keys: Vec<Option<(i8, bool)>> = alloc::vec![None; bars.len()];
+    for k in keys {
+        let t = grid.snap(tick(k.position));
+        let i = grid.bar_index(t);
+        let i = if bars[i].end().saturating_sub(t) < t - bars[i].start { i + 1 } else { i };
+        if let Some(slot) = bar_keys.get_mut(i) {
+            *slot = Some(key_signature(k.key));
+        }
+    }
+    if bar_keys[0].is_none() {
+        bar_keys[0] = keys.first().map(|k| key_signature(k.key)).or(Some((0, false)));
+    }
+
+    let mut marks: Vec<(u64, Mark)> = Vec::new();
+    for s in segments {
+        marks.push((grid.snap(tick(s.onset)), Mark::Rehearsal(marker_text(s))));
+    }
+    for t in tempo_map.tempo_events() {
+        marks.push((grid.snap(tick(t.position)), Mark::Tempo(t.bpm_x1000)));
+    }
+    for c in chords {
+        marks.push((grid.snap(tick(c.onset)), Mark::Harmony(c.chord)));
+    }
+    // Stable: rehearsal, tempo, harmony at the same position.
+    marks.sort_by_key(|&(pos, _)| pos);
+
+    let mut xml = Xml { out: String::new(), depth: 0 };
+    xml.line(r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>"#);
+    xml.line(
+        r#"<!DOCTYPE score-partwise PUBLIC "-//Recordare//DTD MusicXML 4.0 Partwise//EN" "http://www.musicxml.org/dtds/partwise.dtd">"#,
+    );
+    xml.open(r#"score-partwise version="4.0""#);
+    if !config.title.is_empty() {
+        xml.open("work");
+        xml.leaf("work-title", escape(&config.title));
+        xml.close("work");
+    }
+    xml.open("part-list");
+    xml.open(r#"score-part id="P1""#);
+    xml.leaf("part-name", escape(&config.part_name));
+    xml.close("score-part");
+    xml.close("part-list");
+    xml.open(r#"part id="P1""#);
+
+    let mut writer = MeasureWriter { grid: &grid, xml, marks: &marks, next_mark: 0, fifths: 0 };
+    let mut item_index = 0;
+    for (i, bar) in bars.iter().enumerate() {
+        while items[item_index].end <= bar.start {
+            item_index += 1;
+        }
+        let time_changed =
+            i == 0 || (bars[i - 1].beats, bars[i - 1].beat_type) != (bar.beats, bar.beat_type);
+        writer.measure(
+            i,
+            bar,
+            &items[item_index..],
+            bar_keys[i],
+            time_changed,
+            i + 1 == bars.len(),
+        );
+    }
+
+    let mut xml = writer.xml;
+    xml.close("part");
+    xml.close("score-partwise");
+    xml.out
+}
+
+/// `MusicXML` `<kind>` value and display text for a chord kind.
+pub(crate) fn harmony_kind(kind: ChordKindId) -> (&'static str, &'static str) {
+    match kind {
+        ChordKindId::Maj => ("major", ""),
+        ChordKindId::Min => ("minor", "m"),
+        ChordKindId::Dim => ("diminished", "dim"),
+        ChordKindId::Aug => ("augmented", "aug"),
+        ChordKindId::Sus2 => ("suspended-second", "sus2"),
+        ChordKindId::Sus4 => ("suspended-fourth", "sus4"),
+        ChordKindId::Power5 => ("power", "5"),
+        ChordKindId::Maj7 => ("major-seventh", "maj7"),
+        ChordKindId::Min7 => ("minor-seventh", "m7"),
+        ChordKindId::Dom7 => ("dominant", "7"),
+        ChordKindId::HalfDim7 => ("half-diminished", "m7b5"),
+        ChordKindId::Dim7 => ("diminished-seventh", "dim7"),
+        ChordKindId::Maj6 => ("major-sixth", "6"),
+        ChordKindId::Min6 => ("minor-sixth", "m6"),
+        // A major sixth with an added ninth `<degree>`.
+        ChordKindId::SixNine => ("major-sixth", "6/9"),
+    }
+}
+
+/// Step letter and alter for `pc`, using sharps unless `fifths` is negative.
+fn spell(pc: PitchClass, fifths: i8) -> (&'static str, i8) {
+    #[rustfmt::skip]
+    const SHARPS: [(&str, i8); 12] = [
+        ("C", 0), ("C", 1), ("D", 0), ("D", 1), ("E", 0), ("F", 0),
+        ("F", 1), ("G", 0), ("G", 1), ("A", 0), ("A", 1), ("B", 0),
+    ];
+    #[rustfmt::skip]
+    const FLATS: [(&str, i8); 12] = [
+        ("C", 0), ("D", -1), ("D", 0), ("E", -1), ("E", 0), ("F", 0),
+        ("G", -1), ("G", 0), ("A", -1), ("A", 0), ("B", -1), ("B", 0),
+    ];
+    let table = if fifths < 0 { &FLATS } else { &SHARPS };
+    table[usize::from(pc.as_u8())]
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Bar {
+    start: u64,
+    len: u64,
+    beats: u8,
+    beat_type: u8,
+}
+
+impl Bar {
+    fn end(&self) -> u64 {
+        self.start + self.len
+    }
+}
+
+/// Bars covering `[0, end)` (at least one), 4/4 until the first meter.
+fn layout_bars(meters: &[(u64, u8, u8)], end: u64) -> Vec<Bar> {
+    let mut bars = Vec::new();
+    let (mut beats, mut beat_type) = (4u8, 4u8);
+    let mut next = 0;
+    let mut pos = 0;
+    loop {
+        while let Some(&(_, num, den)) = meters.get(next).filter(|m| m.0 <= pos) {
+            (beats, beat_type) = (num, den);
+            next += 1;
+        }
+        let len = u64::from(beats) * BEAT * 4 / u64::from(beat_type);
+        bars.push(Bar { start: pos, len, beats, beat_type });
+        pos += len;
+        if pos >= end {
+            return bars;
+        }
+    }
+}
+
+/// Quantization grid over the bar layout.
+struct Grid<'a> {
+    bars: &'a [Bar],
+    /// Binary grid step in divisions.
+    step: u64,
+    triplets: bool,
+    /// Starts of beats notated as triplets, sorted.
+    triplet_beats: Vec<u64>,
+}
+
+impl Grid<'_> {
+    fn bar_index(&self, t: u64) -> usize {
+        self.bars.partition_point(|b| b.start <= t).saturating_sub(1)
+    }
+
+    /// Start and length of the beat holding `t`; the last beat of a bar
+    /// may be short (3/8).
+    fn beat(&self, t: u64) -> (u64, u64) {
+        let bar = &self.bars[self.bar_index(t)];
+        let start = bar.start + (t.saturating_sub(bar.start) / BEAT) * BEAT;
+        (start, bar.end().saturating_sub(start).min(BEAT))
+    }
+
+    fn is_triplet(&self, beat_start: u64) -> bool {
+        self.triplet_beats.binary_search(&beat_start).is_ok()
+    }
+
+    /// First pass: note whether `t` sits closer to the triplet grid.
+    fn mark(&mut self, t: u64) {
+        let (start, len) = self.beat(t);
+        if !self.triplets || len < BEAT {
+            return;
+        }
+        let x = t - start;
+        let binary = round_to(x, self.step).min(len);
+        let triplet = round_to(x, TRIPLET_STEP);
+        if !triplet.is_multiple_of(self.step)
+            && x.abs_diff(triplet) < x.abs_diff(binary)
+            && let Err(i) = self.triplet_beats.binary_search(&start)
+        {
+            self.triplet_beats.insert(i, start);
+        }
+    }
+
+    fn unit(&self, beat_start: u64) -> u64 {
+        if self.is_triplet(beat_start) { TRIPLET_STEP } else { self.step }
+    }
+
+    fn snap(&self, t: u64) -> u64 {
+        let (start, len) = self.beat(t);
+        start + round_to(t - start, self.unit(start)).min(len)
+    }
+}
+
+fn round_to(x: u64, unit: u64) -> u64 {
+    (x + unit / 2) / unit * unit
+}
+
+/// A note group or rest (empty `pitches`) on the quantized timeline.
+struct Item {
+    start: u64,
+    end: u64,
+    pitches: Vec<u8>,
+}
+
+/// Quantize notes into a gapless sequence of items covering `[0, end)`.
+fn melody(grid: &Grid<'_>, notes: &[(u64, u64, u8)], end: u64) -> Vec<Item> {
+    let mut snapped: Vec<(u64, u64, u8)> =
+        notes.iter().map(|&(on, off, p)| (grid.snap(on), grid.snap(off), p)).collect();
+    snapped.sort_unstable();
+
+    let mut groups: Vec<Item> = Vec::new();
+    for (on, off, pitch) in snapped {
+        match groups.last_mut() {
+            Some(g) if g.start == on => {
+                g.end = g.end.max(off);
+                if !g.pitches.contains(&pitch) {
+                    g.pitches.push(pitch);
+                }
+            }
+            _ => groups.push(Item { start: on, end: off, pitches: alloc::vec![pitch] }),
+        }
+    }
+
+    let mut items = Vec::new();
+    let mut cursor = 0;
+    for i in 0..groups.len() {
+        let next = groups.get(i + 1).map_or(end, |g| g.start).min(end);
+        let g = &mut groups[i];
+        let mut stop = g.end.min(next);
+        if stop <= g.start {
+            // Notes shorter than the grid keep one grid unit.
+            stop = (g.start + grid.unit(grid.beat(g.start).0)).min(next);
+        }
+        if stop <= g.start {
+            continue;
+        }
+        if g.start > cursor {
+            items.push(Item { start: cursor, end: g.start, pitches: Vec::new() });
+        }
+        items.push(Item { start: g.start, end: stop, pitches: core::mem::take(&mut g.pitches) });
+        cursor = stop;
+    }
+    if cursor < end {
+        items.push(Item { start: cursor, end, pitches: Vec::new() });
+    }
+    items
+}
+
+#[derive(Clone, Copy, Debug)]
+struct NoteValue {
+    name: &'static str,
+    dots: u8,
+    triplet: bool,
+}
+
+const fn value(name: &'static str, dots: u8) -> NoteValue {
+    NoteValue { name, dots, triplet: false }
+}
+
+const BINARY_VALUES: [(u64, NoteValue); 11] = [
+    (144, value("whole", 1)),
+    (96, value("whole", 0)),
+    (72, value("half", 1)),
+    (48, value("half", 0)),
+    (36, value("quarter", 1)),
+    (24, value("quarter", 0)),
+    (18, value("eighth", 1)),
+    (12, value("eighth", 0)),
+    (9, value("16th", 1)),
+    (6, value("16th", 0)),
+    (3, value("32nd", 0)),
+];
+
+const TRIPLET_VALUES: [(u64, NoteValue); 2] = [
+    (16, NoteValue { name: "quarter", dots: 0, triplet: true }),
+    (8, NoteValue { name: "eighth", dots: 0, triplet: true }),
+];
+
+fn pick(table: &[(u64, NoteValue)], limit: u64) -> (u64, NoteValue) {
+    table.iter().copied().find(|&(d, _)| d <= limit).unwrap_or((limit, table[table.len() - 1].1))
+}
+
+/// One notated note or rest inside a bar.
+struct Piece<'a> {
+    start: u64,
+    duration: u64,
+    value: Option<NoteValue>,
+    pitches: &'a [u8],
+    tie: Span,
+    tuplet: Span,
+}
+
+/// Whether a piece starts and/or ends a tie or tuplet bracket.
+#[derive(Clone, Copy, Debug, Default)]
+struct Span {
+    start: bool,
+    stop: bool,
+}
+
+enum Mark {
+    Rehearsal(String),
+    Tempo(u32),
+    Harmony(Chord),
+}
+
+struct MeasureWriter<'a> {
+    grid: &'a Grid<'a>,
+    xml: Xml,
+    marks: &'a [(u64, Mark)],
+    next_mark: usize,
+    fifths: i8,
+}
+
+impl MeasureWriter<'_> {
+    fn measure(
+        &mut self,
+        index: usize,
+        bar: &Bar,
+        items: &[Item],
+        key: Option<(i8, bool)>,
+        time_changed: bool,
+        last: bool,
+    ) {
+        self.xml.open(&format!(r#"measure number="{}""#, index + 1));
+        if index == 0 || key.is_some() || time_changed {
+            self.xml.open("attributes");
+            if index == 0 {
+                self.xml.leaf("divisions", DIVISIONS);
+            }
+            if let Some((fifths, minor)) = key {
+                self.fifths = fifths;
+                self.xml.open("key");
+                self.xml.leaf("fifths", fifths);
+                self.xml.leaf("mode", if minor { "minor" } else { "major" });
+                self.xml.close("key");
+            }
+            if time_changed {
+                self.xml.open("time");
+                self.xml.leaf("beats", bar.beats);
+                self.xml.leaf("beat-type", bar.beat_type);
+                self.xml.close("time");
+            }
+            if index == 0 {
+                self.xml.open("clef");
+                self.xml.leaf("sign", "G");
+                self.xml.leaf("line", 2);
+                self.xml.close("clef");
+            }
+            self.xml.close("attributes");
+        }
+
+        for piece in self.pieces(bar, items) {
+            self.marks_before(piece.start, piece.start + piece.duration);
+            self.note(&piece);
+        }
+
+        if last {
+            self.xml.open(r#"barline location="right""#);
+            self.xml.leaf("bar-style", "light-heavy");
+            self.xml.close("barline");
+        }
+        self.xml.close("measure");
+    }
+
+    fn pieces<'i>(&self, bar: &Bar, items: &'i [Item]) -> Vec<Piece<'i>> {
+        let mut pieces = Vec::new();
+        for item in items.iter().take_while(|i| i.start < bar.end()) {
+            let from = item.start.max(bar.start);
+            let to = item.end.min(bar.end());
+            if item.pitches.is_empty() && from == bar.start && to == bar.end() {
+                // Whole-bar rest.
+                pieces.push(Piece {
+                    start: from,
+                    duration: bar.len,
+                    value: None,
+                    pitches: &[],
+                    tie: Span::default(),
+                    tuplet: Span::default(),
+                });
+                continue;
+            }
+            let mut p = from;
+            while p < to {
+                let (duration, value) = self.value_at(bar, p, to);
+                let tied = !item.pitches.is_empty();
+                pieces.push(Piece {
+                    start: p,
+                    duration,
+                    value: Some(value),
+                    pitches: &item.pitches,
+                    tie: Span {
+                        start: tied && p + duration < item.end,
+                        stop: tied && p > item.start,
+                    },
+                    tuplet: Span::default(),
+                });
+                p += duration;
+            }
+        }
+
+        // Bracket each triplet beat.
+        for i in 0..pieces.len() {
+            if !pieces[i].value.is_some_and(|v| v.triplet) {
+                continue;
+            }
+            let beat = self.grid.beat(pieces[i].start).0;
+            let same_beat = |p: &Piece<'_>| {
+                p.value.is_some_and(|v| v.triplet) && self.grid.beat(p.start).0 == beat
+            };
+            pieces[i].tuplet = Span {
+                start: i == 0 || !same_beat(&pieces[i - 1]),
+                stop: pieces.get(i + 1).is_none_or(|p| !same_beat(p)),
+            };
+        }
+        pieces
+    }
+
+    /// Longest notation value starting at `p` and ending by `to`.
+    fn value_at(&self, bar: &Bar, p: u64, to: u64) -> (u64, NoteValue) {
+        let (beat, len) = self.grid.beat(p);
+        if self.grid.is_triplet(beat) {
+            let limit = to.min(beat + len) - p;
+            if p == beat && limit == BEAT {
+                return (BEAT, value("quarter", 0));
+            }
+            return pick(&TRIPLET_VALUES, limit);
+        }
+        let stop = if p == beat {
+            // On the beat a value may span beats, but not into a triplet one.
+            let next_triplet = self.grid.triplet_beats.iter().copied().find(|&b| b > p);
+            to.min(bar.end()).min(next_triplet.unwrap_or(u64::MAX))
+        } else {
+            to.min(beat + len)
+        };
+        pick(&BINARY_VALUES, stop - p)
+    }
+
+    /// Emit marks positioned in `[start, end)`, offset from `start`.
+    fn marks_before(&mut self, start: u64, end: u64) {
+        while let Some((pos, mark)) = self.marks.get(self.next_mark).filter(|m| m.0 < end) {
+            self.next_mark += 1;
+            let offset = pos.saturating_sub(start);
+            match mark {
+                Mark::Rehearsal(text) => {
+                    self.xml.open(r#"direction placement="above""#);
+                    self.xml.open("direction-type");
+                    self.xml.leaf("rehearsal", escape(text));
+                    self.xml.close("direction-type");
+                    self.offset(offset);
+                    self.xml.close("direction");
+                }
+                Mark::Tempo(bpm_x1000) => {
+                    let bpm = format_bpm(*bpm_x1000);
+                    self.xml.open(r#"direction placement="above""#);
+                    self.xml.open("direction-type");
+                    self.xml.open("metronome");
+                    self.xml.leaf("beat-unit", "quarter");
+                    self.xml.leaf("per-minute", &bpm);
+                    self.xml.close("metronome");
+                    self.xml.close("direction-type");
+                    self.offset(offset);
+                    self.xml.line(&format!(r#"<sound tempo="{bpm}"/>"#));
+                    self.xml.close("direction");
+                }
+                Mark::Harmony(chord) => {
+                    self.harmony(*chord);
+                    self.offset(offset);
+                    self.xml.close("harmony");
+                }
+            }
+        }
+    }
+
+    fn offset(&mut self, offset: u64) {
+        if offset > 0 {
+            self.xml.leaf("offset", offset);
+        }
+    }
+
+    /// Opens `<harmony>`; the caller closes it.
+    fn harmony(&mut self, chord: Chord) {
+        let (kind, text) = harmony_kind(chord.kind);
+        self.xml.open("harmony");
+        let (step, alter) = spell(chord.root, self.fifths);
+        self.xml.open("root");
+        self.xml.leaf("root-step", step);
+        if alter != 0 {
+            self.xml.leaf("root-alter", alter);
+        }
+        self.xml.close("root");
+        if text.is_empty() {
+            self.xml.leaf("kind", kind);
+        } else {
+            self.xml.line(&format!(r#"<kind text="{}">{kind}</kind>"#, escape(text)));
+        }
+        if let Some(bass) = chord.bass.filter(|&b| b != chord.root) {
+            let (step, alter) = spell(bass, self.fifths);
+            self.xml.open("bass");
+            self.xml.leaf("bass-step", step);
+            if alter != 0 {
+                self.xml.leaf("bass-alter", alter);
+            }
+            self.xml.close("bass");
+        }
+        if chord.kind == ChordKindId::SixNine {
+            self.xml.open("degree");
+            self.xml.leaf("degree-value", 9);
+            self.xml.leaf("degree-alter", 0);
+            self.xml.leaf("degree-type", "add");
+            self.xml.close("degree");
+        }
+    }
+
+    fn note(&mut self, piece: &Piece<'_>) {
+        if piece.pitches.is_empty() {
+            self.xml.open("note");
+            match piece.value {
+                None => self.xml.line(r#"<rest measure="yes"/>"#),
+                Some(_) => self.xml.line("<rest/>"),
+            }
+            self.xml.leaf("duration", piece.duration);
+            self.xml.leaf("voice", 1);
+            if let Some(value) = piece.value {
+                self.value(value);
+            }
+            self.tuplet(piece, true);
+            self.xml.close("note");
+            return;
+        }
+
+        for (i, &midi) in piece.pitches.iter().enumerate() {
+            self.xml.open("note");
+            if i > 0 {
+                self.xml.line("<chord/>");
+            }
+            let (step, alter) = spell(PitchClass::from_unchecked(midi % 12), self.fifths);
+            self.xml.open("pitch");
+            self.xml.leaf("step", step);
+            if alter != 0 {
+                self.xml.leaf("alter", alter);
+            }
+            self.xml.leaf("octave", i16::from(midi / 12) - 1);
+            self.xml.close("pitch");
+            self.xml.leaf("duration", piece.duration);
+            if piece.tie.stop {
+                self.xml.line(r#"<tie type="stop"/>"#);
+            }
+            if piece.tie.start {
+                self.xml.line(r#"<tie type="start"/>"#);
+            }
+            self.xml.leaf("voice", 1);
+            if let Some(value) = piece.value {
+                self.value(value);
+            }
+            self.tuplet(piece, i == 0);
+            self.xml.close("note");
+        }
+    }
+
+    /// `<type>`, `<dot/>` and `<time-modification>`.
+    fn value(&mut self, value: NoteValue) {
+        self.xml.leaf("type", value.name);
+        for _ in 0..value.dots {
+            self.xml.line("<dot/>");
+        }
+        if value.triplet {
+            self.xml.open("time-modification");
+            self.xml.leaf("actual-notes", 3);
+            self.xml.leaf("normal-notes", 2);
+            self.xml.close("time-modification");
+        }
+    }
+
+    /// `<notations>` with ties and, on the first note of a chord, tuplet
+    /// brackets.
+    fn tuplet(&mut self, piece: &Piece<'_>, first: bool) {
+        let tuplet_start = first && piece.tuplet.start;
+        let tuplet_stop = first && piece.tuplet.stop;
+        if !(piece.tie.stop || piece.tie.start || tuplet_start || tuplet_stop) {
+            return;
+        }
+        self.xml.open("notations");
+        if piece.tie.stop {
+            self.xml.line(r#"<tied type="stop"/>"#);
+        }
+        if piece.tie.start {
+            self.xml.line(r#"<tied type="start"/>"#);
+        }
+        if tuplet_start {
+            self.xml.line(r#"<tuplet type="start" bracket="yes"/>"#);
+        }
+        if tuplet_stop {
+            self.xml.line(r#"<tuplet type="stop"/>"#);
+        }
+        self.xml.close("notations");
+    }
+}
+
+/// BPM with up to three decimals, trailing zeros trimmed.
+fn format_bpm(bpm_x1000: u32) -> String {
+    let whole = bpm_x1000 / 1000;
+    let frac = bpm_x1000 % 1000;
+    if frac == 0 {
+        return format!("{whole}");
+    }
+    let digits = format!("{frac:03}");
+    format!("{whole}.{}", digits.trim_end_matches('0'))
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Indented XML lines.
+struct Xml {
+    out: String,
+    depth: usize,
+}
+
+impl Xml {
+    fn line(&mut self, text: &str) {
+        for _ in 0..self.depth {
+            self.out.push_str("  ");
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    /// `<tag attrs...>`; `close` takes the bare name.
+    fn open(&mut self, tag: &str) {
+        self.line(&format!("<{tag}>"));
+        self.depth += 1;
+    }
+
+    fn close(&mut self, name: &str) {
+        self.depth -= 1;
+        self.line(&format!("</{name}>"));
+    }
+
+    fn leaf(&mut self, name: &str, text: impl Display) {
+        self.line(&format!("<{name}>{text}</{name}>"));
+    }
+}
@@ -0,0 +1,363 @@
+//! Symbolic scores imported from notation formats (`MusicXML`, Humdrum
+//! `**kern`).
+//!
+//! Rules:
+//! - Readers place events in quarter notes as exact fractions; a tempo map
+//!   seeded from the score's tempo marks (120 BPM until the first) turns
+//!   them into `SampleTime`.
+//! - Tied notes merge into one `NoteEvent`. Notes are sorted by onset,
+//!   track and pitch, with ids from 1; `TrackId(i)` is the i-th part.
+//! - When several parts repeat a meter, key or chord at one position, the
+//!   first part's event is kept.
+//! - A chord annotation lasts until the next one; the last one until the
+//!   end of the score, trailing rests included.
+//! - Repeats and endings are read as written, not unfolded.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+
+use mt_alloc::TempoMap;
+use mt_core::chord::Chord;
+use mt_core::events::{ChordEvent, KeyEvent, MeterEvent, NoteEvent, NoteId, TempoEvent, TrackId};
+use mt_core::key::Key;
+use mt_core::pitch::MidiNote;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScoreError {
+    /// XML that is not well formed, at this byte offset.
+    MalformedXml(usize),
+    /// Root element is neither `score-partwise` nor `score-timewise`.
+    NotMusicXml,
+    /// No `**kern` spine.
+    NotKern,
+    /// Token that cannot be read, on this line (1-based).
+    InvalidToken(usize),
+    /// Record whose token count does not match the open spines, or a bad
+    /// spine manipulator, on this line (1-based).
+    InvalidSpines(usize),
+}
+
+impl fmt::Display for ScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MalformedXml(at) => write!(f, "malformed XML at byte {at}"),
+            Self::NotMusicXml => write!(f, "not a MusicXML score"),
+            Self::NotKern => write!(f, "no **kern spine"),
+            Self::InvalidToken(line) => write!(f, "invalid token on line {line}"),
+            Self::InvalidSpines(line) => write!(f, "invalid spine structure on line {line}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ScoreError {}
+
+/// A parsed score.
+#[derive(Clone, Debug)]
+pub struct Score {
+    pub sample_rate: u32,
+    /// Part names in score order.
+    pub parts: Vec<String>,
+    pub notes: Vec<NoteEvent>,
+    pub tempos: Vec<TempoEvent>,
+    pub meters: Vec<MeterEvent>,
+    /// Keys stated in the score, with full confidence.
+    pub keys: Vec<KeyEvent>,
+    /// Chord symbols or Roman numerals written in the score.
+    pub chords: Vec<ChordEvent>,
+}
+
+impl Score {
+    /// Tempo map seeded with the score's tempo and meter events.
+    pub fn tempo_map(&self) -> TempoMap {
+        let mut map = TempoMap::new(self.sample_rate);
+        for &ev in &self.tempos {
+            map.push_tempo(ev);
+        }
+        for &ev in &self.meters {
+            map.push_meter(ev);
+        }
+        map
+    }
+}
+
+/// Velocity for notes without a dynamic marking.
+pub(crate) const DEFAULT_VELOCITY: u8 = 80;
+
+/// Position or duration in quarter notes, as an exact fraction.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Quarters {
+    num: i64,
+    den: i64,
+}
+
+impl Quarters {
+    pub const ZERO: Self = Self { num: 0, den: 1 };
+
+    /// `num / den` quarters; `den` must not be zero.
+    pub fn new(num: i64, den: i64) -> Self {
+        debug_assert!(den != 0, "zero denominator");
+        let sign = if den < 0 { -1 } else { 1 };
+        let g = i64::try_from(gcd(num.unsigned_abs(), den.unsigned_abs()).max(1)).unwrap_or(1);
+        Self { num: sign * num / g, den: sign * den / g }
+    }
+
+    pub fn add(self, other: Self) -> Self {
+        Self::new(self.num * other.den + other.num * self.den, self.den * other.den)
+    }
+
+    pub fn sub(self, other: Self) -> Self {
+        Self::new(self.num * other.den - other.num * self.den, self.den * other.den)
+    }
+
+    pub fn scale(self, num: i64, den: i64) -> Self {
+        Self::new(self.num * num, self.den * den)
+    }
+}
+
+impl Default for Quarters {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl PartialEq for Quarters {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Quarters {}
+
+impl PartialOrd for Quarters {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Quarters {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (i128::from(self.num) * i128::from(other.den))
+            .cmp(&(i128::from(other.num) * i128::from(self.den)))
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Tie flags of a written note.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Tie {
+    pub start: bool,
+    pub stop: bool,
+}
+
+struct RawNote {
+    part: u16,
+    start: Quarters,
+    end: Quarters,
+    midi: u8,
+    velocity: u8,
+}
+
+/// Collects events in quarter notes; `finish` converts them to a `Score`.
+#[derive(Default)]
+pub(crate) struct ScoreBuilder {
+    parts: Vec<String>,
+    notes: Vec<RawNote>,
+    /// Open ties: (part, pitch) → index into `notes`.
+    ties: BTreeMap<(u16, u8), usize>,
+    tempos: Vec<(Quarters, u32)>,
+    meters: Vec<(Quarters, u8, u8)>,
+    keys: Vec<(Quarters, Key)>,
+    chords: Vec<(Quarters, Chord)>,
+    /// Furthest position reached, rests included.
+    end: Quarters,
+}
+
+impl ScoreBuilder {
+    /// Register a part; returns its index.
+    pub fn add_part(&mut self, name: String) -> u16 {
+        self.parts.push(name);
+        (self.parts.len() - 1) as u16
+    }
+
+    pub fn part_count(&self) -> usize {
+        self.parts.len()
+    }
+
+    pub fn rename_part(&mut self, part: u16, name: String) {
+        if let Some(slot) = self.parts.get_mut(usize::from(part)) {
+            *slot = name;
+        }
+    }
+
+    /// Add a note; a tie stop extends the open tie on the same pitch if it
+    /// ends exactly at `start`.
+    pub fn note(
+        &mut self,
+        part: u16,
+        start: Quarters,
+        duration: Quarters,
+        midi: u8,
+        velocity: u8,
+        tie: Tie,
+    ) {
+        let key = (part, midi);
+        let end = start.add(duration);
+        let open = self.ties.get(&key).copied().filter(|&i| self.notes[i].end == start);
+        let index = if let Some(i) = open.filter(|_| tie.stop) {
+            self.notes[i].end = end;
+            i
+        } else {
+            self.notes.push(RawNote { part, start, end, midi, velocity });
+            self.notes.len() - 1
+        };
+        if tie.start {
+            self.ties.insert(key, index);
+        } else {
+            self.ties.remove(&key);
+        }
+    }
+
+    /// Mark the score as lasting at least until `at`.
+    pub fn reach(&mut self, at: Quarters) {
+        self.end = self.end.max(at);
+    }
+
+    pub fn tempo(&mut self, at: Quarters, bpm_x1000: u32) {
+        if bpm_x1000 > 0 {
+            self.tempos.push((at, bpm_x1000));
+        }
+    }
+
+    pub fn meter(&mut self, at: Quarters, numerator: u8, denominator: u8) {
+        if numerator > 0 && denominator > 0 {
+            self.meters.push((at, numerator, denominator));
+        }
+    }
+
+    pub fn key(&mut self, at: Quarters, key: Key) {
+        self.keys.push((at, key));
+    }
+
+    pub fn chord(&mut self, at: Quarters, chord: Chord) {
+        self.chords.push((at, chord));
+    }
+
+    pub fn finish(mut self, sample_rate: u32) -> Score {
+        let notes_end = self.notes.iter().map(|n| n.end);
+        let end = notes_end.chain(self.chords.iter().map(|c| c.0)).fold(self.end, Ord::max);
+
+        // Stable sorts keep the first part's event at each position.
+        self.tempos.sort_by_key(|t| t.0);
+        self.tempos.dedup_by_key(|t| t.0);
+        self.meters.sort_by_key(|m| m.0);
+        self.meters.dedup_by_key(|m| m.0);
+        self.keys.sort_by_key(|k| k.0);
+        self.keys.dedup_by_key(|k| k.0);
+        self.chords.sort_by_key(|c| c.0);
+        self.chords.dedup_by_key(|c| c.0);
+
+        let mut map = TempoMap::new(sample_rate);
+        let mut tempos = Vec::with_capacity(self.tempos.len());
+        for &(at, bpm_x1000) in &self.tempos {
+            let ev = TempoEvent { position: map.beats_to_sample(at.num, at.den), bpm_x1000 };
+            map.push_tempo(ev);
+            tempos.push(ev);
+        }
+        let at = |q: Quarters| map.beats_to_sample(q.num, q.den);
+
+        let mut notes: Vec<NoteEvent> = self
+            .notes
+            .iter()
+            .filter(|n| n.end > n.start)
+            .filter_map(|n| {
+                Some(NoteEvent {
+                    id: NoteId(0),
+                    track: TrackId(n.part),
+                    onset: at(n.start),
+                    offset: at(n.end),
+                    note: MidiNote::new(n.midi).ok()?,
+                    velocity: n.velocity,
+                })
+            })
+            .collect();
+        notes.sort_by_key(|n| (n.onset, n.track.0, n.note.value()));
+        for (n, id) in notes.iter_mut().zip(1..) {
+            n.id = NoteId(id);
+        }
+
+        let chords = self
+            .chords
+            .iter()
+            .enumerate()
+            .map(|(i, &(start, chord))| {
+                let stop = self.chords.get(i + 1).map_or(end, |c| c.0).max(start);
+                ChordEvent { chord, onset: at(start), offset: at(stop), confidence_x1000: 1000 }
+            })
+            .collect();
+
+        Score {
+            sample_rate,
+            parts: self.parts,
+            notes,
+            meters: self
+                .meters
+                .iter()
+                .map(|&(q, numerator, denominator)| MeterEvent {
+                    position: at(q),
+                    numerator,
+                    denominator,
+                })
+                .collect(),
+            keys: self
+                .keys
+                .iter()
+                .map(|&(q, key)| KeyEvent { key, position: at(q), confidence_x1000: 1000 })
+                .collect(),
+            chords,
+            tempos,
+        }
+    }
+}
+
+/// Parse a decimal like `"72"` or `"72.5"` as thousandths.
+pub(crate) fn parse_x1000(text: &str) -> Option<u32> {
+    let (whole, frac) = text.trim().split_once('.').unwrap_or((text.trim(), ""));
+    let whole: u32 = if whole.is_empty() { 0 } else { whole.parse().ok()? };
+    let mut thousandths = 0;
+    for (i, c) in frac.chars().chain("000".chars()).take(3).enumerate() {
+        let digit = c.to_digit(10)?;
+        thousandths += digit * [100, 10, 1][i];
+    }
+    whole.checked_mul(1000)?.checked_add(thousandths)
+}
+
+/// Key from a tonic letter, alteration in semitones and mode.
+pub(crate) fn key_from_name(letter: char, alter: i8, minor: bool) -> Option<Key> {
+    let natural = step_semitone(letter)?;
+    let pc = (i16::from(natural) + i16::from(alter)).rem_euclid(12) as u8;
+    Key::from_semitone(pc, minor).ok()
+}
+
+/// Semitone of a natural step letter (either case).
+pub(crate) fn step_semitone(letter: char) -> Option<u8> {
+    match letter.to_ascii_uppercase() {
+        'C' => Some(0),
+        'D' => Some(2),
+        'E' => Some(4),
+        'F' => Some(5),
+        'G' => Some(7),
+        'A' => Some(9),
+        'B' => Some(11),
+        _ => None,
+    }
+}
@@ -0,0 +1,447 @@
+//! Standard MIDI File (SMF) reader.
+//!
+//! Parses format 0 and 1 files from bytes (no I/O) into timed MIDI events
+//! per track, plus the tempo, meter, key, name and marker meta events.
+//!
+//! Rules:
+//! - Ticks become `SampleTime` through a `TempoMap` seeded from every
+//!   tempo meta event in the file (120 BPM until the first one). SMPTE
+//!   divisions ignore tempo and use the frame rate directly.
+//! - Running status is supported and kept across meta and `SysEx` events,
+//!   which tolerates writers that rely on it.
+//! - `SysEx` (`F0`/`F7`) events and unknown meta events are skipped; unknown
+//!   chunk types are skipped.
+//! - Format 1 tracks get `TrackId(index)`. Format 0 has a single track, so
+//!   its notes are split by channel (`TrackId(channel)`).
+//! - Format 2 (independent sequences) is rejected.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use mt_alloc::{MidiNormalizer, TempoMap};
+use mt_core::events::{KeyEvent, MeterEvent, NoteEvent, NoteId, TempoEvent, TrackId};
+use mt_core::key::{Key, KeyMode};
+use mt_core::midi::{MidiEvent, TimedMidiEvent};
+use mt_core::pedal::PedalConfig;
+use mt_core::pitch::PitchClass;
+use mt_core::time::SampleTime;
+
+pub(crate) const META_TRACK_NAME: u8 = 0x03;
+pub(crate) const META_MARKER: u8 = 0x06;
+pub(crate) const META_END_OF_TRACK: u8 = 0x2F;
+pub(crate) const META_TEMPO: u8 = 0x51;
+pub(crate) const META_TIME_SIGNATURE: u8 = 0x58;
+pub(crate) const META_KEY_SIGNATURE: u8 = 0x59;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SmfError {
+    /// Missing or malformed `MThd` header.
+    NotMidi,
+    /// A chunk or event runs past the end of the data.
+    Truncated,
+    /// SMF format other than 0 or 1.
+    UnsupportedFormat(u16),
+    /// Data byte with no running status in effect.
+    MissingStatus,
+    /// Division of zero ticks.
+    InvalidDivision,
+}
+
+impl fmt::Display for SmfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotMidi => write!(f, "not a standard MIDI file"),
+            Self::Truncated => write!(f, "truncated MIDI file"),
+            Self::UnsupportedFormat(v) => write!(f, "unsupported MIDI file format: {v}"),
+            Self::MissingStatus => write!(f, "data byte without running status"),
+            Self::InvalidDivision => write!(f, "invalid time division"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SmfError {}
+
+/// Time base from the header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Division {
+    TicksPerQuarter(u16),
+    /// `fps` is 24, 25, 29 (29.97 drop-frame) or 30.
+    Smpte {
+        fps: u8,
+        ticks_per_frame: u8,
+    },
+}
+
+/// Marker meta event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SmfMarker {
+    pub position: SampleTime,
+    pub text: String,
+}
+
+/// One `MTrk` chunk.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SmfTrack {
+    pub track: TrackId,
+    pub name: Option<String>,
+    /// Channel voice events in file order (non-decreasing position).
+    pub events: Vec<TimedMidiEvent>,
+    /// Position of the last event (normally end-of-track).
+    pub end: SampleTime,
+}
+
+/// A parsed Standard MIDI File.
+#[derive(Clone, Debug)]
+pub struct MidiFile {
+    pub format: u16,
+    pub division: Division,
+    pub sample_rate: u32,
+    pub tracks: Vec<SmfTrack>,
+    /// Tempo changes from all tracks, in position order.
+    pub tempos: Vec<TempoEvent>,
+    /// Time signatures from all tracks, in position order.
+    pub meters: Vec<MeterEvent>,
+    /// Key signatures from all tracks, in position order.
+    pub keys: Vec<KeyEvent>,
+    pub markers: Vec<SmfMarker>,
+}
+
+impl MidiFile {
+    /// Parse SMF bytes, placing events on a timeline at `sample_rate`.
+    pub fn parse(bytes: &[u8], sample_rate: u32) -> Result<Self, SmfError> {
+        let mut r = Reader { bytes, pos: 0 };
+        if r.take(4).ok() != Some(b"MThd".as_slice()) {
+            return Err(SmfError::NotMidi);
+        }
+        let header_len = r.u32()? as usize;
+        if header_len < 6 {
+            return Err(SmfError::NotMidi);
+        }
+        let header = r.take(header_len)?;
+        let format = u16::from_be_bytes([header[0], header[1]]);
+        let track_count = u16::from_be_bytes([header[2], header[3]]);
+        let division = division(u16::from_be_bytes([header[4], header[5]]))?;
+        if format > 1 {
+            return Err(SmfError::UnsupportedFormat(format));
+        }
+
+        let mut raw_tracks = Vec::new();
+        while raw_tracks.len() < usize::from(track_count) && r.pos < r.bytes.len() {
+            let id = r.take(4)?;
+            let len = r.u32()? as usize;
+            let body = r.take(len)?;
+            if id == b"MTrk" {
+                raw_tracks.push(parse_track(body)?);
+            }
+        }
+
+        let clock = Clock::new(division, sample_rate, &raw_tracks);
+        let mut file = Self {
+            format,
+            division,
+            sample_rate,
+            tracks: Vec::with_capacity(raw_tracks.len()),
+            tempos: clock.tempos.clone(),
+            meters: Vec::new(),
+            keys: Vec::new(),
+            markers: Vec::new(),
+        };
+        for (index, raw) in raw_tracks.into_iter().enumerate() {
+            let mut track = SmfTrack {
+                track: TrackId(index as u16),
+                name: None,
+                events: Vec::new(),
+                end: SampleTime::ZERO,
+            };
+            let mut cursor = 0;
+            for (tick, ev) in raw {
+                let position = clock.position(tick, &mut cursor);
+                track.end = position;
+                match ev {
+                    RawEvent::Midi(event) => track.events.push(TimedMidiEvent::at(position, event)),
+                    RawEvent::Tempo(_) | RawEvent::EndOfTrack => {}
+                    RawEvent::TimeSignature { numerator, denominator } => {
+                        file.meters.push(MeterEvent { position, numerator, denominator });
+                    }
+                    RawEvent::KeySignature(key) => {
+                        file.keys.push(KeyEvent { key, position, confidence_x1000: 1000 });
+                    }
+                    RawEvent::TrackName(name) => {
+                        track.name.get_or_insert(name);
+                    }
+                    RawEvent::Marker(text) => file.markers.push(SmfMarker { position, text }),
+                }
+            }
+            file.tracks.push(track);
+        }
+        file.meters.sort_by_key(|m| m.position);
+        file.keys.sort_by_key(|k| k.position);
+        file.markers.sort_by_key(|m| m.position);
+        Ok(file)
+    }
+
+    /// Tempo map seeded with the file's tempo and meter events.
+    pub fn tempo_map(&self) -> TempoMap {
+        let mut map = TempoMap::new(self.sample_rate);
+        for &ev in &self.tempos {
+            map.push_tempo(ev);
+        }
+        for &ev in &self.meters {
+            map.push_meter(ev);
+        }
+        map
+    }
+
+    /// Normalize every track into `NoteEvent`s, sorted by onset.
+    ///
+    /// Notes still held at the end of a track end there. Ids are assigned
+    /// from 1 in output order.
+    pub fn notes(&self, pedal: PedalConfig) -> Vec<NoteEvent> {
+        let mut notes = Vec::new();
+        for track in &self.tracks {
+            let mut norm = MidiNormalizer::with_pedal(pedal);
+            let mut track_notes = norm.process(&track.events);
+            track_notes.extend(norm.flush(track.end));
+            if self.format != 0 {
+                for n in &mut track_notes {
+                    n.track = track.track;
+                }
+            }
+            notes.extend(track_notes);
+        }
+        notes.sort_by_key(|n| (n.onset, n.track.0, n.note.value()));
+        for (n, id) in notes.iter_mut().zip(1..) {
+            n.id = NoteId(id);
+        }
+        notes
+    }
+}
+
+#[derive(Clone, Debug)]
+enum RawEvent {
+    Midi(MidiEvent),
+    /// Microseconds per quarter note.
+    Tempo(u32),
+    TimeSignature {
+        numerator: u8,
+        denominator: u8,
+    },
+    KeySignature(Key),
+    TrackName(String),
+    Marker(String),
+    EndOfTrack,
+}
+
+/// Events of one track at absolute tick positions.
+type RawTrack = Vec<(u64, RawEvent)>;
+
+fn division(raw: u16) -> Result<Division, SmfError> {
+    let division = if raw & 0x8000 == 0 {
+        Division::TicksPerQuarter(raw)
+    } else {
+        let fps = ((raw >> 8) as u8).wrapping_neg();
+        Division::Smpte { fps, ticks_per_frame: raw as u8 }
+    };
+    match division {
+        Division::TicksPerQuarter(0) | Division::Smpte { fps: 0, .. } => {
+            Err(SmfError::InvalidDivision)
+        }
+        Division::Smpte { ticks_per_frame: 0, .. } => Err(SmfError::InvalidDivision),
+        d => Ok(d),
+    }
+}
+
+fn parse_track(body: &[u8]) -> Result<RawTrack, SmfError> {
+    let mut r = Reader { bytes: body, pos: 0 };
+    let mut out = Vec::new();
+    let mut tick = 0u64;
+    let mut running: Option<u8> = None;
+
+    while r.pos < r.bytes.len() {
+        tick += u64::from(r.varlen()?);
+        let first = r.u8()?;
+        match first {
+            0xFF => {
+                let kind = r.u8()?;
+                let len = r.varlen()? as usize;
+                let data = r.take(len)?;
+                if let Some(ev) = meta_event(kind, data) {
+                    let end = matches!(ev, RawEvent::EndOfTrack);
+                    out.push((tick, ev));
+                    if end {
+                        break;
+                    }
+                }
+            }
+            0xF0 | 0xF7 => {
+                let len = r.varlen()? as usize;
+                r.take(len)?;
+            }
+            _ => {
+                let (status, data1) = if first & 0x80 == 0 {
+                    (running.ok_or(SmfError::MissingStatus)?, first)
+                } else {
+                    running = Some(first);
+                    (first, r.u8()?)
+                };
+                let data2 = match status >> 4 {
+                    0xC | 0xD => 0,
+                    _ => r.u8()?,
+                };
+                if let Some(event) = MidiEvent::from_bytes(status, data1, data2) {
+                    out.push((tick, RawEvent::Midi(event)));
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn meta_event(kind: u8, data: &[u8]) -> Option<RawEvent> {
+    let text = || String::from_utf8_lossy(data).into_owned();
+    match (kind, data) {
+        (META_TRACK_NAME, _) => Some(RawEvent::TrackName(text())),
+        (META_MARKER, _) => Some(RawEvent::Marker(text())),
+        (META_END_OF_TRACK, _) => Some(RawEvent::EndOfTrack),
+        (META_TEMPO, &[a, b, c]) => {
+            let us = u32::from_be_bytes([0, a, b, c]);
+            (us > 0).then_some(RawEvent::Tempo(us))
+        }
+        (META_TIME_SIGNATURE, &[numerator, power, ..]) => {
+            let denominator = 1u8.checked_shl(u32::from(power)).filter(|_| power < 8)?;
+            Some(RawEvent::TimeSignature { numerator, denominator })
+        }
+        (META_KEY_SIGNATURE, &[sharps, minor]) => Some(RawEvent::KeySignature(
+            key_from_signature(i8::from_be_bytes([sharps]), minor == 1),
+        )),
+        _ => None,
+    }
+}
+
+/// Key from a signature (sharps positive, flats negative) and mode.
+pub(crate) fn key_from_signature(sharps: i8, minor: bool) -> Key {
+    // Circle of fifths from C (or A minor).
+    let major_tonic = (i32::from(sharps) * 7).rem_euclid(12) as u8;
+    let tonic = if minor { (major_tonic + 9) % 12 } else { major_tonic };
+    Key::new(PitchClass::from_unchecked(tonic), if minor { KeyMode::Minor } else { KeyMode::Major })
+}
+
+/// Tick to sample conversion for one file.
+///
+/// Tempo changes are kept as segments; `position` walks them with a
+/// caller-held cursor, so converting a track's (sorted) ticks is linear.
+struct Clock {
+    division: Division,
+    sample_rate: u32,
+    segments: Vec<TempoSegment>,
+    tempos: Vec<TempoEvent>,
+}
+
+/// Stretch of constant tempo from `position` on.
+#[derive(Clone, Copy, Debug)]
+struct TempoSegment {
+    position: SampleTime,
+    /// Beats before `position`, in units of `1 / (sr * 60_000)` beat (see
+    /// `TempoMap::beats_to_sample`).
+    units: i128,
+    bpm_x1000: i128,
+}
+
+impl Clock {
+    fn new(division: Division, sample_rate: u32, tracks: &[RawTrack]) -> Self {
+        let mut changes: Vec<(u64, u32)> = tracks
+            .iter()
+            .flatten()
+            .filter_map(|(tick, ev)| match ev {
+                RawEvent::Tempo(us) => Some((*tick, *us)),
+                _ => None,
+            })
+            .collect();
+        changes.sort_by_key(|&(tick, _)| tick);
+
+        // 120 BPM until the first tempo event.
+        let first = TempoSegment { position: SampleTime::ZERO, units: 0, bpm_x1000: 120_000 };
+        let mut clock =
+            Self { division, sample_rate, segments: alloc::vec![first], tempos: Vec::new() };
+        if let Division::TicksPerQuarter(_) = division {
+            let mut cursor = 0;
+            for (tick, us) in changes {
+                let position = clock.position(tick, &mut cursor);
+                let bpm_x1000 = ((60_000_000_000u64 + u64::from(us) / 2) / u64::from(us)) as u32;
+                let last = clock.segments[clock.segments.len() - 1];
+                let units = last.units
+                    + i128::from(position.value() - last.position.value()) * last.bpm_x1000;
+                let bpm = i128::from(bpm_x1000.max(1));
+                clock.segments.push(TempoSegment { position, units, bpm_x1000: bpm });
+                clock.tempos.push(TempoEvent { position, bpm_x1000 });
+            }
+        }
+        clock
+    }
+
+    /// Sample position of `tick`. `cursor` indexes the segment of the
+    /// previous call; start each pass over a track at 0.
+    fn position(&self, tick: u64, cursor: &mut usize) -> SampleTime {
+        let tick = i64::try_from(tick).unwrap_or(i64::MAX);
+        match self.division {
+            Division::TicksPerQuarter(tpq) => {
+                let units_per_beat = i128::from(self.sample_rate) * 60_000;
+                let target = i128::from(tick) * units_per_beat / i128::from(tpq);
+                if self.segments[*cursor].units > target {
+                    *cursor = 0;
+                }
+                while self.segments.get(*cursor + 1).is_some_and(|s| s.units <= target) {
+                    *cursor += 1;
+                }
+                let seg = self.segments[*cursor];
+                let dt = (target - seg.units) / seg.bpm_x1000;
+                SampleTime::new(seg.position.value().saturating_add(dt as i64))
+            }
+            Division::Smpte { fps, ticks_per_frame } => {
+                // 29 means 29.97 frames per second.
+                let (num, den) = if fps == 29 { (2997, 100) } else { (i128::from(fps), 1) };
+                let ticks_per_sec = num * i128::from(ticks_per_frame);
+                let samples = i128::from(tick) * i128::from(self.sample_rate) * den / ticks_per_sec;
+                SampleTime::new(samples as i64)
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], SmfError> {
+        let end = self.pos.checked_add(n).ok_or(SmfError::Truncated)?;
+        let out = self.bytes.get(self.pos..end).ok_or(SmfError::Truncated)?;
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> Result<u8, SmfError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, SmfError> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Variable-length quantity, at most four bytes.
+    fn varlen(&mut self) -> Result<u32, SmfError> {
+        let mut value = 0u32;
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            value = (value << 7) | u32::from(byte & 0x7F);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(SmfError::Truncated)
+    }
+}
@@ -0,0 +1,256 @@
+//! Standard MIDI File (SMF) writer for analysis results.
+//!
+//! Produces a format 1 file with two tracks:
+//! - Conductor: tempo and time signature from the `TempoMap`, key
+//!   signatures from `KeyEvent`s and markers from `SegmentEvent`s.
+//! - Chords: one voicing per `ChordEvent`, held from onset to offset.
+//!
+//! Rules:
+//! - Positions become ticks through the same `TempoMap`, rounded to the
+//!   nearest tick (beats are quarter notes, as in `smf::MidiFile`).
+//! - Events before the timeline start are clamped to tick 0.
+//! - Note-offs precede note-ons at the same tick, so repeated chord tones
+//!   re-strike instead of being cut.
+//! - Meters whose denominator is not a power of two are skipped.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use mt_alloc::TempoMap;
+use mt_core::chord::Chord;
+use mt_core::chord_kind::chord_intervals;
+use mt_core::events::{ChordEvent, KeyEvent, SegmentEvent, SegmentKind};
+use mt_core::key::{Key, KeyMode};
+use mt_core::pitch::PitchClass;
+use mt_core::time::SampleTime;
+
+use crate::smf::{
+    META_END_OF_TRACK, META_KEY_SIGNATURE, META_MARKER, META_TEMPO, META_TIME_SIGNATURE,
+    META_TRACK_NAME, SmfError,
+};
+
+/// How chord tones are laid out on the keyboard.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum VoicingStyle {
+    /// Chord tones stacked within an octave from the root (C3–B3), with the
+    /// bass note lowest for inversions and added below for slash chords.
+    #[default]
+    Close,
+    /// Close voicing with the second-highest tone dropped an octave.
+    Drop2,
+    /// Bass note in the C2 octave under a root-position close voicing from
+    /// C4.
+    Spread,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug)]
+pub struct SmfExportConfig {
+    /// Ticks per quarter note, 1..=32767.
+    pub ppq: u16,
+    pub voicing: VoicingStyle,
+    /// MIDI channel (0-based) of the chord track.
+    pub channel: u8,
+    pub velocity: u8,
+}
+
+impl Default for SmfExportConfig {
+    fn default() -> Self {
+        Self { ppq: 480, voicing: VoicingStyle::Close, channel: 0, velocity: 90 }
+    }
+}
+
+/// Encode analysis results as SMF bytes.
+///
+/// Fails with `SmfError::InvalidDivision` if `config.ppq` is 0 or does not
+/// fit the 15-bit header field.
+pub fn write_smf(
+    tempo_map: &TempoMap,
+    chords: &[ChordEvent],
+    keys: &[KeyEvent],
+    segments: &[SegmentEvent],
+    config: &SmfExportConfig,
+) -> Result<Vec<u8>, SmfError> {
+    if config.ppq == 0 || config.ppq > 0x7FFF {
+        return Err(SmfError::InvalidDivision);
+    }
+    let tick = |pos: SampleTime| tempo_map.sample_to_ticks(pos, config.ppq).max(0) as u64;
+
+    let mut conductor = TrackWriter::default();
+    for ev in tempo_map.tempo_events() {
+        let us = (60_000_000_000u64 + u64::from(ev.bpm_x1000) / 2) / u64::from(ev.bpm_x1000.max(1));
+        let us = us.clamp(1, 0xFF_FFFF) as u32;
+        conductor.meta(tick(ev.position), META_TEMPO, &us.to_be_bytes()[1..]);
+    }
+    for ev in tempo_map.meter_events() {
+        if ev.numerator == 0 || !ev.denominator.is_power_of_two() {
+            continue;
+        }
+        let power = ev.denominator.trailing_zeros() as u8;
+        // 24 MIDI clocks per metronome click, 8 32nd notes per quarter.
+        conductor.meta(tick(ev.position), META_TIME_SIGNATURE, &[ev.numerator, power, 24, 8]);
+    }
+    for ev in keys {
+        let (sharps, minor) = key_signature(ev.key);
+        conductor.meta(tick(ev.position), META_KEY_SIGNATURE, &[sharps as u8, u8::from(minor)]);
+    }
+    for seg in segments {
+        conductor.meta(tick(seg.onset), META_MARKER, marker_text(seg).as_bytes());
+    }
+
+    let mut chord_track = TrackWriter::default();
+    chord_track.meta(0, META_TRACK_NAME, b"Chords");
+    let channel = config.channel & 0x0F;
+    let velocity = config.velocity.clamp(1, 127);
+    for ev in chords {
+        let on = tick(ev.onset);
+        let off = tick(ev.offset).max(on);
+        for note in voicing(&ev.chord, config.voicing) {
+            chord_track.push(off, Order::NoteOff, &[0x80 | channel, note, 0x40]);
+            chord_track.push(on, Order::NoteOn, &[0x90 | channel, note, velocity]);
+        }
+    }
+
+    let mut out = Vec::new();
+    out.extend_from_slice(b"MThd");
+    out.extend_from_slice(&6u32.to_be_bytes());
+    out.extend_from_slice(&1u16.to_be_bytes());
+    out.extend_from_slice(&2u16.to_be_bytes());
+    out.extend_from_slice(&config.ppq.to_be_bytes());
+    conductor.finish(&mut out);
+    chord_track.finish(&mut out);
+    Ok(out)
+}
+
+/// MIDI note numbers (ascending, unique) for `chord` in `style`.
+pub fn voicing(chord: &Chord, style: VoicingStyle) -> Vec<u8> {
+    let root = chord.root.as_u8();
+    let bass = chord.bass.filter(|&b| b != chord.root).map(PitchClass::as_u8);
+    let close = |base: u8| -> Vec<u8> {
+        chord_intervals(chord.kind).iter().map(|iv| base + root + iv).collect()
+    };
+
+    let mut notes = match style {
+        VoicingStyle::Close | VoicingStyle::Drop2 => {
+            let mut notes = close(48);
+            if let Some(b) = bass.filter(|_| chord.is_inversion()) {
+                // Raise the tones under the bass note an octave.
+                let lowest = notes.iter().copied().find(|n| n % 12 == b).unwrap_or(48);
+                for n in &mut notes {
+                    if *n < lowest {
+                        *n += 12;
+                    }
+                }
+            }
+            notes.sort_unstable();
+            if style == VoicingStyle::Drop2 && notes.len() >= 3 {
+                let i = notes.len() - 2;
+                notes[i] -= 12;
+            }
+            if let Some(b) = bass.filter(|_| chord.is_slash()) {
+                let mut note = 36 + b;
+                while notes.iter().any(|&n| n <= note) {
+                    note -= 12;
+                }
+                notes.push(note);
+            }
+            notes
+        }
+        VoicingStyle::Spread => {
+            let mut notes = close(60);
+            notes.push(36 + bass.unwrap_or(root));
+            notes
+        }
+    };
+    notes.retain(|&n| n <= 127);
+    notes.sort_unstable();
+    notes.dedup();
+    notes
+}
+
+/// Key signature as (sharps, minor); flats are negative, from -5 to 6.
+pub(crate) fn key_signature(key: Key) -> (i8, bool) {
+    let minor = key.mode() == KeyMode::Minor;
+    // Minor keys share the signature of their relative major.
+    let major = if minor { key.tonic().transpose(3) } else { key.tonic() };
+    // Seven is its own inverse mod 12: tonic = sharps * 7, so sharps = tonic * 7.
+    let sharps = i32::from(major.as_u8()) * 7 % 12;
+    ((if sharps > 6 { sharps - 12 } else { sharps }) as i8, minor)
+}
+
+pub(crate) fn marker_text(seg: &SegmentEvent) -> String {
+    let kind = match seg.kind {
+        SegmentKind::Intro => String::from("Intro"),
+        SegmentKind::Verse => String::from("Verse"),
+        SegmentKind::Chorus => String::from("Chorus"),
+        SegmentKind::Bridge => String::from("Bridge"),
+        SegmentKind::Solo => String::from("Solo"),
+        SegmentKind::Outro => String::from("Outro"),
+        SegmentKind::Other(id) => format!("Section {id}"),
+    };
+    match seg.label {
+        Some(label) => format!("{kind} {label}"),
+        None => kind,
+    }
+}
+
+/// Tie-break for events on the same tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Order {
+    Meta,
+    NoteOff,
+    NoteOn,
+}
+
+#[derive(Default)]
+struct TrackWriter {
+    events: Vec<(u64, Order, Vec<u8>)>,
+}
+
+impl TrackWriter {
+    fn push(&mut self, tick: u64, order: Order, bytes: &[u8]) {
+        self.events.push((tick, order, bytes.to_vec()));
+    }
+
+    fn meta(&mut self, tick: u64, kind: u8, data: &[u8]) {
+        let mut bytes = Vec::with_capacity(data.len() + 6);
+        bytes.extend_from_slice(&[0xFF, kind]);
+        write_varlen(&mut bytes, data.len() as u32);
+        bytes.extend_from_slice(data);
+        self.events.push((tick, Order::Meta, bytes));
+    }
+
+    /// Append the `MTrk` chunk, ending with end-of-track.
+    fn finish(mut self, out: &mut Vec<u8>) {
+        // Stable, so same-tick events of one kind keep insertion order.
+        self.events.sort_by_key(|&(tick, order, _)| (tick, order));
+        let end = self.events.last().map_or(0, |&(tick, ..)| tick);
+        self.meta(end, META_END_OF_TRACK, &[]);
+
+        let mut body = Vec::new();
+        let mut last = 0u64;
+        for (tick, _, bytes) in &self.events {
+            write_varlen(&mut body, (tick - last).min(0x0FFF_FFFF) as u32);
+            body.extend_from_slice(bytes);
+            last = *tick;
+        }
+        out.extend_from_slice(b"MTrk");
+        out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        out.extend_from_slice(&body);
+    }
+}
+
+/// Variable-length quantity; `value` must fit in 28 bits.
+fn write_varlen(out: &mut Vec<u8>, value: u32) {
+    let mut shift = 21;
+    while shift > 0 && value >> shift == 0 {
+        shift -= 7;
+    }
+    while shift > 0 {
+        out.push(0x80 | (value >> shift) as u8 & 0x7F);
+        shift -= 7;
+    }
+    out.push(value as u8 & 0x7F);
+}
@@ -0,0 +1,203 @@
+//! Minimal XML tree parser for score import.
+//!
+//! Rules:
+//! - Builds a tree of elements with attributes and concatenated direct text;
+//!   the order of text relative to child elements is not kept.
+//! - The XML declaration, processing instructions, comments and the
+//!   DOCTYPE (including an internal subset) are skipped; CDATA is text.
+//! - The predefined and numeric character references are decoded; other
+//!   entity references are kept verbatim.
+//! - No namespaces, validation or encodings other than UTF-8.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Element {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+
+impl Element {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.child(name).is_some()
+    }
+
+    /// Trimmed text of the first child called `name`.
+    pub fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|c| c.text.trim())
+    }
+}
+
+/// Parse a document into its root element; the error is a byte offset.
+pub(crate) fn parse(doc: &str) -> Result<Element, usize> {
+    let bytes = doc.as_bytes();
+    let mut pos = if doc.starts_with('\u{feff}') { 3 } else { 0 };
+    let mut stack: Vec<Element> = Vec::new();
+    let mut root: Option<Element> = None;
+
+    while pos < bytes.len() {
+        if bytes[pos] != b'<' {
+            let end = find(bytes, pos, b"<").unwrap_or(bytes.len());
+            if let Some(top) = stack.last_mut() {
+                decode_into(&doc[pos..end], &mut top.text);
+            } else if !doc[pos..end].trim().is_empty() {
+                return Err(pos);
+            }
+            pos = end;
+            continue;
+        }
+
+        let rest = &bytes[pos..];
+        if rest.starts_with(b"<?") {
+            pos = find(bytes, pos, b"?>").ok_or(pos)? + 2;
+        } else if rest.starts_with(b"<!--") {
+            pos = find(bytes, pos, b"-->").ok_or(pos)? + 3;
+        } else if rest.starts_with(b"<![CDATA[") {
+            let end = find(bytes, pos, b"]]>").ok_or(pos)?;
+            let top = stack.last_mut().ok_or(pos)?;
+            top.text.push_str(&doc[pos + 9..end]);
+            pos = end + 3;
+        } else if rest.starts_with(b"<!") {
+            pos = skip_declaration(bytes, pos).ok_or(pos)?;
+        } else if rest.starts_with(b"</") {
+            let end = find(bytes, pos, b">").ok_or(pos)?;
+            let name = doc[pos + 2..end].trim();
+            let element = stack.pop().filter(|e| e.name == name).ok_or(pos)?;
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(element),
+                None => root = Some(element),
+            }
+            pos = end + 1;
+        } else {
+            let (element, closed, end) = start_tag(doc, pos).ok_or(pos)?;
+            pos = end;
+            if !closed {
+                stack.push(element);
+            } else if let Some(parent) = stack.last_mut() {
+                parent.children.push(element);
+            } else {
+                root = Some(element);
+            }
+        }
+        if root.is_some() {
+            // Anything after the root element is ignored.
+            break;
+        }
+    }
+    if !stack.is_empty() {
+        return Err(bytes.len());
+    }
+    root.ok_or(bytes.len())
+}
+
+fn find(bytes: &[u8], from: usize, pat: &[u8]) -> Option<usize> {
+    bytes[from..].windows(pat.len()).position(|w| w == pat).map(|i| from + i)
+}
+
+/// Skip `<!DOCTYPE ...>` (with a bracketed internal subset) or another
+/// markup declaration; returns the offset after it.
+fn skip_declaration(bytes: &[u8], pos: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote: Option<u8> = None;
+    for (i, &b) in bytes.iter().enumerate().skip(pos + 2) {
+        if let Some(q) = quote {
+            if b == q {
+                quote = None;
+            }
+            continue;
+        }
+        match b {
+            b'"' | b'\'' => quote = Some(b),
+            b'[' => depth += 1,
+            b']' => depth = depth.saturating_sub(1),
+            b'>' if depth == 0 => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parse `<name attr="v" ...>` or `<name .../>` at `pos`; returns the
+/// element, whether it self-closed, and the offset after the tag.
+fn start_tag(doc: &str, pos: usize) -> Option<(Element, bool, usize)> {
+    let bytes = doc.as_bytes();
+    let mut i = pos + 1;
+    let name_end =
+        i + bytes[i..].iter().position(|&b| b.is_ascii_whitespace() || b == b'>' || b == b'/')?;
+    let mut element = Element { name: String::from(&doc[i..name_end]), ..Element::default() };
+    if element.name.is_empty() {
+        return None;
+    }
+    i = name_end;
+    loop {
+        while bytes.get(i)?.is_ascii_whitespace() {
+            i += 1;
+        }
+        match bytes[i] {
+            b'>' => return Some((element, false, i + 1)),
+            b'/' if bytes.get(i + 1) == Some(&b'>') => return Some((element, true, i + 2)),
+            _ => {
+                let eq = i + bytes[i..].iter().position(|&b| b == b'=')?;
+                let key = doc[i..eq].trim();
+                let mut v = eq + 1;
+                while bytes.get(v)?.is_ascii_whitespace() {
+                    v += 1;
+                }
+                let quote = bytes[v];
+                if quote != b'"' && quote != b'\'' {
+                    return None;
+                }
+                let close = v + 1 + bytes[v + 1..].iter().position(|&b| b == quote)?;
+                let mut value = String::new();
+                decode_into(&doc[v + 1..close], &mut value);
+                element.attrs.push((String::from(key), value));
+                i = close + 1;
+            }
+        }
+    }
+}
+
+/// Append `raw` to `out`, decoding character references.
+fn decode_into(raw: &str, out: &mut String) {
+    let mut rest = raw;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.find(';') else { break };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|h| u32::from_str_radix(h, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse::<u32>))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => out.push(c),
+            None => out.push_str(&rest[..=semi]),
+        }
+        rest = &rest[semi + 1..];
+    }
+    out.push_str(rest);
+}
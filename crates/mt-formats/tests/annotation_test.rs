@@ -0,0 +1,211 @@
+use mt_core::chord::{BassPolicy, Chord};
+use mt_core::chord_kind::ChordKindId;
+use mt_core::events::{ChordEvent, KeyEvent, SectionLabel, SegmentEvent, SegmentKind, TempoEvent};
+use mt_core::key::Key;
+use mt_core::pitch::PitchClass;
+use mt_core::time::SampleTime;
+use mt_formats::annotation::{
+    format_harte, parse_harte, parse_key_label, parse_segment_label, samples_to_seconds,
+    seconds_to_samples,
+};
+use mt_formats::lab::LabKind;
+use mt_formats::{AnnotationError, Annotations};
+
+const SR: u32 = 44_100;
+
+fn at(samples: i64) -> SampleTime {
+    SampleTime::new(samples)
+}
+
+fn chord(root: u8, kind: ChordKindId, bass: Option<u8>) -> Chord {
+    let pc = |v| PitchClass::new(v).unwrap();
+    Chord::with_bass_policy(pc(root), kind, bass.map(pc), BassPolicy::Any).unwrap()
+}
+
+fn sample() -> Annotations {
+    Annotations {
+        chords: vec![
+            ChordEvent {
+                chord: chord(10, ChordKindId::Maj, Some(2)),
+                onset: at(22_050),
+                offset: at(88_200),
+                confidence_x1000: 750,
+            },
+            ChordEvent {
+                chord: chord(6, ChordKindId::HalfDim7, None),
+                onset: at(88_200),
+                offset: at(132_301),
+                confidence_x1000: 1000,
+            },
+        ],
+        keys: vec![KeyEvent {
+            key: Key::from_semitone(3, true).unwrap(),
+            position: at(0),
+            confidence_x1000: 900,
+        }],
+        segments: vec![SegmentEvent {
+            kind: SegmentKind::Chorus,
+            label: Some(SectionLabel { group: 1, variant: 1 }),
+            onset: at(0),
+            offset: at(132_301),
+            confidence_x1000: 600,
+        }],
+        tempos: vec![TempoEvent { position: at(0), bpm_x1000: 92_500 }],
+        beats: vec![at(22_050), at(66_150)],
+    }
+}
+
+#[test]
+fn harte_labels() {
+    let parsed = |label| {
+        parse_harte(label).unwrap().map(|c| (c.root.as_u8(), c.kind, c.bass.map(|b| b.as_u8())))
+    };
+    assert_eq!(parsed("C:min7/b3"), Some((0, ChordKindId::Min7, Some(3))));
+    assert_eq!(parsed("Bb:maj6(9)"), Some((10, ChordKindId::SixNine, None)));
+    assert_eq!(parsed("A:(1,5)"), Some((9, ChordKindId::Power5, None)));
+    assert_eq!(parsed("Eb"), Some((3, ChordKindId::Maj, None)));
+    assert_eq!(parsed("D:9"), Some((2, ChordKindId::Dom7, None)));
+    assert_eq!(parsed("F#:min(*b3,3)"), Some((6, ChordKindId::Maj, None)));
+    assert_eq!(parsed("G:sus4(b7)"), Some((7, ChordKindId::Sus4, None)));
+    assert_eq!(parsed("Cb:dim7/bb7"), Some((11, ChordKindId::Dim7, Some(8))));
+    assert_eq!(parsed("N"), None);
+    assert_eq!(parsed("X"), None);
+    assert_eq!(parsed("C:1"), None);
+    assert_eq!(parse_harte("H:maj"), Err(AnnotationError::InvalidChord));
+    assert_eq!(parse_harte("C:foo"), Err(AnnotationError::InvalidChord));
+
+    assert_eq!(format_harte(&chord(10, ChordKindId::Maj, Some(2))), "Bb:maj/3");
+    assert_eq!(format_harte(&chord(8, ChordKindId::Aug, Some(4))), "Ab:aug/#5");
+    assert_eq!(format_harte(&chord(7, ChordKindId::SixNine, None)), "G:maj6(9)");
+}
+
+#[test]
+fn keys_segments_and_times() {
+    let key = parse_key_label("Key C#:minor").unwrap();
+    assert_eq!((key.tonic().as_u8(), key.mode()), (1, mt_core::key::KeyMode::Minor));
+    assert_eq!(parse_key_label("Eb major"), Key::from_semitone(3, false).ok());
+    assert_eq!(parse_key_label("Silence"), None);
+    assert_eq!(parse_key_label("D:dorian"), None);
+
+    assert_eq!(
+        parse_segment_label("Chorus B'"),
+        Some((SegmentKind::Chorus, Some(SectionLabel { group: 1, variant: 1 })))
+    );
+    assert_eq!(parse_segment_label("refrain_(with_horns)"), Some((SegmentKind::Chorus, None)));
+    assert_eq!(
+        parse_segment_label("A''"),
+        Some((SegmentKind::Other(0), Some(SectionLabel { group: 0, variant: 2 })))
+    );
+    assert_eq!(parse_segment_label("Section 4"), Some((SegmentKind::Other(4), None)));
+    assert_eq!(parse_segment_label("silence"), None);
+
+    assert_eq!(seconds_to_samples("2.5", SR), Some(at(110_250)));
+    assert_eq!(seconds_to_samples("1e-1", 48_000), Some(at(4_800)));
+    assert_eq!(seconds_to_samples("0.0000104", 48_000), Some(at(0)));
+    assert_eq!(seconds_to_samples("abc", SR), None);
+    assert_eq!(samples_to_seconds(at(132_301), SR), "3.000023");
+    assert_eq!(seconds_to_samples(&samples_to_seconds(at(132_301), SR), SR), Some(at(132_301)));
+}
+
+#[test]
+fn lab_files() {
+    let isophonics =
+        "0.000000 0.500000 N\n0.500000 2.000000 Bb:maj/3\n\n2.000000\t3.000023\tF#:hdim7\n";
+    let chords = Annotations::from_lab(isophonics, LabKind::Chords, SR).unwrap();
+    let original = sample();
+    assert_eq!(chords.chords.len(), 2);
+    assert_eq!(chords.chords[0].chord, original.chords[0].chord);
+    assert_eq!(chords.chords[1].offset, at(132_301));
+    assert_eq!(
+        original.to_lab(LabKind::Chords, SR),
+        isophonics.replace("\n\n", "\n").replace('\t', " ")
+    );
+
+    let keys = "0.0000 0.4180 Silence\n0.4180 196.3410 Key E\n196.3410 200.0 Key C#:minor\n";
+    let read = Annotations::from_lab(keys, LabKind::Keys, SR).unwrap();
+    assert_eq!(read.keys.iter().map(|k| k.key.tonic().as_u8()).collect::<Vec<_>>(), [4, 1]);
+    assert_eq!(read.keys[0].position, at(18_434));
+    let mut merged = chords.clone();
+    merged.merge(read);
+    assert_eq!((merged.chords.len(), merged.keys.len()), (2, 2));
+    assert_eq!(original.to_lab(LabKind::Keys, SR), "0.000000 3.000023 Key Eb:minor\n");
+    assert_eq!(original.to_lab(LabKind::Segments, SR), "0.000000 3.000023 Chorus B'\n");
+    assert_eq!(original.to_lab(LabKind::Tempo, SR), "0.000000 3.000023 92.5\n");
+    assert_eq!(original.to_lab(LabKind::Beats, SR), "0.500000\n1.500000\n");
+    let beats = Annotations::from_lab("1.5\t2\n0.5 1\n", LabKind::Beats, SR).unwrap();
+    assert_eq!(beats.beats, original.beats);
+
+    assert_eq!(
+        Annotations::from_lab("0.0 1.0 C:maj\n1.0 C:min\n", LabKind::Chords, SR),
+        Err(AnnotationError::InvalidLine(2))
+    );
+}
+
+#[test]
+fn jams_round_trip_and_dense_data() {
+    let original = sample();
+    let jams = original.to_jams(SR);
+    assert!(jams.contains(r#""namespace": "key_mode""#));
+    assert!(jams.contains(r#""value": "Bb:maj/3", "confidence": 0.750"#));
+    assert!(jams.contains(r#""jams_version": "0.3.4""#));
+    assert_eq!(Annotations::from_jams(&jams, SR).unwrap(), original);
+
+    let dense = r#"{"annotations": [
+        {"namespace": "beat", "data": {"time": [0.5], "duration": [0.0], "value": [1], "confidence": [null]}},
+        {"namespace": "chord_harte", "data": {"time": [0.0, 1.5], "duration": [1.5, 1.0],
+            "value": ["C:maj", "G:7/3"], "confidence": [null, 0.5]}},
+        {"namespace": "chord", "data": [{"time": 9.0, "duration": 1.0, "value": "D:min", "confidence": null}]},
+        {"namespace": "tempo", "data": [{"time": 0, "duration": 2.5, "value": 120.0, "confidence": 1.0}]}
+    ], "file_metadata": {"duration": 2.5}}"#;
+    let read = Annotations::from_jams(dense, 48_000).unwrap();
+    let spans: Vec<_> = read
+        .chords
+        .iter()
+        .map(|c| (format_harte(&c.chord), c.onset.value(), c.offset.value(), c.confidence_x1000))
+        .collect();
+    assert_eq!(
+        spans,
+        [(String::from("C:maj"), 0, 72_000, 1000), (String::from("G:7/3"), 72_000, 120_000, 500)]
+    );
+    assert_eq!(read.tempos, [TempoEvent { position: at(0), bpm_x1000: 120_000 }]);
+    assert_eq!(read.beats, [at(24_000)]);
+
+    assert_eq!(Annotations::from_jams("{}", SR), Err(AnnotationError::NotJams));
+    assert!(matches!(
+        Annotations::from_jams("{\"a\": [", SR),
+        Err(AnnotationError::MalformedJson(_))
+    ));
+    assert_eq!(
+        Annotations::from_jams(
+            r#"{"annotations": [{"namespace": "chord", "data": [{"time": 0}]}]}"#,
+            SR
+        ),
+        Err(AnnotationError::InvalidObservation { annotation: 0, index: 0 })
+    );
+}
+
+#[test]
+fn csv_round_trip() {
+    let mut original = sample();
+    original.segments[0].kind = SegmentKind::Other(7);
+    original.segments[0].label = None;
+    let csv = original.to_csv(SR);
+    assert_eq!(
+        csv,
+        "type,start,end,label,confidence\n\
+         key,0.000000,3.000023,Eb:minor,0.900\n\
+         segment,0.000000,3.000023,Section 7,0.600\n\
+         tempo,0.000000,3.000023,92.5,1.000\n\
+         chord,0.500000,2.000000,Bb:maj/3,0.750\n\
+         beat,0.500000,0.500000,,1.000\n\
+         beat,1.500000,1.500000,,1.000\n\
+         chord,2.000000,3.000023,F#:hdim7,1.000\n"
+    );
+    assert_eq!(Annotations::from_csv(&csv, SR).unwrap(), original);
+
+    let quoted = "segment,0,1,\"verse, \"\"quiet\"\"\"\nchord,1,2,A:min\n";
+    let read = Annotations::from_csv(quoted, SR).unwrap();
+    assert_eq!(read.segments[0].kind, SegmentKind::Verse);
+    assert_eq!(read.chords[0].confidence_x1000, 1000);
+    assert_eq!(Annotations::from_csv("bar,0,1,x\n", SR), Err(AnnotationError::InvalidLine(1)));
+}
@@ -0,0 +1,456 @@
+use std::io::Cursor;
+
+use mt_formats::{AudioError, AudioFileFormat, AudioFileInfo, AudioFileReader};
+
+fn decode(bytes: Vec<u8>) -> Result<(AudioFileInfo, Vec<f32>), AudioError> {
+    let mut reader = AudioFileReader::new(Cursor::new(bytes))?;
+    let mut samples = Vec::new();
+    while let Some(block) = reader.next_block(5)? {
+        samples.extend(block);
+    }
+    Ok((reader.info(), samples))
+}
+
+fn scaled(values: &[i64], bits: u32) -> Vec<f32> {
+    values.iter().map(|&v| (v as f64 / (1u64 << (bits - 1)) as f64) as f32).collect()
+}
+
+// --- WAV -------------------------------------------------------------------
+
+fn le_chunk(id: &[u8], body: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.extend((body.len() as u32).to_le_bytes());
+    out.extend(body);
+    if body.len() % 2 == 1 {
+        out.push(0);
+    }
+    out
+}
+
+fn fmt_body(tag: u16, channels: u16, rate: u32, bits: u16) -> Vec<u8> {
+    let block_align = channels * bits.div_ceil(8);
+    let mut body = tag.to_le_bytes().to_vec();
+    body.extend(channels.to_le_bytes());
+    body.extend(rate.to_le_bytes());
+    body.extend((rate * u32::from(block_align)).to_le_bytes());
+    body.extend(block_align.to_le_bytes());
+    body.extend(bits.to_le_bytes());
+    body
+}
+
+fn riff(chunks: &[Vec<u8>]) -> Vec<u8> {
+    let body: Vec<u8> = chunks.concat();
+    let mut out = b"RIFF".to_vec();
+    out.extend((body.len() as u32 + 4).to_le_bytes());
+    out.extend(b"WAVE");
+    out.extend(body);
+    out
+}
+
+fn le_samples(values: &[i64], bytes: usize) -> Vec<u8> {
+    values.iter().flat_map(|&v| v.to_le_bytes()[..bytes].to_vec()).collect()
+}
+
+#[test]
+fn wav_integer_pcm_widths_share_one_scale() {
+    for (bits, values) in [
+        (16u16, vec![0i64, 16_384, -32_768, 32_767]),
+        (24, vec![0, 4_194_304, -8_388_608, -1]),
+        (32, vec![0, 1_073_741_824, i64::from(i32::MIN), 1]),
+    ] {
+        let bytes = usize::from(bits / 8);
+        let file = riff(&[
+            le_chunk(b"fmt ", &fmt_body(1, 2, 48_000, bits)),
+            le_chunk(b"data", &le_samples(&values, bytes)),
+        ]);
+        let (info, samples) = decode(file).unwrap();
+        assert_eq!(info.format, AudioFileFormat::Wav);
+        assert_eq!((info.sample_rate, info.channels, info.bits_per_sample), (48_000, 2, bits));
+        assert_eq!(info.frames, Some(2));
+        assert_eq!(samples, scaled(&values, u32::from(bits)), "{bits}-bit");
+    }
+}
+
+#[test]
+fn wav_float_and_odd_chunks() {
+    let values = [0.25f32, -0.5, 1.0];
+    let data: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    let file = riff(&[
+        le_chunk(b"LIST", b"odd"), // padded to an even length
+        le_chunk(b"fmt ", &fmt_body(3, 1, 44_100, 32)),
+        le_chunk(b"data", &data),
+    ]);
+    let (info, samples) = decode(file).unwrap();
+    assert_eq!(info.bits_per_sample, 32);
+    assert_eq!(samples, values);
+}
+
+#[test]
+fn wav_format_extensible_uses_the_sub_format() {
+    let mut fmt = fmt_body(0xFFFE, 1, 44_100, 24);
+    fmt.extend(22u16.to_le_bytes()); // cbSize
+    fmt.extend(24u16.to_le_bytes()); // valid bits
+    fmt.extend(4u32.to_le_bytes()); // channel mask
+    fmt.extend(1u16.to_le_bytes()); // KSDATAFORMAT_SUBTYPE_PCM
+    fmt.extend([0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38]);
+    fmt.extend([0x9B, 0x71]);
+    let values = [8_388_607i64, -4_194_304];
+    let file = riff(&[le_chunk(b"fmt ", &fmt), le_chunk(b"data", &le_samples(&values, 3))]);
+    let (info, samples) = decode(file).unwrap();
+    assert_eq!(info.bits_per_sample, 24);
+    assert_eq!(samples, scaled(&values, 24));
+}
+
+#[test]
+fn rf64_takes_the_data_size_from_ds64() {
+    let values = [1_000i64, -1_000, 2_000];
+    let data = le_samples(&values, 2);
+    let mut ds64 = 0u64.to_le_bytes().to_vec(); // RIFF size
+    ds64.extend((data.len() as u64).to_le_bytes());
+    ds64.extend(3u64.to_le_bytes()); // sample count
+    ds64.extend(0u32.to_le_bytes()); // table length
+    let mut file = b"RF64".to_vec();
+    file.extend(u32::MAX.to_le_bytes());
+    file.extend(b"WAVE");
+    file.extend(le_chunk(b"ds64", &ds64));
+    file.extend(le_chunk(b"fmt ", &fmt_body(1, 1, 44_100, 16)));
+    file.extend(b"data");
+    file.extend(u32::MAX.to_le_bytes());
+    file.extend(&data);
+    let (info, samples) = decode(file).unwrap();
+    assert_eq!(info.format, AudioFileFormat::Rf64);
+    assert_eq!(info.frames, Some(3));
+    assert_eq!(samples, scaled(&values, 16));
+}
+
+#[test]
+fn oversized_wav_chunk_lengths_are_rejected() {
+    let mut file = b"RIFF\0\0\0\0WAVE".to_vec();
+    file.extend(b"fmt ");
+    file.extend(0xFFFF_FFF0u32.to_le_bytes());
+    file.extend(fmt_body(1, 1, 44_100, 16));
+    assert!(matches!(decode(file), Err(AudioError::Decode(_))));
+}
+
+// --- AIFF ------------------------------------------------------------------
+
+fn be_chunk(id: &[u8], body: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.extend((body.len() as u32).to_be_bytes());
+    out.extend(body);
+    if body.len() % 2 == 1 {
+        out.push(0);
+    }
+    out
+}
+
+/// 44 100 Hz as an 80-bit extended float.
+const RATE_44K: [u8; 10] = [0x40, 0x0E, 0xAC, 0x44, 0, 0, 0, 0, 0, 0];
+
+fn form(kind: &[u8], chunks: &[Vec<u8>]) -> Vec<u8> {
+    let body: Vec<u8> = chunks.concat();
+    let mut out = b"FORM".to_vec();
+    out.extend((body.len() as u32 + 4).to_be_bytes());
+    out.extend(kind);
+    out.extend(body);
+    out
+}
+
+fn comm(channels: u16, frames: u32, bits: u16, compression: Option<&[u8; 4]>) -> Vec<u8> {
+    let mut body = channels.to_be_bytes().to_vec();
+    body.extend(frames.to_be_bytes());
+    body.extend(bits.to_be_bytes());
+    body.extend(RATE_44K);
+    if let Some(id) = compression {
+        body.extend(id);
+        body.extend([0, 0]); // empty compression name, padded
+    }
+    body
+}
+
+fn ssnd(data: &[u8]) -> Vec<u8> {
+    let mut body = vec![0u8; 8]; // offset, block size
+    body.extend(data);
+    body
+}
+
+#[test]
+fn aiff_big_endian_pcm_with_comm_after_ssnd() {
+    let values = [16_384i64, -16_384];
+    let data: Vec<u8> = values.iter().flat_map(|&v| (v as i16).to_be_bytes()).collect();
+    let file =
+        form(b"AIFF", &[be_chunk(b"SSND", &ssnd(&data)), be_chunk(b"COMM", &comm(1, 2, 16, None))]);
+    let (info, samples) = decode(file).unwrap();
+    assert_eq!(info.format, AudioFileFormat::Aiff);
+    assert_eq!(info.sample_rate, 44_100);
+    assert_eq!(samples, scaled(&values, 16));
+}
+
+#[test]
+fn aifc_sowt_is_little_endian() {
+    let values = [12_345i64, -12_345, 0, 32_767];
+    let file = form(
+        b"AIFC",
+        &[
+            be_chunk(b"COMM", &comm(2, 2, 16, Some(b"sowt"))),
+            be_chunk(b"SSND", &ssnd(&le_samples(&values, 2))),
+        ],
+    );
+    let (info, samples) = decode(file).unwrap();
+    assert_eq!(info.format, AudioFileFormat::Aifc);
+    assert_eq!(info.channels, 2);
+    assert_eq!(samples, scaled(&values, 16));
+}
+
+#[test]
+fn oversized_aiff_comm_is_rejected() {
+    let mut file = b"FORM\0\0\0\0AIFF".to_vec();
+    file.extend(b"COMM");
+    file.extend(0xFFFF_FFF0u32.to_be_bytes());
+    file.extend(comm(1, 0, 16, None));
+    assert!(matches!(decode(file), Err(AudioError::Decode(_))));
+}
+
+// --- FLAC ------------------------------------------------------------------
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    acc_bits: u32,
+}
+
+impl BitWriter {
+    fn bits(&mut self, n: u32, value: u64) {
+        for i in (0..n).rev() {
+            self.acc = (self.acc << 1) | ((value >> i) & 1);
+            self.acc_bits += 1;
+            if self.acc_bits == 8 {
+                self.bytes.push(self.acc as u8);
+                self.acc = 0;
+                self.acc_bits = 0;
+            }
+        }
+    }
+
+    fn signed(&mut self, n: u32, value: i64) {
+        self.bits(n, value as u64 & ((1u64 << n) - 1));
+    }
+
+    fn align(&mut self) {
+        while self.acc_bits != 0 {
+            self.bits(1, 0);
+        }
+    }
+
+    /// Rice partition (order 0) with parameter `k`.
+    fn rice(&mut self, k: u32, residuals: &[i64]) {
+        self.bits(2, 0); // 4-bit parameters
+        self.bits(4, 0); // partition order
+        self.bits(4, u64::from(k));
+        for &r in residuals {
+            let folded = ((r << 1) ^ (r >> 63)) as u64;
+            for _ in 0..folded >> k {
+                self.bits(1, 0);
+            }
+            self.bits(1, 1);
+            self.bits(k, folded & ((1 << k) - 1));
+        }
+    }
+}
+
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |mut crc, &b| {
+        crc ^= b;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0u16, |mut crc, &b| {
+        crc ^= u16::from(b) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x8005 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+fn streaminfo(channels: u32, bits: u32, frames: u64) -> Vec<u8> {
+    let mut w = BitWriter::default();
+    w.bits(8, 0x80); // last block, STREAMINFO
+    w.bits(24, 34);
+    w.bits(48, 0); // block and frame size bounds
+    w.bits(32, 0);
+    w.bits(20, 44_100);
+    w.bits(3, u64::from(channels - 1));
+    w.bits(5, u64::from(bits - 1));
+    w.bits(36, frames);
+    w.bits(64, 0); // MD5
+    w.bits(64, 0);
+    let mut out = b"fLaC".to_vec();
+    out.extend(w.bytes);
+    out
+}
+
+/// One frame: `layout` is the 4-bit channel assignment; `subframes` writes
+/// the subframe bits after the header.
+fn frame(block_size: usize, layout: u64, subframes: impl FnOnce(&mut BitWriter)) -> Vec<u8> {
+    let mut w = BitWriter::default();
+    w.bits(16, 0xFFF8);
+    w.bits(4, 7); // 16-bit block size follows
+    w.bits(4, 0); // sample rate from STREAMINFO
+    w.bits(4, layout);
+    w.bits(3, 0); // sample size from STREAMINFO
+    w.bits(1, 0);
+    w.bits(8, 0); // frame number
+    w.bits(16, block_size as u64 - 1);
+    let crc = crc8(&w.bytes);
+    w.bits(8, u64::from(crc));
+    subframes(&mut w);
+    w.align();
+    let crc = crc16(&w.bytes);
+    w.bits(16, u64::from(crc));
+    w.bytes
+}
+
+fn subframe_header(w: &mut BitWriter, kind: u64) {
+    w.bits(1, 0);
+    w.bits(6, kind);
+    w.bits(1, 0); // no wasted bits
+}
+
+fn verbatim(w: &mut BitWriter, bps: u32, samples: &[i64]) {
+    subframe_header(w, 1);
+    for &s in samples {
+        w.signed(bps, s);
+    }
+}
+
+fn flac(channels: u32, bits: u32, frames: usize, body: Vec<u8>) -> Vec<u8> {
+    let mut out = streaminfo(channels, bits, frames as u64);
+    out.extend(body);
+    out
+}
+
+const WAVE: [i64; 12] =
+    [0, 900, 1_700, 2_300, 2_600, 2_400, 1_800, 700, -600, -1_900, -2_800, -3_100];
+
+#[test]
+fn flac_fixed_predictors_reconstruct_the_signal() {
+    for order in 0..=4usize {
+        let predict = |s: &[i64], i: usize| match order {
+            0 => 0,
+            1 => s[i - 1],
+            2 => 2 * s[i - 1] - s[i - 2],
+            3 => 3 * s[i - 1] - 3 * s[i - 2] + s[i - 3],
+            _ => 4 * s[i - 1] - 6 * s[i - 2] + 4 * s[i - 3] - s[i - 4],
+        };
+        let residuals: Vec<i64> =
+            (order..WAVE.len()).map(|i| WAVE[i] - predict(&WAVE, i)).collect();
+        let file = flac(
+            1,
+            16,
+            WAVE.len(),
+            frame(WAVE.len(), 0, |w| {
+                subframe_header(w, 8 + order as u64);
+                for &s in &WAVE[..order] {
+                    w.signed(16, s);
+                }
+                w.rice(6, &residuals);
+            }),
+        );
+        let (info, samples) = decode(file).unwrap();
+        assert_eq!(info.format, AudioFileFormat::Flac);
+        assert_eq!(samples, scaled(&WAVE, 16), "fixed order {order}");
+    }
+}
+
+#[test]
+fn flac_lpc_subframe_applies_coefficients_and_shift() {
+    let coefs = [3i64, -1];
+    let shift = 1;
+    let residuals: Vec<i64> = (2..WAVE.len())
+        .map(|i| WAVE[i] - ((coefs[0] * WAVE[i - 1] + coefs[1] * WAVE[i - 2]) >> shift))
+        .collect();
+    let file = flac(
+        1,
+        16,
+        WAVE.len(),
+        frame(WAVE.len(), 0, |w| {
+            subframe_header(w, 32 + 1); // LPC order 2
+            w.signed(16, WAVE[0]);
+            w.signed(16, WAVE[1]);
+            w.bits(4, 3 - 1); // coefficient precision 3
+            w.signed(5, shift);
+            for &c in &coefs {
+                w.signed(3, c);
+            }
+            w.rice(8, &residuals);
+        }),
+    );
+    let (_, samples) = decode(file).unwrap();
+    assert_eq!(samples, scaled(&WAVE, 16));
+}
+
+#[test]
+fn flac_stereo_decorrelation_modes() {
+    let left: Vec<i64> = WAVE.to_vec();
+    let right: Vec<i64> = WAVE.iter().rev().map(|&v| v / 2 + 1).collect();
+    let side: Vec<i64> = left.iter().zip(&right).map(|(l, r)| l - r).collect();
+    let mid: Vec<i64> = left.iter().zip(&right).map(|(l, r)| (l + r) >> 1).collect();
+    let interleaved: Vec<i64> = left.iter().zip(&right).flat_map(|(&l, &r)| [l, r]).collect();
+    let n = WAVE.len();
+
+    let cases: [(&str, u64, &[i64], u32, &[i64], u32); 4] = [
+        ("independent", 1, &left, 16, &right, 16),
+        ("left/side", 8, &left, 16, &side, 17),
+        ("side/right", 9, &side, 17, &right, 16),
+        ("mid/side", 10, &mid, 16, &side, 17),
+    ];
+    for (name, layout, a, a_bps, b, b_bps) in cases {
+        let file = flac(
+            2,
+            16,
+            n,
+            frame(n, layout, |w| {
+                verbatim(w, a_bps, a);
+                verbatim(w, b_bps, b);
+            }),
+        );
+        let (info, samples) = decode(file).unwrap();
+        assert_eq!(info.channels, 2);
+        assert_eq!(samples, scaled(&interleaved, 16), "{name}");
+    }
+}
+
+#[test]
+fn corrupt_flac_prediction_wraps_instead_of_panicking() {
+    let n = 64;
+    let file = flac(
+        1,
+        32,
+        n,
+        frame(n, 0, |w| {
+            // LPC order 1 with a huge coefficient: the prediction
+            // overflows i64 within a few samples.
+            subframe_header(w, 32);
+            w.signed(32, i64::from(i32::MAX));
+            w.bits(4, 15 - 1);
+            w.signed(5, 0);
+            w.signed(15, 16_383);
+            w.bits(2, 0);
+            w.bits(4, 0);
+            w.bits(4, 15); // escaped partition
+            w.bits(5, 31);
+            for _ in 1..n {
+                w.signed(31, (1 << 30) - 1);
+            }
+        }),
+    );
+    let (_, samples) = decode(file).unwrap();
+    assert_eq!(samples.len(), n);
+}
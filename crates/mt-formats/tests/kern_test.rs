@@ -0,0 +1,132 @@
+use mt_core::chord_kind::ChordKindId;
+use mt_core::key::KeyMode;
+use mt_formats::{Score, ScoreError};
+
+// 60 BPM at 48 kHz: a quarter note is 48_000 samples.
+const Q: i64 = 48_000;
+
+/// (root, kind, bass) per chord annotation.
+fn chords(score: &Score) -> Vec<(u8, ChordKindId, Option<u8>)> {
+    score
+        .chords
+        .iter()
+        .map(|c| (c.chord.root.as_u8(), c.chord.kind, c.chord.bass.map(|b| b.as_u8())))
+        .collect()
+}
+
+const CHORALE: &str = "!!!COM: Anonymous
+**kern\t**kern\t**harm
+*I\"Bass\t*I\"Soprano\t*
+*M4/4\t*M4/4\t*M4/4
+*MM60\t*MM60\t*
+*G:\t*G:\t*G:
+4G\t4d\tI
+=1\t=1\t=1
+*\t*^\t*
+4D\t8c#L\t4B\tV/V
+.\t8dJ\t.\t.
+4GG\t[4g\t2e\tI
+4C\t4g]\t.\tIV
+*\t*v\t*v\t*
+!\t!\t! cadence
+4DD\t4f#\tV7c
+4GG;\t4g;\tI
+==\t==\t==
+*-\t*-\t*-
+";
+
+#[test]
+fn chorale_with_split_spines_ties_and_numerals() {
+    let score = Score::from_kern(CHORALE, 48_000).unwrap();
+
+    assert_eq!(score.parts, ["Bass", "Soprano"]);
+    let spans: Vec<(u16, u8, i64, i64)> = score
+        .notes
+        .iter()
+        .map(|n| (n.track.0, n.note.value(), n.onset.value(), n.offset.value()))
+        .collect();
+    assert_eq!(
+        spans,
+        [
+            (0, 55, 0, Q),
+            (1, 62, 0, Q),
+            (0, 50, Q, 2 * Q),
+            (1, 59, Q, 2 * Q),
+            (1, 61, Q, Q + Q / 2),
+            (1, 62, Q + Q / 2, 2 * Q),
+            (0, 43, 2 * Q, 3 * Q),
+            (1, 64, 2 * Q, 4 * Q),
+            (1, 67, 2 * Q, 4 * Q),
+            (0, 48, 3 * Q, 4 * Q),
+            (0, 38, 4 * Q, 5 * Q),
+            (1, 66, 4 * Q, 5 * Q),
+            (0, 43, 5 * Q, 6 * Q),
+            (1, 67, 5 * Q, 6 * Q),
+        ]
+    );
+
+    assert_eq!(score.tempos.len(), 1);
+    assert_eq!(score.tempos[0].bpm_x1000, 60_000);
+    assert_eq!((score.meters[0].numerator, score.meters[0].denominator), (4, 4));
+    assert_eq!(score.keys.len(), 1);
+    assert_eq!((score.keys[0].key.tonic().as_u8(), score.keys[0].key.mode()), (7, KeyMode::Major));
+    assert_eq!(
+        chords(&score),
+        [
+            (7, ChordKindId::Maj, None),
+            (9, ChordKindId::Maj, None),
+            (7, ChordKindId::Maj, None),
+            (0, ChordKindId::Maj, None),
+            (2, ChordKindId::Dom7, Some(9)),
+            (7, ChordKindId::Maj, None),
+        ]
+    );
+    assert_eq!(score.chords[5].offset.value(), 6 * Q);
+}
+
+#[test]
+fn numerals_in_minor_keys() {
+    let text = "**kern\t**harm
+*c:\t*c:
+1c\ti
+1c\tviio7
+1c\tNb
+1c\tV7
+1c\tIII7
+1c\tii%7
+1c\t#ivo
+1.r\tLt
+*-\t*-
+";
+    let score = Score::from_kern(text, 48_000).unwrap();
+
+    assert_eq!(score.keys[0].key.mode(), KeyMode::Minor);
+    assert_eq!(
+        chords(&score),
+        [
+            (0, ChordKindId::Min, None),
+            (11, ChordKindId::Dim7, None),
+            (1, ChordKindId::Maj, Some(5)),
+            (7, ChordKindId::Dom7, None),
+            (3, ChordKindId::Maj7, None),
+            (2, ChordKindId::HalfDim7, None),
+            (6, ChordKindId::Dim, None),
+        ]
+    );
+    // Seven whole notes and a dotted whole rest: 34 quarter notes at 120 BPM.
+    assert_eq!(score.notes.len(), 7);
+    assert_eq!(score.chords[6].offset.value(), 34 * 24_000);
+}
+
+#[test]
+fn rejects_malformed_files() {
+    assert_eq!(Score::from_kern("**text\n*-\n", 48_000).unwrap_err(), ScoreError::NotKern);
+    assert_eq!(
+        Score::from_kern("**kern\t**kern\n4c\n", 48_000).unwrap_err(),
+        ScoreError::InvalidSpines(2)
+    );
+    assert_eq!(
+        Score::from_kern("**kern\n4c\n4z\n", 48_000).unwrap_err(),
+        ScoreError::InvalidToken(3)
+    );
+}
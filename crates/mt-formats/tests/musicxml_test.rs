@@ -0,0 +1,143 @@
+use mt_core::chord_kind::ChordKindId;
+use mt_core::key::KeyMode;
+use mt_formats::{Score, ScoreError};
+
+// 120 BPM at 48 kHz: a quarter note is 24_000 samples.
+const Q: i64 = 24_000;
+
+/// (track, pitch, onset, offset) per note.
+fn spans(score: &Score) -> Vec<(u16, u8, i64, i64)> {
+    score
+        .notes
+        .iter()
+        .map(|n| (n.track.0, n.note.value(), n.onset.value(), n.offset.value()))
+        .collect()
+}
+
+const PARTWISE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE score-partwise PUBLIC "-//Recordare//DTD MusicXML 4.0 Partwise//EN" "http://www.musicxml.org/dtds/partwise.dtd">
+<score-partwise version="4.0">
+  <part-list>
+    <score-part id="P1"><part-name>Clarinet in B&#x266d;</part-name></score-part>
+    <score-part id="P2"><part-name>Piano</part-name></score-part>
+  </part-list>
+  <part id="P1">
+    <measure number="1">
+      <attributes>
+        <divisions>2</divisions>
+        <key><fifths>-1</fifths><mode>minor</mode></key>
+        <time><beats>2</beats><beat-type>4</beat-type></time>
+        <transpose><diatonic>-1</diatonic><chromatic>-2</chromatic></transpose>
+      </attributes>
+      <direction placement="above">
+        <direction-type><metronome><beat-unit>quarter</beat-unit><per-minute>120</per-minute></metronome></direction-type>
+        <sound tempo="120"/>
+      </direction>
+      <harmony><root><root-step>D</root-step></root><kind>minor</kind></harmony>
+      <note><rest/><duration>2</duration></note>
+      <note><pitch><step>D</step><octave>5</octave></pitch><duration>2</duration><tie type="start"/></note>
+    </measure>
+    <measure number="2">
+      <harmony><root><root-step>A</root-step></root><kind text="7">dominant-ninth</kind><bass><bass-step>C</bass-step><bass-alter>1</bass-alter></bass></harmony>
+      <note><pitch><step>D</step><octave>5</octave></pitch><duration>1</duration><tie type="stop"/></note>
+      <note><pitch><step>E</step><alter>-1</alter><octave>5</octave></pitch><duration>1</duration></note>
+      <direction><direction-type><metronome><beat-unit>half</beat-unit><per-minute>30</per-minute></metronome></direction-type></direction>
+      <note><pitch><step>F</step><octave>5</octave></pitch><duration>2</duration></note>
+    </measure>
+  </part>
+  <part id="P2">
+    <measure number="1">
+      <attributes>
+        <divisions>1</divisions>
+        <key><fifths>-3</fifths><mode>minor</mode></key>
+        <time><beats>2</beats><beat-type>4</beat-type></time>
+      </attributes>
+      <note><pitch><step>C</step><octave>4</octave></pitch><duration>2</duration><voice>1</voice></note>
+      <note><chord/><pitch><step>E</step><alter>-1</alter><octave>4</octave></pitch><duration>2</duration><voice>1</voice></note>
+      <backup><duration>2</duration></backup>
+      <note><pitch><step>C</step><octave>3</octave></pitch><duration>1</duration><voice>2</voice></note>
+      <note><grace/><pitch><step>D</step><octave>3</octave></pitch><voice>2</voice></note>
+      <note><pitch><step>G</step><octave>2</octave></pitch><duration>1</duration><voice>2</voice></note>
+    </measure>
+    <measure number="2">
+      <note><pitch><step>B</step><octave>2</octave></pitch><duration>2</duration></note>
+    </measure>
+  </part>
+</score-partwise>
+"#;
+
+#[test]
+fn partwise_with_transposition_ties_voices_and_harmony() {
+    let score = Score::from_musicxml(PARTWISE, 48_000).unwrap();
+
+    assert_eq!(score.parts, ["Clarinet in B\u{266d}", "Piano"]);
+    assert_eq!(
+        spans(&score),
+        [
+            (1, 48, 0, Q),
+            (1, 60, 0, 2 * Q),
+            (1, 63, 0, 2 * Q),
+            // Written D5, tied over the barline, sounds C5.
+            (0, 72, Q, 2 * Q + Q / 2),
+            (1, 43, Q, 2 * Q),
+            (1, 47, 2 * Q, 5 * Q),
+            (0, 73, 2 * Q + Q / 2, 3 * Q),
+            // The last beat runs at 60 BPM.
+            (0, 75, 3 * Q, 5 * Q),
+        ]
+    );
+    assert_eq!(score.notes.iter().map(|n| n.id.0).collect::<Vec<_>>(), [1, 2, 3, 4, 5, 6, 7, 8]);
+
+    // Half note = 30 is 60 quarters per minute, from beat 2 of bar 2.
+    let tempos: Vec<(i64, u32)> =
+        score.tempos.iter().map(|t| (t.position.value(), t.bpm_x1000)).collect();
+    assert_eq!(tempos, [(0, 120_000), (3 * Q, 60_000)]);
+    assert_eq!(score.meters.len(), 1);
+    assert_eq!((score.meters[0].numerator, score.meters[0].denominator), (2, 4));
+    assert_eq!(score.keys.len(), 1);
+    assert_eq!(score.keys[0].key.tonic().as_u8(), 0);
+    assert_eq!(score.keys[0].key.mode(), KeyMode::Minor);
+
+    let chords: Vec<_> = score
+        .chords
+        .iter()
+        .map(|c| (c.chord.root.as_u8(), c.chord.kind, c.chord.bass.map(|b| b.as_u8())))
+        .collect();
+    assert_eq!(chords, [(0, ChordKindId::Min, None), (7, ChordKindId::Dom7, Some(11))]);
+    assert_eq!(score.chords[0].offset.value(), 2 * Q);
+    assert_eq!(score.chords[1].offset.value(), 5 * Q);
+}
+
+#[test]
+fn timewise_scores_are_regrouped_by_part() {
+    let xml = r#"<score-timewise>
+  <part-list><score-part id="A"><part-name>Alto</part-name></score-part></part-list>
+  <measure number="1">
+    <part id="A">
+      <attributes><divisions>4</divisions><time><beats>3+2</beats><beat-type>8</beat-type></time></attributes>
+      <note><pitch><step>A</step><octave>4</octave></pitch><duration>10</duration><notations><tied type="start"/></notations></note>
+    </part>
+  </measure>
+  <measure number="2">
+    <part id="A">
+      <note><pitch><step>A</step><octave>4</octave></pitch><duration>2</duration><notations><tied type="stop"/></notations></note>
+      <forward><duration>8</duration></forward>
+    </part>
+  </measure>
+</score-timewise>"#;
+
+    let score = Score::from_musicxml(xml, 48_000).unwrap();
+
+    assert_eq!(score.parts, ["Alto"]);
+    assert_eq!(spans(&score), [(0, 69, 0, 3 * Q)]);
+    assert_eq!((score.meters[0].numerator, score.meters[0].denominator), (5, 8));
+}
+
+#[test]
+fn rejects_other_documents() {
+    assert_eq!(Score::from_musicxml("<opus/>", 48_000).unwrap_err(), ScoreError::NotMusicXml);
+    assert!(matches!(
+        Score::from_musicxml("<score-partwise><part>", 48_000),
+        Err(ScoreError::MalformedXml(_))
+    ));
+}
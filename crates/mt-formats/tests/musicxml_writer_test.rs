@@ -0,0 +1,117 @@
+use mt_alloc::TempoMap;
+use mt_core::chord::Chord;
+use mt_core::chord_kind::ChordKindId;
+use mt_core::events::{
+    ChordEvent, KeyEvent, NoteEvent, NoteId, SectionLabel, SegmentEvent, SegmentKind, TempoEvent,
+    TrackId,
+};
+use mt_core::key::Key;
+use mt_core::pitch::{MidiNote, PitchClass};
+use mt_core::time::SampleTime;
+use mt_formats::musicxml_writer::{MusicXmlExportConfig, write_musicxml};
+
+// 120 BPM at 48 kHz: a quarter note is 24_000 samples.
+const Q: i64 = 24_000;
+
+fn note(midi: u8, onset: i64, offset: i64) -> NoteEvent {
+    NoteEvent {
+        id: NoteId(0),
+        track: TrackId(0),
+        onset: SampleTime::new(onset),
+        offset: SampleTime::new(offset),
+        note: MidiNote::new(midi).unwrap(),
+        velocity: 100,
+    }
+}
+
+fn chord(root: u8, kind: ChordKindId, onset: i64, offset: i64) -> ChordEvent {
+    ChordEvent {
+        chord: Chord::new(PitchClass::new(root).unwrap(), kind, None).unwrap(),
+        onset: SampleTime::new(onset),
+        offset: SampleTime::new(offset),
+        confidence_x1000: 900,
+    }
+}
+
+/// Sum of `<duration>` per measure, counting each chord once.
+fn measure_durations(xml: &str) -> Vec<u64> {
+    xml.split("<measure ")
+        .skip(1)
+        .map(|m| {
+            m.split("<note>")
+                .skip(1)
+                .filter(|n| !n.contains("<chord/>"))
+                .map(|n| {
+                    let d = n.split("<duration>").nth(1).unwrap();
+                    d[..d.find('<').unwrap()].parse::<u64>().unwrap()
+                })
+                .sum()
+        })
+        .collect()
+}
+
+#[test]
+fn lead_sheet_with_triplets_ties_and_harmony() {
+    let mut map = TempoMap::new(48_000);
+    map.push_tempo(TempoEvent { position: SampleTime::ZERO, bpm_x1000: 120_000 });
+    let notes = [
+        note(60, 300, Q - 200),
+        // Eighth-note triplet on beat 2.
+        note(62, Q, Q + Q / 3),
+        note(64, Q + Q / 3, Q + 2 * Q / 3),
+        note(65, Q + 2 * Q / 3, 2 * Q),
+        // Dotted half across the barline.
+        note(67, 2 * Q, 5 * Q),
+        // Two-note chord, then a rest to the end of the bar.
+        note(60, 5 * Q, 6 * Q),
+        note(64, 5 * Q + 100, 6 * Q),
+    ];
+    let chords = [chord(5, ChordKindId::Maj, 0, 4 * Q), chord(10, ChordKindId::Dom7, 4 * Q, 8 * Q)];
+    let keys = [KeyEvent {
+        key: Key::from_semitone(5, false).unwrap(),
+        position: SampleTime::ZERO,
+        confidence_x1000: 800,
+    }];
+    let segments = [SegmentEvent {
+        kind: SegmentKind::Verse,
+        label: Some(SectionLabel { group: 0, variant: 0 }),
+        onset: SampleTime::ZERO,
+        offset: SampleTime::new(8 * Q),
+        confidence_x1000: 700,
+    }];
+    let config =
+        MusicXmlExportConfig { title: String::from("Songs & Tests"), ..Default::default() };
+
+    let xml = write_musicxml(&map, &notes, &chords, &keys, &segments, &config);
+
+    assert!(xml.contains(r#"<score-partwise version="4.0">"#));
+    assert!(xml.contains("<work-title>Songs &amp; Tests</work-title>"));
+    assert!(xml.contains("<divisions>24</divisions>"));
+    assert!(xml.contains("<fifths>-1</fifths>"));
+    assert!(xml.contains("<rehearsal>Verse A</rehearsal>"));
+    assert!(xml.contains("<per-minute>120</per-minute>"));
+    assert!(xml.contains("<root-step>B</root-step>\n          <root-alter>-1</root-alter>"));
+    assert!(xml.contains(r#"<kind text="7">dominant</kind>"#));
+    assert_eq!(xml.matches("<time-modification>").count(), 3);
+    assert_eq!(xml.matches(r#"<tuplet type="start" bracket="yes"/>"#).count(), 1);
+    assert_eq!(xml.matches(r#"<tuplet type="stop"/>"#).count(), 1);
+    assert_eq!(xml.matches(r#"<tie type="start"/>"#).count(), 1);
+    assert_eq!(xml.matches(r#"<tie type="stop"/>"#).count(), 1);
+    assert_eq!(xml.matches("<chord/>").count(), 1);
+    assert!(xml.contains("<type>half</type>"));
+    assert_eq!(measure_durations(&xml), [96, 96]);
+}
+
+#[test]
+fn binary_grid_without_triplets() {
+    let map = TempoMap::new(48_000);
+    let notes = [note(72, 0, Q / 3), note(74, Q / 3, 2 * Q / 3), note(76, 2 * Q / 3, 7 * Q)];
+    let config = MusicXmlExportConfig { triplets: false, ..Default::default() };
+
+    let xml = write_musicxml(&map, &notes, &[], &[], &[], &config);
+
+    assert!(!xml.contains("<time-modification>"));
+    assert!(xml.contains("<fifths>0</fifths>"));
+    assert_eq!(measure_durations(&xml), [96, 96]);
+    assert!(xml.contains(r#"<bar-style>light-heavy</bar-style>"#));
+}
@@ -0,0 +1,125 @@
+use mt_core::events::TrackId;
+use mt_core::pedal::PedalConfig;
+use mt_core::time::SampleTime;
+use mt_formats::smf::{Division, MidiFile, SmfError};
+
+fn chunk(id: &[u8], body: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.extend((body.len() as u32).to_be_bytes());
+    out.extend(body);
+    out
+}
+
+fn smf(format: u16, division: u16, tracks: &[&[u8]]) -> Vec<u8> {
+    let mut header = format.to_be_bytes().to_vec();
+    header.extend((tracks.len() as u16).to_be_bytes());
+    header.extend(division.to_be_bytes());
+    let mut out = chunk(b"MThd", &header);
+    for t in tracks {
+        out.extend(chunk(b"MTrk", t));
+    }
+    out
+}
+
+#[rustfmt::skip]
+const CONDUCTOR: &[u8] = &[
+    0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20,       // 120 BPM
+    0x00, 0xFF, 0x58, 0x04, 0x03, 0x02, 0x18, 0x08, // 3/4
+    0x00, 0xFF, 0x59, 0x02, 0x01, 0x00,             // G major
+    0x87, 0x40, 0xFF, 0x51, 0x03, 0x03, 0xD0, 0x90, // tick 960: 240 BPM
+    0x00, 0xFF, 0x06, 0x01, b'B',                   // marker "B"
+    0x00, 0xFF, 0x2F, 0x00,
+];
+
+#[rustfmt::skip]
+const PIANO: &[u8] = &[
+    0x00, 0xFF, 0x03, 0x05, b'P', b'i', b'a', b'n', b'o',
+    0x00, 0x90, 0x3C, 0x64,                   // C4 on
+    0x00, 0x40, 0x50,                         // E4 on (running status)
+    0x00, 0xF0, 0x03, 0x7E, 0x7F, 0xF7,       // SysEx, skipped
+    0x83, 0x60, 0x3C, 0x00,                   // tick 480: C4 off (velocity 0)
+    0x87, 0x40, 0x80, 0x40, 0x40,             // tick 1440: E4 off
+    0x00, 0xFF, 0x2F, 0x00,
+];
+
+#[test]
+fn format1_tempo_map_and_running_status() {
+    let bytes = smf(1, 480, &[CONDUCTOR, PIANO]);
+    let file = MidiFile::parse(&bytes, 48_000).unwrap();
+    assert_eq!(file.division, Division::TicksPerQuarter(480));
+    assert_eq!(file.tracks.len(), 2);
+    assert_eq!(file.tracks[1].name.as_deref(), Some("Piano"));
+
+    let tempos: Vec<(i64, u32)> =
+        file.tempos.iter().map(|t| (t.position.value(), t.bpm_x1000)).collect();
+    assert_eq!(tempos, [(0, 120_000), (48_000, 240_000)]);
+    assert_eq!((file.meters[0].numerator, file.meters[0].denominator), (3, 4));
+    assert_eq!(file.keys[0].key.tonic().as_u8(), 7);
+    assert_eq!(file.markers[0].text, "B");
+    assert_eq!(file.markers[0].position, SampleTime::new(48_000));
+
+    let notes = file.notes(PedalConfig::default());
+    let spans: Vec<(u8, i64, i64)> =
+        notes.iter().map(|n| (n.note.value(), n.onset.value(), n.offset.value())).collect();
+    assert_eq!(spans, [(60, 0, 24_000), (64, 0, 60_000)]);
+    assert!(notes.iter().all(|n| n.track == TrackId(1)));
+    assert_eq!(notes[0].velocity, 100);
+
+    // The tempo map agrees with the positions it produced.
+    assert_eq!(file.tempo_map().sample_to_beats_x1000(SampleTime::new(60_000)), 3_000);
+}
+
+#[test]
+fn smpte_division_and_errors() {
+    // 25 fps, 40 ticks per frame = 1000 ticks per second.
+    let track: &[u8] = &[0x00, 0x91, 0x3C, 0x40, 0x87, 0x68, 0x81, 0x3C, 0x00, 0x00, 0xFF, 0x2F, 0];
+    let file = MidiFile::parse(&smf(0, 0xE728, &[track]), 44_100).unwrap();
+    let notes = file.notes(PedalConfig::default());
+    assert_eq!((notes[0].onset.value(), notes[0].offset.value()), (0, 44_100));
+    // Format 0 splits by channel.
+    assert_eq!(notes[0].track, TrackId(1));
+
+    assert_eq!(MidiFile::parse(b"RIFF", 48_000).unwrap_err(), SmfError::NotMidi);
+    assert_eq!(
+        MidiFile::parse(&smf(2, 480, &[]), 48_000).unwrap_err(),
+        SmfError::UnsupportedFormat(2)
+    );
+    let no_status: &[u8] = &[0x00, 0x3C, 0x40];
+    assert_eq!(
+        MidiFile::parse(&smf(0, 480, &[no_status]), 48_000).unwrap_err(),
+        SmfError::MissingStatus
+    );
+    let mut truncated = smf(1, 480, &[PIANO]);
+    truncated.truncate(truncated.len() - 3);
+    assert_eq!(MidiFile::parse(&truncated, 48_000).unwrap_err(), SmfError::Truncated);
+}
+
+#[test]
+fn positions_follow_many_tempo_changes_across_tracks() {
+    // Conductor: a tempo change every 240 ticks, 60 to 200 BPM.
+    let mut conductor = Vec::new();
+    for (i, bpm) in [60u32, 200, 90, 150, 75, 180, 120, 100].iter().enumerate() {
+        let delta: &[u8] = if i == 0 { &[0x00] } else { &[0x81, 0x70] };
+        conductor.extend(delta);
+        conductor.extend([0xFF, 0x51, 0x03]);
+        conductor.extend(&(60_000_000 / bpm).to_be_bytes()[1..]);
+    }
+    conductor.extend([0x00, 0xFF, 0x2F, 0x00]);
+    // Piano: a note on or off every 100 ticks.
+    let mut piano = Vec::new();
+    for i in 0..20u8 {
+        let status = if i % 2 == 0 { 0x90 } else { 0x80 };
+        piano.extend([if i == 0 { 0x00 } else { 0x64 }, status, 0x3C, 0x40]);
+    }
+    piano.extend([0x00, 0xFF, 0x2F, 0x00]);
+
+    let file = MidiFile::parse(&smf(1, 480, &[&conductor, &piano]), 44_100).unwrap();
+    let map = file.tempo_map();
+    assert_eq!(file.tempos.len(), 8);
+    for (i, tempo) in file.tempos.iter().enumerate() {
+        assert_eq!(tempo.position, map.beats_to_sample(240 * i as i64, 480));
+    }
+    let positions: Vec<SampleTime> = file.tracks[1].events.iter().map(|e| e.position).collect();
+    let expected: Vec<SampleTime> = (0..20).map(|i| map.beats_to_sample(100 * i, 480)).collect();
+    assert_eq!(positions, expected);
+}
@@ -0,0 +1,124 @@
+use mt_alloc::TempoMap;
+use mt_core::chord::{BassPolicy, Chord};
+use mt_core::chord_kind::ChordKindId;
+use mt_core::events::{
+    ChordEvent, KeyEvent, MeterEvent, SectionLabel, SegmentEvent, SegmentKind, TempoEvent,
+};
+use mt_core::key::Key;
+use mt_core::pedal::PedalConfig;
+use mt_core::pitch::PitchClass;
+use mt_core::time::SampleTime;
+use mt_formats::smf::MidiFile;
+use mt_formats::smf_writer::{SmfExportConfig, VoicingStyle, voicing, write_smf};
+
+fn pc(v: u8) -> PitchClass {
+    PitchClass::new(v).unwrap()
+}
+
+fn chord(root: u8, kind: ChordKindId, bass: Option<u8>) -> Chord {
+    Chord::with_bass_policy(pc(root), kind, bass.map(pc), BassPolicy::Any).unwrap()
+}
+
+fn at(samples: i64) -> SampleTime {
+    SampleTime::new(samples)
+}
+
+#[test]
+fn round_trips_through_reader() {
+    let mut map = TempoMap::new(48_000);
+    map.push_tempo(TempoEvent { position: at(0), bpm_x1000: 120_000 });
+    map.push_tempo(TempoEvent { position: at(96_000), bpm_x1000: 90_000 });
+    map.push_meter(MeterEvent { position: at(0), numerator: 3, denominator: 4 });
+    let chords = [
+        ChordEvent {
+            chord: chord(0, ChordKindId::Maj, None),
+            onset: at(0),
+            offset: at(48_000),
+            confidence_x1000: 900,
+        },
+        ChordEvent {
+            chord: chord(0, ChordKindId::Maj, None),
+            onset: at(48_000),
+            offset: at(96_000),
+            confidence_x1000: 900,
+        },
+        ChordEvent {
+            chord: chord(9, ChordKindId::Min7, None),
+            onset: at(96_000),
+            offset: at(160_000),
+            confidence_x1000: 800,
+        },
+    ];
+    let keys = [
+        KeyEvent {
+            key: Key::from_semitone(0, false).unwrap(),
+            position: at(0),
+            confidence_x1000: 700,
+        },
+        KeyEvent {
+            key: Key::from_semitone(10, true).unwrap(),
+            position: at(96_000),
+            confidence_x1000: 700,
+        },
+    ];
+    let segments = [SegmentEvent {
+        kind: SegmentKind::Chorus,
+        label: Some(SectionLabel { group: 1, variant: 1 }),
+        onset: at(96_000),
+        offset: at(160_000),
+        confidence_x1000: 600,
+    }];
+
+    let bytes = write_smf(&map, &chords, &keys, &segments, &SmfExportConfig::default()).unwrap();
+    let file = MidiFile::parse(&bytes, 48_000).unwrap();
+
+    let tempos: Vec<(i64, u32)> =
+        file.tempos.iter().map(|t| (t.position.value(), t.bpm_x1000)).collect();
+    assert_eq!(tempos, [(0, 120_000), (96_000, 90_000)]);
+    assert_eq!((file.meters[0].numerator, file.meters[0].denominator), (3, 4));
+    assert_eq!(file.keys.iter().map(|k| k.key).collect::<Vec<_>>(), [keys[0].key, keys[1].key]);
+    assert_eq!(file.markers[0].text, "Chorus B'");
+    assert_eq!(file.markers[0].position, at(96_000));
+    assert_eq!(file.tracks[1].name.as_deref(), Some("Chords"));
+
+    // The repeated C major re-strikes: three notes per chord, back to back.
+    let notes = file.notes(PedalConfig::default());
+    let spans: Vec<(u8, i64, i64)> =
+        notes.iter().map(|n| (n.note.value(), n.onset.value(), n.offset.value())).collect();
+    assert_eq!(
+        spans,
+        [
+            (48, 0, 48_000),
+            (52, 0, 48_000),
+            (55, 0, 48_000),
+            (48, 48_000, 96_000),
+            (52, 48_000, 96_000),
+            (55, 48_000, 96_000),
+            (57, 96_000, 160_000),
+            (60, 96_000, 160_000),
+            (64, 96_000, 160_000),
+            (67, 96_000, 160_000),
+        ]
+    );
+}
+
+#[test]
+fn voicing_styles() {
+    let c_over_e = chord(0, ChordKindId::Maj, Some(4));
+    let c_over_d = chord(0, ChordKindId::Maj, Some(2));
+    let g7 = chord(7, ChordKindId::Dom7, None);
+
+    assert_eq!(voicing(&c_over_e, VoicingStyle::Close), [52, 55, 60]);
+    assert_eq!(voicing(&c_over_d, VoicingStyle::Close), [38, 48, 52, 55]);
+    assert_eq!(voicing(&g7, VoicingStyle::Close), [55, 59, 62, 65]);
+    assert_eq!(voicing(&g7, VoicingStyle::Drop2), [50, 55, 59, 65]);
+    assert_eq!(voicing(&g7, VoicingStyle::Spread), [43, 67, 71, 74, 77]);
+    assert_eq!(voicing(&c_over_e, VoicingStyle::Spread), [40, 60, 64, 67]);
+}
+
+#[test]
+fn rejects_invalid_ppq() {
+    let map = TempoMap::new(48_000);
+    let config = SmfExportConfig { ppq: 0, ..SmfExportConfig::default() };
+    assert!(write_smf(&map, &[], &[], &[], &config).is_err());
+}
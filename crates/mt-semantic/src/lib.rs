@@ -4,6 +4,7 @@
 //! - Motifs over melodic lines.
 //! - Voice-leading cost between chords.
 //! - Functional harmony classification (T/S/D/Other).
+//! - Chord-tone / non-chord-tone roles of melody notes.
 //! - Semantic graph to relate events and semantic entities.
 //!
 //! Constraints:
@@ -30,11 +31,15 @@ extern crate alloc;
 pub mod motif;
 pub mod voice_leading;
 pub mod functional_harmony;
+pub mod non_chord_tone;
 pub mod graph;
 
-pub use motif::{discover_motifs, Motif, MotifConfig, MotifInstance, MotifPattern};
+pub use motif::{
+    discover_motifs, discover_motifs_in_phrases, Motif, MotifConfig, MotifInstance, MotifPattern,
+};
 pub use voice_leading::{compute_voice_leading, VoiceLeadingMove, VoiceLeadingSummary};
 pub use functional_harmony::{classify_function, Function};
+pub use non_chord_tone::{chord_tone_degree, classify_note_roles};
 pub use graph::{
     SemanticEdge, SemanticEdgeKind, SemanticGraph, SemanticNode, SemanticNodeId, SemanticNodeKind,
 };
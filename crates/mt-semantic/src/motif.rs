@@ -8,7 +8,7 @@
 
 use alloc::vec::Vec;
 
-use mt_core::events::NoteEvent;
+use mt_core::events::{NoteEvent, SegmentEvent};
 
 /// Canonical motif pattern: sequence of semitone intervals.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -82,6 +82,36 @@ fn intervals_from_notes(notes: &[NoteEvent]) -> Vec<i8> {
 /// - Uses exact interval matches, no fuzziness.
 #[must_use]
 pub fn discover_motifs(notes: &[NoteEvent], cfg: MotifConfig) -> Vec<Motif> {
+    discover_where(notes, cfg, |_, _| true)
+}
+
+/// Discover motifs whose occurrences each lie inside a single phrase.
+///
+/// A note belongs to the phrase containing its onset; occurrences spanning a
+/// phrase boundary (or notes outside every phrase) are not counted.
+#[must_use]
+pub fn discover_motifs_in_phrases(
+    notes: &[NoteEvent],
+    phrases: &[SegmentEvent],
+    cfg: MotifConfig,
+) -> Vec<Motif> {
+    let phrase_of: Vec<Option<usize>> = notes
+        .iter()
+        .map(|n| phrases.iter().position(|p| p.onset <= n.onset && n.onset < p.offset))
+        .collect();
+    discover_where(notes, cfg, |start, len| {
+        let first = phrase_of[start];
+        first.is_some() && phrase_of[start..=start + len].iter().all(|p| *p == first)
+    })
+}
+
+/// Core scan; `fits(start, len)` filters occurrences covering notes
+/// `start..=start + len`.
+fn discover_where(
+    notes: &[NoteEvent],
+    cfg: MotifConfig,
+    fits: impl Fn(usize, usize) -> bool,
+) -> Vec<Motif> {
     if notes.len() < cfg.min_len + 1 || cfg.min_len == 0 || cfg.min_len > cfg.max_len {
         return Vec::new();
     }
@@ -109,7 +139,7 @@ pub fn discover_motifs(notes: &[NoteEvent], cfg: MotifConfig) -> Vec<Motif> {
             let mut instances = Vec::new();
             let mut i = start;
             while i + len <= n {
-                if &intervals[i..i + len] == candidate {
+                if &intervals[i..i + len] == candidate && fits(i, len) {
                     let inst = MotifInstance { start_index: i, end_index: i + len };
                     // Enforce non-overlap with last instance.
                     if instances
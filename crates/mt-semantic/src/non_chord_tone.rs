@@ -0,0 +1,160 @@
+//! Chord-tone / non-chord-tone classification of a melodic line.
+//!
+//! Rules (simplified, deterministic, textbook definitions):
+//! - Each note is judged against the chord sounding at its onset. Chord
+//!   members get their degree (root, third, fifth, seventh, tension).
+//! - Non-chord tones are named by how the line approaches and leaves them
+//!   (step = 1–2 semitones, leap = more):
+//!   - pedal: same pitch held or repeated through 3+ chords,
+//!   - suspension: same pitch as the previous note, which was a chord tone,
+//!     resolving down by step (4-3, 7-6, 9-8 above the bass),
+//!   - anticipation: same pitch as the next note, a member of the next chord,
+//!   - passing: step in, step out, same direction,
+//!   - neighbor: step out and back,
+//!   - appoggiatura: leap in, step out the other way,
+//!   - escape tone: step in, leap out the other way.
+//! - A chord tone held across a chord change into a chord without it, then
+//!   resolved down by step, is a suspension positioned at the change.
+//!
+//! Input notes are one melodic line; overlapping voices are not separated.
+//!
+//! Roles are descriptive only. They are not fed back into chord detection:
+//! the note-based detectors have no per-note weights, so a diatonic passing
+//! tone can still be read as a chord extension (C6/9 for C–D–E over C).
+
+use alloc::vec::Vec;
+
+use mt_core::chord::Chord;
+use mt_core::chord_kind::ChordKindId;
+use mt_core::events::{
+    ChordEvent, ChordToneDegree, NonChordToneKind, NoteEvent, NoteRole, NoteRoleEvent,
+    SuspensionKind,
+};
+use mt_core::pitch::PitchClass;
+use mt_core::time::SampleTime;
+
+/// Minimum number of chords a pedal must sound through.
+const PEDAL_MIN_CHORDS: usize = 3;
+
+/// Classify every note of `melody` against `chords`.
+///
+/// Output follows note onset order (ties keep input order).
+#[must_use]
+pub fn classify_note_roles(melody: &[NoteEvent], chords: &[ChordEvent]) -> Vec<NoteRoleEvent> {
+    let mut line = melody.to_vec();
+    line.sort_by_key(|n| n.onset);
+
+    (0..line.len())
+        .map(|k| {
+            let n = &line[k];
+            let (position, role) = classify_note(&line, k, chords);
+            NoteRoleEvent { note: n.id, position, role }
+        })
+        .collect()
+}
+
+/// Degree of `pc` in `chord`, if it is a chord member.
+#[must_use]
+pub fn chord_tone_degree(chord: Chord, pc: PitchClass) -> Option<ChordToneDegree> {
+    if !chord.contains(pc) {
+        return None;
+    }
+    let interval = (pc.as_u8() + 12 - chord.root.as_u8()) % 12;
+    Some(match interval {
+        0 => ChordToneDegree::Root,
+        3 | 4 => ChordToneDegree::Third,
+        6..=8 => ChordToneDegree::Fifth,
+        9 if chord.kind == ChordKindId::Dim7 => ChordToneDegree::Seventh,
+        10 | 11 => ChordToneDegree::Seventh,
+        _ => ChordToneDegree::Tension,
+    })
+}
+
+fn classify_note(line: &[NoteEvent], k: usize, chords: &[ChordEvent]) -> (SampleTime, NoteRole) {
+    let n = &line[k];
+    let pc = n.note.pitch_class();
+    let Some(chord) = chord_at(chords, n.onset) else {
+        return (n.onset, NoteRole::NoChord);
+    };
+    let prev = k.checked_sub(1).map(|i| &line[i]);
+    let next = line.get(k + 1);
+    let pitch = |note: &NoteEvent| i16::from(note.note.value());
+    let resolves_down = |note: &NoteEvent| {
+        next.is_some_and(|q| {
+            matches!(pitch(note) - pitch(q), 1 | 2)
+                && chord_at(chords, q.onset).is_some_and(|c| c.contains(q.note.pitch_class()))
+        })
+    };
+
+    if let Some(degree) = chord_tone_degree(chord, pc) {
+        // Held over a change into a chord without it: tied suspension.
+        let held_into = chords.iter().find(|c| {
+            c.onset > n.onset && c.onset < n.offset && c.chord != chord && !c.chord.contains(pc)
+        });
+        if let Some(change) = held_into
+            && resolves_down(n)
+        {
+            let kind = suspension_kind(change.chord, pc);
+            return (change.onset, NoteRole::NonChordTone(NonChordToneKind::Suspension(kind)));
+        }
+        return (n.onset, NoteRole::ChordTone(degree));
+    }
+
+    let kind = if pedal_chords(line, k, chords) >= PEDAL_MIN_CHORDS {
+        NonChordToneKind::Pedal
+    } else if prev.is_some_and(|p| {
+        pitch(p) == pitch(n)
+            && chord_at(chords, p.onset).is_some_and(|c| c != chord && c.contains(pc))
+    }) && resolves_down(n)
+    {
+        NonChordToneKind::Suspension(suspension_kind(chord, pc))
+    } else if next.is_some_and(|q| {
+        pitch(q) == pitch(n)
+            && chord_at(chords, q.onset).is_some_and(|c| c != chord && c.contains(pc))
+    }) {
+        NonChordToneKind::Anticipation
+    } else if let (Some(p), Some(q)) = (prev, next) {
+        let (into, out) = (pitch(n) - pitch(p), pitch(q) - pitch(n));
+        let step = |d: i16| matches!(d.abs(), 1 | 2);
+        let same_direction = into.signum() == out.signum();
+        if step(into) && step(out) && same_direction {
+            NonChordToneKind::Passing
+        } else if step(into) && step(out) && pitch(q) == pitch(p) {
+            NonChordToneKind::Neighbor
+        } else if into.abs() > 2 && step(out) && !same_direction {
+            NonChordToneKind::Appoggiatura
+        } else if step(into) && out.abs() > 2 && !same_direction {
+            NonChordToneKind::EscapeTone
+        } else {
+            NonChordToneKind::Unclassified
+        }
+    } else {
+        NonChordToneKind::Unclassified
+    };
+    (n.onset, NoteRole::NonChordTone(kind))
+}
+
+/// Chord sounding at `t`.
+fn chord_at(chords: &[ChordEvent], t: SampleTime) -> Option<Chord> {
+    chords.iter().rev().find(|c| c.onset <= t && t < c.offset).map(|c| c.chord)
+}
+
+/// Suspension figure of `pc` over the bass of `chord`.
+fn suspension_kind(chord: Chord, pc: PitchClass) -> SuspensionKind {
+    let bass = chord.bass.unwrap_or(chord.root);
+    match (pc.as_u8() + 12 - bass.as_u8()) % 12 {
+        5 => SuspensionKind::FourThree,
+        10 | 11 => SuspensionKind::SevenSix,
+        1 | 2 => SuspensionKind::NineEight,
+        _ => SuspensionKind::Other,
+    }
+}
+
+/// Distinct chords sounding under the run of repeated pitches around `k`.
+fn pedal_chords(line: &[NoteEvent], k: usize, chords: &[ChordEvent]) -> usize {
+    let same = |i: usize| line[i].note == line[k].note;
+    let first = (0..k).rev().take_while(|&i| same(i)).last().unwrap_or(k);
+    let last = (k + 1..line.len()).take_while(|&i| same(i)).last().unwrap_or(k);
+    let (start, end) = (line[first].onset, line[last].offset);
+    chords.iter().filter(|c| c.onset < end && c.offset > start).count()
+}
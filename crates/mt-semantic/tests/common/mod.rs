@@ -0,0 +1,22 @@
+//! Note fixtures shared by the integration tests.
+
+#![allow(dead_code)]
+
+use mt_core::events::{NoteEvent, NoteId, TrackId};
+use mt_core::pitch::MidiNote;
+use mt_core::time::SampleTime;
+
+/// Notes as `(midi, onset, offset)` in samples, numbered in input order.
+pub fn notes(spec: &[(u8, i64, i64)]) -> Vec<NoteEvent> {
+    spec.iter()
+        .enumerate()
+        .map(|(i, &(p, onset, offset))| NoteEvent {
+            id: NoteId(i as u32),
+            track: TrackId(0),
+            onset: SampleTime::new(onset),
+            offset: SampleTime::new(offset),
+            note: MidiNote::new(p).unwrap(),
+            velocity: 80,
+        })
+        .collect()
+}
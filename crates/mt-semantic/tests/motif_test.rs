@@ -0,0 +1,57 @@
+mod common;
+
+use common::notes;
+use mt_core::events::{NoteEvent, SegmentEvent, SegmentKind};
+use mt_core::time::SampleTime;
+use mt_semantic::{Motif, MotifConfig, discover_motifs, discover_motifs_in_phrases};
+
+/// One note per 100 samples.
+fn line(pitches: &[u8]) -> Vec<NoteEvent> {
+    let spec: Vec<(u8, i64, i64)> =
+        pitches.iter().zip(0..).map(|(&p, i)| (p, i * 100, i * 100 + 90)).collect();
+    notes(&spec)
+}
+
+fn phrase(onset: i64, offset: i64) -> SegmentEvent {
+    SegmentEvent {
+        kind: SegmentKind::Other(0),
+        label: None,
+        onset: SampleTime::new(onset),
+        offset: SampleTime::new(offset),
+        confidence_x1000: 1000,
+    }
+}
+
+/// Note ranges of the motif with `intervals`, if found.
+fn occurrences(motifs: &[Motif], intervals: &[i8]) -> Option<Vec<(usize, usize)>> {
+    motifs
+        .iter()
+        .find(|m| m.pattern.intervals == intervals)
+        .map(|m| m.instances.iter().map(|i| (i.start_index, i.end_index)).collect())
+}
+
+const CFG: MotifConfig = MotifConfig { min_len: 3, max_len: 3, min_occurrences: 2 };
+
+/// C D E C three times.
+const CDEC: [u8; 12] = [60, 62, 64, 60, 60, 62, 64, 60, 60, 62, 64, 60];
+
+#[test]
+fn discover_motifs_finds_disjoint_repeats() {
+    let motifs = discover_motifs(&line(&CDEC), CFG);
+    assert_eq!(occurrences(&motifs, &[2, 2, -4]), Some(vec![(0, 3), (4, 7), (8, 11)]));
+    assert!(discover_motifs(&line(&CDEC[..4]), CFG).is_empty());
+}
+
+#[test]
+fn motifs_in_phrases_skip_occurrences_across_boundaries() {
+    let notes = line(&CDEC);
+    // The second C D E C straddles the boundary at note 6.
+    let phrases = [phrase(0, 600), phrase(600, 1200)];
+    let motifs = discover_motifs_in_phrases(&notes, &phrases, CFG);
+    assert_eq!(occurrences(&motifs, &[2, 2, -4]), Some(vec![(0, 3), (8, 11)]));
+
+    // Notes outside every phrase do not count.
+    let motifs = discover_motifs_in_phrases(&notes, &[phrase(0, 600)], CFG);
+    assert_eq!(occurrences(&motifs, &[2, 2, -4]), None);
+    assert!(discover_motifs_in_phrases(&notes, &[], CFG).is_empty());
+}
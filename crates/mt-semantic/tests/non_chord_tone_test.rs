@@ -0,0 +1,129 @@
+mod common;
+
+use common::notes;
+use mt_core::chord::Chord;
+use mt_core::chord_kind::ChordKindId::{self, Dim7, Dom7, Maj, Maj6, Maj7, Sus4};
+use mt_core::events::{
+    ChordEvent, ChordToneDegree, NonChordToneKind, NoteId, NoteRole, SuspensionKind,
+};
+use mt_core::pitch::PitchClass;
+use mt_core::time::SampleTime;
+use mt_semantic::{chord_tone_degree, classify_note_roles};
+
+fn pc(v: u8) -> PitchClass {
+    PitchClass::new(v).unwrap()
+}
+
+/// Chords as `(root, kind, onset, offset)`.
+fn chords(spec: &[(u8, ChordKindId, i64, i64)]) -> Vec<ChordEvent> {
+    spec.iter()
+        .map(|&(root, kind, onset, offset)| ChordEvent {
+            chord: Chord::new(pc(root), kind, None).unwrap(),
+            onset: SampleTime::new(onset),
+            offset: SampleTime::new(offset),
+            confidence_x1000: 1000,
+        })
+        .collect()
+}
+
+/// One 100-sample note per pitch over `chord`.
+fn line_over(pitches: &[u8], chord: &[ChordEvent]) -> Vec<NoteRole> {
+    let spec: Vec<(u8, i64, i64)> =
+        (0_i64..).zip(pitches).map(|(i, &p)| (p, i * 100, i * 100 + 100)).collect();
+    classify_note_roles(&notes(&spec), chord).iter().map(|e| e.role).collect()
+}
+
+fn nct(kind: NonChordToneKind) -> NoteRole {
+    NoteRole::NonChordTone(kind)
+}
+
+#[test]
+fn chord_members_get_their_degree() {
+    use ChordToneDegree::{Fifth, Root, Seventh, Tension, Third};
+    let c_maj7 = chords(&[(0, Maj7, 0, 1000)]);
+    let roles = line_over(&[60, 64, 67, 71], &c_maj7);
+    assert_eq!(roles, [Root, Third, Fifth, Seventh].map(NoteRole::ChordTone));
+
+    let c = Chord::new(pc(0), Maj6, None).unwrap();
+    assert_eq!(chord_tone_degree(c, pc(9)), Some(Tension));
+    assert_eq!(chord_tone_degree(c, pc(2)), None);
+    let b_dim7 = Chord::new(pc(11), Dim7, None).unwrap();
+    assert_eq!(chord_tone_degree(b_dim7, pc(8)), Some(Seventh));
+    let c_sus4 = Chord::new(pc(0), Sus4, None).unwrap();
+    assert_eq!(chord_tone_degree(c_sus4, pc(5)), Some(Tension));
+}
+
+#[test]
+fn passing_neighbor_appoggiatura_and_escape_tones() {
+    let c = chords(&[(0, Maj, 0, 1000)]);
+    let chord_tone = |d| NoteRole::ChordTone(d);
+    use ChordToneDegree::{Fifth, Root, Third};
+    use NonChordToneKind::{Appoggiatura, EscapeTone, Neighbor, Passing};
+
+    assert_eq!(line_over(&[60, 62, 64], &c), [chord_tone(Root), nct(Passing), chord_tone(Third)]);
+    assert_eq!(line_over(&[64, 65, 64], &c), [chord_tone(Third), nct(Neighbor), chord_tone(Third)]);
+    assert_eq!(
+        line_over(&[60, 69, 67], &c),
+        [chord_tone(Root), nct(Appoggiatura), chord_tone(Fifth)]
+    );
+    assert_eq!(
+        line_over(&[64, 65, 60], &c),
+        [chord_tone(Third), nct(EscapeTone), chord_tone(Root)]
+    );
+    // No neighbours to judge by.
+    assert_eq!(line_over(&[62], &c), [nct(NonChordToneKind::Unclassified)]);
+}
+
+#[test]
+fn anticipation_sounds_the_next_chord_early() {
+    let c_g = chords(&[(0, Maj, 0, 200), (7, Maj, 200, 400)]);
+    let roles = line_over(&[64, 62, 62], &c_g);
+    assert_eq!(roles[1], nct(NonChordToneKind::Anticipation));
+    assert_eq!(roles[2], NoteRole::ChordTone(ChordToneDegree::Fifth));
+}
+
+#[test]
+fn prepared_suspensions_are_named_over_the_bass() {
+    // C held into G (4-3), D held into C (9-8), resolving down by step.
+    let c_g = chords(&[(0, Maj, 0, 100), (7, Maj, 100, 300)]);
+    let roles = line_over(&[72, 72, 71], &c_g);
+    assert_eq!(roles[1], nct(NonChordToneKind::Suspension(SuspensionKind::FourThree)));
+
+    let g_c = chords(&[(7, Maj, 0, 100), (0, Maj, 100, 300)]);
+    let roles = line_over(&[74, 74, 72], &g_c);
+    assert_eq!(roles[1], nct(NonChordToneKind::Suspension(SuspensionKind::NineEight)));
+
+    // Without the step down it is no suspension.
+    let roles = line_over(&[72, 72, 67], &c_g);
+    assert_ne!(roles[1], nct(NonChordToneKind::Suspension(SuspensionKind::FourThree)));
+}
+
+#[test]
+fn tied_suspension_is_placed_at_the_chord_change() {
+    let c_g = chords(&[(0, Maj, 0, 200), (7, Dom7, 200, 400)]);
+    let roles = classify_note_roles(&notes(&[(72, 0, 300), (71, 300, 400)]), &c_g);
+    assert_eq!(roles[0].position, SampleTime::new(200));
+    assert_eq!(roles[0].role, nct(NonChordToneKind::Suspension(SuspensionKind::FourThree)));
+    assert_eq!(roles[1].position, SampleTime::new(300));
+    assert!(roles[1].is_chord_tone());
+}
+
+#[test]
+fn pedal_holds_through_three_chords() {
+    // G over C, F, C.
+    let c_f_c = chords(&[(0, Maj, 0, 100), (5, Maj, 100, 200), (0, Maj, 200, 300)]);
+    let roles = line_over(&[67, 67, 67], &c_f_c);
+    assert_eq!(roles[1], nct(NonChordToneKind::Pedal));
+    assert!(roles[0] == NoteRole::ChordTone(ChordToneDegree::Fifth) && roles[0] == roles[2]);
+}
+
+#[test]
+fn output_follows_onsets_and_marks_notes_without_a_chord() {
+    let c = chords(&[(0, Maj, 100, 200)]);
+    let melody = notes(&[(64, 100, 200), (60, 0, 100)]);
+    let roles = classify_note_roles(&melody, &c);
+    assert_eq!(roles.iter().map(|e| e.note).collect::<Vec<_>>(), [NoteId(1), NoteId(0)]);
+    assert_eq!(roles[0].role, NoteRole::NoChord);
+    assert_eq!(roles[1].role, NoteRole::ChordTone(ChordToneDegree::Third));
+    assert!(classify_note_roles(&[], &c).is_empty());
+}
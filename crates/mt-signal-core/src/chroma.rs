@@ -0,0 +1,58 @@
+//! Chroma (pitch-class energy) from a single audio frame.
+//!
+//! Strategy:
+//! - One Goertzel filter per equal-tempered semitone (a constant-Q style
+//!   filterbank without an FFT).
+//! - Fold semitone energies onto the 12 pitch-classes.
+//!
+//! The caller supplies the analysis window so no allocation is needed.
+
+use core::f32::consts::PI;
+
+/// Frequency in Hz of a MIDI note (A4 = 440 Hz).
+#[must_use]
+pub fn midi_to_hz(midi: u8) -> f32 {
+    440.0 * 2.0_f32.powf((f32::from(midi) - 69.0) / 12.0)
+}
+
+/// Power of the windowed `frame` at `freq_hz` (Goertzel algorithm),
+/// normalized by length. `window` must match `frame` in length.
+#[must_use]
+pub fn goertzel_power(frame: &[f32], window: &[f32], freq_hz: f32, sample_rate: u32) -> f32 {
+    if frame.is_empty() || frame.len() != window.len() || sample_rate == 0 {
+        return 0.0;
+    }
+    let coeff = 2.0 * (2.0 * PI * freq_hz / sample_rate as f32).cos();
+    let mut s1 = 0.0_f32;
+    let mut s2 = 0.0_f32;
+    for (x, w) in frame.iter().zip(window) {
+        let s0 = x * w + coeff * s1 - s2;
+        s2 = s1;
+        s1 = s0;
+    }
+    let power = s1 * s1 + s2 * s2 - coeff * s1 * s2;
+    power.max(0.0) / (frame.len() * frame.len()) as f32
+}
+
+/// Chroma vector of `frame` over MIDI notes `min_midi..=max_midi`.
+///
+/// `window` must have the same length as `frame` (see `window::fill_window`).
+/// Output is the summed semitone power per pitch-class (index 0 = C).
+pub fn chroma_frame(
+    frame: &[f32],
+    window: &[f32],
+    sample_rate: u32,
+    min_midi: u8,
+    max_midi: u8,
+    out: &mut [f32; 12],
+) {
+    *out = [0.0; 12];
+    let nyquist = sample_rate as f32 / 2.0;
+    for midi in min_midi..=max_midi {
+        let freq = midi_to_hz(midi);
+        if freq >= nyquist {
+            break;
+        }
+        out[usize::from(midi % 12)] += goertzel_power(frame, window, freq, sample_rate);
+    }
+}
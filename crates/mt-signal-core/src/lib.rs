@@ -6,6 +6,7 @@
 //! - Sample traits and conversions
 //! - Fixed-size frames for mono/stereo/N-channel audio
 //! - Window functions (Hann, Hamming, Blackman, Rectangular)
+//! - Goertzel filterbank chroma and MFCC
 //! - Minimal complex and spectrum views
 //! - Traits describing STFT/spectrum contracts (no implementations)
 //!
@@ -31,7 +32,9 @@
 #[cfg(feature = "std")]
 extern crate std;
 
+pub mod chroma;
 pub mod frame;
+pub mod mfcc;
 pub mod sample;
 pub mod spectrum;
 pub mod traits;
@@ -0,0 +1,71 @@
+//! Mel-frequency cepstral coefficients (MFCC) from a single audio frame.
+//!
+//! Strategy:
+//! - Triangular mel bands, each sampled with three Goertzel filters (peak
+//!   and both half-slopes) instead of an FFT, like `chroma`.
+//! - Log band energies, decorrelated with a DCT-II.
+//!
+//! The caller supplies the analysis window so no allocation is needed.
+
+use core::f32::consts::PI;
+
+use crate::chroma::goertzel_power;
+
+/// Mel bands between the lower and upper frequency limits.
+pub const MEL_BANDS: usize = 26;
+
+/// Floor added before the logarithm so silent bands stay finite.
+const LOG_FLOOR: f32 = 1e-10;
+
+/// Hz to mel (O'Shaughnessy).
+#[must_use]
+pub fn hz_to_mel(hz: f32) -> f32 {
+    2595.0 * (1.0 + hz / 700.0).log10()
+}
+
+/// Mel to Hz; inverse of `hz_to_mel`.
+#[must_use]
+pub fn mel_to_hz(mel: f32) -> f32 {
+    700.0 * (10.0_f32.powf(mel / 2595.0) - 1.0)
+}
+
+/// MFCCs of `frame` over `min_hz..max_hz`, written to `out`.
+///
+/// `window` must have the same length as `frame`. `out[0]` is the mean log
+/// band energy (c0); at most `MEL_BANDS` coefficients are produced and any
+/// further entries of `out` are zeroed. Bands at or above Nyquist count as
+/// silent.
+pub fn mfcc_frame(
+    frame: &[f32],
+    window: &[f32],
+    sample_rate: u32,
+    min_hz: f32,
+    max_hz: f32,
+    out: &mut [f32],
+) {
+    let nyquist = sample_rate as f32 / 2.0;
+    let (lo, hi) = (hz_to_mel(min_hz.max(0.0)), hz_to_mel(max_hz.min(nyquist)));
+    let step = (hi - lo) / (MEL_BANDS + 1) as f32;
+    let power = |mel: f32| {
+        let hz = mel_to_hz(mel);
+        if hz < nyquist { goertzel_power(frame, window, hz, sample_rate) } else { 0.0 }
+    };
+
+    let mut log_energy = [0.0_f32; MEL_BANDS];
+    for (b, e) in log_energy.iter_mut().enumerate() {
+        let center = lo + (b + 1) as f32 * step;
+        let energy =
+            power(center) + 0.5 * (power(center - step / 2.0) + power(center + step / 2.0));
+        *e = (energy / 2.0 + LOG_FLOOR).ln();
+    }
+
+    out.fill(0.0);
+    for (k, c) in out.iter_mut().take(MEL_BANDS).enumerate() {
+        let sum: f32 = log_energy
+            .iter()
+            .enumerate()
+            .map(|(b, e)| e * (PI * k as f32 * (b as f32 + 0.5) / MEL_BANDS as f32).cos())
+            .sum();
+        *c = sum / MEL_BANDS as f32;
+    }
+}
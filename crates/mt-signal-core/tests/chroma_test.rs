@@ -0,0 +1,79 @@
+use mt_signal_core::chroma::{chroma_frame, goertzel_power, midi_to_hz};
+use mt_signal_core::window::{WindowKind, fill_window};
+
+const SR: u32 = 44_100;
+const N: usize = 8192;
+
+fn sine(freq: f32) -> Vec<f32> {
+    (0..N).map(|i| (2.0 * std::f32::consts::PI * freq * i as f32 / SR as f32).sin()).collect()
+}
+
+fn hann() -> Vec<f32> {
+    let mut w = vec![0.0; N];
+    fill_window(WindowKind::Hann, &mut w);
+    w
+}
+
+fn argmax(v: &[f32; 12]) -> usize {
+    (0..12).max_by(|&a, &b| v[a].total_cmp(&v[b])).unwrap()
+}
+
+#[test]
+fn midi_to_hz_is_equal_tempered_from_a4() {
+    assert!((midi_to_hz(69) - 440.0).abs() < 1e-3);
+    assert!((midi_to_hz(81) - 880.0).abs() < 1e-2);
+    assert!((midi_to_hz(60) - 261.626).abs() < 1e-2);
+}
+
+#[test]
+fn goertzel_power_peaks_at_the_tone() {
+    let (frame, window) = (sine(440.0), hann());
+    let on = goertzel_power(&frame, &window, 440.0, SR);
+    let semitone_up = goertzel_power(&frame, &window, midi_to_hz(70), SR);
+    let octave_up = goertzel_power(&frame, &window, 880.0, SR);
+    assert!(on > 0.0);
+    assert!(on > 100.0 * semitone_up, "{on} vs {semitone_up}");
+    assert!(on > 100.0 * octave_up, "{on} vs {octave_up}");
+
+    // Power scales with amplitude squared and not with length.
+    let half: Vec<f32> = frame.iter().map(|x| x * 0.5).collect();
+    let quarter = goertzel_power(&half, &window, 440.0, SR);
+    assert!((quarter / on - 0.25).abs() < 1e-3);
+}
+
+#[test]
+fn goertzel_power_rejects_degenerate_input() {
+    let (frame, window) = (sine(440.0), hann());
+    assert_eq!(goertzel_power(&[], &[], 440.0, SR), 0.0);
+    assert_eq!(goertzel_power(&frame, &window[1..], 440.0, SR), 0.0);
+    assert_eq!(goertzel_power(&frame, &window, 440.0, 0), 0.0);
+}
+
+#[test]
+fn chroma_frame_folds_octaves_onto_pitch_classes() {
+    let window = hann();
+    let mut out = [0.0; 12];
+    // A3 + A5 both land on A.
+    let frame: Vec<f32> = sine(220.0).iter().zip(sine(880.0)).map(|(a, b)| a + b).collect();
+    chroma_frame(&frame, &window, SR, 36, 95, &mut out);
+    assert_eq!(argmax(&out), 9);
+
+    // E4 alone.
+    chroma_frame(&sine(midi_to_hz(64)), &window, SR, 36, 95, &mut out);
+    assert_eq!(argmax(&out), 4);
+}
+
+#[test]
+fn chroma_frame_ignores_notes_outside_the_range_and_above_nyquist() {
+    let window = hann();
+    let mut out = [1.0; 12];
+    // Only notes above the 4 kHz Nyquist of an 8 kHz rate: nothing is measured.
+    chroma_frame(&sine(440.0), &window, 8_000, 108, 127, &mut out);
+    assert_eq!(out, [0.0; 12]);
+
+    // A4 outside C5..B5 contributes only leakage.
+    chroma_frame(&sine(440.0), &window, SR, 36, 95, &mut out);
+    let full = out[9];
+    chroma_frame(&sine(440.0), &window, SR, 72, 83, &mut out);
+    assert!(out[9] < full / 100.0, "{} vs {full}", out[9]);
+}
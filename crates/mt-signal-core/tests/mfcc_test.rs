@@ -0,0 +1,48 @@
+use mt_signal_core::mfcc::{MEL_BANDS, hz_to_mel, mel_to_hz, mfcc_frame};
+use mt_signal_core::window::{WindowKind, fill_window};
+
+const SR: u32 = 16_000;
+const N: usize = 2048;
+
+fn hann() -> Vec<f32> {
+    let mut w = vec![0.0; N];
+    fill_window(WindowKind::Hann, &mut w);
+    w
+}
+
+fn sine(freq: f32) -> Vec<f32> {
+    (0..N).map(|i| (2.0 * std::f32::consts::PI * freq * i as f32 / SR as f32).sin()).collect()
+}
+
+#[test]
+fn mel_scale_round_trips_and_pins_1000_hz() {
+    assert!((hz_to_mel(1000.0) - 1000.0).abs() < 0.5);
+    for hz in [0.0, 60.0, 440.0, 4000.0, 8000.0] {
+        assert!((mel_to_hz(hz_to_mel(hz)) - hz).abs() < 0.05 * hz.max(1.0), "{hz}");
+    }
+}
+
+#[test]
+fn silence_has_a_flat_log_spectrum() {
+    let mut out = [1.0; 13];
+    mfcc_frame(&[0.0; N], &hann(), SR, 60.0, 8000.0, &mut out);
+    assert!(out[0] < -20.0);
+    assert!(out[1..].iter().all(|c| c.abs() < 1e-4), "{out:?}");
+}
+
+#[test]
+fn first_coefficient_tracks_spectral_tilt() {
+    let window = hann();
+    let (mut low, mut high) = ([0.0; 13], [0.0; 13]);
+    mfcc_frame(&sine(200.0), &window, SR, 60.0, 8000.0, &mut low);
+    mfcc_frame(&sine(5000.0), &window, SR, 60.0, 8000.0, &mut high);
+    assert!(low[1] > 0.0 && high[1] < 0.0, "{} / {}", low[1], high[1]);
+}
+
+#[test]
+fn output_beyond_the_band_count_is_zeroed() {
+    let mut out = [1.0; MEL_BANDS + 4];
+    mfcc_frame(&sine(440.0), &hann(), SR, 60.0, 8000.0, &mut out);
+    assert!(out[..MEL_BANDS].iter().any(|c| *c != 0.0));
+    assert_eq!(out[MEL_BANDS..], [0.0; 4]);
+}